@@ -3,14 +3,18 @@ use std::{mem, str::FromStr, sync::Arc};
 
 use cfg::CfgOptions;
 use rustc_hash::FxHashMap;
+use syntax::{TextRange, TextSize};
 use test_utils::{
-    extract_range_or_offset, Fixture, RangeOrOffset, CURSOR_MARKER, ESCAPED_CURSOR_MARKER,
+    extract_named_offsets, extract_range_or_offset, Fixture, FixtureDep, MiniCore, RangeOrOffset,
+    CURSOR_MARKER, ESCAPED_CURSOR_MARKER,
 };
+use tt::{ExpansionError, Subtree};
 use vfs::{file_set::FileSet, VfsPath};
 
 use crate::{
     input::CrateName, Change, CrateDisplayName, CrateGraph, CrateId, Edition, Env, FileId,
-    FilePosition, FileRange, SourceDatabaseExt, SourceRoot, SourceRootId,
+    FilePosition, FileRange, ProcMacro, ProcMacroExpander, ProcMacroKind, SourceDatabaseExt,
+    SourceRoot, SourceRootId, Toolchain,
 };
 
 pub const WORKSPACE: SourceRootId = SourceRootId(0);
@@ -62,6 +66,25 @@ pub trait WithFixture: Default + SourceDatabaseExt + 'static {
         (db, file_id, range_or_offset)
     }
 
+    /// Like `with_position`, but for fixtures marking several positions with
+    /// `$name` markers, one for each of the `names`.
+    fn with_named_positions(
+        ra_fixture: &str,
+        names: &[&str],
+    ) -> (Self, FxHashMap<String, FilePosition>) {
+        let fixture = ChangeFixture::parse_with_markers(ra_fixture, names);
+        let mut db = Self::default();
+        fixture.change.apply(&mut db);
+        for name in names {
+            assert!(
+                fixture.named_positions.contains_key(*name),
+                "Could not find `${}` marker in fixture",
+                name
+            );
+        }
+        (db, fixture.named_positions)
+    }
+
     fn test_crate(&self) -> CrateId {
         let crate_graph = self.crate_graph();
         let mut it = crate_graph.iter();
@@ -75,55 +98,158 @@ impl<DB: SourceDatabaseExt + Default + 'static> WithFixture for DB {}
 
 pub struct ChangeFixture {
     pub file_position: Option<(FileId, RangeOrOffset)>,
+    /// Positions of the `$name` markers requested by `parse_with_markers`.
+    pub named_positions: FxHashMap<String, FilePosition>,
     pub files: Vec<FileId>,
+    /// Whether the fixture asked for the real standard library with
+    /// `//- sysroot: real`. Loading it is left to the test harness, as this
+    /// crate knows nothing about finding the sysroot.
+    pub real_sysroot: bool,
     pub change: Change,
 }
 
 impl ChangeFixture {
     pub fn parse(ra_fixture: &str) -> ChangeFixture {
-        let (mini_core, fixture) = Fixture::parse(ra_fixture);
+        ChangeFixture::parse_with_markers(ra_fixture, &[])
+    }
+
+    /// Parses the fixture, additionally extracting the `$name` marker of each
+    /// of the `markers` into `named_positions`.
+    pub fn parse_with_markers(ra_fixture: &str, markers: &[&str]) -> ChangeFixture {
+        let (mini_core, proc_macros, fixture) = Fixture::parse(ra_fixture);
+        ChangeFixture::from_parsed(mini_core, proc_macros, fixture, markers)
+    }
+
+    /// Creates the change for fixtures constructed programmatically, like with
+    /// `FixtureBuilder`.
+    pub fn from_fixtures(mini_core: Option<MiniCore>, fixture: Vec<Fixture>) -> ChangeFixture {
+        ChangeFixture::from_parsed(mini_core, Vec::new(), fixture, &[])
+    }
+
+    fn from_parsed(
+        mini_core: Option<MiniCore>,
+        proc_macros: Vec<String>,
+        fixture: Vec<Fixture>,
+        markers: &[&str],
+    ) -> ChangeFixture {
         let mut change = Change::new();
 
+        let real_sysroot = fixture.iter().any(|it| it.real_sysroot);
+        assert!(
+            !(real_sysroot && mini_core.is_some()),
+            "`minicore` can't be used together with the real sysroot"
+        );
+
         let mut files = Vec::new();
         let mut crate_graph = CrateGraph::default();
         let mut crates = FxHashMap::default();
         let mut crate_deps = Vec::new();
         let mut default_crate_root: Option<FileId> = None;
         let mut default_cfg = CfgOptions::default();
+        let mut default_edition = Edition::CURRENT;
+        let mut default_toolchain = None;
 
         let mut file_set = FileSet::default();
+        let mut library_file_set = FileSet::default();
         let source_root_prefix = "/".to_string();
         let mut file_id = FileId(0);
         let mut roots = Vec::new();
 
         let mut file_position = None;
+        let mut named_positions = FxHashMap::default();
+
+        // Features enabled by dependent crates, keyed by the name of the crate
+        // they are enabled for.
+        let mut dep_features: FxHashMap<CrateName, Vec<String>> = FxHashMap::default();
+        for entry in &fixture {
+            for (dep, feature) in &entry.dep_features {
+                let dep = match entry.deps.iter().find(|it| it.extern_name() == dep) {
+                    Some(it) => it,
+                    None => panic!(
+                        "can't enable feature `{}/{}` of a crate which is not a dependency",
+                        dep, feature
+                    ),
+                };
+                dep_features
+                    .entry(CrateName::normalize_dashes(&dep.name))
+                    .or_default()
+                    .push(feature.clone());
+            }
+        }
 
         for entry in fixture {
-            let text = if entry.text.contains(CURSOR_MARKER) {
-                if entry.text.contains(ESCAPED_CURSOR_MARKER) {
-                    entry.text.replace(ESCAPED_CURSOR_MARKER, CURSOR_MARKER)
+            if !entry.crate_attrs.is_empty() {
+                assert!(
+                    entry.krate.is_some() || entry.path == "/main.rs" || entry.path == "/lib.rs",
+                    "crate attributes can only be set on crate roots"
+                );
+            }
+            let mut text =
+                entry.crate_attrs.iter().map(|attr| format!("#![{}]\n", attr)).collect::<String>();
+            text.push_str(&entry.text);
+
+            let (named_offsets, text) = extract_named_offsets(&text, markers);
+            // Parts of the text removed together with `$0` markers, named offsets are adjusted
+            // accordingly.
+            let mut removed = Vec::new();
+            let text = if text.contains(CURSOR_MARKER) {
+                if text.contains(ESCAPED_CURSOR_MARKER) {
+                    removed.extend(
+                        text.match_indices(ESCAPED_CURSOR_MARKER)
+                            .map(|(idx, _)| TextRange::at(TextSize::from(idx as u32), 1.into())),
+                    );
+                    text.replace(ESCAPED_CURSOR_MARKER, CURSOR_MARKER)
                 } else {
-                    let (range_or_offset, text) = extract_range_or_offset(&entry.text);
+                    let (range_or_offset, text) = extract_range_or_offset(&text);
                     assert!(file_position.is_none());
                     file_position = Some((file_id, range_or_offset));
+                    let marker_len = TextSize::of(CURSOR_MARKER);
+                    match range_or_offset {
+                        RangeOrOffset::Offset(offset) => {
+                            removed.push(TextRange::at(offset, marker_len))
+                        }
+                        RangeOrOffset::Range(range) => {
+                            removed.push(TextRange::at(range.start(), marker_len));
+                            removed.push(TextRange::at(range.end() + marker_len, marker_len));
+                        }
+                    }
                     text
                 }
             } else {
-                entry.text.clone()
+                text
+            };
+            for (name, offset) in named_offsets {
+                let shift: TextSize =
+                    removed.iter().filter(|it| it.start() < offset).map(|it| it.len()).sum();
+                let position = FilePosition { file_id, offset: offset - shift };
+                let prev = named_positions.insert(name, position);
+                assert!(prev.is_none(), "a marker is present in several files");
+            }
+            let text = match &entry.bytes {
+                // Like the real VFS, we don't keep the contents of non-UTF-8 files.
+                Some(bytes) => String::from_utf8(bytes.clone()).ok(),
+                None => Some(text),
             };
 
-            let meta = FileMeta::from(entry);
+            let mut meta = FileMeta::from(entry);
             assert!(meta.path.starts_with(&source_root_prefix));
             if !meta.deps.is_empty() {
                 assert!(meta.krate.is_some(), "can't specify deps without naming the crate")
             }
 
             if meta.introduce_new_source_root {
-                roots.push(SourceRoot::new_local(mem::take(&mut file_set)));
+                if meta.is_library {
+                    roots.push(SourceRoot::new_library(mem::take(&mut library_file_set)));
+                } else {
+                    roots.push(SourceRoot::new_local(mem::take(&mut file_set)));
+                }
             }
 
             if let Some(krate) = meta.krate {
                 let crate_name = CrateName::normalize_dashes(&krate);
+                for feature in dep_features.get(&crate_name).into_iter().flatten() {
+                    meta.cfg.insert_key_value("feature".into(), feature.into());
+                }
                 let crate_id = crate_graph.add_crate_root(
                     file_id,
                     meta.edition,
@@ -132,22 +258,31 @@ impl ChangeFixture {
                     meta.cfg,
                     meta.env,
                     Default::default(),
+                    meta.is_proc_macro,
+                    meta.toolchain,
                 );
                 let prev = crates.insert(crate_name.clone(), crate_id);
                 assert!(prev.is_none());
                 for dep in meta.deps {
-                    let dep = CrateName::normalize_dashes(&dep);
-                    crate_deps.push((crate_name.clone(), dep))
+                    let name = CrateName::normalize_dashes(dep.extern_name());
+                    let dep = CrateName::normalize_dashes(&dep.name);
+                    crate_deps.push((crate_name.clone(), dep, name))
                 }
             } else if meta.path == "/main.rs" || meta.path == "/lib.rs" {
                 assert!(default_crate_root.is_none());
                 default_crate_root = Some(file_id);
                 default_cfg = meta.cfg;
+                default_edition = meta.edition;
+                default_toolchain = meta.toolchain;
             }
 
-            change.change_file(file_id, Some(Arc::new(text)));
+            change.change_file(file_id, text.map(Arc::new));
             let path = VfsPath::new_virtual_path(meta.path);
-            file_set.insert(file_id, path);
+            if meta.is_library {
+                library_file_set.insert(file_id, path);
+            } else {
+                file_set.insert(file_id, path);
+            }
             files.push(file_id);
             file_id.0 += 1;
         }
@@ -156,28 +291,33 @@ impl ChangeFixture {
             let crate_root = default_crate_root.unwrap();
             crate_graph.add_crate_root(
                 crate_root,
-                Edition::CURRENT,
+                default_edition,
                 Some(CrateName::new("test").unwrap().into()),
                 default_cfg.clone(),
                 default_cfg,
                 Env::default(),
                 Default::default(),
+                false,
+                default_toolchain,
             );
         } else {
-            for (from, to) in crate_deps {
+            for (from, to, name) in crate_deps {
                 let from_id = crates[&from];
                 let to_id = crates[&to];
-                crate_graph.add_dep(from_id, CrateName::new(&to).unwrap(), to_id).unwrap();
+                crate_graph.add_dep(from_id, name, to_id).unwrap();
             }
         }
 
         if let Some(mini_core) = mini_core {
+            // `std::error::Error` is tested through a `std` shim re-exporting `core`, unless
+            // the fixture has its own `std`.
+            let std_shim = mini_core.activated_flags().iter().any(|it| it == "error")
+                && !crates.contains_key(&CrateName::new("std").unwrap());
             let core_file = file_id;
             file_id.0 += 1;
 
             let mut fs = FileSet::default();
             fs.insert(core_file, VfsPath::new_virtual_path("/sysroot/core/lib.rs".to_string()));
-            roots.push(SourceRoot::new_library(fs));
 
             change.change_file(core_file, Some(Arc::new(mini_core.source_code())));
 
@@ -191,17 +331,154 @@ impl ChangeFixture {
                 CfgOptions::default(),
                 Env::default(),
                 Vec::new(),
+                false,
+                None,
             );
 
-            for krate in all_crates {
+            for &krate in &all_crates {
                 crate_graph.add_dep(krate, CrateName::new("core").unwrap(), core_crate).unwrap();
             }
+
+            if std_shim {
+                let std_file = file_id;
+                file_id.0 += 1;
+                fs.insert(std_file, VfsPath::new_virtual_path("/sysroot/std/lib.rs".to_string()));
+                change.change_file(std_file, Some(Arc::new("pub use core::*;\n".to_string())));
+                let std_crate = crate_graph.add_crate_root(
+                    std_file,
+                    Edition::Edition2021,
+                    Some(CrateDisplayName::from_canonical_name("std".to_string())),
+                    CfgOptions::default(),
+                    CfgOptions::default(),
+                    Env::default(),
+                    Vec::new(),
+                    false,
+                    None,
+                );
+                crate_graph
+                    .add_dep(std_crate, CrateName::new("core").unwrap(), core_crate)
+                    .unwrap();
+                for krate in all_crates {
+                    crate_graph.add_dep(krate, CrateName::new("std").unwrap(), std_crate).unwrap();
+                }
+            }
+            roots.push(SourceRoot::new_library(fs));
+        }
+
+        if !proc_macros.is_empty() {
+            let proc_lib_file = file_id;
+            file_id.0 += 1;
+
+            let (proc_macro, source) = test_proc_macros(&proc_macros);
+            let mut fs = FileSet::default();
+            fs.insert(
+                proc_lib_file,
+                VfsPath::new_virtual_path("/sysroot/proc_macros/lib.rs".to_string()),
+            );
+            roots.push(SourceRoot::new_library(fs));
+
+            change.change_file(proc_lib_file, Some(Arc::new(source)));
+
+            let all_crates = crate_graph.crates_in_topological_order();
+
+            let proc_macros_crate = crate_graph.add_crate_root(
+                proc_lib_file,
+                Edition::Edition2021,
+                Some(CrateDisplayName::from_canonical_name("proc_macros".to_string())),
+                CfgOptions::default(),
+                CfgOptions::default(),
+                Env::default(),
+                proc_macro,
+                true,
+                None,
+            );
+
+            for krate in all_crates {
+                crate_graph
+                    .add_dep(krate, CrateName::new("proc_macros").unwrap(), proc_macros_crate)
+                    .unwrap();
+            }
+        }
+        if !library_file_set.is_empty() {
+            roots.push(SourceRoot::new_library(library_file_set));
         }
         roots.push(SourceRoot::new_local(mem::take(&mut file_set)));
         change.set_roots(roots);
         change.set_crate_graph(crate_graph);
 
-        ChangeFixture { file_position, files, change }
+        ChangeFixture { file_position, named_positions, files, real_sysroot, change }
+    }
+}
+
+fn test_proc_macros(proc_macros: &[String]) -> (Vec<ProcMacro>, String) {
+    // The source here is only required so that paths to the macros exist and are resolvable.
+    let source = r#"
+#[proc_macro_attribute]
+pub fn identity(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+#[proc_macro_derive(DeriveIdentity)]
+pub fn derive_identity(item: TokenStream) -> TokenStream {
+    item
+}
+#[proc_macro_derive(DeriveWithHelpers, attributes(helper))]
+pub fn derive_with_helpers(item: TokenStream) -> TokenStream {
+    item
+}
+"#;
+    let available = vec![
+        ProcMacro {
+            name: "identity".into(),
+            kind: ProcMacroKind::Attr,
+            helpers: Box::new([]),
+            expander: Arc::new(IdentityProcMacroExpander),
+        },
+        ProcMacro {
+            name: "DeriveIdentity".into(),
+            kind: ProcMacroKind::CustomDerive,
+            helpers: Box::new([]),
+            expander: Arc::new(EmptyProcMacroExpander),
+        },
+        ProcMacro {
+            name: "DeriveWithHelpers".into(),
+            kind: ProcMacroKind::CustomDerive,
+            helpers: Box::new(["helper".into()]),
+            expander: Arc::new(EmptyProcMacroExpander),
+        },
+    ];
+    let proc_macros = proc_macros
+        .iter()
+        .map(|name| {
+            available
+                .iter()
+                .find(|pm| *name == stdx::to_lower_snake_case(&pm.name))
+                .cloned()
+                .unwrap_or_else(|| panic!("unknown test proc macro: {:?}", name))
+        })
+        .collect();
+    (proc_macros, source.into())
+}
+
+/// Returns the item it is applied to unchanged.
+#[derive(Debug)]
+struct IdentityProcMacroExpander;
+impl ProcMacroExpander for IdentityProcMacroExpander {
+    fn expand(
+        &self,
+        subtree: &Subtree,
+        _: Option<&Subtree>,
+        _: &Env,
+    ) -> Result<Subtree, ExpansionError> {
+        Ok(subtree.clone())
+    }
+}
+
+/// Expands to nothing, which is what a derive that adds no items does.
+#[derive(Debug)]
+struct EmptyProcMacroExpander;
+impl ProcMacroExpander for EmptyProcMacroExpander {
+    fn expand(&self, _: &Subtree, _: Option<&Subtree>, _: &Env) -> Result<Subtree, ExpansionError> {
+        Ok(Subtree::default())
     }
 }
 
@@ -209,11 +486,14 @@ impl ChangeFixture {
 struct FileMeta {
     path: String,
     krate: Option<String>,
-    deps: Vec<String>,
+    deps: Vec<FixtureDep>,
     cfg: CfgOptions,
     edition: Edition,
+    toolchain: Option<Toolchain>,
     env: Env,
+    is_proc_macro: bool,
     introduce_new_source_root: bool,
+    is_library: bool,
 }
 
 impl From<Fixture> for FileMeta {
@@ -221,15 +501,49 @@ impl From<Fixture> for FileMeta {
         let mut cfg = CfgOptions::default();
         f.cfg_atoms.iter().for_each(|it| cfg.insert_atom(it.into()));
         f.cfg_key_values.iter().for_each(|(k, v)| cfg.insert_key_value(k.into(), v.into()));
+        let features = &f.features;
+        let edition = f.edition.as_ref().map_or(Edition::CURRENT, |it| {
+            Edition::from_str(it).unwrap_or_else(|err| panic!("{} for {}", err, f.path))
+        });
+        let toolchain = f.toolchain.as_ref().map(|it| {
+            Toolchain::from_str(it).unwrap_or_else(|err| panic!("{} for {}", err, f.path))
+        });
 
         FileMeta {
             path: f.path,
             krate: f.krate,
-            deps: f.deps,
+            // Optional dependencies are enabled by the feature of the same name.
+            deps: f
+                .deps
+                .into_iter()
+                .filter(|dep| !dep.optional || features.iter().any(|it| it == dep.extern_name()))
+                .collect(),
             cfg,
-            edition: f.edition.as_ref().map_or(Edition::CURRENT, |v| Edition::from_str(v).unwrap()),
+            edition,
+            toolchain,
             env: f.env.into_iter().collect(),
+            is_proc_macro: f.is_proc_macro,
             introduce_new_source_root: f.introduce_new_source_root,
+            is_library: f.is_library,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ChangeFixture;
+
+    #[test]
+    fn library_files_get_their_own_source_root() {
+        let fixture = ChangeFixture::parse(
+            r#"
+//- /main.rs crate:main deps:std
+//- /std/lib.rs crate:std library
+//- /std/io.rs library
+"#,
+        );
+        let roots = fixture.change.roots.unwrap();
+        let roots = roots.iter().map(|it| (it.is_library, it.iter().count())).collect::<Vec<_>>();
+        assert_eq!(roots, vec![(true, 2), (false, 1)]);
+    }
+}
@@ -10,6 +10,7 @@ use std::{fmt, iter::FromIterator, ops, panic::RefUnwindSafe, str::FromStr, sync
 
 use cfg::CfgOptions;
 use rustc_hash::{FxHashMap, FxHashSet};
+use stdx::split_once;
 use syntax::SmolStr;
 use tt::{ExpansionError, Subtree};
 use vfs::{file_set::FileSet, FileId, VfsPath};
@@ -167,6 +168,9 @@ pub trait ProcMacroExpander: fmt::Debug + Send + Sync + RefUnwindSafe {
 pub struct ProcMacro {
     pub name: SmolStr,
     pub kind: ProcMacroKind,
+    /// The names of the helper attributes registered by a derive macro, like
+    /// `serde` for `Serialize`.
+    pub helpers: Box<[SmolStr]>,
     pub expander: Arc<dyn ProcMacroExpander>,
 }
 
@@ -186,6 +190,10 @@ pub struct CrateData {
     pub env: Env,
     pub dependencies: Vec<Dependency>,
     pub proc_macro: Vec<ProcMacro>,
+    /// Whether this is a `proc-macro` crate, which can only export procedural
+    /// macros.
+    pub is_proc_macro: bool,
+    pub toolchain: Option<Toolchain>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -199,6 +207,32 @@ impl Edition {
     pub const CURRENT: Edition = Edition::Edition2018;
 }
 
+/// The toolchain a crate is compiled with, as reported by `rustc --version`.
+/// Used to gate unstable features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Toolchain {
+    pub channel: ReleaseChannel,
+    /// `(major, minor, patch)` version of the toolchain, if known.
+    pub version: Option<(u32, u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReleaseChannel {
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl ReleaseChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => "stable",
+            ReleaseChannel::Beta => "beta",
+            ReleaseChannel::Nightly => "nightly",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct Env {
     entries: FxHashMap<String, String>,
@@ -220,6 +254,8 @@ impl CrateGraph {
         potential_cfg_options: CfgOptions,
         env: Env,
         proc_macro: Vec<ProcMacro>,
+        is_proc_macro: bool,
+        toolchain: Option<Toolchain>,
     ) -> CrateId {
         let data = CrateData {
             root_file_id: file_id,
@@ -229,6 +265,8 @@ impl CrateGraph {
             potential_cfg_options,
             env,
             proc_macro,
+            is_proc_macro,
+            toolchain,
             dependencies: Vec::new(),
         };
         let crate_id = CrateId(self.arena.len() as u32);
@@ -443,6 +481,60 @@ impl fmt::Display for Edition {
     }
 }
 
+impl FromStr for ReleaseChannel {
+    type Err = ParseToolchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let res = match s {
+            "stable" => ReleaseChannel::Stable,
+            "beta" => ReleaseChannel::Beta,
+            "nightly" => ReleaseChannel::Nightly,
+            _ => return Err(ParseToolchainError { invalid_input: s.to_string() }),
+        };
+        Ok(res)
+    }
+}
+
+impl FromStr for Toolchain {
+    type Err = ParseToolchainError;
+
+    /// Parses either a channel name, like `nightly`, or a version with an
+    /// optional channel suffix, like `1.54`, `1.54.0` or `1.55.0-nightly`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseToolchainError { invalid_input: s.to_string() };
+        if let Ok(channel) = s.parse() {
+            return Ok(Toolchain { channel, version: None });
+        }
+        let (version, channel) = match split_once(s, '-') {
+            Some((version, channel)) => (version, channel.parse().map_err(|_| err())?),
+            None => (s, ReleaseChannel::Stable),
+        };
+        let mut parts = version.split('.').map(|it| it.parse::<u32>().map_err(|_| err()));
+        let major = parts.next().ok_or_else(err)??;
+        let minor = parts.next().ok_or_else(err)??;
+        let patch = parts.next().unwrap_or(Ok(0))?;
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        Ok(Toolchain { channel, version: Some((major, minor, patch)) })
+    }
+}
+
+impl fmt::Display for Toolchain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some((major, minor, patch)) => {
+                write!(f, "{}.{}.{}", major, minor, patch)?;
+                if self.channel != ReleaseChannel::Stable {
+                    write!(f, "-{}", self.channel.as_str())?;
+                }
+                Ok(())
+            }
+            None => f.write_str(self.channel.as_str()),
+        }
+    }
+}
+
 impl FromIterator<(String, String)> for Env {
     fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
         Env { entries: FromIterator::from_iter(iter) }
@@ -476,6 +568,19 @@ impl fmt::Display for ParseEditionError {
 
 impl std::error::Error for ParseEditionError {}
 
+#[derive(Debug)]
+pub struct ParseToolchainError {
+    invalid_input: String,
+}
+
+impl fmt::Display for ParseToolchainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid toolchain: {:?}", self.invalid_input)
+    }
+}
+
+impl std::error::Error for ParseToolchainError {}
+
 #[derive(Debug)]
 pub struct CyclicDependenciesError {
     from: (CrateId, Option<CrateDisplayName>),
@@ -494,7 +599,10 @@ impl fmt::Display for CyclicDependenciesError {
 
 #[cfg(test)]
 mod tests {
-    use super::{CfgOptions, CrateGraph, CrateName, Dependency, Edition::Edition2018, Env, FileId};
+    use super::{
+        CfgOptions, CrateGraph, CrateName, Dependency, Edition::Edition2018, Env, FileId,
+        ReleaseChannel, Toolchain,
+    };
 
     #[test]
     fn detect_cyclic_dependency_indirect() {
@@ -507,6 +615,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -516,6 +626,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate3 = graph.add_crate_root(
             FileId(3u32),
@@ -525,6 +637,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate3").unwrap(), crate3).is_ok());
@@ -542,6 +656,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -551,6 +667,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate2").unwrap(), crate2).is_err());
@@ -567,6 +685,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -576,6 +696,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate3 = graph.add_crate_root(
             FileId(3u32),
@@ -585,6 +707,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         assert!(graph.add_dep(crate1, CrateName::new("crate2").unwrap(), crate2).is_ok());
         assert!(graph.add_dep(crate2, CrateName::new("crate3").unwrap(), crate3).is_ok());
@@ -601,6 +725,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         let crate2 = graph.add_crate_root(
             FileId(2u32),
@@ -610,6 +736,8 @@ mod tests {
             CfgOptions::default(),
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         assert!(graph
             .add_dep(crate1, CrateName::normalize_dashes("crate-name-with-dashes"), crate2)
@@ -622,4 +750,25 @@ mod tests {
             }]
         );
     }
+
+    #[test]
+    fn parse_toolchain() {
+        let parse = |s: &str| s.parse::<Toolchain>().ok();
+        assert_eq!(
+            parse("nightly"),
+            Some(Toolchain { channel: ReleaseChannel::Nightly, version: None })
+        );
+        assert_eq!(
+            parse("1.54"),
+            Some(Toolchain { channel: ReleaseChannel::Stable, version: Some((1, 54, 0)) })
+        );
+        assert_eq!(
+            parse("1.55.1-beta"),
+            Some(Toolchain { channel: ReleaseChannel::Beta, version: Some((1, 55, 1)) })
+        );
+        assert_eq!(parse("1"), None);
+        assert_eq!(parse("1.54.0.0"), None);
+        assert_eq!(parse("1.54-dev"), None);
+        assert_eq!(parse("1.55.1-beta").unwrap().to_string(), "1.55.1-beta");
+    }
 }
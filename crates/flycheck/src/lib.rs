@@ -1,6 +1,11 @@
 //! Flycheck provides the functionality needed to run `cargo check` or
 //! another compatible command (f.x. clippy) in a background thread and provide
 //! LSP diagnostics based on the output of the command.
+//!
+//! It also provides the similar machinery for running tests and reporting
+//! their results, see `TestRunHandle`.
+
+mod test_runner;
 
 use std::{
     fmt,
@@ -19,6 +24,8 @@ pub use cargo_metadata::diagnostic::{
     DiagnosticSpanMacroExpansion,
 };
 
+pub use crate::test_runner::{TestEvent, TestOutcome, TestRunHandle, TestRunMessage};
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum FlycheckConfig {
     CargoCommand {
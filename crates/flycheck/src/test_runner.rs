@@ -0,0 +1,226 @@
+//! Runs `cargo test` (or another command running libtest test binaries) in a
+//! background thread, reporting the results of the individual tests.
+//!
+//! The human readable libtest output is parsed, as the JSON one requires a
+//! nightly toolchain.
+
+use std::{
+    io::{self, BufRead, BufReader, Read},
+    process::{self, Command, Stdio},
+};
+
+use stdx::JodChild;
+
+/// A running test command. The command is killed when this struct is dropped.
+#[derive(Debug)]
+pub struct TestRunHandle {
+    #[allow(unused)]
+    child: JodChild,
+}
+
+impl TestRunHandle {
+    pub fn spawn(
+        id: usize,
+        sender: Box<dyn Fn(TestRunMessage) + Send>,
+        mut command: Command,
+    ) -> io::Result<TestRunHandle> {
+        log::info!("running tests {:?}", command);
+        command.stdout(Stdio::piped()).stderr(Stdio::piped()).stdin(Stdio::null());
+        let mut child = command.spawn().map(JodChild)?;
+        let stdout = child.stdout.take().unwrap();
+        let mut stderr = child.stderr.take().unwrap();
+        // The thread is detached: test binaries spawned by `cargo` may keep
+        // the output open for a while after `cargo` itself is killed.
+        jod_thread::Builder::new()
+            .name("TestRun".to_owned())
+            .spawn(move || {
+                // Read on another thread, as the command blocks once the
+                // stderr pipe is full.
+                let stderr = jod_thread::spawn(move || {
+                    let mut buf = String::new();
+                    let _ = stderr.read_to_string(&mut buf);
+                    buf
+                });
+                let result =
+                    read_test_output(stdout, |event| sender(TestRunMessage::Event { id, event }));
+                let stderr = stderr.join();
+                let result = result.map_err(|err| {
+                    // The compile errors, when the tests failed to build.
+                    let stderr = stderr.trim();
+                    if stderr.is_empty() {
+                        return err;
+                    }
+                    io::Error::new(err.kind(), format!("{}:\n{}", err, stderr))
+                });
+                sender(TestRunMessage::Finished { id, result })
+            })
+            .expect("failed to spawn thread")
+            .detach();
+        Ok(TestRunHandle { child })
+    }
+}
+
+#[derive(Debug)]
+pub enum TestRunMessage {
+    Event {
+        id: usize,
+        event: TestEvent,
+    },
+    /// The command exited or was killed. No more events are sent for the run.
+    Finished {
+        id: usize,
+        result: io::Result<()>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestEvent {
+    Finished {
+        name: String,
+        outcome: TestOutcome,
+    },
+    /// The captured output of a failed test.
+    Output {
+        name: String,
+        output: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+fn read_test_output(stdout: process::ChildStdout, on_event: impl Fn(TestEvent)) -> io::Result<()> {
+    let mut parser = TestOutputParser::default();
+    let mut events = Vec::new();
+    for line in BufReader::new(stdout).lines() {
+        parser.feed(&line?, &mut events);
+        events.drain(..).for_each(&on_event);
+    }
+    parser.finish(&mut events);
+    events.drain(..).for_each(&on_event);
+
+    if !parser.saw_result {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the test command did not report any test results, it probably failed to build",
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct TestOutputParser {
+    /// The test whose captured output is being read, and the output so far.
+    output: Option<(String, String)>,
+    saw_result: bool,
+}
+
+impl TestOutputParser {
+    fn feed(&mut self, line: &str, events: &mut Vec<TestEvent>) {
+        if let Some(name) =
+            line.strip_prefix("---- ").and_then(|it| it.strip_suffix(" stdout ----"))
+        {
+            self.finish(events);
+            self.output = Some((name.to_string(), String::new()));
+            return;
+        }
+        if let Some((_, output)) = &mut self.output {
+            if line != "failures:" && !line.starts_with("test result: ") {
+                output.push_str(line);
+                output.push('\n');
+                return;
+            }
+            self.finish(events);
+        }
+
+        if line.starts_with("test result: ") {
+            self.saw_result = true;
+            return;
+        }
+        let (name, result) = match line.strip_prefix("test ").and_then(|it| split(it, " ... ")) {
+            Some(it) => it,
+            None => return,
+        };
+        let outcome = if result == "ok" {
+            TestOutcome::Passed
+        } else if result.starts_with("FAILED") {
+            TestOutcome::Failed
+        } else if result.starts_with("ignored") {
+            TestOutcome::Ignored
+        } else {
+            // Benchmarks, or the "has been running for over 60 seconds" warnings.
+            return;
+        };
+        let name = name.strip_suffix(" - should panic").unwrap_or(name);
+        events.push(TestEvent::Finished { name: name.to_string(), outcome });
+    }
+
+    fn finish(&mut self, events: &mut Vec<TestEvent>) {
+        if let Some((name, output)) = self.output.take() {
+            let output = output.trim_end().to_string();
+            events.push(TestEvent::Output { name, output });
+        }
+    }
+}
+
+fn split<'a>(text: &'a str, pat: &str) -> Option<(&'a str, &'a str)> {
+    let idx = text.find(pat)?;
+    Some((&text[..idx], &text[idx + pat.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_libtest_output() {
+        let output = r#"
+running 4 tests
+test tests::ignored ... ignored
+test tests::passes ... ok
+test tests::panics - should panic ... ok
+test tests::fails ... FAILED
+
+failures:
+
+---- tests::fails stdout ----
+hello
+thread 'tests::fails' panicked at 'assertion failed: false', src/lib.rs:12:9
+
+
+failures:
+    tests::fails
+
+test result: FAILED. 2 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"#;
+        let mut parser = TestOutputParser::default();
+        let mut events = Vec::new();
+        for line in output.lines() {
+            parser.feed(line, &mut events);
+        }
+        parser.finish(&mut events);
+
+        let finished =
+            |name: &str, outcome| TestEvent::Finished { name: name.to_string(), outcome };
+        assert_eq!(
+            events,
+            vec![
+                finished("tests::ignored", TestOutcome::Ignored),
+                finished("tests::passes", TestOutcome::Passed),
+                finished("tests::panics", TestOutcome::Passed),
+                finished("tests::fails", TestOutcome::Failed),
+                TestEvent::Output {
+                    name: "tests::fails".to_string(),
+                    output: "hello\nthread 'tests::fails' panicked at 'assertion failed: false', \
+                             src/lib.rs:12:9"
+                        .to_string(),
+                },
+            ]
+        );
+        assert!(parser.saw_result);
+    }
+}
@@ -34,7 +34,7 @@ mod display;
 use std::{iter, sync::Arc};
 
 use arrayvec::ArrayVec;
-use base_db::{CrateDisplayName, CrateId, Edition, FileId};
+use base_db::{CrateDisplayName, CrateId, Edition, FileId, Toolchain};
 use either::Either;
 use hir_def::{
     adt::{ReprKind, VariantData},
@@ -70,7 +70,7 @@ use itertools::Itertools;
 use nameres::diagnostics::DefDiagnosticKind;
 use once_cell::unsync::Lazy;
 use rustc_hash::FxHashSet;
-use stdx::{format_to, impl_from};
+use stdx::impl_from;
 use syntax::{
     ast::{self, AttrsOwner, NameOwner},
     AstNode, AstPtr, SmolStr, SyntaxKind, SyntaxNodePtr,
@@ -118,7 +118,13 @@ pub use {
         name::{known, Name},
         ExpandResult, HirFileId, InFile, MacroFile, Origin,
     },
-    hir_ty::display::HirDisplay,
+    hir_ty::{
+        consteval::{ComputedExpr, ConstEvalError},
+        diagnostics::to_camel_case,
+        display::HirDisplay,
+        expr_use::ExprUse,
+        layout::Layout,
+    },
 };
 
 // These are negative re-exports: pub using these names is forbidden, they
@@ -184,6 +190,10 @@ impl Crate {
         db.crate_graph()[self.id].edition
     }
 
+    pub fn toolchain(self, db: &dyn HirDatabase) -> Option<Toolchain> {
+        db.crate_graph()[self.id].toolchain
+    }
+
     pub fn display_name(self, db: &dyn HirDatabase) -> Option<CrateDisplayName> {
         db.crate_graph()[self.id].display_name.clone()
     }
@@ -235,6 +245,16 @@ impl Crate {
     pub fn potential_cfg(&self, db: &dyn HirDatabase) -> CfgOptions {
         db.crate_graph()[self.id].potential_cfg_options.clone()
     }
+
+    /// Whether the `#[cfg]` attributes of a syntax node enable it in this crate.
+    pub fn is_cfg_enabled(
+        &self,
+        db: &dyn HirDatabase,
+        owner: InFile<&dyn ast::AttrsOwner>,
+    ) -> bool {
+        let attrs = hir_def::attr::Attrs::from_syntax(db.upcast(), owner, self.id);
+        attrs.is_cfg_enabled(&db.crate_graph()[self.id].cfg_options)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -681,6 +701,17 @@ impl Field {
     pub fn parent_def(&self, _db: &dyn HirDatabase) -> VariantDef {
         self.parent
     }
+
+    /// Returns the offset of the field in its struct, if the layout of the
+    /// struct is known.
+    pub fn offset(&self, db: &dyn HirDatabase) -> Option<u64> {
+        let struct_ = match self.parent {
+            VariantDef::Struct(it) => it,
+            VariantDef::Union(_) | VariantDef::Variant(_) => return None,
+        };
+        let idx = db.field_types(struct_.id.into()).iter().position(|(id, _)| id == self.id)?;
+        Adt::from(struct_).layout(db)?.field_offsets.get(idx).copied()
+    }
 }
 
 impl HasVisibility for Field {
@@ -836,6 +867,11 @@ impl Variant {
         self.variant_data(db).kind()
     }
 
+    /// Evaluates the discriminant of this variant.
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_discriminant(db, self.into())
+    }
+
     pub(crate) fn variant_data(self, db: &dyn HirDatabase) -> Arc<VariantData> {
         db.enum_data(self.parent.id).variants[self.id].variant_data.clone()
     }
@@ -864,6 +900,11 @@ impl Adt {
         Type::from_def(db, id.module(db.upcast()).krate(), id)
     }
 
+    /// Returns the memory layout of the ADT, which is unknown for generic ones.
+    pub fn layout(self, db: &dyn HirDatabase) -> Option<Layout> {
+        self.ty(db).layout(db)
+    }
+
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         match self {
             Adt::Struct(s) => s.module(db),
@@ -939,8 +980,9 @@ pub enum DefWithBody {
     Function(Function),
     Static(Static),
     Const(Const),
+    Variant(Variant),
 }
-impl_from!(Function, Const, Static for DefWithBody);
+impl_from!(Function, Const, Static, Variant for DefWithBody);
 
 impl DefWithBody {
     pub fn module(self, db: &dyn HirDatabase) -> Module {
@@ -948,6 +990,7 @@ impl DefWithBody {
             DefWithBody::Const(c) => c.module(db),
             DefWithBody::Function(f) => f.module(db),
             DefWithBody::Static(s) => s.module(db),
+            DefWithBody::Variant(v) => v.module(db),
         }
     }
 
@@ -956,8 +999,15 @@ impl DefWithBody {
             DefWithBody::Function(f) => Some(f.name(db)),
             DefWithBody::Static(s) => s.name(db),
             DefWithBody::Const(c) => c.name(db),
+            DefWithBody::Variant(v) => Some(v.name(db)),
         }
     }
+
+    /// A textual representation of the HIR of this body for debugging purposes.
+    pub fn debug_hir(self, db: &dyn HirDatabase) -> String {
+        let id = DefWithBodyId::from(self);
+        db.body(id).pretty_print(db.upcast(), id)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1218,17 +1268,9 @@ impl Function {
         db.function_data(self.id).has_body()
     }
 
-    /// A textual representation of the HIR of this function for debugging purposes.
-    pub fn debug_hir(self, db: &dyn HirDatabase) -> String {
-        let body = db.body(self.id.into());
-
-        let mut result = String::new();
-        format_to!(result, "HIR expressions in the body of `{}`:\n", self.name(db));
-        for (id, expr) in body.exprs.iter() {
-            format_to!(result, "{:?}: {:?}\n", id, expr);
-        }
-
-        result
+    /// Evaluates the body of this function, which must not take parameters.
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_function(db, self.id)
     }
 }
 
@@ -1346,6 +1388,10 @@ impl Const {
     pub fn type_ref(self, db: &dyn HirDatabase) -> TypeRef {
         db.const_data(self.id).type_ref.as_ref().clone()
     }
+
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_const(db, self.id)
+    }
 }
 
 impl HasVisibility for Const {
@@ -1373,6 +1419,22 @@ impl Static {
     pub fn is_mut(self, db: &dyn HirDatabase) -> bool {
         db.static_data(self.id).mutable
     }
+
+    /// Whether the static is declared in an `extern` block.
+    pub fn is_extern(self, db: &dyn HirDatabase) -> bool {
+        db.static_data(self.id).is_extern
+    }
+
+    pub fn ty(self, db: &dyn HirDatabase) -> Type {
+        let krate = self.module(db).id.krate();
+        let ty = db.value_ty(self.id.into()).skip_binders().clone();
+        Type::new(db, krate, self.id, ty)
+    }
+
+    /// Evaluates the initializer of this static.
+    pub fn eval(self, db: &dyn HirDatabase) -> Result<ComputedExpr, ConstEvalError> {
+        hir_ty::consteval::eval_static(db, self.id)
+    }
 }
 
 impl HasVisibility for Static {
@@ -1406,6 +1468,12 @@ impl Trait {
     pub fn is_unsafe(&self, db: &dyn HirDatabase) -> bool {
         db.trait_data(self.id).is_unsafe
     }
+
+    /// The traits this trait directly inherits from, like `A` and `B` for
+    /// `trait Foo: A + B`.
+    pub fn direct_supertraits(self, db: &dyn HirDatabase) -> Vec<Trait> {
+        hir_ty::direct_super_traits(db.upcast(), self.id).into_iter().map(Trait::from).collect()
+    }
 }
 
 impl HasVisibility for Trait {
@@ -2230,6 +2298,53 @@ impl Type {
         self.impls_trait(db, copy_trait.into(), &[])
     }
 
+    /// Whether the type contains an `UnsafeCell`, like `Cell`, `RefCell` and the atomics do, so
+    /// that its values can be mutated through shared references. References and pointers to such
+    /// types aren't interior mutable themselves.
+    pub fn is_interior_mutable(&self, db: &dyn HirDatabase) -> bool {
+        let unsafe_cell = match db.lang_item(self.krate, SmolStr::new("unsafe_cell")) {
+            Some(LangItemTarget::StructId(it)) => it,
+            _ => return false,
+        };
+        return go(db, unsafe_cell, &self.ty, &mut FxHashSet::default());
+
+        fn go(
+            db: &dyn HirDatabase,
+            unsafe_cell: StructId,
+            ty: &Ty,
+            visited: &mut FxHashSet<Ty>,
+        ) -> bool {
+            // Recursive types are only interior mutable if a cell is found somewhere else.
+            if !visited.insert(ty.clone()) {
+                return false;
+            }
+            match ty.kind(&Interner) {
+                TyKind::Adt(hir_ty::AdtId(AdtId::StructId(id)), _) if *id == unsafe_cell => true,
+                TyKind::Adt(hir_ty::AdtId(adt_id), substs) => {
+                    let variants: Vec<VariantDef> = match Adt::from(*adt_id) {
+                        Adt::Struct(it) => vec![it.into()],
+                        Adt::Union(it) => vec![it.into()],
+                        Adt::Enum(it) => {
+                            it.variants(db).into_iter().map(VariantDef::from).collect()
+                        }
+                    };
+                    variants.into_iter().any(|variant| {
+                        db.field_types(variant.into()).iter().any(|(_, field_ty)| {
+                            let field_ty = field_ty.clone().substitute(&Interner, substs);
+                            go(db, unsafe_cell, &field_ty, visited)
+                        })
+                    })
+                }
+                TyKind::Tuple(_, substs) => substs
+                    .iter(&Interner)
+                    .filter_map(|it| it.ty(&Interner))
+                    .any(|ty| go(db, unsafe_cell, ty, visited)),
+                TyKind::Array(ty, _) | TyKind::Slice(ty) => go(db, unsafe_cell, ty, visited),
+                _ => false,
+            }
+        }
+    }
+
     pub fn as_callable(&self, db: &dyn HirDatabase) -> Option<Callable> {
         let def = self.ty.callable_def(db);
 
@@ -2262,6 +2377,14 @@ impl Type {
         matches!(&self.ty.kind(&Interner), TyKind::Raw(..))
     }
 
+    pub fn is_type_param(&self) -> bool {
+        matches!(&self.ty.kind(&Interner), TyKind::Placeholder(_))
+    }
+
+    pub fn layout(&self, db: &dyn HirDatabase) -> Option<Layout> {
+        hir_ty::layout::layout_of_ty(db, &self.ty)
+    }
+
     pub fn contains_unknown(&self) -> bool {
         return go(&self.ty);
 
@@ -2317,6 +2440,26 @@ impl Type {
             .collect()
     }
 
+    /// Returns the fields of `variant`, a variant of this enum type, with
+    /// their types.
+    pub fn variant_fields(&self, db: &dyn HirDatabase, variant: Variant) -> Vec<(Field, Type)> {
+        let substs = match self.ty.kind(&Interner) {
+            TyKind::Adt(hir_ty::AdtId(AdtId::EnumId(e)), substs) if *e == variant.parent.id => {
+                substs
+            }
+            _ => return Vec::new(),
+        };
+
+        db.field_types(VariantDef::from(variant).into())
+            .iter()
+            .map(|(local_id, ty)| {
+                let def = Field { parent: variant.into(), id: local_id };
+                let ty = ty.clone().substitute(&Interner, substs);
+                (def, self.derived(ty))
+            })
+            .collect()
+    }
+
     pub fn tuple_fields(&self, _db: &dyn HirDatabase) -> Vec<Type> {
         if let TyKind::Tuple(_, substs) = &self.ty.kind(&Interner) {
             substs
@@ -10,13 +10,13 @@ use hir_def::{
     resolver::{self, HasResolver, Resolver, TypeNs},
     AsMacroCall, FunctionId, TraitId, VariantId,
 };
-use hir_expand::{name::AsName, ExpansionInfo};
+use hir_expand::{name::AsName, ExpansionInfo, MacroCallId, MacroCallKind, MacroDefKind};
 use hir_ty::{associated_type_shorthand_candidates, Interner};
 use itertools::Itertools;
 use rustc_hash::{FxHashMap, FxHashSet};
 use syntax::{
     algo::find_node_at_offset,
-    ast::{self, GenericParamsOwner, LoopBodyOwner},
+    ast::{self, AttrsOwner, GenericParamsOwner, LoopBodyOwner},
     match_ast, AstNode, SyntaxNode, SyntaxNodePtr, SyntaxToken, TextRange, TextSize,
 };
 
@@ -24,9 +24,9 @@ use crate::{
     db::HirDatabase,
     semantics::source_to_def::{ChildContainer, SourceToDefCache, SourceToDefCtx},
     source_analyzer::{resolve_hir_path, SourceAnalyzer},
-    Access, AssocItem, Callable, ConstParam, Crate, Field, Function, HirFileId, Impl, InFile,
-    Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Name, Path, ScopeDef, Trait, Type,
-    TypeAlias, TypeParam, VariantDef,
+    Access, AssocItem, Callable, ConstParam, Crate, ExprUse, Field, Function, HirFileId, Impl,
+    InFile, Label, LifetimeParam, Local, MacroDef, Module, ModuleDef, Name, Path, ScopeDef, Trait,
+    Type, TypeAlias, TypeParam, VariantDef,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -128,6 +128,22 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.is_attr_macro_call(item)
     }
 
+    /// Expands the derive macro called `derive_name` in the `#[derive]` attribute `attr`.
+    pub fn expand_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> Option<SyntaxNode> {
+        self.imp.expand_derive_macro(attr, derive_name)
+    }
+
+    /// Whether the derive macro called `derive_name` in the `#[derive]` attribute `attr` is built
+    /// into the compiler, rather than a procedural macro.
+    pub fn is_builtin_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> bool {
+        self.imp.is_builtin_derive_macro(attr, derive_name)
+    }
+
+    /// Resolves the derive macro called `derive_name` in the `#[derive]` attribute `attr`.
+    pub fn resolve_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> Option<MacroDef> {
+        self.imp.resolve_derive_macro(attr, derive_name)
+    }
+
     pub fn speculative_expand(
         &self,
         actual_macro_call: &ast::MacroCall,
@@ -153,6 +169,12 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.original_range(node)
     }
 
+    /// Returns the range of the token in the original file a token of a macro
+    /// expansion was copied from, if it comes from the input of the call.
+    pub fn original_token_range(&self, token: &SyntaxToken) -> Option<FileRange> {
+        self.imp.original_token_range(token)
+    }
+
     pub fn diagnostics_display_range(&self, diagnostics: InFile<SyntaxNodePtr>) -> FileRange {
         self.imp.diagnostics_display_range(diagnostics)
     }
@@ -232,6 +254,12 @@ impl<'db, DB: HirDatabase> Semantics<'db, DB> {
         self.imp.type_of_self(param)
     }
 
+    /// Returns whether the place expression `expr`, like a local variable or a
+    /// field access, is moved, copied or borrowed.
+    pub fn expr_use(&self, expr: &ast::Expr) -> Option<ExprUse> {
+        self.imp.expr_use(expr)
+    }
+
     pub fn resolve_method_call(&self, call: &ast::MethodCallExpr) -> Option<Function> {
         self.imp.resolve_method_call(call).map(Function::from)
     }
@@ -362,6 +390,38 @@ impl<'db> SemanticsImpl<'db> {
         Some(node)
     }
 
+    fn expand_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> Option<SyntaxNode> {
+        let file_id = self.derive_macro_call(attr, derive_name)?.as_file();
+        let node = self.db.parse_or_expand(file_id)?;
+        self.cache(node.clone(), file_id);
+        Some(node)
+    }
+
+    fn is_builtin_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> bool {
+        self.derive_macro_call(attr, derive_name).map_or(false, |it| {
+            matches!(self.db.lookup_intern_macro(it).def.kind, MacroDefKind::BuiltInDerive(..))
+        })
+    }
+
+    fn resolve_derive_macro(&self, attr: &ast::Attr, derive_name: &str) -> Option<MacroDef> {
+        let macro_call_id = self.derive_macro_call(attr, derive_name)?;
+        Some(self.db.lookup_intern_macro(macro_call_id).def.into())
+    }
+
+    fn derive_macro_call(&self, attr: &ast::Attr, derive_name: &str) -> Option<MacroCallId> {
+        let item = attr.syntax().parent().and_then(ast::Item::cast)?;
+        let attr_index = item.attrs().position(|it| it == *attr)? as u32;
+        let sa = self.analyze(item.syntax());
+        let src = InFile::new(sa.file_id, item);
+        let macro_call_ids = self.with_ctx(|ctx| ctx.item_to_derive_macro_calls(src))?;
+        macro_call_ids.iter().copied().find(|it| match &self.db.lookup_intern_macro(*it).kind {
+            MacroCallKind::Derive { derive_name: name, derive_attr_index, .. } => {
+                *derive_attr_index == attr_index && name == derive_name
+            }
+            _ => false,
+        })
+    }
+
     fn is_attr_macro_call(&self, item: &ast::Item) -> bool {
         let sa = self.analyze(item.syntax());
         let src = InFile::new(sa.file_id, item.clone());
@@ -481,6 +541,13 @@ impl<'db> SemanticsImpl<'db> {
         node.as_ref().original_file_range(self.db.upcast())
     }
 
+    fn original_token_range(&self, token: &SyntaxToken) -> Option<FileRange> {
+        let file_id = self.find_file(token.parent()?).file_id;
+        let token = InFile::new(file_id, token.clone()).original_call_token(self.db.upcast())?;
+        let file_id = token.file_id.original_file(self.db.upcast());
+        Some(FileRange { file_id, range: token.value.text_range() })
+    }
+
     fn diagnostics_display_range(&self, src: InFile<SyntaxNodePtr>) -> FileRange {
         let root = self.db.parse_or_expand(src.file_id).unwrap();
         let node = src.value.to_node(&root);
@@ -584,6 +651,10 @@ impl<'db> SemanticsImpl<'db> {
         self.analyze(param.syntax()).type_of_self(self.db, param)
     }
 
+    fn expr_use(&self, expr: &ast::Expr) -> Option<ExprUse> {
+        self.analyze(expr.syntax()).expr_use(self.db, expr)
+    }
+
     fn resolve_method_call(&self, call: &ast::MethodCallExpr) -> Option<FunctionId> {
         self.analyze(call.syntax()).resolve_method_call(self.db, call).map(|(id, _)| id)
     }
@@ -235,6 +235,14 @@ impl SourceToDefCtx<'_, '_> {
         map[keys::ATTR_MACRO].get(&src).copied()
     }
 
+    pub(super) fn item_to_derive_macro_calls(
+        &mut self,
+        src: InFile<ast::Item>,
+    ) -> Option<Box<[MacroCallId]>> {
+        let map = self.dyn_map(src.as_ref())?;
+        map[keys::DERIVE_MACRO].get(&src).cloned()
+    }
+
     fn to_def<Ast: AstNode + 'static, ID: Copy + 'static>(
         &mut self,
         src: InFile<Ast>,
@@ -21,6 +21,7 @@ use hir_def::{
 use hir_expand::{hygiene::Hygiene, name::AsName, HirFileId, InFile};
 use hir_ty::{
     diagnostics::{record_literal_missing_fields, record_pattern_missing_fields},
+    expr_use::ExprUse,
     InferenceResult, Interner, Substitution, TyExt, TyLoweringContext,
 };
 use syntax::{
@@ -169,6 +170,12 @@ impl SourceAnalyzer {
         Type::new_with_resolver(db, &self.resolver, ty)
     }
 
+    pub(crate) fn expr_use(&self, db: &dyn HirDatabase, expr: &ast::Expr) -> Option<ExprUse> {
+        let expr_id = self.expr_id(db, expr)?;
+        let def = self.resolver.body_owner()?;
+        db.expr_uses(def).get(expr_id)
+    }
+
     pub(crate) fn resolve_method_call(
         &self,
         db: &dyn HirDatabase,
@@ -14,6 +14,7 @@ use tt::{Delimiter, DelimiterKind, Leaf, Subtree, TokenTree};
 
 use crate::{
     body::{CfgExpander, LowerCtx},
+    builtin_type::{BuiltinInt, BuiltinUint},
     db::DefDatabase,
     intern::Interned,
     item_tree::{AttrOwner, Field, Fields, ItemTree, ModItem, RawVisibilityId},
@@ -39,6 +40,7 @@ pub struct StructData {
 pub struct EnumData {
     pub name: Name,
     pub variants: Arena<EnumVariantData>,
+    pub repr: Option<ReprKind>,
     pub visibility: RawVisibility,
 }
 
@@ -66,6 +68,11 @@ pub struct FieldData {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ReprKind {
     Packed,
+    C,
+    Transparent,
+    /// The integer type of the discriminant of an enum, like `#[repr(i8)]`.
+    Int(BuiltinInt),
+    Uint(BuiltinUint),
     Other,
 }
 
@@ -85,10 +92,21 @@ fn parse_repr_tt(tt: &Subtree) -> Option<ReprKind> {
     }
 
     let mut it = tt.token_trees.iter();
-    match it.next()? {
-        TokenTree::Leaf(Leaf::Ident(ident)) if ident.text == "packed" => Some(ReprKind::Packed),
-        _ => Some(ReprKind::Other),
-    }
+    let ident = match it.next()? {
+        TokenTree::Leaf(Leaf::Ident(ident)) => ident,
+        _ => return Some(ReprKind::Other),
+    };
+    let repr = match ident.text.as_str() {
+        "packed" => ReprKind::Packed,
+        "C" => ReprKind::C,
+        "transparent" => ReprKind::Transparent,
+        it => match (BuiltinInt::from_suffix(it), BuiltinUint::from_suffix(it)) {
+            (Some(int), _) => ReprKind::Int(int),
+            (_, Some(uint)) => ReprKind::Uint(uint),
+            (None, None) => ReprKind::Other,
+        },
+    };
+    Some(repr)
 }
 
 impl StructData {
@@ -132,6 +150,7 @@ impl EnumData {
         let loc = e.lookup(db);
         let krate = loc.container.krate;
         let item_tree = loc.id.item_tree(db);
+        let repr = repr_from_value(db, krate, &item_tree, ModItem::from(loc.id.value).into());
         let cfg_options = db.crate_graph()[krate].cfg_options.clone();
 
         let enum_ = &item_tree[loc.id.value];
@@ -158,6 +177,7 @@ impl EnumData {
         Arc::new(EnumData {
             name: enum_.name.clone(),
             variants,
+            repr,
             visibility: item_tree[enum_.visibility].clone(),
         })
     }
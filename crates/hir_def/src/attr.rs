@@ -197,6 +197,16 @@ impl RawAttrs {
 impl Attrs {
     pub const EMPTY: Self = Self(RawAttrs::EMPTY);
 
+    /// Collects the attributes of a syntax node, processing its `cfg_attr`s
+    /// for `krate`.
+    pub fn from_syntax(
+        db: &dyn DefDatabase,
+        owner: InFile<&dyn ast::AttrsOwner>,
+        krate: CrateId,
+    ) -> Attrs {
+        RawAttrs::from_attrs_owner(db, owner).filter(db, krate)
+    }
+
     pub(crate) fn variants_attrs_query(
         db: &dyn DefDatabase,
         e: EnumId,
@@ -248,13 +258,43 @@ impl Attrs {
             _ => Some(CfgExpr::All(cfgs)),
         }
     }
-    pub(crate) fn is_cfg_enabled(&self, cfg_options: &CfgOptions) -> bool {
+    pub fn is_cfg_enabled(&self, cfg_options: &CfgOptions) -> bool {
         match self.cfg() {
             None => true,
             Some(cfg) => cfg_options.check(&cfg) != Some(false),
         }
     }
 
+    /// The names given to the item with `#[doc(alias = "name")]` and `#[doc(alias("a", "b"))]`.
+    pub fn doc_aliases(&self) -> Vec<SmolStr> {
+        let unquote = |lit: &tt::Literal| SmolStr::from(lit.text.trim_matches('"'));
+        let mut res = Vec::new();
+        for tt in self.by_key("doc").tt_values() {
+            let mut trees = tt.token_trees.iter();
+            while let Some(tree) = trees.next() {
+                match tree {
+                    tt::TokenTree::Leaf(tt::Leaf::Ident(ident)) if ident.text == "alias" => (),
+                    _ => continue,
+                }
+                match trees.next() {
+                    Some(tt::TokenTree::Leaf(tt::Leaf::Punct(punct))) if punct.char == '=' => {
+                        if let Some(tt::TokenTree::Leaf(tt::Leaf::Literal(lit))) = trees.next() {
+                            res.push(unquote(lit));
+                        }
+                    }
+                    Some(tt::TokenTree::Subtree(subtree)) => {
+                        res.extend(subtree.token_trees.iter().filter_map(|it| match it {
+                            tt::TokenTree::Leaf(tt::Leaf::Literal(lit)) => Some(unquote(lit)),
+                            _ => None,
+                        }))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        res
+    }
+
     pub fn docs(&self) -> Option<Documentation> {
         let docs = self.by_key("doc").attrs().flat_map(|attr| match attr.input.as_deref()? {
             AttrInput::Literal(s) => Some(s),
@@ -498,7 +498,15 @@ impl ExprCollector<'_> {
                     .ret_type()
                     .and_then(|r| r.ty())
                     .map(|it| Interned::new(TypeRef::from_ast(&self.ctx(), it)));
-                let body = self.collect_expr_opt(e.body());
+                let mut body = self.collect_expr_opt(e.body());
+                // The body of an `async` closure is an `async` block, the return type
+                // annotation is then the output of the future, which isn't checked.
+                let ret_type = if e.async_token().is_some() {
+                    body = self.alloc_expr_desugared(Expr::Async { body });
+                    None
+                } else {
+                    ret_type
+                };
                 self.alloc_expr(Expr::Lambda { args, arg_types, ret_type, body }, syntax_ptr)
             }
             ast::Expr::BinExpr(e) => {
@@ -0,0 +1,762 @@
+//! Pretty-prints the lowered body of a function, const or static as Rust-like
+//! source code, to inspect the result of lowering.
+
+use std::fmt::{self, Write};
+
+use hir_expand::name::Name;
+
+use crate::{
+    body::Body,
+    builtin_type::BuiltinType,
+    db::DefDatabase,
+    expr::{
+        ArithOp, Array, BinaryOp, BindingAnnotation, CmpOp, Expr, ExprId, Literal, LogicOp,
+        Ordering, Pat, PatId, Statement, UnaryOp,
+    },
+    path::{GenericArg, Path, PathKind},
+    type_ref::{Rawness, TypeBound, TypeRef},
+    DefWithBodyId,
+};
+
+pub(super) fn print_body_hir(db: &dyn DefDatabase, body: &Body, owner: DefWithBodyId) -> String {
+    let header = match owner {
+        DefWithBodyId::FunctionId(it) => format!("fn {}", db.function_data(it).name),
+        DefWithBodyId::StaticId(it) => {
+            let name = db.static_data(it).name.clone();
+            format!("static {} = ", name.map_or_else(|| "_".to_string(), |it| it.to_string()))
+        }
+        DefWithBodyId::ConstId(it) => {
+            let name = db.const_data(it).name.clone();
+            format!("const {} = ", name.map_or_else(|| "_".to_string(), |it| it.to_string()))
+        }
+        DefWithBodyId::VariantId(it) => {
+            let enum_data = db.enum_data(it.parent);
+            format!("{}::{} = ", enum_data.name, enum_data.variants[it.local_id].name)
+        }
+    };
+
+    let mut p = Printer { body, buf: header, indent_level: 0, needs_indent: false };
+    if let DefWithBodyId::FunctionId(_) = owner {
+        p.buf.push('(');
+        for (idx, &param) in body.params.iter().enumerate() {
+            if idx != 0 {
+                p.buf.push_str(", ");
+            }
+            p.print_pat(param);
+        }
+        p.buf.push_str(") ");
+    }
+    p.print_expr(body.body_expr);
+    if let DefWithBodyId::StaticId(_) | DefWithBodyId::ConstId(_) = owner {
+        p.buf.push(';');
+    }
+    p.buf.push('\n');
+    p.buf
+}
+
+struct Printer<'a> {
+    body: &'a Body,
+    buf: String,
+    indent_level: usize,
+    needs_indent: bool,
+}
+
+impl<'a> Write for Printer<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (idx, line) in s.split('\n').enumerate() {
+            if idx != 0 {
+                self.buf.push('\n');
+                self.needs_indent = true;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if self.needs_indent {
+                self.buf.push_str(&"    ".repeat(self.indent_level));
+                self.needs_indent = false;
+            }
+            self.buf.push_str(line);
+        }
+        Ok(())
+    }
+}
+
+// `Printer` writes to a `String`, so formatting into it can't fail.
+macro_rules! w {
+    ($dst:expr, $($arg:tt)*) => {
+        { let _ = write!($dst, $($arg)*); }
+    };
+}
+
+macro_rules! wln {
+    ($dst:expr) => {
+        { let _ = writeln!($dst); }
+    };
+    ($dst:expr, $($arg:tt)*) => {
+        { let _ = writeln!($dst, $($arg)*); }
+    };
+}
+
+impl<'a> Printer<'a> {
+    fn indented(&mut self, f: impl FnOnce(&mut Self)) {
+        self.indent_level += 1;
+        wln!(self);
+        f(self);
+        self.indent_level -= 1;
+    }
+
+    fn print_expr(&mut self, expr: ExprId) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Missing => w!(self, "{{missing}}"),
+            Expr::Path(path) => self.print_path(path),
+            Expr::If { condition, then_branch, else_branch } => {
+                w!(self, "if ");
+                self.print_expr(*condition);
+                w!(self, " ");
+                self.print_expr(*then_branch);
+                if let Some(els) = else_branch {
+                    w!(self, " else ");
+                    self.print_expr(*els);
+                }
+            }
+            Expr::Loop { body, label } => {
+                self.print_label(*label);
+                w!(self, "loop ");
+                self.print_expr(*body);
+            }
+            Expr::While { condition, body, label } => {
+                self.print_label(*label);
+                w!(self, "while ");
+                self.print_expr(*condition);
+                w!(self, " ");
+                self.print_expr(*body);
+            }
+            Expr::For { iterable, pat, body, label } => {
+                self.print_label(*label);
+                w!(self, "for ");
+                self.print_pat(*pat);
+                w!(self, " in ");
+                self.print_expr(*iterable);
+                w!(self, " ");
+                self.print_expr(*body);
+            }
+            Expr::Call { callee, args } => {
+                self.print_expr(*callee);
+                w!(self, "(");
+                self.print_comma_separated(args, |p, &arg| p.print_expr(arg));
+                w!(self, ")");
+            }
+            Expr::MethodCall { receiver, method_name, args, generic_args } => {
+                self.print_expr(*receiver);
+                w!(self, ".{}", method_name);
+                if let Some(generic_args) = generic_args {
+                    w!(self, "::<");
+                    self.print_comma_separated(&generic_args.args, |p, arg| {
+                        p.print_generic_arg(arg)
+                    });
+                    w!(self, ">");
+                }
+                w!(self, "(");
+                self.print_comma_separated(args, |p, &arg| p.print_expr(arg));
+                w!(self, ")");
+            }
+            Expr::Match { expr, arms } => {
+                w!(self, "match ");
+                self.print_expr(*expr);
+                w!(self, " {{");
+                self.indented(|p| {
+                    for arm in arms {
+                        p.print_pat(arm.pat);
+                        if let Some(guard) = arm.guard {
+                            w!(p, " if ");
+                            p.print_expr(guard);
+                        }
+                        w!(p, " => ");
+                        p.print_expr(arm.expr);
+                        wln!(p, ",");
+                    }
+                });
+                w!(self, "}}");
+            }
+            Expr::Continue { label } => {
+                w!(self, "continue");
+                if let Some(label) = label {
+                    w!(self, " {}", label);
+                }
+            }
+            Expr::Break { expr, label } => {
+                w!(self, "break");
+                if let Some(label) = label {
+                    w!(self, " {}", label);
+                }
+                if let Some(expr) = expr {
+                    w!(self, " ");
+                    self.print_expr(*expr);
+                }
+            }
+            Expr::Return { expr } => {
+                w!(self, "return");
+                if let Some(expr) = expr {
+                    w!(self, " ");
+                    self.print_expr(*expr);
+                }
+            }
+            Expr::Yield { expr } => {
+                w!(self, "yield");
+                if let Some(expr) = expr {
+                    w!(self, " ");
+                    self.print_expr(*expr);
+                }
+            }
+            Expr::RecordLit { path, fields, spread } => {
+                match path {
+                    Some(path) => self.print_path(path),
+                    None => w!(self, "{{missing}}"),
+                }
+                w!(self, " {{");
+                self.indented(|p| {
+                    for field in fields {
+                        w!(p, "{}: ", field.name);
+                        p.print_expr(field.expr);
+                        wln!(p, ",");
+                    }
+                    if let Some(spread) = spread {
+                        w!(p, "..");
+                        p.print_expr(*spread);
+                        wln!(p);
+                    }
+                });
+                w!(self, "}}");
+            }
+            Expr::Field { expr, name } => {
+                self.print_expr(*expr);
+                w!(self, ".{}", name);
+            }
+            Expr::Await { expr } => {
+                self.print_expr(*expr);
+                w!(self, ".await");
+            }
+            Expr::Try { expr } => {
+                self.print_expr(*expr);
+                w!(self, "?");
+            }
+            Expr::TryBlock { body } => {
+                w!(self, "try ");
+                self.print_expr(*body);
+            }
+            Expr::Async { body } => {
+                w!(self, "async ");
+                self.print_expr(*body);
+            }
+            Expr::Const { body } => {
+                w!(self, "const ");
+                self.print_expr(*body);
+            }
+            Expr::Unsafe { body } => {
+                w!(self, "unsafe ");
+                self.print_expr(*body);
+            }
+            Expr::Cast { expr, type_ref } => {
+                self.print_expr(*expr);
+                w!(self, " as ");
+                self.print_type_ref(type_ref);
+            }
+            Expr::Ref { expr, rawness, mutability } => {
+                w!(self, "&");
+                match rawness {
+                    Rawness::RawPtr => w!(self, "raw {}", mutability.as_keyword_for_ptr()),
+                    Rawness::Ref => w!(self, "{}", mutability.as_keyword_for_ref()),
+                }
+                self.print_expr(*expr);
+            }
+            Expr::Box { expr } => {
+                w!(self, "box ");
+                self.print_expr(*expr);
+            }
+            Expr::UnaryOp { expr, op } => {
+                let op = match op {
+                    UnaryOp::Deref => "*",
+                    UnaryOp::Not => "!",
+                    UnaryOp::Neg => "-",
+                };
+                w!(self, "{}", op);
+                self.print_expr(*expr);
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                self.print_expr(*lhs);
+                match op {
+                    Some(op) => w!(self, " {} ", binary_op_str(*op)),
+                    None => w!(self, " {{missing op}} "),
+                }
+                self.print_expr(*rhs);
+            }
+            Expr::Range { lhs, rhs, range_type } => {
+                if let Some(lhs) = lhs {
+                    self.print_expr(*lhs);
+                }
+                match range_type {
+                    syntax::ast::RangeOp::Exclusive => w!(self, ".."),
+                    syntax::ast::RangeOp::Inclusive => w!(self, "..="),
+                }
+                if let Some(rhs) = rhs {
+                    self.print_expr(*rhs);
+                }
+            }
+            Expr::Index { base, index } => {
+                self.print_expr(*base);
+                w!(self, "[");
+                self.print_expr(*index);
+                w!(self, "]");
+            }
+            Expr::Lambda { args, arg_types, ret_type, body } => {
+                w!(self, "|");
+                for (idx, (pat, ty)) in args.iter().zip(arg_types.iter()).enumerate() {
+                    if idx != 0 {
+                        w!(self, ", ");
+                    }
+                    self.print_pat(*pat);
+                    if let Some(ty) = ty {
+                        w!(self, ": ");
+                        self.print_type_ref(ty);
+                    }
+                }
+                w!(self, "| ");
+                if let Some(ret_type) = ret_type {
+                    w!(self, "-> ");
+                    self.print_type_ref(ret_type);
+                    w!(self, " ");
+                }
+                self.print_expr(*body);
+            }
+            Expr::Tuple { exprs } => {
+                w!(self, "(");
+                self.print_comma_separated(exprs, |p, &expr| p.print_expr(expr));
+                if exprs.len() == 1 {
+                    w!(self, ",");
+                }
+                w!(self, ")");
+            }
+            Expr::Array(Array::ElementList(exprs)) => {
+                w!(self, "[");
+                self.print_comma_separated(exprs, |p, &expr| p.print_expr(expr));
+                w!(self, "]");
+            }
+            Expr::Array(Array::Repeat { initializer, repeat }) => {
+                w!(self, "[");
+                self.print_expr(*initializer);
+                w!(self, "; ");
+                self.print_expr(*repeat);
+                w!(self, "]");
+            }
+            Expr::Literal(lit) => self.print_literal(lit),
+            Expr::Block { id: _, statements, tail, label } => {
+                self.print_label(*label);
+                self.print_block(statements, *tail);
+            }
+            Expr::MacroStmts { tail } => self.print_expr(*tail),
+        }
+    }
+
+    fn print_block(&mut self, statements: &[Statement], tail: Option<ExprId>) {
+        w!(self, "{{");
+        if !statements.is_empty() || tail.is_some() {
+            self.indented(|p| {
+                for stmt in statements {
+                    p.print_stmt(stmt);
+                }
+                if let Some(tail) = tail {
+                    p.print_expr(tail);
+                    wln!(p);
+                }
+            });
+        }
+        w!(self, "}}");
+    }
+
+    fn print_stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Let { pat, type_ref, initializer } => {
+                w!(self, "let ");
+                self.print_pat(*pat);
+                if let Some(ty) = type_ref {
+                    w!(self, ": ");
+                    self.print_type_ref(ty);
+                }
+                if let Some(init) = initializer {
+                    w!(self, " = ");
+                    self.print_expr(*init);
+                }
+                wln!(self, ";");
+            }
+            Statement::Expr { expr, has_semi } => {
+                self.print_expr(*expr);
+                if *has_semi {
+                    w!(self, ";");
+                }
+                wln!(self);
+            }
+        }
+    }
+
+    fn print_label(&mut self, label: Option<crate::expr::LabelId>) {
+        if let Some(label) = label {
+            w!(self, "{}: ", self.body[label].name);
+        }
+    }
+
+    fn print_pat(&mut self, pat: PatId) {
+        let body = self.body;
+        match &body[pat] {
+            Pat::Missing => w!(self, "{{missing}}"),
+            Pat::Wild => w!(self, "_"),
+            Pat::Tuple { args, ellipsis } => {
+                w!(self, "(");
+                self.print_pats_with_ellipsis(args, *ellipsis);
+                w!(self, ")");
+            }
+            Pat::Or(pats) => {
+                for (idx, &pat) in pats.iter().enumerate() {
+                    if idx != 0 {
+                        w!(self, " | ");
+                    }
+                    self.print_pat(pat);
+                }
+            }
+            Pat::Record { path, args, ellipsis } => {
+                match path {
+                    Some(path) => self.print_path(path),
+                    None => w!(self, "{{missing}}"),
+                }
+                w!(self, " {{ ");
+                self.print_comma_separated(args, |p, field| {
+                    w!(p, "{}: ", field.name);
+                    p.print_pat(field.pat);
+                });
+                if *ellipsis {
+                    if !args.is_empty() {
+                        w!(self, ", ");
+                    }
+                    w!(self, "..");
+                }
+                w!(self, " }}");
+            }
+            Pat::Range { start, end } => {
+                self.print_expr(*start);
+                w!(self, "..=");
+                self.print_expr(*end);
+            }
+            Pat::Slice { prefix, slice, suffix } => {
+                w!(self, "[");
+                let mut first = true;
+                for &pat in prefix.iter().chain(slice.iter()).chain(suffix.iter()) {
+                    if !first {
+                        w!(self, ", ");
+                    }
+                    first = false;
+                    if Some(pat) == *slice {
+                        self.print_rest_pat(pat);
+                    } else {
+                        self.print_pat(pat);
+                    }
+                }
+                w!(self, "]");
+            }
+            Pat::Path(path) => self.print_path(path),
+            Pat::Lit(expr) => self.print_expr(*expr),
+            Pat::Bind { mode, name, subpat } => {
+                self.print_binding(*mode, name);
+                if let Some(pat) = subpat {
+                    w!(self, " @ ");
+                    self.print_pat(*pat);
+                }
+            }
+            Pat::TupleStruct { path, args, ellipsis } => {
+                match path {
+                    Some(path) => self.print_path(path),
+                    None => w!(self, "{{missing}}"),
+                }
+                w!(self, "(");
+                self.print_pats_with_ellipsis(args, *ellipsis);
+                w!(self, ")");
+            }
+            Pat::Ref { pat, mutability } => {
+                w!(self, "&{}", mutability.as_keyword_for_ref());
+                self.print_pat(*pat);
+            }
+            Pat::Box { inner } => {
+                w!(self, "box ");
+                self.print_pat(*inner);
+            }
+            Pat::ConstBlock(expr) => {
+                w!(self, "const ");
+                self.print_expr(*expr);
+            }
+        }
+    }
+
+    /// `..` and `name @ ..` in slice patterns are lowered with a missing
+    /// pattern in place of the `..`.
+    fn print_rest_pat(&mut self, pat: PatId) {
+        let body = self.body;
+        match &body[pat] {
+            Pat::Missing => w!(self, ".."),
+            Pat::Bind { mode, name, subpat: Some(subpat) } if body[*subpat] == Pat::Missing => {
+                self.print_binding(*mode, name);
+                w!(self, " @ ..");
+            }
+            _ => self.print_pat(pat),
+        }
+    }
+
+    fn print_binding(&mut self, mode: BindingAnnotation, name: &Name) {
+        let mode = match mode {
+            BindingAnnotation::Unannotated => "",
+            BindingAnnotation::Mutable => "mut ",
+            BindingAnnotation::Ref => "ref ",
+            BindingAnnotation::RefMut => "ref mut ",
+        };
+        w!(self, "{}{}", mode, name);
+    }
+
+    fn print_pats_with_ellipsis(&mut self, pats: &[PatId], ellipsis: Option<usize>) {
+        for (idx, &pat) in pats.iter().enumerate() {
+            if idx != 0 {
+                w!(self, ", ");
+            }
+            if ellipsis == Some(idx) {
+                w!(self, ".., ");
+            }
+            self.print_pat(pat);
+        }
+        if ellipsis == Some(pats.len()) {
+            if !pats.is_empty() {
+                w!(self, ", ");
+            }
+            w!(self, "..");
+        }
+    }
+
+    fn print_literal(&mut self, literal: &Literal) {
+        match literal {
+            Literal::String(it) => w!(self, "{:?}", it),
+            Literal::ByteString(it) => w!(self, "b{:?}", String::from_utf8_lossy(it)),
+            Literal::Char(it) => w!(self, "{:?}", it),
+            Literal::Bool(it) => w!(self, "{}", it),
+            Literal::Int(value, suffix) => {
+                w!(self, "{}", value);
+                if let Some(suffix) = suffix {
+                    w!(self, "{}", BuiltinType::Int(*suffix));
+                }
+            }
+            Literal::Uint(value, suffix) => {
+                w!(self, "{}", value);
+                if let Some(suffix) = suffix {
+                    w!(self, "{}", BuiltinType::Uint(*suffix));
+                }
+            }
+            // The values of float literals aren't lowered.
+            Literal::Float(_, suffix) => {
+                w!(self, "{{float}}");
+                if let Some(suffix) = suffix {
+                    w!(self, "{}", BuiltinType::Float(*suffix));
+                }
+            }
+        }
+    }
+
+    fn print_path(&mut self, path: &Path) {
+        if let Some(anchor) = path.type_anchor() {
+            w!(self, "<");
+            self.print_type_ref(anchor);
+            w!(self, ">::");
+        }
+        let mut first_segment = true;
+        let mut write_segment = |p: &mut Self, segment: &dyn fmt::Display| {
+            if !first_segment {
+                w!(p, "::");
+            }
+            first_segment = false;
+            w!(p, "{}", segment);
+        };
+        match path.kind() {
+            PathKind::Plain => {}
+            PathKind::Super(0) => write_segment(self, &"self"),
+            PathKind::Super(n) => {
+                for _ in 0..*n {
+                    write_segment(self, &"super");
+                }
+            }
+            PathKind::Crate => write_segment(self, &"crate"),
+            PathKind::Abs => write_segment(self, &""),
+            PathKind::DollarCrate(_) => write_segment(self, &"$crate"),
+        }
+        for segment in path.segments().iter() {
+            write_segment(self, segment.name);
+            if let Some(generics) = segment.args_and_bindings {
+                w!(self, "::<");
+                let mut first = true;
+                let args =
+                    if generics.has_self_type { &generics.args[1..] } else { &generics.args };
+                for arg in args {
+                    if !first {
+                        w!(self, ", ");
+                    }
+                    first = false;
+                    self.print_generic_arg(arg);
+                }
+                for binding in &generics.bindings {
+                    if !first {
+                        w!(self, ", ");
+                    }
+                    first = false;
+                    w!(self, "{}", binding.name);
+                    if let Some(ty) = &binding.type_ref {
+                        w!(self, " = ");
+                        self.print_type_ref(ty);
+                    }
+                }
+                w!(self, ">");
+            }
+        }
+    }
+
+    fn print_generic_arg(&mut self, arg: &GenericArg) {
+        match arg {
+            GenericArg::Type(ty) => self.print_type_ref(ty),
+            GenericArg::Lifetime(lt) => w!(self, "{}", lt.name),
+        }
+    }
+
+    fn print_type_ref(&mut self, type_ref: &TypeRef) {
+        match type_ref {
+            TypeRef::Never => w!(self, "!"),
+            TypeRef::Placeholder => w!(self, "_"),
+            TypeRef::Tuple(fields) => {
+                w!(self, "(");
+                self.print_comma_separated(fields, |p, field| p.print_type_ref(field));
+                if fields.len() == 1 {
+                    w!(self, ",");
+                }
+                w!(self, ")");
+            }
+            TypeRef::Path(path) => self.print_path(path),
+            TypeRef::RawPtr(pointee, mutability) => {
+                w!(self, "*{}", mutability.as_keyword_for_ptr());
+                self.print_type_ref(pointee);
+            }
+            TypeRef::Reference(pointee, lifetime, mutability) => {
+                w!(self, "&");
+                if let Some(lt) = lifetime {
+                    w!(self, "{} ", lt.name);
+                }
+                w!(self, "{}", mutability.as_keyword_for_ref());
+                self.print_type_ref(pointee);
+            }
+            TypeRef::Array(elem, len) => {
+                w!(self, "[");
+                self.print_type_ref(elem);
+                w!(self, "; {}]", len);
+            }
+            TypeRef::Slice(elem) => {
+                w!(self, "[");
+                self.print_type_ref(elem);
+                w!(self, "]");
+            }
+            TypeRef::Fn(args_and_ret, varargs) => {
+                let (ret, args) =
+                    args_and_ret.split_last().expect("TypeRef::Fn is missing return type");
+                w!(self, "fn(");
+                self.print_comma_separated(args, |p, arg| p.print_type_ref(arg));
+                if *varargs {
+                    if !args.is_empty() {
+                        w!(self, ", ");
+                    }
+                    w!(self, "...");
+                }
+                w!(self, ") -> ");
+                self.print_type_ref(ret);
+            }
+            TypeRef::ImplTrait(bounds) => {
+                w!(self, "impl ");
+                self.print_type_bounds(bounds);
+            }
+            TypeRef::DynTrait(bounds) => {
+                w!(self, "dyn ");
+                self.print_type_bounds(bounds);
+            }
+            TypeRef::Macro(_) => w!(self, "{{macro}}"),
+            TypeRef::Error => w!(self, "{{error}}"),
+        }
+    }
+
+    fn print_type_bounds(&mut self, bounds: &[crate::intern::Interned<TypeBound>]) {
+        for (idx, bound) in bounds.iter().enumerate() {
+            if idx != 0 {
+                w!(self, " + ");
+            }
+            match &**bound {
+                TypeBound::Path(path) => self.print_path(path),
+                TypeBound::ForLifetime(lifetimes, path) => {
+                    w!(self, "for<");
+                    self.print_comma_separated(lifetimes, |p, lt| w!(p, "{}", lt));
+                    w!(self, "> ");
+                    self.print_path(path);
+                }
+                TypeBound::Lifetime(lt) => w!(self, "{}", lt.name),
+                TypeBound::Error => w!(self, "{{error}}"),
+            }
+        }
+    }
+
+    fn print_comma_separated<T>(&mut self, items: &[T], mut f: impl FnMut(&mut Self, &T)) {
+        for (idx, item) in items.iter().enumerate() {
+            if idx != 0 {
+                w!(self, ", ");
+            }
+            f(self, item);
+        }
+    }
+}
+
+fn binary_op_str(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::LogicOp(LogicOp::And) => "&&",
+        BinaryOp::LogicOp(LogicOp::Or) => "||",
+        BinaryOp::ArithOp(op) => arith_op_str(op),
+        BinaryOp::CmpOp(CmpOp::Eq { negated: false }) => "==",
+        BinaryOp::CmpOp(CmpOp::Eq { negated: true }) => "!=",
+        BinaryOp::CmpOp(CmpOp::Ord { ordering: Ordering::Less, strict: true }) => "<",
+        BinaryOp::CmpOp(CmpOp::Ord { ordering: Ordering::Less, strict: false }) => "<=",
+        BinaryOp::CmpOp(CmpOp::Ord { ordering: Ordering::Greater, strict: true }) => ">",
+        BinaryOp::CmpOp(CmpOp::Ord { ordering: Ordering::Greater, strict: false }) => ">=",
+        BinaryOp::Assignment { op: None } => "=",
+        BinaryOp::Assignment { op: Some(op) } => match op {
+            ArithOp::Add => "+=",
+            ArithOp::Mul => "*=",
+            ArithOp::Sub => "-=",
+            ArithOp::Div => "/=",
+            ArithOp::Rem => "%=",
+            ArithOp::Shl => "<<=",
+            ArithOp::Shr => ">>=",
+            ArithOp::BitXor => "^=",
+            ArithOp::BitOr => "|=",
+            ArithOp::BitAnd => "&=",
+        },
+    }
+}
+
+fn arith_op_str(op: ArithOp) -> &'static str {
+    match op {
+        ArithOp::Add => "+",
+        ArithOp::Mul => "*",
+        ArithOp::Sub => "-",
+        ArithOp::Div => "/",
+        ArithOp::Rem => "%",
+        ArithOp::Shl => "<<",
+        ArithOp::Shr => ">>",
+        ArithOp::BitXor => "^",
+        ArithOp::BitOr => "|",
+        ArithOp::BitAnd => "&",
+    }
+}
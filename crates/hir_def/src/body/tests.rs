@@ -1,7 +1,7 @@
 mod block;
 
 use base_db::{fixture::WithFixture, SourceDatabase};
-use expect_test::Expect;
+use expect_test::{expect, Expect};
 
 use crate::ModuleDefId;
 
@@ -9,7 +9,10 @@ use super::*;
 
 fn lower(ra_fixture: &str) -> Arc<Body> {
     let db = crate::test_db::TestDB::with_files(ra_fixture);
+    db.body(first_fn(&db).into())
+}
 
+fn first_fn(db: &crate::test_db::TestDB) -> crate::FunctionId {
     let krate = db.crate_graph().iter().next().unwrap();
     let def_map = db.crate_def_map(krate);
     let mut fn_def = None;
@@ -22,7 +25,14 @@ fn lower(ra_fixture: &str) -> Arc<Body> {
         }
     }
 
-    db.body(fn_def.unwrap().into())
+    fn_def.unwrap()
+}
+
+fn check_pretty_print(ra_fixture: &str, expect: Expect) {
+    let db = crate::test_db::TestDB::with_files(ra_fixture);
+    let def = first_fn(&db).into();
+    let actual = db.body(def).pretty_print(&db, def);
+    expect.assert_eq(&actual);
 }
 
 fn block_def_map_at(ra_fixture: &str) -> String {
@@ -79,3 +89,49 @@ mod m {
 "#,
     );
 }
+
+#[test]
+fn pretty_print_desugared_body() {
+    check_pretty_print(
+        r#"
+fn main(x: u32, (a, b): (u8, u8)) {
+    let mut y: Vec<u8> = Vec::<u8>::new();
+    if let Some(z) = x.checked_add(1) {
+        y[0] += z as u8;
+    }
+    'outer: for i in 0..=x {
+        match &y {
+            [first, rest @ ..] if *first > 0 => break 'outer,
+            _ => continue,
+        }
+    }
+    let f = |p: u8| -> u8 { p * 2 };
+    S { a, ..S::default() };
+}
+"#,
+        expect![[r#"
+            fn main(x, (a, b)) {
+                let mut y: Vec::<u8> = Vec::<u8>::new();
+                match x.checked_add(1) {
+                    Some(z) => {
+                        y[0] += z as u8;
+                    },
+                    {missing} => (),
+                }
+                'outer: for i in 0..=x {
+                    match &y {
+                        [first, rest @ ..] if *first > 0 => break 'outer,
+                        _ => continue,
+                    }
+                }
+                let f = |p: u8| -> u8 {
+                    p * 2
+                };
+                S {
+                    a: a,
+                    ..S::default()
+                };
+            }
+        "#]],
+    );
+}
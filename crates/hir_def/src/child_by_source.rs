@@ -89,6 +89,10 @@ impl ChildBySource for ItemScope {
             let item = ast_id.with_value(ast_id.to_node(db.upcast()));
             res[keys::ATTR_MACRO].insert(item, call_id);
         });
+        self.derive_macro_invocs().for_each(|(ast_id, call_ids)| {
+            let item = ast_id.with_value(ast_id.to_node(db.upcast()));
+            res[keys::DERIVE_MACRO].insert(item, call_ids.into());
+        });
 
         fn add_module_def(db: &dyn DefDatabase, map: &mut DynMap, item: ModuleDefId) {
             match item {
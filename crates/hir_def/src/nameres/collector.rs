@@ -390,8 +390,10 @@ impl DefCollector<'_> {
         }
         self.unresolved_imports = unresolved_imports;
 
-        // FIXME: This condition should instead check if this is a `proc-macro` type crate.
-        if self.exports_proc_macros {
+        // Crates which aren't known to be `proc-macro` crates, but still export procedural macros,
+        // are treated the same.
+        let is_proc_macro = self.db.crate_graph()[self.def_map.krate].is_proc_macro;
+        if is_proc_macro || self.exports_proc_macros {
             // A crate exporting procedural macros is not allowed to export anything else.
             //
             // Additionally, while the proc macro entry points must be `pub`, they are not publicly
@@ -1044,6 +1046,10 @@ impl DefCollector<'_> {
                         &resolver,
                     ) {
                         Ok(call_id) => {
+                            self.def_map.modules[directive.module_id]
+                                .scope
+                                .add_derive_macro_invoc(ast_id.ast_id, call_id);
+
                             resolved.push((directive.module_id, call_id, directive.depth));
                             res = ReachedFixedPoint::No;
                             return false;
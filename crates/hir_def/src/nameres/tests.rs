@@ -493,6 +493,32 @@ fn no_std_prelude() {
     );
 }
 
+#[test]
+fn no_std_prelude_via_crate_attr() {
+    check(
+        r#"
+        //- /main.rs crate:main deps:core,std crate-attr:no_std
+        use Rust;
+
+        //- /core.rs crate:core
+        pub mod prelude {
+            pub mod rust_2018 {
+                pub struct Rust;
+            }
+        }
+        //- /std.rs crate:std deps:core
+        pub mod prelude {
+            pub mod rust_2018 {
+            }
+        }
+    "#,
+        expect![[r#"
+        crate
+        Rust: t v
+    "#]],
+    );
+}
+
 #[test]
 fn edition_specific_preludes() {
     // We can't test the 2015 prelude here since you can't reexport its contents with 2015's
@@ -620,6 +646,73 @@ pub mod prelude {
     );
 }
 
+#[test]
+fn cfg_features() {
+    check(
+        r#"
+//- /main.rs crate:main deps:dep features:std,dep/derive
+#[cfg(feature = "std")]
+use dep::{Derive, Serialize};
+
+//- /lib.rs crate:dep
+#[cfg(feature = "derive")]
+pub struct Derive;
+#[cfg(feature = "serialize")]
+pub struct Serialize;
+"#,
+        expect![[r#"
+            crate
+            Derive: t v
+            Serialize: _
+        "#]],
+    );
+}
+
+#[test]
+fn renamed_and_optional_deps() {
+    check(
+        r#"
+//- /main.rs crate:main deps:foo(rename=bar),baz?,qux? features:qux
+use bar::Foo;
+use baz::Baz;
+use qux::Qux;
+
+//- /foo.rs crate:foo
+pub struct Foo;
+//- /baz.rs crate:baz
+pub struct Baz;
+//- /qux.rs crate:qux
+pub struct Qux;
+"#,
+        expect![[r#"
+            crate
+            Baz: _
+            Foo: t v
+            Qux: t v
+        "#]],
+    );
+}
+
+#[test]
+fn cfg_target() {
+    check(
+        r#"
+//- /main.rs crate:main target:x86_64-pc-windows-msvc
+#[cfg(windows)]
+struct Windows;
+#[cfg(unix)]
+struct Unix;
+#[cfg(all(target_os = "windows", target_pointer_width = "64"))]
+struct Win64;
+"#,
+        expect![[r#"
+            crate
+            Win64: t v
+            Windows: t v
+        "#]],
+    );
+}
+
 #[test]
 fn infer_multiple_namespace() {
     check(
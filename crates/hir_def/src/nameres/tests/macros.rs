@@ -953,6 +953,46 @@ fn proc_macro_censoring() {
     );
 }
 
+#[test]
+fn proc_macro_crate_censoring_without_proc_macros() {
+    // Items of a crate marked as `proc-macro` crate are never exported, even without any
+    // `#[proc_macro]` functions.
+
+    check(
+        r"
+        //- /main.rs crate:main deps:macros
+        pub use macros::*;
+
+        //- /macros.rs crate:macros crate-type:proc-macro
+        pub struct TokenStream;
+
+        #[macro_export]
+        macro_rules! mbe {
+            () => {};
+        }
+        ",
+        expect![[r#"
+            crate
+        "#]],
+    );
+}
+
+#[test]
+fn resolves_test_proc_macros() {
+    check(
+        r#"
+//- proc_macros: identity, derive_identity
+//- /lib.rs
+pub use proc_macros::{identity, DeriveIdentity};
+"#,
+        expect![[r#"
+            crate
+            DeriveIdentity: m
+            identity: m
+        "#]],
+    );
+}
+
 #[test]
 fn collects_derive_helpers() {
     let def_map = compute_crate_def_map(
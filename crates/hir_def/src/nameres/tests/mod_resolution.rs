@@ -52,6 +52,28 @@ struct X;
     );
 }
 
+#[test]
+fn nested_module_resolution_auto_mods() {
+    check(
+        r#"
+//- auto_mods
+//- /lib.rs
+//- /n1/n2.rs
+struct X;
+"#,
+        expect![[r#"
+            crate
+            n1: t
+
+            crate::n1
+            n2: t
+
+            crate::n1::n2
+            X: t v
+        "#]],
+    );
+}
+
 #[test]
 fn nested_module_resolution_2() {
     check(
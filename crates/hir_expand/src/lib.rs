@@ -523,6 +523,14 @@ fn ascend_call_token(
 }
 
 impl InFile<SyntaxToken> {
+    /// Maps a token of a macro expansion to the token of the original file it
+    /// was copied from, through the inputs of the macro calls. Returns `None`
+    /// for tokens produced by macro definitions.
+    pub fn original_call_token(self, db: &dyn db::AstDatabase) -> Option<InFile<SyntaxToken>> {
+        let expansion = self.file_id.expansion_info(db)?;
+        ascend_call_token(db, &expansion, self)
+    }
+
     pub fn ancestors_with_macros(
         self,
         db: &dyn db::AstDatabase,
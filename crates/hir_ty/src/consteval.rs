@@ -1,14 +1,29 @@
 //! Constant evaluation details
 
-use std::convert::TryInto;
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt, iter,
+};
 
 use hir_def::{
-    builtin_type::BuiltinUint,
-    expr::{Expr, Literal},
+    adt::ReprKind,
+    body::Body,
+    builtin_type::{BuiltinInt, BuiltinType, BuiltinUint},
+    expr::{
+        ArithOp, BinaryOp, CmpOp, Expr, ExprId, Literal, LogicOp, Ordering, Pat, PatId, Statement,
+        UnaryOp,
+    },
+    resolver::{resolver_for_expr, ValueNs},
     type_ref::ConstScalar,
+    ConstId, DefWithBodyId, EnumId, EnumVariantId, FunctionId, StaticId,
 };
+use rustc_hash::FxHashMap;
 
-use crate::{Const, ConstData, ConstValue, Interner, TyKind};
+use crate::{
+    db::HirDatabase,
+    primitive::{IntTy, UintTy},
+    Const, ConstData, ConstValue, InferenceResult, Interner, Scalar, Ty, TyBuilder, TyExt, TyKind,
+};
 
 /// Extension trait for [`Const`]
 pub trait ConstExt {
@@ -54,3 +69,619 @@ pub fn usize_const(value: Option<u64>) -> Const {
     }
     .intern(&Interner)
 }
+
+/// A value computed by [`eval_function`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputedExpr {
+    Literal(Literal),
+    Tuple(Box<[ComputedExpr]>),
+}
+
+impl ComputedExpr {
+    fn unit() -> ComputedExpr {
+        ComputedExpr::Tuple(Box::new([]))
+    }
+
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            ComputedExpr::Literal(Literal::Int(it, _)) => Some(*it),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            ComputedExpr::Literal(Literal::Bool(it)) => Some(*it),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ComputedExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputedExpr::Literal(lit) => match lit {
+                Literal::Int(it, _) => write!(f, "{}", it),
+                Literal::Uint(it, _) => write!(f, "{}", it),
+                Literal::Float(it, _) => write!(f, "{}", f64::from_bits(*it)),
+                Literal::Bool(it) => write!(f, "{}", it),
+                Literal::Char(it) => write!(f, "{:?}", it),
+                Literal::String(it) => write!(f, "{:?}", it),
+                Literal::ByteString(it) => write!(f, "b{:?}", String::from_utf8_lossy(it)),
+            },
+            ComputedExpr::Tuple(fields) => {
+                write!(f, "(")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", field)?;
+                }
+                if fields.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// The reason a body can't be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstEvalError {
+    /// The body uses something the evaluator doesn't support yet.
+    NotSupported(&'static str),
+    /// The body has type errors or unresolved names.
+    TypeError,
+    /// The body is syntactically incomplete, like `1 +`.
+    IncompleteExpr,
+    /// The evaluation would panic at runtime, like on an overflow.
+    Panic(String),
+    /// The evaluation takes too many steps or is recursing too deep.
+    LimitReached,
+}
+
+impl fmt::Display for ConstEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstEvalError::NotSupported(what) => write!(f, "{} are not supported yet", what),
+            ConstEvalError::TypeError => write!(f, "the code contains type errors"),
+            ConstEvalError::IncompleteExpr => write!(f, "the code is incomplete"),
+            ConstEvalError::Panic(msg) => write!(f, "evaluation panicked: {}", msg),
+            ConstEvalError::LimitReached => write!(f, "evaluation limit reached"),
+        }
+    }
+}
+
+const MAX_DEPTH: usize = 64;
+const MAX_STEPS: usize = 1_000_000;
+
+/// Evaluates the body of `func`, which must not take any parameters.
+///
+/// This is a simple tree-walking interpreter over the HIR of the body,
+/// supporting integers, booleans, tuples, local variables, control flow and
+/// calls of other functions. Anything else results in an error.
+pub fn eval_function(
+    db: &dyn HirDatabase,
+    func: FunctionId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    if !db.body(func.into()).params.is_empty() {
+        return Err(ConstEvalError::NotSupported("functions with parameters"));
+    }
+    let mut steps = 0;
+    eval_body(db, func.into(), Vec::new(), 0, &mut steps)
+}
+
+/// Evaluates the initializer of a `const`.
+pub fn eval_const(db: &dyn HirDatabase, konst: ConstId) -> Result<ComputedExpr, ConstEvalError> {
+    let mut steps = 0;
+    eval_body(db, konst.into(), Vec::new(), 0, &mut steps)
+}
+
+/// Evaluates the initializer of a `static`.
+pub fn eval_static(
+    db: &dyn HirDatabase,
+    static_: StaticId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let mut steps = 0;
+    eval_body(db, static_.into(), Vec::new(), 0, &mut steps)
+}
+
+/// Evaluates the discriminant of an enum variant. A variant without an explicit discriminant
+/// has the one of the previous variant plus one, or zero if it is the first variant.
+pub fn eval_discriminant(
+    db: &dyn HirDatabase,
+    variant: EnumVariantId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    let enum_data = db.enum_data(variant.parent);
+    let previous = enum_data
+        .variants
+        .iter()
+        .map(|(local_id, _)| EnumVariantId { parent: variant.parent, local_id })
+        .take_while(|&it| it != variant)
+        .collect::<Vec<_>>();
+    // The number of variants since the last one with an explicit discriminant.
+    let mut offset = 0;
+    let mut value = None;
+    for it in iter::once(variant).chain(previous.into_iter().rev()) {
+        let body = db.body(it.into());
+        if let Expr::Missing = body[body.body_expr] {
+            offset += 1;
+            continue;
+        }
+        let mut steps = 0;
+        let base = eval_body(db, it.into(), Vec::new(), 0, &mut steps)?;
+        value = Some(base.as_int().ok_or(ConstEvalError::TypeError)?);
+        break;
+    }
+    let value = match value {
+        Some(it) => it.checked_add(offset),
+        None => Some(offset - 1),
+    };
+    let (bits, signed) =
+        int_ty(&discriminant_ty(db, variant.parent)).ok_or(ConstEvalError::TypeError)?;
+    let (min, max) = int_bounds(bits, signed);
+    match value {
+        Some(it) if min <= it && it <= max => Ok(ComputedExpr::Literal(Literal::Int(it, None))),
+        _ => Err(ConstEvalError::Panic("enum discriminant overflowed".to_string())),
+    }
+}
+
+/// Returns the type of the discriminants of an enum, given by its `repr` attribute.
+pub(crate) fn discriminant_ty(db: &dyn HirDatabase, enum_: EnumId) -> Ty {
+    let builtin = match db.enum_data(enum_).repr {
+        Some(ReprKind::Int(it)) => BuiltinType::Int(it),
+        Some(ReprKind::Uint(it)) => BuiltinType::Uint(it),
+        _ => BuiltinType::Int(BuiltinInt::Isize),
+    };
+    TyBuilder::builtin(builtin)
+}
+
+fn eval_body(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    args: Vec<ComputedExpr>,
+    depth: usize,
+    steps: &mut usize,
+) -> Result<ComputedExpr, ConstEvalError> {
+    if depth > MAX_DEPTH {
+        return Err(ConstEvalError::LimitReached);
+    }
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    if body.params.len() != args.len() {
+        return Err(ConstEvalError::TypeError);
+    }
+    let mut ctx = ConstEvalCtx {
+        db,
+        owner,
+        body: &body,
+        infer: &infer,
+        locals: FxHashMap::default(),
+        depth,
+        steps,
+    };
+    for (&pat, arg) in body.params.iter().zip(args) {
+        ctx.bind(pat, arg)?;
+    }
+    match ctx.eval_expr(body.body_expr) {
+        Ok(it) | Err(Interrupt::Return(it)) => Ok(it),
+        Err(Interrupt::Error(err)) => Err(err),
+        Err(Interrupt::Break(_)) | Err(Interrupt::Continue) => Err(ConstEvalError::TypeError),
+    }
+}
+
+/// Control flow leaving the expression being evaluated.
+enum Interrupt {
+    Break(Option<ComputedExpr>),
+    Continue,
+    Return(ComputedExpr),
+    Error(ConstEvalError),
+}
+
+impl From<ConstEvalError> for Interrupt {
+    fn from(err: ConstEvalError) -> Interrupt {
+        Interrupt::Error(err)
+    }
+}
+
+struct ConstEvalCtx<'a> {
+    db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    locals: FxHashMap<PatId, ComputedExpr>,
+    depth: usize,
+    steps: &'a mut usize,
+}
+
+impl ConstEvalCtx<'_> {
+    fn eval_expr(&mut self, expr: ExprId) -> Result<ComputedExpr, Interrupt> {
+        *self.steps += 1;
+        if *self.steps > MAX_STEPS {
+            return Err(ConstEvalError::LimitReached.into());
+        }
+
+        let body = self.body;
+        match &body[expr] {
+            Expr::Missing => Err(ConstEvalError::IncompleteExpr.into()),
+            Expr::Literal(lit) => match lit {
+                Literal::Int(it, _) => Ok(self.int(expr, Some(*it), "compute")?),
+                Literal::Uint(it, _) => Ok(self.int(expr, i128::try_from(*it).ok(), "compute")?),
+                Literal::Float(..) => Err(ConstEvalError::NotSupported("floats").into()),
+                _ => Ok(ComputedExpr::Literal(lit.clone())),
+            },
+            Expr::Path(path) => {
+                let resolver = resolver_for_expr(self.db.upcast(), self.owner, expr);
+                match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path()) {
+                    Some(ValueNs::LocalBinding(pat)) => match self.locals.get(&pat) {
+                        Some(it) => Ok(it.clone()),
+                        None => Err(ConstEvalError::TypeError.into()),
+                    },
+                    Some(ValueNs::ConstId(konst)) => Ok(eval_body(
+                        self.db,
+                        konst.into(),
+                        Vec::new(),
+                        self.depth + 1,
+                        self.steps,
+                    )?),
+                    Some(_) => {
+                        Err(ConstEvalError::NotSupported("paths to non-constant items").into())
+                    }
+                    None => Err(ConstEvalError::TypeError.into()),
+                }
+            }
+            Expr::Block { statements, tail, label, .. } => {
+                if label.is_some() {
+                    return Err(ConstEvalError::NotSupported("labeled blocks").into());
+                }
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer, .. } => {
+                            if let Some(initializer) = initializer {
+                                let value = self.eval_expr(*initializer)?;
+                                self.bind(*pat, value)?;
+                            }
+                        }
+                        Statement::Expr { expr, .. } => {
+                            self.eval_expr(*expr)?;
+                        }
+                    }
+                }
+                match tail {
+                    Some(tail) => self.eval_expr(*tail),
+                    None => Ok(ComputedExpr::unit()),
+                }
+            }
+            Expr::Unsafe { body } => self.eval_expr(*body),
+            Expr::If { condition, then_branch, else_branch } => {
+                if self.eval_bool(*condition)? {
+                    self.eval_expr(*then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.eval_expr(*else_branch)
+                } else {
+                    Ok(ComputedExpr::unit())
+                }
+            }
+            Expr::Loop { body, label } => {
+                if label.is_some() {
+                    return Err(ConstEvalError::NotSupported("labeled loops").into());
+                }
+                loop {
+                    match self.eval_expr(*body) {
+                        Ok(_) | Err(Interrupt::Continue) => (),
+                        Err(Interrupt::Break(value)) => {
+                            return Ok(value.unwrap_or_else(ComputedExpr::unit))
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+            Expr::While { condition, body, label } => {
+                if label.is_some() {
+                    return Err(ConstEvalError::NotSupported("labeled loops").into());
+                }
+                while self.eval_bool(*condition)? {
+                    match self.eval_expr(*body) {
+                        Ok(_) | Err(Interrupt::Continue) => (),
+                        Err(Interrupt::Break(None)) => break,
+                        Err(Interrupt::Break(Some(_))) => {
+                            return Err(ConstEvalError::TypeError.into())
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                Ok(ComputedExpr::unit())
+            }
+            Expr::Break { expr, label } => {
+                if label.is_some() {
+                    return Err(ConstEvalError::NotSupported("labeled loops").into());
+                }
+                let value = match expr {
+                    Some(expr) => Some(self.eval_expr(*expr)?),
+                    None => None,
+                };
+                Err(Interrupt::Break(value))
+            }
+            Expr::Continue { label } => {
+                if label.is_some() {
+                    return Err(ConstEvalError::NotSupported("labeled loops").into());
+                }
+                Err(Interrupt::Continue)
+            }
+            Expr::Return { expr } => {
+                let value = match expr {
+                    Some(expr) => self.eval_expr(*expr)?,
+                    None => ComputedExpr::unit(),
+                };
+                Err(Interrupt::Return(value))
+            }
+            Expr::Tuple { exprs } => {
+                let fields =
+                    exprs.iter().map(|&it| self.eval_expr(it)).collect::<Result<Vec<_>, _>>()?;
+                Ok(ComputedExpr::Tuple(fields.into_boxed_slice()))
+            }
+            Expr::Call { callee, args } => {
+                let func = match self.infer[*callee].as_fn_def(self.db) {
+                    Some(it) => it,
+                    None => {
+                        return Err(ConstEvalError::NotSupported("calls of non-functions").into())
+                    }
+                };
+                let args = args.iter().map(|&it| self.eval_expr(it)).collect::<Result<_, _>>()?;
+                Ok(eval_body(self.db, func.into(), args, self.depth + 1, self.steps)?)
+            }
+            Expr::UnaryOp { expr: operand, op } => {
+                let value = self.eval_expr(*operand)?;
+                match (op, value) {
+                    (UnaryOp::Not, ComputedExpr::Literal(Literal::Bool(it))) => {
+                        Ok(ComputedExpr::Literal(Literal::Bool(!it)))
+                    }
+                    (UnaryOp::Not, ComputedExpr::Literal(Literal::Int(it, _))) => {
+                        let (bits, signed) = self.int_ty(expr)?;
+                        Ok(ComputedExpr::Literal(Literal::Int(wrap(!it, bits, signed), None)))
+                    }
+                    (UnaryOp::Neg, ComputedExpr::Literal(Literal::Int(it, _))) => {
+                        Ok(self.int(expr, it.checked_neg(), "negate")?)
+                    }
+                    (UnaryOp::Deref, _) => Err(ConstEvalError::NotSupported("dereferences").into()),
+                    _ => Err(ConstEvalError::TypeError.into()),
+                }
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                let op = match op {
+                    Some(it) => *it,
+                    None => return Err(ConstEvalError::IncompleteExpr.into()),
+                };
+                match op {
+                    BinaryOp::LogicOp(op) => {
+                        let lhs = self.eval_bool(*lhs)?;
+                        let value = match op {
+                            LogicOp::And => lhs && self.eval_bool(*rhs)?,
+                            LogicOp::Or => lhs || self.eval_bool(*rhs)?,
+                        };
+                        Ok(ComputedExpr::Literal(Literal::Bool(value)))
+                    }
+                    BinaryOp::ArithOp(op) => {
+                        let lhs_value = self.eval_expr(*lhs)?;
+                        let rhs_value = self.eval_expr(*rhs)?;
+                        Ok(self.arith(expr, op, lhs_value, rhs_value)?)
+                    }
+                    BinaryOp::CmpOp(op) => {
+                        let lhs = self.eval_expr(*lhs)?;
+                        let rhs = self.eval_expr(*rhs)?;
+                        let value = match op {
+                            CmpOp::Eq { negated } => (lhs == rhs) != negated,
+                            CmpOp::Ord { ordering, strict } => {
+                                let (lhs, rhs) = match (ordering_key(&lhs), ordering_key(&rhs)) {
+                                    (Some(lhs), Some(rhs)) => (lhs, rhs),
+                                    _ => return Err(ConstEvalError::TypeError.into()),
+                                };
+                                match (ordering, strict) {
+                                    (Ordering::Less, true) => lhs < rhs,
+                                    (Ordering::Less, false) => lhs <= rhs,
+                                    (Ordering::Greater, true) => lhs > rhs,
+                                    (Ordering::Greater, false) => lhs >= rhs,
+                                }
+                            }
+                        };
+                        Ok(ComputedExpr::Literal(Literal::Bool(value)))
+                    }
+                    BinaryOp::Assignment { op } => {
+                        let local = match &body[*lhs] {
+                            Expr::Path(path) => {
+                                let resolver =
+                                    resolver_for_expr(self.db.upcast(), self.owner, *lhs);
+                                resolver.resolve_path_in_value_ns_fully(
+                                    self.db.upcast(),
+                                    path.mod_path(),
+                                )
+                            }
+                            _ => None,
+                        };
+                        let local = match local {
+                            Some(ValueNs::LocalBinding(pat)) => pat,
+                            _ => {
+                                return Err(ConstEvalError::NotSupported(
+                                    "assignments to non-variables",
+                                )
+                                .into())
+                            }
+                        };
+                        let mut value = self.eval_expr(*rhs)?;
+                        if let Some(op) = op {
+                            let current = match self.locals.get(&local) {
+                                Some(it) => it.clone(),
+                                None => return Err(ConstEvalError::TypeError.into()),
+                            };
+                            value = self.arith(*lhs, op, current, value)?;
+                        }
+                        self.locals.insert(local, value);
+                        Ok(ComputedExpr::unit())
+                    }
+                }
+            }
+            Expr::Cast { expr: operand, .. } => {
+                let value = match self.eval_expr(*operand)? {
+                    ComputedExpr::Literal(Literal::Int(it, _)) => it,
+                    ComputedExpr::Literal(Literal::Bool(it)) => it as i128,
+                    ComputedExpr::Literal(Literal::Char(it)) => it as i128,
+                    _ => return Err(ConstEvalError::NotSupported("non-integer casts").into()),
+                };
+                let (bits, signed) = self.int_ty(expr)?;
+                Ok(ComputedExpr::Literal(Literal::Int(wrap(value, bits, signed), None)))
+            }
+            _ => Err(ConstEvalError::NotSupported("such expressions").into()),
+        }
+    }
+
+    fn eval_bool(&mut self, expr: ExprId) -> Result<bool, Interrupt> {
+        match self.eval_expr(expr)?.as_bool() {
+            Some(it) => Ok(it),
+            None => Err(ConstEvalError::TypeError.into()),
+        }
+    }
+
+    fn bind(&mut self, pat: PatId, value: ComputedExpr) -> Result<(), ConstEvalError> {
+        let body = self.body;
+        match &body[pat] {
+            Pat::Bind { subpat: None, .. } => {
+                self.locals.insert(pat, value);
+            }
+            Pat::Wild => (),
+            Pat::Tuple { args, ellipsis: None } => {
+                let fields = match value {
+                    ComputedExpr::Tuple(it) if it.len() == args.len() => it,
+                    _ => return Err(ConstEvalError::TypeError),
+                };
+                for (&pat, field) in args.iter().zip(fields.into_vec()) {
+                    self.bind(pat, field)?;
+                }
+            }
+            _ => return Err(ConstEvalError::NotSupported("such patterns")),
+        }
+        Ok(())
+    }
+
+    fn arith(
+        &self,
+        expr: ExprId,
+        op: ArithOp,
+        lhs: ComputedExpr,
+        rhs: ComputedExpr,
+    ) -> Result<ComputedExpr, ConstEvalError> {
+        if let (Some(lhs), Some(rhs)) = (lhs.as_bool(), rhs.as_bool()) {
+            let value = match op {
+                ArithOp::BitAnd => lhs & rhs,
+                ArithOp::BitOr => lhs | rhs,
+                ArithOp::BitXor => lhs ^ rhs,
+                _ => return Err(ConstEvalError::TypeError),
+            };
+            return Ok(ComputedExpr::Literal(Literal::Bool(value)));
+        }
+        let (lhs, rhs) = match (lhs.as_int(), rhs.as_int()) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return Err(ConstEvalError::TypeError),
+        };
+        match op {
+            ArithOp::Add => self.int(expr, lhs.checked_add(rhs), "add"),
+            ArithOp::Sub => self.int(expr, lhs.checked_sub(rhs), "subtract"),
+            ArithOp::Mul => self.int(expr, lhs.checked_mul(rhs), "multiply"),
+            ArithOp::Div | ArithOp::Rem if rhs == 0 => {
+                Err(ConstEvalError::Panic("attempt to divide by zero".to_string()))
+            }
+            ArithOp::Div => self.int(expr, lhs.checked_div(rhs), "divide"),
+            ArithOp::Rem => self.int(expr, lhs.checked_rem(rhs), "calculate the remainder"),
+            ArithOp::Shl | ArithOp::Shr => {
+                let (bits, signed) = self.int_ty(expr)?;
+                let shift = match u32::try_from(rhs) {
+                    Ok(it) if it < bits => it,
+                    _ => {
+                        return Err(ConstEvalError::Panic(
+                            "attempt to shift with overflow".to_string(),
+                        ))
+                    }
+                };
+                let value = if op == ArithOp::Shl { lhs << shift } else { lhs >> shift };
+                Ok(ComputedExpr::Literal(Literal::Int(wrap(value, bits, signed), None)))
+            }
+            ArithOp::BitAnd => self.int(expr, Some(lhs & rhs), "compute"),
+            ArithOp::BitOr => self.int(expr, Some(lhs | rhs), "compute"),
+            ArithOp::BitXor => self.int(expr, Some(lhs ^ rhs), "compute"),
+        }
+    }
+
+    /// Checks that `value` fits into the integer type of `expr`.
+    fn int(
+        &self,
+        expr: ExprId,
+        value: Option<i128>,
+        op: &str,
+    ) -> Result<ComputedExpr, ConstEvalError> {
+        let (bits, signed) = self.int_ty(expr)?;
+        let (min, max) = int_bounds(bits, signed);
+        match value {
+            Some(it) if min <= it && it <= max => Ok(ComputedExpr::Literal(Literal::Int(it, None))),
+            _ => Err(ConstEvalError::Panic(format!("attempt to {} with overflow", op))),
+        }
+    }
+
+    /// Returns the bit width and signedness of the integer type of `expr`.
+    fn int_ty(&self, expr: ExprId) -> Result<(u32, bool), ConstEvalError> {
+        int_ty(&self.infer[expr]).ok_or(ConstEvalError::TypeError)
+    }
+}
+
+fn int_bounds(bits: u32, signed: bool) -> (i128, i128) {
+    match (bits, signed) {
+        (128, true) => (i128::MIN, i128::MAX),
+        // FIXME: values of `u128` beyond `i128::MAX` are not supported.
+        (128, false) => (0, i128::MAX),
+        (bits, true) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        (bits, false) => (0, (1 << bits) - 1),
+    }
+}
+
+fn int_ty(ty: &Ty) -> Option<(u32, bool)> {
+    // FIXME: this assumes a 64-bit target.
+    let res = match ty.kind(&Interner) {
+        TyKind::Scalar(Scalar::Int(int)) => match int {
+            IntTy::I8 => (8, true),
+            IntTy::I16 => (16, true),
+            IntTy::I32 => (32, true),
+            IntTy::I64 | IntTy::Isize => (64, true),
+            IntTy::I128 => (128, true),
+        },
+        TyKind::Scalar(Scalar::Uint(uint)) => match uint {
+            UintTy::U8 => (8, false),
+            UintTy::U16 => (16, false),
+            UintTy::U32 => (32, false),
+            UintTy::U64 | UintTy::Usize => (64, false),
+            UintTy::U128 => (128, false),
+        },
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// Truncates `value` to an integer type, like `as` casts do.
+fn wrap(value: i128, bits: u32, signed: bool) -> i128 {
+    if bits == 128 {
+        return value;
+    }
+    let value = value & ((1 << bits) - 1);
+    if signed && value >> (bits - 1) == 1 {
+        value - (1 << bits)
+    } else {
+        value
+    }
+}
+
+fn ordering_key(value: &ComputedExpr) -> Option<i128> {
+    match value {
+        ComputedExpr::Literal(Literal::Int(it, _)) => Some(*it),
+        ComputedExpr::Literal(Literal::Bool(it)) => Some(*it as i128),
+        ComputedExpr::Literal(Literal::Char(it)) => Some(*it as i128),
+        _ => None,
+    }
+}
@@ -14,6 +14,7 @@ use syntax::{ast, AstPtr};
 use crate::db::HirDatabase;
 
 pub use crate::diagnostics::{
+    decl_check::case_conv::to_camel_case,
     expr::{
         record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
     },
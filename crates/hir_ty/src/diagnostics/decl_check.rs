@@ -10,7 +10,7 @@
 //! - static items (e.g. `static FOO: u8 = 10;`)
 //! - match arm bindings (e.g. `foo @ Some(_)`)
 
-mod case_conv;
+pub(super) mod case_conv;
 
 use base_db::CrateId;
 use hir_def::{
@@ -6,7 +6,7 @@
 
 /// Converts an identifier to an UpperCamelCase form.
 /// Returns `None` if the string is already in UpperCamelCase.
-pub(crate) fn to_camel_case(ident: &str) -> Option<String> {
+pub fn to_camel_case(ident: &str) -> Option<String> {
     if is_camel_case(ident) {
         return None;
     }
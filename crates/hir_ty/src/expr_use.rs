@@ -0,0 +1,243 @@
+//! Classifies how the places of a body, like local variables and their
+//! fields, are used: whether their value is moved or copied out, or whether
+//! they are borrowed.
+//!
+//! This is an approximation of what borrow checking computes. Autoref of
+//! method receivers is taken into account, but not the binding modes of
+//! patterns, so the scrutinees of `match` and of destructuring `let`s aren't
+//! classified. Neither are the values of unknown type.
+
+use std::sync::Arc;
+
+use hir_def::{
+    body::Body,
+    expr::{BinaryOp, BindingAnnotation, Expr, ExprId, Pat, Statement, UnaryOp},
+    lang_item::LangItemTarget,
+    type_ref::{Mutability, TypeRef},
+    DefWithBodyId, HasModule,
+};
+use rustc_hash::FxHashMap;
+use syntax::SmolStr;
+
+use crate::{
+    db::HirDatabase, method_resolution::implements_trait, Canonical, CanonicalVarKinds,
+    InferenceResult, Interner, TraitEnvironment, Ty, TyExt,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprUse {
+    /// The value is copied out of the place.
+    Copy,
+    /// The value is moved out of the place.
+    Move,
+    /// The place is borrowed immutably, explicitly or by a method call.
+    Borrow,
+    /// The place is borrowed mutably, explicitly or by a method call, or it
+    /// is assigned to.
+    MutableBorrow,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExprUses {
+    uses: FxHashMap<ExprId, ExprUse>,
+}
+
+impl ExprUses {
+    /// Returns how the place expression `expr` is used, if it is a classified
+    /// place expression.
+    pub fn get(&self, expr: ExprId) -> Option<ExprUse> {
+        self.uses.get(&expr).copied()
+    }
+}
+
+pub(crate) fn expr_uses_query(db: &dyn HirDatabase, def: DefWithBodyId) -> Arc<ExprUses> {
+    let _p = profile::span("expr_uses_query");
+    let body = db.body(def);
+    let infer = db.infer(def);
+    let krate = def.module(db.upcast()).krate();
+    let env = def
+        .as_generic_def_id()
+        .map_or_else(|| Arc::new(TraitEnvironment::empty(krate)), |it| db.trait_environment(it));
+    let copy_trait = match db.lang_item(krate, SmolStr::new("copy")) {
+        Some(LangItemTarget::TraitId(it)) => Some(it),
+        _ => None,
+    };
+
+    let is_copy = |ty: &Ty| {
+        let ty = Canonical { value: ty.clone(), binders: CanonicalVarKinds::empty(&Interner) };
+        copy_trait.map_or(false, |it| implements_trait(&ty, db, env.clone(), krate, it))
+    };
+
+    let mut collector = UseCollector {
+        db,
+        body: &body,
+        infer: &infer,
+        is_copy: &is_copy,
+        res: ExprUses::default(),
+    };
+    collector.walk(body.body_expr, Context::Value);
+    Arc::new(collector.res)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Context {
+    /// The value of the expression is used, which moves it unless its type is
+    /// `Copy`.
+    Value,
+    /// The place is used in the given way.
+    Place(ExprUse),
+    /// The use isn't classified.
+    Unknown,
+}
+
+struct UseCollector<'a> {
+    db: &'a dyn HirDatabase,
+    body: &'a Body,
+    infer: &'a InferenceResult,
+    is_copy: &'a dyn Fn(&Ty) -> bool,
+    res: ExprUses,
+}
+
+impl UseCollector<'_> {
+    fn walk(&mut self, expr: ExprId, cx: Context) {
+        let body = self.body;
+        match &body[expr] {
+            Expr::Path(_) => self.record(expr, cx),
+            Expr::Field { expr: base, .. } | Expr::UnaryOp { expr: base, op: UnaryOp::Deref } => {
+                self.record(expr, cx);
+                let base_cx = self.projection_context(expr, cx);
+                self.walk(*base, base_cx);
+            }
+            Expr::Index { base, index } => {
+                self.record(expr, cx);
+                let base_cx = self.projection_context(expr, cx);
+                self.walk(*base, base_cx);
+                self.walk(*index, Context::Value);
+            }
+            Expr::Ref { expr: inner, mutability, .. } => {
+                let use_ = match mutability {
+                    Mutability::Shared => ExprUse::Borrow,
+                    Mutability::Mut => ExprUse::MutableBorrow,
+                };
+                self.walk(*inner, Context::Place(use_));
+            }
+            Expr::BinaryOp { lhs, rhs, op } => {
+                let lhs_cx = match op {
+                    Some(BinaryOp::Assignment { .. }) => Context::Place(ExprUse::MutableBorrow),
+                    // Comparison operators take their operands by reference.
+                    Some(BinaryOp::CmpOp(_)) => Context::Place(ExprUse::Borrow),
+                    _ => Context::Value,
+                };
+                let rhs_cx = match op {
+                    Some(BinaryOp::CmpOp(_)) => Context::Place(ExprUse::Borrow),
+                    _ => Context::Value,
+                };
+                self.walk(*lhs, lhs_cx);
+                self.walk(*rhs, rhs_cx);
+            }
+            Expr::MethodCall { receiver, args, .. } => {
+                let receiver_cx = match self.infer.method_resolution(expr) {
+                    Some((func, _)) => {
+                        let data = self.db.function_data(func);
+                        match data.params.first().map(|it| &**it) {
+                            Some(TypeRef::Reference(_, _, Mutability::Shared))
+                                if data.has_self_param() =>
+                            {
+                                Context::Place(ExprUse::Borrow)
+                            }
+                            Some(TypeRef::Reference(_, _, Mutability::Mut))
+                                if data.has_self_param() =>
+                            {
+                                Context::Place(ExprUse::MutableBorrow)
+                            }
+                            _ => Context::Value,
+                        }
+                    }
+                    None => Context::Unknown,
+                };
+                self.walk(*receiver, receiver_cx);
+                for arg in args {
+                    self.walk(*arg, Context::Value);
+                }
+            }
+            Expr::Call { callee, args } => {
+                // Whether the callee is moved depends on the `Fn*` trait used.
+                self.walk(*callee, Context::Unknown);
+                for arg in args {
+                    self.walk(*arg, Context::Value);
+                }
+            }
+            Expr::Match { expr: scrutinee, arms } => {
+                self.walk(*scrutinee, Context::Unknown);
+                for arm in arms {
+                    if let Some(guard) = arm.guard {
+                        self.walk(guard, Context::Value);
+                    }
+                    self.walk(arm.expr, Context::Value);
+                }
+            }
+            Expr::Block { statements, tail, .. } => {
+                for stmt in statements {
+                    match stmt {
+                        Statement::Let { pat, initializer: Some(initializer), .. } => {
+                            let cx = if is_by_value_binding(&body[*pat]) {
+                                Context::Value
+                            } else {
+                                Context::Unknown
+                            };
+                            self.walk(*initializer, cx);
+                        }
+                        Statement::Let { initializer: None, .. } => (),
+                        Statement::Expr { expr, .. } => self.walk(*expr, Context::Value),
+                    }
+                }
+                if let Some(tail) = tail {
+                    self.walk(*tail, Context::Value);
+                }
+            }
+            Expr::RecordLit { fields, spread, .. } => {
+                for field in fields {
+                    self.walk(field.expr, Context::Value);
+                }
+                // Only the fields which aren't specified are moved out of the base.
+                if let Some(spread) = spread {
+                    self.walk(*spread, Context::Unknown);
+                }
+            }
+            it => it.walk_child_exprs(|child| self.walk(child, Context::Value)),
+        }
+    }
+
+    /// Returns how the base of the field access, index or dereference `expr`
+    /// is used when `expr` is used in the context `cx`.
+    fn projection_context(&self, expr: ExprId, cx: Context) -> Context {
+        match cx {
+            Context::Value if self.infer[expr].is_unknown() => Context::Unknown,
+            Context::Value if (self.is_copy)(&self.infer[expr]) => Context::Place(ExprUse::Copy),
+            _ => cx,
+        }
+    }
+
+    fn record(&mut self, expr: ExprId, cx: Context) {
+        let use_ = match cx {
+            // Whether a value of unknown type is moved isn't known either.
+            Context::Value if self.infer[expr].is_unknown() => return,
+            Context::Value if (self.is_copy)(&self.infer[expr]) => ExprUse::Copy,
+            Context::Value => ExprUse::Move,
+            Context::Place(it) => it,
+            Context::Unknown => return,
+        };
+        self.res.uses.insert(expr, use_);
+    }
+}
+
+/// `let _ = place;` doesn't move the place, and patterns other than plain
+/// bindings may bind by reference or move only some fields.
+fn is_by_value_binding(pat: &Pat) -> bool {
+    match pat {
+        Pat::Bind { mode, subpat: None, .. } => {
+            matches!(mode, BindingAnnotation::Unannotated | BindingAnnotation::Mutable)
+        }
+        _ => false,
+    }
+}
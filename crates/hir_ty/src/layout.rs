@@ -0,0 +1,336 @@
+//! Computes the memory layout of types.
+//!
+//! This approximates the layout rustc computes for a 64-bit target. The fields
+//! of `repr(Rust)` types are ordered by decreasing alignment, and an enum whose
+//! variants but one have no fields stores its discriminant in the invalid values
+//! of that variant, like `Option<&T>`. Explicit discriminants are ignored.
+//!
+//! The layout of types which depend on generic parameters or on constants we
+//! can't evaluate is unknown.
+
+use std::cmp::Reverse;
+
+use hir_def::{
+    adt::ReprKind, type_ref::ConstScalar, AdtId, AttrDefId, EnumId, EnumVariantId, VariantId,
+};
+
+use crate::{
+    db::HirDatabase,
+    primitive::{int_ty_from_builtin, uint_ty_from_builtin, FloatTy, IntTy, UintTy},
+    Const, ConstValue, Interner, Scalar, Substitution, Ty, TyKind,
+};
+use rustc_hash::FxHashMap;
+
+const POINTER_SIZE: u64 = 8;
+/// How deep nested types are looked into, to bail out on infinitely sized types.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub size: u64,
+    pub align: u64,
+    /// The number of invalid values of the type, which enums containing it can
+    /// use to store their discriminant.
+    pub niches: u128,
+    /// The offsets of the fields of a struct, union or tuple, in declaration
+    /// order.
+    pub field_offsets: Vec<u64>,
+}
+
+impl Layout {
+    fn scalar(size: u64, niches: u128) -> Layout {
+        Layout { size, align: size, niches, field_offsets: Vec::new() }
+    }
+
+    fn zero_sized() -> Layout {
+        Layout { size: 0, align: 1, niches: 0, field_offsets: Vec::new() }
+    }
+}
+
+pub fn layout_of_ty(db: &dyn HirDatabase, ty: &Ty) -> Option<Layout> {
+    LayoutCx { db, cache: FxHashMap::default() }.layout_of(ty, 0)
+}
+
+/// Remembers the layouts computed so far, so that a type used in many places of
+/// a nested type is only laid out once.
+struct LayoutCx<'a> {
+    db: &'a dyn HirDatabase,
+    cache: FxHashMap<Ty, Option<Layout>>,
+}
+
+impl LayoutCx<'_> {
+    fn layout_of(&mut self, ty: &Ty, depth: usize) -> Option<Layout> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if let Some(layout) = self.cache.get(ty) {
+            return layout.clone();
+        }
+        let layout = self.compute_layout(ty, depth + 1);
+        self.cache.insert(ty.clone(), layout.clone());
+        layout
+    }
+
+    fn compute_layout(&mut self, ty: &Ty, depth: usize) -> Option<Layout> {
+        let db = self.db;
+        let layout = match ty.kind(&Interner) {
+            TyKind::Scalar(scalar) => match scalar {
+                Scalar::Bool => Layout::scalar(1, 254),
+                // Neither the values above `char::MAX` nor the surrogates are
+                // valid.
+                Scalar::Char => Layout::scalar(4, (1 << 32) - 0x11_0000 + 0x800),
+                Scalar::Int(it) => Layout::scalar(int_size(*it), 0),
+                Scalar::Uint(it) => Layout::scalar(uint_size(*it), 0),
+                Scalar::Float(FloatTy::F32) => Layout::scalar(4, 0),
+                Scalar::Float(FloatTy::F64) => Layout::scalar(8, 0),
+            },
+            TyKind::Never | TyKind::FnDef(..) => Layout::zero_sized(),
+            // References and function pointers can't be null.
+            TyKind::Ref(_, _, pointee) => pointer_layout(db, pointee, 1, depth)?,
+            TyKind::Raw(_, pointee) => pointer_layout(db, pointee, 0, depth)?,
+            TyKind::Function(_) => Layout::scalar(POINTER_SIZE, 1),
+            TyKind::Array(elem, len) => {
+                let len = array_len(len)?;
+                let elem = self.layout_of(elem, depth)?;
+                Layout {
+                    size: elem.size.checked_mul(len)?,
+                    align: elem.align,
+                    niches: if len == 0 { 0 } else { elem.niches },
+                    field_offsets: Vec::new(),
+                }
+            }
+            TyKind::Tuple(_, subst) => {
+                let fields = subst
+                    .iter(&Interner)
+                    .filter_map(|it| it.ty(&Interner))
+                    .map(|it| self.layout_of(it, depth))
+                    .collect::<Option<Vec<_>>>()?;
+                lay_out_fields(&fields, true, false, 0)?
+            }
+            TyKind::Adt(crate::AdtId(adt), subst) => self.adt_layout(*adt, subst, depth)?,
+            _ => return None,
+        };
+        Some(layout)
+    }
+
+    fn adt_layout(&mut self, adt: AdtId, subst: &Substitution, depth: usize) -> Option<Layout> {
+        let db = self.db;
+        match adt {
+            AdtId::StructId(id) => {
+                let repr = db.struct_data(id).repr.clone();
+                let fields = self.field_layouts(id.into(), subst, depth)?;
+                let mut layout = lay_out_fields(
+                    &fields,
+                    matches!(repr, None | Some(ReprKind::Other)),
+                    matches!(repr, Some(ReprKind::Packed)),
+                    0,
+                )?;
+                // Like `NonNull`, which is never null.
+                let non_null = db
+                    .attrs(AttrDefId::AdtId(adt))
+                    .by_key("rustc_layout_scalar_valid_range_start")
+                    .exists();
+                if non_null && layout.niches == 0 {
+                    layout.niches = 1;
+                }
+                Some(layout)
+            }
+            AdtId::UnionId(id) => {
+                let packed = matches!(db.union_data(id).repr, Some(ReprKind::Packed));
+                let fields = self.field_layouts(id.into(), subst, depth)?;
+                let align =
+                    if packed { 1 } else { fields.iter().map(|it| it.align).max().unwrap_or(1) };
+                let size = fields.iter().map(|it| it.size).max().unwrap_or(0);
+                Some(Layout {
+                    size: align_to(size, align)?,
+                    align,
+                    niches: 0,
+                    field_offsets: vec![0; fields.len()],
+                })
+            }
+            AdtId::EnumId(id) => self.enum_layout(id, subst, depth),
+        }
+    }
+
+    fn enum_layout(&mut self, id: EnumId, subst: &Substitution, depth: usize) -> Option<Layout> {
+        let data = self.db.enum_data(id);
+        let variants = data
+            .variants
+            .iter()
+            .map(|(local_id, _)| {
+                let variant = EnumVariantId { parent: id, local_id };
+                self.field_layouts(variant.into(), subst, depth)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let tag_size = match &data.repr {
+            Some(ReprKind::Int(it)) => Some(int_size(int_ty_from_builtin(*it))),
+            Some(ReprKind::Uint(it)) => Some(uint_size(uint_ty_from_builtin(*it))),
+            // C enums use an `int` as their tag.
+            Some(ReprKind::C) => Some(4),
+            _ => None,
+        };
+
+        let variant_count = variants.len() as u128;
+        match variants.len() {
+            0 => return Some(Layout::zero_sized()),
+            1 if tag_size.is_none() => {
+                let mut layout = lay_out_fields(&variants[0], true, false, 0)?;
+                layout.field_offsets.clear();
+                return Some(layout);
+            }
+            _ => {}
+        }
+
+        if tag_size.is_none() {
+            let structs = variants
+                .iter()
+                .map(|fields| lay_out_fields(fields, true, false, 0))
+                .collect::<Option<Vec<_>>>()?;
+            let mut dataful = structs.iter().filter(|it| it.size > 0);
+            if let (Some(dataful), None) = (dataful.next(), dataful.next()) {
+                if dataful.niches >= variant_count - 1 {
+                    let align = structs.iter().map(|it| it.align).max().unwrap_or(1);
+                    return Some(Layout {
+                        size: align_to(dataful.size, align)?,
+                        align,
+                        niches: dataful.niches - (variant_count - 1),
+                        field_offsets: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        let tag_size = tag_size.unwrap_or(if variant_count <= 1 << 8 {
+            1
+        } else if variant_count <= 1 << 16 {
+            2
+        } else {
+            4
+        });
+        // The tag is the first field of every variant.
+        let mut size = tag_size;
+        let mut align = tag_size;
+        for fields in &variants {
+            let variant = lay_out_fields(fields, data.repr.is_none(), false, tag_size)?;
+            size = size.max(variant.size);
+            align = align.max(variant.align);
+        }
+        let tag_values = if tag_size >= 16 { u128::MAX } else { (1 << (8 * tag_size)) - 1 };
+        Some(Layout {
+            size: align_to(size, align)?,
+            align,
+            // An explicit `repr` can be too small for all the variants.
+            niches: tag_values.checked_sub(variant_count - 1)?,
+            field_offsets: Vec::new(),
+        })
+    }
+
+    fn field_layouts(
+        &mut self,
+        variant: VariantId,
+        subst: &Substitution,
+        depth: usize,
+    ) -> Option<Vec<Layout>> {
+        self.db
+            .field_types(variant)
+            .iter()
+            .map(|(_, ty)| self.layout_of(&ty.clone().substitute(&Interner, subst), depth))
+            .collect()
+    }
+}
+
+/// Pointers to unsized types also store the length or the vtable of the
+/// pointee.
+fn pointer_layout(
+    db: &dyn HirDatabase,
+    pointee: &Ty,
+    niches: u128,
+    depth: usize,
+) -> Option<Layout> {
+    let size = if is_sized(db, pointee, depth)? { POINTER_SIZE } else { 2 * POINTER_SIZE };
+    Some(Layout { size, align: POINTER_SIZE, niches, field_offsets: Vec::new() })
+}
+
+fn is_sized(db: &dyn HirDatabase, ty: &Ty, depth: usize) -> Option<bool> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match ty.kind(&Interner) {
+        TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => Some(false),
+        // Only the last field of a struct can be unsized.
+        TyKind::Adt(crate::AdtId(AdtId::StructId(id)), subst) => {
+            let field_types = db.field_types((*id).into());
+            match field_types.iter().last() {
+                Some((_, ty)) => is_sized(db, &ty.clone().substitute(&Interner, subst), depth + 1),
+                None => Some(true),
+            }
+        }
+        TyKind::Tuple(_, subst) => match subst.iter(&Interner).last() {
+            Some(it) => is_sized(db, it.ty(&Interner)?, depth + 1),
+            None => Some(true),
+        },
+        _ => Some(true),
+    }
+}
+
+fn array_len(len: &Const) -> Option<u64> {
+    match &len.data(&Interner).value {
+        ConstValue::Concrete(it) => match it.interned {
+            ConstScalar::Usize(it) => Some(it),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Places `fields` one after the other, starting at `start`, and reordering
+/// them by decreasing alignment if `reorder` is set. Zero-sized fields are
+/// placed first.
+fn lay_out_fields(fields: &[Layout], reorder: bool, packed: bool, start: u64) -> Option<Layout> {
+    let mut order = (0..fields.len()).collect::<Vec<_>>();
+    if reorder {
+        order.sort_by_key(|&idx| (fields[idx].size != 0, Reverse(fields[idx].align)));
+    }
+
+    let mut field_offsets = vec![0; fields.len()];
+    let mut size = start;
+    let mut align = 1;
+    for idx in order {
+        let field = &fields[idx];
+        let field_align = if packed { 1 } else { field.align };
+        let offset = align_to(size, field_align)?;
+        field_offsets[idx] = offset;
+        size = offset.checked_add(field.size)?;
+        align = align.max(field_align);
+    }
+    Some(Layout {
+        size: align_to(size, align)?,
+        align,
+        niches: fields.iter().map(|it| it.niches).max().unwrap_or(0),
+        field_offsets,
+    })
+}
+
+fn align_to(size: u64, align: u64) -> Option<u64> {
+    Some(size.checked_add(align - 1)? / align * align)
+}
+
+fn int_size(ty: IntTy) -> u64 {
+    match ty {
+        IntTy::I8 => 1,
+        IntTy::I16 => 2,
+        IntTy::I32 => 4,
+        IntTy::Isize | IntTy::I64 => 8,
+        IntTy::I128 => 16,
+    }
+}
+
+fn uint_size(ty: UintTy) -> u64 {
+    match ty {
+        UintTy::U8 => 1,
+        UintTy::U16 => 2,
+        UintTy::U32 => 4,
+        UintTy::Usize | UintTy::U64 => 8,
+        UintTy::U128 => 16,
+    }
+}
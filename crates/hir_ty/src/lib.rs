@@ -11,6 +11,8 @@ mod builder;
 mod chalk_db;
 mod chalk_ext;
 pub mod consteval;
+pub mod expr_use;
+pub mod layout;
 mod infer;
 mod interner;
 mod lower;
@@ -61,7 +63,7 @@ pub use mapping::{
     to_foreign_def_id, to_placeholder_idx,
 };
 pub use traits::TraitEnvironment;
-pub use utils::all_super_traits;
+pub use utils::{all_super_traits, direct_super_traits};
 pub use walk::TypeWalk;
 
 pub use chalk_ir::{
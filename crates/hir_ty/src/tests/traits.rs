@@ -39,6 +39,44 @@ fn test() {
     );
 }
 
+#[test]
+fn infer_ready_and_into_future() {
+    check_types(
+        r#"
+//- minicore: into_future
+use core::future::{ready, IntoFuture};
+
+async fn test() {
+    let r = ready(92u8).await;
+    r;
+  //^ u8
+    let f = ready(()).into_future();
+    f;
+  //^ Ready<()>
+}
+"#,
+    );
+}
+
+#[test]
+fn infer_async_closure() {
+    check_types(
+        r#"
+//- minicore: future
+async fn test() {
+    let c = async |x: u64| x;
+    let r = c(1).await;
+    r;
+  //^ u64
+    let c = async move || 92u8;
+    let r = c().await;
+    r;
+  //^ u8
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_desugar_async() {
     check_types(
@@ -1419,6 +1457,70 @@ fn api_walkthrough() {
     );
 }
 
+#[test]
+fn iterator_adapter_chain() {
+    check_types(
+        r#"
+//- minicore: iterators
+use core::iter::FromIterator;
+
+struct Wrapper(u32);
+struct Collection;
+impl FromIterator<Wrapper> for Collection {
+    fn from_iter<T: IntoIterator<Item = Wrapper>>(iter: T) -> Self {
+        Collection
+    }
+}
+
+fn test(it: impl Iterator<Item = u32>) {
+    let first = it.take(1).map(|x| Wrapper(x)).next();
+    first;
+  //^^^^^ Option<Wrapper>
+    let collection: Collection = it.map(|x| Wrapper(x)).filter(|w| { w; true }).collect();
+                                                                   //^ &Wrapper
+}
+"#,
+    );
+}
+
+#[test]
+fn error_trait_source() {
+    check_types(
+        r#"
+//- minicore: error
+use std::error::Error;
+
+struct MyError;
+impl Error for MyError {}
+
+fn test(e: MyError) {
+    let source = e.source();
+    source;
+  //^^^^^^ Option<&dyn Error>
+}
+"#,
+    );
+}
+
+#[test]
+fn write_macro_in_display_impl() {
+    check_types(
+        r#"
+//- minicore: fmt
+use core::fmt;
+
+struct S;
+impl fmt::Display for S {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let res = write!(f, "{}", 92);
+        res
+      //^^^ Result<(), Error>
+    }
+}
+"#,
+    );
+}
+
 #[test]
 fn projection_eq_within_chalk() {
     check_infer(
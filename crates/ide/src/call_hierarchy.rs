@@ -2,8 +2,14 @@
 
 use indexmap::IndexMap;
 
-use hir::Semantics;
-use ide_db::{call_info::FnCallNode, RootDatabase};
+use hir::{AsAssocItem, PathResolution, Semantics};
+use ide_db::{
+    base_db::FileId,
+    call_info::FnCallNode,
+    defs::Definition,
+    search::{FileReference, ReferenceCategory},
+    RootDatabase,
+};
 use syntax::{ast, AstNode, TextRange};
 
 use crate::{
@@ -14,6 +20,10 @@ use crate::{
 pub struct CallItem {
     pub target: NavigationTarget,
     pub ranges: Vec<TextRange>,
+    /// Whether the calls might not reach the function, like the calls through
+    /// trait objects or generic bounds and the uses of the function as a
+    /// function pointer.
+    pub potential: bool,
 }
 
 impl CallItem {
@@ -25,7 +35,8 @@ impl CallItem {
 
     #[cfg(test)]
     pub(crate) fn debug_render(&self) -> String {
-        format!("{} : {:?}", self.target.debug_render(), self.ranges)
+        let potential = if self.potential { " potential" } else { "" };
+        format!("{} : {:?}{}", self.target.debug_render(), self.ranges, potential)
     }
 }
 
@@ -36,7 +47,11 @@ pub(crate) fn call_hierarchy(
     goto_definition::goto_definition(db, position)
 }
 
-pub(crate) fn incoming_calls(db: &RootDatabase, position: FilePosition) -> Option<Vec<CallItem>> {
+pub(crate) fn incoming_calls(
+    db: &RootDatabase,
+    position: FilePosition,
+    potential_calls: bool,
+) -> Option<Vec<CallItem>> {
     let sema = Semantics::new(db);
 
     // 1. Find all refs
@@ -47,26 +62,91 @@ pub(crate) fn incoming_calls(db: &RootDatabase, position: FilePosition) -> Optio
     let mut calls = CallLocations::default();
 
     for (file_id, references) in refs.references {
-        let file = sema.parse(file_id);
-        let file = file.syntax();
-        for (relative_range, token) in references
-            .into_iter()
-            .filter_map(|(range, _)| Some(range).zip(file.token_at_offset(range.start()).next()))
+        for (range, category) in references {
+            let potential = match category {
+                Some(ReferenceCategory::Call) => false,
+                Some(ReferenceCategory::Import) => continue,
+                // The function is used as a function pointer.
+                _ => true,
+            };
+            calls.add_caller(&sema, file_id, range, potential);
+        }
+    }
+
+    if potential_calls {
+        let file = sema.parse(position.file_id);
+        if let Some(Definition::ModuleDef(hir::ModuleDef::Function(func))) =
+            references::find_def(&sema, file.syntax(), position.offset)
         {
-            let token = sema.descend_into_macros(token);
-            // This target is the containing function
-            if let Some(nav) = token.ancestors().find_map(|node| {
-                let def = ast::Fn::cast(node).and_then(|fn_| sema.to_def(&fn_))?;
-                def.try_to_nav(sema.db)
-            }) {
-                calls.add(&nav, relative_range);
-            }
+            add_trait_method_calls(&sema, func, &mut calls);
         }
     }
 
     Some(calls.into_items())
 }
 
+/// Adds the calls of the trait method implemented by `func`: the ones on
+/// trait objects or generic types bounded by the trait as potential calls,
+/// the ones on the implementing type as actual calls.
+fn add_trait_method_calls(
+    sema: &Semantics<RootDatabase>,
+    func: hir::Function,
+    calls: &mut CallLocations,
+) -> Option<()> {
+    let db = sema.db;
+    let impl_ = match func.as_assoc_item(db)?.container(db) {
+        hir::AssocItemContainer::Impl(it) => it,
+        hir::AssocItemContainer::Trait(_) => return None,
+    };
+    let name = func.name(db);
+    let trait_func = impl_.trait_(db)?.items(db).into_iter().find_map(|item| match item {
+        hir::AssocItem::Function(it) if it.name(db) == name => Some(it),
+        _ => None,
+    })?;
+    let self_ty = impl_.self_ty(db);
+
+    let usages = Definition::ModuleDef(trait_func.into()).usages(sema).all();
+    for (file_id, references) in usages {
+        for FileReference { range, name, category } in references {
+            if category != Some(ReferenceCategory::Call) {
+                continue;
+            }
+            let ty = match name.as_name_ref().and_then(|it| receiver_ty(sema, it)) {
+                Some(it) => it.strip_references(),
+                None => continue,
+            };
+            let potential = if ty.as_dyn_trait().is_some() || ty.is_type_param() {
+                true
+            } else if ty.could_unify_with(db, &self_ty) {
+                false
+            } else {
+                continue;
+            };
+            calls.add_caller(sema, file_id, range, potential);
+        }
+    }
+    Some(())
+}
+
+/// The type a method is called on, either as `receiver.method()` or as
+/// `Type::method()`.
+fn receiver_ty(sema: &Semantics<RootDatabase>, name_ref: &ast::NameRef) -> Option<hir::Type> {
+    if let Some(method_call) = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast) {
+        return sema.type_of_expr(&method_call.receiver()?);
+    }
+    let path = name_ref.syntax().ancestors().find_map(ast::Path::cast)?;
+    match sema.resolve_path(&path.qualifier()?)? {
+        PathResolution::Def(hir::ModuleDef::Trait(_)) => {
+            let call = path.syntax().parent()?.parent().and_then(ast::CallExpr::cast)?;
+            sema.type_of_expr(&call.arg_list()?.args().next()?)
+        }
+        PathResolution::Def(hir::ModuleDef::Adt(adt)) => Some(adt.ty(sema.db)),
+        PathResolution::TypeParam(it) => Some(it.ty(sema.db)),
+        PathResolution::SelfType(impl_) => Some(impl_.self_ty(sema.db)),
+        _ => None,
+    }
+}
+
 pub(crate) fn outgoing_calls(db: &RootDatabase, position: FilePosition) -> Option<Vec<CallItem>> {
     let sema = Semantics::new(db);
     let file_id = position.file_id;
@@ -99,23 +179,48 @@ pub(crate) fn outgoing_calls(db: &RootDatabase, position: FilePosition) -> Optio
             }?;
             Some((func_target, name_ref.syntax().text_range()))
         })
-        .for_each(|(nav, range)| calls.add(&nav, range));
+        .for_each(|(nav, range)| calls.add(&nav, range, false));
 
     Some(calls.into_items())
 }
 
 #[derive(Default)]
 struct CallLocations {
-    funcs: IndexMap<NavigationTarget, Vec<TextRange>>,
+    funcs: IndexMap<(NavigationTarget, bool), Vec<TextRange>>,
 }
 
 impl CallLocations {
-    fn add(&mut self, target: &NavigationTarget, range: TextRange) {
-        self.funcs.entry(target.clone()).or_default().push(range);
+    fn add(&mut self, target: &NavigationTarget, range: TextRange, potential: bool) {
+        self.funcs.entry((target.clone(), potential)).or_default().push(range);
+    }
+
+    /// Adds the call at `range` to the function containing it.
+    fn add_caller(
+        &mut self,
+        sema: &Semantics<RootDatabase>,
+        file_id: FileId,
+        range: TextRange,
+        potential: bool,
+    ) {
+        let file = sema.parse(file_id);
+        let token = match file.syntax().token_at_offset(range.start()).next() {
+            Some(it) => sema.descend_into_macros(it),
+            None => return,
+        };
+        // This target is the containing function
+        if let Some(nav) = token.ancestors().find_map(|node| {
+            let def = ast::Fn::cast(node).and_then(|fn_| sema.to_def(&fn_))?;
+            def.try_to_nav(sema.db)
+        }) {
+            self.add(&nav, range, potential);
+        }
     }
 
     fn into_items(self) -> Vec<CallItem> {
-        self.funcs.into_iter().map(|(target, ranges)| CallItem { target, ranges }).collect()
+        self.funcs
+            .into_iter()
+            .map(|((target, potential), ranges)| CallItem { target, ranges, potential })
+            .collect()
     }
 }
 
@@ -140,7 +245,7 @@ mod tests {
 
         let item_pos =
             FilePosition { file_id: nav.file_id, offset: nav.focus_or_full_range().start() };
-        let incoming_calls = analysis.incoming_calls(item_pos).unwrap().unwrap();
+        let incoming_calls = analysis.incoming_calls(item_pos, false).unwrap().unwrap();
         assert_eq!(incoming_calls.len(), expected_incoming.len());
 
         for call in 0..incoming_calls.len() {
@@ -338,6 +443,57 @@ fn caller3() {
         );
     }
 
+    #[test]
+    fn test_call_hierarchy_fn_pointer() {
+        check_hierarchy(
+            r#"
+//- /lib.rs
+fn callee() {}
+fn caller() {
+    call$0ee();
+    let f = callee;
+}
+"#,
+            "callee Function FileId(0) 0..14 3..9",
+            &[
+                "caller Function FileId(0) 15..64 18..24 : [33..39]",
+                "caller Function FileId(0) 15..64 18..24 : [55..61] potential",
+            ],
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_call_hierarchy_potential_trait_method_calls() {
+        let (analysis, pos) = fixture::position(
+            r#"
+//- /lib.rs
+trait Tr { fn f(&self); }
+struct S;
+struct T;
+impl Tr for S { fn $0f(&self) {} }
+impl Tr for T { fn f(&self) {} }
+fn on_s(s: S) { s.f(); }
+fn on_t(t: T) { t.f(); }
+fn on_dyn(d: &dyn Tr) { d.f(); }
+fn on_generic<U: Tr>(u: U) { u.f(); }
+"#,
+        );
+        let incoming_calls = analysis.incoming_calls(pos, true).unwrap().unwrap();
+        let actual = incoming_calls.iter().map(|it| it.debug_render()).collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![
+                "on_s Function FileId(0) 112..136 115..119 : [130..131]",
+                "on_dyn Function FileId(0) 162..194 165..171 : [188..189] potential",
+                "on_generic Function FileId(0) 195..232 198..208 : [226..227] potential",
+            ]
+        );
+
+        let incoming_calls = analysis.incoming_calls(pos, false).unwrap().unwrap();
+        assert!(incoming_calls.iter().all(|it| !it.potential));
+    }
+
     #[test]
     fn test_call_hierarchy_issue_5103() {
         check_hierarchy(
@@ -580,4 +580,19 @@ struct Foo;
         let navs = analysis.symbol_search(Query::new("foo".to_string())).unwrap();
         assert_eq!(navs.len(), 2)
     }
+
+    #[test]
+    fn test_world_symbols_include_doc_aliases() {
+        let (analysis, _) = fixture::file(
+            r#"
+#[doc(alias = "Bar")]
+struct Foo;
+"#,
+        );
+
+        let navs = analysis.symbol_search(Query::new("Bar".to_string())).unwrap();
+        assert_eq!(navs.len(), 1);
+        assert_eq!(navs[0].name, "Bar");
+        assert_eq!(navs[0].description.as_deref(), Some("struct Foo"));
+    }
 }
@@ -1,20 +1,30 @@
-use std::iter;
-
 use hir::Semantics;
-use ide_db::{helpers::pick_best_token, RootDatabase};
-use syntax::{ast, ted, AstNode, NodeOrToken, SyntaxKind, SyntaxKind::*, SyntaxNode, WalkEvent, T};
+use ide_db::{
+    base_db::FileRange,
+    helpers::{insert_whitespaces, pick_best_token, TokenSpacing},
+    RootDatabase,
+};
+use syntax::{
+    ast, AstNode, NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken, TextRange, WalkEvent,
+};
 
 use crate::FilePosition;
 
 pub struct ExpandedMacro {
     pub name: String,
     pub expansion: String,
+    /// The ranges of `expansion` copied from the input of the macro call,
+    /// along with the ranges they were copied from.
+    pub origins: Vec<(TextRange, FileRange)>,
 }
 
 // Feature: Expand Macro Recursively
 //
 // Shows the full macro expansion of the macro at current cursor.
 //
+// The expansion can also be limited to a number of levels of nested macro
+// calls, a depth of 1 only expanding the macro at the cursor.
+//
 // |===
 // | Editor  | Action Name
 //
@@ -22,7 +32,11 @@ pub struct ExpandedMacro {
 // |===
 //
 // image::https://user-images.githubusercontent.com/48062697/113020648-b3973180-917a-11eb-84a9-ecb921293dc5.gif[]
-pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<ExpandedMacro> {
+pub(crate) fn expand_macro(
+    db: &RootDatabase,
+    position: FilePosition,
+    depth: Option<u32>,
+) -> Option<ExpandedMacro> {
     let sema = Semantics::new(db);
     let file = sema.parse(position.file_id);
 
@@ -37,7 +51,6 @@ pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<
             expanded = sema.expand_attr_macro(&item);
             if expanded.is_some() {
                 // FIXME: add the macro name
-                // FIXME: make this recursive too
                 name = Some("?".to_string());
                 break;
             }
@@ -45,110 +58,49 @@ pub(crate) fn expand_macro(db: &RootDatabase, position: FilePosition) -> Option<
 
         if let Some(mac) = ast::MacroCall::cast(node) {
             name = Some(mac.path()?.segment()?.name_ref()?.to_string());
-            expanded = expand_macro_recur(&sema, &mac);
+            expanded = sema.expand(&mac);
             break;
         }
     }
 
+    let mut tokens = Vec::new();
+    // The macro at the cursor is the first level.
+    collect_expansion_tokens(&sema, expanded?, depth.map(|it| it.saturating_sub(1)), &mut tokens);
+
     // FIXME:
     // macro expansion may lose all white space information
     // But we hope someday we can use ra_fmt for that
-    let expansion = insert_whitespaces(expanded?);
-    Some(ExpandedMacro { name: name?, expansion })
-}
-
-fn expand_macro_recur(
-    sema: &Semantics<RootDatabase>,
-    macro_call: &ast::MacroCall,
-) -> Option<SyntaxNode> {
-    let expanded = sema.expand(macro_call)?.clone_for_update();
-
-    let children = expanded.descendants().filter_map(ast::MacroCall::cast);
-    let mut replacements = Vec::new();
-
-    for child in children {
-        if let Some(new_node) = expand_macro_recur(sema, &child) {
-            // check if the whole original syntax is replaced
-            if expanded == *child.syntax() {
-                return Some(new_node);
-            }
-            replacements.push((child, new_node));
+    let mut origins = Vec::new();
+    let expansion = insert_whitespaces(tokens, 2, TokenSpacing::Compact, |token, range| {
+        if let Some(origin) = sema.original_token_range(token) {
+            origins.push((range, origin));
         }
-    }
-
-    replacements.into_iter().rev().for_each(|(old, new)| ted::replace(old.syntax(), new));
-    Some(expanded)
+    });
+    Some(ExpandedMacro { name: name?, expansion, origins })
 }
 
-// FIXME: It would also be cool to share logic here and in the mbe tests,
-// which are pretty unreadable at the moment.
-fn insert_whitespaces(syn: SyntaxNode) -> String {
-    let mut res = String::new();
-    let mut token_iter = syn
-        .preorder_with_tokens()
-        .filter_map(|event| {
-            if let WalkEvent::Enter(NodeOrToken::Token(token)) = event {
-                Some(token)
-            } else {
-                None
+/// Collects the tokens of `expanded`, replacing the macro calls in it by
+/// their expansions, up to `depth` levels deep.
+fn collect_expansion_tokens(
+    sema: &Semantics<RootDatabase>,
+    expanded: SyntaxNode,
+    depth: Option<u32>,
+    acc: &mut Vec<SyntaxToken>,
+) {
+    let mut preorder = expanded.preorder_with_tokens();
+    while let Some(event) = preorder.next() {
+        let node = match event {
+            WalkEvent::Enter(NodeOrToken::Token(token)) => {
+                acc.push(token);
+                continue;
             }
-        })
-        .peekable();
-
-    let mut indent = 0;
-    let mut last: Option<SyntaxKind> = None;
-
-    while let Some(token) = token_iter.next() {
-        let mut is_next = |f: fn(SyntaxKind) -> bool, default| -> bool {
-            token_iter.peek().map(|it| f(it.kind())).unwrap_or(default)
+            WalkEvent::Enter(NodeOrToken::Node(node)) if depth != Some(0) => node,
+            _ => continue,
         };
-        let is_last =
-            |f: fn(SyntaxKind) -> bool, default| -> bool { last.map(f).unwrap_or(default) };
-
-        match token.kind() {
-            k if is_text(k) && is_next(|it| !it.is_punct(), true) => {
-                res.push_str(token.text());
-                res.push(' ');
-            }
-            L_CURLY if is_next(|it| it != R_CURLY, true) => {
-                indent += 1;
-                if is_last(is_text, false) {
-                    res.push(' ');
-                }
-                res.push_str("{\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
-            }
-            R_CURLY if is_last(|it| it != L_CURLY, true) => {
-                indent = indent.saturating_sub(1);
-                res.push('\n');
-                res.extend(iter::repeat(" ").take(2 * indent));
-                res.push_str("}");
-            }
-            R_CURLY => {
-                res.push_str("}\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
-            }
-            LIFETIME_IDENT if is_next(|it| it == IDENT, true) => {
-                res.push_str(token.text());
-                res.push(' ');
-            }
-            T![;] => {
-                res.push_str(";\n");
-                res.extend(iter::repeat(" ").take(2 * indent));
-            }
-            T![->] => res.push_str(" -> "),
-            T![=] => res.push_str(" = "),
-            T![=>] => res.push_str(" => "),
-            _ => res.push_str(token.text()),
+        if let Some(expanded) = ast::MacroCall::cast(node).and_then(|mac| sema.expand(&mac)) {
+            preorder.skip_subtree();
+            collect_expansion_tokens(sema, expanded, depth.map(|it| it - 1), acc);
         }
-
-        last = Some(token.kind());
-    }
-
-    return res;
-
-    fn is_text(k: SyntaxKind) -> bool {
-        k.is_keyword() || k.is_literal() || k == IDENT
     }
 }
 
@@ -159,8 +111,12 @@ mod tests {
     use crate::fixture;
 
     fn check(ra_fixture: &str, expect: Expect) {
+        check_with_depth(ra_fixture, None, expect)
+    }
+
+    fn check_with_depth(ra_fixture: &str, depth: Option<u32>, expect: Expect) {
         let (analysis, pos) = fixture::position(ra_fixture);
-        let expansion = analysis.expand_macro(pos).unwrap().unwrap();
+        let expansion = analysis.expand_macro(pos, depth).unwrap().unwrap();
         let actual = format!("{}\n{}", expansion.name, expansion.expansion);
         expect.assert_eq(&actual);
     }
@@ -187,6 +143,61 @@ f$0oo!();
         );
     }
 
+    #[test]
+    fn macro_expand_limited_depth() {
+        let fixture = r#"
+macro_rules! bar {
+    () => { fn  b() {} }
+}
+macro_rules! foo {
+    () => { bar!(); }
+}
+macro_rules! baz {
+    () => { foo!(); }
+}
+b$0az!();
+"#;
+        check_with_depth(
+            fixture,
+            Some(1),
+            expect![[r#"
+                baz
+                foo!();
+            "#]],
+        );
+        check_with_depth(
+            fixture,
+            Some(2),
+            expect![[r#"
+                baz
+                bar!();
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macro_expand_origins() {
+        let (analysis, pos) = fixture::position(
+            r#"
+macro_rules! foo {
+    ($name:ident, $value:expr) => { fn $name() -> u32 { $value } }
+}
+f$0oo!(answer, 42);
+"#,
+        );
+        let expansion = analysis.expand_macro(pos, None).unwrap().unwrap();
+        let file_text = analysis.file_text(pos.file_id).unwrap();
+        let origins = expansion
+            .origins
+            .iter()
+            .map(|(range, origin)| {
+                assert_eq!(origin.file_id, pos.file_id);
+                (&expansion.expansion[*range], &file_text[origin.range])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(origins, vec![("answer", "answer"), ("42", "42")]);
+    }
+
     #[test]
     fn macro_expand_multiple_lines() {
         check(
@@ -1,10 +1,10 @@
 use std::iter::successors;
 
 use hir::Semantics;
-use ide_db::RootDatabase;
+use ide_db::{helpers::format_placeholders, RootDatabase};
 use syntax::{
     algo::{self, skip_trivia_token},
-    ast::{self, AstNode, AstToken},
+    ast::{self, AstNode, AstToken, IsString},
     Direction, NodeOrToken,
     SyntaxKind::{self, *},
     SyntaxNode, SyntaxToken, TextRange, TextSize, TokenAtOffset, T,
@@ -83,6 +83,11 @@ fn try_extend_selection(
     let node = match root.covering_element(range) {
         NodeOrToken::Token(token) => {
             if token.text_range() != range {
+                if string_kinds.contains(&token.kind()) {
+                    if let Some(range) = extend_in_string_or_comment(&token, range) {
+                        return Some(range);
+                    }
+                }
                 return Some(token.text_range());
             }
             if let Some(comment) = ast::Comment::cast(token.clone()) {
@@ -97,10 +102,13 @@ fn try_extend_selection(
 
     // if we are in single token_tree, we maybe live in macro or attr
     if node.kind() == TOKEN_TREE {
-        if let Some(macro_call) = node.ancestors().find_map(ast::MacroCall::cast) {
-            if let Some(range) = extend_tokens_from_range(sema, macro_call, range) {
-                return Some(range);
-            }
+        let from_expansion = node
+            .ancestors()
+            .find_map(ast::MacroCall::cast)
+            .and_then(|macro_call| extend_tokens_from_range(sema, macro_call, range));
+        let element = extend_token_tree_element(&node, range);
+        if let Some(range) = from_expansion.into_iter().chain(element).min_by_key(|it| it.len()) {
+            return Some(range);
         }
     }
 
@@ -186,6 +194,95 @@ fn extend_tokens_from_range(
     }
 }
 
+/// Extends a selection inside a token tree to the comma-separated element of the
+/// tree containing it, like an argument of a macro call.
+fn extend_token_tree_element(token_tree: &SyntaxNode, range: TextRange) -> Option<TextRange> {
+    let children = token_tree.children_with_tokens().collect::<Vec<_>>();
+    let inner = match children.as_slice() {
+        [open, inner @ .., _close] if matches!(open.kind(), T!['('] | T!['['] | T!['{']) => inner,
+        _ => return None,
+    };
+
+    let mut elements = Vec::new();
+    let mut current: Option<TextRange> = None;
+    for child in inner {
+        match child.kind() {
+            T![,] => elements.extend(current.take()),
+            WHITESPACE | COMMENT => (),
+            _ => {
+                let child_range = child.text_range();
+                current = Some(current.map_or(child_range, |it| it.cover(child_range)));
+            }
+        }
+    }
+    elements.extend(current);
+    elements.into_iter().find(|it| it.contains_range(range) && *it != range)
+}
+
+/// Extends a selection inside a string literal or a comment to the smallest
+/// part of it around the selection: an interpolation of a format string, like
+/// `x:?` and then `{x:?}`, a path or a code span in a comment, or the contents
+/// of the literal.
+fn extend_in_string_or_comment(token: &SyntaxToken, range: TextRange) -> Option<TextRange> {
+    let text = token.text();
+    let offset = token.text_range().start();
+    let relative = range - offset;
+    let mut candidates = Vec::new();
+    if token.kind() == COMMENT {
+        candidates.push(path_range(text, relative) + offset);
+        candidates.extend(code_span_range(text, relative).map(|it| it + offset));
+    } else {
+        for (open, close) in format_placeholders(text) {
+            candidates.push(TextRange::new(open + TextSize::of('{'), close) + offset);
+            candidates.push(TextRange::new(open, close + TextSize::of('}')) + offset);
+        }
+        candidates
+            .extend(ast::String::cast(token.clone()).and_then(|it| it.text_range_between_quotes()));
+        candidates.extend(
+            ast::ByteString::cast(token.clone()).and_then(|it| it.text_range_between_quotes()),
+        );
+    }
+    candidates
+        .into_iter()
+        .filter(|it| it.contains_range(range) && *it != range)
+        .min_by_key(|it| it.len())
+}
+
+/// Extends `range` to the path around it, like `foo::bar` in a comment.
+fn path_range(text: &str, range: TextRange) -> TextRange {
+    fn is_path_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == ':'
+    }
+
+    let before = &text[..usize::from(range.start())];
+    let after = &text[usize::from(range.end())..];
+    let path_before = &before[before.trim_end_matches(is_path_char).len()..];
+    let path_before = path_before.trim_start_matches(':');
+    let path_after = &after[..after.len() - after.trim_start_matches(is_path_char).len()];
+    let path_after = path_after.trim_end_matches(':');
+    TextRange::new(
+        range.start() - TextSize::of(path_before),
+        range.end() + TextSize::of(path_after),
+    )
+}
+
+/// Returns the contents of the code span in backticks around `range` in a
+/// comment.
+fn code_span_range(text: &str, range: TextRange) -> Option<TextRange> {
+    let before = &text[..usize::from(range.start())];
+    let open = before.rfind('`')?;
+    // The backtick before the selection has to open a code span.
+    if before[..open].matches('`').count() % 2 != 0 {
+        return None;
+    }
+    let start = open + 1;
+    let end = usize::from(range.end()) + text[usize::from(range.end())..].find('`')?;
+    if text[start..end].contains('`') {
+        return None;
+    }
+    Some(TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32)))
+}
+
 /// Find the shallowest node with same range, which allows us to traverse siblings.
 fn shallowest_node(node: &SyntaxNode) -> SyntaxNode {
     node.ancestors().take_while(|n| n.text_range() == node.text_range()).last().unwrap()
@@ -496,7 +593,44 @@ fn bar(){}
 
 " fn f$0oo() {"
 "#,
-            &["foo", "\" fn foo() {\""],
+            &["foo", " fn foo() {", "\" fn foo() {\""],
+        );
+    }
+
+    #[test]
+    fn test_extend_selection_format_string() {
+        do_check(
+            r#"
+fn main() { format!("a {x$0:?} {{b}}", x = 1); }
+"#,
+            &["x", "x:?", "{x:?}", "a {x:?} {{b}}", "\"a {x:?} {{b}}\""],
+        );
+    }
+
+    #[test]
+    fn test_extend_selection_path_in_doc_comment() {
+        do_check(
+            r#"
+/// See [`foo::ba$0r()`] for details.
+struct S;
+"#,
+            &["bar", "foo::bar", "foo::bar()", "/// See [`foo::bar()`] for details."],
+        );
+    }
+
+    #[test]
+    fn test_extend_selection_token_tree_element() {
+        do_check(
+            r#"
+fn main() { foo!(a + b$0, [c, d]); }
+"#,
+            &["b", "a + b", "(a + b, [c, d])"],
+        );
+        do_check(
+            r#"
+fn main() { foo!(a + b, [c, $0d]); }
+"#,
+            &["d", "[c, d]", "(a + b, [c, d])"],
         );
     }
 
@@ -1,10 +1,13 @@
-use ide_db::SymbolKind;
+use hir::Semantics;
+use ide_db::{base_db::FileId, RootDatabase, SymbolKind};
 use syntax::{
     ast::{self, AttrsOwner, GenericParamsOwner, NameOwner},
-    match_ast, AstNode, AstToken, NodeOrToken, SourceFile, SyntaxNode, SyntaxToken, TextRange,
-    WalkEvent,
+    match_ast, AstNode, AstToken, NodeOrToken, SyntaxNode, SyntaxToken, TextRange, WalkEvent,
 };
 
+/// How deep macro calls in the expansions of other macro calls are expanded.
+const MAX_EXPANSION_DEPTH: usize = 16;
+
 #[derive(Debug, Clone)]
 pub struct StructureNode {
     pub parent: Option<usize>,
@@ -14,6 +17,9 @@ pub struct StructureNode {
     pub kind: StructureNodeKind,
     pub detail: Option<String>,
     pub deprecated: bool,
+    /// Whether the symbol is defined by a macro call. Its ranges are the ones
+    /// of the outermost macro call.
+    pub macro_generated: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -22,6 +28,11 @@ pub enum StructureNodeKind {
     Region,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileStructureConfig {
+    pub include_macro_generated: bool,
+}
+
 // Feature: File Structure
 //
 // Provides a tree of the symbols defined in the file. Can be used to
@@ -38,17 +49,66 @@ pub enum StructureNodeKind {
 //
 // image::https://user-images.githubusercontent.com/48062697/113020654-b42fc800-917a-11eb-8388-e7dc4d92b02e.gif[]
 
-pub(crate) fn file_structure(file: &SourceFile) -> Vec<StructureNode> {
+pub(crate) fn file_structure(
+    db: &RootDatabase,
+    file_id: FileId,
+    config: &FileStructureConfig,
+) -> Vec<StructureNode> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id);
+    let mut expand = |call: &ast::MacroCall| {
+        if config.include_macro_generated {
+            sema.expand(call)
+        } else {
+            None
+        }
+    };
     let mut res = Vec::new();
-    let mut stack = Vec::new();
+    collect_structure(file.syntax(), None, 0, &mut expand, &mut Vec::new(), &mut res);
+    res
+}
+
+/// Adds the symbols of `root` to `res`, as children of the last node of
+/// `stack`. `call_site` is the macro call `root` is the expansion of, if any.
+fn collect_structure(
+    root: &SyntaxNode,
+    call_site: Option<&ast::MacroCall>,
+    depth: usize,
+    expand: &mut dyn FnMut(&ast::MacroCall) -> Option<SyntaxNode>,
+    stack: &mut Vec<usize>,
+    res: &mut Vec<StructureNode>,
+) {
+    let push = |mut symbol: StructureNode, stack: &mut Vec<usize>, res: &mut Vec<StructureNode>| {
+        if let Some(call) = call_site {
+            symbol.node_range = call.syntax().text_range();
+            symbol.navigation_range =
+                call.path().map_or(symbol.node_range, |path| path.syntax().text_range());
+            symbol.macro_generated = true;
+        }
+        symbol.parent = stack.last().copied();
+        stack.push(res.len());
+        res.push(symbol);
+    };
 
-    for event in file.syntax().preorder_with_tokens() {
+    for event in root.preorder_with_tokens() {
         match event {
             WalkEvent::Enter(NodeOrToken::Node(node)) => {
-                if let Some(mut symbol) = structure_node(&node) {
-                    symbol.parent = stack.last().copied();
-                    stack.push(res.len());
-                    res.push(symbol);
+                if let Some(symbol) = structure_node(&node) {
+                    push(symbol, stack, res);
+                } else if depth < MAX_EXPANSION_DEPTH {
+                    if let Some(call) = item_macro_call(&node) {
+                        if let Some(expansion) = expand(&call) {
+                            let call_site = call_site.unwrap_or(&call);
+                            collect_structure(
+                                &expansion,
+                                Some(call_site),
+                                depth + 1,
+                                expand,
+                                stack,
+                                res,
+                            );
+                        }
+                    }
                 }
             }
             WalkEvent::Leave(NodeOrToken::Node(node)) => {
@@ -57,10 +117,8 @@ pub(crate) fn file_structure(file: &SourceFile) -> Vec<StructureNode> {
                 }
             }
             WalkEvent::Enter(NodeOrToken::Token(token)) => {
-                if let Some(mut symbol) = structure_token(token) {
-                    symbol.parent = stack.last().copied();
-                    stack.push(res.len());
-                    res.push(symbol);
+                if let Some(symbol) = structure_token(token) {
+                    push(symbol, stack, res);
                 }
             }
             WalkEvent::Leave(NodeOrToken::Token(token)) => {
@@ -70,7 +128,21 @@ pub(crate) fn file_structure(file: &SourceFile) -> Vec<StructureNode> {
             }
         }
     }
-    res
+}
+
+/// Returns the macro call `node` is, if it is in item position.
+fn item_macro_call(node: &SyntaxNode) -> Option<ast::MacroCall> {
+    let call = ast::MacroCall::cast(node.clone())?;
+    let parent = call.syntax().parent()?;
+    if ast::SourceFile::can_cast(parent.kind())
+        || ast::ItemList::can_cast(parent.kind())
+        || ast::AssocItemList::can_cast(parent.kind())
+        || ast::MacroItems::can_cast(parent.kind())
+    {
+        Some(call)
+    } else {
+        None
+    }
 }
 
 fn structure_node(node: &SyntaxNode) -> Option<StructureNode> {
@@ -106,6 +178,7 @@ fn structure_node(node: &SyntaxNode) -> Option<StructureNode> {
             kind,
             detail,
             deprecated: node.attrs().filter_map(|x| x.simple_name()).any(|x| x == "deprecated"),
+            macro_generated: false,
         })
     }
 
@@ -172,6 +245,7 @@ fn structure_node(node: &SyntaxNode) -> Option<StructureNode> {
                     kind: StructureNodeKind::SymbolKind(SymbolKind::Impl),
                     detail: None,
                     deprecated: false,
+                    macro_generated: false,
                 };
                 Some(node)
             },
@@ -194,6 +268,7 @@ fn structure_token(token: SyntaxToken) -> Option<StructureNode> {
                 kind: StructureNodeKind::Region,
                 detail: None,
                 deprecated: false,
+                macro_generated: false,
             });
         }
     }
@@ -204,15 +279,44 @@ fn structure_token(token: SyntaxToken) -> Option<StructureNode> {
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
+    use syntax::SourceFile;
 
     use super::*;
+    use crate::fixture;
 
     fn check(ra_fixture: &str, expect: Expect) {
         let file = SourceFile::parse(ra_fixture).ok().unwrap();
-        let structure = file_structure(&file);
+        let mut structure = Vec::new();
+        collect_structure(file.syntax(), None, 0, &mut |_| None, &mut Vec::new(), &mut structure);
         expect.assert_debug_eq(&structure)
     }
 
+    fn check_macro_generated(ra_fixture: &str, include_macro_generated: bool, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let config = FileStructureConfig { include_macro_generated };
+        let structure = analysis.file_structure(&config, file_id).unwrap();
+        let mut actual = String::new();
+        for (idx, node) in structure.iter().enumerate() {
+            let mut depth = 0;
+            let mut parent = node.parent;
+            while let Some(idx) = parent {
+                depth += 1;
+                parent = structure[idx].parent;
+            }
+            actual += &format!("{}{}: {:?}", "  ".repeat(depth), node.label, node.kind);
+            if node.macro_generated {
+                actual += &format!(
+                    " from `{}` at `{}`",
+                    &text[node.node_range], &text[node.navigation_range]
+                );
+            }
+            actual += "\n";
+            assert!(node.parent.map_or(true, |it| it < idx));
+        }
+        expect.assert_eq(&actual)
+    }
+
     #[test]
     fn test_file_structure() {
         check(
@@ -282,6 +386,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -297,6 +402,7 @@ fn g() {}
                             "i32",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -308,6 +414,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -323,6 +430,7 @@ fn g() {}
                             "fn()",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -338,6 +446,7 @@ fn g() {}
                             "fn<T>(t: T) -> T",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -353,6 +462,7 @@ fn g() {}
                             "fn<A, B>(a: A, b: B) -> Vec< u32 >",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -364,6 +474,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -377,6 +488,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -390,6 +502,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -403,6 +516,7 @@ fn g() {}
                             "()",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -416,6 +530,7 @@ fn g() {}
                             "i32",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -429,6 +544,7 @@ fn g() {}
                             "i32",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -440,6 +556,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -451,6 +568,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -462,6 +580,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -473,6 +592,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -484,6 +604,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -497,6 +618,7 @@ fn g() {}
                             "fn()",
                         ),
                         deprecated: true,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -510,6 +632,7 @@ fn g() {}
                             "fn()",
                         ),
                         deprecated: true,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -519,6 +642,7 @@ fn g() {}
                         kind: Region,
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: None,
@@ -530,6 +654,7 @@ fn g() {}
                         ),
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -541,6 +666,7 @@ fn g() {}
                         kind: Region,
                         detail: None,
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -556,6 +682,7 @@ fn g() {}
                             "fn()",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                     StructureNode {
                         parent: Some(
@@ -571,9 +698,61 @@ fn g() {}
                             "fn()",
                         ),
                         deprecated: false,
+                        macro_generated: false,
                     },
                 ]
             "#]],
         );
     }
+
+    #[test]
+    fn macro_generated_items() {
+        check_macro_generated(
+            r#"
+macro_rules! gen {
+    ($name:ident) => {
+        fn $name() {}
+        struct S { field: u32 }
+    };
+}
+macro_rules! nested {
+    () => { gen!(bar); };
+}
+mod m {
+    gen!(foo);
+    fn f() {}
+}
+nested!();
+"#,
+            true,
+            expect![[r#"
+                gen: SymbolKind(Macro)
+                nested: SymbolKind(Macro)
+                m: SymbolKind(Module)
+                  foo: SymbolKind(Function) from `gen!(foo);` at `gen`
+                  S: SymbolKind(Struct) from `gen!(foo);` at `gen`
+                    field: SymbolKind(Field) from `gen!(foo);` at `gen`
+                  f: SymbolKind(Function)
+                bar: SymbolKind(Function) from `nested!();` at `nested`
+                S: SymbolKind(Struct) from `nested!();` at `nested`
+                  field: SymbolKind(Field) from `nested!();` at `nested`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn macro_generated_items_disabled() {
+        check_macro_generated(
+            r#"
+macro_rules! gen {
+    () => { fn foo() {} };
+}
+gen!();
+"#,
+            false,
+            expect![[r#"
+                gen: SymbolKind(Macro)
+            "#]],
+        );
+    }
 }
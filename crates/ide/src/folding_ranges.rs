@@ -1,7 +1,9 @@
+use hir::{InFile, Semantics};
+use ide_db::{base_db::FileId, RootDatabase};
 use rustc_hash::FxHashSet;
 
 use syntax::{
-    ast::{self, AstNode, AstToken},
+    ast::{self, AstNode, AstToken, AttrsOwner},
     match_ast, Direction, NodeOrToken, SourceFile,
     SyntaxKind::{self, *},
     TextRange, TextSize,
@@ -25,6 +27,8 @@ pub enum FoldKind {
     Array,
     WhereClause,
     ReturnType,
+    /// Code disabled by a `#[cfg]` attribute.
+    InactiveCode,
 }
 
 #[derive(Debug)]
@@ -36,8 +40,17 @@ pub struct Fold {
 // Feature: Folding
 //
 // Defines folding regions for curly braced blocks, runs of consecutive use, mod, const or static
-// items, and `region` / `endregion` comment markers.
-pub(crate) fn folding_ranges(file: &SourceFile) -> Vec<Fold> {
+// items, multi-line where clauses, code disabled by `#[cfg]` attributes, and `region` /
+// `endregion` comment markers. A run of `use` items is split where their attributes change.
+pub(crate) fn folding_ranges(db: &RootDatabase, file_id: FileId) -> Vec<Fold> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id);
+    let mut res = syntactic_folding_ranges(&file);
+    res.extend(inactive_code_folding_ranges(&sema, file_id, &file));
+    res
+}
+
+fn syntactic_folding_ranges(file: &SourceFile) -> Vec<Fold> {
     let mut res = vec![];
     let mut visited_comments = FxHashSet::default();
     let mut visited_imports = FxHashSet::default();
@@ -93,23 +106,24 @@ pub(crate) fn folding_ranges(file: &SourceFile) -> Vec<Fold> {
                                 if let Some(range) = contiguous_range_for_item_group(
                                     module,
                                     &mut visited_mods,
+                                    false,
                                 ) {
                                     res.push(Fold { range, kind: FoldKind::Mods })
                                 }
                             }
                         },
                         ast::Use(use_) => {
-                            if let Some(range) = contiguous_range_for_item_group(use_, &mut visited_imports) {
+                            if let Some(range) = contiguous_range_for_item_group(use_, &mut visited_imports, true) {
                                 res.push(Fold { range, kind: FoldKind::Imports })
                             }
                         },
                         ast::Const(konst) => {
-                            if let Some(range) = contiguous_range_for_item_group(konst, &mut visited_consts) {
+                            if let Some(range) = contiguous_range_for_item_group(konst, &mut visited_consts, false) {
                                 res.push(Fold { range, kind: FoldKind::Consts })
                             }
                         },
                         ast::Static(statik) => {
-                            if let Some(range) = contiguous_range_for_item_group(statik, &mut visited_statics) {
+                            if let Some(range) = contiguous_range_for_item_group(statik, &mut visited_statics, false) {
                                 res.push(Fold { range, kind: FoldKind::Statics })
                             }
                         },
@@ -149,14 +163,24 @@ fn fold_kind(kind: SyntaxKind) -> Option<FoldKind> {
     }
 }
 
-fn contiguous_range_for_item_group<N>(first: N, visited: &mut FxHashSet<N>) -> Option<TextRange>
+/// Finds the run of items of the same kind and visibility starting at `first`.
+/// If `split_on_attrs` is set, the run also ends at an item with different
+/// attributes.
+fn contiguous_range_for_item_group<N>(
+    first: N,
+    visited: &mut FxHashSet<N>,
+    split_on_attrs: bool,
+) -> Option<TextRange>
 where
-    N: ast::VisibilityOwner + Clone + Hash + Eq,
+    N: ast::VisibilityOwner + AttrsOwner + Clone + Hash + Eq,
 {
     if !visited.insert(first.clone()) {
         return None;
     }
 
+    let attrs_text =
+        |item: &N| item.attrs().map(|attr| attr.syntax().text().to_string()).collect::<Vec<_>>();
+    let first_attrs = if split_on_attrs { attrs_text(&first) } else { Vec::new() };
     let (mut last, mut last_vis) = (first.clone(), first.visibility());
     for element in first.syntax().siblings_with_tokens(Direction::Next) {
         let node = match element {
@@ -176,14 +200,16 @@ where
 
         if let Some(next) = N::cast(node) {
             let next_vis = next.visibility();
-            if eq_visibility(next_vis.clone(), last_vis) {
+            let same_attrs = !split_on_attrs || attrs_text(&next) == first_attrs;
+            if same_attrs && eq_visibility(next_vis.clone(), last_vis) {
                 visited.insert(next.clone());
                 last_vis = next_vis;
                 last = next;
                 continue;
             }
         }
-        // Stop if we find an item of a different kind or with a different visibility.
+        // Stop if we find an item of a different kind, or with a different visibility or
+        // attributes.
         break;
     }
 
@@ -257,7 +283,8 @@ fn fold_range_for_where_clause(where_clause: ast::WhereClause) -> Option<TextRan
     let first_where_pred = where_clause.predicates().next();
     let last_where_pred = where_clause.predicates().last();
 
-    if first_where_pred != last_where_pred {
+    let is_multiline = where_clause.syntax().text().contains_char('\n');
+    if is_multiline && first_where_pred != last_where_pred {
         let start = where_clause.where_token()?.text_range().end();
         let end = where_clause.syntax().text_range().end();
         return Some(TextRange::new(start, end));
@@ -265,17 +292,65 @@ fn fold_range_for_where_clause(where_clause: ast::WhereClause) -> Option<TextRan
     None
 }
 
+/// Folds the items, fields, variants and match arms disabled by their `#[cfg]`
+/// attributes in the crate of the file.
+fn inactive_code_folding_ranges(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    file: &SourceFile,
+) -> Vec<Fold> {
+    let krate = match sema.to_module_def(file_id) {
+        Some(it) => it.krate(),
+        None => return Vec::new(),
+    };
+    let mut res: Vec<Fold> = Vec::new();
+    for attr in file.syntax().descendants().filter_map(ast::Attr::cast) {
+        if !attr.kind().is_outer() || attr.simple_name().as_deref() != Some("cfg") {
+            continue;
+        }
+        let owner = match attr.syntax().parent().and_then(attrs_owner) {
+            Some(it) => it,
+            None => continue,
+        };
+        let range = owner.syntax().text_range();
+        // Several `cfg`s on the same node, or nodes nested in a folded one.
+        if res.last().map_or(false, |fold| fold.range.contains_range(range)) {
+            continue;
+        }
+        let owner = InFile::new(file_id.into(), &owner as &dyn AttrsOwner);
+        if !krate.is_cfg_enabled(sema.db, owner) && owner.value.syntax().text().contains_char('\n')
+        {
+            res.push(Fold { range, kind: FoldKind::InactiveCode });
+        }
+    }
+    res
+}
+
+fn attrs_owner(node: syntax::SyntaxNode) -> Option<ast::AttrsOwnerNode> {
+    match_ast! {
+        match node {
+            ast::Item(it) => Some(ast::AttrsOwnerNode::new(it)),
+            ast::Variant(it) => Some(ast::AttrsOwnerNode::new(it)),
+            ast::RecordField(it) => Some(ast::AttrsOwnerNode::new(it)),
+            ast::TupleField(it) => Some(ast::AttrsOwnerNode::new(it)),
+            ast::MatchArm(it) => Some(ast::AttrsOwnerNode::new(it)),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_utils::extract_tags;
 
     use super::*;
+    use crate::Analysis;
 
     fn check(ra_fixture: &str) {
         let (ranges, text) = extract_tags(ra_fixture, "fold");
 
-        let parse = SourceFile::parse(&text);
-        let mut folds = folding_ranges(&parse.tree());
+        let (analysis, file_id) = Analysis::from_single_file(text);
+        let mut folds = analysis.folding_ranges(file_id).unwrap();
         folds.sort_by_key(|fold| (fold.range.start(), fold.range.end()));
 
         assert_eq!(
@@ -300,6 +375,7 @@ mod tests {
                 FoldKind::Array => "array",
                 FoldKind::WhereClause => "whereclause",
                 FoldKind::ReturnType => "returntype",
+                FoldKind::InactiveCode => "inactive",
             };
             assert_eq!(kind, &attr.unwrap());
         }
@@ -564,6 +640,53 @@ where
         )
     }
 
+    #[test]
+    fn fold_single_line_where_clause() {
+        check(
+            r#"
+fn foo() where A: Foo, B: Foo {}
+"#,
+        )
+    }
+
+    #[test]
+    fn fold_import_groups_split_by_attributes() {
+        check(
+            r#"
+<fold imports>use std::str;
+use std::vec;</fold>
+<fold imports>#[cfg(test)]
+use std::io;
+#[cfg(test)]
+use std::fmt;</fold>
+#[cfg(feature = "foo")] use std::mem;
+"#,
+        )
+    }
+
+    #[test]
+    fn fold_inactive_code() {
+        check(
+            r#"
+<fold inactive>#[cfg(never)]
+fn foo() <fold block>{
+    #[cfg(never)]
+    let x = 92;
+}</fold></fold>
+
+#[cfg(test)]
+fn bar() {}
+
+#[cfg(never)] struct S;
+
+struct T <fold block>{
+    <fold inactive>#[cfg(never)]
+    field: u32</fold>,
+}</fold>
+"#,
+        )
+    }
+
     #[test]
     fn fold_return_type() {
         check(
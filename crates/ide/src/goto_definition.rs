@@ -78,7 +78,10 @@ pub(crate) fn goto_definition(
             } else {
                 reference_definition(&sema, Either::Left(&lt))
             },
-            ast::TokenTree(tt) => try_lookup_include_path(sema.db, tt, token, position.file_id)?,
+            ast::TokenTree(tt) => match try_lookup_doc_alias(&sema, &token) {
+                Some(navs) => navs,
+                None => try_lookup_include_path(sema.db, tt, token, position.file_id)?,
+            },
             _ => return None,
         }
     };
@@ -112,6 +115,19 @@ fn try_lookup_include_path(
     }])
 }
 
+/// Navigates from an alias in a `#[doc(alias = "...")]` attribute to the aliased item.
+fn try_lookup_doc_alias(
+    sema: &Semantics<RootDatabase>,
+    token: &SyntaxToken,
+) -> Option<Vec<NavigationTarget>> {
+    let attr = token.ancestors().find_map(ast::Attr::cast)?;
+    if !attr.doc_aliases().iter().any(|alias| alias.syntax() == token) {
+        return None;
+    }
+    let (_, def) = doc_attributes(sema, &attr.syntax().parent()?)?;
+    Some(def_to_nav(sema.db, def))
+}
+
 /// finds the trait definition of an impl'd item
 /// e.g.
 /// ```rust
@@ -1330,6 +1346,24 @@ fn main() {
         );
     }
 
+    #[test]
+    fn goto_def_for_doc_alias() {
+        check(
+            r#"
+#[doc(alias("Bar", "B$0az"))]
+struct Foo;
+     //^^^
+"#,
+        );
+        check(
+            r#"
+#[doc(alias = "B$0ar")]
+fn foo() {}
+ //^^^
+"#,
+        );
+    }
+
     #[test]
     fn goto_def_of_trait_impl_fn() {
         check(
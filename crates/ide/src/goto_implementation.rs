@@ -11,6 +11,9 @@ use crate::{display::TryToNav, FilePosition, NavigationTarget, RangeInfo};
 //
 // Navigates to the impl block of structs, enums or traits. Also implemented as a code lens.
 //
+// On a call of a trait method, navigates to the implementations of the method. Only the impls
+// for the type of the receiver are listed, unless the receiver is generic or a trait object.
+//
 // |===
 // | Editor  | Shortcut
 //
@@ -61,13 +64,17 @@ pub(crate) fn goto_implementation(
             let assoc = f.as_assoc_item(sema.db)?;
             let name = assoc.name(sema.db)?;
             let trait_ = assoc.containing_trait_or_trait_impl(sema.db)?;
-            impls_for_trait_item(&sema, trait_, name)
+            let receiver = match &node {
+                ast::NameLike::NameRef(name_ref) => method_receiver_ty(&sema, name_ref),
+                _ => None,
+            };
+            impls_for_trait_item(&sema, trait_, name, receiver)
         }
         hir::ModuleDef::Const(c) => {
             let assoc = c.as_assoc_item(sema.db)?;
             let name = assoc.name(sema.db)?;
             let trait_ = assoc.containing_trait_or_trait_impl(sema.db)?;
-            impls_for_trait_item(&sema, trait_, name)
+            impls_for_trait_item(&sema, trait_, name, None)
         }
         _ => return None,
     };
@@ -85,12 +92,28 @@ fn impls_for_trait(sema: &Semantics<RootDatabase>, trait_: hir::Trait) -> Vec<Na
         .collect()
 }
 
+/// The impls of `trait_` defining the item `fun_name`. If `receiver` is the
+/// concrete type the item is used on, only the impls for this type are listed.
 fn impls_for_trait_item(
     sema: &Semantics<RootDatabase>,
     trait_: hir::Trait,
     fun_name: hir::Name,
+    receiver: Option<hir::Type>,
 ) -> Vec<NavigationTarget> {
-    Impl::all_for_trait(sema.db, trait_)
+    let mut impls = Impl::all_for_trait(sema.db, trait_);
+    if let Some(receiver) = receiver {
+        if receiver.as_dyn_trait().is_none() && !receiver.is_type_param() {
+            let matching = impls
+                .iter()
+                .copied()
+                .filter(|imp| receiver.could_unify_with(sema.db, &imp.self_ty(sema.db)))
+                .collect::<Vec<_>>();
+            if !matching.is_empty() {
+                impls = matching;
+            }
+        }
+    }
+    impls
         .into_iter()
         .filter_map(|imp| {
             let item = imp.items(sema.db).iter().find_map(|itm| {
@@ -102,6 +125,14 @@ fn impls_for_trait_item(
         .collect()
 }
 
+fn method_receiver_ty(
+    sema: &Semantics<RootDatabase>,
+    name_ref: &ast::NameRef,
+) -> Option<hir::Type> {
+    let method_call = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast)?;
+    Some(sema.type_of_expr(&method_call.receiver()?)?.strip_references())
+}
+
 #[cfg(test)]
 mod tests {
     use ide_db::base_db::FileRange;
@@ -323,6 +354,86 @@ impl Tr for S {
     const C: usize = 4;
         //^
 }
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_implementation_method_call_concrete_receiver() {
+        check(
+            r#"
+trait Tr {
+    fn f(&self);
+}
+
+struct S;
+struct T;
+
+impl Tr for S {
+    fn f(&self) {}
+     //^
+}
+impl Tr for T {
+    fn f(&self) {}
+}
+
+fn main() {
+    (&S).f$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_implementation_method_call_generic_receiver() {
+        check(
+            r#"
+trait Tr {
+    fn f(&self);
+}
+
+struct S;
+struct T;
+
+impl Tr for S {
+    fn f(&self) {}
+     //^
+}
+impl Tr for T {
+    fn f(&self) {}
+     //^
+}
+
+fn generic<X: Tr>(x: X) {
+    x.f$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_implementation_method_call_dyn_receiver() {
+        check(
+            r#"
+trait Tr {
+    fn f(&self);
+}
+
+struct S;
+struct T;
+
+impl Tr for S {
+    fn f(&self) {}
+     //^
+}
+impl Tr for T {
+    fn f(&self) {}
+     //^
+}
+
+fn dynamic(x: &dyn Tr) {
+    x.f$0();
+}
 "#,
         );
     }
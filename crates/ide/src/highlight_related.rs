@@ -1,9 +1,9 @@
 use hir::Semantics;
 use ide_db::{
-    base_db::FilePosition,
+    base_db::{FileId, FilePosition},
     defs::Definition,
     helpers::{for_each_break_expr, for_each_tail_expr, pick_best_token},
-    search::{FileReference, ReferenceAccess, SearchScope},
+    search::{FileReference, ReferenceCategory, SearchScope},
     RootDatabase,
 };
 use syntax::{
@@ -15,7 +15,7 @@ use crate::{display::TryToNav, references, NavigationTarget};
 
 pub struct HighlightedRange {
     pub range: TextRange,
-    pub access: Option<ReferenceAccess>,
+    pub category: Option<ReferenceCategory>,
 }
 
 // Feature: Highlight Related
@@ -23,7 +23,7 @@ pub struct HighlightedRange {
 // Highlights constructs related to the thing under the cursor:
 // - if on an identifier, highlights all references to that identifier in the current file
 // - if on an `async` or `await token, highlights all yield points for that async context
-// - if on a `return` token, `?` character or `->` return type arrow, highlights all exit points for that context
+// - if on a `fn` or `return` token, `?` character or `->` return type arrow, highlights all exit points for that context
 // - if on a `break`, `loop`, `while` or `for` token, highlights all break points for that loop or block context
 pub(crate) fn highlight_related(
     sema: &Semantics<RootDatabase>,
@@ -41,13 +41,18 @@ pub(crate) fn highlight_related(
         | T![loop]
         | T![for]
         | T![while]
+        | T![fn]
         | T![->] => 1,
         _ => 0,
     })?;
 
     match token.kind() {
-        T![return] | T![?] | T![->] => highlight_exit_points(sema, token),
-        T![await] | T![async] => highlight_yield_points(token),
+        T![return] | T![?] | T![->] => highlight_exit_points(sema, position.file_id, token),
+        // Not the `fn` of function pointer types.
+        T![fn] if token.parent().map_or(false, |it| ast::Fn::can_cast(it.kind())) => {
+            highlight_exit_points(sema, position.file_id, token)
+        }
+        T![await] | T![async] => highlight_yield_points(sema, position.file_id, token),
         T![break] | T![loop] | T![for] | T![while] => highlight_break_points(token),
         _ => highlight_references(sema, &syntax, position),
     }
@@ -74,8 +79,8 @@ fn highlight_references(
     .filter(|decl| decl.file_id == file_id)
     .and_then(|decl| {
         let range = decl.focus_range?;
-        let access = references::decl_access(&def, syntax, range);
-        Some(HighlightedRange { range, access })
+        let category = references::decl_category(&def, syntax, range);
+        Some(HighlightedRange { range, category })
     });
 
     let file_refs = usages.references.get(&file_id).map_or(&[][..], Vec::as_slice);
@@ -84,36 +89,44 @@ fn highlight_references(
     res.extend(
         file_refs
             .iter()
-            .map(|&FileReference { access, range, .. }| HighlightedRange { range, access }),
+            .map(|&FileReference { category, range, .. }| HighlightedRange { range, category }),
     );
     Some(res)
 }
 
 fn highlight_exit_points(
     sema: &Semantics<RootDatabase>,
+    file_id: FileId,
     token: SyntaxToken,
 ) -> Option<Vec<HighlightedRange>> {
     fn hl(
         sema: &Semantics<RootDatabase>,
+        file_id: FileId,
         body: Option<ast::Expr>,
     ) -> Option<Vec<HighlightedRange>> {
         let mut highlights = Vec::new();
         let body = body?;
-        body.walk(&mut |expr| match expr {
+        walk_expr_with_macros(sema, &body, &mut |expr| match expr {
             ast::Expr::ReturnExpr(expr) => {
-                if let Some(token) = expr.return_token() {
-                    highlights.push(HighlightedRange { access: None, range: token.text_range() });
+                if let Some(range) =
+                    expr.return_token().and_then(|it| token_range(sema, file_id, &it))
+                {
+                    highlights.push(HighlightedRange { category: None, range });
                 }
             }
             ast::Expr::TryExpr(try_) => {
-                if let Some(token) = try_.question_mark_token() {
-                    highlights.push(HighlightedRange { access: None, range: token.text_range() });
+                if let Some(range) =
+                    try_.question_mark_token().and_then(|it| token_range(sema, file_id, &it))
+                {
+                    highlights.push(HighlightedRange { category: None, range });
                 }
             }
             ast::Expr::MethodCallExpr(_) | ast::Expr::CallExpr(_) | ast::Expr::MacroCall(_) => {
                 if sema.type_of_expr(&expr).map_or(false, |ty| ty.is_never()) {
-                    highlights
-                        .push(HighlightedRange { access: None, range: expr.syntax().text_range() });
+                    let range = sema.original_range(expr.syntax());
+                    if range.file_id == file_id {
+                        highlights.push(HighlightedRange { category: None, range: range.range });
+                    }
                 }
             }
             _ => (),
@@ -131,7 +144,7 @@ fn highlight_exit_points(
                         .map_or_else(|| tail.syntax().text_range(), |tok| tok.text_range()),
                     _ => tail.syntax().text_range(),
                 };
-                highlights.push(HighlightedRange { access: None, range })
+                highlights.push(HighlightedRange { category: None, range })
             });
         }
         Some(highlights)
@@ -139,10 +152,10 @@ fn highlight_exit_points(
     for anc in token.ancestors() {
         return match_ast! {
             match anc {
-                ast::Fn(fn_) => hl(sema, fn_.body().map(ast::Expr::BlockExpr)),
-                ast::ClosureExpr(closure) => hl(sema, closure.body()),
+                ast::Fn(fn_) => hl(sema, file_id, fn_.body().map(ast::Expr::BlockExpr)),
+                ast::ClosureExpr(closure) => hl(sema, file_id, closure.body()),
                 ast::EffectExpr(effect) => if matches!(effect.effect(), ast::Effect::Async(_) | ast::Effect::Try(_)| ast::Effect::Const(_)) {
-                    hl(sema, effect.block_expr().map(ast::Expr::BlockExpr))
+                    hl(sema, file_id, effect.block_expr().map(ast::Expr::BlockExpr))
                 } else {
                     continue;
                 },
@@ -164,13 +177,13 @@ fn highlight_break_points(token: SyntaxToken) -> Option<Vec<HighlightedRange>> {
             token.map(|tok| tok.text_range()),
             label.as_ref().map(|it| it.syntax().text_range()),
         );
-        highlights.extend(range.map(|range| HighlightedRange { access: None, range }));
+        highlights.extend(range.map(|range| HighlightedRange { category: None, range }));
         for_each_break_expr(label, body, &mut |break_| {
             let range = cover_range(
                 break_.break_token().map(|it| it.text_range()),
                 break_.lifetime().map(|it| it.syntax().text_range()),
             );
-            highlights.extend(range.map(|range| HighlightedRange { access: None, range }));
+            highlights.extend(range.map(|range| HighlightedRange { category: None, range }));
         });
         Some(highlights)
     }
@@ -212,19 +225,26 @@ fn highlight_break_points(token: SyntaxToken) -> Option<Vec<HighlightedRange>> {
     None
 }
 
-fn highlight_yield_points(token: SyntaxToken) -> Option<Vec<HighlightedRange>> {
+fn highlight_yield_points(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    token: SyntaxToken,
+) -> Option<Vec<HighlightedRange>> {
     fn hl(
+        sema: &Semantics<RootDatabase>,
+        file_id: FileId,
         async_token: Option<SyntaxToken>,
         body: Option<ast::Expr>,
     ) -> Option<Vec<HighlightedRange>> {
         let mut highlights = Vec::new();
-        highlights.push(HighlightedRange { access: None, range: async_token?.text_range() });
+        highlights.push(HighlightedRange { category: None, range: async_token?.text_range() });
         if let Some(body) = body {
-            body.walk(&mut |expr| {
+            walk_expr_with_macros(sema, &body, &mut |expr| {
                 if let ast::Expr::AwaitExpr(expr) = expr {
-                    if let Some(token) = expr.await_token() {
-                        highlights
-                            .push(HighlightedRange { access: None, range: token.text_range() });
+                    if let Some(range) =
+                        expr.await_token().and_then(|it| token_range(sema, file_id, &it))
+                    {
+                        highlights.push(HighlightedRange { category: None, range });
                     }
                 }
             });
@@ -234,9 +254,9 @@ fn highlight_yield_points(token: SyntaxToken) -> Option<Vec<HighlightedRange>> {
     for anc in token.ancestors() {
         return match_ast! {
             match anc {
-                ast::Fn(fn_) => hl(fn_.async_token(), fn_.body().map(ast::Expr::BlockExpr)),
-                ast::EffectExpr(effect) => hl(effect.async_token(), effect.block_expr().map(ast::Expr::BlockExpr)),
-                ast::ClosureExpr(closure) => hl(closure.async_token(), closure.body()),
+                ast::Fn(fn_) => hl(sema, file_id, fn_.async_token(), fn_.body().map(ast::Expr::BlockExpr)),
+                ast::EffectExpr(effect) => hl(sema, file_id, effect.async_token(), effect.block_expr().map(ast::Expr::BlockExpr)),
+                ast::ClosureExpr(closure) => hl(sema, file_id, closure.async_token(), closure.body()),
                 _ => continue,
             }
         };
@@ -244,6 +264,70 @@ fn highlight_yield_points(token: SyntaxToken) -> Option<Vec<HighlightedRange>> {
     None
 }
 
+/// Calls `cb` on the expressions of `body`, like [`ast::Expr::walk`], and on the ones of the
+/// expansions of the macro calls in it, which `walk` skips.
+fn walk_expr_with_macros(
+    sema: &Semantics<RootDatabase>,
+    body: &ast::Expr,
+    cb: &mut dyn FnMut(ast::Expr),
+) {
+    body.walk(cb);
+    for call in body.syntax().descendants().filter_map(ast::MacroCall::cast) {
+        if !in_same_context(call.syntax(), body.syntax()) {
+            continue;
+        }
+        let expansion = match sema.expand(&call) {
+            Some(it) => it,
+            None => continue,
+        };
+        let exprs = match ast::MacroStmts::cast(expansion.clone()) {
+            Some(stmts) => stmts
+                .statements()
+                .filter_map(|stmt| match stmt {
+                    ast::Stmt::LetStmt(it) => it.initializer(),
+                    ast::Stmt::ExprStmt(it) => it.expr(),
+                    ast::Stmt::Item(_) => None,
+                })
+                .chain(stmts.expr())
+                .collect(),
+            None => ast::Expr::cast(expansion).into_iter().collect::<Vec<_>>(),
+        };
+        for expr in exprs {
+            walk_expr_with_macros(sema, &expr, cb);
+        }
+    }
+}
+
+/// Whether `node` is evaluated in the context of `root`, and not in a closure, an async, try or
+/// const block or an item nested in it.
+fn in_same_context(node: &SyntaxNode, root: &SyntaxNode) -> bool {
+    node.ancestors().take_while(|it| it != root).skip(1).all(|it| {
+        match ast::Expr::cast(it.clone()) {
+            Some(ast::Expr::ClosureExpr(_)) => false,
+            Some(ast::Expr::EffectExpr(effect)) => !matches!(
+                effect.effect(),
+                ast::Effect::Async(_) | ast::Effect::Try(_) | ast::Effect::Const(_)
+            ),
+            _ => !ast::Item::can_cast(it.kind()) && !ast::GenericArg::can_cast(it.kind()),
+        }
+    })
+}
+
+/// Returns the range of `token` in the file, mapping tokens of macro expansions to the macro
+/// call input they come from, or to the whole macro call.
+fn token_range(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    token: &SyntaxToken,
+) -> Option<TextRange> {
+    let parent = token.parent()?;
+    if ast::SourceFile::can_cast(parent.ancestors().last()?.kind()) {
+        return Some(token.text_range());
+    }
+    let range = sema.original_token_range(token).unwrap_or_else(|| sema.original_range(&parent));
+    (range.file_id == file_id).then(|| range.range)
+}
+
 fn cover_range(r0: Option<TextRange>, r1: Option<TextRange>) -> Option<TextRange> {
     match (r0, r1) {
         (Some(r0), Some(r1)) => Some(r0.cover(r1)),
@@ -265,7 +349,7 @@ mod tests {
 
         let mut expected = annotations
             .into_iter()
-            .map(|(r, access)| (r.range, (!access.is_empty()).then(|| access)))
+            .map(|(r, category)| (r.range, (!category.is_empty()).then(|| category)))
             .collect::<Vec<_>>();
 
         let mut actual = hls
@@ -273,10 +357,13 @@ mod tests {
             .map(|hl| {
                 (
                     hl.range,
-                    hl.access.map(|it| {
+                    hl.category.map(|it| {
                         match it {
-                            ReferenceAccess::Read => "read",
-                            ReferenceAccess::Write => "write",
+                            ReferenceCategory::Read => "read",
+                            ReferenceCategory::Write => "write",
+                            ReferenceCategory::Call => "call",
+                            ReferenceCategory::Import => "import",
+                            ReferenceCategory::TraitImpl => "trait_impl",
                         }
                         .to_string()
                     }),
@@ -302,6 +389,18 @@ struct Foo;
         );
     }
 
+    #[test]
+    fn test_hl_trait_impl() {
+        check(
+            r#"
+trait Foo$0 {}
+   // ^^^
+impl Foo for () {}
+  // ^^^ trait_impl
+"#,
+        );
+    }
+
     #[test]
     fn test_hl_self_in_crate_root() {
         check(
@@ -319,7 +418,7 @@ use self$0;
 mod foo;
 //- /foo.rs
 use self$0;
- // ^^^^
+ // ^^^^ import
 "#,
         );
     }
@@ -450,6 +549,75 @@ fn foo() ->$0 u32 {
         );
     }
 
+    #[test]
+    fn test_hl_exit_points_fn_keyword() {
+        check(
+            r#"
+fn$0 foo() -> u32 {
+    if true {
+        return 0;
+     // ^^^^^^
+    }
+
+    0?;
+  // ^
+    0xDEAD_BEEF
+ // ^^^^^^^^^^^
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_hl_fn_pointer_is_not_exit_point() {
+        let (analysis, pos) = fixture::position(
+            r#"
+fn foo(f: fn$0() -> u32) -> u32 {
+    return f();
+}
+"#,
+        );
+        assert!(analysis.highlight_related(pos).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_hl_exit_points_in_macro_calls() {
+        check(
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* }; }
+macro_rules! try_ { ($e:expr) => { $e? }; }
+fn$0 foo() -> u32 {
+    let x = id!(0?);
+              // ^
+    let y = try_!(0);
+         // ^^^^^^^^
+    id!(return 1);
+     // ^^^^^^
+    || id!(0?);
+    0
+ // ^
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn test_hl_yield_points_in_macro_calls() {
+        check(
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* }; }
+pub async$0 fn foo() {
+ // ^^^^^
+    id!(0.await);
+       // ^^^^^
+    let x = id!(async { 0.await });
+    id!(0).await
+        // ^^^^^
+}
+"#,
+        );
+    }
+
     #[test]
     fn test_hl_prefer_ref_over_tail_exit() {
         check(
@@ -463,7 +631,7 @@ fn foo() -> u32 {
     0?;
 
     foo$0()
- // ^^^
+ // ^^^ call
 }
 "#,
         );
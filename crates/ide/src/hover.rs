@@ -12,7 +12,7 @@ use ide_db::{
 use itertools::Itertools;
 use stdx::format_to;
 use syntax::{
-    algo, ast, display::fn_as_proc_macro_label, match_ast, AstNode, AstToken, Direction,
+    algo, ast, display::fn_as_proc_macro_label, match_ast, AstNode, AstToken, Direction, SmolStr,
     SyntaxKind::*, SyntaxToken, T,
 };
 
@@ -32,6 +32,7 @@ use crate::{
 pub struct HoverConfig {
     pub links_in_hover: bool,
     pub documentation: Option<HoverDocFormat>,
+    pub memory_layout: bool,
 }
 
 impl HoverConfig {
@@ -431,9 +432,29 @@ fn hover_for_definition(
             hir::ModuleDef::Module(it) => label_and_docs(db, it),
             hir::ModuleDef::Function(it) => label_and_docs(db, it),
             hir::ModuleDef::Adt(it) => label_and_docs(db, it),
-            hir::ModuleDef::Variant(it) => label_and_docs(db, it),
-            hir::ModuleDef::Const(it) => label_and_docs(db, it),
-            hir::ModuleDef::Static(it) => label_and_docs(db, it),
+            hir::ModuleDef::Variant(it) => label_value_and_docs(db, it, |&it| {
+                let is_c_like = it
+                    .parent_enum(db)
+                    .variants(db)
+                    .iter()
+                    .all(|variant| variant.kind(db) == hir::StructKind::Unit);
+                if is_c_like {
+                    it.eval(db).ok().map(render_const_value)
+                } else {
+                    None
+                }
+            }),
+            hir::ModuleDef::Const(it) => {
+                label_value_and_docs(db, it, |it| it.eval(db).ok().map(render_const_value))
+            }
+            // The value of a mutable static may change at runtime.
+            hir::ModuleDef::Static(it) => label_value_and_docs(db, it, |it| {
+                if it.is_mut(db) || it.is_extern(db) {
+                    None
+                } else {
+                    it.eval(db).ok().map(render_const_value)
+                }
+            }),
             hir::ModuleDef::Trait(it) => label_and_docs(db, it),
             hir::ModuleDef::TypeAlias(it) => label_and_docs(db, it),
             hir::ModuleDef::BuiltinType(it) => {
@@ -449,6 +470,10 @@ fn hover_for_definition(
         Definition::GenericParam(it) => label_and_docs(db, it),
         Definition::Label(it) => return Some(Markup::fenced_block(&it.name(db))),
     };
+    let label = match config.memory_layout.then(|| memory_layout(db, def)).flatten() {
+        Some(layout) => format!("{}\n{}", layout, label),
+        None => label,
+    };
 
     return hover_markup(
         docs.filter(|_| config.documentation.is_some()).map(Into::into),
@@ -461,9 +486,72 @@ fn hover_for_definition(
         D: HasAttrs + HirDisplay,
     {
         let label = def.display(db).to_string();
-        let docs = def.attrs(db).docs();
+        let attrs = def.attrs(db);
+        let docs = docs_with_aliases(attrs.docs(), &attrs.doc_aliases());
         (label, docs)
     }
+
+    fn label_value_and_docs<D>(
+        db: &RootDatabase,
+        def: D,
+        value: impl FnOnce(&D) -> Option<String>,
+    ) -> (String, Option<hir::Documentation>)
+    where
+        D: HasAttrs + HirDisplay,
+    {
+        let value = value(&def);
+        let (label, docs) = label_and_docs(db, def);
+        match value {
+            Some(value) => (format!("{} = {}", label, value), docs),
+            None => (label, docs),
+        }
+    }
+}
+
+fn render_const_value(value: hir::ComputedExpr) -> String {
+    match value.as_int() {
+        Some(it) => render_int(it),
+        None => value.to_string(),
+    }
+}
+
+/// Renders a non-negative integer in decimal, hexadecimal and binary.
+fn render_int(value: i128) -> String {
+    if value < 0 {
+        return value.to_string();
+    }
+    format!("{} ({:#X}, {:#b})", value, value, value)
+}
+
+/// Describes the size and alignment of a type, and the offset of a field in
+/// its struct, as a comment.
+fn memory_layout(db: &RootDatabase, def: Definition) -> Option<String> {
+    let (layout, offset) = match def {
+        Definition::ModuleDef(hir::ModuleDef::Adt(it)) => (it.layout(db)?, None),
+        Definition::Field(it) => (it.ty(db).layout(db)?, it.offset(db)),
+        _ => return None,
+    };
+    let mut res = format!("// size = {}, align = {}", layout.size, layout.align);
+    if let Some(offset) = offset {
+        format_to!(res, ", offset = {}", offset);
+    }
+    if layout.niches > 0 {
+        format_to!(res, ", niches = {}", layout.niches);
+    }
+    Some(res)
+}
+
+/// Appends the `#[doc(alias)]`es of an item to its documentation.
+fn docs_with_aliases(
+    docs: Option<hir::Documentation>,
+    aliases: &[SmolStr],
+) -> Option<hir::Documentation> {
+    if aliases.is_empty() {
+        return docs;
+    }
+    let mut buf = docs.map_or_else(String::new, |it| format!("{}\n\n", it.as_str()));
+    format_to!(buf, "Aliases: {}", aliases.iter().map(|it| format!("`{}`", it)).join(", "));
+    Some(hir::Documentation::new(buf))
 }
 
 fn hover_for_local(it: hir::Local, db: &RootDatabase) -> Option<Markup> {
@@ -542,6 +630,7 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    memory_layout: false,
                 },
                 position,
             )
@@ -556,6 +645,7 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    memory_layout: false,
                 },
                 position,
             )
@@ -576,6 +666,7 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: false,
                     documentation: Some(HoverDocFormat::Markdown),
+                    memory_layout: false,
                 },
                 position,
             )
@@ -596,6 +687,7 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::PlainText),
+                    memory_layout: false,
                 },
                 position,
             )
@@ -609,6 +701,18 @@ mod tests {
         expect.assert_eq(&actual)
     }
 
+    fn check_memory_layout(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let hover = analysis
+            .hover(
+                &HoverConfig { links_in_hover: true, documentation: None, memory_layout: true },
+                position,
+            )
+            .unwrap()
+            .unwrap();
+        expect.assert_eq(&hover.info.markup.to_string())
+    }
+
     fn check_actions(ra_fixture: &str, expect: Expect) {
         let (analysis, position) = fixture::position(ra_fixture);
         let hover = analysis
@@ -616,6 +720,7 @@ mod tests {
                 &HoverConfig {
                     links_in_hover: true,
                     documentation: Some(HoverDocFormat::Markdown),
+                    memory_layout: false,
                 },
                 position,
             )
@@ -913,7 +1018,7 @@ fn main() {
                 ```
 
                 ```rust
-                const foo: u32
+                const foo: u32 = 123 (0x7B, 0b1111011)
                 ```
             "#]],
         );
@@ -927,7 +1032,143 @@ fn main() {
                 ```
 
                 ```rust
-                static foo: u32
+                static foo: u32 = 456 (0x1C8, 0b111001000)
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_const_eval() {
+        check(
+            r#"
+const fn double(x: u32) -> u32 { x * 2 }
+const FOO$0: u32 = double(BAR) + 1;
+const BAR: u32 = 10;
+"#,
+            expect![[r#"
+                *FOO*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const FOO: u32 = 21 (0x15, 0b10101)
+                ```
+            "#]],
+        );
+        check(
+            r#"const FOO$0: (i32, bool) = (-1, true);"#,
+            expect![[r#"
+                *FOO*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                const FOO: (i32, bool) = (-1, true)
+                ```
+            "#]],
+        );
+        check(
+            r#"static mut FOO$0: u32 = 1;"#,
+            expect![[r#"
+                *FOO*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                static mut FOO: u32
+                ```
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hover_enum_discriminant() {
+        check(
+            r#"
+enum E { A = -2, B, C = (16), D$0 }
+"#,
+            expect![[r#"
+                *D*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                D = 17 (0x11, 0b10001)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum E { A = -2, B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B = -1
+                ```
+            "#]],
+        );
+        check(
+            r#"
+const X: isize = 1;
+enum E { A = X << 2, B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B = 5 (0x5, 0b101)
+                ```
+            "#]],
+        );
+        check(
+            r#"
+enum E { A(u8), B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B
+                ```
+            "#]],
+        );
+        check(
+            r#"
+#[repr(u8)]
+enum E { A = 255, B$0 }
+"#,
+            expect![[r#"
+                *B*
+
+                ```rust
+                test::E
+                ```
+
+                ```rust
+                B
                 ```
             "#]],
         );
@@ -1008,7 +1249,7 @@ enum Option<T> {
                 ```
 
                 ```rust
-                None
+                None = 0 (0x0, 0b0)
                 ```
 
                 ---
@@ -1196,7 +1437,7 @@ fn main() {
                 ```
 
                 ```rust
-                const C: u32
+                const C: u32 = 1 (0x1, 0b1)
                 ```
             "#]],
         )
@@ -1721,6 +1962,37 @@ fn foo() { let bar = Ba$0r; }
         );
     }
 
+    #[test]
+    fn test_hover_doc_aliases() {
+        check(
+            r#"
+/// A bar.
+#[doc(alias("Pub", "Tavern"))]
+#[doc(alias = "Counter")]
+struct Bar;
+
+fn foo() { let bar = Ba$0r; }
+"#,
+            expect![[r#"
+                *Bar*
+
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Bar
+                ```
+
+                ---
+
+                A bar.
+
+                Aliases: `Pub`, `Tavern`, `Counter`
+            "#]],
+        );
+    }
+
     #[test]
     fn test_hover_struct_doc_attr() {
         check(
@@ -3758,7 +4030,7 @@ fn foo() {
                 ```
 
                 ```rust
-                const FOO: usize
+                const FOO: usize = 3 (0x3, 0b11)
                 ```
 
                 ---
@@ -4162,4 +4434,85 @@ struct Foo;
             "#]],
         )
     }
+
+    #[test]
+    fn hover_memory_layout_struct() {
+        check_memory_layout(
+            r#"
+struct Foo$0 { a: u8, b: u32, c: u16 }
+"#,
+            expect![[r#"
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 8, align = 4
+                struct Foo
+                ```"#]],
+        );
+        check_memory_layout(
+            r#"
+struct Foo { a$0: u8, b: u32, c: u16 }
+"#,
+            expect![[r#"
+                ```rust
+                test::Foo
+                ```
+
+                ```rust
+                // size = 1, align = 1, offset = 6
+                a: u8
+                ```"#]],
+        );
+    }
+
+    #[test]
+    fn hover_memory_layout_enum_niches() {
+        check_memory_layout(
+            r#"
+enum Foo$0 { A(bool), B, C }
+"#,
+            expect![[r#"
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 1, align = 1, niches = 252
+                enum Foo
+                ```"#]],
+        );
+        check_memory_layout(
+            r#"
+enum Foo$0 { None, Some(&'static u32) }
+"#,
+            expect![[r#"
+                ```rust
+                test
+                ```
+
+                ```rust
+                // size = 8, align = 8
+                enum Foo
+                ```"#]],
+        );
+    }
+
+    #[test]
+    fn hover_memory_layout_generic() {
+        check_memory_layout(
+            r#"
+struct Foo$0<T> { a: T }
+"#,
+            expect![[r#"
+                ```rust
+                test
+                ```
+
+                ```rust
+                struct Foo<T>
+                ```"#]],
+        );
+    }
 }
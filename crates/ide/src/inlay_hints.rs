@@ -1,11 +1,17 @@
 use either::Either;
 use hir::{known, Callable, HirDisplay, Semantics};
-use ide_db::helpers::FamousDefs;
-use ide_db::RootDatabase;
+use ide_db::{
+    base_db::FileRange,
+    defs::Definition,
+    helpers::FamousDefs,
+    search::{FileReference, ReferenceCategory, SearchScope},
+    RootDatabase,
+};
 use stdx::to_lower_snake_case;
 use syntax::{
     ast::{self, ArgListOwner, AstNode, NameOwner},
-    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, TextRange, T,
+    match_ast, Direction, NodeOrToken, SmolStr, SyntaxKind, SyntaxToken, TextRange, TextSize,
+    WalkEvent, T,
 };
 
 use crate::FileId;
@@ -15,6 +21,8 @@ pub struct InlayHintsConfig {
     pub type_hints: bool,
     pub parameter_hints: bool,
     pub chaining_hints: bool,
+    pub implicit_drop_hints: bool,
+    pub lifetime_elision_hints: LifetimeElisionHints,
     pub max_length: Option<usize>,
 }
 
@@ -23,6 +31,16 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ImplicitDropHint,
+    LifetimeHint,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifetimeElisionHints {
+    Always,
+    /// Only show the hints of functions returning a reference with an elided lifetime.
+    SkipTrivial,
+    Never,
 }
 
 #[derive(Debug)]
@@ -42,6 +60,22 @@ pub struct InlayHint {
 // * types of local variables
 // * names of function arguments
 // * types of chained expressions
+// * values dropped at the end of their scope (disabled by default)
+// * lifetimes elided in function signatures (disabled by default)
+//
+// Drop hints are shown before the closing brace of a block, for the local variables and function
+// parameters whose values are dropped there, in the order they are dropped. A `return` also gets
+// hints for the values of all the blocks it leaves, and the semicolon of an expression statement
+// for the temporary values of calls which are only borrowed. Values of `Copy` types and references
+// don't need to be dropped, and values moved out are dropped wherever they were moved to, so
+// neither get a hint. Whether a value is moved is decided from its usages only, a value moved in
+// a single branch of an `if` is considered moved.
+//
+// Lifetime elision hints name the lifetimes the compiler infers for the references without a
+// lifetime in function signatures `'0`, `'1` and so on, and show them as generic parameters of the
+// function. The lifetime parameters of structs, enums, unions and type aliases left out of their
+// paths are named too. With the "skip trivial" setting, they are only shown for functions
+// returning such a reference or path.
 //
 // **Note:** VS Code does not have native support for inlay hints https://github.com/microsoft/vscode/issues/16221[yet] and the hints are implemented using decorations.
 // This approach has limitations, the caret movement and bracket highlighting near the edges of the hint may be weird:
@@ -74,6 +108,10 @@ pub(crate) fn inlay_hints(
                 ast::CallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::MethodCallExpr(it) => { get_param_name_hints(&mut res, &sema, config, ast::Expr::from(it)); },
                 ast::IdentPat(it) => { get_bind_pat_hints(&mut res, &sema, config, it); },
+                ast::BlockExpr(it) => { get_implicit_drop_hints(&mut res, &sema, config, file_id, it); },
+                ast::ReturnExpr(it) => { get_return_drop_hints(&mut res, &sema, config, file_id, it); },
+                ast::ExprStmt(it) => { get_temporary_drop_hints(&mut res, &sema, config, it); },
+                ast::Fn(it) => { get_lifetime_elision_hints(&mut res, &sema, config, it); },
                 _ => (),
             }
         }
@@ -140,6 +178,411 @@ fn get_chaining_hints(
     Some(())
 }
 
+fn get_implicit_drop_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    file_id: FileId,
+    block: ast::BlockExpr,
+) -> Option<()> {
+    if !config.implicit_drop_hints {
+        return None;
+    }
+    let r_curly = block.r_curly_token()?;
+    for name in dropped_bindings(sema, file_id, &block, r_curly.text_range().start()) {
+        acc.push(InlayHint {
+            range: r_curly.text_range(),
+            kind: InlayKind::ImplicitDropHint,
+            label: format!("drop({})", name).into(),
+        });
+    }
+    Some(())
+}
+
+/// Adds hints for the values of all the blocks a `return` leaves.
+fn get_return_drop_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    file_id: FileId,
+    return_expr: ast::ReturnExpr,
+) -> Option<()> {
+    if !config.implicit_drop_hints {
+        return None;
+    }
+    let return_token = return_expr.return_token()?;
+    let start = return_token.text_range().start();
+    let blocks = return_expr
+        .syntax()
+        .ancestors()
+        .take_while(|it| !matches!(it.kind(), SyntaxKind::FN | SyntaxKind::CLOSURE_EXPR))
+        .filter_map(ast::BlockExpr::cast);
+    for block in blocks {
+        for name in dropped_bindings(sema, file_id, &block, start) {
+            acc.push(InlayHint {
+                range: return_token.text_range(),
+                kind: InlayKind::ImplicitDropHint,
+                label: format!("drop({})", name).into(),
+            });
+        }
+    }
+    Some(())
+}
+
+/// Adds hints for the temporaries of an expression statement, which are dropped at its end.
+fn get_temporary_drop_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    stmt: ast::ExprStmt,
+) -> Option<()> {
+    if !config.implicit_drop_hints {
+        return None;
+    }
+    let semicolon = stmt.semicolon_token()?;
+    let temporaries = stmt
+        .syntax()
+        .descendants()
+        .filter(|node| {
+            // Blocks and closures have their own statements.
+            !node.ancestors().take_while(|it| it != stmt.syntax()).any(|it| {
+                it != *node
+                    && matches!(it.kind(), SyntaxKind::BLOCK_EXPR | SyntaxKind::CLOSURE_EXPR)
+            })
+        })
+        .filter_map(ast::Expr::cast)
+        .filter(|expr| matches!(expr, ast::Expr::CallExpr(_) | ast::Expr::MethodCallExpr(_)))
+        .filter(|expr| {
+            let dropped_by_stmt =
+                expr.syntax().parent().map_or(false, |it| it.kind() == SyntaxKind::EXPR_STMT);
+            dropped_by_stmt || !is_moved(sema, expr)
+        })
+        .filter(|expr| {
+            sema.type_of_expr(expr).map_or(false, |ty| {
+                !ty.is_unknown()
+                    && !ty.is_unit()
+                    && !ty.is_copy(sema.db)
+                    && ty.remove_ref().is_none()
+            })
+        })
+        .collect::<Vec<_>>();
+    // Temporaries are dropped in the reverse order of their creation, and a call creates its
+    // value after the ones of its arguments.
+    for expr in temporaries.into_iter().rev() {
+        let text = expr.syntax().text().to_string();
+        if text.contains('\n') {
+            continue;
+        }
+        acc.push(InlayHint {
+            range: semicolon.text_range(),
+            kind: InlayKind::ImplicitDropHint,
+            label: format!("drop({})", text).into(),
+        });
+    }
+    Some(())
+}
+
+/// Returns the names of the local variables declared in `block` before `end`, and of the
+/// parameters of the function whose body it is, whose values are dropped when leaving the block,
+/// in the order they are dropped.
+fn dropped_bindings(
+    sema: &Semantics<RootDatabase>,
+    file_id: FileId,
+    block: &ast::BlockExpr,
+    end: TextSize,
+) -> Vec<hir::Name> {
+    let params = block
+        .syntax()
+        .parent()
+        .and_then(ast::Fn::cast)
+        .and_then(|it| it.param_list())
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|it| it.pat());
+    let lets = block.statements().filter_map(|stmt| match stmt {
+        ast::Stmt::LetStmt(it) if it.syntax().text_range().end() <= end => it.pat(),
+        _ => None,
+    });
+    let bindings = params
+        .chain(lets)
+        .flat_map(|pat| {
+            pat.syntax().descendants().filter_map(ast::IdentPat::cast).collect::<Vec<_>>()
+        })
+        .filter(|pat| pat.ref_token().is_none())
+        .collect::<Vec<_>>();
+
+    let search_range = FileRange { file_id, range: block.syntax().text_range() };
+    // Values are dropped in the reverse order of their declaration.
+    let mut res = Vec::new();
+    for pat in bindings.into_iter().rev() {
+        let local = match sema.to_def(&pat) {
+            Some(it) => it,
+            None => continue,
+        };
+        let ty = local.ty(sema.db);
+        if ty.is_unknown() || ty.is_copy(sema.db) || ty.remove_ref().is_some() {
+            continue;
+        }
+        let is_moved = Definition::Local(local)
+            .usages(sema)
+            .in_scope(SearchScope::file_range(search_range))
+            .all()
+            .into_iter()
+            .flat_map(|(_, refs)| refs)
+            .any(|reference| is_move(sema, &reference));
+        match local.name(sema.db) {
+            Some(it) if !is_moved => res.push(it),
+            _ => (),
+        }
+    }
+    res
+}
+
+/// Whether the value of a local is moved by the given usage.
+fn is_move(sema: &Semantics<RootDatabase>, reference: &FileReference) -> bool {
+    if reference.category == Some(ReferenceCategory::Write) {
+        return false;
+    }
+    let name_ref = match &reference.name {
+        ast::NameLike::NameRef(it) => it,
+        _ => return false,
+    };
+    let path_expr = match name_ref.syntax().ancestors().find_map(ast::PathExpr::cast) {
+        Some(it) => it,
+        None => return false,
+    };
+    if path_expr.path().and_then(|it| it.as_single_name_ref()).as_ref() != Some(name_ref) {
+        return false;
+    }
+    is_moved(sema, &ast::Expr::PathExpr(path_expr))
+}
+
+/// Whether the value of `expr` is moved, rather than only borrowed, by its parent.
+fn is_moved(sema: &Semantics<RootDatabase>, expr: &ast::Expr) -> bool {
+    let parent = match expr.syntax().parent() {
+        Some(it) => it,
+        None => return false,
+    };
+    match_ast! {
+        match parent {
+            ast::RefExpr(_) => false,
+            ast::FieldExpr(_) => false,
+            ast::IndexExpr(_) => false,
+            ast::MatchExpr(_) => false,
+            ast::MethodCallExpr(it) => {
+                let is_receiver = it.receiver().map_or(false, |it| it.syntax() == expr.syntax());
+                !is_receiver || sema
+                    .resolve_method_call(&it)
+                    .and_then(|func| func.self_param(sema.db))
+                    .map_or(false, |param| matches!(param.access(sema.db), hir::Access::Owned))
+            },
+            _ => true,
+        }
+    }
+}
+
+fn get_lifetime_elision_hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<RootDatabase>,
+    config: &InlayHintsConfig,
+    func: ast::Fn,
+) -> Option<()> {
+    if config.lifetime_elision_hints == LifetimeElisionHints::Never {
+        return None;
+    }
+    let param_list = func.param_list()?;
+
+    // `self: &Self` and `self: &Foo` in `impl Foo` are references to `self` too.
+    let self_is_ref = sema
+        .to_def(&func)?
+        .self_param(sema.db)
+        .map_or(false, |it| !matches!(it.access(sema.db), hir::Access::Owned));
+    let self_positions = match param_list.self_param() {
+        Some(it) => match (it.amp_token(), it.ty()) {
+            (Some(amp), _) => vec![ref_lifetime_position(amp, it.lifetime())],
+            (None, Some(ty)) => lifetime_positions(sema, &ty),
+            (None, None) => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    let inputs = self_positions
+        .into_iter()
+        .chain(
+            param_list
+                .params()
+                .filter_map(|it| it.ty())
+                .flat_map(|ty| lifetime_positions(sema, &ty)),
+        )
+        .collect::<Vec<_>>();
+    let outputs = func
+        .ret_type()
+        .and_then(|it| it.ty())
+        .map_or_else(Vec::new, |ty| lifetime_positions(sema, &ty))
+        .into_iter()
+        .filter(|it| !matches!(it, LifetimePosition::Explicit(_)))
+        .collect::<Vec<_>>();
+    if config.lifetime_elision_hints == LifetimeElisionHints::SkipTrivial && outputs.is_empty() {
+        return None;
+    }
+
+    // Elided output lifetimes get the lifetime of `self` if it is a reference, or else the one of
+    // the only input lifetime position. In both cases, that is the first input position.
+    let output_position = if self_is_ref || inputs.len() == 1 { inputs.first() } else { None };
+    let output_label = match output_position {
+        Some(LifetimePosition::Explicit(lifetime)) => lifetime
+            .lifetime_ident_token()
+            .map(|it| SmolStr::from(it.text()))
+            .filter(|it| it != "'_"),
+        Some(_) => Some(SmolStr::from("'0")),
+        None => None,
+    };
+
+    let elided_inputs =
+        inputs.iter().filter(|it| !matches!(it, LifetimePosition::Explicit(_))).collect::<Vec<_>>();
+    if !elided_inputs.is_empty() {
+        let names =
+            (0..elided_inputs.len()).map(|idx| format!("'{}", idx)).collect::<Vec<_>>().join(", ");
+        match func.generic_param_list().and_then(|it| it.l_angle_token()) {
+            Some(l_angle) => acc.push(InlayHint {
+                range: l_angle.text_range(),
+                kind: InlayKind::LifetimeHint,
+                label: format!("{},", names).into(),
+            }),
+            None => acc.push(InlayHint {
+                range: func.name()?.syntax().text_range(),
+                kind: InlayKind::LifetimeHint,
+                label: format!("<{}>", names).into(),
+            }),
+        }
+    }
+    let input_labels = (0..elided_inputs.len()).map(|idx| SmolStr::from(format!("'{}", idx)));
+    push_lifetime_hints(acc, elided_inputs.into_iter().zip(input_labels));
+    if let Some(label) = output_label {
+        push_lifetime_hints(acc, outputs.iter().map(|it| (it, label.clone())));
+    }
+    Some(())
+}
+
+/// Adds the hints of the elided lifetimes, the ones hidden in the same path being shown together.
+fn push_lifetime_hints<'a>(
+    acc: &mut Vec<InlayHint>,
+    positions: impl Iterator<Item = (&'a LifetimePosition, SmolStr)>,
+) {
+    let mut hidden: Option<(&ast::PathSegment, Vec<SmolStr>)> = None;
+    for (position, label) in positions {
+        match position {
+            LifetimePosition::Elided(amp) => {
+                push_hidden_lifetimes_hint(acc, hidden.take());
+                acc.push(InlayHint {
+                    range: amp.text_range(),
+                    kind: InlayKind::LifetimeHint,
+                    label,
+                });
+            }
+            LifetimePosition::Hidden(segment) => match &mut hidden {
+                Some((it, labels)) if *it == segment => labels.push(label),
+                _ => {
+                    push_hidden_lifetimes_hint(acc, hidden.take());
+                    hidden = Some((segment, vec![label]));
+                }
+            },
+            LifetimePosition::Explicit(_) => (),
+        }
+    }
+    push_hidden_lifetimes_hint(acc, hidden);
+}
+
+fn push_hidden_lifetimes_hint(
+    acc: &mut Vec<InlayHint>,
+    hidden: Option<(&ast::PathSegment, Vec<SmolStr>)>,
+) {
+    let (segment, labels) = match hidden {
+        Some(it) => it,
+        None => return,
+    };
+    let labels = labels.join(", ");
+    let (range, label) = match segment.generic_arg_list().and_then(|it| it.l_angle_token()) {
+        Some(l_angle) => (l_angle.text_range(), format!("{},", labels)),
+        None => (segment.syntax().text_range(), format!("<{}>", labels)),
+    };
+    acc.push(InlayHint { range, kind: InlayKind::LifetimeHint, label: label.into() });
+}
+
+/// A place where a lifetime is given in a function signature.
+enum LifetimePosition {
+    /// A reference without a lifetime, with its `&` token.
+    Elided(SyntaxToken),
+    /// A lifetime parameter of a struct, enum, union or type alias left out of its path, which
+    /// is there once per lifetime parameter.
+    Hidden(ast::PathSegment),
+    Explicit(ast::Lifetime),
+}
+
+fn ref_lifetime_position(amp: SyntaxToken, lifetime: Option<ast::Lifetime>) -> LifetimePosition {
+    match lifetime {
+        Some(it) => LifetimePosition::Explicit(it),
+        None => LifetimePosition::Elided(amp),
+    }
+}
+
+fn lifetime_positions(sema: &Semantics<RootDatabase>, ty: &ast::Type) -> Vec<LifetimePosition> {
+    let mut res = Vec::new();
+    let mut preorder = ty.syntax().preorder();
+    while let Some(event) = preorder.next() {
+        let node = match event {
+            WalkEvent::Enter(it) => it,
+            WalkEvent::Leave(_) => continue,
+        };
+        if let Some(it) = ast::RefType::cast(node.clone()) {
+            res.extend(it.amp_token().map(|amp| ref_lifetime_position(amp, it.lifetime())));
+        } else if let Some(it) = ast::PathType::cast(node.clone()) {
+            res.extend(hidden_lifetime_positions(sema, &it));
+        } else if let Some(it) = ast::LifetimeArg::cast(node.clone()) {
+            res.extend(it.lifetime().map(LifetimePosition::Explicit));
+        } else if matches!(
+            node.kind(),
+            SyntaxKind::FN_PTR_TYPE | SyntaxKind::PARAM_LIST | SyntaxKind::RET_TYPE
+        ) {
+            // Function pointers and `Fn` traits have lifetimes of their own.
+            preorder.skip_subtree();
+        }
+    }
+    res
+}
+
+/// Returns the lifetime parameters of the type `ty` refers to when its path leaves them out,
+/// like in `Ref` for `type Ref<'a> = &'a u32;`. `Self` has no such lifetimes, it stands for
+/// the self type of the impl with the lifetimes of the impl.
+fn hidden_lifetime_positions(
+    sema: &Semantics<RootDatabase>,
+    ty: &ast::PathType,
+) -> Vec<LifetimePosition> {
+    let path = match ty.path() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let segment = match path.segment() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let has_lifetime_args = segment.generic_arg_list().map_or(false, |it| {
+        it.generic_args().any(|it| matches!(it, ast::GenericArg::LifetimeArg(_)))
+    });
+    if has_lifetime_args {
+        return Vec::new();
+    }
+    let def: hir::GenericDef = match sema.resolve_path(&path) {
+        Some(hir::PathResolution::Def(hir::ModuleDef::Adt(it))) => it.into(),
+        Some(hir::PathResolution::Def(hir::ModuleDef::TypeAlias(it))) => it.into(),
+        _ => return Vec::new(),
+    };
+    def.params(sema.db)
+        .into_iter()
+        .filter(|it| matches!(it, hir::GenericParam::LifetimeParam(_)))
+        .map(|_| LifetimePosition::Hidden(segment.clone()))
+        .collect()
+}
+
 fn get_param_name_hints(
     acc: &mut Vec<InlayHint>,
     sema: &Semantics<RootDatabase>,
@@ -436,12 +879,17 @@ mod tests {
     use expect_test::{expect, Expect};
     use test_utils::extract_annotations;
 
-    use crate::{fixture, inlay_hints::InlayHintsConfig};
+    use crate::{
+        fixture,
+        inlay_hints::{InlayHintsConfig, LifetimeElisionHints},
+    };
 
     const TEST_CONFIG: InlayHintsConfig = InlayHintsConfig {
         type_hints: true,
         parameter_hints: true,
         chaining_hints: true,
+        implicit_drop_hints: false,
+        lifetime_elision_hints: LifetimeElisionHints::Never,
         max_length: None,
     };
 
@@ -455,6 +903,8 @@ mod tests {
                 parameter_hints: true,
                 type_hints: false,
                 chaining_hints: false,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             ra_fixture,
@@ -467,6 +917,8 @@ mod tests {
                 parameter_hints: false,
                 type_hints: true,
                 chaining_hints: false,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             ra_fixture,
@@ -479,6 +931,45 @@ mod tests {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
+                max_length: None,
+            },
+            ra_fixture,
+        );
+    }
+
+    fn check_drops(ra_fixture: &str) {
+        let config = InlayHintsConfig {
+            parameter_hints: false,
+            type_hints: false,
+            chaining_hints: false,
+            implicit_drop_hints: true,
+            lifetime_elision_hints: LifetimeElisionHints::Never,
+            max_length: None,
+        };
+        // The hints of a block are computed before the ones of the blocks it contains.
+        let (analysis, file_id) = fixture::file(&ra_fixture);
+        let mut expected = extract_annotations(&*analysis.file_text(file_id).unwrap());
+        expected.sort_by_key(|(range, _)| range.start());
+        let mut actual = analysis
+            .inlay_hints(&config, file_id)
+            .unwrap()
+            .into_iter()
+            .map(|it| (it.range, it.label.to_string()))
+            .collect::<Vec<_>>();
+        actual.sort_by_key(|(range, _)| range.start());
+        assert_eq!(expected, actual, "\nExpected:\n{:#?}\n\nActual:\n{:#?}", expected, actual);
+    }
+
+    fn check_lifetimes(lifetime_elision_hints: LifetimeElisionHints, ra_fixture: &str) {
+        check_with_config(
+            InlayHintsConfig {
+                parameter_hints: false,
+                type_hints: false,
+                chaining_hints: false,
+                implicit_drop_hints: false,
+                lifetime_elision_hints,
                 max_length: None,
             },
             ra_fixture,
@@ -507,6 +998,8 @@ mod tests {
                 type_hints: false,
                 parameter_hints: false,
                 chaining_hints: false,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             r#"
@@ -1198,6 +1691,8 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             r#"
@@ -1254,6 +1749,8 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             r#"
@@ -1298,6 +1795,8 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             r#"
@@ -1343,6 +1842,8 @@ fn main() {
                 parameter_hints: false,
                 type_hints: false,
                 chaining_hints: true,
+                implicit_drop_hints: false,
+                lifetime_elision_hints: LifetimeElisionHints::Never,
                 max_length: None,
             },
             r#"
@@ -1392,4 +1893,173 @@ fn main() {
             "#]],
         );
     }
+
+    #[test]
+    fn implicit_drops() {
+        check_drops(
+            r#"
+//- minicore: copy
+struct S;
+impl S {
+    fn by_ref(&self) {}
+    fn by_value(self) {}
+}
+fn consume(_: S) {}
+fn main() {
+    {
+        let a = S;
+        let (b, c) = (S, S);
+        let d = 92;
+        let e = &a;
+        b.by_ref();
+        c.by_value();
+        let moved = S;
+        consume(moved);
+    }
+  //^ drop(b)
+  //^ drop(a)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn implicit_drops_of_params() {
+        check_drops(
+            r#"
+//- minicore: copy
+struct S;
+fn consume(_: S) {}
+impl S {
+    fn f(self, p: S, q: S, n: u32) {
+        let r;
+        r = S;
+        consume(q);
+    }
+  //^ drop(r)
+  //^ drop(p)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn implicit_drops_on_return() {
+        check_drops(
+            r#"
+//- minicore: copy
+struct S;
+impl S {
+    fn f(self, p: S, flag: bool) {
+        let a = S;
+        if flag {
+            let b = S;
+            return;
+          //^^^^^^ drop(b)
+          //^^^^^^ drop(a)
+          //^^^^^^ drop(p)
+        }
+      //^ drop(b)
+        let c = S;
+    }
+  //^ drop(c)
+  //^ drop(a)
+  //^ drop(p)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn implicit_drops_of_temporaries() {
+        check_drops(
+            r#"
+//- minicore: copy
+struct S;
+impl S {
+    fn by_ref(&self) -> u32 { 0 }
+    fn by_value(self) {}
+}
+fn make() -> S { S }
+fn main() {
+    make().by_ref();
+                  //^ drop(make())
+    make().by_value();
+    make();
+        //^ drop(make())
+    let n = make().by_ref();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_elision_hints() {
+        check_lifetimes(
+            LifetimeElisionHints::Always,
+            r#"
+fn foo(a: &u32, b: &mut u32) {}
+ //^^^ <'0, '1>
+        //^ '0
+                 //^ '1
+struct S;
+impl S {
+    fn get(&self, other: &u32) -> &u32 { loop {} }
+     //^^^ <'0, '1>
+         //^ '0
+                       //^ '1
+                                //^ '0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_elision_hints_with_explicit_lifetimes() {
+        check_lifetimes(
+            LifetimeElisionHints::Always,
+            r#"
+fn foo<'a, T>(a: &'a T, b: &T, f: fn(&u32) -> &u32) -> &'a T { loop {} }
+    //^ '0,
+                         //^ '0
+fn bar(a: &'static u32) -> &u32 { a }
+                         //^ 'static
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_elision_hints_in_paths() {
+        check_lifetimes(
+            LifetimeElisionHints::Always,
+            r#"
+struct Wrap<'a>(&'a u32);
+type Ref<'a> = &'a u32;
+fn alias(r: Ref) -> &u32 { loop {} }
+ //^^^^^ <'0>
+          //^^^ <'0>
+                  //^ '0
+fn wrap(w: Wrap) -> Wrap { loop {} }
+ //^^^^ <'0>
+         //^^^^ <'0>
+                  //^^^^ <'0>
+fn explicit(w: Wrap<'static>) -> Wrap { loop {} }
+                               //^^^^ <'static>
+"#,
+        );
+    }
+
+    #[test]
+    fn lifetime_elision_hints_skip_trivial() {
+        check_lifetimes(
+            LifetimeElisionHints::SkipTrivial,
+            r#"
+fn trivial(a: &u32) {}
+fn first(a: &u32) -> &u32 { a }
+ //^^^^^ <'0>
+          //^ '0
+                   //^ '0
+"#,
+        );
+    }
 }
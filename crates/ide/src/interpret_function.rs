@@ -0,0 +1,125 @@
+use hir::{Function, Semantics};
+use ide_db::base_db::FilePosition;
+use ide_db::RootDatabase;
+use syntax::{algo::find_node_at_offset, ast, AstNode};
+
+// Feature: Interpret Function
+//
+// Evaluates the body of the function containing the cursor, without building
+// the crate. Only functions without parameters can be evaluated, and only a
+// subset of the language is supported: integers, booleans, tuples, local
+// variables, control flow and calls of other functions.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Rust Analyzer: Interpret Function**
+// |===
+pub(crate) fn interpret_function(db: &RootDatabase, position: FilePosition) -> String {
+    interpret(db, position).unwrap_or_else(|| "Not inside a function body".to_string())
+}
+
+fn interpret(db: &RootDatabase, position: FilePosition) -> Option<String> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(position.file_id);
+
+    let function = find_node_at_offset::<ast::Fn>(source_file.syntax(), position.offset)?;
+    function.body()?;
+
+    let function: Function = sema.to_def(&function)?;
+    let name = function.name(db);
+    let res = match function.eval(db) {
+        Ok(value) => format!("{}() = {}", name, value),
+        Err(err) => format!("Failed to evaluate {}(): {}", name, err),
+    };
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let res = analysis.interpret_function(position).unwrap();
+        expect.assert_eq(&res);
+    }
+
+    #[test]
+    fn arithmetic_and_locals() {
+        check(
+            r#"
+fn foo() -> i32 {
+    let x = 40;
+    let (a, b) = (1, 1);
+    x + a * b + $01
+}
+"#,
+            expect![["foo() = 42"]],
+        );
+    }
+
+    #[test]
+    fn loops_and_calls() {
+        check(
+            r#"
+fn factorial(n: u64) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    n * factorial(n - 1)
+}
+
+fn sum() -> (u64, u64) {
+    let mut i = 0;
+    let mut total = 0;
+    while i < 5 {
+        i += 1;
+        total += i;
+    }
+    (total, $0factorial(5))
+}
+"#,
+            expect![["sum() = (15, 120)"]],
+        );
+    }
+
+    #[test]
+    fn overflow_panics() {
+        check(
+            r#"
+fn foo() -> u8 {
+    let x: u8 = 200;
+    x + $0100
+}
+"#,
+            expect![[
+                "Failed to evaluate foo(): evaluation panicked: attempt to add with overflow"
+            ]],
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        check(
+            r#"
+fn foo(x: i32) -> i32 {
+    x$0
+}
+"#,
+            expect![["Failed to evaluate foo(): functions with parameters are not supported yet"]],
+        );
+    }
+
+    #[test]
+    fn not_in_function() {
+        check(
+            r#"
+const X: i32 = $01;
+"#,
+            expect![["Not inside a function body"]],
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::convert::TryFrom;
+use std::{convert::TryFrom, iter::successors};
 
 use ide_assists::utils::extract_trivial_expression;
 use itertools::Itertools;
@@ -6,7 +6,9 @@ use syntax::{
     algo::non_trivia_sibling,
     ast::{self, AstNode, AstToken, IsString},
     Direction, NodeOrToken, SourceFile, SyntaxElement,
-    SyntaxKind::{self, USE_TREE, WHITESPACE},
+    SyntaxKind::{
+        self, AWAIT_EXPR, COMMENT, FIELD_EXPR, METHOD_CALL_EXPR, TRY_EXPR, USE_TREE, WHITESPACE,
+    },
     SyntaxNode, SyntaxToken, TextRange, TextSize, T,
 };
 
@@ -16,6 +18,7 @@ pub struct JoinLinesConfig {
     pub join_else_if: bool,
     pub remove_trailing_comma: bool,
     pub unwrap_trivial_blocks: bool,
+    pub join_method_chains: bool,
 }
 
 // Feature: Join Lines
@@ -188,6 +191,22 @@ fn remove_newline(
         }
     }
 
+    if config.join_method_chains && matches!(next.kind(), T![.] | T![?]) {
+        // Collapses the whole chain at once:
+        //
+        // ```
+        // foo$0
+        //     .bar()?
+        //     .baz
+        //     .await
+        // ```
+        //
+        // into `foo.bar()?.baz.await`
+        if join_method_chain(edit, token).is_some() {
+            return;
+        }
+    }
+
     if let (Some(_), Some(next)) = (
         prev.as_token().cloned().and_then(ast::Comment::cast),
         next.as_token().cloned().and_then(ast::Comment::cast),
@@ -240,6 +259,44 @@ fn join_single_use_tree(edit: &mut TextEditBuilder, token: &SyntaxToken) -> Opti
     Some(())
 }
 
+fn join_method_chain(edit: &mut TextEditBuilder, token: &SyntaxToken) -> Option<()> {
+    let mut chain = token.parent().filter(is_chain_link)?;
+    while let Some(parent) = chain.parent() {
+        if !is_chain_link(&parent) || parent.first_child().as_ref() != Some(&chain) {
+            break;
+        }
+        chain = parent;
+    }
+
+    let mut line_breaks = Vec::new();
+    let links = successors(Some(chain), |it| it.first_child().filter(is_chain_link));
+    for link in links {
+        for token in link.children_with_tokens().filter_map(|it| it.into_token()) {
+            match token.kind() {
+                // Joining would comment out the rest of the chain.
+                COMMENT => return None,
+                WHITESPACE if token.text().contains('\n') => {
+                    let next = token.next_sibling_or_token().map(|it| it.kind());
+                    if matches!(next, Some(T![.]) | Some(T![?])) {
+                        line_breaks.push(token.text_range());
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    for range in line_breaks {
+        if !edit.invalidates_offset(range.start()) {
+            edit.delete(range);
+        }
+    }
+    Some(())
+}
+
+fn is_chain_link(node: &SyntaxNode) -> bool {
+    matches!(node.kind(), METHOD_CALL_EXPR | FIELD_EXPR | AWAIT_EXPR | TRY_EXPR)
+}
+
 fn as_if_expr(element: &SyntaxElement) -> Option<ast::IfExpr> {
     let mut node = element.as_node()?.clone();
     if let Some(stmt) = ast::ExprStmt::cast(node.clone()) {
@@ -265,7 +322,7 @@ fn compute_ws(left: SyntaxKind, right: SyntaxKind) -> &'static str {
                 return "";
             }
         }
-        T![.] => return "",
+        T![.] | T![?] => return "",
         _ => (),
     }
     " "
@@ -283,6 +340,7 @@ mod tests {
             join_else_if: true,
             remove_trailing_comma: true,
             unwrap_trivial_blocks: true,
+            join_method_chains: true,
         };
 
         let (before_cursor_pos, before) = extract_offset(ra_fixture_before);
@@ -308,6 +366,7 @@ mod tests {
             join_else_if: true,
             remove_trailing_comma: true,
             unwrap_trivial_blocks: true,
+            join_method_chains: true,
         };
 
         let (sel, before) = extract_range(ra_fixture_before);
@@ -758,6 +817,52 @@ fn foo() {
         );
     }
 
+    #[test]
+    fn test_join_lines_method_chain() {
+        check_join_lines(
+            r"
+async fn foo() {
+    let x = foo$0
+        .bar()?
+        .baz
+        .await;
+}",
+            r"
+async fn foo() {
+    let x = foo$0.bar()?.baz.await;
+}",
+        );
+        check_join_lines(
+            r"
+fn foo() {
+    foo()$0
+        ?
+        .bar();
+}",
+            r"
+fn foo() {
+    foo()$0?.bar();
+}",
+        );
+    }
+
+    #[test]
+    fn test_join_lines_method_chain_with_comment() {
+        check_join_lines(
+            r"
+fn foo() {
+    foo$0
+        .bar() // comment
+        .baz();
+}",
+            r"
+fn foo() {
+    foo$0.bar() // comment
+        .baz();
+}",
+        );
+    }
+
     #[test]
     fn test_join_lines_selection_lambda_block_body() {
         check_join_lines_sel(
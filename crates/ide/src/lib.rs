@@ -37,9 +37,11 @@ mod goto_implementation;
 mod goto_type_definition;
 mod hover;
 mod inlay_hints;
+mod interpret_function;
 mod join_lines;
 mod markdown_remove;
 mod matching_brace;
+mod moniker;
 mod move_item;
 mod parent_module;
 mod references;
@@ -49,21 +51,24 @@ mod ssr;
 mod status;
 mod syntax_highlighting;
 mod syntax_tree;
+mod type_hierarchy;
 mod typing;
+mod unused_public_items;
 mod view_crate_graph;
 mod view_hir;
 mod view_item_tree;
+mod view_memory_layout;
 
-use std::sync::Arc;
+use std::{error::Error, fmt, io, path::Path, sync::Arc};
 
 use cfg::CfgOptions;
 use ide_db::{
     base_db::{
-        salsa::{self, ParallelDatabase},
+        salsa::{self, Durability, ParallelDatabase},
         Env, FileLoader, FileSet, SourceDatabase, VfsPath,
     },
-    symbol_index::{self, FileSymbol},
-    LineIndexDatabase,
+    symbol_index::{self, FileSymbol, SymbolCache, SymbolsDatabase},
+    LineIndexDatabase, TokenCancelled,
 };
 use syntax::SourceFile;
 
@@ -74,22 +79,27 @@ pub use crate::{
     call_hierarchy::CallItem,
     display::navigation_target::NavigationTarget,
     expand_macro::ExpandedMacro,
-    file_structure::{StructureNode, StructureNodeKind},
+    file_structure::{FileStructureConfig, StructureNode, StructureNodeKind},
     folding_ranges::{Fold, FoldKind},
     highlight_related::HighlightedRange,
     hover::{HoverAction, HoverConfig, HoverDocFormat, HoverGotoTypeData, HoverResult},
-    inlay_hints::{InlayHint, InlayHintsConfig, InlayKind},
+    inlay_hints::{InlayHint, InlayHintsConfig, InlayKind, LifetimeElisionHints},
     join_lines::JoinLinesConfig,
     markup::Markup,
+    moniker::{MonikerIdentifier, MonikerKind, MonikerResult},
     move_item::Direction,
     prime_caches::PrimeCachesProgress,
     references::ReferenceSearchResult,
     rename::RenameError,
     runnables::{Runnable, RunnableKind, TestId},
+    ssr::SsrRuleMatches,
     syntax_highlighting::{
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
         HlRange,
     },
+    typing::TypingConfig,
+    view_crate_graph::{CrateGraphConfig, CrateGraphNode, CrateGraphView},
+    view_memory_layout::MemoryLayoutNode,
 };
 pub use hir::{Documentation, Semantics};
 pub use ide_assists::{
@@ -97,19 +107,20 @@ pub use ide_assists::{
 };
 pub use ide_completion::{
     CompletionConfig, CompletionItem, CompletionItemKind, CompletionRelevance, ImportEdit,
+    PostfixSnippet,
 };
 pub use ide_db::{
     base_db::{
-        Cancelled, Change, CrateGraph, CrateId, Edition, FileId, FilePosition, FileRange,
-        SourceRoot, SourceRootId,
+        Change, CrateGraph, CrateId, Edition, FileId, FilePosition, FileRange, SourceRoot,
+        SourceRootId,
     },
     call_info::CallInfo,
     label::Label,
     line_index::{LineCol, LineColUtf16, LineIndex},
-    search::{ReferenceAccess, SearchScope},
+    search::{ReferenceCategory, SearchScope},
     source_change::{FileSystemEdit, SourceChange},
     symbol_index::Query,
-    RootDatabase, SymbolKind,
+    CancellationToken, RootDatabase, SymbolKind,
 };
 pub use ide_diagnostics::{Diagnostic, DiagnosticsConfig, Severity};
 pub use ide_ssr::SsrError;
@@ -118,6 +129,26 @@ pub use text_edit::{Indel, TextEdit};
 
 pub type Cancellable<T> = Result<T, Cancelled>;
 
+/// The reason an `Analysis` call returned without a result.
+#[derive(Debug)]
+pub enum Cancelled {
+    /// The state of the world changed while the call was running.
+    Revision(ide_db::base_db::Cancelled),
+    /// The `CancellationToken` of the `Analysis` was cancelled.
+    Token,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancelled::Revision(it) => fmt::Display::fmt(it, f),
+            Cancelled::Token => f.write_str("cancelled"),
+        }
+    }
+}
+
+impl Error for Cancelled {}
+
 /// Info associated with a text range.
 #[derive(Debug)]
 pub struct RangeInfo<T> {
@@ -146,12 +177,36 @@ impl AnalysisHost {
         self.db.update_lru_capacity(lru_capacity);
     }
 
+    /// Persists the symbols of the files used for workspace symbol search in
+    /// `dir`, reusing the ones saved there by previous sessions.
+    pub fn set_symbol_cache_dir(&mut self, dir: Option<&Path>) {
+        let cache = dir.map(|dir| Arc::new(SymbolCache::new(dir)));
+        self.db.set_symbol_cache_with_durability(cache, Durability::HIGH);
+    }
+
+    /// Writes the symbols computed since the last save to the cache directory,
+    /// if any.
+    pub fn save_symbol_cache(&self) -> io::Result<()> {
+        match self.db.symbol_cache() {
+            Some(cache) => cache.save(),
+            None => Ok(()),
+        }
+    }
+
     /// Returns a snapshot of the current state, which you can query for
     /// semantic information.
     pub fn analysis(&self) -> Analysis {
         Analysis { db: self.db.snapshot() }
     }
 
+    /// Like `analysis`, but the returned snapshot can also be cancelled on its
+    /// own, through `token`. The token is checked whenever a call starts or
+    /// finishes, and while searching through files, by the reference and the
+    /// symbol search.
+    pub fn analysis_with_cancellation(&self, token: CancellationToken) -> Analysis {
+        Analysis { db: self.db.snapshot_with_cancellation(token) }
+    }
+
     /// Applies changes to the current state of the world. If there are
     /// outstanding snapshots, they will be canceled.
     pub fn apply_change(&mut self, change: Change) {
@@ -165,6 +220,11 @@ impl AnalysisHost {
     pub fn per_query_memory_usage(&mut self) -> Vec<(String, profile::Bytes)> {
         self.db.per_query_memory_usage()
     }
+    /// The crates, the number of files and the length of the source text of
+    /// each source root.
+    pub fn per_source_root_text_size(&self) -> Vec<(Vec<String>, usize, usize)> {
+        self.db.per_source_root_text_size()
+    }
     pub fn request_cancellation(&mut self) {
         self.db.request_cancellation();
     }
@@ -223,6 +283,8 @@ impl Analysis {
             cfg_options,
             Env::default(),
             Default::default(),
+            false,
+            None,
         );
         change.change_file(file_id, Some(Arc::new(text)));
         change.set_crate_graph(crate_graph);
@@ -293,17 +355,51 @@ impl Analysis {
         self.with_db(|db| view_hir::view_hir(db, position))
     }
 
+    /// Evaluates the body of the function containing the position, returns
+    /// the result or the reason it can't be evaluated as `String`.
+    pub fn interpret_function(&self, position: FilePosition) -> Cancellable<String> {
+        self.with_db(|db| interpret_function::interpret_function(db, position))
+    }
+
+    /// Computes the memory layout of the type at the position and of its
+    /// fields.
+    pub fn view_memory_layout(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<MemoryLayoutNode>> {
+        self.with_db(|db| view_memory_layout::view_memory_layout(db, position))
+    }
+
+    /// Returns the monikers of the definition at the position, which identify
+    /// it across crates.
+    pub fn moniker(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<MonikerResult>>>> {
+        self.with_db(|db| moniker::moniker(db, position))
+    }
+
     pub fn view_item_tree(&self, file_id: FileId) -> Cancellable<String> {
         self.with_db(|db| view_item_tree::view_item_tree(db, file_id))
     }
 
-    /// Renders the crate graph to GraphViz "dot" syntax.
-    pub fn view_crate_graph(&self, full: bool) -> Cancellable<Result<String, String>> {
-        self.with_db(|db| view_crate_graph::view_crate_graph(db, full))
+    /// Renders the crate graph to GraphViz "dot" syntax, along with the list
+    /// of rendered crates and their dependencies.
+    pub fn view_crate_graph(
+        &self,
+        config: CrateGraphConfig,
+    ) -> Cancellable<Result<CrateGraphView, String>> {
+        self.with_db(|db| view_crate_graph::view_crate_graph(db, &config))
     }
 
-    pub fn expand_macro(&self, position: FilePosition) -> Cancellable<Option<ExpandedMacro>> {
-        self.with_db(|db| expand_macro::expand_macro(db, position))
+    /// Expands the macro at `position`, along with the macro calls in its
+    /// expansion up to `depth` levels of nesting, or all of them for `None`.
+    pub fn expand_macro(
+        &self,
+        position: FilePosition,
+        depth: Option<u32>,
+    ) -> Cancellable<Option<ExpandedMacro>> {
+        self.with_db(|db| expand_macro::expand_macro(db, position, depth))
     }
 
     /// Returns an edit to remove all newlines in the range, cleaning up minor
@@ -328,6 +424,7 @@ impl Analysis {
     /// automatically.
     pub fn on_char_typed(
         &self,
+        config: &TypingConfig,
         position: FilePosition,
         char_typed: char,
     ) -> Cancellable<Option<SourceChange>> {
@@ -335,13 +432,17 @@ impl Analysis {
         if !typing::TRIGGER_CHARS.contains(char_typed) {
             return Ok(None);
         }
-        self.with_db(|db| typing::on_char_typed(db, position, char_typed))
+        self.with_db(|db| typing::on_char_typed(db, config, position, char_typed))
     }
 
     /// Returns a tree representation of symbols in the file. Useful to draw a
     /// file outline.
-    pub fn file_structure(&self, file_id: FileId) -> Cancellable<Vec<StructureNode>> {
-        self.with_db(|db| file_structure::file_structure(&db.parse(file_id).tree()))
+    pub fn file_structure(
+        &self,
+        config: &FileStructureConfig,
+        file_id: FileId,
+    ) -> Cancellable<Vec<StructureNode>> {
+        self.with_db(|db| file_structure::file_structure(db, file_id, config))
     }
 
     /// Returns a list of the places in the file where type hints can be displayed.
@@ -355,7 +456,7 @@ impl Analysis {
 
     /// Returns the set of folding ranges.
     pub fn folding_ranges(&self, file_id: FileId) -> Cancellable<Vec<Fold>> {
-        self.with_db(|db| folding_ranges::folding_ranges(&db.parse(file_id).tree()))
+        self.with_db(|db| folding_ranges::folding_ranges(db, file_id))
     }
 
     /// Fuzzy searches for a symbol.
@@ -444,9 +545,14 @@ impl Analysis {
         self.with_db(|db| call_hierarchy::call_hierarchy(db, position))
     }
 
-    /// Computes incoming calls for the given file position.
-    pub fn incoming_calls(&self, position: FilePosition) -> Cancellable<Option<Vec<CallItem>>> {
-        self.with_db(|db| call_hierarchy::incoming_calls(db, position))
+    /// Computes incoming calls for the given file position. With `potential_calls`, the calls
+    /// which might dispatch to a trait method implementation are included as well.
+    pub fn incoming_calls(
+        &self,
+        position: FilePosition,
+        potential_calls: bool,
+    ) -> Cancellable<Option<Vec<CallItem>>> {
+        self.with_db(|db| call_hierarchy::incoming_calls(db, position, potential_calls))
     }
 
     /// Computes outgoing calls for the given file position.
@@ -454,6 +560,24 @@ impl Analysis {
         self.with_db(|db| call_hierarchy::outgoing_calls(db, position))
     }
 
+    /// Computes type hierarchy candidates for the given file position.
+    pub fn type_hierarchy(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| type_hierarchy::type_hierarchy(db, position))
+    }
+
+    /// Computes the supertypes of the trait or type at the given file position.
+    pub fn supertypes(&self, position: FilePosition) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::supertypes(db, position))
+    }
+
+    /// Computes the subtypes of the trait or type at the given file position.
+    pub fn subtypes(&self, position: FilePosition) -> Cancellable<Option<Vec<NavigationTarget>>> {
+        self.with_db(|db| type_hierarchy::subtypes(db, position))
+    }
+
     /// Returns a `mod name;` declaration which created the current module.
     pub fn parent_module(&self, position: FilePosition) -> Cancellable<Vec<NavigationTarget>> {
         self.with_db(|db| parent_module::parent_module(db, position))
@@ -464,6 +588,12 @@ impl Analysis {
         self.with_db(|db| parent_module::crate_for(db, file_id))
     }
 
+    /// Returns the `pub` items of the given crate which are not used by the
+    /// other crates of the workspace.
+    pub fn unused_public_items(&self, crate_id: CrateId) -> Cancellable<Vec<NavigationTarget>> {
+        self.with_db(|db| unused_public_items::unused_public_items(db, crate_id))
+    }
+
     /// Returns the edition of the given crate.
     pub fn crate_edition(&self, crate_id: CrateId) -> Cancellable<Edition> {
         self.with_db(|db| db.crate_graph()[crate_id].edition)
@@ -630,20 +760,38 @@ impl Analysis {
         self.with_db(|db| rename::will_rename_file(db, file_id, new_name_stem))
     }
 
+    /// Applies the structural search replace `query`, a rule or several rules, one per line.
+    /// Returns the edits along with the matches of each rule.
     pub fn structural_search_replace(
         &self,
         query: &str,
         parse_only: bool,
         resolve_context: FilePosition,
         selections: Vec<FileRange>,
-    ) -> Cancellable<Result<SourceChange, SsrError>> {
+    ) -> Cancellable<Result<(SourceChange, Vec<SsrRuleMatches>), SsrError>> {
         self.with_db(|db| {
-            let rule: ide_ssr::SsrRule = query.parse()?;
+            // A rule may span several lines, but has a single `==>>`. Errors in a list of
+            // rules mention the line.
+            let rules = if query.matches("==>>").count() > 1 {
+                ide_ssr::parse_rules(query)?
+            } else {
+                vec![query.parse()?]
+            };
+            let rule_texts = rules.iter().map(|it| it.to_string()).collect::<Vec<_>>();
             let mut match_finder =
                 ide_ssr::MatchFinder::in_context(db, resolve_context, selections);
-            match_finder.add_rule(rule)?;
-            let edits = if parse_only { Default::default() } else { match_finder.edits() };
-            Ok(SourceChange::from(edits))
+            for rule in rules {
+                match_finder.add_rule(rule)?;
+            }
+            let (edits, mut match_counts) =
+                if parse_only { Default::default() } else { match_finder.edits_and_match_counts() };
+            match_counts.resize_with(rule_texts.len(), Default::default);
+            let rules = rule_texts
+                .into_iter()
+                .zip(match_counts)
+                .map(|(rule, match_counts)| SsrRuleMatches { rule, match_counts })
+                .collect();
+            Ok((SourceChange::from(edits), rules))
         })
     }
 
@@ -680,11 +828,27 @@ impl Analysis {
     ///
     /// Salsa implements cancelation by unwinding with a special value and
     /// catching it on the API boundary.
+    ///
+    /// The `CancellationToken` of the snapshot unwinds the same way, but only
+    /// outside of queries: unwinding from the middle of a query would also
+    /// abort the other snapshots waiting for its result.
     fn with_db<F, T>(&self, f: F) -> Cancellable<T>
     where
         F: FnOnce(&RootDatabase) -> T + std::panic::UnwindSafe,
     {
-        Cancelled::catch(|| f(&self.db))
+        let res = ide_db::base_db::Cancelled::catch(|| {
+            TokenCancelled::catch(|| {
+                self.db.unwind_if_token_cancelled();
+                let res = f(&self.db);
+                self.db.unwind_if_token_cancelled();
+                res
+            })
+        });
+        match res {
+            Ok(Ok(it)) => Ok(it),
+            Ok(Err(TokenCancelled)) => Err(Cancelled::Token),
+            Err(it) => Err(Cancelled::Revision(it)),
+        }
     }
 }
 
@@ -693,3 +857,15 @@ fn analysis_is_send() {
     fn is_send<T: Send>() {}
     is_send::<Analysis>();
 }
+
+#[test]
+fn cancelled_token_cancels_only_its_analysis() {
+    let (analysis, file_id) = Analysis::from_single_file("fn main() {}".to_string());
+    let token = CancellationToken::default();
+    let cancellable = Analysis { db: analysis.db.snapshot_with_cancellation(token.clone()) };
+    assert!(cancellable.file_text(file_id).is_ok());
+
+    token.cancel();
+    assert!(matches!(cancellable.file_text(file_id), Err(Cancelled::Token)));
+    assert!(analysis.file_text(file_id).is_ok());
+}
@@ -0,0 +1,371 @@
+//! Computes the monikers of definitions: identifiers which are the same in
+//! every crate using a definition, so that code navigation indexes built for
+//! several crates can link uses to definitions.
+
+use std::fmt;
+
+use hir::{AsAssocItem, AssocItemContainer, Crate, HirDisplay, Semantics};
+use ide_db::{
+    base_db::FilePosition,
+    defs::{Definition, NameClass, NameRefClass},
+    RootDatabase,
+};
+use syntax::{ast, AstNode};
+
+use crate::RangeInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonikerKind {
+    /// The definition is in another crate.
+    Import,
+    /// The definition is in the crate of the file.
+    Export,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonikerResult {
+    pub identifier: MonikerIdentifier,
+    pub kind: MonikerKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonikerIdentifier {
+    pub crate_name: String,
+    /// The path to the definition from the root of its crate.
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for MonikerIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.crate_name)?;
+        for segment in &self.path {
+            write!(f, "::{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+// Feature: Moniker
+//
+// Provides the monikers of the definition under the cursor, which identify it across crates.
+// The identifier of a definition is its path from the root of its crate, prefixed by the name of
+// the crate:
+//
+// * `krate::module::Item` for items, including type aliases, and for macros,
+// * `krate::module::Enum::Variant` for enum variants,
+// * `krate::module::Struct::field` and `krate::module::Enum::Variant::field` for fields, tuple
+//   fields being named by their index,
+// * `krate::module::Trait::item` for the items of traits,
+// * `krate::module::Type::item` for the items of inherent impls and
+//   `krate::module::<Type as dep::path::Trait>::item` for the items of trait impls, `module`
+//   being the module of the impl. Self types which aren't ADTs are rendered, as in
+//   `core::slice::<[T]>::len`.
+//
+// Local variables, generic parameters, labels and the items of blocks have no moniker.
+pub(crate) fn moniker(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<MonikerResult>>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let current_crate = sema.to_module_def(position.file_id)?.krate();
+    let node =
+        sema.find_node_at_offset_with_descend::<ast::NameLike>(file.syntax(), position.offset)?;
+    let defs = match &node {
+        ast::NameLike::Name(name) => match NameClass::classify(&sema, name)? {
+            NameClass::Definition(it) | NameClass::ConstReference(it) => vec![it],
+            NameClass::PatFieldShorthand { field_ref, .. } => vec![Definition::Field(field_ref)],
+        },
+        ast::NameLike::NameRef(name_ref) => match NameRefClass::classify(&sema, name_ref)? {
+            NameRefClass::Definition(it) => vec![it],
+            NameRefClass::FieldShorthand { field_ref, .. } => vec![Definition::Field(field_ref)],
+        },
+        ast::NameLike::Lifetime(_) => return None,
+    };
+    let monikers =
+        defs.into_iter().filter_map(|def| def_to_moniker(db, def, current_crate)).collect();
+    Some(RangeInfo::new(node.syntax().text_range(), monikers))
+}
+
+fn def_to_moniker(db: &RootDatabase, def: Definition, from_crate: Crate) -> Option<MonikerResult> {
+    let (module, item_path) = def_path(db, def)?;
+    let krate = module.krate();
+    let mut path = module_path(db, module)?;
+    path.extend(item_path);
+    let identifier = MonikerIdentifier { crate_name: krate.display_name(db)?.to_string(), path };
+    let kind = if krate == from_crate { MonikerKind::Export } else { MonikerKind::Import };
+    Some(MonikerResult { identifier, kind })
+}
+
+/// Returns the module `def` is defined in, and the path to `def` from that
+/// module.
+fn def_path(db: &RootDatabase, def: Definition) -> Option<(hir::Module, Vec<String>)> {
+    match def {
+        Definition::Macro(it) => Some((it.module(db)?, vec![it.name(db)?.to_string()])),
+        Definition::Field(it) => {
+            let parent = match it.parent_def(db) {
+                hir::VariantDef::Struct(it) => hir::ModuleDef::from(hir::Adt::from(it)),
+                hir::VariantDef::Union(it) => hir::ModuleDef::from(hir::Adt::from(it)),
+                hir::VariantDef::Variant(it) => hir::ModuleDef::from(it),
+            };
+            let (module, mut path) = module_def_path(db, parent)?;
+            path.push(it.name(db).to_string());
+            Some((module, path))
+        }
+        Definition::ModuleDef(it) => module_def_path(db, it),
+        Definition::SelfType(_)
+        | Definition::Local(_)
+        | Definition::GenericParam(_)
+        | Definition::Label(_) => None,
+    }
+}
+
+fn module_def_path(db: &RootDatabase, def: hir::ModuleDef) -> Option<(hir::Module, Vec<String>)> {
+    match def {
+        hir::ModuleDef::Module(it) => return Some((it, Vec::new())),
+        hir::ModuleDef::Variant(it) => {
+            let (module, mut path) =
+                module_def_path(db, hir::Adt::Enum(it.parent_enum(db)).into())?;
+            path.push(it.name(db).to_string());
+            return Some((module, path));
+        }
+        _ => (),
+    }
+
+    let mut path = Vec::new();
+    if let Some(assoc) = def.as_assoc_item(db) {
+        match assoc.container(db) {
+            AssocItemContainer::Trait(it) => path.push(it.name(db).to_string()),
+            AssocItemContainer::Impl(it) => {
+                let self_ty = it.self_ty(db);
+                let adt = self_ty.as_adt();
+                let self_ty = match adt {
+                    Some(adt) => adt.name(db).to_string(),
+                    None => self_ty.display(db).to_string(),
+                };
+                let segment = match (it.trait_(db), adt) {
+                    (Some(trait_), _) => format!("<{} as {}>", self_ty, trait_path(db, trait_)?),
+                    (None, Some(_)) => self_ty,
+                    (None, None) => format!("<{}>", self_ty),
+                };
+                path.push(segment);
+            }
+        }
+    }
+    path.push(def.name(db)?.to_string());
+    Some((def.module(db)?, path))
+}
+
+/// Renders the path of `trait_` from the root of its crate, prefixed by the
+/// name of the crate.
+fn trait_path(db: &RootDatabase, trait_: hir::Trait) -> Option<String> {
+    let module = trait_.module(db);
+    let mut path = module_path(db, module)?;
+    path.push(trait_.name(db).to_string());
+    let identifier =
+        MonikerIdentifier { crate_name: module.krate().display_name(db)?.to_string(), path };
+    Some(identifier.to_string())
+}
+
+/// Returns the names of the modules from the crate root to `module`, or `None`
+/// if `module` is in a block.
+fn module_path(db: &RootDatabase, module: hir::Module) -> Option<Vec<String>> {
+    let modules = module.path_to_root(db);
+    let root = modules.last()?;
+    if let hir::ModuleSource::BlockExpr(_) = root.definition_source(db).value {
+        return None;
+    }
+    Some(modules.iter().rev().filter_map(|it| it.name(db)).map(|it| it.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let monikers = analysis.moniker(position).unwrap().map_or_else(Vec::new, |it| it.info);
+        let actual = monikers
+            .into_iter()
+            .map(|it| format!("{:?} {}\n", it.kind, it.identifier))
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn items_and_variants() {
+        check(
+            r#"
+//- /lib.rs crate:main
+mod m {
+    pub enum E { V$0ariant }
+}
+"#,
+            expect![[r#"
+                Export main::m::E::Variant
+            "#]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main
+mod m {
+    pub type Alias$0 = u32;
+}
+"#,
+            expect![[r#"
+                Export main::m::Alias
+            "#]],
+        );
+    }
+
+    #[test]
+    fn fields() {
+        check(
+            r#"
+//- /lib.rs crate:main
+enum E { V { field: u32 }, T(u32) }
+fn f(e: E) {
+    if let E::V { field$0 } = e {}
+}
+"#,
+            expect![[r#"
+                Export main::E::V::field
+            "#]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main
+struct S(u32);
+fn f(s: S) {
+    s.0$0;
+}
+"#,
+            expect![[r#"
+                Export main::S::0
+            "#]],
+        );
+    }
+
+    #[test]
+    fn assoc_items() {
+        check(
+            r#"
+//- /lib.rs crate:main deps:dep
+mod m {
+    struct S;
+    impl dep::Trait for S {
+        fn method$0(&self) {}
+    }
+}
+//- /dep.rs crate:dep
+pub trait Trait {
+    fn method(&self);
+}
+"#,
+            expect![[r#"
+                Export main::m::<S as dep::Trait>::method
+            "#]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main deps:dep
+fn f(s: dep::m::S) {
+    s.method$0();
+    dep::Trait::method(&s);
+}
+//- /dep.rs crate:dep
+pub trait Trait {
+    fn method(&self);
+}
+pub mod m {
+    pub struct S;
+    impl S {
+        pub fn method(&self) {}
+    }
+}
+"#,
+            expect![[r#"
+                Import dep::m::S::method
+            "#]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main deps:dep
+fn f(s: &[u8]) {
+    s.len$0();
+}
+//- /dep.rs crate:dep
+#[lang = "slice"]
+impl<T> [T] {
+    pub fn len(&self) -> usize { 0 }
+}
+"#,
+            expect![[r#"
+                Import dep::<[T]>::len
+            "#]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main deps:dep
+mod m {
+    impl dep::m::Trait for &str {
+        fn method$0(&self) {}
+    }
+}
+//- /dep.rs crate:dep
+pub mod m {
+    pub trait Trait {
+        fn method(&self);
+    }
+}
+"#,
+            expect![[r#"
+                Export main::m::<&str as dep::m::Trait>::method
+            "#]],
+        );
+    }
+
+    #[test]
+    fn trait_items() {
+        check(
+            r#"
+//- /lib.rs crate:main deps:dep
+fn f<T: dep::Trait>(t: T) {
+    t.method$0();
+}
+//- /dep.rs crate:dep
+pub trait Trait {
+    fn method(&self);
+}
+"#,
+            expect![[r#"
+                Import dep::Trait::method
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_moniker_for_locals_and_block_items() {
+        check(
+            r#"
+//- /lib.rs crate:main
+fn f() {
+    struct Local;
+    let x$0 = Local;
+}
+"#,
+            expect![[""]],
+        );
+        check(
+            r#"
+//- /lib.rs crate:main
+fn f() {
+    struct Local$0;
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
@@ -13,7 +13,7 @@ use hir::{PathResolution, Semantics};
 use ide_db::{
     base_db::FileId,
     defs::{Definition, NameClass, NameRefClass},
-    search::{ReferenceAccess, SearchScope, UsageSearchResult},
+    search::{ReferenceCategory, SearchScope, UsageSearchResult},
     RootDatabase,
 };
 use rustc_hash::FxHashMap;
@@ -28,13 +28,13 @@ use crate::{display::TryToNav, FilePosition, NavigationTarget};
 #[derive(Debug, Clone)]
 pub struct ReferenceSearchResult {
     pub declaration: Option<Declaration>,
-    pub references: FxHashMap<FileId, Vec<(TextRange, Option<ReferenceAccess>)>>,
+    pub references: FxHashMap<FileId, Vec<(TextRange, Option<ReferenceCategory>)>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Declaration {
     pub nav: NavigationTarget,
-    pub access: Option<ReferenceAccess>,
+    pub category: Option<ReferenceCategory>,
 }
 
 // Feature: Find All References
@@ -78,7 +78,7 @@ pub(crate) fn find_all_refs(
     }
     .map(|nav| {
         let decl_range = nav.focus_or_full_range();
-        Declaration { nav, access: decl_access(&def, &syntax, decl_range) }
+        Declaration { nav, category: decl_category(&def, &syntax, decl_range) }
     });
     if is_literal_search {
         retain_adt_literal_usages(&mut usages, def, sema);
@@ -87,7 +87,10 @@ pub(crate) fn find_all_refs(
     let references = usages
         .into_iter()
         .map(|(file_id, refs)| {
-            (file_id, refs.into_iter().map(|file_ref| (file_ref.range, file_ref.access)).collect())
+            (
+                file_id,
+                refs.into_iter().map(|file_ref| (file_ref.range, file_ref.category)).collect(),
+            )
         })
         .collect();
 
@@ -127,11 +130,11 @@ pub(crate) fn find_def(
     Some(def)
 }
 
-pub(crate) fn decl_access(
+pub(crate) fn decl_category(
     def: &Definition,
     syntax: &SyntaxNode,
     range: TextRange,
-) -> Option<ReferenceAccess> {
+) -> Option<ReferenceCategory> {
     match def {
         Definition::Local(_) | Definition::Field(_) => {}
         _ => return None,
@@ -142,7 +145,7 @@ pub(crate) fn decl_access(
         let pat = stmt.pat()?;
         if let ast::Pat::IdentPat(it) = pat {
             if it.mut_token().is_some() {
-                return Some(ReferenceAccess::Write);
+                return Some(ReferenceCategory::Write);
             }
         }
     }
@@ -714,7 +717,7 @@ pub struct Foo {
             expect![[r#"
                 foo Module FileId(0) 0..8 4..7
 
-                FileId(0) 14..17
+                FileId(0) 14..17 Import
             "#]],
         );
     }
@@ -732,7 +735,7 @@ use self$0;
             expect![[r#"
                 foo Module FileId(0) 0..8 4..7
 
-                FileId(1) 4..8
+                FileId(1) 4..8 Import
             "#]],
         );
     }
@@ -774,7 +777,7 @@ pub(super) struct Foo$0 {
             expect![[r#"
                 Foo Struct FileId(2) 0..41 18..21
 
-                FileId(1) 20..23
+                FileId(1) 20..23 Import
                 FileId(1) 47..50
             "#]],
         );
@@ -802,8 +805,8 @@ pub(super) struct Foo$0 {
             expect![[r#"
                 quux Function FileId(0) 19..35 26..30
 
-                FileId(1) 16..20
-                FileId(2) 16..20
+                FileId(1) 16..20 Call
+                FileId(2) 16..20 Call
             "#]],
         );
 
@@ -813,7 +816,7 @@ pub(super) struct Foo$0 {
             expect![[r#"
                 quux Function FileId(0) 19..35 26..30
 
-                FileId(2) 16..20
+                FileId(2) 16..20 Call
             "#]],
         );
     }
@@ -915,7 +918,7 @@ fn main() {
             expect![[r#"
                 new Function FileId(0) 54..81 61..64
 
-                FileId(0) 126..129
+                FileId(0) 126..129 Call
             "#]],
         );
     }
@@ -937,8 +940,8 @@ fn g() { f(); }
             expect![[r#"
                 f Function FileId(0) 22..31 25..26
 
-                FileId(1) 11..12
-                FileId(1) 24..25
+                FileId(1) 11..12 Import
+                FileId(1) 24..25 Call
             "#]],
         );
     }
@@ -1070,17 +1073,17 @@ impl Foo {
         let mut actual = String::new();
         if let Some(decl) = refs.declaration {
             format_to!(actual, "{}", decl.nav.debug_render());
-            if let Some(access) = decl.access {
-                format_to!(actual, " {:?}", access)
+            if let Some(category) = decl.category {
+                format_to!(actual, " {:?}", category)
             }
             actual += "\n\n";
         }
 
         for (file_id, references) in refs.references {
-            for (range, access) in references {
+            for (range, category) in references {
                 format_to!(actual, "{:?} {:?}", file_id, range);
-                if let Some(access) = access {
-                    format_to!(actual, " {:?}", access);
+                if let Some(category) = category {
+                    format_to!(actual, " {:?}", category);
                 }
                 actual += "\n";
             }
@@ -1234,7 +1237,93 @@ impl Foo for () {}
             expect![[r#"
                 Foo Trait FileId(0) 0..24 6..9
 
-                FileId(0) 31..34
+                FileId(0) 31..34 TraitImpl
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_categories_fn() {
+        check(
+            r#"
+//- /lib.rs
+mod m {
+    pub trait Tr { fn f(&self); }
+}
+use m::Tr;
+struct S;
+impl Tr for S { fn f(&self) {} }
+fn fo$0o() {}
+fn main() {
+    foo();
+    let f = foo;
+    S.f();
+    m::Tr::f(&S);
+}
+"#,
+            expect![[r#"
+                foo Function FileId(0) 98..109 101..104
+
+                FileId(0) 126..129 Call
+                FileId(0) 145..148
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_categories_trait() {
+        check(
+            r#"
+//- /lib.rs
+mod m {
+    pub trait T$0r { fn f(&self); }
+}
+use m::Tr;
+struct S;
+impl Tr for S { fn f(&self) {} }
+fn foo() {}
+fn main() {
+    foo();
+    let f = foo;
+    S.f();
+    m::Tr::f(&S);
+}
+"#,
+            expect![[r#"
+                Tr Trait FileId(0) 12..41 22..24
+
+                FileId(0) 51..53 Import
+                FileId(0) 70..72 TraitImpl
+                FileId(0) 168..170
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_categories_method() {
+        check(
+            r#"
+//- /lib.rs
+mod m {
+    pub trait Tr { fn f$0(&self); }
+}
+use m::Tr;
+struct S;
+impl Tr for S { fn f(&self) {} }
+fn foo() {}
+fn main() {
+    foo();
+    let f = foo;
+    S.f();
+    m::Tr::f(&S);
+}
+"#,
+            expect![[r#"
+                f Function FileId(0) 27..39 30..31
+
+                FileId(0) 84..85
+                FileId(0) 156..157 Call
+                FileId(0) 172..173 Call
             "#]],
         );
     }
@@ -1328,7 +1417,7 @@ fn test$0() {
             expect![[r#"
                 test Function FileId(0) 0..33 11..15
 
-                FileId(0) 24..28
+                FileId(0) 24..28 Call
             "#]],
         );
     }
@@ -1387,9 +1476,9 @@ pub use level1::Foo;
             expect![[r#"
                 Foo Struct FileId(0) 0..15 11..14
 
-                FileId(1) 16..19
-                FileId(2) 16..19
-                FileId(3) 16..19
+                FileId(1) 16..19 Import
+                FileId(2) 16..19 Import
+                FileId(3) 16..19 Import
             "#]],
         );
     }
@@ -1417,9 +1506,9 @@ lib::foo!();
             expect![[r#"
                 foo Macro FileId(1) 0..61 29..32
 
-                FileId(0) 46..49
-                FileId(2) 0..3
-                FileId(3) 5..8
+                FileId(0) 46..49 Import
+                FileId(2) 0..3 Call
+                FileId(3) 5..8 Call
             "#]],
         );
     }
@@ -1439,7 +1528,7 @@ m$0!();
             expect![[r#"
                 m Macro FileId(0) 0..32 13..14
 
-                FileId(0) 64..65
+                FileId(0) 64..65 Call
             "#]],
         );
     }
@@ -137,11 +137,7 @@ pub(crate) fn runnables(db: &RootDatabase, file_id: FileId) -> Vec<Runnable> {
             )
         }) {
             if let Some(def) = def {
-                let file_id = match def {
-                    hir::ModuleDef::Module(it) => it.declaration_source(db).map(|src| src.file_id),
-                    hir::ModuleDef::Function(it) => it.source(db).map(|src| src.file_id),
-                    _ => None,
-                };
+                let file_id = def_file_id(db, def);
                 if let Some(file_id) = file_id.filter(|file| file.call_node(db).is_some()) {
                     in_macro_expansion.entry(file_id).or_default().push(runnable);
                     return;
@@ -193,6 +189,23 @@ pub(crate) fn runnables(db: &RootDatabase, file_id: FileId) -> Vec<Runnable> {
     res
 }
 
+/// Returns the file `def` is defined in, which is a macro file for the items generated by macros.
+fn def_file_id(db: &RootDatabase, def: hir::ModuleDef) -> Option<hir::HirFileId> {
+    match def {
+        hir::ModuleDef::Module(it) => it.declaration_source(db).map(|src| src.file_id),
+        hir::ModuleDef::Function(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Adt(hir::Adt::Struct(it)) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Adt(hir::Adt::Union(it)) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Adt(hir::Adt::Enum(it)) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Variant(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Const(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Static(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::Trait(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::TypeAlias(it) => it.source(db).map(|src| src.file_id),
+        hir::ModuleDef::BuiltinType(_) => None,
+    }
+}
+
 // Feature: Related Tests
 //
 // Provides a sneak peek of all tests where the current item is used.
@@ -504,7 +517,9 @@ fn has_test_function_or_multiple_test_submodules(
         match item {
             hir::ModuleDef::Function(f) => {
                 if let Some(it) = f.source(sema.db) {
-                    if test_related_attribute(&it.value).is_some() {
+                    if test_related_attribute(&it.value).is_some()
+                        || it.value.has_atom_attr("bench")
+                    {
                         return true;
                     }
                 }
@@ -1367,6 +1382,85 @@ impl Foo {
         );
     }
 
+    #[test]
+    fn test_doctest_and_bench_in_macro() {
+        check(
+            r#"
+//- /lib.rs
+$0
+macro_rules! gen {
+    () => {
+        /// ```
+        /// let x = 5;
+        /// ```
+        pub struct Foo;
+        #[bench]
+        fn bench() {}
+    }
+}
+mod benches {
+    gen!();
+}
+"#,
+            &[TestMod, DocTest, Bench],
+            expect![[r#"
+                [
+                    Runnable {
+                        use_name_in_title: false,
+                        nav: NavigationTarget {
+                            file_id: FileId(
+                                0,
+                            ),
+                            full_range: 158..185,
+                            focus_range: 162..169,
+                            name: "benches",
+                            kind: Module,
+                            description: "mod benches",
+                        },
+                        kind: TestMod {
+                            path: "benches",
+                        },
+                        cfg: None,
+                    },
+                    Runnable {
+                        use_name_in_title: true,
+                        nav: NavigationTarget {
+                            file_id: FileId(
+                                0,
+                            ),
+                            full_range: 176..183,
+                            name: "Foo",
+                        },
+                        kind: DocTest {
+                            test_id: Path(
+                                "benches::Foo",
+                            ),
+                        },
+                        cfg: None,
+                    },
+                    Runnable {
+                        use_name_in_title: true,
+                        nav: NavigationTarget {
+                            file_id: FileId(
+                                0,
+                            ),
+                            full_range: 176..183,
+                            focus_range: 176..183,
+                            name: "bench",
+                            kind: Function,
+                        },
+                        kind: Bench {
+                            test_id: Path(
+                                "benches::bench",
+                            ),
+                        },
+                        cfg: None,
+                    },
+                ]
+            "#]],
+        );
+    }
+
     #[test]
     fn test_runnables_in_macro() {
         check(
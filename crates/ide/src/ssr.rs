@@ -3,7 +3,22 @@
 //! depend on the ide_ssr crate.
 
 use ide_assists::{Assist, AssistId, AssistKind, AssistResolveStrategy, GroupLabel};
-use ide_db::{base_db::FileRange, label::Label, source_change::SourceChange, RootDatabase};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    label::Label,
+    source_change::SourceChange,
+    RootDatabase,
+};
+use rustc_hash::FxHashMap;
+
+/// The matches of one of the rules of a structural search replace query.
+#[derive(Debug)]
+pub struct SsrRuleMatches {
+    /// The text of the rule.
+    pub rule: String,
+    /// The number of matches in each file.
+    pub match_counts: FxHashMap<FileId, usize>,
+}
 
 pub(crate) fn ssr_assists(
     db: &RootDatabase,
@@ -131,18 +131,20 @@ pub struct HlRange {
 // attribute:: Emitted for tokens inside attributes.
 // callable:: Emitted for locals whose types implements one of the `Fn*` traits.
 // constant:: Emitted for consts.
-// consuming:: Emitted for locals that are being consumed when use in a function call.
+// consuming:: Emitted for uses of locals which move their value, like passing a non-`Copy` local to a function.
 // controlFlow:: Emitted for control-flow related tokens, this includes the `?` operator.
 // declaration:: Emitted for names of definitions, like `foo` in `fn foo() {}`.
 // documentation:: Emitted for documentation comments.
 // injected:: Emitted for doc-string injected highlighting like rust source blocks in documentation.
+// interiorMutable:: Emitted for types containing an `UnsafeCell`, like `Cell` and `RefCell`, and for locals and statics of such types.
 // intraDocLink:: Emitted for intra doc links in doc-strings.
 // library:: Emitted for items that are defined outside of the current crate.
 // public:: Emitted for items that are from the current crate and are `pub`.
 // mutable:: Emitted for mutable locals and statics.
+// mutableUse:: Emitted for uses of locals which borrow them mutably, like `&mut` and calls to `&mut self` methods, or assign to them.
 // static:: Emitted for "static" functions, also known as functions that do not take a `self` param, as well as statics and consts.
 // trait:: Emitted for associated trait items.
-// unsafe:: Emitted for unsafe operations, like unsafe function calls, as well as the `unsafe` token.
+// unsafe:: Emitted for unsafe operations, like unsafe function calls, raw pointer dereferences, union field accesses and accesses to mutable or extern statics, as well as the `unsafe` token.
 //
 //
 // image::https://user-images.githubusercontent.com/48062697/113164457-06cfb980-9239-11eb-819b-0f93e646acf8.png[]
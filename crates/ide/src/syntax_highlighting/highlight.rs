@@ -266,11 +266,11 @@ fn highlight_name_ref(
                 let mut h = highlight_def(db, krate, def);
 
                 match def {
-                    Definition::Local(local)
-                        if is_consumed_lvalue(name_ref.syntax(), &local, db) =>
-                    {
-                        h |= HlMod::Consuming;
-                    }
+                    Definition::Local(_) => match local_use(sema, name_ref.syntax()) {
+                        Some(hir::ExprUse::Move) => h |= HlMod::Consuming,
+                        Some(hir::ExprUse::MutableBorrow) => h |= HlMod::MutableUse,
+                        _ => (),
+                    },
                     Definition::ModuleDef(hir::ModuleDef::Trait(trait_))
                         if trait_.is_unsafe(db) =>
                     {
@@ -398,8 +398,13 @@ fn highlight_def(db: &RootDatabase, krate: Option<hir::Crate>, def: Definition)
                     hir::Adt::Enum(_) => HlTag::Symbol(SymbolKind::Enum),
                     hir::Adt::Union(_) => HlTag::Symbol(SymbolKind::Union),
                 };
+                let mut h = Highlight::new(h);
 
-                Highlight::new(h)
+                if adt.ty(db).is_interior_mutable(db) {
+                    h |= HlMod::InteriorMutable;
+                }
+
+                h
             }
             hir::ModuleDef::Variant(_) => Highlight::new(HlTag::Symbol(SymbolKind::Variant)),
             hir::ModuleDef::Const(konst) => {
@@ -448,6 +453,11 @@ fn highlight_def(db: &RootDatabase, krate: Option<hir::Crate>, def: Definition)
                 if s.is_mut(db) {
                     h |= HlMod::Mutable;
                     h |= HlMod::Unsafe;
+                } else if s.is_extern(db) {
+                    h |= HlMod::Unsafe;
+                }
+                if s.ty(db).is_interior_mutable(db) {
+                    h |= HlMod::InteriorMutable;
                 }
 
                 h
@@ -479,6 +489,9 @@ fn highlight_def(db: &RootDatabase, krate: Option<hir::Crate>, def: Definition)
             if ty.as_callable(db).is_some() || ty.impls_fnonce(db) {
                 h |= HlMod::Callable;
             }
+            if ty.strip_references().is_interior_mutable(db) {
+                h |= HlMod::InteriorMutable;
+            }
             h
         }
         Definition::Label(_) => Highlight::new(HlTag::Symbol(SymbolKind::Label)),
@@ -646,10 +659,14 @@ fn highlight_name_ref_by_syntax(
     }
 }
 
-fn is_consumed_lvalue(node: &SyntaxNode, local: &hir::Local, db: &RootDatabase) -> bool {
-    // When lvalues are passed as arguments and they're not Copy, then mark them as Consuming.
-    parents_match(node.clone().into(), &[PATH_SEGMENT, PATH, PATH_EXPR, ARG_LIST])
-        && !local.ty(db).is_copy(db)
+/// Returns whether the local variable `node` refers to is moved or borrowed
+/// by the path expression it forms.
+fn local_use(sema: &Semantics<RootDatabase>, node: &SyntaxNode) -> Option<hir::ExprUse> {
+    if !parents_match(node.clone().into(), &[PATH_SEGMENT, PATH, PATH_EXPR]) {
+        return None;
+    }
+    let path_expr = node.ancestors().find_map(ast::PathExpr::cast)?;
+    sema.expr_use(&path_expr.into())
 }
 
 /// Returns true if the parent nodes of `node` all match the `SyntaxKind`s in `kinds` exactly.
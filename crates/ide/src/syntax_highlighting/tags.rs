@@ -49,7 +49,7 @@ pub enum HlMod {
     Attribute,
     /// Callable item or value.
     Callable,
-    /// Value that is being consumed in a function call
+    /// Local variable whose value is moved.
     Consuming,
     /// Used with keywords like `if` and `break`.
     ControlFlow,
@@ -64,6 +64,9 @@ pub enum HlMod {
     IntraDocLink,
     /// Mutable binding.
     Mutable,
+    /// Local variable which is borrowed mutably, by a `&mut self` method call
+    /// for example, or assigned to.
+    MutableUse,
     /// Used for associated functions.
     Static,
     /// Used for items in traits and trait impls.
@@ -74,6 +77,8 @@ pub enum HlMod {
     Library,
     /// Used for public items.
     Public,
+    /// Used for types containing an `UnsafeCell`, and for bindings and statics of such types.
+    InteriorMutable,
     // Keep this last!
     /// Used for unsafe functions, unsafe traits, mutable statics, union accesses and unsafe operations.
     Unsafe,
@@ -194,11 +199,13 @@ impl HlMod {
         HlMod::Injected,
         HlMod::IntraDocLink,
         HlMod::Mutable,
+        HlMod::MutableUse,
         HlMod::Static,
         HlMod::Trait,
         HlMod::Async,
         HlMod::Library,
         HlMod::Public,
+        HlMod::InteriorMutable,
         HlMod::Unsafe,
     ];
 
@@ -214,11 +221,13 @@ impl HlMod {
             HlMod::Injected => "injected",
             HlMod::IntraDocLink => "intra_doc_link",
             HlMod::Mutable => "mutable",
+            HlMod::MutableUse => "mutable_use",
             HlMod::Static => "static",
             HlMod::Trait => "trait",
             HlMod::Async => "async",
             HlMod::Library => "library",
             HlMod::Public => "public",
+            HlMod::InteriorMutable => "interior_mutable",
             HlMod::Unsafe => "unsafe",
         }
     }
@@ -586,6 +586,50 @@ fn main() {
     );
 }
 
+#[test]
+fn test_interior_mutability_highlighting() {
+    let (analysis, file_id) = fixture::file(
+        r#"
+//- minicore: cell
+use core::cell::Cell;
+
+struct Counter {
+    count: Cell<u32>,
+}
+
+struct Plain {
+    count: u32,
+}
+
+extern "C" {
+    static EXTERN_STATIC: u32;
+}
+
+fn f(counter: &Counter, plain: Plain) {
+    let _ = unsafe { EXTERN_STATIC };
+}
+"#,
+    );
+    let text = analysis.file_text(file_id).unwrap();
+    let highlights = analysis.highlight(file_id).unwrap();
+    let highlights_of = |name: &str| {
+        highlights
+            .iter()
+            .filter(|it| &text[it.range] == name)
+            .map(|it| it.highlight.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        highlights_of("Counter"),
+        ["struct.declaration.interior_mutable", "struct.interior_mutable"]
+    );
+    assert_eq!(highlights_of("Plain"), ["struct.declaration", "struct"]);
+    assert_eq!(highlights_of("counter"), ["value_param.declaration.interior_mutable"]);
+    assert_eq!(highlights_of("plain"), ["value_param.declaration"]);
+    assert_eq!(highlights_of("EXTERN_STATIC"), ["static.declaration.unsafe", "static.unsafe"]);
+}
+
 #[test]
 fn test_highlight_doc_comment() {
     check_highlighting(
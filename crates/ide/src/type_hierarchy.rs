@@ -0,0 +1,208 @@
+//! Entry point for type-hierarchy
+
+use hir::{Impl, ModuleDef, Semantics, Trait};
+use ide_db::{
+    defs::{Definition, NameClass, NameRefClass},
+    RootDatabase,
+};
+use rustc_hash::FxHashSet;
+use syntax::{ast, AstNode};
+
+use crate::{display::TryToNav, FilePosition, NavigationTarget, RangeInfo};
+
+// Feature: Type Hierarchy
+//
+// Shows the traits a type implements, the supertraits of a trait and the types implementing a
+// trait. The supertypes of a struct, enum or union are the traits it implements, the ones of a
+// trait are its supertraits, and the subtypes of a trait are its subtraits and the types
+// implementing it. Implementors which aren't structs, enums or unions are shown as their impls.
+pub(crate) fn type_hierarchy(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantics::new(db);
+    let RangeInfo { range, info: def } = type_def_at(&sema, position)?;
+    let nav = match def {
+        ModuleDef::Trait(it) => it.try_to_nav(db),
+        ModuleDef::Adt(it) => it.try_to_nav(db),
+        _ => None,
+    }?;
+    Some(RangeInfo::new(range, vec![nav]))
+}
+
+pub(crate) fn supertypes(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let traits = match type_def_at(&sema, position)?.info {
+        ModuleDef::Trait(trait_) => trait_.direct_supertraits(db),
+        ModuleDef::Adt(adt) => Impl::all_for_type(db, adt.ty(db))
+            .into_iter()
+            .filter(|impl_| !impl_.is_negative(db))
+            .filter_map(|impl_| impl_.trait_(db))
+            .collect(),
+        _ => return None,
+    };
+    Some(dedup_navs(traits.into_iter().filter_map(|it| it.try_to_nav(db))))
+}
+
+pub(crate) fn subtypes(db: &RootDatabase, position: FilePosition) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let navs = match type_def_at(&sema, position)?.info {
+        ModuleDef::Trait(trait_) => {
+            // Implementors which aren't ADTs, like primitive types or references, are shown as
+            // their impls.
+            let implementors = Impl::all_for_trait(db, trait_)
+                .into_iter()
+                .filter(|impl_| !impl_.is_negative(db))
+                .filter_map(|impl_| match impl_.self_ty(db).as_adt() {
+                    Some(adt) => adt.try_to_nav(db),
+                    None => impl_.try_to_nav(db),
+                });
+            let subtraits = subtraits(db, trait_).into_iter().filter_map(|it| it.try_to_nav(db));
+            dedup_navs(implementors.chain(subtraits))
+        }
+        ModuleDef::Adt(_) => Vec::new(),
+        _ => return None,
+    };
+    Some(navs)
+}
+
+/// Returns the traits having `trait_` as a direct supertrait, which are declared in the crate
+/// of `trait_` or in the crates depending on it, outside of function bodies.
+fn subtraits(db: &RootDatabase, trait_: Trait) -> Vec<Trait> {
+    let krate = trait_.module(db).krate();
+    let mut modules = krate
+        .transitive_reverse_dependencies(db)
+        .into_iter()
+        .map(|it| it.root_module(db))
+        .collect::<Vec<_>>();
+    let mut res = Vec::new();
+    while let Some(module) = modules.pop() {
+        modules.extend(module.children(db));
+        for def in module.declarations(db) {
+            match def {
+                ModuleDef::Trait(it) if it.direct_supertraits(db).contains(&trait_) => res.push(it),
+                _ => (),
+            }
+        }
+    }
+    res
+}
+
+fn type_def_at(
+    sema: &Semantics<RootDatabase>,
+    position: FilePosition,
+) -> Option<RangeInfo<ModuleDef>> {
+    let file = sema.parse(position.file_id);
+    let node =
+        sema.find_node_at_offset_with_descend::<ast::NameLike>(file.syntax(), position.offset)?;
+    let def = match &node {
+        ast::NameLike::Name(name) => NameClass::classify(sema, name)?.defined()?,
+        ast::NameLike::NameRef(name_ref) => match NameRefClass::classify(sema, name_ref)? {
+            NameRefClass::Definition(it) => it,
+            NameRefClass::FieldShorthand { .. } => return None,
+        },
+        ast::NameLike::Lifetime(_) => return None,
+    };
+    match def {
+        Definition::ModuleDef(def @ ModuleDef::Trait(_))
+        | Definition::ModuleDef(def @ ModuleDef::Adt(_)) => {
+            Some(RangeInfo::new(node.syntax().text_range(), def))
+        }
+        _ => None,
+    }
+}
+
+/// Removes duplicates, like the traits implemented by several impls with
+/// different generic arguments, keeping the first occurrence.
+fn dedup_navs(navs: impl Iterator<Item = NavigationTarget>) -> Vec<NavigationTarget> {
+    let mut seen = FxHashSet::default();
+    navs.filter(|nav| seen.insert((nav.file_id, nav.full_range))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, supertypes: Expect, subtypes: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let render = |navs: Vec<crate::NavigationTarget>| {
+            let mut names = navs.into_iter().map(|nav| nav.name.to_string()).collect::<Vec<_>>();
+            names.sort();
+            names.join(", ")
+        };
+        supertypes.assert_eq(&render(analysis.supertypes(position).unwrap().unwrap()));
+        subtypes.assert_eq(&render(analysis.subtypes(position).unwrap().unwrap()));
+    }
+
+    #[test]
+    fn prepare_type_hierarchy() {
+        let (analysis, position) = fixture::position(
+            r#"
+struct Foo;
+fn f(_: Fo$0o) {}
+"#,
+        );
+        let navs = analysis.type_hierarchy(position).unwrap().unwrap().info;
+        assert_eq!(navs.len(), 1);
+        navs[0].assert_match("Foo Struct FileId(0) 0..11 7..10");
+    }
+
+    #[test]
+    fn trait_hierarchy() {
+        check(
+            r#"
+trait A {}
+trait B {}
+trait C$0: A + B {}
+struct S;
+struct T<U>(U);
+enum E {}
+impl C for S {}
+impl C for T<u8> {}
+impl C for T<u16> {}
+impl C for i32 {}
+impl C for &E {}
+trait D: C {}
+mod m {
+    trait F: super::C {}
+    trait G: F {}
+}
+"#,
+            expect![["A, B"]],
+            expect![["D, F, S, T, impl, impl"]],
+        );
+    }
+
+    #[test]
+    fn struct_hierarchy() {
+        check(
+            r#"
+trait A {}
+trait B<T> {}
+struct $0S;
+impl S {}
+impl A for S {}
+impl B<u8> for S {}
+impl B<u16> for S {}
+"#,
+            expect![["A, B"]],
+            expect![[""]],
+        );
+    }
+
+    #[test]
+    fn not_a_type() {
+        let (analysis, position) = fixture::position(
+            r#"
+fn $0f() {}
+"#,
+        );
+        assert!(analysis.type_hierarchy(position).unwrap().is_none());
+        assert!(analysis.supertypes(position).unwrap().is_none());
+    }
+}
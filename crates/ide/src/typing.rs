@@ -15,8 +15,11 @@
 
 mod on_enter;
 
+use std::iter::successors;
+
 use ide_db::{
     base_db::{FilePosition, SourceDatabase},
+    helpers::SnippetCap,
     RootDatabase,
 };
 use syntax::{
@@ -24,7 +27,7 @@ use syntax::{
     ast::{self, edit::IndentLevel, AstToken},
     AstNode, Parse, SourceFile,
     SyntaxKind::{self, FIELD_EXPR, METHOD_CALL_EXPR},
-    TextRange, TextSize,
+    TextRange, TextSize, T,
 };
 
 use text_edit::{Indel, TextEdit};
@@ -34,7 +37,14 @@ use crate::SourceChange;
 pub(crate) use on_enter::on_enter;
 
 // Don't forget to add new trigger characters to `server_capabilities` in `caps.rs`.
-pub(crate) const TRIGGER_CHARS: &str = ".=>{";
+pub(crate) const TRIGGER_CHARS: &str = ".=>{|<";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypingConfig {
+    pub close_closure_pipes: bool,
+    pub close_generic_angles: bool,
+    pub snippet_cap: Option<SnippetCap>,
+}
 
 // Feature: On Typing Assists
 //
@@ -43,6 +53,10 @@ pub(crate) const TRIGGER_CHARS: &str = ".=>{";
 // - typing `let =` tries to smartly add `;` if `=` is followed by an existing expression
 // - typing `.` in a chain method call auto-indents
 // - typing `{` in front of an expression inserts a closing `}` after the expression
+// - typing `|` in expression position inserts the closing `|` of the closure parameter list
+//   (`rust-analyzer.typing.autoClosingPipes`)
+// - typing `<` after a path in type position inserts the closing `>` of the generic arguments
+//   (`rust-analyzer.typing.autoClosingAngleBrackets`)
 //
 // VS Code::
 //
@@ -56,6 +70,7 @@ pub(crate) const TRIGGER_CHARS: &str = ".=>{";
 // image::https://user-images.githubusercontent.com/48062697/113171066-105c2000-923f-11eb-87ab-f4a263346567.gif[]
 pub(crate) fn on_char_typed(
     db: &RootDatabase,
+    config: &TypingConfig,
     position: FilePosition,
     char_typed: char,
 ) -> Option<SourceChange> {
@@ -66,11 +81,15 @@ pub(crate) fn on_char_typed(
     if !stdx::always!(file.tree().syntax().text().char_at(position.offset) == Some(char_typed)) {
         return None;
     }
-    let edit = on_char_typed_inner(file, position.offset, char_typed)?;
-    Some(SourceChange::from_text_edit(position.file_id, edit))
+    let edit = on_char_typed_inner(config, file, position.offset, char_typed)?;
+    let mut change = SourceChange::from_text_edit(position.file_id, edit);
+    // The closing `|` and `>` are inserted after the cursor.
+    change.is_snippet = config.snippet_cap.is_some() && matches!(char_typed, '|' | '<');
+    Some(change)
 }
 
 fn on_char_typed_inner(
+    config: &TypingConfig,
     file: &Parse<SourceFile>,
     offset: TextSize,
     char_typed: char,
@@ -83,6 +102,13 @@ fn on_char_typed_inner(
         '=' => on_eq_typed(&file.tree(), offset),
         '>' => on_arrow_typed(&file.tree(), offset),
         '{' => on_opening_brace_typed(file, offset),
+        '|' if config.close_closure_pipes => {
+            on_pipe_typed(&file.tree(), offset, config.snippet_cap)
+        }
+        '<' if config.close_generic_angles => {
+            on_left_angle_typed(&file.tree(), offset, config.snippet_cap)
+        }
+        '|' | '<' => None,
         _ => unreachable!(),
     }
 }
@@ -234,6 +260,68 @@ fn on_arrow_typed(file: &SourceFile, offset: TextSize) -> Option<TextEdit> {
     Some(TextEdit::insert(after_arrow, " ".to_string()))
 }
 
+/// Inserts the closing `|` when the user starts the parameter list of a
+/// closure.
+fn on_pipe_typed(
+    file: &SourceFile,
+    offset: TextSize,
+    snippet_cap: Option<SnippetCap>,
+) -> Option<TextEdit> {
+    if !stdx::always!(file.syntax().text().char_at(offset) == Some('|')) {
+        return None;
+    }
+    let pipe = file.syntax().token_at_offset(offset).right_biased()?;
+    if pipe.kind() != T![|] {
+        return None;
+    }
+    let param_list = ast::ParamList::cast(pipe.parent()?)?;
+    if !ast::ClosureExpr::can_cast(param_list.syntax().parent()?.kind()) {
+        return None;
+    }
+    let n_pipes =
+        param_list.syntax().children_with_tokens().filter(|it| it.kind() == T![|]).count();
+    if n_pipes != 1 {
+        return None;
+    }
+    Some(insert_closing(offset + TextSize::of('|'), '|', snippet_cap))
+}
+
+/// Inserts the closing `>` when the user starts the generic arguments of a
+/// path in type position.
+fn on_left_angle_typed(
+    file: &SourceFile,
+    offset: TextSize,
+    snippet_cap: Option<SnippetCap>,
+) -> Option<TextEdit> {
+    if !stdx::always!(file.syntax().text().char_at(offset) == Some('<')) {
+        return None;
+    }
+    let l_angle = file.syntax().token_at_offset(offset).right_biased()?;
+    if l_angle.kind() != T![<] {
+        return None;
+    }
+    let generic_args = ast::GenericArgList::cast(l_angle.parent()?)?;
+    if generic_args.coloncolon_token().is_some() || generic_args.r_angle_token().is_some() {
+        return None;
+    }
+    let path = generic_args.syntax().ancestors().find_map(ast::Path::cast)?;
+    let path = successors(Some(path), |it| it.parent_path()).last()?;
+    if !ast::PathType::can_cast(path.syntax().parent()?.kind()) {
+        return None;
+    }
+    Some(insert_closing(offset + TextSize::of('<'), '>', snippet_cap))
+}
+
+/// Inserts the closing half of a pair, leaving the cursor between the two if
+/// snippets are supported.
+fn insert_closing(offset: TextSize, closing: char, snippet_cap: Option<SnippetCap>) -> TextEdit {
+    let text = match snippet_cap {
+        Some(_) => format!("$0{}", closing),
+        None => closing.to_string(),
+    };
+    TextEdit::insert(offset, text)
+}
+
 #[cfg(test)]
 mod tests {
     use test_utils::{assert_eq_text, extract_offset};
@@ -245,7 +333,12 @@ mod tests {
         let edit = TextEdit::insert(offset, char_typed.to_string());
         edit.apply(&mut before);
         let parse = SourceFile::parse(&before);
-        on_char_typed_inner(&parse, offset, char_typed).map(|it| {
+        let config = TypingConfig {
+            close_closure_pipes: true,
+            close_generic_angles: true,
+            snippet_cap: SnippetCap::new(true),
+        };
+        on_char_typed_inner(&config, &parse, offset, char_typed).map(|it| {
             it.apply(&mut before);
             before.to_string()
         })
@@ -661,4 +754,116 @@ use some::pa$0th::to::Item;
             "#,
         );
     }
+
+    #[test]
+    fn adds_closing_pipe() {
+        type_char(
+            '|',
+            r#"
+fn main() {
+    xs.map($0)
+}
+"#,
+            r#"
+fn main() {
+    xs.map(|$0|)
+}
+"#,
+        );
+        type_char(
+            '|',
+            r#"
+fn main() {
+    let f = move $0
+}
+"#,
+            r#"
+fn main() {
+    let f = move |$0|
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn does_not_add_closing_pipe() {
+        type_char_noop(
+            '|',
+            r#"
+fn main() {
+    let f = |x$0
+}
+"#,
+        );
+        type_char_noop(
+            '|',
+            r#"
+fn main() {
+    let x = a $0 b;
+}
+"#,
+        );
+        type_char_noop(
+            '|',
+            r#"
+fn main() {
+    match x {
+        1 $0 2 => (),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_closing_angle_bracket() {
+        type_char(
+            '<',
+            r#"
+fn foo(x: Vec$0) {}
+"#,
+            r#"
+fn foo(x: Vec<$0>) {}
+"#,
+        );
+        type_char(
+            '<',
+            r#"
+fn main() {
+    let x: std::collections::HashMap$0
+}
+"#,
+            r#"
+fn main() {
+    let x: std::collections::HashMap<$0>
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn does_not_add_closing_angle_bracket() {
+        type_char_noop(
+            '<',
+            r#"
+fn foo(x: Vec$0u8>) {}
+"#,
+        );
+        type_char_noop(
+            '<',
+            r#"
+fn main() {
+    let x = a $0 b;
+}
+"#,
+        );
+        type_char_noop(
+            '<',
+            r#"
+fn main() {
+    Vec::$0
+}
+"#,
+        );
+    }
 }
@@ -0,0 +1,187 @@
+use hir::{Crate, HasVisibility, ModuleDef, Semantics, Visibility};
+use ide_db::{
+    base_db::{CrateId, FileId, SourceDatabase, SourceDatabaseExt},
+    defs::Definition,
+    search::SearchScope,
+    RootDatabase,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{display::TryToNav, NavigationTarget};
+
+// Feature: Unused Public Items
+//
+// Finds the `pub` items of a crate which are never referenced by the other crates of the
+// workspace, that is which could be made `pub(crate)` or private. Both the items declared in
+// modules and the associated items of inherent impls are checked, modules themselves are not.
+// Items are public only if all the modules containing them are public up to the crate root, the
+// items of private modules can't be used by other crates.
+//
+// Only usages in workspace crates depending on the crate are looked for, so every public item of
+// a crate nothing depends on, like a binary, is reported.
+//
+// |===
+// | Editor  | Action Name
+//
+// | CLI     | **rust-analyzer unused-public-items**
+// |===
+pub(crate) fn unused_public_items(db: &RootDatabase, krate: CrateId) -> Vec<NavigationTarget> {
+    let _p = profile::span("unused_public_items");
+    let sema = Semantics::new(db);
+    let files = dependent_workspace_files(db, krate);
+
+    let mut res = Vec::new();
+    for def in public_items(db, Crate::from(krate)) {
+        let is_used = Definition::ModuleDef(def)
+            .usages(&sema)
+            .in_scope(SearchScope::files(&files))
+            .at_least_one();
+        if !is_used {
+            res.extend(def.try_to_nav(db));
+        }
+    }
+    res.sort_by_key(|nav| (nav.file_id, nav.full_range.start()));
+    res
+}
+
+/// Files of the workspace crates depending on `krate`, excluding the files
+/// which are part of `krate` itself.
+fn dependent_workspace_files(db: &RootDatabase, krate: CrateId) -> Vec<FileId> {
+    let crate_graph = db.crate_graph();
+    let mut visited_roots = FxHashSet::default();
+    let mut res = Vec::new();
+    for rev_dep in crate_graph.transitive_rev_deps(krate) {
+        let source_root_id = db.file_source_root(crate_graph[rev_dep].root_file_id);
+        if !visited_roots.insert(source_root_id) {
+            continue;
+        }
+        let source_root = db.source_root(source_root_id);
+        if source_root.is_library {
+            continue;
+        }
+        // Crates of the same package share a source root with `krate`.
+        res.extend(
+            source_root.iter().filter(|&file_id| !db.relevant_crates(file_id).contains(&krate)),
+        );
+    }
+    res
+}
+
+/// The public items of `krate`, which are declared in modules public up to the
+/// crate root.
+fn public_items(db: &RootDatabase, krate: Crate) -> Vec<ModuleDef> {
+    let mut res = Vec::new();
+    let mut worklist = vec![krate.root_module(db)];
+    while let Some(module) = worklist.pop() {
+        worklist.extend(module.children(db).filter(|&child| {
+            module.visibility_of(db, &ModuleDef::Module(child)) == Some(Visibility::Public)
+        }));
+        res.extend(module.declarations(db).into_iter().filter(|def| {
+            !matches!(def, ModuleDef::Module(_))
+                && module.visibility_of(db, def) == Some(Visibility::Public)
+        }));
+        for impl_ in module.impl_defs(db) {
+            if impl_.trait_(db).is_some() {
+                continue;
+            }
+            res.extend(
+                impl_
+                    .items(db)
+                    .into_iter()
+                    .filter(|item| item.visibility(db) == Visibility::Public)
+                    .map(ModuleDef::from),
+            );
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expected: &[&str]) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let krate = analysis.crate_for(file_id).unwrap()[0];
+        let mut actual = analysis
+            .unused_public_items(krate)
+            .unwrap()
+            .into_iter()
+            .map(|nav| nav.name.to_string())
+            .collect::<Vec<_>>();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn finds_items_unused_by_dependents() {
+        check(
+            r#"
+//- /lib.rs crate:lib
+pub struct Used;
+pub struct Unused;
+pub(crate) struct Private;
+pub fn used_fn() {}
+pub fn unused_fn() { used_internally() }
+pub fn used_internally() {}
+pub mod inner {
+    pub const USED: u32 = 0;
+    pub const UNUSED: u32 = 0;
+}
+impl Used {
+    pub fn used_method(&self) {}
+    pub fn unused_method(&self) {}
+    fn private_method(&self) {}
+}
+//- /main.rs crate:main deps:lib
+use lib::{inner::USED, Used};
+fn main() {
+    lib::used_fn();
+    Used.used_method();
+    let _ = USED;
+}
+"#,
+            &["UNUSED", "Unused", "unused_fn", "unused_method", "used_internally"],
+        );
+    }
+
+    #[test]
+    fn ignores_items_of_private_modules() {
+        check(
+            r#"
+//- /lib.rs crate:lib
+mod private {
+    pub struct Hidden;
+    impl Hidden {
+        pub fn method(&self) {}
+    }
+}
+pub mod public {
+    pub fn unused() {}
+    pub(crate) mod restricted {
+        pub fn hidden() {}
+    }
+    mod nested {
+        pub fn hidden() {}
+    }
+}
+//- /main.rs crate:main deps:lib
+fn main() {}
+"#,
+            &["unused"],
+        );
+    }
+
+    #[test]
+    fn ignores_library_dependents() {
+        check(
+            r#"
+//- /lib.rs crate:lib
+pub fn foo() {}
+//- /ext.rs crate:ext deps:lib library
+fn bar() { lib::foo() }
+"#,
+            &["foo"],
+        );
+    }
+}
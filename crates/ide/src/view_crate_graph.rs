@@ -2,10 +2,11 @@ use std::sync::Arc;
 
 use dot::{Id, LabelText};
 use ide_db::{
-    base_db::{CrateGraph, CrateId, Dependency, SourceDatabase, SourceDatabaseExt},
+    base_db::{CrateGraph, CrateId, SourceDatabase, SourceDatabaseExt},
     RootDatabase,
 };
-use rustc_hash::FxHashSet;
+use regex::Regex;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 // Feature: View Crate Graph
 //
@@ -14,67 +15,172 @@ use rustc_hash::FxHashSet;
 //
 // Only workspace crates are included, no crates.io dependencies or sysroot crates.
 //
+// The graph is also available as a list of crates with their dependencies, so that clients can
+// render it without graphviz. It can be narrowed down to crates with names matching a regular
+// expression, and chains of crates with a single dependent and a single dependency can be
+// collapsed into a single edge.
+//
 // |===
 // | Editor  | Action Name
 //
 // | VS Code | **Rust Analyzer: View Crate Graph**
 // |===
-pub(crate) fn view_crate_graph(db: &RootDatabase, full: bool) -> Result<String, String> {
+#[derive(Debug, Clone, Default)]
+pub struct CrateGraphConfig {
+    /// Include all crates, not just the workspace ones.
+    pub full: bool,
+    /// Only include crates with names matching this regular expression.
+    pub name_filter: Option<String>,
+    /// Replace crates with exactly one dependent and one dependency by an
+    /// edge from the former to the latter.
+    pub collapse_chains: bool,
+}
+
+#[derive(Debug)]
+pub struct CrateGraphView {
+    /// The graph in GraphViz "dot" syntax.
+    pub dot: String,
+    pub crates: Vec<CrateGraphNode>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrateGraphNode {
+    pub id: CrateId,
+    pub name: String,
+    pub is_workspace_member: bool,
+    /// Dependencies of the crate included in the view, possibly through a
+    /// collapsed chain of crates.
+    pub dependencies: Vec<CrateId>,
+}
+
+pub(crate) fn view_crate_graph(
+    db: &RootDatabase,
+    config: &CrateGraphConfig,
+) -> Result<CrateGraphView, String> {
+    let name_filter = match &config.name_filter {
+        Some(it) => Some(Regex::new(it).map_err(|err| format!("invalid crate filter: {}", err))?),
+        None => None,
+    };
+
     let crate_graph = db.crate_graph();
-    let crates_to_render = crate_graph
+    let is_workspace_member = |krate: CrateId| {
+        let root_id = db.file_source_root(crate_graph[krate].root_file_id);
+        !db.source_root(root_id).is_library
+    };
+    let mut crates_to_render: FxHashSet<CrateId> = crate_graph
         .iter()
-        .filter(|krate| {
-            if full {
-                true
-            } else {
-                // Only render workspace crates
-                let root_id = db.file_source_root(crate_graph[*krate].root_file_id);
-                !db.source_root(root_id).is_library
-            }
+        .filter(|&krate| config.full || is_workspace_member(krate))
+        .filter(|&krate| match &name_filter {
+            Some(regex) => regex.is_match(crate_name(&crate_graph, krate)),
+            None => true,
+        })
+        .collect();
+
+    let mut dependencies: FxHashMap<CrateId, Vec<CrateId>> = crates_to_render
+        .iter()
+        .map(|&krate| {
+            let deps = crate_graph[krate]
+                .dependencies
+                .iter()
+                .map(|dep| dep.crate_id)
+                .filter(|dep| crates_to_render.contains(dep))
+                .collect();
+            (krate, deps)
         })
         .collect();
-    let graph = DotCrateGraph { graph: crate_graph, crates_to_render };
+    if config.collapse_chains {
+        collapse_chains(&mut crates_to_render, &mut dependencies);
+    }
+
+    let mut crates = crates_to_render
+        .iter()
+        .map(|&krate| {
+            let mut dependencies = dependencies[&krate].clone();
+            dependencies.sort_by_key(|it| it.0);
+            CrateGraphNode {
+                id: krate,
+                name: crate_name(&crate_graph, krate).to_string(),
+                is_workspace_member: is_workspace_member(krate),
+                dependencies,
+            }
+        })
+        .collect::<Vec<_>>();
+    crates.sort_by_key(|it| it.id.0);
 
+    let graph = DotCrateGraph { graph: crate_graph.clone(), crates_to_render, dependencies };
     let mut dot = Vec::new();
     dot::render(&graph, &mut dot).unwrap();
-    Ok(String::from_utf8(dot).unwrap())
+    Ok(CrateGraphView { dot: String::from_utf8(dot).unwrap(), crates })
+}
+
+fn crate_name(crate_graph: &CrateGraph, krate: CrateId) -> &str {
+    crate_graph[krate].display_name.as_ref().map_or("(unnamed crate)", |name| &*name)
+}
+
+/// Removes crates with exactly one dependent and one dependency, linking
+/// the dependent to the dependency directly.
+fn collapse_chains(
+    crates: &mut FxHashSet<CrateId>,
+    dependencies: &mut FxHashMap<CrateId, Vec<CrateId>>,
+) {
+    loop {
+        let mut dependents: FxHashMap<CrateId, Vec<CrateId>> = FxHashMap::default();
+        for (&krate, deps) in dependencies.iter() {
+            for &dep in deps {
+                dependents.entry(dep).or_default().push(krate);
+            }
+        }
+        let link = crates.iter().copied().find_map(|krate| {
+            match (dependents.get(&krate).map(|it| &it[..]), &dependencies[&krate][..]) {
+                (Some(&[dependent]), &[dep]) if dependent != dep => Some((dependent, krate, dep)),
+                _ => None,
+            }
+        });
+        let (dependent, krate, dep) = match link {
+            Some(it) => it,
+            None => break,
+        };
+
+        crates.remove(&krate);
+        dependencies.remove(&krate);
+        let deps = dependencies.get_mut(&dependent).unwrap();
+        deps.retain(|&it| it != krate);
+        if !deps.contains(&dep) {
+            deps.push(dep);
+        }
+    }
 }
 
 struct DotCrateGraph {
     graph: Arc<CrateGraph>,
     crates_to_render: FxHashSet<CrateId>,
+    dependencies: FxHashMap<CrateId, Vec<CrateId>>,
 }
 
-type Edge<'a> = (CrateId, &'a Dependency);
+type Edge = (CrateId, CrateId);
 
-impl<'a> dot::GraphWalk<'a, CrateId, Edge<'a>> for DotCrateGraph {
+impl<'a> dot::GraphWalk<'a, CrateId, Edge> for DotCrateGraph {
     fn nodes(&'a self) -> dot::Nodes<'a, CrateId> {
         self.crates_to_render.iter().copied().collect()
     }
 
-    fn edges(&'a self) -> dot::Edges<'a, Edge<'a>> {
-        self.crates_to_render
+    fn edges(&'a self) -> dot::Edges<'a, Edge> {
+        self.dependencies
             .iter()
-            .flat_map(|krate| {
-                self.graph[*krate]
-                    .dependencies
-                    .iter()
-                    .filter(|dep| self.crates_to_render.contains(&dep.crate_id))
-                    .map(move |dep| (*krate, dep))
-            })
+            .flat_map(|(&krate, deps)| deps.iter().map(move |&dep| (krate, dep)))
             .collect()
     }
 
-    fn source(&'a self, edge: &Edge<'a>) -> CrateId {
+    fn source(&'a self, edge: &Edge) -> CrateId {
         edge.0
     }
 
-    fn target(&'a self, edge: &Edge<'a>) -> CrateId {
-        edge.1.crate_id
+    fn target(&'a self, edge: &Edge) -> CrateId {
+        edge.1
     }
 }
 
-impl<'a> dot::Labeller<'a, CrateId, Edge<'a>> for DotCrateGraph {
+impl<'a> dot::Labeller<'a, CrateId, Edge> for DotCrateGraph {
     fn graph_id(&'a self) -> Id<'a> {
         Id::new("rust_analyzer_crate_graph").unwrap()
     }
@@ -88,7 +194,68 @@ impl<'a> dot::Labeller<'a, CrateId, Edge<'a>> for DotCrateGraph {
     }
 
     fn node_label(&'a self, n: &CrateId) -> LabelText<'a> {
-        let name = self.graph[*n].display_name.as_ref().map_or("(unnamed crate)", |name| &*name);
-        LabelText::LabelStr(name.into())
+        LabelText::LabelStr(crate_name(&self.graph, *n).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixture, CrateGraphConfig};
+
+    fn check(ra_fixture: &str, config: CrateGraphConfig, expected: &[(&str, &[&str])]) {
+        let (analysis, _) = fixture::file(ra_fixture);
+        let view = analysis.view_crate_graph(config).unwrap().unwrap();
+        let name = |id| view.crates.iter().find(|it| it.id == id).unwrap().name.as_str();
+        let mut actual = view
+            .crates
+            .iter()
+            .map(|krate| {
+                let mut deps = krate.dependencies.iter().map(|&it| name(it)).collect::<Vec<_>>();
+                deps.sort();
+                (krate.name.as_str(), deps)
+            })
+            .collect::<Vec<_>>();
+        actual.sort();
+        let expected =
+            expected.iter().map(|(name, deps)| (*name, deps.to_vec())).collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    const FIXTURE: &str = r#"
+//- /main.rs crate:main deps:a,util
+//- /a.rs crate:a deps:b
+//- /b.rs crate:b deps:util
+//- /util.rs crate:util
+//- /ext.rs crate:ext library
+"#;
+
+    #[test]
+    fn workspace_only() {
+        check(
+            FIXTURE,
+            CrateGraphConfig::default(),
+            &[("a", &["b"]), ("b", &["util"]), ("main", &["a", "util"]), ("util", &[])],
+        );
+    }
+
+    #[test]
+    fn name_filter() {
+        check(
+            FIXTURE,
+            CrateGraphConfig {
+                name_filter: Some("^(main|a|b)$".to_string()),
+                ..Default::default()
+            },
+            &[("a", &["b"]), ("b", &[]), ("main", &["a"])],
+        );
+    }
+
+    #[test]
+    fn collapse_chains() {
+        check(
+            FIXTURE,
+            CrateGraphConfig { collapse_chains: true, ..Default::default() },
+            &[("main", &["util"]), ("util", &[])],
+        );
     }
 }
@@ -1,10 +1,13 @@
-use hir::{Function, Semantics};
+use hir::{DefWithBody, Semantics};
 use ide_db::base_db::FilePosition;
 use ide_db::RootDatabase;
-use syntax::{algo::find_node_at_offset, ast, AstNode};
+use syntax::{algo::ancestors_at_offset, ast, match_ast, AstNode};
 
 // Feature: View Hir
 //
+// Shows the lowered HIR of the body of the function, const or static containing the cursor,
+// printed as Rust-like source code.
+//
 // |===
 // | Editor  | Action Name
 //
@@ -12,15 +15,23 @@ use syntax::{algo::find_node_at_offset, ast, AstNode};
 // |===
 // image::https://user-images.githubusercontent.com/48062697/113065588-068bdb80-91b1-11eb-9a78-0b4ef1e972fb.gif[]
 pub(crate) fn view_hir(db: &RootDatabase, position: FilePosition) -> String {
-    body_hir(db, position).unwrap_or_else(|| "Not inside a function body".to_string())
+    body_hir(db, position).unwrap_or_else(|| "Not inside a function, const or static".to_string())
 }
 
 fn body_hir(db: &RootDatabase, position: FilePosition) -> Option<String> {
     let sema = Semantics::new(db);
     let source_file = sema.parse(position.file_id);
 
-    let function = find_node_at_offset::<ast::Fn>(source_file.syntax(), position.offset)?;
-
-    let function: Function = sema.to_def(&function)?;
-    Some(function.debug_hir(db))
+    let def: DefWithBody =
+        ancestors_at_offset(source_file.syntax(), position.offset).find_map(|node| {
+            match_ast! {
+                match node {
+                    ast::Fn(it) => sema.to_def(&it).map(Into::into),
+                    ast::Const(it) => sema.to_def(&it).map(Into::into),
+                    ast::Static(it) => sema.to_def(&it).map(Into::into),
+                    _ => None,
+                }
+            }
+        })?;
+    Some(def.debug_hir(db))
 }
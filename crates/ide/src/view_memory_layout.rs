@@ -0,0 +1,198 @@
+use hir::{Semantics, Type};
+use ide_db::{
+    base_db::FilePosition,
+    defs::{Definition, NameClass, NameRefClass},
+    RootDatabase,
+};
+use syntax::{ast, AstNode};
+
+/// A field of the type whose layout is viewed, or the type itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryLayoutNode {
+    /// The name of the field, or of the item the type belongs to for the root.
+    pub item_name: String,
+    pub typename: String,
+    pub size: u64,
+    pub alignment: u64,
+    /// The offset in the parent, zero for the root.
+    pub offset: u64,
+    /// The number of padding bytes after the field, before the next field or
+    /// the end of the parent.
+    pub padding: u64,
+    /// The fields, in the order they are placed in memory.
+    pub children: Vec<MemoryLayoutNode>,
+}
+
+// Feature: View Memory Layout
+//
+// Shows the memory layout of the type under the cursor, with the size, offset and padding of
+// its fields, recursively.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **Rust Analyzer: View Memory Layout**
+// |===
+pub(crate) fn view_memory_layout(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<MemoryLayoutNode> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let node =
+        sema.find_node_at_offset_with_descend::<ast::NameLike>(file.syntax(), position.offset)?;
+    let def = match &node {
+        ast::NameLike::Name(name) => NameClass::classify(&sema, name)?.defined()?,
+        ast::NameLike::NameRef(name_ref) => match NameRefClass::classify(&sema, name_ref)? {
+            NameRefClass::Definition(it) => it,
+            NameRefClass::FieldShorthand { local_ref, .. } => Definition::Local(local_ref),
+        },
+        ast::NameLike::Lifetime(_) => return None,
+    };
+    let ty = match def {
+        Definition::ModuleDef(hir::ModuleDef::Adt(it)) => it.ty(db),
+        Definition::ModuleDef(hir::ModuleDef::TypeAlias(it)) => it.ty(db),
+        Definition::ModuleDef(hir::ModuleDef::BuiltinType(it)) => {
+            it.ty(db, sema.to_module_def(position.file_id)?)
+        }
+        Definition::ModuleDef(hir::ModuleDef::Static(it)) => it.ty(db),
+        Definition::SelfType(it) => it.self_ty(db),
+        Definition::Local(it) => it.ty(db),
+        Definition::Field(it) => it.ty(db),
+        _ => return None,
+    };
+    let name = def.name(db).map_or_else(|| ty.display(db).to_string(), |it| it.to_string());
+    layout_node(db, name, &ty, 0)
+}
+
+fn layout_node(
+    db: &RootDatabase,
+    item_name: String,
+    ty: &Type,
+    offset: u64,
+) -> Option<MemoryLayoutNode> {
+    let layout = ty.layout(db)?;
+    let mut fields = ty
+        .fields(db)
+        .into_iter()
+        .map(|(field, ty)| (field.name(db).to_string(), ty))
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        fields = ty
+            .tuple_fields(db)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ty)| (idx.to_string(), ty))
+            .collect();
+    }
+
+    let mut children = fields
+        .into_iter()
+        .zip(layout.field_offsets.iter())
+        .map(|((name, ty), &offset)| layout_node(db, name, &ty, offset))
+        .collect::<Option<Vec<_>>>()?;
+    children.sort_by_key(|it| it.offset);
+    let offsets = children.iter().map(|it| it.offset).collect::<Vec<_>>();
+    for (idx, child) in children.iter_mut().enumerate() {
+        let end = child.offset + child.size;
+        // The fields of a union all start at the same offset.
+        let next = offsets[idx + 1..].iter().copied().find(|&it| it >= end).unwrap_or(layout.size);
+        child.padding = next - end;
+    }
+
+    Some(MemoryLayoutNode {
+        item_name,
+        typename: ty.display(db).to_string(),
+        size: layout.size,
+        alignment: layout.align,
+        offset,
+        padding: 0,
+        children,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use stdx::format_to;
+
+    use super::MemoryLayoutNode;
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let node = analysis.view_memory_layout(position).unwrap().unwrap();
+        let mut buf = String::new();
+        render(&mut buf, &node, 0);
+        expect.assert_eq(&buf);
+    }
+
+    fn render(buf: &mut String, node: &MemoryLayoutNode, depth: usize) {
+        format_to!(
+            buf,
+            "{}{}: {} size={} align={} offset={} padding={}\n",
+            "  ".repeat(depth),
+            node.item_name,
+            node.typename,
+            node.size,
+            node.alignment,
+            node.offset,
+            node.padding,
+        );
+        for child in &node.children {
+            render(buf, child, depth + 1);
+        }
+    }
+
+    #[test]
+    fn struct_fields() {
+        check(
+            r#"
+struct Inner { a: u8, b: u16 }
+struct Foo$0 { x: u8, inner: Inner, y: u64 }
+"#,
+            expect![[r#"
+                Foo: Foo size=16 align=8 offset=0 padding=0
+                  y: u64 size=8 align=8 offset=0 padding=0
+                  inner: Inner size=4 align=2 offset=8 padding=0
+                    b: u16 size=2 align=2 offset=0 padding=0
+                    a: u8 size=1 align=1 offset=2 padding=1
+                  x: u8 size=1 align=1 offset=12 padding=3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn repr_c_tuple_local() {
+        check(
+            r#"
+#[repr(C)]
+struct Foo { a: u8, b: u32 }
+fn main() {
+    let x$0 = (Foo { a: 0, b: 0 }, true);
+}
+"#,
+            expect![[r#"
+                x: (Foo, bool) size=12 align=4 offset=0 padding=0
+                  0: Foo size=8 align=4 offset=0 padding=0
+                    a: u8 size=1 align=1 offset=0 padding=3
+                    b: u32 size=4 align=4 offset=4 padding=0
+                  1: bool size=1 align=1 offset=8 padding=3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn union_fields() {
+        check(
+            r#"
+union U$0 { a: u8, b: u32 }
+"#,
+            expect![[r#"
+                U: U size=4 align=4 offset=0 padding=0
+                  a: u8 size=1 align=1 offset=0 padding=3
+                  b: u32 size=4 align=4 offset=0 padding=0
+            "#]],
+        );
+    }
+}
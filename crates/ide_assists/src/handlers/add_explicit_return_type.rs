@@ -0,0 +1,247 @@
+use hir::{HirDisplay, ModuleDef, PathResolution};
+use itertools::Itertools;
+use syntax::{
+    ast::{self, AstNode},
+    TextRange, T,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: add_explicit_return_type
+//
+// Adds the inferred return type to a closure, or spells out the output type of an
+// `async` block, which becomes an `async move` block, with a turbofish on its tail.
+// Unlike `infer_function_return_type`, this uses the whole signature of the closure,
+// so `return` expressions are taken into account.
+//
+// ```
+// fn main() {
+//     let x = |$0a: i32, b: i32| a + b;
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let x = |a: i32, b: i32| -> i32 { a + b };
+// }
+// ```
+pub(crate) fn add_explicit_return_type(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    if let Some(closure) = ctx.find_node_at_offset::<ast::ClosureExpr>() {
+        let param_list = closure.param_list()?;
+        let header = TextRange::new(
+            closure.syntax().text_range().start(),
+            param_list.syntax().text_range().end(),
+        );
+        if header.contains_range(ctx.frange.range) {
+            return add_closure_return_type(acc, ctx, closure, param_list);
+        }
+    }
+    let block = ctx.find_node_at_offset::<ast::EffectExpr>()?;
+    let async_token = block.async_token()?;
+    let block_expr = block.block_expr()?;
+    let header = TextRange::new(
+        block.syntax().text_range().start(),
+        block_expr.syntax().text_range().start(),
+    );
+    if !header.contains_range(ctx.frange.range) {
+        return None;
+    }
+    add_async_block_output_type(acc, ctx, async_token.text_range(), &block, &block_expr)
+}
+
+fn add_closure_return_type(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+    closure: ast::ClosureExpr,
+    param_list: ast::ParamList,
+) -> Option<()> {
+    let existing = match closure.ret_type() {
+        Some(ret_type) => match ret_type.ty() {
+            Some(ast::Type::InferType(_)) | None => Some(ret_type.syntax().text_range()),
+            Some(_) => return None,
+        },
+        None => None,
+    };
+    let body = closure.body()?;
+    let module = ctx.sema.scope(closure.syntax()).module()?;
+    let callable =
+        ctx.sema.type_of_expr(&ast::Expr::ClosureExpr(closure.clone()))?.as_callable(ctx.db())?;
+    let ret_ty = callable.return_type();
+    if ret_ty.is_unit() || ret_ty.is_unknown() {
+        return None;
+    }
+    let ret_ty = ret_ty.display_source_code(ctx.db(), module.into()).ok()?;
+
+    acc.add(
+        AssistId("add_explicit_return_type", AssistKind::RefactorRewrite),
+        "Add explicit return type to closure",
+        param_list.syntax().text_range(),
+        |builder| {
+            match existing {
+                Some(range) => builder.replace(range, format!("-> {}", ret_ty)),
+                None => builder
+                    .insert(param_list.syntax().text_range().end(), format!(" -> {}", ret_ty)),
+            }
+            // `|x| -> T x` isn't valid, the body has to be a block.
+            if !matches!(body, ast::Expr::BlockExpr(_)) {
+                cov_mark::hit!(add_explicit_return_type_wrap_body);
+                builder.replace(body.syntax().text_range(), format!("{{ {} }}", body));
+            }
+        },
+    )
+}
+
+fn add_async_block_output_type(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+    async_range: TextRange,
+    block: &ast::EffectExpr,
+    block_expr: &ast::BlockExpr,
+) -> Option<()> {
+    // The type can only be spelled out as the generic arguments of an enum variant.
+    let tail = match block_expr.tail_expr()? {
+        ast::Expr::CallExpr(it) => it,
+        _ => return None,
+    };
+    let path = match tail.expr()? {
+        ast::Expr::PathExpr(it) => it.path()?,
+        _ => return None,
+    };
+    if path.segment()?.generic_arg_list().is_some() {
+        return None;
+    }
+    match ctx.sema.resolve_path(&path)? {
+        PathResolution::Def(ModuleDef::Variant(_)) => (),
+        _ => return None,
+    }
+    let module = ctx.sema.scope(block.syntax()).module()?;
+    let ty = ctx.sema.type_of_expr(&ast::Expr::CallExpr(tail.clone()))?;
+    let args = ty
+        .type_arguments()
+        .map(|it| it.display_source_code(ctx.db(), module.into()).ok())
+        .collect::<Option<Vec<_>>>()?;
+    if args.is_empty() {
+        return None;
+    }
+    let move_token = block.syntax().children_with_tokens().find(|it| it.kind() == T![move]);
+
+    acc.add(
+        AssistId("add_explicit_return_type", AssistKind::RefactorRewrite),
+        "Add explicit output type to async block",
+        async_range,
+        |builder| {
+            if move_token.is_none() {
+                builder.insert(async_range.end(), " move");
+            }
+            builder.insert(
+                path.syntax().text_range().end(),
+                format!("::<{}>", args.iter().join(", ")),
+            );
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn uses_the_signature_of_closures() {
+        check_assist(
+            add_explicit_return_type,
+            r#"
+//- minicore: option
+fn f() {
+    let parse = |$0s: &str| {
+        if s.is_empty() {
+            return None;
+        }
+        Some(s.len())
+    };
+}
+"#,
+            r#"
+fn f() {
+    let parse = |s: &str| -> Option<usize> {
+        if s.is_empty() {
+            return None;
+        }
+        Some(s.len())
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_closure_body_in_block() {
+        cov_mark::check!(add_explicit_return_type_wrap_body);
+        check_assist(
+            add_explicit_return_type,
+            r#"
+fn f() {
+    let inc = move |$0x: u32| x + 1;
+}
+"#,
+            r#"
+fn f() {
+    let inc = move |x: u32| -> u32 { x + 1 };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_turbofish_to_async_block_tail() {
+        check_assist(
+            add_explicit_return_type,
+            r#"
+//- minicore: option, future
+fn f() {
+    let fut = as$0ync {
+        let x = 1u8;
+        Some(x)
+    };
+}
+"#,
+            r#"
+fn f() {
+    let fut = async move {
+        let x = 1u8;
+        Some::<u8>(x)
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable() {
+        check_assist_not_applicable(
+            add_explicit_return_type,
+            r#"
+fn f() {
+    let x = |$0a: u32| -> u32 { a };
+}
+"#,
+        );
+        check_assist_not_applicable(
+            add_explicit_return_type,
+            r#"
+fn f() {
+    let x = |$0| ();
+}
+"#,
+        );
+        check_assist_not_applicable(
+            add_explicit_return_type,
+            r#"
+fn f() {
+    let fut = async$0 { 1u32 };
+}
+"#,
+        );
+    }
+}
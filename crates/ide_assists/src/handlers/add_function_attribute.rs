@@ -0,0 +1,214 @@
+use ide_db::{base_db::FileId, defs::Definition};
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, edit::IndentLevel, AttrsOwner, NameOwner},
+    AstNode, SyntaxKind, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists, GroupLabel};
+
+// Assist: add_function_attribute
+//
+// Adds one of `#[must_use]`, `#[inline]` or `#[track_caller]` to a function. The calls
+// discarding the result, which `#[must_use]` would make warn, are turned into `let _ = ..;`
+// statements, so they show up in the preview of the edit.
+//
+// ```
+// fn $0area(w: u32, h: u32) -> u32 { w * h }
+// ```
+// ->
+// ```
+// #[must_use]
+// fn area(w: u32, h: u32) -> u32 { w * h }
+// ```
+pub(crate) fn add_function_attribute(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let func = ctx.find_node_at_offset::<ast::Fn>()?;
+    let header_end = match func.body() {
+        Some(body) => body.syntax().text_range().start(),
+        None => func.syntax().text_range().end(),
+    };
+    if !TextRange::new(func.syntax().text_range().start(), header_end).contains(ctx.offset()) {
+        return None;
+    }
+    let name = func.name()?;
+    // Attributes go after the existing ones and the doc comments.
+    let offset = func
+        .syntax()
+        .children_with_tokens()
+        .find(|it| {
+            !matches!(it.kind(), SyntaxKind::ATTR | SyntaxKind::COMMENT | SyntaxKind::WHITESPACE)
+        })?
+        .text_range()
+        .start();
+    let indent = IndentLevel::from_node(func.syntax());
+    let has_attr = |name: &str| {
+        func.attrs().any(|attr| attr.path().map_or(false, |path| path.syntax().text() == name))
+    };
+    let in_trait_impl = func
+        .syntax()
+        .parent()
+        .and_then(|it| it.parent())
+        .and_then(ast::Impl::cast)
+        .map_or(false, |it| it.trait_().is_some());
+
+    let group = GroupLabel(format!("Add attribute to `{}`", name));
+    let mut add = |attr: &str, label: String| {
+        acc.add_group(
+            &group,
+            AssistId("add_function_attribute", AssistKind::Generate),
+            label,
+            name.syntax().text_range(),
+            |builder| {
+                // Searching for the callers is only worth it once the assist is applied.
+                if attr == "must_use" {
+                    for (file_id, stmt) in discarding_calls(ctx, &func) {
+                        builder.edit_file(file_id);
+                        builder.insert(stmt.syntax().text_range().start(), "let _ = ");
+                    }
+                    builder.edit_file(ctx.frange.file_id);
+                }
+                builder.insert(offset, format!("#[{}]\n{}", attr, indent))
+            },
+        )
+    };
+
+    let returns_value = func.ret_type().and_then(|it| it.ty()).map_or(false, |ty| match ty {
+        ast::Type::TupleType(it) => it.fields().next().is_some(),
+        ast::Type::NeverType(_) => false,
+        _ => true,
+    });
+    if returns_value && !in_trait_impl && !has_attr("must_use") {
+        add("must_use", "Add `#[must_use]`".to_string());
+    }
+    if func.body().is_some() && !has_attr("inline") {
+        add("inline", "Add `#[inline]`".to_string());
+    }
+    if func.abi().is_none() && !has_attr("track_caller") {
+        add("track_caller", "Add `#[track_caller]`".to_string());
+    }
+    Some(())
+}
+
+/// Returns the calls of `func` which are statements, discarding the result.
+fn discarding_calls(ctx: &AssistContext, func: &ast::Fn) -> Vec<(FileId, ast::ExprStmt)> {
+    let def = match ctx.sema.to_def(func) {
+        Some(it) => Definition::ModuleDef(it.into()),
+        None => return Vec::new(),
+    };
+    let mut res = Vec::new();
+    for (file_id, references) in def.usages(&ctx.sema).all() {
+        let source_file = ctx.sema.parse(file_id);
+        for reference in references {
+            let name_ref =
+                find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range);
+            let call = name_ref.and_then(|it| {
+                it.syntax().ancestors().find_map(|node| {
+                    if let Some(call) = ast::CallExpr::cast(node.clone()) {
+                        let callee_range = call.expr()?.syntax().text_range();
+                        return callee_range.contains_range(reference.range).then(|| node);
+                    }
+                    let call = ast::MethodCallExpr::cast(node.clone())?;
+                    (call.name_ref()?.syntax().text_range() == reference.range).then(|| node)
+                })
+            });
+            let stmt = call
+                .and_then(|it| it.parent())
+                .and_then(ast::ExprStmt::cast)
+                .filter(|it| it.semicolon_token().is_some());
+            if let Some(stmt) = stmt {
+                res.push((file_id, stmt));
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn adds_must_use_and_keeps_discarding_calls_quiet() {
+        check_assist_by_label(
+            add_function_attribute,
+            r#"
+struct Counter;
+impl Counter {
+    /// Adds one.
+    #[doc(alias = "inc")]
+    pub fn $0increment(&mut self) -> u32 { 0 }
+}
+fn f(c: &mut Counter) -> u32 {
+    c.increment();
+    Counter::increment(c);
+    let x = c.increment();
+    c.increment()
+}
+"#,
+            r#"
+struct Counter;
+impl Counter {
+    /// Adds one.
+    #[doc(alias = "inc")]
+    #[must_use]
+    pub fn increment(&mut self) -> u32 { 0 }
+}
+fn f(c: &mut Counter) -> u32 {
+    let _ = c.increment();
+    let _ = Counter::increment(c);
+    let x = c.increment();
+    c.increment()
+}
+"#,
+            "Add `#[must_use]`",
+        );
+    }
+
+    #[test]
+    fn adds_inline_and_track_caller() {
+        check_assist_by_label(
+            add_function_attribute,
+            r#"
+fn check$0(x: u32) {
+    assert!(x > 0);
+}
+"#,
+            r#"
+#[inline]
+fn check(x: u32) {
+    assert!(x > 0);
+}
+"#,
+            "Add `#[inline]`",
+        );
+        check_assist_by_label(
+            add_function_attribute,
+            r#"
+mod m {
+    fn check$0(x: u32) {}
+}
+"#,
+            r#"
+mod m {
+    #[track_caller]
+    fn check(x: u32) {}
+}
+"#,
+            "Add `#[track_caller]`",
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_attributes_exist() {
+        check_assist_not_applicable(
+            add_function_attribute,
+            r#"
+#[inline]
+#[track_caller]
+extern "C" fn $0f() {}
+"#,
+        );
+    }
+}
@@ -40,7 +40,9 @@ use crate::{AssistContext, AssistId, AssistKind, Assists, GroupLabel};
 //
 // - `crate`: Merge imports from the same crate into a single use statement. This kind of
 //  nesting is only supported in Rust versions later than 1.24.
-// - `module`: Merge imports from the same module into a single use statement.
+// - `module`: Merge imports from the same module into a single use statement. An existing import
+//  nesting the imports of several modules, including the one of the new import, is split into one
+//  import per module first.
 // - `item`: Don't merge imports at all, creating one import per item.
 // - `preserve`: Do not change the granularity of any imports. For auto-import this has the same
 //  effect as `item`.
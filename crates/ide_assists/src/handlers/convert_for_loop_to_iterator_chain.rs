@@ -0,0 +1,523 @@
+use ide_db::{
+    defs::Definition,
+    search::{ReferenceCategory, SearchScope},
+};
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, ArgListOwner, LoopBodyOwner, NameOwner},
+    AstNode, SyntaxKind, SyntaxNode, TextRange,
+};
+
+use crate::{utils::iterator_of, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_for_loop_to_iterator_chain
+//
+// Converts a `for` loop pushing into a vector or accumulating into a variable, which is
+// declared right before, into an iterator chain collecting or folding the items.
+//
+// ```
+// # //- minicore: iterators
+// fn main() {
+//     let mut squares = Vec::new();
+//     for$0 x in 0..10 {
+//         squares.push(x * x);
+//     }
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let squares: Vec<_> = (0..10).map(|x| x * x).collect();
+// }
+// ```
+pub(crate) fn convert_for_loop_to_iterator_chain(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let for_loop = ctx.find_node_at_offset::<ast::ForExpr>()?;
+    let body = for_loop.loop_body()?;
+    if body.syntax().text_range().start() < ctx.offset() || for_loop.label().is_some() {
+        return None;
+    }
+    let pat = for_loop.pat()?;
+    let iterable = for_loop.iterable()?;
+    let loop_node = match for_loop.syntax().parent().and_then(ast::ExprStmt::cast) {
+        Some(stmt) => stmt.syntax().clone(),
+        None => for_loop.syntax().clone(),
+    };
+    let let_stmt = loop_node.prev_sibling().and_then(ast::LetStmt::cast)?;
+    let ident_pat = match let_stmt.pat()? {
+        ast::Pat::IdentPat(it) if it.mut_token().is_some() => it,
+        _ => return None,
+    };
+    let name = ident_pat.name()?.to_string();
+    let init = let_stmt.initializer()?;
+
+    let expr = match (body.statements().next(), body.tail_expr()) {
+        (Some(ast::Stmt::ExprStmt(stmt)), None) if body.statements().count() == 1 => stmt.expr()?,
+        (None, Some(tail)) => tail,
+        _ => return None,
+    };
+    if has_control_flow(expr.syntax()) {
+        cov_mark::hit!(for_loop_to_chain_control_flow);
+        return None;
+    }
+    let step = LoopStep::new(&expr, &name, &init)?;
+
+    let local = ctx.sema.to_def(&ident_pat)?;
+    let range = let_stmt.syntax().text_range().cover(loop_node.text_range());
+    acc.add(
+        AssistId("convert_for_loop_to_iterator_chain", AssistKind::RefactorRewrite),
+        "Convert `for` loop to iterator chain",
+        range,
+        |builder| {
+            let keep_mut = is_mutated_outside(ctx, local, range);
+            let mut buf = format!("let {}{}", if keep_mut { "mut " } else { "" }, name);
+            let iterator = iterator_of(&ctx.sema, &iterable);
+            match step {
+                LoopStep::Push(item) => {
+                    match let_stmt.ty() {
+                        Some(ty) => format_to!(buf, ": {}", ty),
+                        None => buf.push_str(": Vec<_>"),
+                    }
+                    format_to!(buf, " = {}.map(|{}| {}).collect();", iterator, pat, item)
+                }
+                LoopStep::Accumulate(op, rhs) => {
+                    if let Some(ty) = let_stmt.ty() {
+                        format_to!(buf, ": {}", ty);
+                    }
+                    let rhs = match rhs {
+                        ast::Expr::BinExpr(_) => format!("({})", rhs),
+                        _ => rhs.to_string(),
+                    };
+                    format_to!(
+                        buf,
+                        " = {}.fold({}, |{2}, {3}| {2} {4} {5});",
+                        iterator,
+                        init,
+                        name,
+                        pat,
+                        op,
+                        rhs
+                    )
+                }
+            }
+            builder.replace(range, buf);
+        },
+    )
+}
+
+// Assist: convert_iterator_chain_to_for_loop
+//
+// Converts a variable initialized by collecting a mapped iterator into a vector, or by
+// folding an iterator, into a `for` loop.
+//
+// ```
+// fn main() {
+//     let sum = $0[1, 2, 3].iter().fold(0, |acc, x| acc + x);
+// }
+// ```
+// ->
+// ```
+// fn main() {
+//     let mut sum = 0;
+//     for x in [1, 2, 3].iter() {
+//         sum += x;
+//     }
+// }
+// ```
+pub(crate) fn convert_iterator_chain_to_for_loop(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let let_stmt = ctx.find_node_at_offset::<ast::LetStmt>()?;
+    let name = match let_stmt.pat()? {
+        ast::Pat::IdentPat(it) if it.ref_token().is_none() && it.pat().is_none() => it.name()?,
+        _ => return None,
+    };
+    let call = match let_stmt.initializer()? {
+        ast::Expr::MethodCallExpr(it) => it,
+        _ => return None,
+    };
+    let args = call.arg_list()?.args().collect::<Vec<_>>();
+    let chain = match (call.name_ref()?.text().as_str(), args.as_slice()) {
+        ("collect", []) => {
+            let map = match call.receiver()? {
+                ast::Expr::MethodCallExpr(it) if it.name_ref()?.text() == "map" => it,
+                _ => return None,
+            };
+            let is_vec = match let_stmt.ty() {
+                Some(ast::Type::PathType(ty)) => ty
+                    .path()
+                    .and_then(|it| it.segment())
+                    .and_then(|it| it.name_ref())
+                    .map_or(false, |it| it.text() == "Vec"),
+                Some(_) => false,
+                None => ctx
+                    .sema
+                    .type_of_expr(&ast::Expr::MethodCallExpr(call.clone()))
+                    .and_then(|ty| ty.as_adt())
+                    .map_or(false, |adt| adt.name(ctx.db()).to_string() == "Vec"),
+            };
+            if !is_vec {
+                return None;
+            }
+            let closure = match map.arg_list()?.args().collect::<Vec<_>>().as_slice() {
+                [ast::Expr::ClosureExpr(it)] => it.clone(),
+                _ => return None,
+            };
+            Chain::Collect { iterator: map.receiver()?, closure }
+        }
+        ("fold", [init, ast::Expr::ClosureExpr(closure)]) => {
+            Chain::Fold { iterator: call.receiver()?, init: init.clone(), closure: closure.clone() }
+        }
+        _ => return None,
+    };
+
+    let closure = chain.closure();
+    let mut params =
+        closure.param_list()?.params().map(|it| it.pat()).collect::<Option<Vec<_>>>()?;
+    let closure_body = match closure.body()? {
+        ast::Expr::BlockExpr(block) if block.statements().next().is_none() => block.tail_expr()?,
+        ast::Expr::BlockExpr(_) => return None,
+        it => it,
+    };
+    if has_control_flow(closure_body.syntax()) {
+        cov_mark::hit!(chain_to_for_loop_control_flow);
+        return None;
+    }
+    let item = params.pop()?;
+    let acc_param = params.pop();
+    if !params.is_empty() || matches!(chain, Chain::Fold { .. }) != acc_param.is_some() {
+        return None;
+    }
+    let renames = match &acc_param {
+        Some(ast::Pat::IdentPat(pat)) => {
+            let local = ctx.sema.to_def(pat)?;
+            Definition::Local(local)
+                .usages(&ctx.sema)
+                .in_scope(SearchScope::single_file(ctx.frange.file_id))
+                .all()
+                .references
+                .values()
+                .flatten()
+                .map(|it| it.range)
+                .collect()
+        }
+        Some(_) => return None,
+        None => Vec::new(),
+    };
+
+    acc.add(
+        AssistId("convert_iterator_chain_to_for_loop", AssistKind::RefactorRewrite),
+        "Convert iterator chain to `for` loop",
+        let_stmt.syntax().text_range(),
+        |builder| {
+            let indent = IndentLevel::from_node(let_stmt.syntax());
+            let mut buf = format!("let mut {}", name);
+            if let Some(ty) = let_stmt.ty() {
+                format_to!(buf, ": {}", ty);
+            }
+            let iterator = match chain.iterator() {
+                ast::Expr::MethodCallExpr(call)
+                    if call.name_ref().map_or(false, |it| it.text() == "into_iter")
+                        && call.arg_list().map_or(false, |it| it.args().next().is_none()) =>
+                {
+                    call.receiver()
+                        .map_or_else(|| chain.iterator().to_string(), |it| it.to_string())
+                }
+                it => it.to_string(),
+            };
+            let step = match &chain {
+                Chain::Collect { .. } => {
+                    format_to!(buf, " = Vec::new();");
+                    format!("{}.push({});", name, closure_body)
+                }
+                Chain::Fold { init, .. } => {
+                    format_to!(buf, " = {};", init);
+                    accumulation(&closure_body, &renames, &name.to_string())
+                }
+            };
+            format_to!(
+                buf,
+                "\n{0}for {1} in {2} {{\n{3}{4}\n{0}}}",
+                indent,
+                item,
+                iterator,
+                indent + 1,
+                step
+            );
+            builder.replace(let_stmt.syntax().text_range(), buf);
+        },
+    )
+}
+
+enum LoopStep {
+    /// `v.push(item)`
+    Push(ast::Expr),
+    /// `acc op= rhs`
+    Accumulate(&'static str, ast::Expr),
+}
+
+impl LoopStep {
+    fn new(expr: &ast::Expr, name: &str, init: &ast::Expr) -> Option<LoopStep> {
+        match expr {
+            ast::Expr::MethodCallExpr(call) => {
+                let receiver = call.receiver()?;
+                if receiver.syntax().text() != name || call.name_ref()?.text() != "push" {
+                    return None;
+                }
+                let is_new_vec = match init {
+                    ast::Expr::CallExpr(it) => it.syntax().text() == "Vec::new()",
+                    ast::Expr::MacroCall(it) => it.syntax().text() == "vec![]",
+                    _ => false,
+                };
+                let item = match call.arg_list()?.args().collect::<Vec<_>>().as_slice() {
+                    [item] => item.clone(),
+                    _ => return None,
+                };
+                if !is_new_vec || mentions(item.syntax(), name) {
+                    return None;
+                }
+                Some(LoopStep::Push(item))
+            }
+            ast::Expr::BinExpr(bin) => {
+                if bin.lhs()?.syntax().text() != name {
+                    return None;
+                }
+                let op = COMPOUND_OPS.iter().find(|(_, it)| bin.op_kind() == Some(*it))?.0;
+                Some(LoopStep::Accumulate(op, bin.rhs()?))
+            }
+            _ => None,
+        }
+    }
+}
+
+const COMPOUND_OPS: &[(&str, ast::BinOp)] = &[
+    ("+", ast::BinOp::AddAssign),
+    ("-", ast::BinOp::SubAssign),
+    ("*", ast::BinOp::MulAssign),
+    ("/", ast::BinOp::DivAssign),
+    ("%", ast::BinOp::RemAssign),
+    ("&", ast::BinOp::BitAndAssign),
+    ("|", ast::BinOp::BitOrAssign),
+    ("^", ast::BinOp::BitXorAssign),
+    ("<<", ast::BinOp::ShlAssign),
+    (">>", ast::BinOp::ShrAssign),
+];
+
+enum Chain {
+    /// `iterator.map(closure).collect()`
+    Collect { iterator: ast::Expr, closure: ast::ClosureExpr },
+    /// `iterator.fold(init, closure)`
+    Fold { iterator: ast::Expr, init: ast::Expr, closure: ast::ClosureExpr },
+}
+
+impl Chain {
+    fn iterator(&self) -> &ast::Expr {
+        match self {
+            Chain::Collect { iterator, .. } | Chain::Fold { iterator, .. } => iterator,
+        }
+    }
+
+    fn closure(&self) -> &ast::ClosureExpr {
+        match self {
+            Chain::Collect { closure, .. } | Chain::Fold { closure, .. } => closure,
+        }
+    }
+}
+
+/// Returns the statement updating `name` with the value of the folding
+/// closure `body`, where `renames` are the uses of the accumulator.
+fn accumulation(body: &ast::Expr, renames: &[TextRange], name: &str) -> String {
+    let renamed = |node: &SyntaxNode| {
+        let mut buf = String::new();
+        for token in node.descendants_with_tokens().filter_map(|it| it.into_token()) {
+            if renames.contains(&token.text_range()) {
+                buf.push_str(name);
+            } else {
+                buf.push_str(token.text());
+            }
+        }
+        buf
+    };
+    if let ast::Expr::BinExpr(bin) = body {
+        let op =
+            COMPOUND_OPS.iter().find(|(op, _)| bin.op_token().map_or(false, |it| it.text() == *op));
+        if let (Some((op, _)), Some(lhs), Some(rhs)) = (op, bin.lhs(), bin.rhs()) {
+            if renames.contains(&lhs.syntax().text_range()) {
+                return format!("{} {}= {};", name, op, renamed(rhs.syntax()));
+            }
+        }
+    }
+    format!("{} = {};", name, renamed(body.syntax()))
+}
+
+/// Checks if `node` contains expressions which jump out of a loop or of a
+/// closure, and whose meaning would change.
+fn has_control_flow(node: &SyntaxNode) -> bool {
+    node.descendants().any(|it| {
+        matches!(
+            it.kind(),
+            SyntaxKind::BREAK_EXPR
+                | SyntaxKind::CONTINUE_EXPR
+                | SyntaxKind::RETURN_EXPR
+                | SyntaxKind::TRY_EXPR
+                | SyntaxKind::AWAIT_EXPR
+        )
+    })
+}
+
+fn mentions(node: &SyntaxNode, name: &str) -> bool {
+    node.descendants().filter_map(ast::NameRef::cast).any(|it| it.text() == name)
+}
+
+/// Checks if `local` is assigned or borrowed mutably outside of `range`, so that
+/// it has to stay mutable.
+fn is_mutated_outside(ctx: &AssistContext, local: hir::Local, range: TextRange) -> bool {
+    let usages = Definition::Local(local)
+        .usages(&ctx.sema)
+        .in_scope(SearchScope::single_file(ctx.frange.file_id))
+        .all();
+    usages.references.values().flatten().filter(|it| !range.contains_range(it.range)).any(|it| {
+        if it.category == Some(ReferenceCategory::Write) {
+            return true;
+        }
+        let node = match &it.name {
+            ast::NameLike::NameRef(name_ref) => name_ref.syntax().clone(),
+            _ => return false,
+        };
+        let expr = match node.ancestors().find_map(ast::PathExpr::cast) {
+            Some(it) => it,
+            None => return false,
+        };
+        match expr.syntax().parent() {
+            Some(parent) => {
+                ast::MethodCallExpr::cast(parent.clone()).is_some()
+                    || ast::RefExpr::cast(parent).map_or(false, |it| it.mut_token().is_some())
+            }
+            None => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn converts_push_loop_to_collect() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+fn f(names: &[&str]) {
+    let mut lengths: Vec<usize> = Vec::new();
+    $0for name in names {
+        lengths.push(name.len());
+    }
+    lengths.sort();
+}
+"#,
+            r#"
+fn f(names: &[&str]) {
+    let mut lengths: Vec<usize> = names.into_iter().map(|name| name.len()).collect();
+    lengths.sort();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn converts_accumulating_loop_to_fold() {
+        check_assist(
+            convert_for_loop_to_iterator_chain,
+            r#"
+fn f(xs: Vec<u32>) -> u32 {
+    let mut total = 1;
+    for$0 x in xs {
+        total *= x + 1;
+    }
+    total
+}
+"#,
+            r#"
+fn f(xs: Vec<u32>) -> u32 {
+    let total = xs.into_iter().fold(1, |total, x| total * (x + 1));
+    total
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn for_loop_not_applicable_with_control_flow() {
+        cov_mark::check!(for_loop_to_chain_control_flow);
+        check_assist_not_applicable(
+            convert_for_loop_to_iterator_chain,
+            r#"
+fn f(xs: Vec<Option<u32>>) -> Option<()> {
+    let mut v = Vec::new();
+    for$0 x in xs {
+        v.push(x?);
+    }
+    None
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn converts_collect_to_loop() {
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+fn f(xs: Vec<u32>) {
+    let doubled: Vec<u32> = $0xs.into_iter().map(|x| { x * 2 }).collect();
+}
+"#,
+            r#"
+fn f(xs: Vec<u32>) {
+    let mut doubled: Vec<u32> = Vec::new();
+    for x in xs {
+        doubled.push(x * 2);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn converts_fold_to_loop() {
+        check_assist(
+            convert_iterator_chain_to_for_loop,
+            r#"
+fn f(xs: Vec<u32>) {
+    let $0max = xs.into_iter().fold(0, |m, x| if x > m { x } else { m });
+}
+"#,
+            r#"
+fn f(xs: Vec<u32>) {
+    let mut max = 0;
+    for x in xs {
+        max = if x > max { x } else { max };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn chain_not_applicable_with_control_flow() {
+        cov_mark::check!(chain_to_for_loop_control_flow);
+        check_assist_not_applicable(
+            convert_iterator_chain_to_for_loop,
+            r#"
+fn f(xs: Vec<u32>) {
+    let v: Vec<u32> = $0xs.into_iter().map(|x| x.checked_add(1)?).collect();
+}
+"#,
+        );
+    }
+}
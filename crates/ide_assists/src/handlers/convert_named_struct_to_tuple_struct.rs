@@ -0,0 +1,446 @@
+use either::Either;
+use ide_db::{defs::Definition, search::FileReference};
+use syntax::{
+    ast::{self, AstNode, GenericParamsOwner, NameOwner, VisibilityOwner},
+    match_ast, SyntaxKind, SyntaxNode, TextRange,
+};
+
+use crate::{assist_context::AssistBuilder, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_named_struct_to_tuple_struct
+//
+// Converts struct with named fields to tuple struct, and analogously for enum variants with named
+// fields.
+//
+// ```
+// struct Point$0 { x: f32, y: f32 }
+//
+// impl Point {
+//     pub fn new(x: f32, y: f32) -> Self {
+//         Point { x, y }
+//     }
+//
+//     pub fn x(&self) -> f32 {
+//         self.x
+//     }
+//
+//     pub fn y(&self) -> f32 {
+//         self.y
+//     }
+// }
+// ```
+// ->
+// ```
+// struct Point(f32, f32);
+//
+// impl Point {
+//     pub fn new(x: f32, y: f32) -> Self {
+//         Point(x, y)
+//     }
+//
+//     pub fn x(&self) -> f32 {
+//         self.0
+//     }
+//
+//     pub fn y(&self) -> f32 {
+//         self.1
+//     }
+// }
+// ```
+pub(crate) fn convert_named_struct_to_tuple_struct(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let strukt = ctx
+        .find_node_at_offset::<ast::Struct>()
+        .map(Either::Left)
+        .or_else(|| ctx.find_node_at_offset::<ast::Variant>().map(Either::Right))?;
+    let field_list = strukt.as_ref().either(|s| s.field_list(), |v| v.field_list())?;
+    let record_fields = match field_list {
+        ast::FieldList::RecordFieldList(it) => it,
+        ast::FieldList::TupleFieldList(_) => return None,
+    };
+    let strukt_def = match &strukt {
+        Either::Left(s) => Either::Left(ctx.sema.to_def(s)?),
+        Either::Right(v) => Either::Right(ctx.sema.to_def(v)?),
+    };
+    let target = strukt.as_ref().either(|s| s.syntax(), |v| v.syntax()).text_range();
+
+    acc.add(
+        AssistId("convert_named_struct_to_tuple_struct", AssistKind::RefactorRewrite),
+        "Convert to tuple struct",
+        target,
+        |edit| {
+            let names = record_fields
+                .fields()
+                .map(|it| it.name().map(|it| it.to_string()).unwrap_or_default())
+                .collect::<Vec<_>>();
+            edit_field_references(ctx, edit, record_fields.fields());
+            edit_struct_references(ctx, edit, strukt_def, &names);
+            edit_struct_def(ctx, edit, &strukt, record_fields);
+        },
+    )
+}
+
+fn edit_struct_def(
+    ctx: &AssistContext,
+    edit: &mut AssistBuilder,
+    strukt: &Either<ast::Struct, ast::Variant>,
+    record_fields: ast::RecordFieldList,
+) {
+    let tuple_fields = record_fields
+        .fields()
+        .filter_map(|f| Some(ast::make::tuple_field(f.visibility(), f.ty()?)));
+    let tuple_fields = ast::make::tuple_field_list(tuple_fields);
+    let record_fields_text_range = record_fields.syntax().text_range();
+
+    edit.edit_file(ctx.frange.file_id);
+
+    // The tuple fields follow the name or the generic parameters directly.
+    let mut start = record_fields_text_range.start();
+    if let Some(ws) = record_fields.syntax().prev_sibling_or_token() {
+        if ws.kind() == SyntaxKind::WHITESPACE {
+            start = ws.text_range().start();
+        }
+    }
+
+    match strukt {
+        Either::Left(strukt) => match strukt.where_clause() {
+            // `struct Foo<T> where T: Clone { .. }` becomes `struct Foo<T>(..)\nwhere T: Clone;`
+            Some(w) => {
+                let mut where_start = w.syntax().text_range().start();
+                if let Some(ws) = w.syntax().prev_sibling_or_token() {
+                    if ws.kind() == SyntaxKind::WHITESPACE {
+                        where_start = ws.text_range().start();
+                    }
+                }
+                edit.delete(TextRange::new(where_start, start));
+                edit.replace(
+                    TextRange::new(start, record_fields_text_range.end()),
+                    format!("{}\n{};", tuple_fields, w.to_string().trim_end_matches(',')),
+                );
+            }
+            None => edit.replace(
+                TextRange::new(start, record_fields_text_range.end()),
+                format!("{};", tuple_fields),
+            ),
+        },
+        Either::Right(_) => edit.replace(
+            TextRange::new(start, record_fields_text_range.end()),
+            tuple_fields.to_string(),
+        ),
+    }
+}
+
+fn edit_struct_references(
+    ctx: &AssistContext,
+    edit: &mut AssistBuilder,
+    strukt: Either<hir::Struct, hir::Variant>,
+    names: &[String],
+) {
+    let strukt_def = match strukt {
+        Either::Left(s) => Definition::ModuleDef(hir::ModuleDef::Adt(hir::Adt::Struct(s))),
+        Either::Right(v) => Definition::ModuleDef(hir::ModuleDef::Variant(v)),
+    };
+    let usages = strukt_def.usages(&ctx.sema).include_self_refs().all();
+    let field_index = |name: &str| names.iter().position(|it| it == name);
+
+    let edit_node = |edit: &mut AssistBuilder, node: SyntaxNode| -> Option<()> {
+        match_ast! {
+            match node {
+                ast::RecordPat(record_pat) => {
+                    let field_list = record_pat.record_pat_field_list()?;
+                    let mut pats = vec![None; names.len()];
+                    for field in field_list.fields() {
+                        let idx = field_index(&field.field_name()?.text())?;
+                        pats[idx] = field.pat();
+                    }
+                    if field_list.dotdot_token().is_some() {
+                        // Only the fields after the last one which is matched may be left out.
+                        let len = pats.iter().rposition(Option::is_some).map_or(0, |it| it + 1);
+                        pats.truncate(len);
+                    }
+                    let mut pats = pats
+                        .into_iter()
+                        .map(|it| it.unwrap_or_else(|| ast::make::wildcard_pat().into()))
+                        .map(|it| it.to_string())
+                        .collect::<Vec<_>>();
+                    if field_list.dotdot_token().is_some() {
+                        pats.push("..".to_string());
+                    }
+                    edit.replace(
+                        record_pat.syntax().text_range(),
+                        format!("{}({})", record_pat.path()?, pats.join(", ")),
+                    );
+                },
+                ast::RecordExpr(record_expr) => {
+                    let field_list = record_expr.record_expr_field_list()?;
+                    let fields = field_list.fields().collect::<Vec<_>>();
+                    let indices = fields
+                        .iter()
+                        .map(|it| field_index(&it.field_name()?.text()))
+                        .collect::<Option<Vec<_>>>()?;
+
+                    let in_order = indices.iter().enumerate().all(|(pos, idx)| pos == *idx);
+                    if in_order && field_list.spread().is_none() {
+                        // The fields are only unwrapped to keep the edits of their values intact.
+                        edit_record_expr_in_place(edit, &record_expr, &field_list, &fields)?;
+                    } else {
+                        // Tuple structs can be created with fields named by their index, which
+                        // keeps the order of evaluation and the base of the update:
+                        // `Foo { 1: y, 0: x }`, `Foo { 0: x, ..base }`.
+                        for (field, idx) in fields.iter().zip(indices) {
+                            match field.name_ref() {
+                                Some(name_ref) => {
+                                    edit.replace(name_ref.syntax().text_range(), idx.to_string())
+                                }
+                                None => edit.insert(
+                                    field.syntax().text_range().start(),
+                                    format!("{}: ", idx),
+                                ),
+                            }
+                        }
+                    }
+                },
+                _ => return None,
+            }
+        }
+        Some(())
+    };
+
+    for (file_id, refs) in usages {
+        edit.edit_file(file_id);
+        for r in refs {
+            if let Some(node) = record_of_path(&r) {
+                edit_node(edit, node);
+            }
+        }
+    }
+}
+
+/// Returns the record expression or pattern whose path is the reference `r`,
+/// like `Foo { .. }` for `Foo`.
+fn record_of_path(r: &FileReference) -> Option<SyntaxNode> {
+    let mut path = r.name.syntax().ancestors().find_map(ast::Path::cast)?;
+    while let Some(parent) = path.syntax().parent().and_then(ast::Path::cast) {
+        path = parent;
+    }
+    let parent = path.syntax().parent()?;
+    match parent.kind() {
+        SyntaxKind::RECORD_EXPR | SyntaxKind::RECORD_PAT => Some(parent),
+        _ => None,
+    }
+}
+
+/// Rewrites `Foo { a: x, b }` into `Foo(x, b)` by only replacing the braces and
+/// removing the field names.
+fn edit_record_expr_in_place(
+    edit: &mut AssistBuilder,
+    record_expr: &ast::RecordExpr,
+    field_list: &ast::RecordExprFieldList,
+    fields: &[ast::RecordExprField],
+) -> Option<()> {
+    let path_end = record_expr.path()?.syntax().text_range().end();
+    let l_curly = field_list.l_curly_token()?.text_range();
+    let r_curly = field_list.r_curly_token()?.text_range();
+    let (first, last) = match (fields.first(), fields.last()) {
+        (Some(first), Some(last)) => (first.syntax().text_range(), last.syntax().text_range()),
+        _ => {
+            edit.replace(TextRange::new(path_end, r_curly.end()), "()");
+            return Some(());
+        }
+    };
+
+    if field_list.syntax().text().contains_char('\n') {
+        edit.replace(TextRange::new(path_end, l_curly.end()), "(");
+        edit.replace(r_curly, ")");
+    } else {
+        edit.replace(TextRange::new(path_end, first.start()), "(");
+        edit.replace(TextRange::new(last.end(), r_curly.end()), ")");
+    }
+    for field in fields {
+        if let (Some(name_ref), Some(expr)) = (field.name_ref(), field.expr()) {
+            let name_start = name_ref.syntax().text_range().start();
+            edit.delete(TextRange::new(name_start, expr.syntax().text_range().start()));
+        }
+    }
+    Some(())
+}
+
+fn edit_field_references(
+    ctx: &AssistContext,
+    edit: &mut AssistBuilder,
+    fields: impl Iterator<Item = ast::RecordField>,
+) {
+    for (index, field) in fields.enumerate() {
+        let field = match ctx.sema.to_def(&field) {
+            Some(it) => it,
+            None => continue,
+        };
+        let def = Definition::Field(field);
+        let usages = def.usages(&ctx.sema).all();
+        for (file_id, refs) in usages {
+            edit.edit_file(file_id);
+            for r in refs {
+                // The fields of records are rewritten along with the records.
+                let name_ref = match r.name.as_name_ref() {
+                    Some(it) if it.syntax().parent().and_then(ast::FieldExpr::cast).is_some() => it,
+                    _ => continue,
+                };
+                edit.replace(name_ref.syntax().text_range(), index.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn not_applicable_other_than_record_struct() {
+        check_assist_not_applicable(convert_named_struct_to_tuple_struct, r#"struct Foo$0(u32);"#);
+        check_assist_not_applicable(convert_named_struct_to_tuple_struct, r#"struct Foo$0;"#);
+    }
+
+    #[test]
+    fn convert_simple_struct() {
+        check_assist(
+            convert_named_struct_to_tuple_struct,
+            r#"
+struct Inner;
+struct A$0 { inner: Inner }
+
+impl A {
+    fn new(inner: Inner) -> A {
+        A { inner }
+    }
+
+    fn with_inner(&self, inner: Inner) -> Self {
+        Self {
+            inner: Inner,
+        }
+    }
+
+    fn into_inner(self) -> Inner {
+        self.inner
+    }
+}
+"#,
+            r#"
+struct Inner;
+struct A(Inner);
+
+impl A {
+    fn new(inner: Inner) -> A {
+        A(inner)
+    }
+
+    fn with_inner(&self, inner: Inner) -> Self {
+        Self(
+            Inner,
+        )
+    }
+
+    fn into_inner(self) -> Inner {
+        self.0
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_destructured_and_updated_struct() {
+        check_assist(
+            convert_named_struct_to_tuple_struct,
+            r#"
+struct A$0 { first: u32, second: u64, third: bool }
+
+fn f(a: A) -> A {
+    let A { second, .. } = a;
+    let A { third: t, first, second: _ } = a;
+    A { first: a.first + 1, ..a }
+}
+
+fn g(a: &A) -> A {
+    A { third: true, second: 0, first: a.first }
+}
+"#,
+            r#"
+struct A(u32, u64, bool);
+
+fn f(a: A) -> A {
+    let A(_, second, ..) = a;
+    let A(first, _, t) = a;
+    A { 0: a.0 + 1, ..a }
+}
+
+fn g(a: &A) -> A {
+    A { 2: true, 1: 0, 0: a.0 }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_variant_across_files() {
+        check_assist(
+            convert_named_struct_to_tuple_struct,
+            r#"
+//- /main.rs
+mod foo;
+
+pub enum E {
+    V$0 { pub x: i32 },
+}
+//- /foo.rs
+use crate::E;
+
+fn f(e: E) -> i32 {
+    match e {
+        E::V { x } => x,
+    }
+}
+"#,
+            r#"
+//- /main.rs
+mod foo;
+
+pub enum E {
+    V(pub i32),
+}
+//- /foo.rs
+use crate::E;
+
+fn f(e: E) -> i32 {
+    match e {
+        E::V(x) => x,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_struct_with_where_clause() {
+        check_assist(
+            convert_named_struct_to_tuple_struct,
+            r#"
+struct Wrap$0<T>
+where
+    T: Clone,
+{
+    field: T,
+}
+"#,
+            r#"
+struct Wrap<T>(T)
+where
+    T: Clone;
+"#,
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use ide_db::helpers::{insert_whitespaces, TokenSpacing};
+use itertools::Itertools;
+use syntax::{ast, AstNode, SyntaxKind::IDENT};
+
+use crate::{utils::remove_derive, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: expand_derive
+//
+// Replaces a derive with the items it expands to, so that they can be customized. Built-in
+// derives of traits with methods, like `Clone`, expand to impls without any items, and can't be
+// expanded.
+//
+// ```
+// # //- minicore: derive, clone, copy
+// #[derive(Clone, Co$0py)]
+// struct Point;
+// ```
+// ->
+// ```
+// #[derive(Clone)]
+// struct Point;
+//
+// impl core::marker::Copy for Point {}
+// ```
+pub(crate) fn expand_derive(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let attr = ctx.find_node_at_offset::<ast::Attr>()?;
+    let (name, args) = attr.as_simple_call()?;
+    if name != "derive" || !args.syntax().text_range().contains(ctx.offset()) {
+        return None;
+    }
+    let derive_token = args.syntax().token_at_offset(ctx.offset()).find(|t| t.kind() == IDENT)?;
+    let derive_name = derive_token.text();
+    let adt = attr.syntax().parent().and_then(ast::Adt::cast)?;
+
+    // Only marker traits can be implemented without items.
+    if ctx.sema.is_builtin_derive_macro(&attr, derive_name) && !matches!(derive_name, "Copy" | "Eq")
+    {
+        cov_mark::hit!(expand_derive_builtin_with_items);
+        return None;
+    }
+
+    let expansion = ctx.sema.expand_derive_macro(&attr, derive_name)?;
+    let items = expansion.children().filter_map(ast::Item::cast).collect::<Vec<_>>();
+    if items.is_empty() {
+        cov_mark::hit!(expand_derive_empty_expansion);
+        return None;
+    }
+
+    acc.add(
+        AssistId("expand_derive", AssistKind::RefactorRewrite),
+        format!("Expand `{}` derive", derive_name),
+        attr.syntax().text_range(),
+        |builder| {
+            remove_derive(builder, &args, derive_name, &attr);
+            let items = items.iter().map(|item| {
+                let tokens = item
+                    .syntax()
+                    .descendants_with_tokens()
+                    .filter_map(|it| it.into_token())
+                    .collect::<Vec<_>>();
+                insert_whitespaces(tokens, 4, TokenSpacing::Rustfmt, |_, _| ()).trim().to_string()
+            });
+            let text = format!("\n\n{}", items.format("\n\n"));
+            builder.insert(adt.syntax().text_range().end(), text);
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn expands_derive_of_generic_struct() {
+        check_assist(
+            expand_derive,
+            r#"
+//- minicore: derive, copy
+#[derive(Clone, Co$0py)]
+struct Wrapper<T>(T);
+"#,
+            r#"
+#[derive(Clone)]
+struct Wrapper<T>(T);
+
+impl<T0: core::marker::Copy> core::marker::Copy for Wrapper<T0> {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_builtin_derives_with_items() {
+        cov_mark::check!(expand_derive_builtin_with_items);
+        check_assist_not_applicable(
+            expand_derive,
+            r#"
+//- minicore: derive, clone
+#[derive(Cl$0one)]
+struct S;
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_unresolved_derives() {
+        check_assist_not_applicable(
+            expand_derive,
+            r#"
+#[derive(Unknown$0)]
+struct S;
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_empty_expansions() {
+        cov_mark::check!(expand_derive_empty_expansion);
+        check_assist_not_applicable(
+            expand_derive,
+            r#"
+//- proc_macros: derive_identity
+#[derive(proc_macros::DeriveIden$0tity)]
+struct S;
+"#,
+        );
+    }
+}
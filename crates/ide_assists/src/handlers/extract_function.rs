@@ -5,7 +5,7 @@ use either::Either;
 use hir::{HirDisplay, Local};
 use ide_db::{
     defs::{Definition, NameRefClass},
-    search::{FileReference, ReferenceAccess, SearchScope},
+    search::{FileReference, ReferenceCategory, SearchScope},
 };
 use itertools::Itertools;
 use stdx::format_to;
@@ -27,7 +27,9 @@ use crate::{
 
 // Assist: extract_function
 //
-// Extracts selected statements into new function.
+// Extracts selected statements into new function. Statements using `self` are
+// extracted into a new method of the impl, which only borrows `self` mutably if
+// they need to.
 //
 // ```
 // fn main() {
@@ -89,17 +91,19 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext) -> Option
     let control_flow = external_control_flow(ctx, &body)?;
 
     let target_range = body.text_range();
+    let label = if self_param.is_some() { "Extract into method" } else { "Extract into function" };
 
     acc.add(
         AssistId("extract_function", crate::AssistKind::RefactorExtract),
-        "Extract into function",
+        label,
         target_range,
         move |builder| {
             let params = extracted_function_params(ctx, &body, &vars_used_in_body);
+            let self_param = self_param.map(|it| extracted_self_param(ctx, &body, it));
 
             let fun = Function {
                 name: "fun_name".to_string(),
-                self_param: self_param.map(|(_, pat)| pat),
+                self_param,
                 params,
                 control_flow,
                 ret_ty,
@@ -686,6 +690,31 @@ fn self_param_from_usages(
     self_param
 }
 
+/// Makes the receiver of the extracted method. When the original function
+/// borrows `self` mutably, the method borrows it immutably only if the body
+/// provably only reads it.
+fn extracted_self_param(
+    ctx: &AssistContext,
+    body: &FunctionBody,
+    (var, self_param): (Local, ast::SelfParam),
+) -> ast::SelfParam {
+    let kind = match self_param.kind() {
+        ast::SelfParamKind::Owned | ast::SelfParamKind::Ref => return self_param,
+        ast::SelfParamKind::MutRef
+            if has_only_shared_usages(ctx, &LocalUsages::find(ctx, var), body) =>
+        {
+            ast::SelfParamKind::Ref
+        }
+        ast::SelfParamKind::MutRef => return self_param,
+    };
+    if self_param.kind() == kind {
+        // Keeps explicit lifetimes, like in `&'a self`.
+        self_param
+    } else {
+        make::self_param(kind)
+    }
+}
+
 /// find variables that should be extracted as params
 ///
 /// Computes additional info that affects param type and mutability
@@ -734,6 +763,15 @@ fn has_exclusive_usages(ctx: &AssistContext, usages: &LocalUsages, body: &Functi
         .any(|reference| reference_is_exclusive(reference, body, ctx))
 }
 
+/// checks if relevant var is provably only read inside body
+fn has_only_shared_usages(ctx: &AssistContext, usages: &LocalUsages, body: &FunctionBody) -> bool {
+    usages.iter().filter(|reference| body.contains_range(reference.range)).all(|reference| {
+        reference.category != Some(ReferenceCategory::Write)
+            && path_element_of_reference(body, reference)
+                .map_or(false, |path| expr_is_shared_access(ctx, &path))
+    })
+}
+
 /// checks if this reference requires `&mut` access inside node
 fn reference_is_exclusive(
     reference: &FileReference,
@@ -741,7 +779,7 @@ fn reference_is_exclusive(
     ctx: &AssistContext,
 ) -> bool {
     // we directly modify variable with set: `n = 0`, `n += 1`
-    if reference.access == Some(ReferenceAccess::Write) {
+    if reference.category == Some(ReferenceCategory::Write) {
         return true;
     }
 
@@ -792,6 +830,57 @@ fn expr_require_exclusive_access(ctx: &AssistContext, expr: &ast::Expr) -> Optio
     Some(false)
 }
 
+/// checks if this expr is provably only read, recurses on field access
+fn expr_is_shared_access(ctx: &AssistContext, expr: &ast::Expr) -> bool {
+    // FIXME: expand macros and check how the variable is used there?
+    if let ast::Expr::MacroCall(_) = expr {
+        return false;
+    }
+    let parent = match expr.syntax().parent() {
+        Some(it) => it,
+        None => return false,
+    };
+
+    if let Some(bin_expr) = ast::BinExpr::cast(parent.clone()) {
+        return match bin_expr.op_kind() {
+            Some(op) if op.is_assignment() => {
+                let is_lhs = bin_expr.lhs().map_or(true, |lhs| lhs.syntax() == expr.syntax());
+                !is_lhs && is_copied_field(ctx, expr)
+            }
+            Some(_) => true,
+            None => false,
+        };
+    }
+
+    if let Some(ref_expr) = ast::RefExpr::cast(parent.clone()) {
+        return ref_expr.mut_token().is_none();
+    }
+
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        if method_call.receiver().map_or(false, |it| it.syntax() == expr.syntax()) {
+            return ctx
+                .sema
+                .resolve_method_call(&method_call)
+                .and_then(|func| func.self_param(ctx.db()))
+                .map_or(false, |param| matches!(param.access(ctx.db()), hir::Access::Shared));
+        }
+        return is_copied_field(ctx, expr);
+    }
+
+    if let Some(field) = ast::FieldExpr::cast(parent) {
+        return expr_is_shared_access(ctx, &field.into());
+    }
+
+    // Any other use, like passing `self` to a function, may need the exclusive reference itself.
+    is_copied_field(ctx, expr)
+}
+
+/// checks if using this expr by value copies a field, which only reads it
+fn is_copied_field(ctx: &AssistContext, expr: &ast::Expr) -> bool {
+    matches!(expr, ast::Expr::FieldExpr(_))
+        && ctx.sema.type_of_expr(expr).map_or(false, |ty| ty.is_copy(ctx.db()))
+}
+
 /// Container of local variable usages
 ///
 /// Semanticall same as `UsageSearchResult`, but provides more convenient interface
@@ -2283,6 +2372,97 @@ impl S {
         );
     }
 
+    #[test]
+    fn method_with_shared_access_in_mut_method() {
+        check_assist(
+            extract_function,
+            r#"
+struct S { f: i32 };
+
+impl S {
+    fn foo(&mut self) {
+        let n = $0self.f + 1$0;
+        self.f = n;
+    }
+}
+"#,
+            r#"
+struct S { f: i32 };
+
+impl S {
+    fn foo(&mut self) {
+        let n = self.fun_name();
+        self.f = n;
+    }
+
+    fn $0fun_name(&self) -> i32 {
+        self.f + 1
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_passing_mut_self_along() {
+        check_assist(
+            extract_function,
+            r#"
+struct S { f: i32 };
+fn bar(s: &mut S) {}
+
+impl S {
+    fn foo(&mut self) {
+        $0bar(self);$0
+    }
+}
+"#,
+            r#"
+struct S { f: i32 };
+fn bar(s: &mut S) {}
+
+impl S {
+    fn foo(&mut self) {
+        self.fun_name();
+    }
+
+    fn $0fun_name(&mut self) {
+        bar(self);
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn method_with_owned_self() {
+        check_assist(
+            extract_function,
+            r#"
+struct S { f: i32 };
+
+impl S {
+    fn into_f(self) -> i32 {
+        $0self.f$0
+    }
+}
+"#,
+            r#"
+struct S { f: i32 };
+
+impl S {
+    fn into_f(self) -> i32 {
+        self.fun_name()
+    }
+
+    fn $0fun_name(self) -> i32 {
+        self.f
+    }
+}
+"#,
+        );
+    }
+
     #[test]
     fn variable_defined_inside_and_used_after_no_ret() {
         check_assist(
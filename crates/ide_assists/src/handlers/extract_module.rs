@@ -0,0 +1,713 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use hir::PathResolution;
+use ide_db::{
+    base_db::{AnchoredPathBuf, FileId},
+    defs::NameClass,
+};
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+use stdx::format_to;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, edit::IndentLevel, NameOwner, VisibilityOwner},
+    AstNode, NodeOrToken, SyntaxKind, SyntaxNode, TextRange, TextSize,
+};
+
+use crate::{
+    assist_context::{AssistBuilder, AssistContext, Assists},
+    utils::vis_offset,
+    AssistId, AssistKind, GroupLabel,
+};
+
+// Assist: extract_module
+//
+// Extracts the selected items into a new module. The public items are re-exported with
+// their visibility, the other items used outside of the selection are made `pub(crate)` and
+// the paths to them are fixed up.
+//
+// ```
+// $0fn foo(name: i32) -> i32 {
+//     name + 1
+// }$0
+//
+// fn bar(name: i32) -> i32 {
+//     foo(name) + 1
+// }
+// ```
+// ->
+// ```
+// use modname::foo;
+//
+// mod modname {
+//     pub(crate) fn foo(name: i32) -> i32 {
+//         name + 1
+//     }
+// }
+//
+// fn bar(name: i32) -> i32 {
+//     foo(name) + 1
+// }
+// ```
+pub(crate) fn extract_module(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    if ctx.frange.range.is_empty() {
+        return None;
+    }
+    let items = selected_items(ctx)?;
+    let first = items.first()?.syntax().clone();
+    let target =
+        TextRange::new(first.text_range().start(), items.last()?.syntax().text_range().end());
+    let module = ctx.sema.scope(&first).module()?;
+    let is_file_level = first.parent()?.kind() == SyntaxKind::SOURCE_FILE;
+
+    let group = GroupLabel("Extract into module".to_string());
+    acc.add_group(
+        &group,
+        AssistId("extract_module", AssistKind::RefactorExtract),
+        "Extract into module",
+        target,
+        |builder| {
+            let extraction = Extraction::new(ctx, module, &items, target);
+            let indent = IndentLevel::from_node(&first);
+            let mut buf = extraction.parent_imports(indent);
+            format_to!(buf, "{}mod {} {{\n", extraction.module_visibility(), MODULE_NAME);
+            let inner_indent = indent + 1;
+            if let Some(imports) = extraction.super_imports() {
+                format_to!(buf, "{}{}\n\n", inner_indent, imports);
+            }
+            for (idx, line) in extraction.items.lines().enumerate() {
+                match idx {
+                    0 => format_to!(buf, "{}{}\n", inner_indent, line),
+                    _ if line.trim().is_empty() => buf.push('\n'),
+                    _ => format_to!(buf, "    {}\n", line),
+                }
+            }
+            format_to!(buf, "{}}}", indent);
+
+            builder.replace(target, buf);
+            extraction.fix_paths(builder, ctx.frange.file_id);
+        },
+    );
+
+    if !is_file_level {
+        return Some(());
+    }
+    acc.add_group(
+        &group,
+        AssistId("extract_module", AssistKind::RefactorExtract),
+        "Extract into module file",
+        target,
+        |builder| {
+            let extraction = Extraction::new(ctx, module, &items, target);
+            let mut buf = extraction.parent_imports(IndentLevel(0));
+            format_to!(buf, "{}mod {};", extraction.module_visibility(), MODULE_NAME);
+            builder.replace(target, buf);
+
+            let mut contents = String::new();
+            if let Some(imports) = extraction.super_imports() {
+                format_to!(contents, "{}\n\n", imports);
+            }
+            format_to!(contents, "{}\n", extraction.items);
+            let path = match module.name(ctx.db()) {
+                Some(name) if !module.is_mod_rs(ctx.db()) => {
+                    format!("./{}/{}.rs", name, MODULE_NAME)
+                }
+                _ => format!("./{}.rs", MODULE_NAME),
+            };
+            let dst = AnchoredPathBuf { anchor: ctx.frange.file_id, path };
+            builder.create_file(dst, contents);
+            extraction.fix_paths(builder, ctx.frange.file_id);
+        },
+    )
+}
+
+const MODULE_NAME: &str = "modname";
+
+/// Returns the items covered by the selection, which mustn't cut any of them.
+fn selected_items(ctx: &AssistContext) -> Option<Vec<ast::Item>> {
+    let range = ctx.frange.range;
+    let node = match ctx.covering_element() {
+        NodeOrToken::Node(it) => it,
+        NodeOrToken::Token(it) => it.parent()?,
+    };
+    let parent = node
+        .ancestors()
+        .find(|it| matches!(it.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST))?;
+    let items = parent
+        .children()
+        .filter_map(ast::Item::cast)
+        .filter(|it| it.syntax().text_range().intersect(range).map_or(false, |it| !it.is_empty()))
+        .collect::<Vec<_>>();
+    let is_valid = |item: &ast::Item| match item {
+        // The file of an out-of-line module would have to move too.
+        ast::Item::Module(it) => it.item_list().is_some(),
+        _ => true,
+    };
+    if items.is_empty()
+        || !items.iter().all(|it| range.contains_range(it.syntax().text_range()) && is_valid(it))
+    {
+        return None;
+    }
+    Some(items)
+}
+
+/// The edits moving the items into the new module, which don't depend on
+/// where the module is written.
+struct Extraction {
+    /// The text of the items, with their visibilities and paths fixed up.
+    items: String,
+    /// The names the items refer to which are in scope of the original
+    /// module, imported with `use super::...`.
+    super_imports: BTreeSet<String>,
+    /// The moved items the rest of the original module refers to, imported
+    /// from the new module.
+    parent_imports: BTreeSet<String>,
+    /// The moved public items, re-exported from the original module with their
+    /// visibility, by visibility.
+    reexports: BTreeMap<String, BTreeSet<String>>,
+    /// The offsets where `modname::` is inserted into paths outside of the
+    /// items.
+    path_fixups: FxHashMap<FileId, BTreeSet<TextSize>>,
+}
+
+impl Extraction {
+    fn new(
+        ctx: &AssistContext,
+        module: hir::Module,
+        items: &[ast::Item],
+        range: TextRange,
+    ) -> Extraction {
+        let mut res = Extraction {
+            items: String::new(),
+            super_imports: BTreeSet::new(),
+            parent_imports: BTreeSet::new(),
+            reexports: BTreeMap::new(),
+            path_fixups: FxHashMap::default(),
+        };
+        // Edits of the text of the items, by range in the original file.
+        let mut item_fixups = Vec::new();
+        let moved_names = items
+            .iter()
+            .filter_map(item_name)
+            .map(|it| it.text().to_string())
+            .collect::<BTreeSet<_>>();
+
+        for item in items {
+            res.collect_paths_in_items(ctx, module, item, &moved_names, &mut item_fixups);
+            let reexport = item_name(item).zip(reexport_visibility(item));
+            for def in visibility_candidates(item) {
+                if reexport.is_some() && def == *item.syntax() {
+                    // The item has to be visible enough for its re-export.
+                    item_fixups.extend(reexport_visibility_fixup(item));
+                } else if let Some(fixup) = visibility_fixup(ctx, range, &def) {
+                    item_fixups.push(fixup);
+                }
+            }
+            match reexport {
+                Some((name, visibility)) => {
+                    res.reexports
+                        .entry(visibility.syntax().to_string())
+                        .or_default()
+                        .insert(name.text().to_string());
+                }
+                None => {
+                    if let Some(name) = item_name(item) {
+                        res.collect_references(ctx, module, range, &name, &mut item_fixups);
+                    }
+                }
+            }
+        }
+
+        let root = match items.first() {
+            Some(it) => it.syntax().ancestors().last().unwrap(),
+            None => return res,
+        };
+        let mut text = root.text().slice(range).to_string();
+        item_fixups.sort_by_key(|(range, _)| range.start());
+        for (fixup_range, replacement) in item_fixups.into_iter().rev() {
+            let fixup_range = fixup_range - range.start();
+            text.replace_range(std::ops::Range::<usize>::from(fixup_range), &replacement);
+        }
+        res.items = text;
+        res
+    }
+
+    /// Finds the names used by `item` which won't be in scope in the new module,
+    /// and the relative paths which have to go up one more module.
+    fn collect_paths_in_items(
+        &mut self,
+        ctx: &AssistContext,
+        module: hir::Module,
+        item: &ast::Item,
+        moved_names: &BTreeSet<String>,
+        item_fixups: &mut Vec<(TextRange, String)>,
+    ) {
+        let scope = module.scope(ctx.db(), None);
+        for path in item.syntax().descendants().filter_map(ast::Path::cast) {
+            if path.qualifier().is_some() {
+                continue;
+            }
+            let segment = match path.segment() {
+                Some(it) => it,
+                None => continue,
+            };
+            match segment.kind() {
+                Some(ast::PathSegmentKind::Name(name_ref)) => {
+                    let name = name_ref.text().to_string();
+                    if moved_names.contains(&name) || self.super_imports.contains(&name) {
+                        continue;
+                    }
+                    let def = match ctx.sema.resolve_path(&path) {
+                        Some(PathResolution::Def(def)) => hir::ScopeDef::ModuleDef(def),
+                        Some(PathResolution::Macro(mac)) => hir::ScopeDef::MacroDef(mac),
+                        _ => continue,
+                    };
+                    let in_scope = scope.iter().any(|(scope_name, scope_def)| {
+                        scope_name.to_string() == name && *scope_def == def
+                    });
+                    if in_scope {
+                        self.super_imports.insert(name);
+                    }
+                }
+                // Only the relative paths to other modules, not `self` values.
+                Some(ast::PathSegmentKind::SelfKw) | Some(ast::PathSegmentKind::SuperKw)
+                    if !is_path_prefix(&path) => {}
+                Some(ast::PathSegmentKind::SelfKw) => {
+                    item_fixups.push((segment.syntax().text_range(), "super".to_string()))
+                }
+                Some(ast::PathSegmentKind::SuperKw) => item_fixups.push((
+                    TextRange::empty(segment.syntax().text_range().start()),
+                    "super::".to_string(),
+                )),
+                _ => (),
+            }
+        }
+    }
+
+    /// Finds the paths to the moved item `name` which go through the original
+    /// module, and the uses of it in the original module which need an import.
+    fn collect_references(
+        &mut self,
+        ctx: &AssistContext,
+        module: hir::Module,
+        range: TextRange,
+        name: &ast::Name,
+        item_fixups: &mut Vec<(TextRange, String)>,
+    ) -> Option<()> {
+        let def = NameClass::classify(&ctx.sema, name)?.defined()?;
+        for (file_id, references) in def.usages(&ctx.sema).all() {
+            let source_file = ctx.sema.parse(file_id);
+            for reference in references {
+                let name_ref =
+                    match find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range)
+                    {
+                        Some(it) => it,
+                        None => continue,
+                    };
+                let is_in_items =
+                    file_id == ctx.frange.file_id && range.contains_range(reference.range);
+                match qualifier_of(&name_ref) {
+                    Some(qualifier) => {
+                        let is_original_module = matches!(
+                            ctx.sema.resolve_path(&qualifier),
+                            Some(PathResolution::Def(hir::ModuleDef::Module(it))) if it == module
+                        );
+                        if !is_original_module {
+                            continue;
+                        }
+                        let offset = reference.range.start();
+                        if is_in_items {
+                            item_fixups
+                                .push((TextRange::empty(offset), format!("{}::", MODULE_NAME)));
+                        } else {
+                            self.path_fixups.entry(file_id).or_default().insert(offset);
+                        }
+                    }
+                    None if !is_in_items
+                        && ctx.sema.scope(name_ref.syntax()).module() == Some(module) =>
+                    {
+                        self.parent_imports.insert(name.text().to_string());
+                    }
+                    None => (),
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// The new module has to be visible to the paths going through it.
+    fn module_visibility(&self) -> &'static str {
+        if self.path_fixups.is_empty() {
+            ""
+        } else {
+            "pub(crate) "
+        }
+    }
+
+    fn super_imports(&self) -> Option<String> {
+        import("super", &self.super_imports)
+    }
+
+    /// The imports and re-exports of the moved items in the original module,
+    /// followed by the indentation of the new module.
+    fn parent_imports(&self, indent: IndentLevel) -> String {
+        let reexports = self.reexports.iter().filter_map(|(visibility, names)| {
+            import(MODULE_NAME, names).map(|it| format!("{} {}", visibility, it))
+        });
+        let imports = import(MODULE_NAME, &self.parent_imports).into_iter().chain(reexports);
+        let imports = imports.join(&format!("\n{}", indent));
+        if imports.is_empty() {
+            return imports;
+        }
+        format!("{}\n\n{}", imports, indent)
+    }
+
+    /// Inserts `modname::` into the paths to the moved items outside of them.
+    fn fix_paths(&self, builder: &mut AssistBuilder, current_file: FileId) {
+        let mut files = self.path_fixups.iter().collect::<Vec<_>>();
+        // The current file is edited first, before switching to the others.
+        files.sort_by_key(|(file_id, _)| **file_id != current_file);
+        for (&file_id, offsets) in files {
+            if file_id != current_file {
+                builder.edit_file(file_id);
+            }
+            for &offset in offsets {
+                builder.insert(offset, format!("{}::", MODULE_NAME));
+            }
+        }
+    }
+}
+
+fn item_name(item: &ast::Item) -> Option<ast::Name> {
+    match item {
+        ast::Item::Const(it) => it.name(),
+        ast::Item::Enum(it) => it.name(),
+        ast::Item::Fn(it) => it.name(),
+        ast::Item::Module(it) => it.name(),
+        ast::Item::Static(it) => it.name(),
+        ast::Item::Struct(it) => it.name(),
+        ast::Item::Trait(it) => it.name(),
+        ast::Item::TypeAlias(it) => it.name(),
+        ast::Item::Union(it) => it.name(),
+        _ => None,
+    }
+}
+
+/// Returns the visibility of `item` if it's public, in which case it's
+/// re-exported from the original module, and the paths to it stay valid.
+fn reexport_visibility(item: &ast::Item) -> Option<ast::Visibility> {
+    let visibility = match item {
+        ast::Item::Const(it) => it.visibility(),
+        ast::Item::Enum(it) => it.visibility(),
+        ast::Item::Fn(it) => it.visibility(),
+        ast::Item::Module(it) => it.visibility(),
+        ast::Item::Static(it) => it.visibility(),
+        ast::Item::Struct(it) => it.visibility(),
+        ast::Item::Trait(it) => it.visibility(),
+        ast::Item::TypeAlias(it) => it.visibility(),
+        ast::Item::Union(it) => it.visibility(),
+        _ => None,
+    }?;
+    match visibility.kind() {
+        ast::VisibilityKind::PubSelf => None,
+        _ => Some(visibility),
+    }
+}
+
+/// Makes the re-exported `item` `pub(crate)` if its visibility is relative to
+/// the original module, and would be too narrow in the new one.
+fn reexport_visibility_fixup(item: &ast::Item) -> Option<(TextRange, String)> {
+    let visibility = reexport_visibility(item)?;
+    match visibility.kind() {
+        ast::VisibilityKind::PubSuper | ast::VisibilityKind::In(_) => {
+            Some((visibility.syntax().text_range(), "pub(crate)".to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the definitions of `item` whose visibility may have to be widened:
+/// the item itself, its fields and the items of its inherent impl.
+fn visibility_candidates(item: &ast::Item) -> Vec<SyntaxNode> {
+    let mut res = Vec::new();
+    if item_name(item).is_some() {
+        res.push(item.syntax().clone());
+    }
+    match item {
+        ast::Item::Struct(it) => res.extend(record_fields(it.field_list())),
+        ast::Item::Union(it) => {
+            let fields = it.record_field_list().into_iter().flat_map(|it| it.fields());
+            res.extend(fields.map(|it| it.syntax().clone()))
+        }
+        ast::Item::Impl(it) if it.trait_().is_none() => {
+            let assoc_items = it.assoc_item_list().into_iter().flat_map(|it| it.assoc_items());
+            res.extend(assoc_items.map(|it| it.syntax().clone()))
+        }
+        _ => (),
+    }
+    res
+}
+
+fn record_fields(field_list: Option<ast::FieldList>) -> Vec<SyntaxNode> {
+    match field_list {
+        Some(ast::FieldList::RecordFieldList(it)) => {
+            it.fields().map(|it| it.syntax().clone()).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Makes the definition `node` `pub(crate)` if it's used outside of the moved
+/// items and isn't visible enough for it.
+fn visibility_fixup(
+    ctx: &AssistContext,
+    range: TextRange,
+    node: &SyntaxNode,
+) -> Option<(TextRange, String)> {
+    let visibility = node.children().find_map(ast::Visibility::cast);
+    let fixup = match visibility.as_ref().map(|it| it.kind()) {
+        None => (TextRange::empty(vis_offset(node)), "pub(crate) ".to_string()),
+        Some(ast::VisibilityKind::PubSelf) | Some(ast::VisibilityKind::PubSuper) => {
+            (visibility?.syntax().text_range(), "pub(crate)".to_string())
+        }
+        Some(_) => return None,
+    };
+    let name = node.children().find_map(ast::Name::cast)?;
+    let def = NameClass::classify(&ctx.sema, &name)?.defined()?;
+    let is_used_outside = def.usages(&ctx.sema).all().iter().any(|(&file_id, references)| {
+        file_id != ctx.frange.file_id || references.iter().any(|it| !range.contains_range(it.range))
+    });
+    is_used_outside.then(|| fixup)
+}
+
+/// Returns the path qualifying the reference `name_ref`: its qualifier, or the
+/// prefix of the use tree list it's in, like `a` in `use a::{b, c}`.
+fn qualifier_of(name_ref: &ast::NameRef) -> Option<ast::Path> {
+    let path = ast::PathSegment::cast(name_ref.syntax().parent()?)?.parent_path();
+    if let Some(qualifier) = path.qualifier() {
+        return Some(qualifier);
+    }
+    let tree = ast::UseTree::cast(path.syntax().parent()?)?;
+    let list = ast::UseTreeList::cast(tree.syntax().parent()?)?;
+    list.parent_use_tree().path()
+}
+
+/// Checks if `path` is the beginning of a longer path, like `self` in
+/// `self::foo` or in `use self::{foo, bar}`.
+fn is_path_prefix(path: &ast::Path) -> bool {
+    path.syntax()
+        .parent()
+        .map_or(false, |it| ast::Path::can_cast(it.kind()) || ast::UseTree::can_cast(it.kind()))
+}
+
+fn import(prefix: &str, names: &BTreeSet<String>) -> Option<String> {
+    match names.len() {
+        0 => None,
+        1 => Some(format!("use {}::{};", prefix, names.iter().next()?)),
+        _ => Some(format!("use {}::{{{}}};", prefix, names.iter().join(", "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extracts_items_with_imports() {
+        check_assist(
+            extract_module,
+            r#"
+const LIMIT: u32 = 10;
+
+$0struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn new() -> Counter {
+        Counter { count: 0 }
+    }
+
+    fn is_full(&self) -> bool {
+        self.count >= LIMIT
+    }
+}$0
+
+fn main() {
+    let counter = Counter::new();
+    counter.count;
+}
+"#,
+            r#"
+const LIMIT: u32 = 10;
+
+use modname::Counter;
+
+mod modname {
+    use super::LIMIT;
+
+    pub(crate) struct Counter {
+        pub(crate) count: u32,
+    }
+
+    impl Counter {
+        pub(crate) fn new() -> Counter {
+            Counter { count: 0 }
+        }
+
+        fn is_full(&self) -> bool {
+            self.count >= LIMIT
+        }
+    }
+}
+
+fn main() {
+    let counter = Counter::new();
+    counter.count;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fixes_qualified_paths() {
+        check_assist(
+            extract_module,
+            r#"
+mod outer {
+    $0fn foo() {
+        self::bar();
+    }$0
+
+    fn bar() {}
+
+    mod inner {
+        fn f() {
+            super::foo();
+        }
+    }
+}
+"#,
+            r#"
+mod outer {
+    pub(crate) mod modname {
+        pub(crate) fn foo() {
+            super::bar();
+        }
+    }
+
+    fn bar() {}
+
+    mod inner {
+        fn f() {
+            super::modname::foo();
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reexports_public_items() {
+        check_assist(
+            extract_module,
+            r#"
+mod outer {
+    $0pub fn foo() {}
+
+    pub(super) struct S;
+
+    fn baz() {}$0
+
+    fn bar() {
+        foo();
+        baz();
+    }
+}
+
+fn main() {
+    outer::foo();
+    let _ = crate::outer::S;
+}
+"#,
+            r#"
+mod outer {
+    use modname::baz;
+    pub use modname::foo;
+    pub(super) use modname::S;
+
+    mod modname {
+        pub fn foo() {}
+
+        pub(crate) struct S;
+
+        pub(crate) fn baz() {}
+    }
+
+    fn bar() {
+        foo();
+        baz();
+    }
+}
+
+fn main() {
+    outer::foo();
+    let _ = crate::outer::S;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extracts_into_file() {
+        check_assist_by_label(
+            extract_module,
+            r#"
+//- /main.rs
+mod foo;
+//- /foo.rs
+$0pub fn bar() {}$0
+
+fn baz() {
+    bar();
+}
+"#,
+            r#"
+//- /foo.rs
+pub use modname::bar;
+
+mod modname;
+
+fn baz() {
+    bar();
+}
+//- /foo/modname.rs
+pub fn bar() {}
+"#,
+            "Extract into module file",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_partially_selected_items() {
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+fn foo() {}
+fn b$0ar() {}
+fn baz() {}$0
+"#,
+        );
+        check_assist_not_applicable(
+            extract_module,
+            r#"
+//- /main.rs
+$0mod foo;$0
+//- /foo.rs
+"#,
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use hir::{Module, ModuleDef, PathResolution};
+use ide_db::{
+    base_db::FileId,
+    defs::Definition,
+    helpers::mod_path_to_ast,
+    search::{FileReference, SearchScope},
+};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, edit::IndentLevel, ArgListOwner, NameOwner, VisibilityOwner},
+    AstNode, SyntaxKind, TextRange,
+};
+
+use crate::{assist_context::AssistBuilder, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: extract_struct_from_function_signature
+//
+// Bundles the selected parameters of a function into a new struct, which is passed
+// instead of them.
+//
+// ```
+// fn draw(canvas: &mut Vec<u8>, $0x: u32, y: u32$0) {
+//     canvas.push((x + y) as u8);
+// }
+//
+// fn main() {
+//     draw(&mut Vec::new(), 1, 2);
+// }
+// ```
+// ->
+// ```
+// struct DrawParams {
+//     x: u32,
+//     y: u32,
+// }
+//
+// fn draw(canvas: &mut Vec<u8>, params: DrawParams) {
+//     canvas.push((params.x + params.y) as u8);
+// }
+//
+// fn main() {
+//     draw(&mut Vec::new(), DrawParams { x: 1, y: 2 });
+// }
+// ```
+pub(crate) fn extract_struct_from_function_signature(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    if ctx.frange.range.is_empty() {
+        return None;
+    }
+    let param_list = ctx.covering_element().ancestors().find_map(ast::ParamList::cast)?;
+    let func = ast::Fn::cast(param_list.syntax().parent()?)?;
+    // Only free functions are supported, the struct is put next to them.
+    if !matches!(func.syntax().parent()?.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST) {
+        return None;
+    }
+
+    let params = param_list.params().collect::<Vec<_>>();
+    let selected = params
+        .iter()
+        .enumerate()
+        .filter(|(_, param)| {
+            ctx.frange
+                .range
+                .intersect(param.syntax().text_range())
+                .map_or(false, |it| !it.is_empty())
+        })
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    let (first, last) = (*selected.first()?, *selected.last()?);
+
+    let mut fields = Vec::new();
+    for param in &params[first..=last] {
+        let ident_pat = match param.pat()? {
+            ast::Pat::IdentPat(it) if it.ref_token().is_none() && it.pat().is_none() => it,
+            _ => return None,
+        };
+        let ty = param.ty()?;
+        if uses_generics(ctx, &ty) {
+            cov_mark::hit!(extract_struct_from_signature_generics);
+            return None;
+        }
+        let local = ctx.sema.to_def(&ident_pat)?;
+        fields.push(Field {
+            name: ident_pat.name()?.to_string(),
+            ty,
+            local,
+            is_mut: ident_pat.mut_token().is_some(),
+        });
+    }
+
+    let name = func.name()?;
+    let fn_name = name.text();
+    let struct_name =
+        format!("{}Params", hir::to_camel_case(&fn_name).unwrap_or_else(|| fn_name.to_string()));
+    let db = ctx.db();
+    let module = ctx.sema.scope(func.syntax()).module()?;
+    if module.scope(db, None).iter().any(|(name, _)| name.to_string() == struct_name) {
+        cov_mark::hit!(extract_struct_from_signature_exists);
+        return None;
+    }
+    let fn_def = Definition::ModuleDef(ctx.sema.to_def(&func)?.into());
+    // Only the calls can be updated, not the uses of the function as a value.
+    let usages = fn_def.usages(&ctx.sema).all();
+    let is_value = usages.references.values().flatten().any(|reference| match &reference.name {
+        ast::NameLike::NameRef(name_ref) => !is_callee_or_import(name_ref),
+        _ => false,
+    });
+    if is_value {
+        cov_mark::hit!(extract_struct_from_signature_fn_value);
+        return None;
+    }
+
+    let target = params[first].syntax().text_range().cover(params[last].syntax().text_range());
+    acc.add(
+        AssistId("extract_struct_from_function_signature", AssistKind::RefactorExtract),
+        "Extract struct from function signature",
+        target,
+        |builder| {
+            let indent = IndentLevel::from_node(func.syntax());
+            let vis = func.visibility().map_or(String::new(), |v| format!("{} ", v));
+            let mut buf = format!("{}struct {} {{\n", vis, struct_name);
+            for field in &fields {
+                format_to!(buf, "{}{}{}: {},\n", indent + 1, vis, field.name, field.ty);
+            }
+            format_to!(buf, "{}}}\n\n{}", indent, indent);
+            builder.insert(func.syntax().text_range().start(), buf);
+
+            let is_mut = fields.iter().any(|it| it.is_mut);
+            let param = format!("{}params: {}", if is_mut { "mut " } else { "" }, struct_name);
+            builder.replace(target, param);
+
+            for field in &fields {
+                let usages = Definition::Local(field.local)
+                    .usages(&ctx.sema)
+                    .in_scope(SearchScope::single_file(ctx.frange.file_id))
+                    .all();
+                for reference in usages.references.values().flatten() {
+                    let is_shorthand = match &reference.name {
+                        ast::NameLike::NameRef(name_ref) => is_shorthand_field(name_ref),
+                        _ => false,
+                    };
+                    let text = if is_shorthand {
+                        format!("{0}: params.{0}", field.name)
+                    } else {
+                        format!("params.{}", field.name)
+                    };
+                    builder.replace(reference.range, text);
+                }
+            }
+
+            let call_sites =
+                CallSites { struct_name: &struct_name, module, first, last, fields: &fields };
+            for (file_id, references) in usages {
+                call_sites.process(ctx, builder, file_id, references);
+            }
+        },
+    )
+}
+
+struct Field {
+    name: String,
+    ty: ast::Type,
+    local: hir::Local,
+    is_mut: bool,
+}
+
+/// Checks if `ty` mentions lifetimes, generic parameters or `impl Trait`,
+/// which would have to become the generics of the struct.
+fn uses_generics(ctx: &AssistContext, ty: &ast::Type) -> bool {
+    ty.syntax().descendants().any(|it| match it.kind() {
+        SyntaxKind::LIFETIME | SyntaxKind::IMPL_TRAIT_TYPE => true,
+        _ => ast::Path::cast(it).map_or(false, |path| {
+            matches!(
+                ctx.sema.resolve_path(&path),
+                Some(PathResolution::TypeParam(_)) | Some(PathResolution::ConstParam(_))
+            )
+        }),
+    })
+}
+
+/// Checks if `name_ref` is a field of a record expression written with the
+/// shorthand syntax, as in `S { x }`.
+fn is_shorthand_field(name_ref: &ast::NameRef) -> bool {
+    name_ref
+        .syntax()
+        .ancestors()
+        .take(5)
+        .find_map(ast::RecordExprField::cast)
+        .map_or(false, |field| field.name_ref().is_none())
+}
+
+/// Checks if `name_ref` is the callee of a call, as in `f(x)`, or is imported.
+fn is_callee_or_import(name_ref: &ast::NameRef) -> bool {
+    if name_ref.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+        return true;
+    }
+    let path_expr = match name_ref.syntax().ancestors().find_map(ast::PathExpr::cast) {
+        Some(it) => it,
+        None => return false,
+    };
+    path_expr
+        .syntax()
+        .parent()
+        .and_then(ast::CallExpr::cast)
+        .and_then(|call| call.expr())
+        .map_or(false, |callee| callee.syntax() == path_expr.syntax())
+}
+
+struct CallSites<'a> {
+    struct_name: &'a str,
+    /// The module of the function, where the struct is defined.
+    module: Module,
+    first: usize,
+    last: usize,
+    fields: &'a [Field],
+}
+
+impl CallSites<'_> {
+    fn process(
+        &self,
+        ctx: &AssistContext,
+        builder: &mut AssistBuilder,
+        file_id: FileId,
+        references: Vec<FileReference>,
+    ) {
+        let source_file = ctx.sema.parse(file_id);
+        builder.edit_file(file_id);
+        for FileReference { range, .. } in references {
+            let call = match find_node_at_range::<ast::CallExpr>(source_file.syntax(), range) {
+                Some(it) => it,
+                None => continue,
+            };
+            if !call.expr().map_or(false, |it| it.syntax().text_range().contains_range(range)) {
+                continue;
+            }
+            let args = match call.arg_list() {
+                Some(it) => it.args().collect::<Vec<_>>(),
+                None => continue,
+            };
+            if let Some((range, text)) = self.struct_literal(ctx, &call, &args) {
+                builder.replace(range, text);
+            }
+        }
+    }
+
+    /// Returns the range of the arguments of `call` which are bundled, and the
+    /// struct literal replacing them.
+    fn struct_literal(
+        &self,
+        ctx: &AssistContext,
+        call: &ast::CallExpr,
+        args: &[ast::Expr],
+    ) -> Option<(TextRange, String)> {
+        let args = args.get(self.first..=self.last)?;
+        let call_module = ctx.sema.scope(call.syntax()).module()?;
+        let path = if call_module == self.module {
+            self.struct_name.to_string()
+        } else {
+            let module_path =
+                call_module.find_use_path(ctx.db(), ModuleDef::Module(self.module))?;
+            format!("{}::{}", mod_path_to_ast(&module_path), self.struct_name)
+        };
+        let fields = self.fields.iter().zip(args).map(|(field, arg)| {
+            if arg.syntax().text() == field.name.as_str() {
+                field.name.clone()
+            } else {
+                format!("{}: {}", field.name, arg)
+            }
+        });
+        let range = args.first()?.syntax().text_range().cover(args.last()?.syntax().text_range());
+        Some((range, format!("{} {{ {} }}", path, fields.format(", "))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extracts_struct_in_module_and_fixes_calls() {
+        check_assist(
+            extract_struct_from_function_signature,
+            r#"
+mod shapes {
+    pub fn rect($0width: u32, mut height: u32$0, fill: bool) -> u32 {
+        height += 1;
+        let size = Size { width };
+        width * height
+    }
+    struct Size {
+        width: u32,
+    }
+    fn square(width: u32) -> u32 {
+        rect(width, width, true)
+    }
+}
+fn main() {
+    let height = 2;
+    shapes::rect(1, height, false);
+}
+"#,
+            r#"
+mod shapes {
+    pub struct RectParams {
+        pub width: u32,
+        pub height: u32,
+    }
+
+    pub fn rect(mut params: RectParams, fill: bool) -> u32 {
+        params.height += 1;
+        let size = Size { width: params.width };
+        params.width * params.height
+    }
+    struct Size {
+        width: u32,
+    }
+    fn square(width: u32) -> u32 {
+        rect(RectParams { width, height: width }, true)
+    }
+}
+fn main() {
+    let height = 2;
+    shapes::rect(shapes::RectParams { width: 1, height }, false);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_generic_parameters() {
+        cov_mark::check!(extract_struct_from_signature_generics);
+        check_assist_not_applicable(
+            extract_struct_from_function_signature,
+            r#"fn f<T>($0x: T, y: u32$0) {}"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_fn_is_used_as_value() {
+        cov_mark::check!(extract_struct_from_signature_fn_value);
+        check_assist_not_applicable(
+            extract_struct_from_function_signature,
+            r#"
+fn add($0x: u32, y: u32$0) -> u32 { x + y }
+fn apply(f: fn(u32, u32) -> u32) -> u32 { f(1, 2) }
+fn main() {
+    add(1, 2);
+    apply(add);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_struct_exists() {
+        cov_mark::check!(extract_struct_from_signature_exists);
+        check_assist_not_applicable(
+            extract_struct_from_function_signature,
+            r#"
+struct FParams;
+fn f($0x: u32$0) {}
+"#,
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use ide_db::helpers::FamousDefs;
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, AstNode, GenericParamsOwner, NameOwner, StructKind, VisibilityOwner},
+    SourceFile,
+};
+
+use crate::{utils::generate_impl_text, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_builder
+//
+// Generates a builder for a struct with named fields, with a setter per field. Building
+// fails with a panic when a field which has no default value isn't set.
+//
+// ```
+// # //- minicore: option
+// struct Person$0 {
+//     name: String,
+//     age: Option<u32>,
+// }
+// ```
+// ->
+// ```
+// struct Person {
+//     name: String,
+//     age: Option<u32>,
+// }
+//
+// struct PersonBuilder {
+//     name: Option<String>,
+//     age: Option<u32>,
+// }
+//
+// impl PersonBuilder {
+//     fn new() -> Self {
+//         Self { name: None, age: None }
+//     }
+//
+//     fn name(mut self, name: String) -> Self {
+//         self.name = Some(name);
+//         self
+//     }
+//
+//     fn age(mut self, age: u32) -> Self {
+//         self.age = Some(age);
+//         self
+//     }
+//
+//     fn build(self) -> Person {
+//         Person {
+//             name: self.name.expect("`name` is not set"),
+//             age: self.age,
+//         }
+//     }
+// }
+// ```
+pub(crate) fn generate_builder(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let strukt = ctx.find_node_at_offset::<ast::Struct>()?;
+    let field_list = match strukt.kind() {
+        StructKind::Record(it) => it,
+        _ => return None,
+    };
+    let name = strukt.name()?;
+    let builder_name = format!("{}Builder", name);
+
+    let db = ctx.db();
+    let module = ctx.sema.scope(strukt.syntax()).module()?;
+    if module.scope(db, None).iter().any(|(it, _)| it.to_string() == builder_name) {
+        cov_mark::hit!(generate_builder_already_exists);
+        return None;
+    }
+
+    let famous_defs = FamousDefs(&ctx.sema, Some(module.krate()));
+    let option = famous_defs.core_option_Option();
+    let default = famous_defs.core_default_Default();
+    let mut fields = Vec::new();
+    for field in field_list.fields() {
+        let ty = ctx.sema.to_def(&field)?.ty(db);
+        let ast_ty = field.ty()?;
+        let option_arg = match (ty.as_adt(), option) {
+            (Some(adt), Some(option)) if adt == option.into() => option_arg(&ast_ty),
+            _ => None,
+        };
+        let kind = match option_arg {
+            Some(it) => FieldKind::Optional(it),
+            None if default.map_or(false, |it| ty.impls_trait(db, it, &[])) => FieldKind::Default,
+            None => FieldKind::Required,
+        };
+        fields.push(BuilderField { name: field.name()?.to_string(), ty: ast_ty, kind });
+    }
+
+    let target = strukt.syntax().text_range();
+    acc.add(
+        AssistId("generate_builder", AssistKind::Generate),
+        format!("Generate `{}`", builder_name),
+        target,
+        |builder| {
+            let vis = strukt.visibility().map_or(String::new(), |v| format!("{} ", v));
+            let generic_params = strukt.generic_param_list();
+
+            let mut buf = format!("\n\n{}struct {}", vis, builder_name);
+            if let Some(params) = &generic_params {
+                format_to!(buf, "{}", params);
+            }
+            match strukt.where_clause() {
+                Some(where_clause) => format_to!(buf, "\n{}\n{{\n", where_clause),
+                None => buf.push_str(" {\n"),
+            }
+            for field in &fields {
+                match field.kind {
+                    FieldKind::Optional(_) => {
+                        format_to!(buf, "    {}: {},\n", field.name, field.ty)
+                    }
+                    _ => format_to!(buf, "    {}: Option<{}>,\n", field.name, field.ty),
+                }
+            }
+            buf.push('}');
+            let builder_struct =
+                SourceFile::parse(&buf).tree().syntax().descendants().find_map(ast::Struct::cast);
+
+            let mut methods = Vec::new();
+            let nones = fields.iter().map(|it| format!("{}: None", it.name)).join(", ");
+            let new_body = format!("        Self {{ {} }}", nones);
+            methods.push(format!("    {}fn new() -> Self {{\n{}\n    }}", vis, new_body));
+            for field in &fields {
+                let ty = match &field.kind {
+                    FieldKind::Optional(it) => it,
+                    _ => &field.ty,
+                };
+                let setter_body =
+                    format!("        self.{0} = Some({0});\n        self", field.name);
+                methods.push(format!(
+                    "    {0}fn {1}(mut self, {1}: {2}) -> Self {{\n{3}\n    }}",
+                    vis, field.name, ty, setter_body
+                ));
+            }
+            let mut build = format!("    {}fn build(self) -> {}", vis, name);
+            if let Some(params) = &generic_params {
+                let args = params.generic_params().filter_map(|param| match param {
+                    ast::GenericParam::LifetimeParam(it) => it.lifetime().map(|it| it.to_string()),
+                    ast::GenericParam::TypeParam(it) => it.name().map(|it| it.to_string()),
+                    ast::GenericParam::ConstParam(it) => it.name().map(|it| it.to_string()),
+                });
+                format_to!(build, "<{}>", args.format(", "));
+            }
+            format_to!(build, " {{\n        {} {{\n", name);
+            for field in &fields {
+                let value = match field.kind {
+                    FieldKind::Optional(_) => format!("self.{}", field.name),
+                    FieldKind::Default => format!("self.{}.unwrap_or_default()", field.name),
+                    FieldKind::Required => {
+                        format!("self.{0}.expect(\"`{0}` is not set\")", field.name)
+                    }
+                };
+                format_to!(build, "            {}: {},\n", field.name, value);
+            }
+            build.push_str("        }\n    }");
+            methods.push(build);
+
+            if let Some(builder_struct) = builder_struct {
+                buf.push_str(&generate_impl_text(
+                    &ast::Adt::Struct(builder_struct),
+                    &methods.join("\n\n"),
+                ));
+            }
+            builder.insert(strukt.syntax().text_range().end(), buf);
+        },
+    )
+}
+
+struct BuilderField {
+    name: String,
+    ty: ast::Type,
+    kind: FieldKind,
+}
+
+enum FieldKind {
+    /// An `Option`, which is set to the value of the setter, of the given type.
+    Optional(ast::Type),
+    /// A field defaulting to `Default::default()`.
+    Default,
+    /// A field which has to be set.
+    Required,
+}
+
+/// Returns `T` in `Option<T>`.
+fn option_arg(ty: &ast::Type) -> Option<ast::Type> {
+    let path = match ty {
+        ast::Type::PathType(it) => it.path()?,
+        _ => return None,
+    };
+    let arg = path.segment()?.generic_arg_list()?.generic_args().next()?;
+    match arg {
+        ast::GenericArg::TypeArg(it) => it.ty(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn generates_builder_for_field_kinds() {
+        check_assist(
+            generate_builder,
+            r#"
+//- minicore: option, default
+struct Config;
+impl Default for Config {
+    fn default() -> Self { Config }
+}
+pub struct Server$0 {
+    host: String,
+    port: Option<u16>,
+    config: Config,
+}
+"#,
+            r#"
+struct Config;
+impl Default for Config {
+    fn default() -> Self { Config }
+}
+pub struct Server {
+    host: String,
+    port: Option<u16>,
+    config: Config,
+}
+
+pub struct ServerBuilder {
+    host: Option<String>,
+    port: Option<u16>,
+    config: Option<Config>,
+}
+
+impl ServerBuilder {
+    pub fn new() -> Self {
+        Self { host: None, port: None, config: None }
+    }
+
+    pub fn host(mut self, host: String) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn build(self) -> Server {
+        Server {
+            host: self.host.expect("`host` is not set"),
+            port: self.port,
+            config: self.config.unwrap_or_default(),
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_builder_for_generic_struct() {
+        check_assist(
+            generate_builder,
+            r#"
+struct Wrapper<'a, T: Clone>$0 {
+    inner: &'a T,
+}
+"#,
+            r#"
+struct Wrapper<'a, T: Clone> {
+    inner: &'a T,
+}
+
+struct WrapperBuilder<'a, T: Clone> {
+    inner: Option<&'a T>,
+}
+
+impl<'a, T: Clone> WrapperBuilder<'a, T> {
+    fn new() -> Self {
+        Self { inner: None }
+    }
+
+    fn inner(mut self, inner: &'a T) -> Self {
+        self.inner = Some(inner);
+        self
+    }
+
+    fn build(self) -> Wrapper<'a, T> {
+        Wrapper {
+            inner: self.inner.expect("`inner` is not set"),
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_builder_exists() {
+        cov_mark::check!(generate_builder_already_exists);
+        check_assist_not_applicable(
+            generate_builder,
+            r#"
+struct Foo$0 { x: u32 }
+struct FooBuilder;
+"#,
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use hir::{HasSource, Impl};
+use ide_db::helpers::mod_path_to_ast;
+use itertools::Itertools;
+use rustc_hash::FxHashSet;
+use stdx::format_to;
+use syntax::{
+    ast::{self, GenericParamsOwner, NameOwner},
+    AstNode,
+};
+
+use crate::{
+    assist_context::{AssistContext, Assists},
+    utils::generate_trait_impl_text,
+    AssistId, AssistKind, GroupLabel,
+};
+
+// Assist: generate_delegate_trait
+//
+// Generate an impl of a trait implemented by the given struct field, which forwards every item
+// to the field. The associated types and constants are the ones of the field's type. Methods are
+// called through the trait, as the field's type may have inherent methods of the same names.
+//
+// ```
+// trait Greet {
+//     type Output;
+//     fn greet(&self, name: &str) -> Self::Output;
+// }
+// struct Inner;
+// impl Greet for Inner {
+//     type Output = String;
+//     fn greet(&self, name: &str) -> String { name.to_string() }
+// }
+// struct Wrapper {
+//     $0inner: Inner,
+// }
+// ```
+// ->
+// ```
+// trait Greet {
+//     type Output;
+//     fn greet(&self, name: &str) -> Self::Output;
+// }
+// struct Inner;
+// impl Greet for Inner {
+//     type Output = String;
+//     fn greet(&self, name: &str) -> String { name.to_string() }
+// }
+// struct Wrapper {
+//     inner: Inner,
+// }
+//
+// impl Greet for Wrapper {
+//     type Output = <Inner as Greet>::Output;
+//
+//     fn greet(&self, name: &str) -> Self::Output {
+//         Greet::greet(&self.inner, name)
+//     }
+// }
+// ```
+pub(crate) fn generate_delegate_trait(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let strukt = ctx.find_node_at_offset::<ast::Struct>()?;
+    let (field_name, field_ty) = match ctx.find_node_at_offset::<ast::RecordField>() {
+        Some(field) => (field.name()?.to_string(), field.ty()?),
+        None => {
+            let field = ctx.find_node_at_offset::<ast::TupleField>()?;
+            let field_list = ctx.find_node_at_offset::<ast::TupleFieldList>()?;
+            let idx = field_list.fields().position(|it| it == field)?;
+            (idx.to_string(), field.ty()?)
+        }
+    };
+
+    let db = ctx.db();
+    let module = ctx.sema.scope(strukt.syntax()).module()?;
+    let strukt_ty = ctx.sema.to_def(&strukt)?.ty(db);
+    let inner_ty = ctx.sema.resolve_type(&field_ty)?;
+
+    let mut seen = FxHashSet::default();
+    let traits = Impl::all_for_type(db, inner_ty)
+        .into_iter()
+        .filter_map(|it| it.trait_(db))
+        .filter(|it| seen.insert(*it) && !strukt_ty.impls_trait(db, *it, &[]));
+
+    let group = GroupLabel(format!("Generate delegating impl via `{}`", field_name));
+    let adt = ast::Adt::Struct(strukt.clone());
+    for trait_ in traits {
+        let source = match trait_.source(db) {
+            Some(it) if !it.file_id.is_macro() => it.value,
+            _ => continue,
+        };
+        let path = match module.find_use_path(db, hir::ModuleDef::Trait(trait_)) {
+            Some(it) => mod_path_to_ast(&it),
+            None => continue,
+        };
+        let delegation = Delegation {
+            field_name: &field_name,
+            field_ty: field_ty.to_string(),
+            trait_path: path.to_string(),
+        };
+        let items = match delegation.items(&source) {
+            Some(it) if !it.is_empty() => it,
+            _ => continue,
+        };
+
+        acc.add_group(
+            &group,
+            AssistId("generate_delegate_trait", AssistKind::Generate),
+            format!("Generate delegating impl of `{}` via `{}`", path, field_name),
+            strukt.syntax().text_range(),
+            |builder| {
+                let code = items.iter().join("\n\n");
+                let impl_text = generate_trait_impl_text(&adt, &delegation.trait_path, &code);
+                builder.insert(strukt.syntax().text_range().end(), impl_text);
+            },
+        );
+    }
+    Some(())
+}
+
+struct Delegation<'a> {
+    field_name: &'a str,
+    field_ty: String,
+    trait_path: String,
+}
+
+impl Delegation<'_> {
+    /// Returns the text of the forwarding items of the trait, or `None` if some
+    /// of them can't be forwarded.
+    fn items(&self, trait_: &ast::Trait) -> Option<Vec<String>> {
+        if trait_.generic_param_list().is_some() {
+            return None;
+        }
+        trait_
+            .assoc_item_list()?
+            .assoc_items()
+            .map(|item| match item {
+                ast::AssocItem::Fn(it) => self.forward_fn(&it),
+                ast::AssocItem::TypeAlias(it) if it.generic_param_list().is_none() => {
+                    Some(format!(
+                        "    type {0} = <{1} as {2}>::{0};",
+                        it.name()?,
+                        self.field_ty,
+                        self.trait_path
+                    ))
+                }
+                ast::AssocItem::Const(it) => Some(format!(
+                    "    const {0}: {1} = <{2} as {3}>::{0};",
+                    it.name()?,
+                    it.ty()?,
+                    self.field_ty,
+                    self.trait_path
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forwards a method, which has to take `self` and mustn't otherwise refer
+    /// to `Self` but through its associated items.
+    fn forward_fn(&self, func: &ast::Fn) -> Option<String> {
+        if func.async_token().is_some() || func.unsafe_token().is_some() {
+            return None;
+        }
+        let self_param = func.param_list()?.self_param()?;
+        if self_param.ty().is_some() {
+            return None;
+        }
+        let params = func.param_list()?.params().collect::<Vec<_>>();
+        let types = params.iter().filter_map(|it| it.ty()).map(|it| it.syntax().clone());
+        let ret_type = func.ret_type().and_then(|it| it.ty()).map(|it| it.syntax().clone());
+        let mentions_self = types.chain(ret_type).flat_map(|it| it.descendants()).any(|it| {
+            ast::Path::cast(it).map_or(false, |path| {
+                path.syntax().text() == "Self"
+                    && path.syntax().parent().and_then(ast::Path::cast).is_none()
+            })
+        });
+        if mentions_self {
+            return None;
+        }
+
+        let name = func.name()?;
+        let mut args = Vec::new();
+        let mut buf = format!("    fn {}", name);
+        if let Some(generics) = func.generic_param_list() {
+            format_to!(buf, "{}", generics);
+        }
+        format_to!(buf, "({}", self_param);
+        for (idx, param) in params.iter().enumerate() {
+            let arg = match param.pat() {
+                Some(ast::Pat::IdentPat(it)) if it.is_simple_ident() => it.to_string(),
+                _ => format!("arg{}", idx),
+            };
+            format_to!(buf, ", {}: {}", arg, param.ty()?);
+            args.push(arg);
+        }
+        buf.push(')');
+        if let Some(ret_type) = func.ret_type() {
+            format_to!(buf, " {}", ret_type);
+        }
+        if let Some(where_clause) = func.where_clause() {
+            format_to!(buf, " {}", where_clause);
+        }
+
+        let receiver = match self_param.kind() {
+            ast::SelfParamKind::Owned => "",
+            ast::SelfParamKind::Ref => "&",
+            ast::SelfParamKind::MutRef => "&mut ",
+        };
+        let args =
+            std::iter::once(format!("{}self.{}", receiver, self.field_name)).chain(args).join(", ");
+        format_to!(buf, " {{\n        {}::{}({})\n    }}", self.trait_path, name, args);
+        Some(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn delegates_trait_via_record_field() {
+        check_assist(
+            generate_delegate_trait,
+            r#"
+trait Counter {
+    const START: u32;
+    fn count(&mut self, step: u32) -> u32;
+    fn reset(self);
+}
+struct Inner;
+impl Counter for Inner {
+    const START: u32 = 0;
+    fn count(&mut self, step: u32) -> u32 { step }
+    fn reset(self) {}
+}
+struct Wrapper {
+    name: String,
+    $0inner: Inner,
+}
+"#,
+            r#"
+trait Counter {
+    const START: u32;
+    fn count(&mut self, step: u32) -> u32;
+    fn reset(self);
+}
+struct Inner;
+impl Counter for Inner {
+    const START: u32 = 0;
+    fn count(&mut self, step: u32) -> u32 { step }
+    fn reset(self) {}
+}
+struct Wrapper {
+    name: String,
+    inner: Inner,
+}
+
+impl Counter for Wrapper {
+    const START: u32 = <Inner as Counter>::START;
+
+    fn count(&mut self, step: u32) -> u32 {
+        Counter::count(&mut self.inner, step)
+    }
+
+    fn reset(self) {
+        Counter::reset(self.inner)
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn delegates_trait_out_of_scope_via_tuple_field() {
+        check_assist(
+            generate_delegate_trait,
+            r#"
+mod shapes {
+    pub trait Area {
+        fn area<T: Into<f64>>(&self, scale: T) -> f64;
+    }
+    pub struct Square;
+    impl Area for Square {
+        fn area<T: Into<f64>>(&self, scale: T) -> f64 { scale.into() }
+    }
+}
+struct Tile($0shapes::Square);
+"#,
+            r#"
+mod shapes {
+    pub trait Area {
+        fn area<T: Into<f64>>(&self, scale: T) -> f64;
+    }
+    pub struct Square;
+    impl Area for Square {
+        fn area<T: Into<f64>>(&self, scale: T) -> f64 { scale.into() }
+    }
+}
+struct Tile(shapes::Square);
+
+impl shapes::Area for Tile {
+    fn area<T: Into<f64>>(&self, scale: T) -> f64 {
+        shapes::Area::area(&self.0, scale)
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_methods_returning_self_or_implemented_traits() {
+        check_assist_not_applicable(
+            generate_delegate_trait,
+            r#"
+trait Duplicate {
+    fn duplicate(&self) -> Self;
+}
+trait Name {
+    fn name(&self) -> String;
+}
+struct Inner;
+impl Duplicate for Inner {
+    fn duplicate(&self) -> Self { Inner }
+}
+impl Name for Inner {
+    fn name(&self) -> String { String::new() }
+}
+struct Wrapper {
+    $0inner: Inner,
+}
+impl Name for Wrapper {
+    fn name(&self) -> String { String::new() }
+}
+"#,
+        );
+    }
+}
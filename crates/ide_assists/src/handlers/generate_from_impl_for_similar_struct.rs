@@ -0,0 +1,253 @@
+use hir::{HasSource, ModuleDef, StructKind};
+use ide_db::helpers::{mod_path_to_ast, FamousDefs};
+use stdx::format_to;
+use syntax::ast::{self, AstNode, GenericParamsOwner, NameOwner};
+
+use crate::{
+    utils::generate_trait_impl_text, AssistContext, AssistId, AssistKind, Assists, GroupLabel,
+};
+
+// Assist: generate_from_impl_for_similar_struct
+//
+// Adds a `From` impl converting another struct of the module, which shares fields with
+// this one, field by field. Fields of other types are converted with `.into()`, or with
+// `TryFrom`, which makes the impl a `TryFrom` impl. Fields which can't be converted are
+// left as `todo!()`.
+//
+// ```
+// # //- minicore: from
+// struct Point3 { x: i32, y: i32, z: i32 }
+// struct $0Point2 { x: i32, y: i32 }
+// ```
+// ->
+// ```
+// struct Point3 { x: i32, y: i32, z: i32 }
+// struct Point2 { x: i32, y: i32 }
+//
+// impl From<Point3> for Point2 {
+//     fn from(value: Point3) -> Self {
+//         Self {
+//             x: value.x,
+//             y: value.y,
+//         }
+//     }
+// }
+// ```
+pub(crate) fn generate_from_impl_for_similar_struct(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let strukt = ctx.find_node_at_offset::<ast::Struct>()?;
+    let field_list = match strukt.field_list()? {
+        ast::FieldList::RecordFieldList(it) => it,
+        ast::FieldList::TupleFieldList(_) => return None,
+    };
+    if ctx.offset() >= field_list.syntax().text_range().start()
+        || strukt.generic_param_list().is_some()
+    {
+        return None;
+    }
+    let name = strukt.name()?;
+    let def = ctx.sema.to_def(&strukt)?;
+    let db = ctx.db();
+    let module = def.module(db);
+    let famous_defs = FamousDefs(&ctx.sema, Some(module.krate()));
+    let from_trait = famous_defs.core_convert_From()?;
+    let try_from_trait = famous_defs.core_convert_TryFrom();
+    let from_path =
+        mod_path_to_ast(&module.find_use_path(db, ModuleDef::Trait(from_trait))?).to_string();
+    let ty = def.ty(db);
+    let fields = def.fields(db);
+
+    let group = GroupLabel(format!("Generate conversion into `{}`", name));
+    for source in module.declarations(db) {
+        let source = match source {
+            ModuleDef::Adt(hir::Adt::Struct(it)) if it != def => it,
+            _ => continue,
+        };
+        let is_generic =
+            source.source(db).map_or(true, |it| it.value.generic_param_list().is_some());
+        if source.kind(db) != StructKind::Record || is_generic {
+            continue;
+        }
+        let source_ty = source.ty(db);
+        let source_fields = source.fields(db);
+        let shared = fields
+            .iter()
+            .filter(|field| source_fields.iter().any(|it| it.name(db) == field.name(db)))
+            .count();
+        if shared == 0 || shared * 2 < fields.len() {
+            continue;
+        }
+        let already_implemented = ty.impls_trait(db, from_trait, &[source_ty.clone()])
+            || try_from_trait.map_or(false, |it| ty.impls_trait(db, it, &[source_ty.clone()]));
+        if already_implemented {
+            cov_mark::hit!(generate_from_impl_for_similar_struct_exists);
+            continue;
+        }
+
+        let try_from_path = try_from_trait
+            .and_then(|it| module.find_use_path(db, ModuleDef::Trait(it)))
+            .map(|it| mod_path_to_ast(&it).to_string());
+        let mut needs_try = false;
+        let mut field_inits = String::new();
+        for field in &fields {
+            let field_name = field.name(db);
+            let field_ty = field.ty(db);
+            let conversion = match source_fields.iter().find(|it| it.name(db) == field_name) {
+                Some(source_field) => {
+                    let source_field_ty = source_field.ty(db);
+                    if source_field_ty.could_unify_with(db, &field_ty) {
+                        format!("value.{}", field_name)
+                    } else if field_ty.impls_trait(db, from_trait, &[source_field_ty.clone()]) {
+                        format!("value.{}.into()", field_name)
+                    } else {
+                        match (try_from_trait, &try_from_path) {
+                            (Some(trait_), Some(path))
+                                if field_ty.impls_trait(db, trait_, &[source_field_ty]) =>
+                            {
+                                needs_try = true;
+                                format!("{}::try_from(value.{}).map_err(|_| ())?", path, field_name)
+                            }
+                            _ => "todo!()".to_string(),
+                        }
+                    }
+                }
+                None => "todo!()".to_string(),
+            };
+            format_to!(field_inits, "\n            {}: {},", field_name, conversion);
+        }
+
+        let source_name = source.name(db);
+        let (trait_text, code) = match (needs_try, &try_from_path) {
+            (true, Some(path)) => (
+                format!("{}<{}>", path, source_name),
+                format!(
+                    "    type Error = ();
+
+    fn try_from(value: {}) -> Result<Self, Self::Error> {{
+        Ok(Self {{{}
+        }})
+    }}",
+                    source_name, field_inits
+                ),
+            ),
+            _ => (
+                format!("{}<{}>", from_path, source_name),
+                format!(
+                    "    fn from(value: {}) -> Self {{
+        Self {{{}
+        }}
+    }}",
+                    source_name, field_inits
+                ),
+            ),
+        };
+        let label = format!("Generate `{}` for `{}`", trait_text, name);
+        acc.add_group(
+            &group,
+            AssistId("generate_from_impl_for_similar_struct", AssistKind::Generate),
+            label,
+            name.syntax().text_range(),
+            |builder| {
+                let adt = ast::Adt::Struct(strukt.clone());
+                let impl_text = generate_trait_impl_text(&adt, &trait_text, &code);
+                builder.insert(strukt.syntax().text_range().end(), impl_text);
+            },
+        );
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn converts_fields_with_into_and_todo() {
+        check_assist(
+            generate_from_impl_for_similar_struct,
+            r#"
+//- minicore: from
+struct Meters(u32);
+impl From<u32> for Meters {
+    fn from(v: u32) -> Self { Meters(v) }
+}
+struct Raw { id: u32, length: u32, note: bool }
+struct $0Parsed { id: u32, length: Meters, checked: bool }
+"#,
+            r#"
+struct Meters(u32);
+impl From<u32> for Meters {
+    fn from(v: u32) -> Self { Meters(v) }
+}
+struct Raw { id: u32, length: u32, note: bool }
+struct Parsed { id: u32, length: Meters, checked: bool }
+
+impl From<Raw> for Parsed {
+    fn from(value: Raw) -> Self {
+        Self {
+            id: value.id,
+            length: value.length.into(),
+            checked: todo!(),
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generates_try_from_for_fallible_fields() {
+        check_assist(
+            generate_from_impl_for_similar_struct,
+            r#"
+//- minicore: try_from
+struct Small(u8);
+impl core::convert::TryFrom<u32> for Small {
+    type Error = ();
+    fn try_from(v: u32) -> Result<Self, ()> { Err(()) }
+}
+struct Wide { value: u32 }
+struct $0Narrow { value: Small }
+"#,
+            r#"
+struct Small(u8);
+impl core::convert::TryFrom<u32> for Small {
+    type Error = ();
+    fn try_from(v: u32) -> Result<Self, ()> { Err(()) }
+}
+struct Wide { value: u32 }
+struct Narrow { value: Small }
+
+impl core::convert::TryFrom<Wide> for Narrow {
+    type Error = ();
+
+    fn try_from(value: Wide) -> Result<Self, Self::Error> {
+        Ok(Self {
+            value: core::convert::TryFrom::try_from(value.value).map_err(|_| ())?,
+        })
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_impl_exists() {
+        cov_mark::check!(generate_from_impl_for_similar_struct_exists);
+        check_assist_not_applicable(
+            generate_from_impl_for_similar_struct,
+            r#"
+//- minicore: from
+struct A { x: u32 }
+struct $0B { x: u32 }
+impl From<A> for B {
+    fn from(a: A) -> Self { B { x: a.x } }
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use hir::{Adt, HirDisplay};
+use ide_db::helpers::FamousDefs;
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, AttrsOwner, ModuleItemOwner, NameOwner},
+    AstNode, SyntaxNode, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_test
+//
+// Adds a unit test calling the function to the `tests` module of the file, which is
+// created if needed. The arguments and the expected result are left as `todo!()` placeholders.
+//
+// ```
+// fn add$0(a: u32, b: u32) -> u32 { a + b }
+// ```
+// ->
+// ```
+// fn add(a: u32, b: u32) -> u32 { a + b }
+//
+// #[cfg(test)]
+// mod tests {
+//     use super::*;
+//
+//     #[test]
+//     fn test_add() {
+//         let result = add(${1:todo!()}, ${2:todo!()});
+//         assert_eq!(result, ${0:todo!()});
+//     }
+// }
+// ```
+pub(crate) fn generate_test(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let func = ctx.find_node_at_offset::<ast::Fn>()?;
+    let header_end = func.body()?.syntax().text_range().start();
+    if !TextRange::new(func.syntax().text_range().start(), header_end).contains(ctx.offset())
+        || func.async_token().is_some()
+        || is_test_code(func.syntax())
+    {
+        return None;
+    }
+    let name = func.name()?;
+
+    // Methods are called through their type, the items of the test module are found next
+    // to the function or its impl.
+    let (callee, item) = match func.syntax().parent().and_then(ast::AssocItemList::cast) {
+        Some(list) => {
+            let impl_ = list.syntax().parent().and_then(ast::Impl::cast)?;
+            if impl_.trait_().is_some() {
+                return None;
+            }
+            let self_ty = match impl_.self_ty()? {
+                ast::Type::PathType(it) => it.path()?.segment()?.name_ref()?,
+                _ => return None,
+            };
+            (format!("{}::{}", self_ty, name), impl_.syntax().clone())
+        }
+        None => (name.to_string(), func.syntax().clone()),
+    };
+    let items = item.parent()?;
+    let tests_module = items.children().filter_map(ast::Module::cast).find(|it| {
+        it.name().map_or(false, |name| name.text() == "tests") && it.item_list().is_some()
+    });
+    let test_name = format!("test_{}", name);
+    if let Some(item_list) = tests_module.as_ref().and_then(|it| it.item_list()) {
+        let exists = item_list.items().any(|it| match it {
+            ast::Item::Fn(it) => it.name().map_or(false, |it| it.text() == test_name),
+            _ => false,
+        });
+        if exists {
+            cov_mark::hit!(generate_test_exists);
+            return None;
+        }
+    }
+
+    let def = ctx.sema.to_def(&func)?;
+    let module = def.module(ctx.db());
+    let ret_ty = def.ret_type(ctx.db());
+    let result = FamousDefs(&ctx.sema, Some(module.krate())).core_result_Result();
+    let err_ty = match ret_ty.as_adt() {
+        Some(Adt::Enum(it)) if Some(it) == result => ret_ty
+            .type_arguments()
+            .nth(1)
+            .and_then(|it| it.display_source_code(ctx.db(), module.into()).ok()),
+        _ => None,
+    };
+    let ok_is_unit = match &err_ty {
+        Some(_) => ret_ty.type_arguments().next().map_or(false, |it| it.is_unit()),
+        None => ret_ty.is_unit(),
+    };
+    let arg_count =
+        func.param_list()?.self_param().into_iter().count() + func.param_list()?.params().count();
+    let cap = ctx.config.snippet_cap;
+    let todo = |idx: usize| match cap {
+        Some(_) => format!("${{{}:todo!()}}", idx),
+        None => "todo!()".to_string(),
+    };
+    let args = (1..=arg_count).map(todo).join(", ");
+
+    acc.add(
+        AssistId("generate_test", AssistKind::Generate),
+        format!("Generate test for `{}`", name),
+        name.syntax().text_range(),
+        |builder| {
+            let has_glob_import = tests_module.as_ref().map_or(true, |it| {
+                it.item_list().into_iter().flat_map(|it| it.items()).any(|it| match it {
+                    ast::Item::Use(it) => it.syntax().text() == "use super::*;",
+                    _ => false,
+                })
+            });
+            let callee = if has_glob_import { callee } else { format!("super::{}", callee) };
+            let call = format!("{}({}){}", callee, args, if err_ty.is_some() { "?" } else { "" });
+
+            let mut lines = Vec::new();
+            if ok_is_unit {
+                lines.push(format!("{};", call));
+            } else {
+                lines.push(format!("let result = {};", call));
+                lines.push(format!("assert_eq!(result, {});", todo(0)));
+            }
+            let ret = match &err_ty {
+                Some(err_ty) => {
+                    lines.push("Ok(())".to_string());
+                    format!(" -> Result<(), {}>", err_ty)
+                }
+                None => String::new(),
+            };
+
+            let (offset, buf) = match &tests_module {
+                Some(tests_module) => {
+                    let indent = IndentLevel::from_node(tests_module.syntax()) + 1;
+                    let mut buf = String::new();
+                    format_to!(buf, "\n\n");
+                    format_test(&mut buf, indent, &test_name, &ret, &lines);
+                    let item_list = tests_module.item_list();
+                    let offset = match item_list.as_ref().and_then(|it| it.items().last()) {
+                        Some(it) => it.syntax().text_range().end(),
+                        None => {
+                            buf = buf[1..].to_string();
+                            match item_list.and_then(|it| it.l_curly_token()) {
+                                Some(it) => it.text_range().end(),
+                                None => return,
+                            }
+                        }
+                    };
+                    (offset, buf)
+                }
+                None => {
+                    let indent = IndentLevel::from_node(&item);
+                    let mut buf = String::new();
+                    format_to!(buf, "\n\n{0}#[cfg(test)]\n{0}mod tests {{\n", indent);
+                    format_to!(buf, "{}use super::*;\n\n", indent + 1);
+                    format_test(&mut buf, indent + 1, &test_name, &ret, &lines);
+                    format_to!(buf, "\n{}}}", indent);
+                    let offset = match items.children().last() {
+                        Some(it) => it.text_range().end(),
+                        None => return,
+                    };
+                    (offset, buf)
+                }
+            };
+            match cap {
+                Some(cap) => builder.insert_snippet(cap, offset, buf),
+                None => builder.insert(offset, buf),
+            }
+        },
+    )
+}
+
+fn format_test(buf: &mut String, indent: IndentLevel, name: &str, ret: &str, lines: &[String]) {
+    format_to!(buf, "{0}#[test]\n{0}fn {1}(){2} {{\n", indent, name, ret);
+    for line in lines {
+        format_to!(buf, "{}{}\n", indent + 1, line);
+    }
+    format_to!(buf, "{}}}", indent);
+}
+
+/// Checks whether `node` is a test or is inside of a `#[cfg(test)]` module.
+fn is_test_code(node: &SyntaxNode) -> bool {
+    node.ancestors().filter_map(ast::Item::cast).any(|item| {
+        item.attrs().any(|attr| {
+            let text = attr.syntax().text();
+            text == "#[test]" || text == "#[cfg(test)]"
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn extends_existing_module_with_result_test() {
+        check_assist(
+            generate_test,
+            r#"
+//- minicore: result
+struct ParseError;
+fn $0parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn works() {}
+}
+"#,
+            r#"
+struct ParseError;
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn works() {}
+
+    #[test]
+    fn test_parse() -> Result<(), ParseError> {
+        let result = super::parse(${1:todo!()})?;
+        assert_eq!(result, ${0:todo!()});
+        Ok(())
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn calls_methods_through_their_type() {
+        check_assist(
+            generate_test,
+            r#"
+mod geometry {
+    struct Point;
+    impl Point {
+        fn move_by$0(&mut self, dx: i32) {}
+    }
+}
+"#,
+            r#"
+mod geometry {
+    struct Point;
+    impl Point {
+        fn move_by(&mut self, dx: i32) {}
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_move_by() {
+            Point::move_by(${1:todo!()}, ${2:todo!()});
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_test_exists() {
+        cov_mark::check!(generate_test_exists);
+        check_assist_not_applicable(
+            generate_test,
+            r#"
+fn $0f() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f() {}
+}
+"#,
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use ast::make;
-use hir::{HasSource, PathResolution};
-use ide_db::{defs::Definition, search::FileReference};
+use hir::{HasSource, HasVisibility, PathResolution};
+use ide_db::{defs::Definition, search::FileReference, RootDatabase};
 use itertools::izip;
 use syntax::{
-    ast::{self, edit::AstNodeEdit, ArgListOwner},
-    ted, AstNode,
+    algo::find_node_at_range,
+    ast::{
+        self,
+        edit::{AstNodeEdit, IndentLevel},
+        ArgListOwner,
+    },
+    ted, AstNode, SyntaxKind,
 };
 
 use crate::{
@@ -16,7 +21,9 @@ use crate::{
 //
 // Inlines a function or method body creating a `let` statement per parameter unless the parameter
 // can be inlined. The parameter will be inlined either if it the supplied argument is a simple local
-// or if the parameter is only accessed inside the function body once.
+// or if the parameter is only accessed inside the function body once. Early `return`s become breaks
+// out of a labeled `loop`, as labeled blocks aren't stable. Functions using items which aren't
+// visible at the call site aren't inlined.
 //
 // ```
 // # //- minicore: option
@@ -101,13 +108,24 @@ pub(crate) fn inline_(
     }
 
     let body = function_source.body()?;
+    let call_module = ctx.sema.scope(expr.syntax()).module()?;
+    if !file_id.is_macro() {
+        // The body has to come from the tree of `ctx.sema` to be analyzed.
+        let source_file = ctx.sema.parse(file_id.original_file(ctx.sema.db));
+        let body =
+            find_node_at_range::<ast::BlockExpr>(source_file.syntax(), body.syntax().text_range())?;
+        if uses_invisible_items(ctx, &body, call_module) {
+            cov_mark::hit!(inline_call_invisible_items);
+            return None;
+        }
+    }
 
     acc.add(
         AssistId("inline_call", AssistKind::RefactorInline),
         label,
         expr.syntax().text_range(),
         |builder| {
-            let body = body.clone_for_update();
+            let mut body = body.clone_for_update();
 
             let file_id = file_id.original_file(ctx.sema.db);
             let usages_for_locals = |local| {
@@ -200,6 +218,21 @@ pub(crate) fn inline_(
                 }
             }
 
+            let returns = body
+                .syntax()
+                .descendants()
+                .filter_map(ast::ReturnExpr::cast)
+                .filter(|it| returns_from(it, &body))
+                .collect::<Vec<_>>();
+            if !returns.is_empty() {
+                cov_mark::hit!(inline_call_early_return);
+                for ret in returns {
+                    let brk = make::expr_break_with_label(INLINE_LABEL, ret.expr());
+                    ted::replace(ret.syntax(), brk.syntax().clone_for_update());
+                }
+                body = wrap_in_labeled_loop(&body);
+            }
+
             let original_indentation = expr.indent_level();
             let replacement = body.reset_indent().indent(original_indentation);
 
@@ -212,6 +245,85 @@ pub(crate) fn inline_(
     )
 }
 
+const INLINE_LABEL: &str = "'inline";
+
+/// Checks if the body refers to items, fields or methods which aren't visible
+/// from `module`.
+fn uses_invisible_items(ctx: &AssistContext, body: &ast::BlockExpr, module: hir::Module) -> bool {
+    let db = ctx.db();
+    body.syntax().descendants().any(|node| {
+        let is_visible = if let Some(path) = ast::Path::cast(node.clone()) {
+            match ctx.sema.resolve_path(&path) {
+                Some(PathResolution::Def(def)) => module_def_is_visible(db, def, module),
+                Some(PathResolution::AssocItem(it)) => it.is_visible_from(db, module),
+                _ => true,
+            }
+        } else if let Some(field) = ast::FieldExpr::cast(node.clone()) {
+            ctx.sema.resolve_field(&field).map_or(true, |it| it.is_visible_from(db, module))
+        } else if let Some(call) = ast::MethodCallExpr::cast(node.clone()) {
+            ctx.sema.resolve_method_call(&call).map_or(true, |it| it.is_visible_from(db, module))
+        } else if let Some(field) = ast::RecordExprField::cast(node.clone()) {
+            ctx.sema
+                .resolve_record_field(&field)
+                .map_or(true, |(field, _, _)| field.is_visible_from(db, module))
+        } else if let Some(field) = ast::RecordPatField::cast(node) {
+            ctx.sema
+                .resolve_record_pat_field(&field)
+                .map_or(true, |it| it.is_visible_from(db, module))
+        } else {
+            true
+        };
+        !is_visible
+    })
+}
+
+fn module_def_is_visible(db: &RootDatabase, def: hir::ModuleDef, module: hir::Module) -> bool {
+    match def {
+        hir::ModuleDef::Function(it) => it.is_visible_from(db, module),
+        hir::ModuleDef::Adt(it) => it.is_visible_from(db, module),
+        hir::ModuleDef::Const(it) => it.is_visible_from(db, module),
+        hir::ModuleDef::Static(it) => it.is_visible_from(db, module),
+        hir::ModuleDef::Trait(it) => it.is_visible_from(db, module),
+        hir::ModuleDef::TypeAlias(it) => it.is_visible_from(db, module),
+        _ => true,
+    }
+}
+
+/// Checks if `ret` returns from the function of `body`, and not from a closure,
+/// an `async` block or an item inside of it.
+fn returns_from(ret: &ast::ReturnExpr, body: &ast::BlockExpr) -> bool {
+    ret.syntax().ancestors().take_while(|it| it != body.syntax()).all(|it| match it.kind() {
+        SyntaxKind::CLOSURE_EXPR | SyntaxKind::FN => false,
+        SyntaxKind::EFFECT_EXPR => {
+            ast::EffectExpr::cast(it).map_or(true, |it| it.async_token().is_none())
+        }
+        _ => true,
+    })
+}
+
+/// Wraps the statements of `body`, whose `return`s were replaced with breaks,
+/// into a `loop` which breaks with the value of the tail expression.
+fn wrap_in_labeled_loop(body: &ast::BlockExpr) -> ast::BlockExpr {
+    let is_break = |expr: &ast::Expr| {
+        matches!(expr, ast::Expr::BreakExpr(it)
+            if it.lifetime().map_or(false, |it| it.text() == INLINE_LABEL))
+    };
+    let mut stmts = body.statements().map(|it| it.reset_indent()).collect::<Vec<_>>();
+    let ends_with_break = matches!(stmts.last(),
+        Some(ast::Stmt::ExprStmt(it)) if it.expr().map_or(false, |it| is_break(&it)));
+    match body.tail_expr().map(|it| it.reset_indent()) {
+        Some(tail) if is_break(&tail) => stmts.push(make::expr_stmt(tail).into()),
+        None if ends_with_break => (),
+        tail => {
+            let brk = make::expr_break_with_label(INLINE_LABEL, tail);
+            stmts.push(make::expr_stmt(brk).into())
+        }
+    }
+    let loop_body = make::block_expr(stmts, None).indent(IndentLevel(1));
+    let loop_expr = make::expr_loop_with_label(INLINE_LABEL, loop_body);
+    make::block_expr(None, Some(loop_expr)).clone_for_update()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests::{check_assist, check_assist_not_applicable};
@@ -645,6 +757,103 @@ fn main() {
         x as u32
     };
 }
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_early_return() {
+        cov_mark::check!(inline_call_early_return);
+        check_assist(
+            inline_call,
+            r#"
+fn foo(x: u32) -> u32 {
+    if x == 0 {
+        return 1;
+    }
+    let f = || { return 2; };
+    let g = async { return 3; };
+    x + 1
+}
+
+fn main() {
+    let y = foo$0(92);
+}
+"#,
+            r#"
+fn foo(x: u32) -> u32 {
+    if x == 0 {
+        return 1;
+    }
+    let f = || { return 2; };
+    let g = async { return 3; };
+    x + 1
+}
+
+fn main() {
+    let y = {
+        'inline: loop {
+            let x = 92;
+            if x == 0 {
+                break 'inline 1;
+            }
+            let f = || { return 2; };
+            let g = async { return 3; };
+            break 'inline x + 1;
+        }
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_invisible_items() {
+        cov_mark::check!(inline_call_invisible_items);
+        check_assist_not_applicable(
+            inline_call,
+            r#"
+mod foo {
+    fn secret() -> u32 { 92 }
+    pub fn bar() -> u32 {
+        secret()
+    }
+}
+
+fn main() {
+    foo::bar$0();
+}
+"#,
+        );
+        check_assist_not_applicable(
+            inline_call,
+            r#"
+mod foo {
+    pub struct S { secret: u32 }
+    pub fn make() -> S {
+        S { secret: 92 }
+    }
+}
+
+fn main() {
+    foo::make$0();
+}
+"#,
+        );
+        check_assist_not_applicable(
+            inline_call,
+            r#"
+mod foo {
+    pub struct S { secret: u32 }
+    pub fn get(s: S) -> u32 {
+        let S { secret } = s;
+        secret
+    }
+}
+
+fn main(s: foo::S) {
+    foo::get$0(s);
+}
 "#,
         );
     }
@@ -0,0 +1,289 @@
+use hir::{HirDisplay, PathResolution};
+use ide_db::defs::Definition;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, ArgListOwner},
+    AstNode, SyntaxKind, TextSize,
+};
+
+use crate::{utils::suggest_name, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: introduce_parameter
+//
+// Replaces the selected expression with a new parameter of the function, and passes the
+// expression at every call site. The function must only be called, not used as a value, and an
+// expression with paths is only passed in the module of the function.
+//
+// ```
+// fn area() -> u32 {
+//     $04 * 4$0 + 1
+// }
+//
+// fn main() {
+//     area();
+// }
+// ```
+// ->
+// ```
+// fn area(var_name: u32) -> u32 {
+//     var_name + 1
+// }
+//
+// fn main() {
+//     area(4 * 4);
+// }
+// ```
+pub(crate) fn introduce_parameter(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    if ctx.frange.range.is_empty() {
+        return None;
+    }
+    let node = ctx.covering_element();
+    if node.kind() == SyntaxKind::COMMENT {
+        return None;
+    }
+    let expr = node.ancestors().find_map(ast::Expr::cast)?;
+    let func = expr.syntax().ancestors().find_map(ast::Fn::cast)?;
+    if !func.body()?.syntax().text_range().contains_range(expr.syntax().text_range()) {
+        return None;
+    }
+
+    // The signatures of trait methods and of their impls can't change alone.
+    let is_in_trait = func.syntax().parent().and_then(|it| it.parent()).map_or(false, |it| {
+        it.kind() == SyntaxKind::TRAIT
+            || ast::Impl::cast(it).map_or(false, |it| it.trait_().is_some())
+    });
+    if is_in_trait {
+        return None;
+    }
+
+    if uses_function_scope(ctx, &expr) {
+        cov_mark::hit!(introduce_parameter_uses_locals);
+        return None;
+    }
+
+    let module = ctx.sema.scope(expr.syntax()).module()?;
+    let ty = ctx.sema.type_of_expr(&expr)?;
+    if ty.is_unit() || ty.contains_unknown() || ty.is_closure() {
+        return None;
+    }
+    let ty = ty.display_source_code(ctx.db(), module.into()).ok()?;
+    let fn_def = Definition::ModuleDef(ctx.sema.to_def(&func)?.into());
+    let param_list = func.param_list()?;
+
+    // The paths of the expression may not resolve, or resolve to something else, elsewhere.
+    let has_paths = expr.syntax().descendants().any(|it| ast::Path::can_cast(it.kind()));
+    let mut calls = Vec::new();
+    for (file_id, references) in fn_def.usages(&ctx.sema).all() {
+        let source_file = ctx.sema.parse(file_id);
+        let mut arg_lists = Vec::new();
+        for reference in references {
+            let name_ref =
+                match find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range) {
+                    Some(it) => it,
+                    None => continue,
+                };
+            if name_ref.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+                continue;
+            }
+            let arg_list = match call_arg_list(&name_ref) {
+                Some(it) => it,
+                None => {
+                    cov_mark::hit!(introduce_parameter_fn_as_value);
+                    return None;
+                }
+            };
+            if has_paths && ctx.sema.scope(arg_list.syntax()).module() != Some(module) {
+                cov_mark::hit!(introduce_parameter_paths_in_other_module);
+                return None;
+            }
+            arg_lists.push(arg_list);
+        }
+        calls.push((file_id, arg_lists));
+    }
+
+    acc.add(
+        AssistId("introduce_parameter", AssistKind::RefactorExtract),
+        "Introduce parameter",
+        expr.syntax().text_range(),
+        |builder| {
+            let name = suggest_name::for_variable(&expr, &ctx.sema);
+            let last_param = param_list
+                .params()
+                .last()
+                .map(|it| it.syntax().clone())
+                .or_else(|| param_list.self_param().map(|it| it.syntax().clone()));
+            match last_param {
+                Some(it) => builder.insert(it.text_range().end(), format!(", {}: {}", name, ty)),
+                None => builder.insert(
+                    param_list.syntax().text_range().start() + TextSize::of('('),
+                    format!("{}: {}", name, ty),
+                ),
+            }
+            builder.replace(expr.syntax().text_range(), name);
+
+            let arg = expr.syntax().to_string();
+            for (file_id, arg_lists) in calls {
+                builder.edit_file(file_id);
+                for arg_list in arg_lists {
+                    let (offset, text) = match arg_list.args().last() {
+                        Some(it) => (it.syntax().text_range().end(), format!(", {}", arg)),
+                        None => (
+                            arg_list.syntax().text_range().start() + TextSize::of('('),
+                            arg.clone(),
+                        ),
+                    };
+                    builder.insert(offset, text);
+                }
+            }
+        },
+    )
+}
+
+/// Checks if `expr` uses the locals or the generic parameters of the function,
+/// which don't exist at its call sites.
+fn uses_function_scope(ctx: &AssistContext, expr: &ast::Expr) -> bool {
+    expr.syntax().descendants().filter_map(ast::Path::cast).any(|path| {
+        matches!(
+            ctx.sema.resolve_path(&path),
+            Some(PathResolution::Local(_))
+                | Some(PathResolution::TypeParam(_))
+                | Some(PathResolution::ConstParam(_))
+        )
+    })
+}
+
+/// Returns the arguments of the call of the function referred to by `name_ref`,
+/// either as a function or as a method.
+fn call_arg_list(name_ref: &ast::NameRef) -> Option<ast::ArgList> {
+    let range = name_ref.syntax().text_range();
+    name_ref.syntax().ancestors().find_map(|it| {
+        if let Some(call) = ast::CallExpr::cast(it.clone()) {
+            let callee = match call.expr()? {
+                ast::Expr::PathExpr(it) => it,
+                _ => return None,
+            };
+            let is_callee = callee.path()?.segment()?.name_ref()?.syntax().text_range() == range;
+            return is_callee.then(|| call.arg_list()).flatten();
+        }
+        let call = ast::MethodCallExpr::cast(it)?;
+        let name_range = call.name_ref()?.syntax().text_range();
+        (name_range == range).then(|| call.arg_list()).flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn introduces_parameter_in_calls_and_method_calls() {
+        check_assist(
+            introduce_parameter,
+            r#"
+struct Limits;
+impl Limits {
+    fn check(&self, value: u32) -> bool {
+        value < $0100 * 2$0
+    }
+}
+fn f(limits: &Limits) {
+    limits.check(5);
+    Limits::check(limits, 6);
+}
+"#,
+            r#"
+struct Limits;
+impl Limits {
+    fn check(&self, value: u32, var_name: u32) -> bool {
+        value < var_name
+    }
+}
+fn f(limits: &Limits) {
+    limits.check(5, 100 * 2);
+    Limits::check(limits, 6, 100 * 2);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn introduces_parameter_across_files() {
+        check_assist(
+            introduce_parameter,
+            r#"
+//- /main.rs
+mod other;
+
+pub fn limit() -> u64 {
+    $010 * 1024$0
+}
+//- /other.rs
+fn f() -> u64 {
+    crate::limit() + 1
+}
+"#,
+            r#"
+//- /main.rs
+mod other;
+
+pub fn limit(var_name: u64) -> u64 {
+    var_name
+}
+//- /other.rs
+fn f() -> u64 {
+    crate::limit(10 * 1024) + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_used_as_value() {
+        cov_mark::check!(introduce_parameter_fn_as_value);
+        check_assist_not_applicable(
+            introduce_parameter,
+            r#"
+fn limit() -> u32 {
+    $010 * 2$0
+}
+fn f() {
+    let g = limit;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_paths_used_in_other_modules() {
+        cov_mark::check!(introduce_parameter_paths_in_other_module);
+        check_assist_not_applicable(
+            introduce_parameter,
+            r#"
+const BASE: u32 = 10;
+pub fn limit() -> u32 {
+    $0BASE * 2$0
+}
+mod other {
+    fn f() -> u32 {
+        crate::limit()
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_locals() {
+        cov_mark::check!(introduce_parameter_uses_locals);
+        check_assist_not_applicable(
+            introduce_parameter,
+            r#"
+fn square(x: u32) -> u32 {
+    $0x * x$0
+}
+"#,
+        );
+    }
+}
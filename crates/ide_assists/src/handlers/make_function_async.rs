@@ -0,0 +1,362 @@
+use ide_db::{base_db::FileId, defs::Definition, search::FileReference};
+use rustc_hash::FxHashSet;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, AstNode, NameOwner},
+    SourceFile, SyntaxKind, TextRange, TextSize,
+};
+
+use crate::{
+    assist_context::{AssistBuilder, AssistContext, Assists},
+    AssistId, AssistKind, GroupLabel,
+};
+
+// Assist: make_function_async
+//
+// Makes a function `async`. A function returning `impl Future` returns the output of the
+// future instead, which is awaited. The calls of the function can be awaited as well, which
+// makes the functions calling it `async` too.
+//
+// ```
+// fn fetch$0() -> u32 { 92 }
+//
+// fn main() {
+//     let x = fetch();
+// }
+// ```
+// ->
+// ```
+// async fn fetch() -> u32 { 92 }
+//
+// fn main() {
+//     let x = fetch();
+// }
+// ```
+pub(crate) fn make_function_async(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let func = ctx.find_node_at_offset::<ast::Fn>()?;
+    let header_end = match func.body() {
+        Some(body) => body.syntax().text_range().start(),
+        None => func.syntax().text_range().end(),
+    };
+    if !TextRange::new(func.syntax().text_range().start(), header_end).contains(ctx.offset()) {
+        return None;
+    }
+    if func.async_token().is_some() || func.const_token().is_some() {
+        return None;
+    }
+    // `async` functions aren't allowed in traits.
+    let in_trait = func.syntax().parent().and_then(|it| it.parent()).map_or(false, |it| {
+        it.kind() == SyntaxKind::TRAIT
+            || ast::Impl::cast(it).map_or(false, |it| it.trait_().is_some())
+    });
+    if in_trait {
+        cov_mark::hit!(make_function_async_in_trait);
+        return None;
+    }
+    let name = func.name()?;
+    // `main` can't be `async`.
+    if name.text() == "main" {
+        return None;
+    }
+    let function = ctx.sema.to_def(&func)?;
+    let fn_def = Definition::ModuleDef(function.into());
+    let target = func.syntax().text_range();
+
+    let group = GroupLabel(format!("Make `{}` async", name));
+    acc.add_group(
+        &group,
+        AssistId("make_function_async", AssistKind::RefactorRewrite),
+        format!("Make `{}` async", name),
+        target,
+        |builder| {
+            make_async(builder, &func);
+        },
+    );
+    acc.add_group(
+        &group,
+        AssistId("make_function_async", AssistKind::RefactorRewrite),
+        format!("Make `{}` async and await its calls", name),
+        target,
+        |builder| {
+            make_async(builder, &func);
+            for (file_id, references) in fn_def.usages(&ctx.sema).all() {
+                await_calls(ctx, builder, file_id, references, function);
+            }
+        },
+    )
+}
+
+fn make_async(builder: &mut AssistBuilder, func: &ast::Fn) -> Option<()> {
+    builder.insert(async_offset(func)?, "async ");
+
+    let ret_type = func.ret_type()?;
+    let output = future_output(&ret_type.ty()?)?;
+    match output {
+        Some(ty) => builder.replace(ret_type.syntax().text_range(), format!("-> {}", ty)),
+        None => {
+            let mut range = ret_type.syntax().text_range();
+            if let Some(ws) = ret_type.syntax().prev_sibling_or_token() {
+                if ws.kind() == SyntaxKind::WHITESPACE {
+                    range = range.cover(ws.text_range());
+                }
+            }
+            builder.delete(range)
+        }
+    }
+
+    // The returned futures are awaited in the body now.
+    let body = func.body()?;
+    let returns = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::ReturnExpr::cast)
+        .filter(|it| {
+            it.syntax()
+                .ancestors()
+                .take_while(|it| it != body.syntax())
+                .all(|it| !matches!(it.kind(), SyntaxKind::CLOSURE_EXPR | SyntaxKind::FN))
+        })
+        .filter_map(|it| it.expr());
+    for expr in body.tail_expr().into_iter().chain(returns) {
+        await_expr(builder, &expr);
+    }
+    Some(())
+}
+
+/// Returns the offset of `async` among the qualifiers of `func`.
+fn async_offset(func: &ast::Fn) -> Option<TextSize> {
+    let unsafe_token = func.unsafe_token().map(|it| it.text_range().start());
+    let abi = func.abi().map(|it| it.syntax().text_range().start());
+    let fn_token = func.fn_token().map(|it| it.text_range().start());
+    unsafe_token.or(abi).or(fn_token)
+}
+
+/// Returns the output type of `impl Future<Output = T>`, where `None` stands
+/// for `()`, or `None` for other types.
+fn future_output(ty: &ast::Type) -> Option<Option<ast::Type>> {
+    let bounds = match ty {
+        ast::Type::ImplTraitType(it) => it.type_bound_list()?,
+        _ => return None,
+    };
+    let path = bounds.bounds().find_map(|bound| match bound.ty()? {
+        ast::Type::PathType(it) => it.path(),
+        _ => None,
+    })?;
+    let segment = path.segment()?;
+    if segment.name_ref()?.text() != "Future" {
+        return None;
+    }
+    let output = segment.generic_arg_list()?.generic_args().find_map(|arg| match arg {
+        ast::GenericArg::AssocTypeArg(it) if it.name_ref()?.text() == "Output" => it.ty(),
+        _ => None,
+    })?;
+    match &output {
+        ast::Type::TupleType(it) if it.fields().next().is_none() => Some(None),
+        _ => Some(Some(output)),
+    }
+}
+
+fn await_expr(builder: &mut AssistBuilder, expr: &ast::Expr) {
+    let range = expr.syntax().text_range();
+    match expr {
+        ast::Expr::CallExpr(_)
+        | ast::Expr::MethodCallExpr(_)
+        | ast::Expr::PathExpr(_)
+        | ast::Expr::FieldExpr(_)
+        | ast::Expr::ParenExpr(_)
+        | ast::Expr::MacroCall(_) => builder.insert(range.end(), ".await"),
+        _ => {
+            builder.insert(range.start(), "(");
+            builder.insert(range.end(), ").await");
+        }
+    }
+}
+
+/// Awaits the calls of `func` among `references`, and makes the functions they
+/// are in `async`. The calls in closures and in `main` are left alone, as they
+/// can't be awaited, and so are the recursive calls, which would make the future
+/// infinitely sized.
+fn await_calls(
+    ctx: &AssistContext,
+    builder: &mut AssistBuilder,
+    file_id: FileId,
+    references: Vec<FileReference>,
+    func: hir::Function,
+) {
+    let source_file = ctx.sema.parse(file_id);
+    builder.edit_file(file_id);
+    let mut callers = FxHashSet::default();
+    for reference in references {
+        let call = match call_of(&source_file, reference) {
+            Some(it) => it,
+            None => continue,
+        };
+        let caller = call.syntax().ancestors().find_map(|it| match it.kind() {
+            SyntaxKind::CLOSURE_EXPR => Some(None),
+            SyntaxKind::FN => Some(ast::Fn::cast(it)),
+            _ => None,
+        });
+        let caller = match caller {
+            Some(Some(it)) => it,
+            _ => continue,
+        };
+        if caller.name().map_or(false, |it| it.text() == "main") {
+            cov_mark::hit!(make_function_async_main_caller);
+            continue;
+        }
+        if ctx.sema.to_def(&caller) == Some(func) {
+            cov_mark::hit!(make_function_async_recursive_call);
+            continue;
+        }
+        builder.insert(call.syntax().text_range().end(), ".await");
+
+        if caller.async_token().is_none() {
+            if let Some(offset) = async_offset(&caller) {
+                if callers.insert(offset) {
+                    builder.insert(offset, "async ");
+                }
+            }
+        }
+    }
+}
+
+/// Returns the call of the function referred to by `reference`.
+fn call_of(
+    source_file: &SourceFile,
+    FileReference { range, .. }: FileReference,
+) -> Option<ast::Expr> {
+    let name_ref = find_node_at_range::<ast::NameRef>(source_file.syntax(), range)?;
+    name_ref.syntax().ancestors().find_map(|it| {
+        if let Some(call) = ast::CallExpr::cast(it.clone()) {
+            let callee_range = call.expr()?.syntax().text_range();
+            return callee_range.contains_range(range).then(|| call.into());
+        }
+        let call = ast::MethodCallExpr::cast(it)?;
+        (call.name_ref()?.syntax().text_range() == range).then(|| call.into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn makes_function_returning_future_async() {
+        check_assist(
+            make_function_async,
+            r#"
+//- minicore: future
+use core::future::Future;
+
+pub unsafe fn $0load(id: u32) -> impl Future<Output = u32> {
+    if id == 0 {
+        return ready(0);
+    }
+    ready(id)
+}
+
+fn ready(x: u32) -> impl Future<Output = u32> { async move { x } }
+"#,
+            r#"
+use core::future::Future;
+
+pub async unsafe fn load(id: u32) -> u32 {
+    if id == 0 {
+        return ready(0).await;
+    }
+    ready(id).await
+}
+
+fn ready(x: u32) -> impl Future<Output = u32> { async move { x } }
+"#,
+        );
+    }
+
+    #[test]
+    fn awaits_calls_and_makes_callers_async() {
+        check_assist_by_label(
+            make_function_async,
+            r#"
+struct Client;
+impl Client {
+    fn send$0(&self) -> u32 { 0 }
+}
+
+fn one(client: &Client) -> u32 {
+    client.send() + Client::send(client)
+}
+
+async fn two(client: &Client) {
+    let f = || client.send();
+    client.send();
+}
+"#,
+            r#"
+struct Client;
+impl Client {
+    async fn send(&self) -> u32 { 0 }
+}
+
+async fn one(client: &Client) -> u32 {
+    client.send().await + Client::send(client).await
+}
+
+async fn two(client: &Client) {
+    let f = || client.send();
+    client.send().await;
+}
+"#,
+            "Make `send` async and await its calls",
+        );
+    }
+
+    #[test]
+    fn skips_recursive_calls_and_main() {
+        cov_mark::check!(make_function_async_recursive_call);
+        cov_mark::check!(make_function_async_main_caller);
+        check_assist_by_label(
+            make_function_async,
+            r#"
+fn count$0(n: u32) -> u32 {
+    if n == 0 { 0 } else { count(n - 1) + 1 }
+}
+
+fn twice(n: u32) -> u32 { count(n) * 2 }
+
+fn main() {
+    count(3);
+}
+"#,
+            r#"
+async fn count(n: u32) -> u32 {
+    if n == 0 { 0 } else { count(n - 1) + 1 }
+}
+
+async fn twice(n: u32) -> u32 { count(n).await * 2 }
+
+fn main() {
+    count(3);
+}
+"#,
+            "Make `count` async and await its calls",
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_async_or_trait_functions() {
+        check_assist_not_applicable(make_function_async, r#"async fn $0f() {}"#);
+        check_assist_not_applicable(make_function_async, r#"fn f() { $0 }"#);
+        check_assist_not_applicable(make_function_async, r#"fn $0main() {}"#);
+        cov_mark::check!(make_function_async_in_trait);
+        check_assist_not_applicable(
+            make_function_async,
+            r#"
+trait Fetch {
+    fn fetch$0(&self);
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,687 @@
+use hir::{HasSource, Module, ModuleDef};
+use ide_db::{
+    defs::Definition,
+    helpers::mod_path_to_ast,
+    search::{FileReference, SearchScope},
+};
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, edit::IndentLevel, ArgListOwner, GenericParamsOwner, NameOwner},
+    AstNode, NodeOrToken, SyntaxKind, SyntaxNode, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: move_fn_out_of_impl
+//
+// Moves an associated function or a method out of an impl block, turning it into a free
+// function. `self` becomes an explicit `this` parameter and the calls are rewritten. The impl
+// block is removed if the function was its only item.
+//
+// ```
+// struct Counter(u32);
+// impl Counter {
+//     fn $0bump(&mut self) {
+//         self.0 += 1;
+//     }
+// }
+// fn main() {
+//     let mut c = Counter(0);
+//     c.bump();
+// }
+// ```
+// ->
+// ```
+// struct Counter(u32);
+// fn bump(this: &mut Counter) {
+//     this.0 += 1;
+// }
+// fn main() {
+//     let mut c = Counter(0);
+//     bump(&mut c);
+// }
+// ```
+pub(crate) fn move_fn_out_of_impl(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let func = ast::Fn::cast(name.syntax().parent()?)?;
+    let impl_ = func.syntax().ancestors().nth(2).and_then(ast::Impl::cast)?;
+    if impl_.trait_().is_some() || impl_.generic_param_list().is_some() {
+        return None;
+    }
+    let self_ty = impl_.self_ty()?.to_string();
+    let def = ctx.sema.to_def(&func)?;
+    let db = ctx.db();
+    let module = def.module(db);
+    if module.scope(db, None).iter().any(|(it, _)| it.to_string() == name.text()) {
+        cov_mark::hit!(move_fn_out_of_impl_name_taken);
+        return None;
+    }
+    let self_param = func.param_list().and_then(|it| it.self_param());
+    let file_id = ctx.frange.file_id;
+
+    acc.add(
+        AssistId("move_fn_out_of_impl", AssistKind::RefactorRewrite),
+        "Move function out of impl",
+        func.syntax().text_range(),
+        |builder| {
+            let mut edits = Vec::new();
+            for (ref_file_id, references) in
+                Definition::ModuleDef(def.into()).usages(&ctx.sema).all()
+            {
+                let source_file = ctx.sema.parse(ref_file_id);
+                for FileReference { range, .. } in references {
+                    let name_ref =
+                        match find_node_at_range::<ast::NameRef>(source_file.syntax(), range) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                    edits.extend(
+                        free_fn_call(ctx, &name_ref, module, self_param.as_ref())
+                            .into_iter()
+                            .map(|(range, text)| (ref_file_id, range, text)),
+                    );
+                }
+            }
+            if let Some(self_param) = &self_param {
+                edits.extend(
+                    self_param_to_this(ctx, self_param, &self_ty)
+                        .into_iter()
+                        .map(|(range, text)| (file_id, range, text)),
+                );
+            }
+            // `Self` refers to the type of the impl, unless it is part of a rewritten call.
+            let fn_range = func.syntax().text_range();
+            let self_types = func
+                .syntax()
+                .descendants()
+                .filter_map(ast::NameRef::cast)
+                .filter(|it| it.text() == "Self")
+                .map(|it| it.syntax().text_range())
+                .filter(|range| {
+                    !edits.iter().any(|(id, it, _)| *id == file_id && it.contains_range(*range))
+                })
+                .collect::<Vec<_>>();
+            edits.extend(self_types.into_iter().map(|range| (file_id, range, self_ty.clone())));
+
+            let (inner, outer): (Vec<_>, Vec<_>) = edits
+                .into_iter()
+                .partition(|(id, range, _)| *id == file_id && fn_range.contains_range(*range));
+            let inner = inner.into_iter().map(|(_, range, text)| (range, text)).collect();
+            let text = rewrite(func.syntax(), inner);
+            let indent = IndentLevel::from_node(impl_.syntax());
+            let text = text.replace(
+                &format!("\n{}", IndentLevel::from_node(func.syntax())),
+                &format!("\n{}", indent),
+            );
+
+            if has_only_item(&impl_, func.syntax()) {
+                builder.replace(impl_.syntax().text_range(), text);
+            } else {
+                builder.delete(with_leading_whitespace(func.syntax()));
+                builder
+                    .insert(impl_.syntax().text_range().end(), format!("\n\n{}{}", indent, text));
+            }
+            for (id, range, text) in outer {
+                builder.edit_file(id);
+                builder.replace(range, text);
+            }
+        },
+    )
+}
+
+// Assist: move_fn_into_impl
+//
+// Moves a free function into the impl block of the type of its first parameter, which
+// becomes `self`. The calls are rewritten into method calls.
+//
+// ```
+// struct Counter(u32);
+// impl Counter {}
+// fn $0bump(counter: &mut Counter) {
+//     counter.0 += 1;
+// }
+// fn main() {
+//     let mut c = Counter(0);
+//     bump(&mut c);
+// }
+// ```
+// ->
+// ```
+// struct Counter(u32);
+// impl Counter {
+//     fn bump(&mut self) {
+//         self.0 += 1;
+//     }
+// }
+// fn main() {
+//     let mut c = Counter(0);
+//     c.bump();
+// }
+// ```
+pub(crate) fn move_fn_into_impl(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let func = ast::Fn::cast(name.syntax().parent()?)?;
+    if !matches!(func.syntax().parent()?.kind(), SyntaxKind::SOURCE_FILE | SyntaxKind::ITEM_LIST) {
+        return None;
+    }
+    let param = func.param_list()?.params().next()?;
+    let ident_pat = match param.pat()? {
+        ast::Pat::IdentPat(it) if it.ref_token().is_none() && it.pat().is_none() => it,
+        _ => return None,
+    };
+    let self_kind = match param.ty()? {
+        ast::Type::RefType(it) if it.mut_token().is_some() => ast::SelfParamKind::MutRef,
+        ast::Type::RefType(_) => ast::SelfParamKind::Ref,
+        _ => ast::SelfParamKind::Owned,
+    };
+    let db = ctx.db();
+    let local = ctx.sema.to_def(&ident_pat)?;
+    let adt = local.ty(db).strip_references().as_adt()?;
+    let def = ctx.sema.to_def(&func)?;
+
+    let impls = hir::Impl::all_for_type(db, adt.ty(db));
+    let fn_name = name.text();
+    let name_taken = impls.iter().flat_map(|it| it.items(db)).any(|item| match item {
+        hir::AssocItem::Function(it) => it.name(db).to_string() == fn_name,
+        _ => false,
+    });
+    if name_taken {
+        cov_mark::hit!(move_fn_into_impl_name_taken);
+        return None;
+    }
+    let file_id = ctx.frange.file_id;
+    let impl_ = impls.into_iter().find_map(|it| {
+        if it.trait_(db).is_some() {
+            return None;
+        }
+        let source = it.source(db)?;
+        if source.file_id != file_id.into() || source.value.generic_param_list().is_some() {
+            return None;
+        }
+        Some(source.value)
+    })?;
+    let item_list = impl_.assoc_item_list()?;
+
+    acc.add(
+        AssistId("move_fn_into_impl", AssistKind::RefactorRewrite),
+        format!("Move function into `impl {}`", adt.name(db)),
+        func.syntax().text_range(),
+        |builder| {
+            let mut edits = Vec::new();
+            for (ref_file_id, references) in
+                Definition::ModuleDef(def.into()).usages(&ctx.sema).all()
+            {
+                let source_file = ctx.sema.parse(ref_file_id);
+                for FileReference { range, .. } in references {
+                    let name_ref =
+                        match find_node_at_range::<ast::NameRef>(source_file.syntax(), range) {
+                            Some(it) => it,
+                            None => continue,
+                        };
+                    edits.extend(
+                        method_call(ctx, &name_ref, adt, self_kind)
+                            .into_iter()
+                            .map(|(range, text)| (ref_file_id, range, text)),
+                    );
+                }
+            }
+            let self_text = match self_kind {
+                ast::SelfParamKind::Owned if ident_pat.mut_token().is_some() => "mut self",
+                ast::SelfParamKind::Owned => "self",
+                ast::SelfParamKind::Ref => "&self",
+                ast::SelfParamKind::MutRef => "&mut self",
+            };
+            edits.push((file_id, param.syntax().text_range(), self_text.to_string()));
+            let usages = Definition::Local(local)
+                .usages(&ctx.sema)
+                .in_scope(SearchScope::single_file(file_id))
+                .all();
+            for reference in usages.references.values().flatten() {
+                let is_shorthand = match &reference.name {
+                    ast::NameLike::NameRef(name_ref) => name_ref
+                        .syntax()
+                        .ancestors()
+                        .take(5)
+                        .find_map(ast::RecordExprField::cast)
+                        .map_or(false, |field| field.name_ref().is_none()),
+                    _ => false,
+                };
+                let text = if is_shorthand {
+                    format!("{}: self", ident_pat.syntax())
+                } else {
+                    "self".to_string()
+                };
+                edits.push((file_id, reference.range, text));
+            }
+
+            let fn_range = func.syntax().text_range();
+            let (inner, outer): (Vec<_>, Vec<_>) = edits
+                .into_iter()
+                .partition(|(id, range, _)| *id == file_id && fn_range.contains_range(*range));
+            let inner = inner.into_iter().map(|(_, range, text)| (range, text)).collect();
+            let text = rewrite(func.syntax(), inner);
+            let indent = IndentLevel::from_node(impl_.syntax());
+            let text = text.replace(
+                &format!("\n{}", IndentLevel::from_node(func.syntax())),
+                &format!("\n{}", indent + 1),
+            );
+
+            builder.delete(with_leading_whitespace(func.syntax()));
+            match item_list.assoc_items().last() {
+                Some(last) => builder.insert(
+                    last.syntax().text_range().end(),
+                    format!("\n\n{}{}", indent + 1, text),
+                ),
+                None => builder.replace(
+                    item_list.syntax().text_range(),
+                    format!("{{\n{}{}\n{}}}", indent + 1, text, indent),
+                ),
+            }
+            for (id, range, text) in outer {
+                builder.edit_file(id);
+                builder.replace(range, text);
+            }
+        },
+    )
+}
+
+/// Returns the edits turning `self_param` into an explicit `this` parameter.
+fn self_param_to_this(
+    ctx: &AssistContext,
+    self_param: &ast::SelfParam,
+    self_ty: &str,
+) -> Vec<(TextRange, String)> {
+    let self_token = match self_param.self_token() {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let mut edits = Vec::new();
+    if self_param.ty().is_some() {
+        edits.push((self_token.text_range(), "this".to_string()));
+    } else if self_param.kind() == ast::SelfParamKind::Owned {
+        edits.push((self_token.text_range(), format!("this: {}", self_ty)));
+    } else {
+        let prefix = TextRange::new(
+            self_param.syntax().text_range().start(),
+            self_token.text_range().start(),
+        );
+        let prefix_text = &self_param.syntax().text().to_string()[..usize::from(prefix.len())];
+        edits.push((prefix, String::new()));
+        edits.push((self_token.text_range(), format!("this: {}{}", prefix_text, self_ty)));
+    }
+    if let Some(local) = ctx.sema.to_def(self_param) {
+        let usages = Definition::Local(local)
+            .usages(&ctx.sema)
+            .in_scope(SearchScope::single_file(ctx.frange.file_id))
+            .all();
+        edits.extend(usages.references.values().flatten().map(|it| (it.range, "this".to_string())));
+    }
+    edits
+}
+
+/// Returns the edits turning a reference to a method or an associated function
+/// into a reference to the free function, defined in `module`.
+fn free_fn_call(
+    ctx: &AssistContext,
+    name_ref: &ast::NameRef,
+    module: Module,
+    self_param: Option<&ast::SelfParam>,
+) -> Vec<(TextRange, String)> {
+    let path = match ctx.sema.scope(name_ref.syntax()).module() {
+        Some(it) if it != module => match it.find_use_path(ctx.db(), ModuleDef::Module(module)) {
+            Some(mod_path) => format!("{}::{}", mod_path_to_ast(&mod_path), name_ref),
+            None => return Vec::new(),
+        },
+        Some(_) => name_ref.to_string(),
+        None => return Vec::new(),
+    };
+    if let Some(call) = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast) {
+        let (receiver, arg_list) = match (call.receiver(), call.arg_list()) {
+            (Some(receiver), Some(arg_list)) => (receiver, arg_list),
+            _ => return Vec::new(),
+        };
+        let is_reference =
+            ctx.sema.type_of_expr(&receiver).map_or(false, |ty| ty.remove_ref().is_some());
+        let borrow = match self_param {
+            Some(it) if it.ty().is_none() && !is_reference => match it.kind() {
+                ast::SelfParamKind::Owned => "",
+                ast::SelfParamKind::Ref => "&",
+                ast::SelfParamKind::MutRef => "&mut ",
+            },
+            _ => "",
+        };
+        let generic_args =
+            call.generic_arg_list().map_or(String::new(), |it| it.syntax().to_string());
+        let receiver_range = receiver.syntax().text_range();
+        let separator = if arg_list.args().next().is_some() { ", " } else { "" };
+        let l_paren_end = match arg_list.l_paren_token() {
+            Some(it) => it.text_range().end(),
+            None => return Vec::new(),
+        };
+        return vec![
+            (
+                TextRange::empty(receiver_range.start()),
+                format!("{}{}({}", path, generic_args, borrow),
+            ),
+            (TextRange::new(receiver_range.end(), l_paren_end), separator.to_string()),
+        ];
+    }
+    match name_ref.syntax().ancestors().find_map(ast::PathExpr::cast).and_then(|it| it.path()) {
+        Some(fn_path) => vec![(fn_path.syntax().text_range(), {
+            let generic_args = fn_path
+                .segment()
+                .and_then(|it| it.generic_arg_list())
+                .map_or(String::new(), |it| it.syntax().to_string());
+            format!("{}{}", path, generic_args)
+        })],
+        None => Vec::new(),
+    }
+}
+
+/// Returns the edits turning a reference to a free function into a method
+/// call, or into a path to the associated function of `adt`.
+fn method_call(
+    ctx: &AssistContext,
+    name_ref: &ast::NameRef,
+    adt: hir::Adt,
+    self_kind: ast::SelfParamKind,
+) -> Vec<(TextRange, String)> {
+    let fn_path = match name_ref.syntax().ancestors().find_map(ast::PathExpr::cast) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+    let generic_args = fn_path
+        .path()
+        .and_then(|it| it.segment())
+        .and_then(|it| it.generic_arg_list())
+        .map_or(String::new(), |it| it.syntax().to_string());
+    let call = fn_path.syntax().parent().and_then(ast::CallExpr::cast);
+    let args = call.as_ref().and_then(|it| it.arg_list());
+    let first_arg = args.as_ref().and_then(|it| it.args().next());
+    let (call, args, first_arg) = match (call, args, first_arg) {
+        (Some(call), Some(args), Some(first_arg)) => (call, args, first_arg),
+        _ => {
+            let module = match ctx.sema.scope(name_ref.syntax()).module() {
+                Some(it) => it,
+                None => return Vec::new(),
+            };
+            return match module.find_use_path(ctx.db(), ModuleDef::Adt(adt)) {
+                Some(ty_path) => vec![(
+                    fn_path.syntax().text_range(),
+                    format!("{}::{}{}", mod_path_to_ast(&ty_path), name_ref, generic_args),
+                )],
+                None => Vec::new(),
+            };
+        }
+    };
+    let receiver = match (&first_arg, self_kind) {
+        (ast::Expr::RefExpr(it), ast::SelfParamKind::Ref)
+        | (ast::Expr::RefExpr(it), ast::SelfParamKind::MutRef)
+            if it.mut_token().is_some() == (self_kind == ast::SelfParamKind::MutRef) =>
+        {
+            it.expr().unwrap_or_else(|| first_arg.clone())
+        }
+        _ => first_arg.clone(),
+    };
+    let needs_parens = !matches!(
+        receiver,
+        ast::Expr::PathExpr(_)
+            | ast::Expr::MethodCallExpr(_)
+            | ast::Expr::FieldExpr(_)
+            | ast::Expr::CallExpr(_)
+            | ast::Expr::IndexExpr(_)
+            | ast::Expr::ParenExpr(_)
+            | ast::Expr::Literal(_)
+            | ast::Expr::MacroCall(_)
+            | ast::Expr::TupleExpr(_)
+            | ast::Expr::ArrayExpr(_)
+    );
+    let (open, close) = if needs_parens { ("(", ")") } else { ("", "") };
+    let receiver_range = receiver.syntax().text_range();
+    let next_start = match (args.args().nth(1), args.r_paren_token()) {
+        (Some(arg), _) => arg.syntax().text_range().start(),
+        (None, Some(r_paren)) => r_paren.text_range().start(),
+        (None, None) => return Vec::new(),
+    };
+    vec![
+        (
+            TextRange::new(call.syntax().text_range().start(), receiver_range.start()),
+            open.to_string(),
+        ),
+        (
+            TextRange::new(receiver_range.end(), next_start),
+            format!("{}.{}{}(", close, name_ref, generic_args),
+        ),
+    ]
+}
+
+/// Returns the text of `node` with the `edits`, which are inside of it, applied.
+fn rewrite(node: &SyntaxNode, mut edits: Vec<(TextRange, String)>) -> String {
+    let mut text = node.to_string();
+    let start = node.text_range().start();
+    edits.sort_by_key(|(range, _)| (range.start(), range.end()));
+    for (range, replacement) in edits.into_iter().rev() {
+        text.replace_range(std::ops::Range::<usize>::from(range - start), &replacement);
+    }
+    text
+}
+
+/// Checks that `item` is all there is in the body of `impl_`, comments included.
+fn has_only_item(impl_: &ast::Impl, item: &SyntaxNode) -> bool {
+    let item_list = match impl_.assoc_item_list() {
+        Some(it) => it,
+        None => return false,
+    };
+    item_list.syntax().children_with_tokens().all(|it| match it {
+        NodeOrToken::Node(node) => &node == item,
+        NodeOrToken::Token(token) => matches!(
+            token.kind(),
+            SyntaxKind::WHITESPACE | SyntaxKind::L_CURLY | SyntaxKind::R_CURLY
+        ),
+    })
+}
+
+fn with_leading_whitespace(node: &SyntaxNode) -> TextRange {
+    match node.prev_sibling_or_token() {
+        Some(ws) if ws.kind() == SyntaxKind::WHITESPACE => node.text_range().cover(ws.text_range()),
+        _ => node.text_range(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn moves_method_out_of_impl_and_fixes_calls() {
+        check_assist(
+            move_fn_out_of_impl,
+            r#"
+mod shapes {
+    pub struct Rect { pub w: u32, pub h: u32 }
+    impl Rect {
+        pub fn new(w: u32, h: u32) -> Self { Self { w, h } }
+        pub fn $0area(&self, scale: u32) -> u32 {
+            if self.w == 0 { return 0; }
+            self.w * self.h * scale + Self::new(0, 0).area(1)
+        }
+    }
+}
+fn main() {
+    let r = shapes::Rect::new(1, 2);
+    let r_ref = &r;
+    r.area(2);
+    r_ref.area(3);
+}
+"#,
+            r#"
+mod shapes {
+    pub struct Rect { pub w: u32, pub h: u32 }
+    impl Rect {
+        pub fn new(w: u32, h: u32) -> Self { Self { w, h } }
+    }
+
+    pub fn area(this: &Rect, scale: u32) -> u32 {
+        if this.w == 0 { return 0; }
+        this.w * this.h * scale + area(&Rect::new(0, 0), 1)
+    }
+}
+fn main() {
+    let r = shapes::Rect::new(1, 2);
+    let r_ref = &r;
+    shapes::area(&r, 2);
+    shapes::area(r_ref, 3);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_associated_fn_out_of_impl() {
+        check_assist(
+            move_fn_out_of_impl,
+            r#"
+struct S;
+impl S {
+    fn $0make() -> Self { S }
+    fn other() {}
+}
+fn f() -> S {
+    let g = S::make;
+    S::make()
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn other() {}
+}
+
+fn make() -> S { S }
+fn f() -> S {
+    let g = make;
+    make()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_emptied_impl() {
+        check_assist(
+            move_fn_out_of_impl,
+            r#"
+mod m {
+    pub struct S;
+    /// Docs.
+    impl S {
+        pub fn $0f(self) {}
+    }
+    fn g() {
+        S.f();
+    }
+}
+"#,
+            r#"
+mod m {
+    pub struct S;
+    pub fn f(this: S) {}
+    fn g() {
+        f(S);
+    }
+}
+"#,
+        );
+        check_assist(
+            move_fn_out_of_impl,
+            r#"
+struct S;
+impl S {
+    // Kept.
+
+    fn $0f() {}
+}
+"#,
+            r#"
+struct S;
+impl S {
+    // Kept.
+}
+
+fn f() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_free_fn_into_impl() {
+        check_assist(
+            move_fn_into_impl,
+            r#"
+struct Point { x: i32, y: i32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+}
+fn $0shifted(point: &Point, dx: i32) -> Point {
+    let Point { y, .. } = *point;
+    Point { x: point.x + dx, y }
+}
+fn main() {
+    let p = Point::origin();
+    let q = shifted(&p, 1);
+    shifted(&shifted(&q, 2), 3);
+    let f = shifted;
+}
+"#,
+            r#"
+struct Point { x: i32, y: i32 }
+impl Point {
+    fn origin() -> Point { Point { x: 0, y: 0 } }
+
+    fn shifted(&self, dx: i32) -> Point {
+        let Point { y, .. } = *self;
+        Point { x: self.x + dx, y }
+    }
+}
+fn main() {
+    let p = Point::origin();
+    let q = p.shifted(1);
+    q.shifted(2).shifted(3);
+    let f = Point::shifted;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_name_is_taken() {
+        cov_mark::check!(move_fn_out_of_impl_name_taken);
+        check_assist_not_applicable(
+            move_fn_out_of_impl,
+            r#"
+struct S;
+impl S {
+    fn $0f(&self) {}
+}
+fn f() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_method_exists() {
+        cov_mark::check!(move_fn_into_impl_name_taken);
+        check_assist_not_applicable(
+            move_fn_into_impl,
+            r#"
+struct S;
+impl S {
+    fn f(&self) {}
+}
+fn $0f(s: &S) {}
+"#,
+        );
+    }
+}
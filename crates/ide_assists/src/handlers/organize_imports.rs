@@ -0,0 +1,253 @@
+use hir::{ModuleDef, PathResolution};
+use ide_db::{defs::Definition, helpers::insert_use, search::SearchScope};
+use itertools::Itertools;
+use syntax::{
+    ast::{self, edit::IndentLevel, AttrsOwner, VisibilityOwner},
+    AstNode, SyntaxKind, SyntaxNode, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: organize_imports
+//
+// Rebuilds all the imports of the file or module according to the configured import
+// granularity and groups, sorting them and removing the ones which are not used.
+//
+// ```
+// mod fmt { pub struct Display; pub struct Debug; pub struct Formatter; }
+// use fmt::Formatter;
+// $0use fmt::Display;
+// use fmt::Debug;
+//
+// fn show(f: Formatter, d: Display) {}
+// ```
+// ->
+// ```
+// mod fmt { pub struct Display; pub struct Debug; pub struct Formatter; }
+// use fmt::{Display, Formatter};
+//
+// fn show(f: Formatter, d: Display) {}
+// ```
+pub(crate) fn organize_imports(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let use_item = ctx.find_node_at_offset::<ast::Use>()?;
+    let parent = use_item.syntax().parent()?;
+    let uses = parent
+        .children()
+        .filter_map(ast::Use::cast)
+        .filter(|it| it.attrs().next().is_none())
+        .collect::<Vec<_>>();
+    let has_comments = uses
+        .iter()
+        .any(|it| it.syntax().descendants_with_tokens().any(|it| it.kind() == SyntaxKind::COMMENT));
+    if !uses.contains(&use_item) || has_comments {
+        return None;
+    }
+    let first = uses.first()?;
+    let last = uses.last()?;
+    let range =
+        TextRange::new(first.syntax().text_range().start(), last.syntax().text_range().end());
+    let module = ctx.sema.scope(use_item.syntax()).module()?;
+    let indent = IndentLevel::from_node(first.syntax());
+    let organize = |keep: &dyn Fn(&ast::UseTree) -> bool| {
+        insert_use::organize_imports(&uses, &ctx.config.insert_use, keep)
+            .iter()
+            .map(|group| group.iter().join(&format!("\n{}", indent)))
+            .join(&format!("\n\n{}", indent))
+    };
+
+    // Searching for the usages of the imports is only done when the assist is applied, so the
+    // imports may not change when some of them could be unused.
+    let is_organized =
+        organize(&|_| true) == parent.text().slice(range - parent.text_range().start()).to_string();
+    let may_be_unused = uses
+        .iter()
+        .flat_map(|it| it.syntax().descendants().filter_map(ast::UseTree::cast))
+        .any(|tree| tree.use_tree_list().is_none() && removable_import(ctx, &tree).is_some());
+    if is_organized && !may_be_unused {
+        cov_mark::hit!(organize_imports_unchanged);
+        return None;
+    }
+
+    acc.add(
+        AssistId("organize_imports", AssistKind::RefactorRewrite),
+        "Organize imports",
+        range,
+        |builder| {
+            // The imports of a module are also used by its child modules, through `super`.
+            let scope = SearchScope::module(ctx.db(), module);
+            let text = organize(&|tree| is_used(ctx, tree, &uses, &scope));
+            if text.is_empty() {
+                for use_item in &uses {
+                    builder.delete(with_whitespace(use_item.syntax(), false));
+                }
+                return;
+            }
+            builder.replace(first.syntax().text_range(), text);
+            for use_item in &uses[1..] {
+                builder.delete(with_whitespace(use_item.syntax(), true));
+            }
+        },
+    )
+}
+
+/// Returns whether the import `tree` has to be kept: unless the item it imports can be
+/// resolved and is referred to only by `uses`, the imports being organized.
+fn is_used(
+    ctx: &AssistContext,
+    tree: &ast::UseTree,
+    uses: &[ast::Use],
+    scope: &SearchScope,
+) -> bool {
+    let def = match removable_import(ctx, tree) {
+        Some(it) => it,
+        None => return true,
+    };
+    let usages = def.usages(&ctx.sema).in_scope(scope.clone()).all();
+    usages.references.values().flatten().any(|reference| {
+        !uses.iter().any(|it| it.syntax().text_range().contains_range(reference.range))
+    })
+}
+
+/// Returns the item imported by `tree` if the import can be removed when it isn't used, which
+/// isn't the case of public, glob and renaming imports, nor of trait imports.
+fn removable_import(ctx: &AssistContext, tree: &ast::UseTree) -> Option<Definition> {
+    let path = match tree.path() {
+        Some(it) if tree.star_token().is_none() && tree.rename().is_none() => it,
+        _ => return None,
+    };
+    let is_pub = tree
+        .syntax()
+        .ancestors()
+        .find_map(ast::Use::cast)
+        .map_or(true, |it| it.visibility().is_some());
+    let name_ref = match path.segment().and_then(|it| it.name_ref()) {
+        Some(it) if !is_pub && it.self_token().is_none() => it,
+        _ => return None,
+    };
+    let def = match ctx.sema.resolve_path(&path) {
+        // Traits are used by their methods, which the search doesn't find.
+        Some(PathResolution::Def(ModuleDef::Trait(_))) => return None,
+        Some(PathResolution::Def(def)) => Definition::ModuleDef(def),
+        Some(PathResolution::Macro(def)) => Definition::Macro(def),
+        _ => return None,
+    };
+    if def.name(ctx.db()).map_or(true, |it| it.to_string() != name_ref.text()) {
+        return None;
+    }
+    Some(def)
+}
+
+/// Returns the range of `node` along with the whitespace before or after it.
+fn with_whitespace(node: &SyntaxNode, leading: bool) -> TextRange {
+    let whitespace =
+        if leading { node.prev_sibling_or_token() } else { node.next_sibling_or_token() };
+    match whitespace {
+        Some(it) if it.kind() == SyntaxKind::WHITESPACE => node.text_range().cover(it.text_range()),
+        _ => node.text_range(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn merges_sorts_and_removes_unused_imports() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo {
+    pub struct A;
+    pub struct B;
+    pub fn f() {}
+    pub mod bar { pub struct C; }
+    pub trait T {}
+}
+use crate::foo::T;
+use foo::{A, f};
+$0use foo::bar::C;
+use foo::B;
+
+fn main() {
+    let _ = (A, C);
+    f();
+}
+"#,
+            r#"
+mod foo {
+    pub struct A;
+    pub struct B;
+    pub fn f() {}
+    pub mod bar { pub struct C; }
+    pub trait T {}
+}
+use foo::{A, bar::C, f};
+
+use crate::foo::T;
+
+fn main() {
+    let _ = (A, C);
+    f();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_all_unused_imports() {
+        check_assist(
+            organize_imports,
+            r#"
+mod foo { pub struct A; pub struct B; }
+mod m {
+    use super::foo::A;$0
+    use super::foo::B;
+    fn f() {}
+}
+"#,
+            r#"
+mod foo { pub struct A; pub struct B; }
+mod m {
+    fn f() {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_imports_used_by_child_modules() {
+        check_assist(
+            organize_imports,
+            r#"
+//- /main.rs
+mod foo { pub struct A; pub struct B; pub struct C; }
+use foo::C;
+use foo::B;$0
+use foo::A;
+mod tests;
+//- /tests.rs
+use super::*;
+fn f(a: A, b: super::B) {}
+"#,
+            r#"
+mod foo { pub struct A; pub struct B; pub struct C; }
+use foo::{A, B};
+mod tests;
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_organized() {
+        cov_mark::check!(organize_imports_unchanged);
+        check_assist_not_applicable(
+            organize_imports,
+            r#"
+mod foo { pub struct A; pub struct B; }
+pub use foo::{A, B};$0
+"#,
+        );
+    }
+}
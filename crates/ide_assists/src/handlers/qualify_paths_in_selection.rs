@@ -0,0 +1,383 @@
+use hir::{AsAssocItem, ModuleDef, PathResolution, PrefixKind};
+use ide_db::helpers::{
+    insert_use::{insert_use, ImportScope},
+    mod_path_to_ast,
+};
+use rustc_hash::FxHashMap;
+use syntax::{
+    ast::{self, make},
+    ted, AstNode, SyntaxNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: qualify_paths_in_selection
+//
+// Replaces all the paths in the selection, which refer to items through imports, with
+// fully qualified paths. Select the whole file to qualify all of it. The items of the
+// prelude and of the current module are left as they are.
+//
+// ```
+// mod fmt { pub struct Formatter; }
+// use fmt::Formatter;
+//
+// $0fn show(f: &mut Formatter) {}$0
+// ```
+// ->
+// ```
+// mod fmt { pub struct Formatter; }
+// use fmt::Formatter;
+//
+// fn show(f: &mut crate::fmt::Formatter) {}
+// ```
+pub(crate) fn qualify_paths_in_selection(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let node = ctx.covering_element().ancestors().next()?;
+    let paths = paths_in_selection(ctx, &node)?;
+    let module = ctx.sema.scope(&node).module()?;
+
+    let mut replacements = Vec::new();
+    for path in paths {
+        let first = path.first_qualifier_or_self();
+        let segment = match first.segment() {
+            Some(it) if it.coloncolon_token().is_none() => it,
+            _ => continue,
+        };
+        let name_ref = match segment.kind() {
+            Some(ast::PathSegmentKind::Name(it)) => it,
+            _ => continue,
+        };
+        let path_module = match ctx.sema.scope(first.syntax()).module() {
+            Some(it) => it,
+            None => continue,
+        };
+        // The names which aren't in the scope of the module are found through the prelude.
+        let in_scope = path_module
+            .scope(ctx.db(), None)
+            .iter()
+            .any(|(name, _)| name.to_string() == name_ref.text());
+        if !in_scope {
+            cov_mark::hit!(qualify_paths_skips_prelude);
+            continue;
+        }
+        let (def_module, mod_path) = match ctx.sema.resolve_path(&first) {
+            Some(PathResolution::Def(def)) => (
+                def.module(ctx.db()),
+                module.find_use_path_prefixed(ctx.db(), def, PrefixKind::ByCrate),
+            ),
+            Some(PathResolution::Macro(def)) => (
+                def.module(ctx.db()),
+                module.find_use_path_prefixed(ctx.db(), def, PrefixKind::ByCrate),
+            ),
+            _ => continue,
+        };
+        if def_module == Some(path_module) {
+            cov_mark::hit!(qualify_paths_skips_local_items);
+            continue;
+        }
+        let qualified = match mod_path {
+            Some(it) => mod_path_to_ast(&it).to_string(),
+            None => continue,
+        };
+        if qualified != name_ref.text() {
+            replacements.push((name_ref.syntax().text_range(), qualified));
+        }
+    }
+    if replacements.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("qualify_paths_in_selection", AssistKind::RefactorRewrite),
+        "Qualify all paths in selection",
+        ctx.frange.range,
+        |builder| {
+            for (range, text) in replacements {
+                builder.replace(range, text);
+            }
+        },
+    )
+}
+
+// Assist: unqualify_paths_in_selection
+//
+// Shortens all the qualified paths in the selection to the names of the items they refer
+// to, importing them. Paths whose names are already taken by other items are kept.
+//
+// ```
+// mod fmt { pub struct Formatter; }
+//
+// $0fn show(f: &mut fmt::Formatter) {}$0
+// ```
+// ->
+// ```
+// use fmt::Formatter;
+//
+// mod fmt { pub struct Formatter; }
+//
+// fn show(f: &mut Formatter) {}
+// ```
+pub(crate) fn unqualify_paths_in_selection(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let node = ctx.covering_element().ancestors().next()?;
+    let paths = paths_in_selection(ctx, &node)?;
+    let import_scope = ImportScope::find_insert_use_container_with_macros(&node, &ctx.sema)?;
+    let module = ctx.sema.scope(import_scope.as_syntax_node()).module()?;
+
+    let mut shortened = Vec::new();
+    let mut imports: FxHashMap<String, (PathResolution, ast::Path)> = FxHashMap::default();
+    for path in paths {
+        // Paths in nested modules or blocks would need imports of their own.
+        let path_scope =
+            ImportScope::find_insert_use_container_with_macros(path.syntax(), &ctx.sema)?;
+        if path_scope.as_syntax_node() != import_scope.as_syntax_node() {
+            continue;
+        }
+        let (prefix, resolution) =
+            match std::iter::successors(Some(path.clone()), ast::Path::qualifier).find_map(
+                |prefix| {
+                    prefix.qualifier()?;
+                    let resolution = importable(ctx, &prefix)?;
+                    Some((prefix, resolution))
+                },
+            ) {
+                Some(it) => it,
+                None => continue,
+            };
+        let name = match prefix.segment().and_then(|it| it.name_ref()) {
+            Some(it) => it.text().to_string(),
+            None => continue,
+        };
+
+        let scope = ctx.sema.scope(path.syntax());
+        match scope.speculative_resolve(&make::ext::ident_path(&name)) {
+            Some(it) if it == resolution => (),
+            Some(_) => continue,
+            None => match imports.get(&name) {
+                Some((it, _)) if *it == resolution => (),
+                Some(_) => continue,
+                None => {
+                    let mod_path = match resolution {
+                        PathResolution::Def(def) => module.find_use_path_prefixed(
+                            ctx.db(),
+                            def,
+                            ctx.config.insert_use.prefix_kind,
+                        ),
+                        PathResolution::Macro(def) => module.find_use_path_prefixed(
+                            ctx.db(),
+                            def,
+                            ctx.config.insert_use.prefix_kind,
+                        ),
+                        _ => None,
+                    };
+                    match mod_path {
+                        Some(it) => imports.insert(name, (resolution, mod_path_to_ast(&it))),
+                        None => continue,
+                    };
+                }
+            },
+        }
+        shortened.push(prefix);
+    }
+    if shortened.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("unqualify_paths_in_selection", AssistKind::RefactorRewrite),
+        "Replace qualified paths in selection with imports",
+        ctx.frange.range,
+        |builder| {
+            let import_scope = match import_scope {
+                ImportScope::File(it) => ImportScope::File(builder.make_mut(it)),
+                ImportScope::Module(it) => ImportScope::Module(builder.make_mut(it)),
+                ImportScope::Block(it) => ImportScope::Block(builder.make_mut(it)),
+            };
+            let shortened =
+                shortened.into_iter().map(|it| builder.make_mut(it)).collect::<Vec<_>>();
+            for path in shortened {
+                if let Some(qualifier) = path.qualifier() {
+                    ted::remove(qualifier.syntax());
+                }
+                if let Some(double_colon) = path.coloncolon_token() {
+                    ted::remove(&double_colon);
+                }
+            }
+            let mut imports = imports.into_iter().collect::<Vec<_>>();
+            imports.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (_, (_, path)) in imports {
+                insert_use(&import_scope, path, &ctx.config.insert_use);
+            }
+        },
+    )
+}
+
+/// Returns the outermost paths inside of the selection, which is covered by `node`,
+/// leaving out the ones in `use` items.
+fn paths_in_selection(ctx: &AssistContext, node: &SyntaxNode) -> Option<Vec<ast::Path>> {
+    let range = ctx.frange.range;
+    if range.is_empty() {
+        return None;
+    }
+    let paths = node
+        .descendants()
+        .filter_map(ast::Path::cast)
+        .filter(|path| {
+            range.contains_range(path.syntax().text_range())
+                && path.syntax().parent().and_then(ast::Path::cast).is_none()
+                && !path.syntax().ancestors().any(|it| ast::Use::can_cast(it.kind()))
+        })
+        .collect();
+    Some(paths)
+}
+
+/// Resolves `path` if it refers to an item which can be imported by its name,
+/// which excludes modules, enum variants and associated items.
+fn importable(ctx: &AssistContext, path: &ast::Path) -> Option<PathResolution> {
+    let resolution = ctx.sema.resolve_path(path)?;
+    match resolution {
+        PathResolution::Def(ModuleDef::Module(_))
+        | PathResolution::Def(ModuleDef::Variant(_))
+        | PathResolution::Def(ModuleDef::BuiltinType(_)) => None,
+        PathResolution::Def(def) if def.as_assoc_item(ctx.db()).is_none() => Some(resolution),
+        PathResolution::Macro(_) => Some(resolution),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn qualifies_imported_items_and_associated_paths() {
+        check_assist(
+            qualify_paths_in_selection,
+            r#"
+mod geometry {
+    pub struct Point;
+    impl Point {
+        pub fn new() -> Point { Point }
+    }
+    pub fn origin() -> Point { Point }
+}
+use geometry::{origin, Point};
+
+$0fn f(x: u8) -> Point {
+    let p = Point::new();
+    let y = x;
+    origin()
+}$0
+"#,
+            r#"
+mod geometry {
+    pub struct Point;
+    impl Point {
+        pub fn new() -> Point { Point }
+    }
+    pub fn origin() -> Point { Point }
+}
+use geometry::{origin, Point};
+
+fn f(x: u8) -> crate::geometry::Point {
+    let p = crate::geometry::Point::new();
+    let y = x;
+    crate::geometry::origin()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn leaves_prelude_and_local_items() {
+        cov_mark::check!(qualify_paths_skips_prelude);
+        cov_mark::check!(qualify_paths_skips_local_items);
+        check_assist(
+            qualify_paths_in_selection,
+            r#"
+//- minicore: option
+mod geometry { pub struct Point; }
+use geometry::Point;
+struct Line;
+
+$0fn f(l: Line) -> Option<Point> {
+    None
+}$0
+"#,
+            r#"
+mod geometry { pub struct Point; }
+use geometry::Point;
+struct Line;
+
+fn f(l: Line) -> Option<crate::geometry::Point> {
+    None
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unqualifies_paths_skipping_taken_names() {
+        check_assist(
+            unqualify_paths_in_selection,
+            r#"
+mod geometry {
+    pub struct Point;
+    impl Point {
+        pub fn new() -> Point { Point }
+    }
+    pub fn origin() -> Point { Point }
+    pub mod shapes {
+        pub struct Point;
+    }
+}
+struct Point;
+
+$0fn f() -> geometry::Point {
+    let p = geometry::shapes::Point;
+    let q = geometry::Point::new();
+    geometry::origin()
+}$0
+"#,
+            r#"
+use geometry::origin;
+
+mod geometry {
+    pub struct Point;
+    impl Point {
+        pub fn new() -> Point { Point }
+    }
+    pub fn origin() -> Point { Point }
+    pub mod shapes {
+        pub struct Point;
+    }
+}
+struct Point;
+
+fn f() -> geometry::Point {
+    let p = geometry::shapes::Point;
+    let q = geometry::Point::new();
+    origin()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_paths_to_change() {
+        check_assist_not_applicable(
+            qualify_paths_in_selection,
+            r#"
+mod geometry { pub struct Point; }
+$0fn f(p: crate::geometry::Point) {}$0
+"#,
+        );
+        check_assist_not_applicable(
+            unqualify_paths_in_selection,
+            r#"
+use std::fmt;
+$0fn f() {}$0
+"#,
+        );
+    }
+}
@@ -3,16 +3,13 @@ use rustc_hash::FxHashMap;
 
 use hir::{PathResolution, Semantics};
 use ide_db::RootDatabase;
-use syntax::{
-    ast::{self, NameOwner},
-    ted, AstNode,
-};
+use syntax::{ast, ted, AstNode};
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{utils::assoc_item_name, AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: reorder_impl
 //
-// Reorder the methods of an `impl Trait`. The methods will be ordered
+// Reorder the items of an `impl Trait`. The items will be ordered
 // in the same order as in the trait definition.
 //
 // ```
@@ -48,7 +45,7 @@ use crate::{AssistContext, AssistId, AssistKind, Assists};
 pub(crate) fn reorder_impl(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
     let impl_ast = ctx.find_node_at_offset::<ast::Impl>()?;
     let items = impl_ast.assoc_item_list()?;
-    let methods = get_methods(&items);
+    let methods = get_items(&items);
 
     let path = impl_ast
         .trait_()
@@ -63,7 +60,7 @@ pub(crate) fn reorder_impl(acc: &mut Assists, ctx: &AssistContext) -> Option<()>
         .iter()
         .cloned()
         .sorted_by_key(|f| {
-            f.name().and_then(|n| ranks.get(&n.to_string()).copied()).unwrap_or(usize::max_value())
+            assoc_item_name(f).and_then(|n| ranks.get(&n).copied()).unwrap_or(usize::max_value())
         })
         .collect();
 
@@ -76,10 +73,11 @@ pub(crate) fn reorder_impl(acc: &mut Assists, ctx: &AssistContext) -> Option<()>
     let target = items.syntax().text_range();
     acc.add(
         AssistId("reorder_impl", AssistKind::RefactorRewrite),
-        "Sort methods",
+        "Sort items by trait definition",
         target,
         |builder| {
-            let methods = methods.into_iter().map(|fn_| builder.make_mut(fn_)).collect::<Vec<_>>();
+            let methods =
+                methods.into_iter().map(|item| builder.make_mut(item)).collect::<Vec<_>>();
             methods
                 .into_iter()
                 .zip(sorted)
@@ -95,11 +93,12 @@ fn compute_method_ranks(path: &ast::Path, ctx: &AssistContext) -> Option<FxHashM
         td.items(ctx.db())
             .iter()
             .flat_map(|i| match i {
-                hir::AssocItem::Function(f) => Some(f),
-                _ => None,
+                hir::AssocItem::Function(f) => Some(f.name(ctx.db())),
+                hir::AssocItem::Const(c) => c.name(ctx.db()),
+                hir::AssocItem::TypeAlias(t) => Some(t.name(ctx.db())),
             })
             .enumerate()
-            .map(|(idx, func)| (func.name(ctx.db()).to_string(), idx))
+            .map(|(idx, name)| (name.to_string(), idx))
             .collect(),
     )
 }
@@ -111,15 +110,8 @@ fn trait_definition(path: &ast::Path, sema: &Semantics<RootDatabase>) -> Option<
     }
 }
 
-fn get_methods(items: &ast::AssocItemList) -> Vec<ast::Fn> {
-    items
-        .assoc_items()
-        .flat_map(|i| match i {
-            ast::AssocItem::Fn(f) => Some(f),
-            _ => None,
-        })
-        .filter(|f| f.name().is_some())
-        .collect()
+fn get_items(items: &ast::AssocItemList) -> Vec<ast::AssocItem> {
+    items.assoc_items().filter(|i| assoc_item_name(i).is_some()).collect()
 }
 
 #[cfg(test)]
@@ -200,6 +192,43 @@ impl Bar for Foo {
         )
     }
 
+    #[test]
+    fn reorder_impl_trait_assoc_items() {
+        check_assist(
+            reorder_impl,
+            r#"
+trait Bar {
+    type T;
+    const C: u32;
+    fn a();
+}
+
+struct Foo;
+$0impl Bar for Foo {
+    fn a() {}
+    /// Constant.
+    const C: u32 = 0;
+    type T = ();
+}
+        "#,
+            r#"
+trait Bar {
+    type T;
+    const C: u32;
+    fn a();
+}
+
+struct Foo;
+impl Bar for Foo {
+    type T = ();
+    /// Constant.
+    const C: u32 = 0;
+    fn a() {}
+}
+        "#,
+        )
+    }
+
     #[test]
     fn reorder_impl_trait_methods_uneven_ident_lengths() {
         check_assist(
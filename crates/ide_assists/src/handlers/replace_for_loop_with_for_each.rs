@@ -1,10 +1,8 @@
 use ast::LoopBodyOwner;
-use hir::known;
-use ide_db::helpers::FamousDefs;
 use stdx::format_to;
 use syntax::{ast, AstNode};
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{utils::iterator_of, AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: replace_for_loop_with_for_each
 //
@@ -42,24 +40,7 @@ pub(crate) fn replace_for_loop_with_for_each(acc: &mut Assists, ctx: &AssistCont
         "Replace this for loop with `Iterator::for_each`",
         for_loop.syntax().text_range(),
         |builder| {
-            let mut buf = String::new();
-
-            if let Some((expr_behind_ref, method)) =
-                is_ref_and_impls_iter_method(&ctx.sema, &iterable)
-            {
-                // We have either "for x in &col" and col implements a method called iter
-                //             or "for x in &mut col" and col implements a method called iter_mut
-                format_to!(buf, "{}.{}()", expr_behind_ref, method);
-            } else if impls_core_iter(&ctx.sema, &iterable) {
-                format_to!(buf, "{}", iterable);
-            } else {
-                if let ast::Expr::RefExpr(_) = iterable {
-                    format_to!(buf, "({}).into_iter()", iterable);
-                } else {
-                    format_to!(buf, "{}.into_iter()", iterable);
-                }
-            }
-
+            let mut buf = iterator_of(&ctx.sema, &iterable);
             format_to!(buf, ".for_each(|{}| {});", pat, body);
 
             builder.replace(for_loop.syntax().text_range(), buf)
@@ -67,68 +48,6 @@ pub(crate) fn replace_for_loop_with_for_each(acc: &mut Assists, ctx: &AssistCont
     )
 }
 
-/// If iterable is a reference where the expression behind the reference implements a method
-/// returning an Iterator called iter or iter_mut (depending on the type of reference) then return
-/// the expression behind the reference and the method name
-fn is_ref_and_impls_iter_method(
-    sema: &hir::Semantics<ide_db::RootDatabase>,
-    iterable: &ast::Expr,
-) -> Option<(ast::Expr, hir::Name)> {
-    let ref_expr = match iterable {
-        ast::Expr::RefExpr(r) => r,
-        _ => return None,
-    };
-    let wanted_method = if ref_expr.mut_token().is_some() { known::iter_mut } else { known::iter };
-    let expr_behind_ref = ref_expr.expr()?;
-    let typ = sema.type_of_expr(&expr_behind_ref)?;
-    let scope = sema.scope(iterable.syntax());
-    let krate = scope.module()?.krate();
-    let traits_in_scope = scope.traits_in_scope();
-    let iter_trait = FamousDefs(sema, Some(krate)).core_iter_Iterator()?;
-
-    let has_wanted_method = typ
-        .iterate_method_candidates(
-            sema.db,
-            krate,
-            &traits_in_scope,
-            Some(&wanted_method),
-            |_, func| {
-                if func.ret_type(sema.db).impls_trait(sema.db, iter_trait, &[]) {
-                    return Some(());
-                }
-                None
-            },
-        )
-        .is_some();
-    if !has_wanted_method {
-        return None;
-    }
-
-    Some((expr_behind_ref, wanted_method))
-}
-
-/// Whether iterable implements core::Iterator
-fn impls_core_iter(sema: &hir::Semantics<ide_db::RootDatabase>, iterable: &ast::Expr) -> bool {
-    let it_typ = match sema.type_of_expr(iterable) {
-        Some(it) => it,
-        None => return false,
-    };
-
-    let module = match sema.scope(iterable.syntax()).module() {
-        Some(it) => it,
-        None => return false,
-    };
-
-    let krate = module.krate();
-    match FamousDefs(sema, Some(krate)).core_iter_Iterator() {
-        Some(iter_trait) => {
-            cov_mark::hit!(test_already_impls_iterator);
-            it_typ.impls_trait(sema.db, iter_trait, &[])
-        }
-        None => false,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::tests::{check_assist, check_assist_not_applicable};
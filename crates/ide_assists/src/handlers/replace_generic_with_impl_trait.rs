@@ -0,0 +1,239 @@
+use hir::GenericParam;
+use ide_db::{defs::Definition, search::SearchScope};
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, AstNode, GenericParamsOwner, NameOwner, TypeBoundsOwner},
+    match_ast, SyntaxKind, SyntaxNode, TextRange, T,
+};
+
+use crate::{utils::next_prev, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_generic_with_impl_trait
+//
+// Replaces a generic parameter, which is only the type of a function argument, with
+// `impl Trait`. The bounds of the where clause are kept.
+//
+// ```
+// fn foo<$0I: Iterator<Item = u32>>(iter: I) where I: Clone {}
+// ```
+// ->
+// ```
+// fn foo(iter: impl Iterator<Item = u32> + Clone) {}
+// ```
+pub(crate) fn replace_generic_with_impl_trait(
+    acc: &mut Assists,
+    ctx: &AssistContext,
+) -> Option<()> {
+    let type_param = ctx.find_node_at_offset::<ast::TypeParam>()?;
+    let generic_param_list = ast::GenericParamList::cast(type_param.syntax().parent()?)?;
+    let fn_ = ast::Fn::cast(generic_param_list.syntax().parent()?)?;
+    let name = type_param.name()?;
+    if type_param.default_type().is_some() {
+        return None;
+    }
+
+    let predicates = fn_
+        .where_clause()
+        .into_iter()
+        .flat_map(|it| it.predicates())
+        .filter(|it| it.ty().map_or(false, |ty| ty.syntax().text() == name.text().as_str()))
+        .collect::<Vec<_>>();
+    // The parameter has to be used only as the type of a single argument, and
+    // by the predicates which are moved.
+    let def = ctx.sema.to_def(&type_param)?;
+    let usages = Definition::GenericParam(GenericParam::TypeParam(def))
+        .usages(&ctx.sema)
+        .in_scope(SearchScope::single_file(ctx.frange.file_id))
+        .all();
+    let mut references = usages.references.values().flatten().filter(|it| {
+        !predicates.iter().any(|pred| pred.syntax().text_range().contains_range(it.range))
+    });
+    let reference = references.next()?;
+    if references.next().is_some() {
+        cov_mark::hit!(replace_generic_used_several_times);
+        return None;
+    }
+    let param_ty = fn_
+        .param_list()?
+        .params()
+        .filter_map(|param| param.ty())
+        .find(|ty| ty.syntax().text_range() == reference.range)?;
+
+    let bounds = type_param
+        .type_bound_list()
+        .into_iter()
+        .chain(predicates.iter().filter_map(|it| it.type_bound_list()))
+        .flat_map(|it| it.bounds())
+        .map(|it| it.syntax().to_string())
+        .collect::<Vec<_>>();
+    if bounds.is_empty() || predicates.iter().any(|it| it.for_token().is_some()) {
+        return None;
+    }
+    // `impl Trait` arguments can't be given explicitly, so calls with a turbofish would break.
+    if has_turbofish_calls(ctx, &fn_) {
+        cov_mark::hit!(replace_generic_turbofish);
+        return None;
+    }
+
+    acc.add(
+        AssistId("replace_generic_with_impl_trait", AssistKind::RefactorRewrite),
+        "Replace generic with impl trait",
+        type_param.syntax().text_range(),
+        |edit| {
+            if generic_param_list.generic_params().count() == 1 {
+                edit.delete(generic_param_list.syntax().text_range());
+            } else {
+                edit.delete(range_in_list(type_param.syntax()));
+            }
+            edit.replace(param_ty.syntax().text_range(), format!("impl {}", bounds.join(" + ")));
+
+            let where_clause = match fn_.where_clause() {
+                Some(it) => it,
+                None => return,
+            };
+            if where_clause.predicates().count() == predicates.len() {
+                // The whitespace before the where clause goes with it.
+                let start = where_clause
+                    .syntax()
+                    .prev_sibling_or_token()
+                    .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+                    .map_or(where_clause.syntax().text_range().start(), |it| {
+                        it.text_range().start()
+                    });
+                edit.delete(TextRange::new(start, where_clause.syntax().text_range().end()));
+            } else {
+                for range in predicates.iter().map(|it| range_in_list(it.syntax())) {
+                    edit.delete(range);
+                }
+            }
+        },
+    )
+}
+
+fn has_turbofish_calls(ctx: &AssistContext, fn_: &ast::Fn) -> bool {
+    let def = match ctx.sema.to_def(fn_) {
+        Some(it) => Definition::ModuleDef(it.into()),
+        None => return true,
+    };
+    def.usages(&ctx.sema).all().into_iter().any(|(file_id, references)| {
+        let source_file = ctx.sema.parse(file_id);
+        references.into_iter().any(|reference| {
+            let parent = find_node_at_range::<ast::NameRef>(source_file.syntax(), reference.range)
+                .and_then(|it| it.syntax().parent());
+            let parent = match parent {
+                Some(it) => it,
+                None => return false,
+            };
+            match_ast! {
+                match parent {
+                    ast::PathSegment(it) => it.generic_arg_list().is_some(),
+                    ast::MethodCallExpr(it) => it.generic_arg_list().is_some(),
+                    _ => false,
+                }
+            }
+        })
+    })
+}
+
+/// Returns the range of `node` in a comma-separated list, along with the comma
+/// and the whitespace separating it from its neighbour.
+fn range_in_list(node: &SyntaxNode) -> TextRange {
+    let comma = next_prev().find_map(|dir| {
+        node.siblings_with_tokens(dir)
+            .skip(1)
+            .find(|it| !it.kind().is_trivia())
+            .filter(|it| it.kind() == T![,])
+            .map(|it| (dir, it))
+    });
+    match comma {
+        Some((dir, comma)) => {
+            let whitespace = comma
+                .siblings_with_tokens(dir)
+                .skip(1)
+                .take_while(|it| it.kind() == SyntaxKind::WHITESPACE)
+                .last();
+            let end = whitespace.unwrap_or(comma);
+            node.text_range().cover(end.text_range())
+        }
+        None => node.text_range(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replaces_generic_with_bounds() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"fn foo<T, $0I: Iterator<Item = u32>>(x: T, iter: I) -> T { x }"#,
+            r#"fn foo<T>(x: T, iter: impl Iterator<Item = u32>) -> T { x }"#,
+        );
+    }
+
+    #[test]
+    fn keeps_where_clause_bounds() {
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<$0F, T>(f: F, t: T)
+where
+    F: Fn() + Clone,
+    T: Copy,
+{
+}
+"#,
+            r#"
+fn foo<T>(f: impl Fn() + Clone, t: T)
+where
+    T: Copy,
+{
+}
+"#,
+        );
+        check_assist(
+            replace_generic_with_impl_trait,
+            r#"fn foo<$0D: Clone>(d: D) where D: Default {}"#,
+            r#"fn foo(d: impl Clone + Default) {}"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_used_elsewhere() {
+        cov_mark::check!(replace_generic_used_several_times);
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"fn foo<$0T: Clone>(x: T) -> T { x }"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_turbofish_calls() {
+        cov_mark::check!(replace_generic_turbofish);
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+fn foo<$0T: Clone>(x: T) {}
+fn main() {
+    foo(1);
+    foo::<u32>(2);
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_generic_with_impl_trait,
+            r#"
+struct S;
+impl S {
+    fn foo<$0T: Clone>(&self, x: T) {}
+}
+fn main() {
+    S.foo::<u32>(2);
+}
+"#,
+        );
+    }
+}
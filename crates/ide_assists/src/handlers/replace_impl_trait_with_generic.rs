@@ -1,9 +1,13 @@
+use rustc_hash::FxHashSet;
 use syntax::{
-    ast::{self, edit_in_place::GenericParamsOwnerEdit, make, AstNode},
+    ast::{self, edit_in_place::GenericParamsOwnerEdit, make, AstNode, NameOwner},
     ted,
 };
 
-use crate::{utils::suggest_name, AssistContext, AssistId, AssistKind, Assists};
+use crate::{
+    assist_context::AssistBuilder, utils::suggest_name, AssistContext, AssistId, AssistKind,
+    Assists,
+};
 
 // Assist: replace_impl_trait_with_generic
 //
@@ -24,34 +28,81 @@ pub(crate) fn replace_impl_trait_with_generic(
     let param = impl_trait_type.syntax().parent().and_then(ast::Param::cast)?;
     let fn_ = param.syntax().ancestors().find_map(ast::Fn::cast)?;
 
-    let type_bound_list = impl_trait_type.type_bound_list()?;
+    impl_trait_type.type_bound_list()?;
+    let all_impl_trait_types = fn_
+        .param_list()?
+        .params()
+        .filter_map(|param| match param.ty()? {
+            ast::Type::ImplTraitType(it) if it.type_bound_list().is_some() => Some(it),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
 
     let target = fn_.syntax().text_range();
     acc.add(
         AssistId("replace_impl_trait_with_generic", AssistKind::RefactorRewrite),
         "Replace impl trait with generic",
         target,
-        |edit| {
-            let impl_trait_type = edit.make_mut(impl_trait_type);
-            let fn_ = edit.make_mut(fn_);
-
-            let type_param_name = suggest_name::for_generic_parameter(&impl_trait_type);
-
-            let type_param = make::type_param(make::name(&type_param_name), Some(type_bound_list))
-                .clone_for_update();
-            let new_ty = make::ty(&type_param_name).clone_for_update();
+        |edit| replace_with_generics(edit, &fn_, vec![impl_trait_type]),
+    );
+    if all_impl_trait_types.len() > 1 {
+        acc.add(
+            AssistId("replace_impl_trait_with_generic", AssistKind::RefactorRewrite),
+            "Replace all impl traits with generics",
+            target,
+            |edit| replace_with_generics(edit, &fn_, all_impl_trait_types),
+        );
+    }
+    Some(())
+}
 
-            ted::replace(impl_trait_type.syntax(), new_ty.syntax());
-            fn_.get_or_create_generic_param_list().add_generic_param(type_param.into())
-        },
-    )
+fn replace_with_generics(
+    edit: &mut AssistBuilder,
+    fn_: &ast::Fn,
+    impl_trait_types: Vec<ast::ImplTraitType>,
+) {
+    let mut taken_names = fn_
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|it| it.generic_params())
+        .filter_map(|param| match param {
+            ast::GenericParam::TypeParam(it) => it.name(),
+            ast::GenericParam::ConstParam(it) => it.name(),
+            ast::GenericParam::LifetimeParam(_) => None,
+        })
+        .map(|it| it.to_string())
+        .collect::<FxHashSet<_>>();
+
+    let impl_trait_types =
+        impl_trait_types.into_iter().map(|it| edit.make_mut(it)).collect::<Vec<_>>();
+    let fn_ = edit.make_mut(fn_.clone());
+    for impl_trait_type in impl_trait_types {
+        let type_bound_list = match impl_trait_type.type_bound_list() {
+            Some(it) => it,
+            None => continue,
+        };
+        let suggested_name = suggest_name::for_generic_parameter(&impl_trait_type);
+        let mut type_param_name = suggested_name.to_string();
+        let mut idx = 1;
+        while !taken_names.insert(type_param_name.clone()) {
+            type_param_name = format!("{}{}", suggested_name, idx);
+            idx += 1;
+        }
+
+        let type_param = make::type_param(make::name(&type_param_name), Some(type_bound_list))
+            .clone_for_update();
+        let new_ty = make::ty(&type_param_name).clone_for_update();
+
+        ted::replace(impl_trait_type.syntax(), new_ty.syntax());
+        fn_.get_or_create_generic_param_list().add_generic_param(type_param.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use crate::tests::check_assist;
+    use crate::tests::{check_assist, check_assist_by_label};
 
     #[test]
     fn replace_impl_trait_with_generic_params() {
@@ -106,12 +157,10 @@ fn foo<B: Bar
 
     #[test]
     fn replace_impl_trait_with_exist_generic_letter() {
-        // FIXME: This is wrong, we should pick a different name if the one we
-        // want is already bound.
         check_assist(
             replace_impl_trait_with_generic,
             r#"fn foo<B>(bar: $0impl Bar) {}"#,
-            r#"fn foo<B, B: Bar>(bar: B) {}"#,
+            r#"fn foo<B, B1: Bar>(bar: B1) {}"#,
         );
     }
 
@@ -144,4 +193,14 @@ fn foo<
             r#"fn foo<F: Foo + Bar>(bar: F) {}"#,
         );
     }
+
+    #[test]
+    fn replace_all_impl_traits() {
+        check_assist_by_label(
+            replace_impl_trait_with_generic,
+            r#"fn foo<T>(a: $0impl Iterator<Item = u32>, b: impl Iterator<Item = u8> + Clone, c: T) {}"#,
+            r#"fn foo<T, I: Iterator<Item = u32>, I1: Iterator<Item = u8> + Clone>(a: I, b: I1, c: T) {}"#,
+            "Replace all impl traits with generics",
+        );
+    }
 }
@@ -0,0 +1,368 @@
+use std::iter;
+
+use hir::Adt;
+use ide_db::helpers::FamousDefs;
+use syntax::{
+    ast::{self, edit::IndentLevel, make, ArgListOwner, Effect},
+    match_ast, ted, AstNode, TextRange, T,
+};
+
+use crate::{utils::returned_exprs, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_unwrap_with_try
+//
+// Replaces `.unwrap()` or `.expect(..)` with `?`. If the function doesn't return a
+// `Result` or an `Option` yet, its return type is wrapped and the returned values are
+// wrapped in `Ok` or `Some`.
+//
+// ```
+// # //- minicore: result
+// fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+// fn double(s: &str) -> u32 {
+//     parse(s).unwrap$0() * 2
+// }
+// ```
+// ->
+// ```
+// fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+// fn double(s: &str) -> Result<u32, ()> {
+//     Ok(parse(s)? * 2)
+// }
+// ```
+pub(crate) fn replace_unwrap_with_try(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let call = ctx.find_node_at_offset::<ast::MethodCallExpr>()?;
+    let name_ref = call.name_ref()?;
+    let arg_count = call.arg_list()?.args().count();
+    match name_ref.text().as_str() {
+        "unwrap" if arg_count == 0 => (),
+        "expect" if arg_count == 1 => (),
+        _ => return None,
+    }
+    let receiver = call.receiver()?;
+    if !name_ref.syntax().text_range().contains_range(ctx.frange.range) {
+        return None;
+    }
+
+    // `?` returns from the innermost function, closure or `async` block.
+    let func = call.syntax().ancestors().find_map(|node| {
+        match_ast! {
+            match node {
+                ast::Fn(it) => Some(Some(it)),
+                ast::ClosureExpr(_) => Some(None),
+                ast::EffectExpr(it) => match it.effect() {
+                    Effect::Async(_) | Effect::Try(_) => Some(None),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+    })??;
+    let body = func.body()?;
+
+    let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(call.syntax()).krate());
+    let option = famous_defs.core_option_Option()?;
+    let result = famous_defs.core_result_Result()?;
+    let receiver_ty = ctx.sema.type_of_expr(&receiver)?;
+    let (wrapper, container) = match receiver_ty.as_adt()? {
+        Adt::Enum(it) if it == option => ("Some", option),
+        Adt::Enum(it) if it == result => ("Ok", result),
+        _ => return None,
+    };
+    let ret_ty = ctx.sema.to_def(&func)?.ret_type(ctx.db());
+    match ret_ty.as_adt() {
+        Some(Adt::Enum(it)) if it == container => {
+            if container == result && !error_converts(ctx, &famous_defs, &receiver_ty, &ret_ty) {
+                cov_mark::hit!(replace_unwrap_with_try_error_mismatch);
+                return None;
+            }
+            return acc.add(
+                AssistId("replace_unwrap_with_try", AssistKind::RefactorRewrite),
+                format!("Replace `{}` with `?`", name_ref),
+                call.syntax().text_range(),
+                |builder| {
+                    let range = TextRange::new(
+                        receiver.syntax().text_range().end(),
+                        call.syntax().text_range().end(),
+                    );
+                    builder.replace(range, "?")
+                },
+            );
+        }
+        Some(Adt::Enum(it)) if it == option || it == result => return None,
+        _ => (),
+    }
+
+    // The new return type keeps the error type of the unwrapped `Result`.
+    let module = ctx.sema.scope(call.syntax()).module()?;
+    let ok_ty = if ret_ty.is_unit() {
+        "()".to_string()
+    } else {
+        ret_ty.display_source_code(ctx.db(), module.into()).ok()?
+    };
+    let new_ret_ty = if container == result {
+        let err_ty = receiver_ty.type_arguments().nth(1)?;
+        let err_ty = err_ty.display_source_code(ctx.db(), module.into()).ok()?;
+        format!("Result<{}, {}>", ok_ty, err_ty)
+    } else {
+        format!("Option<{}>", ok_ty)
+    };
+    let is_unit = ret_ty.is_unit();
+    let mut returned = returned_exprs(&ast::Expr::BlockExpr(body.clone()));
+    returned.dedup();
+    // `walk` skips closures and inner items, whose returns stay as they are.
+    let mut empty_returns = Vec::new();
+    ast::Expr::BlockExpr(body.clone()).walk(&mut |expr| match expr {
+        ast::Expr::ReturnExpr(it) if it.expr().is_none() => empty_returns.push(it),
+        _ => (),
+    });
+
+    acc.add(
+        AssistId("replace_unwrap_with_try", AssistKind::RefactorRewrite),
+        format!("Replace `{}` with `?` and return `{}`", name_ref, container.name(ctx.db())),
+        call.syntax().text_range(),
+        |builder| {
+            let call = builder.make_mut(call);
+            let func = builder.make_mut(func);
+            let body = builder.make_mut(body);
+            let returned = returned.into_iter().map(|it| builder.make_mut(it)).collect::<Vec<_>>();
+            let empty_returns =
+                empty_returns.into_iter().map(|it| builder.make_mut(it)).collect::<Vec<_>>();
+            let wrap = |expr: ast::Expr| {
+                make::expr_call(
+                    make::expr_path(make::ext::ident_path(wrapper)),
+                    make::arg_list(iter::once(expr)),
+                )
+                .clone_for_update()
+            };
+            let wrapped_unit = || wrap(make::expr_unit());
+
+            let receiver = match call.receiver() {
+                Some(it) => it,
+                None => return,
+            };
+            let try_expr = make::expr_try(receiver).clone_for_update();
+            ted::replace(call.syntax(), try_expr.syntax());
+
+            if is_unit {
+                for ret in empty_returns {
+                    ted::replace(
+                        ret.syntax(),
+                        make::expr_return(Some(wrapped_unit())).clone_for_update().syntax(),
+                    );
+                }
+                let indent = IndentLevel::from_node(func.syntax()) + 1;
+                let anchor = match body.tail_expr() {
+                    Some(tail) => {
+                        let tail = match &tail {
+                            ast::Expr::MethodCallExpr(it) if *it == call => try_expr.clone(),
+                            _ => tail,
+                        };
+                        ted::insert(ted::Position::after(tail.syntax()), make::token(T![;]));
+                        tail.syntax().next_sibling_or_token()
+                    }
+                    None => body
+                        .statements()
+                        .last()
+                        .map(|it| it.syntax().clone().into())
+                        .or_else(|| body.l_curly_token().map(Into::into)),
+                };
+                if let Some(anchor) = anchor {
+                    ted::insert_all(
+                        ted::Position::after(anchor),
+                        vec![
+                            make::tokens::whitespace(&format!("\n{}", indent)).into(),
+                            wrapped_unit().syntax().clone().into(),
+                        ],
+                    );
+                }
+            } else {
+                for expr in returned {
+                    let expr = match &expr {
+                        ast::Expr::MethodCallExpr(it) if *it == call => try_expr.clone(),
+                        _ => expr,
+                    };
+                    ted::replace(expr.syntax(), wrap(expr.clone()).syntax());
+                }
+            }
+
+            let new_ret_ty = make::ty(&new_ret_ty).clone_for_update();
+            match func.ret_type().and_then(|it| it.ty()) {
+                Some(ty) => ted::replace(ty.syntax(), new_ret_ty.syntax()),
+                None => {
+                    if let Some(param_list) = func.param_list() {
+                        let ret_type = make::ret_type(new_ret_ty).clone_for_update();
+                        ted::insert_all(
+                            ted::Position::after(param_list.syntax()),
+                            vec![
+                                make::tokens::single_space().into(),
+                                ret_type.syntax().clone().into(),
+                            ],
+                        );
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Checks if `?` can convert the error of the `receiver` result into the one of `ret`.
+fn error_converts(
+    ctx: &AssistContext,
+    famous_defs: &FamousDefs,
+    receiver: &hir::Type,
+    ret: &hir::Type,
+) -> bool {
+    let (from, into) = match (receiver.type_arguments().nth(1), ret.type_arguments().nth(1)) {
+        (Some(from), Some(into)) => (from, into),
+        _ => return false,
+    };
+    if from.could_unify_with(ctx.db(), &into) {
+        return true;
+    }
+    famous_defs
+        .core_convert_From()
+        .map_or(false, |trait_| into.impls_trait(ctx.db(), trait_, &[from]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replaces_unwrap_in_fn_returning_result() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn f(s: &str) -> Result<u32, ()> {
+    let x = parse(s)
+        .expect$0("not a number");
+    Ok(x)
+}
+"#,
+            r#"
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn f(s: &str) -> Result<u32, ()> {
+    let x = parse(s)?;
+    Ok(x)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_returned_values() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result
+struct ParseError;
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn f(s: &str) -> u32 {
+    if s.is_empty() {
+        return 0;
+    }
+    let x = parse(s).unwrap$0();
+    x + 1
+}
+"#,
+            r#"
+struct ParseError;
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn f(s: &str) -> Result<u32, ParseError> {
+    if s.is_empty() {
+        return Ok(0);
+    }
+    let x = parse(s)?;
+    Ok(x + 1)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_return_type_to_unit_fn() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+fn first(v: &[u32]) -> Option<u32> { None }
+fn consume(x: u32) {}
+fn f(v: &[u32]) {
+    if v.is_empty() {
+        return;
+    }
+    let log = || {
+        return;
+    };
+    fn inner() {
+        return;
+    }
+    consume(first(v).unwrap$0())
+}
+"#,
+            r#"
+fn first(v: &[u32]) -> Option<u32> { None }
+fn consume(x: u32) {}
+fn f(v: &[u32]) -> Option<()> {
+    if v.is_empty() {
+        return Some(());
+    }
+    let log = || {
+        return;
+    };
+    fn inner() {
+        return;
+    }
+    consume(first(v)?);
+    Some(())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_unconvertible_errors() {
+        cov_mark::check!(replace_unwrap_with_try_error_mismatch);
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result, from
+struct ParseError;
+struct OtherErr;
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn f(s: &str) -> Result<u32, OtherErr> {
+    Ok(parse(s).unwrap$0())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_other_containers() {
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option, result
+fn first() -> Option<u32> { None }
+fn f() -> Result<u32, ()> {
+    Ok(first().unwrap$0())
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+fn first() -> Option<u32> { None }
+fn f() -> Option<u32> {
+    let g = || first().unwrap$0();
+    None
+}
+"#,
+        );
+    }
+}
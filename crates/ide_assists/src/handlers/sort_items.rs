@@ -0,0 +1,217 @@
+use itertools::Itertools;
+use syntax::{
+    ast::{self, AttrsOwner, NameOwner},
+    ted, AstNode, SyntaxKind,
+};
+
+use crate::{utils::assoc_item_name, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: sort_items
+//
+// Sorts the items of a trait or an impl, the variants of an enum or the fields of a struct
+// alphabetically. The comments and the attributes of the items move along with them. The
+// variants of enums deriving `PartialOrd` or with a `#[repr]` aren't sorted, as their order
+// matters.
+//
+// ```
+// enum $0Animal {
+//     Dog,
+//     /// Says "meow".
+//     Cat,
+//     Bird,
+// }
+// ```
+// ->
+// ```
+// enum Animal {
+//     Bird,
+//     /// Says "meow".
+//     Cat,
+//     Dog,
+// }
+// ```
+pub(crate) fn sort_items(acc: &mut Assists, ctx: &AssistContext) -> Option<()> {
+    let list = ctx.covering_element().ancestors().find_map(|it| match it.kind() {
+        SyntaxKind::BLOCK_EXPR => Some(None),
+        SyntaxKind::ASSOC_ITEM_LIST | SyntaxKind::VARIANT_LIST | SyntaxKind::RECORD_FIELD_LIST => {
+            Some(Some(it))
+        }
+        SyntaxKind::TRAIT => {
+            Some(ast::Trait::cast(it)?.assoc_item_list().map(|it| it.syntax().clone()))
+        }
+        SyntaxKind::IMPL => {
+            Some(ast::Impl::cast(it)?.assoc_item_list().map(|it| it.syntax().clone()))
+        }
+        SyntaxKind::ENUM => Some(ast::Enum::cast(it)?.variant_list().map(|it| it.syntax().clone())),
+        SyntaxKind::STRUCT => match ast::Struct::cast(it)?.field_list()? {
+            ast::FieldList::RecordFieldList(it) => Some(Some(it.syntax().clone())),
+            ast::FieldList::TupleFieldList(_) => Some(None),
+        },
+        _ => None,
+    })??;
+
+    if let Some(list) = ast::AssocItemList::cast(list.clone()) {
+        add_assist(acc, "Sort items alphabetically", list.assoc_items(), assoc_item_name)
+    } else if let Some(list) = ast::VariantList::cast(list.clone()) {
+        let enum_ = list.syntax().parent().and_then(ast::Enum::cast)?;
+        if variant_order_matters(&enum_) {
+            cov_mark::hit!(sort_items_ordered_variants);
+            return None;
+        }
+        add_assist(acc, "Sort variants alphabetically", list.variants(), |it| {
+            it.name().map(|it| it.to_string())
+        })
+    } else {
+        let list = ast::RecordFieldList::cast(list)?;
+        add_assist(acc, "Sort fields alphabetically", list.fields(), |it| {
+            it.name().map(|it| it.to_string())
+        })
+    }
+}
+
+fn add_assist<N: AstNode>(
+    acc: &mut Assists,
+    label: &str,
+    items: impl Iterator<Item = N>,
+    name: impl Fn(&N) -> Option<String>,
+) -> Option<()> {
+    let items = items.collect::<Vec<_>>();
+    let names = items.iter().map(|it| name(it)).collect::<Option<Vec<_>>>()?;
+    let sorted = items
+        .iter()
+        .zip(&names)
+        .sorted_by(|(_, a), (_, b)| a.cmp(b))
+        .map(|(it, _)| it.syntax().clone())
+        .collect::<Vec<_>>();
+    if items.iter().zip(&sorted).all(|(it, sorted)| it.syntax() == sorted) {
+        cov_mark::hit!(sort_items_already_sorted);
+        return None;
+    }
+
+    let target = items.iter().map(|it| it.syntax().text_range()).fold1(|a, b| a.cover(b))?;
+    acc.add(AssistId("sort_items", AssistKind::RefactorRewrite), label, target, |builder| {
+        let items =
+            items.iter().map(|it| builder.make_syntax_mut(it.syntax().clone())).collect::<Vec<_>>();
+        for (item, sorted) in items.into_iter().zip(sorted) {
+            ted::replace(item, sorted.clone_for_update());
+        }
+    })
+}
+
+/// Checks whether the order of the variants of `enum_` matters: it defines
+/// the comparisons derived by `PartialOrd` and `Ord`, and the discriminants
+/// a `#[repr]` enum is laid out with.
+fn variant_order_matters(enum_: &ast::Enum) -> bool {
+    enum_.attrs().filter_map(|it| it.as_simple_call()).any(|(name, args)| match name.as_str() {
+        "repr" => true,
+        "derive" => args
+            .syntax()
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .any(|it| matches!(it.text(), "PartialOrd" | "Ord")),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn sorts_trait_items_with_comments_and_attributes() {
+        check_assist(
+            sort_items,
+            r#"
+trait $0Storage {
+    // Writes a value.
+    fn write(&mut self, value: u32);
+    type Error;
+    #[doc(hidden)]
+    const CAPACITY: usize;
+    fn read(&self) -> u32;
+}
+"#,
+            r#"
+trait Storage {
+    #[doc(hidden)]
+    const CAPACITY: usize;
+    type Error;
+    fn read(&self) -> u32;
+    // Writes a value.
+    fn write(&mut self, value: u32);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn sorts_struct_and_variant_fields() {
+        check_assist(
+            sort_items,
+            r#"
+struct Point {
+    y: f64,$0
+    x: f64,
+}
+"#,
+            r#"
+struct Point {
+    x: f64,
+    y: f64,
+}
+"#,
+        );
+        check_assist(
+            sort_items,
+            r#"
+enum Shape {
+    Circle { radius: f64, $0center: (f64, f64) },
+    Square,
+}
+"#,
+            r#"
+enum Shape {
+    Circle { center: (f64, f64), radius: f64 },
+    Square,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_sorted_or_in_bodies() {
+        cov_mark::check!(sort_items_already_sorted);
+        check_assist_not_applicable(sort_items, r#"enum $0E { A, B, C }"#);
+        check_assist_not_applicable(
+            sort_items,
+            r#"
+impl S {
+    fn b() { $0 }
+    fn a() {}
+}
+"#,
+        );
+        check_assist_not_applicable(sort_items, r#"struct $0S(u32, bool);"#);
+    }
+
+    #[test]
+    fn not_applicable_when_variant_order_matters() {
+        cov_mark::check!(sort_items_ordered_variants);
+        check_assist_not_applicable(
+            sort_items,
+            r#"
+#[derive(Debug, PartialOrd, PartialEq)]
+enum $0Level { Warn, Error }
+"#,
+        );
+        check_assist_not_applicable(
+            sort_items,
+            r#"
+#[repr(u8)]
+enum $0Flag { On, Off }
+"#,
+        );
+    }
+}
@@ -49,6 +49,8 @@ mod handlers {
     pub(crate) type Handler = fn(&mut Assists, &AssistContext) -> Option<()>;
 
     mod add_explicit_type;
+    mod add_explicit_return_type;
+    mod add_function_attribute;
     mod add_lifetime_to_type;
     mod add_missing_impl_members;
     mod add_turbo_fish;
@@ -58,12 +60,17 @@ mod handlers {
     mod convert_integer_literal;
     mod convert_comment_block;
     mod convert_iter_for_each_to_for;
+    mod convert_for_loop_to_iterator_chain;
     mod convert_into_to_from;
+    mod convert_named_struct_to_tuple_struct;
     mod convert_tuple_struct_to_named_struct;
     mod early_return;
+    mod expand_derive;
     mod expand_glob_import;
     mod extract_function;
+    mod extract_module;
     mod extract_struct_from_enum_variant;
+    mod extract_struct_from_function_signature;
     mod extract_type_alias;
     mod extract_variable;
     mod fill_match_arms;
@@ -71,31 +78,40 @@ mod handlers {
     mod flip_binexpr;
     mod flip_comma;
     mod flip_trait_bound;
+    mod generate_builder;
     mod generate_default_from_enum_variant;
     mod generate_default_from_new;
     mod generate_is_empty_from_len;
+    mod generate_delegate_trait;
     mod generate_deref;
     mod generate_derive;
     mod generate_enum_is_method;
     mod generate_enum_projection_method;
     mod generate_from_impl_for_enum;
+    mod generate_from_impl_for_similar_struct;
     mod generate_function;
     mod generate_getter;
     mod generate_impl;
     mod generate_new;
     mod generate_setter;
+    mod generate_test;
     mod infer_function_return_type;
     mod inline_call;
+    mod make_function_async;
     mod inline_local_variable;
     mod introduce_named_lifetime;
+    mod introduce_parameter;
     mod invert_if;
     mod merge_imports;
     mod merge_match_arms;
     mod move_bounds;
+    mod move_fn_out_of_impl;
     mod move_guard;
     mod move_module_to_file;
+    mod organize_imports;
     mod pull_assignment_up;
     mod qualify_path;
+    mod qualify_paths_in_selection;
     mod raw_string;
     mod remove_dbg;
     mod remove_mut;
@@ -104,11 +120,14 @@ mod handlers {
     mod reorder_impl;
     mod replace_derive_with_manual_impl;
     mod replace_for_loop_with_for_each;
+    mod replace_generic_with_impl_trait;
     mod replace_if_let_with_match;
     mod replace_impl_trait_with_generic;
     mod replace_let_with_if_let;
     mod replace_qualified_name_with_use;
     mod replace_string_with_char;
+    mod replace_unwrap_with_try;
+    mod sort_items;
     mod split_import;
     mod toggle_ignore;
     mod unmerge_use;
@@ -119,6 +138,8 @@ mod handlers {
         &[
             // These are alphabetic for the foolish consistency
             add_explicit_type::add_explicit_type,
+            add_explicit_return_type::add_explicit_return_type,
+            add_function_attribute::add_function_attribute,
             add_lifetime_to_type::add_lifetime_to_type,
             add_turbo_fish::add_turbo_fish,
             apply_demorgan::apply_demorgan,
@@ -127,45 +148,62 @@ mod handlers {
             convert_integer_literal::convert_integer_literal,
             convert_comment_block::convert_comment_block,
             convert_iter_for_each_to_for::convert_iter_for_each_to_for,
+            convert_for_loop_to_iterator_chain::convert_for_loop_to_iterator_chain,
+            convert_for_loop_to_iterator_chain::convert_iterator_chain_to_for_loop,
             convert_into_to_from::convert_into_to_from,
+            convert_named_struct_to_tuple_struct::convert_named_struct_to_tuple_struct,
             convert_tuple_struct_to_named_struct::convert_tuple_struct_to_named_struct,
             early_return::convert_to_guarded_return,
+            expand_derive::expand_derive,
             expand_glob_import::expand_glob_import,
+            extract_module::extract_module,
             extract_struct_from_enum_variant::extract_struct_from_enum_variant,
+            extract_struct_from_function_signature::extract_struct_from_function_signature,
             extract_type_alias::extract_type_alias,
             fill_match_arms::fill_match_arms,
             fix_visibility::fix_visibility,
             flip_binexpr::flip_binexpr,
             flip_comma::flip_comma,
             flip_trait_bound::flip_trait_bound,
+            generate_builder::generate_builder,
             generate_default_from_enum_variant::generate_default_from_enum_variant,
             generate_default_from_new::generate_default_from_new,
             generate_is_empty_from_len::generate_is_empty_from_len,
+            generate_delegate_trait::generate_delegate_trait,
             generate_deref::generate_deref,
             generate_derive::generate_derive,
             generate_enum_is_method::generate_enum_is_method,
             generate_enum_projection_method::generate_enum_as_method,
             generate_enum_projection_method::generate_enum_try_into_method,
             generate_from_impl_for_enum::generate_from_impl_for_enum,
+            generate_from_impl_for_similar_struct::generate_from_impl_for_similar_struct,
             generate_function::generate_function,
             generate_getter::generate_getter,
             generate_getter::generate_getter_mut,
             generate_impl::generate_impl,
             generate_new::generate_new,
             generate_setter::generate_setter,
+            generate_test::generate_test,
             infer_function_return_type::infer_function_return_type,
             inline_call::inline_call,
+            make_function_async::make_function_async,
             inline_local_variable::inline_local_variable,
             introduce_named_lifetime::introduce_named_lifetime,
+            introduce_parameter::introduce_parameter,
             invert_if::invert_if,
             merge_imports::merge_imports,
             merge_match_arms::merge_match_arms,
             move_bounds::move_bounds_to_where_clause,
+            move_fn_out_of_impl::move_fn_out_of_impl,
+            move_fn_out_of_impl::move_fn_into_impl,
             move_guard::move_arm_cond_to_match_guard,
             move_guard::move_guard_to_arm_body,
             move_module_to_file::move_module_to_file,
+            organize_imports::organize_imports,
             pull_assignment_up::pull_assignment_up,
             qualify_path::qualify_path,
+            qualify_paths_in_selection::qualify_paths_in_selection,
+            qualify_paths_in_selection::unqualify_paths_in_selection,
             raw_string::add_hash,
             raw_string::make_usual_string,
             raw_string::remove_hash,
@@ -176,6 +214,7 @@ mod handlers {
             reorder_impl::reorder_impl,
             replace_derive_with_manual_impl::replace_derive_with_manual_impl,
             replace_for_loop_with_for_each::replace_for_loop_with_for_each,
+            replace_generic_with_impl_trait::replace_generic_with_impl_trait,
             replace_if_let_with_match::replace_if_let_with_match,
             replace_if_let_with_match::replace_match_with_if_let,
             replace_impl_trait_with_generic::replace_impl_trait_with_generic,
@@ -194,6 +233,8 @@ mod handlers {
             add_missing_impl_members::add_missing_default_members,
             //
             replace_string_with_char::replace_string_with_char,
+            replace_unwrap_with_try::replace_unwrap_with_try,
+            sort_items::sort_items,
             raw_string::make_raw_string,
             //
             extract_variable::extract_variable,
@@ -2,6 +2,23 @@
 
 use super::check_doc_test;
 
+#[test]
+fn doctest_add_explicit_return_type() {
+    check_doc_test(
+        "add_explicit_return_type",
+        r#####"
+fn main() {
+    let x = |$0a: i32, b: i32| a + b;
+}
+"#####,
+        r#####"
+fn main() {
+    let x = |a: i32, b: i32| -> i32 { a + b };
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_add_explicit_type() {
     check_doc_test(
@@ -19,6 +36,20 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_add_function_attribute() {
+    check_doc_test(
+        "add_function_attribute",
+        r#####"
+fn $0area(w: u32, h: u32) -> u32 { w * h }
+"#####,
+        r#####"
+#[must_use]
+fn area(w: u32, h: u32) -> u32 { w * h }
+"#####,
+    )
+}
+
 #[test]
 fn doctest_add_hash() {
     check_doc_test(
@@ -191,6 +222,27 @@ pub(crate) fn frobnicate() {}
     )
 }
 
+#[test]
+fn doctest_convert_for_loop_to_iterator_chain() {
+    check_doc_test(
+        "convert_for_loop_to_iterator_chain",
+        r#####"
+//- minicore: iterators
+fn main() {
+    let mut squares = Vec::new();
+    for$0 x in 0..10 {
+        squares.push(x * x);
+    }
+}
+"#####,
+        r#####"
+fn main() {
+    let squares: Vec<_> = (0..10).map(|x| x * x).collect();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_integer_literal() {
     check_doc_test(
@@ -258,6 +310,67 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_iterator_chain_to_for_loop() {
+    check_doc_test(
+        "convert_iterator_chain_to_for_loop",
+        r#####"
+fn main() {
+    let sum = $0[1, 2, 3].iter().fold(0, |acc, x| acc + x);
+}
+"#####,
+        r#####"
+fn main() {
+    let mut sum = 0;
+    for x in [1, 2, 3].iter() {
+        sum += x;
+    }
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_convert_named_struct_to_tuple_struct() {
+    check_doc_test(
+        "convert_named_struct_to_tuple_struct",
+        r#####"
+struct Point$0 { x: f32, y: f32 }
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Point { x, y }
+    }
+
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+}
+"#####,
+        r#####"
+struct Point(f32, f32);
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Point(x, y)
+    }
+
+    pub fn x(&self) -> f32 {
+        self.0
+    }
+
+    pub fn y(&self) -> f32 {
+        self.1
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_to_guarded_return() {
     check_doc_test(
@@ -323,6 +436,24 @@ impl Point {
     )
 }
 
+#[test]
+fn doctest_expand_derive() {
+    check_doc_test(
+        "expand_derive",
+        r#####"
+//- minicore: derive, clone, copy
+#[derive(Clone, Co$0py)]
+struct Point;
+"#####,
+        r#####"
+#[derive(Clone)]
+struct Point;
+
+impl core::marker::Copy for Point {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_expand_glob_import() {
     check_doc_test(
@@ -377,6 +508,35 @@ fn $0fun_name(n: i32) {
     )
 }
 
+#[test]
+fn doctest_extract_module() {
+    check_doc_test(
+        "extract_module",
+        r#####"
+$0fn foo(name: i32) -> i32 {
+    name + 1
+}$0
+
+fn bar(name: i32) -> i32 {
+    foo(name) + 1
+}
+"#####,
+        r#####"
+use modname::foo;
+
+mod modname {
+    pub(crate) fn foo(name: i32) -> i32 {
+        name + 1
+    }
+}
+
+fn bar(name: i32) -> i32 {
+    foo(name) + 1
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_struct_from_enum_variant() {
     check_doc_test(
@@ -392,6 +552,36 @@ enum A { One(One) }
     )
 }
 
+#[test]
+fn doctest_extract_struct_from_function_signature() {
+    check_doc_test(
+        "extract_struct_from_function_signature",
+        r#####"
+fn draw(canvas: &mut Vec<u8>, $0x: u32, y: u32$0) {
+    canvas.push((x + y) as u8);
+}
+
+fn main() {
+    draw(&mut Vec::new(), 1, 2);
+}
+"#####,
+        r#####"
+struct DrawParams {
+    x: u32,
+    y: u32,
+}
+
+fn draw(canvas: &mut Vec<u8>, params: DrawParams) {
+    canvas.push((params.x + params.y) as u8);
+}
+
+fn main() {
+    draw(&mut Vec::new(), DrawParams { x: 1, y: 2 });
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_extract_type_alias() {
     check_doc_test(
@@ -525,6 +715,54 @@ fn foo<T: Copy + Clone>() { }
     )
 }
 
+#[test]
+fn doctest_generate_builder() {
+    check_doc_test(
+        "generate_builder",
+        r#####"
+//- minicore: option
+struct Person$0 {
+    name: String,
+    age: Option<u32>,
+}
+"#####,
+        r#####"
+struct Person {
+    name: String,
+    age: Option<u32>,
+}
+
+struct PersonBuilder {
+    name: Option<String>,
+    age: Option<u32>,
+}
+
+impl PersonBuilder {
+    fn new() -> Self {
+        Self { name: None, age: None }
+    }
+
+    fn name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    fn age(mut self, age: u32) -> Self {
+        self.age = Some(age);
+        self
+    }
+
+    fn build(self) -> Person {
+        Person {
+            name: self.name.expect("`name` is not set"),
+            age: self.age,
+        }
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_default_from_enum_variant() {
     check_doc_test(
@@ -583,6 +821,49 @@ impl Default for Example {
     )
 }
 
+#[test]
+fn doctest_generate_delegate_trait() {
+    check_doc_test(
+        "generate_delegate_trait",
+        r#####"
+trait Greet {
+    type Output;
+    fn greet(&self, name: &str) -> Self::Output;
+}
+struct Inner;
+impl Greet for Inner {
+    type Output = String;
+    fn greet(&self, name: &str) -> String { name.to_string() }
+}
+struct Wrapper {
+    $0inner: Inner,
+}
+"#####,
+        r#####"
+trait Greet {
+    type Output;
+    fn greet(&self, name: &str) -> Self::Output;
+}
+struct Inner;
+impl Greet for Inner {
+    type Output = String;
+    fn greet(&self, name: &str) -> String { name.to_string() }
+}
+struct Wrapper {
+    inner: Inner,
+}
+
+impl Greet for Wrapper {
+    type Output = <Inner as Greet>::Output;
+
+    fn greet(&self, name: &str) -> Self::Output {
+        Greet::greet(&self.inner, name)
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_deref() {
     check_doc_test(
@@ -735,6 +1016,31 @@ impl From<u32> for A {
     )
 }
 
+#[test]
+fn doctest_generate_from_impl_for_similar_struct() {
+    check_doc_test(
+        "generate_from_impl_for_similar_struct",
+        r#####"
+//- minicore: from
+struct Point3 { x: i32, y: i32, z: i32 }
+struct $0Point2 { x: i32, y: i32 }
+"#####,
+        r#####"
+struct Point3 { x: i32, y: i32, z: i32 }
+struct Point2 { x: i32, y: i32 }
+
+impl From<Point3> for Point2 {
+    fn from(value: Point3) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_function() {
     check_doc_test(
@@ -905,6 +1211,30 @@ impl Person {
     )
 }
 
+#[test]
+fn doctest_generate_test() {
+    check_doc_test(
+        "generate_test",
+        r#####"
+fn add$0(a: u32, b: u32) -> u32 { a + b }
+"#####,
+        r#####"
+fn add(a: u32, b: u32) -> u32 { a + b }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let result = add(${1:todo!()}, ${2:todo!()});
+        assert_eq!(result, ${0:todo!()});
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_infer_function_return_type() {
     check_doc_test(
@@ -982,6 +1312,31 @@ impl<'a> Cursor<'a> {
     )
 }
 
+#[test]
+fn doctest_introduce_parameter() {
+    check_doc_test(
+        "introduce_parameter",
+        r#####"
+fn area() -> u32 {
+    $04 * 4$0 + 1
+}
+
+fn main() {
+    area();
+}
+"#####,
+        r#####"
+fn area(var_name: u32) -> u32 {
+    var_name + 1
+}
+
+fn main() {
+    area(4 * 4);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_invert_if() {
     check_doc_test(
@@ -999,6 +1354,27 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_make_function_async() {
+    check_doc_test(
+        "make_function_async",
+        r#####"
+fn fetch$0() -> u32 { 92 }
+
+fn main() {
+    let x = fetch();
+}
+"#####,
+        r#####"
+async fn fetch() -> u32 { 92 }
+
+fn main() {
+    let x = fetch();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_make_raw_string() {
     check_doc_test(
@@ -1117,6 +1493,65 @@ fn apply<T, U, F>(f: F, x: T) -> U where F: FnOnce(T) -> U {
     )
 }
 
+#[test]
+fn doctest_move_fn_into_impl() {
+    check_doc_test(
+        "move_fn_into_impl",
+        r#####"
+struct Counter(u32);
+impl Counter {}
+fn $0bump(counter: &mut Counter) {
+    counter.0 += 1;
+}
+fn main() {
+    let mut c = Counter(0);
+    bump(&mut c);
+}
+"#####,
+        r#####"
+struct Counter(u32);
+impl Counter {
+    fn bump(&mut self) {
+        self.0 += 1;
+    }
+}
+fn main() {
+    let mut c = Counter(0);
+    c.bump();
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_move_fn_out_of_impl() {
+    check_doc_test(
+        "move_fn_out_of_impl",
+        r#####"
+struct Counter(u32);
+impl Counter {
+    fn $0bump(&mut self) {
+        self.0 += 1;
+    }
+}
+fn main() {
+    let mut c = Counter(0);
+    c.bump();
+}
+"#####,
+        r#####"
+struct Counter(u32);
+fn bump(this: &mut Counter) {
+    this.0 += 1;
+}
+fn main() {
+    let mut c = Counter(0);
+    bump(&mut c);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_move_guard_to_arm_body() {
     check_doc_test(
@@ -1161,6 +1596,27 @@ mod foo;
     )
 }
 
+#[test]
+fn doctest_organize_imports() {
+    check_doc_test(
+        "organize_imports",
+        r#####"
+mod fmt { pub struct Display; pub struct Debug; pub struct Formatter; }
+use fmt::Formatter;
+$0use fmt::Display;
+use fmt::Debug;
+
+fn show(f: Formatter, d: Display) {}
+"#####,
+        r#####"
+mod fmt { pub struct Display; pub struct Debug; pub struct Formatter; }
+use fmt::{Display, Formatter};
+
+fn show(f: Formatter, d: Display) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_pull_assignment_up() {
     check_doc_test(
@@ -1209,6 +1665,25 @@ pub mod std { pub mod collections { pub struct HashMap { } } }
     )
 }
 
+#[test]
+fn doctest_qualify_paths_in_selection() {
+    check_doc_test(
+        "qualify_paths_in_selection",
+        r#####"
+mod fmt { pub struct Formatter; }
+use fmt::Formatter;
+
+$0fn show(f: &mut Formatter) {}$0
+"#####,
+        r#####"
+mod fmt { pub struct Formatter; }
+use fmt::Formatter;
+
+fn show(f: &mut crate::fmt::Formatter) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_remove_dbg() {
     check_doc_test(
@@ -1377,6 +1852,19 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_replace_generic_with_impl_trait() {
+    check_doc_test(
+        "replace_generic_with_impl_trait",
+        r#####"
+fn foo<$0I: Iterator<Item = u32>>(iter: I) where I: Clone {}
+"#####,
+        r#####"
+fn foo(iter: impl Iterator<Item = u32> + Clone) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_if_let_with_match() {
     check_doc_test(
@@ -1506,6 +1994,49 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_replace_unwrap_with_try() {
+    check_doc_test(
+        "replace_unwrap_with_try",
+        r#####"
+//- minicore: result
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> u32 {
+    parse(s).unwrap$0() * 2
+}
+"#####,
+        r#####"
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Result<u32, ()> {
+    Ok(parse(s)? * 2)
+}
+"#####,
+    )
+}
+
+#[test]
+fn doctest_sort_items() {
+    check_doc_test(
+        "sort_items",
+        r#####"
+enum $0Animal {
+    Dog,
+    /// Says "meow".
+    Cat,
+    Bird,
+}
+"#####,
+        r#####"
+enum Animal {
+    Bird,
+    /// Says "meow".
+    Cat,
+    Dog,
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_split_import() {
     check_doc_test(
@@ -1553,6 +2084,25 @@ use std::fmt::Display;
     )
 }
 
+#[test]
+fn doctest_unqualify_paths_in_selection() {
+    check_doc_test(
+        "unqualify_paths_in_selection",
+        r#####"
+mod fmt { pub struct Formatter; }
+
+$0fn show(f: &mut fmt::Formatter) {}$0
+"#####,
+        r#####"
+use fmt::Formatter;
+
+mod fmt { pub struct Formatter; }
+
+fn show(f: &mut Formatter) {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_unwrap_block() {
     check_doc_test(
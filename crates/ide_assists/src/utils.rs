@@ -6,7 +6,7 @@ use std::ops;
 
 use hir::{Adt, HasSource, Semantics};
 use ide_db::{
-    helpers::{FamousDefs, SnippetCap},
+    helpers::{for_each_tail_expr, FamousDefs, SnippetCap},
     path_transform::PathTransform,
     RootDatabase,
 };
@@ -493,3 +493,165 @@ pub(crate) fn add_method_to_adt(
 
     builder.insert(start_offset, buf);
 }
+
+/// Removes `derive_name` from the arguments of the `derive` attribute `attr`,
+/// or the whole attribute if it's the only one.
+pub(crate) fn remove_derive(
+    builder: &mut AssistBuilder,
+    input: &ast::TokenTree,
+    derive_name: &str,
+    attr: &ast::Attr,
+) {
+    let new_attr_input = input
+        .syntax()
+        .descendants_with_tokens()
+        .filter(|t| t.kind() == IDENT)
+        .filter_map(|t| t.into_token().map(|t| t.text().to_string()))
+        .filter(|t| t != derive_name)
+        .collect::<Vec<_>>();
+    let has_more_derives = !new_attr_input.is_empty();
+
+    if has_more_derives {
+        let new_attr_input = format!("({})", new_attr_input.iter().format(", "));
+        builder.replace(input.syntax().text_range(), new_attr_input);
+    } else {
+        let attr_range = attr.syntax().text_range();
+        builder.delete(attr_range);
+
+        if let Some(line_break_range) = attr
+            .syntax()
+            .next_sibling_or_token()
+            .filter(|t| t.kind() == WHITESPACE)
+            .map(|t| t.text_range())
+        {
+            builder.delete(line_break_range);
+        }
+    }
+}
+
+/// Returns the text of an iterator over `iterable`, the expression a `for` loop
+/// iterates over.
+pub(crate) fn iterator_of(sema: &Semantics<RootDatabase>, iterable: &ast::Expr) -> String {
+    if let Some((expr_behind_ref, method)) = is_ref_and_impls_iter_method(sema, iterable) {
+        // We have either "for x in &col" and col implements a method called iter
+        //             or "for x in &mut col" and col implements a method called iter_mut
+        format!("{}.{}()", expr_behind_ref, method)
+    } else if impls_core_iter(sema, iterable) {
+        match iterable {
+            ast::Expr::RangeExpr(_) | ast::Expr::BinExpr(_) | ast::Expr::CastExpr(_) => {
+                format!("({})", iterable)
+            }
+            _ => iterable.to_string(),
+        }
+    } else if let ast::Expr::RefExpr(_) = iterable {
+        format!("({}).into_iter()", iterable)
+    } else {
+        format!("{}.into_iter()", iterable)
+    }
+}
+
+/// If iterable is a reference where the expression behind the reference implements a method
+/// returning an Iterator called iter or iter_mut (depending on the type of reference) then return
+/// the expression behind the reference and the method name
+fn is_ref_and_impls_iter_method(
+    sema: &hir::Semantics<ide_db::RootDatabase>,
+    iterable: &ast::Expr,
+) -> Option<(ast::Expr, hir::Name)> {
+    let ref_expr = match iterable {
+        ast::Expr::RefExpr(r) => r,
+        _ => return None,
+    };
+    let wanted_method =
+        if ref_expr.mut_token().is_some() { hir::known::iter_mut } else { hir::known::iter };
+    let expr_behind_ref = ref_expr.expr()?;
+    let typ = sema.type_of_expr(&expr_behind_ref)?;
+    let scope = sema.scope(iterable.syntax());
+    let krate = scope.module()?.krate();
+    let traits_in_scope = scope.traits_in_scope();
+    let iter_trait = FamousDefs(sema, Some(krate)).core_iter_Iterator()?;
+
+    let has_wanted_method = typ
+        .iterate_method_candidates(
+            sema.db,
+            krate,
+            &traits_in_scope,
+            Some(&wanted_method),
+            |_, func| {
+                if func.ret_type(sema.db).impls_trait(sema.db, iter_trait, &[]) {
+                    return Some(());
+                }
+                None
+            },
+        )
+        .is_some();
+    if !has_wanted_method {
+        return None;
+    }
+
+    Some((expr_behind_ref, wanted_method))
+}
+
+/// Whether iterable implements core::Iterator
+fn impls_core_iter(sema: &hir::Semantics<ide_db::RootDatabase>, iterable: &ast::Expr) -> bool {
+    let it_typ = match sema.type_of_expr(iterable) {
+        Some(it) => it,
+        None => return false,
+    };
+
+    let module = match sema.scope(iterable.syntax()).module() {
+        Some(it) => it,
+        None => return false,
+    };
+
+    let krate = module.krate();
+    match FamousDefs(sema, Some(krate)).core_iter_Iterator() {
+        Some(iter_trait) => {
+            cov_mark::hit!(test_already_impls_iterator);
+            it_typ.impls_trait(sema.db, iter_trait, &[])
+        }
+        None => false,
+    }
+}
+
+/// Returns the expressions whose values are returned from `body`, the body of a function
+/// or closure: its tail expressions and the arguments of its `return` expressions.
+pub(crate) fn returned_exprs(body: &ast::Expr) -> Vec<ast::Expr> {
+    let mut res = Vec::new();
+    let tail_cb = &mut |e: &_| tail_cb_impl(&mut res, e);
+    body.walk(&mut |expr| {
+        if let ast::Expr::ReturnExpr(ret_expr) = expr {
+            if let Some(ret_expr_arg) = &ret_expr.expr() {
+                for_each_tail_expr(ret_expr_arg, tail_cb);
+            }
+        }
+    });
+    for_each_tail_expr(body, tail_cb);
+    res
+}
+
+/// Returns the name of an associated item, or `None` for macro calls.
+pub(crate) fn assoc_item_name(item: &ast::AssocItem) -> Option<String> {
+    let name = match item {
+        ast::AssocItem::Const(it) => it.name(),
+        ast::AssocItem::Fn(it) => it.name(),
+        ast::AssocItem::TypeAlias(it) => it.name(),
+        ast::AssocItem::MacroCall(_) => None,
+    };
+    name.map(|it| it.to_string())
+}
+
+fn tail_cb_impl(acc: &mut Vec<ast::Expr>, e: &ast::Expr) {
+    match e {
+        ast::Expr::BreakExpr(break_expr) => {
+            if let Some(break_expr_arg) = break_expr.expr() {
+                for_each_tail_expr(&break_expr_arg, &mut |e| tail_cb_impl(acc, e))
+            }
+        }
+        ast::Expr::ReturnExpr(ret_expr) => {
+            if let Some(ret_expr_arg) = &ret_expr.expr() {
+                for_each_tail_expr(ret_expr_arg, &mut |e| tail_cb_impl(acc, e));
+            }
+        }
+        e => acc.push(e.clone()),
+    }
+}
@@ -5,10 +5,18 @@
 
 use array_iterator::ArrayIterator;
 use hir::HasAttrs;
-use ide_db::helpers::generated_lints::{CLIPPY_LINTS, DEFAULT_LINTS, FEATURES};
+use ide_db::{
+    base_db::ReleaseChannel,
+    helpers::generated_lints::{CLIPPY_LINTS, DEFAULT_LINTS, FEATURES},
+    proc_macros::ProcMacrosDatabase,
+};
 use once_cell::sync::Lazy;
 use rustc_hash::{FxHashMap, FxHashSet};
-use syntax::{algo::non_trivia_sibling, ast, AstNode, Direction, NodeOrToken, SyntaxKind, T};
+use syntax::{
+    algo::non_trivia_sibling,
+    ast::{self, AttrsOwner},
+    AstNode, Direction, NodeOrToken, SyntaxKind, T,
+};
 
 use crate::{
     context::CompletionContext,
@@ -27,14 +35,29 @@ pub(crate) fn complete_attribute(acc: &mut Completions, ctx: &CompletionContext)
         (Some(path), Some(token_tree)) => match path.text().as_str() {
             "derive" => derive::complete_derive(acc, ctx, token_tree),
             "repr" => repr::complete_repr(acc, ctx, token_tree),
-            "feature" => lint::complete_lint(acc, ctx, token_tree, FEATURES),
+            "feature" if allows_features(ctx) => {
+                lint::complete_lint(acc, ctx, token_tree, FEATURES)
+            }
             "allow" | "warn" | "deny" | "forbid" => {
                 lint::complete_lint(acc, ctx, token_tree.clone(), DEFAULT_LINTS);
-                lint::complete_lint(acc, ctx, token_tree, CLIPPY_LINTS);
+                lint::complete_lint(acc, ctx, token_tree.clone(), CLIPPY_LINTS);
+                lint::complete_additional_lints(acc, ctx, token_tree);
             }
             "cfg" => {
                 cfg::complete_cfg(acc, ctx);
             }
+            "cfg_attr" => {
+                // Only the predicate, before the first comma, is a cfg.
+                let cursor = ctx.source_range().start();
+                let in_predicate = token_tree
+                    .syntax()
+                    .children_with_tokens()
+                    .filter(|it| it.kind() == T![,])
+                    .all(|it| it.text_range().start() >= cursor);
+                if in_predicate {
+                    cfg::complete_cfg(acc, ctx);
+                }
+            }
             _ => (),
         },
         (None, Some(_)) => (),
@@ -75,6 +98,10 @@ fn complete_new_attribute(acc: &mut Completions, ctx: &CompletionContext, attrib
             item.insert_snippet(cap, snippet);
         }
 
+        if attr_completion.label == "feature(…)" && !allows_features(ctx) {
+            return;
+        }
+
         if is_inner || !attr_completion.prefer_inner {
             item.add_to(acc);
         }
@@ -90,6 +117,10 @@ fn complete_new_attribute(acc: &mut Completions, ctx: &CompletionContext, attrib
         None => ATTRIBUTES.iter().filter(|compl| !compl.prefer_inner).for_each(add_completion),
     }
 
+    if !is_inner {
+        complete_derive_helpers(acc, ctx, attribute);
+    }
+
     // FIXME: write a test for this when we can
     ctx.scope.process_all_names(&mut |name, scope_def| {
         if let hir::ScopeDef::MacroDef(mac) = scope_def {
@@ -109,6 +140,51 @@ fn complete_new_attribute(acc: &mut Completions, ctx: &CompletionContext, attrib
     });
 }
 
+/// Unstable features can only be enabled on the nightly channel, which is
+/// assumed when the toolchain of the crate is unknown.
+fn allows_features(ctx: &CompletionContext) -> bool {
+    let toolchain = ctx.krate.and_then(|krate| krate.toolchain(ctx.db));
+    toolchain.map_or(true, |it| it.channel == ReleaseChannel::Nightly)
+}
+
+/// Completes the helper attributes of the derives of the annotated ADT, or of
+/// the ADT the annotated field or variant belongs to, like `serde` for
+/// `#[derive(Serialize)]`.
+fn complete_derive_helpers(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    attribute: &ast::Attr,
+) -> Option<()> {
+    let krate = ctx.krate?;
+    let adt = attribute.syntax().ancestors().find_map(ast::Adt::cast)?;
+    let derive_helpers = ctx.db.derive_helpers(krate.into());
+    let mut seen = FxHashSet::default();
+    for attr in adt.attrs().filter(|attr| attr.simple_name().as_deref() == Some("derive")) {
+        let derives = match attr.token_tree().and_then(parse_comma_sep_input) {
+            Some(it) => it,
+            None => continue,
+        };
+        for derive in derives {
+            let name = derive.rsplit("::").next().unwrap_or_default();
+            let mac = ctx.sema.resolve_derive_macro(&attr, name);
+            let helpers = match mac.and_then(|it| derive_helpers.get(&it)) {
+                Some(it) => it,
+                None => continue,
+            };
+            for helper in helpers.iter().filter(|&it| seen.insert(it.clone())) {
+                let mut item = CompletionItem::new(
+                    CompletionKind::Attribute,
+                    ctx.source_range(),
+                    helper.as_str(),
+                );
+                item.kind(CompletionItemKind::Attribute).detail(format!("derive({})", name));
+                item.add_to(acc);
+            }
+        }
+    }
+    Some(())
+}
+
 struct AttrCompletion {
     label: &'static str,
     lookup: Option<&'static str>,
@@ -10,56 +10,86 @@ use crate::{
 };
 
 pub(crate) fn complete_cfg(acc: &mut Completions, ctx: &CompletionContext) {
-    let add_completion = |item: &&str| {
+    let add_completion = |item: &str| {
         let mut completion =
-            CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), *item);
+            CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), item);
         completion.insert_text(format!(r#""{}""#, item));
         completion.kind(CompletionItemKind::Attribute);
         acc.add(completion.build());
     };
 
-    let previous = iter::successors(ctx.original_token.prev_token(), |t| {
+    // The token under the cursor is the one being completed if it is a key or
+    // a value, otherwise the cursor is after it.
+    let before_cursor = match ctx.original_token.kind() {
+        SyntaxKind::IDENT | SyntaxKind::STRING => ctx.original_token.prev_token(),
+        _ => Some(ctx.original_token.clone()),
+    };
+    let previous = iter::successors(before_cursor, |t| {
         (matches!(t.kind(), SyntaxKind::EQ) || t.kind().is_trivia())
             .then(|| t.prev_token())
             .flatten()
     })
     .find(|t| matches!(t.kind(), SyntaxKind::IDENT));
+    let potential_cfg = ctx.krate.map(|krate| krate.potential_cfg(ctx.db));
 
     match previous.as_ref().map(|p| p.text()) {
-        Some("target_arch") => KNOWN_ARCH.iter().for_each(add_completion),
-        Some("target_env") => KNOWN_ENV.iter().for_each(add_completion),
-        Some("target_os") => KNOWN_OS.iter().for_each(add_completion),
-        Some("target_vendor") => KNOWN_VENDOR.iter().for_each(add_completion),
-        Some("target_endian") => ["little", "big"].iter().for_each(add_completion),
         Some(name) => {
-            ctx.krate.map(|krate| {
-                krate.potential_cfg(ctx.db).get_cfg_values(&name).iter().for_each(|s| {
-                    let mut item = CompletionItem::new(
-                        CompletionKind::Attribute,
-                        ctx.source_range(),
-                        s.as_str(),
-                    );
-                    item.insert_text(format!(r#""{}""#, s));
-
-                    acc.add(item.build());
-                })
-            });
+            let known: &[&str] = match name {
+                "target_arch" => &KNOWN_ARCH,
+                "target_env" => &KNOWN_ENV,
+                "target_os" => &KNOWN_OS,
+                "target_vendor" => &KNOWN_VENDOR,
+                "target_endian" => &["little", "big"],
+                "target_family" => &["unix", "wasm", "windows"],
+                "target_pointer_width" => &["16", "32", "64"],
+                "panic" => &["abort", "unwind"],
+                _ => &[],
+            };
+            // The values the crate can be built with, like its Cargo features.
+            let mut values = potential_cfg
+                .as_ref()
+                .map(|cfg| cfg.get_cfg_values(name).into_iter().map(|it| it.as_str()).collect())
+                .unwrap_or_else(Vec::new);
+            values.sort_unstable();
+            values.retain(|it| !known.contains(it));
+            known.iter().copied().chain(values).for_each(add_completion);
         }
         None => {
-            ctx.krate.map(|krate| {
-                krate.potential_cfg(ctx.db).get_cfg_keys().iter().for_each(|s| {
-                    let item = CompletionItem::new(
-                        CompletionKind::Attribute,
-                        ctx.source_range(),
-                        s.as_str(),
-                    );
-                    acc.add(item.build());
-                })
+            let mut keys = potential_cfg
+                .as_ref()
+                .map(|cfg| cfg.get_cfg_keys().into_iter().map(|it| it.as_str()).collect())
+                .unwrap_or_else(Vec::new);
+            keys.extend(KNOWN_KEYS.iter().copied());
+            keys.sort_unstable();
+            keys.dedup();
+            keys.into_iter().for_each(|key| {
+                let mut item =
+                    CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), key);
+                item.kind(CompletionItemKind::Attribute);
+                acc.add(item.build());
             });
         }
     };
 }
 
+const KNOWN_KEYS: [&'static str; 15] = [
+    "debug_assertions",
+    "doc",
+    "feature",
+    "panic",
+    "target_arch",
+    "target_endian",
+    "target_env",
+    "target_family",
+    "target_feature",
+    "target_os",
+    "target_pointer_width",
+    "target_vendor",
+    "test",
+    "unix",
+    "windows",
+];
+
 const KNOWN_ARCH: [&'static str; 19] = [
     "aarch64",
     "arm",
@@ -1,5 +1,6 @@
 //! Completion for lints
-use ide_db::helpers::generated_lints::Lint;
+use ide_db::helpers::generated_lints::{Lint, CLIPPY_LINTS, DEFAULT_LINTS};
+use itertools::Itertools;
 use syntax::ast;
 
 use crate::{
@@ -25,8 +26,48 @@ pub(super) fn complete_lint(
                 lint_completion.label,
             );
             item.kind(CompletionItemKind::Attribute)
+                .set_detail(summary(lint_completion.description))
                 .documentation(hir::Documentation::new(lint_completion.description.to_owned()));
             item.add_to(acc)
         }
     }
 }
+
+/// Completes the lints which aren't built-in, like the ones of newer versions
+/// of the toolchain reported by `cargo check`.
+pub(super) fn complete_additional_lints(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    derive_input: ast::TokenTree,
+) {
+    if let Some(existing_lints) = super::parse_comma_sep_input(derive_input) {
+        let is_builtin = |lint: &str| {
+            DEFAULT_LINTS.iter().chain(CLIPPY_LINTS).any(|builtin| builtin.label == lint)
+        };
+        for lint in ctx
+            .config
+            .additional_lints
+            .iter()
+            .filter(|lint| !existing_lints.contains(*lint) && !is_builtin(lint))
+        {
+            let mut item =
+                CompletionItem::new(CompletionKind::Attribute, ctx.source_range(), lint.as_str());
+            item.kind(CompletionItemKind::Attribute).detail("reported by cargo check");
+            item.add_to(acc)
+        }
+    }
+}
+
+/// Returns the first sentence of the description of a lint, on a single line.
+/// The descriptions of features are whole documents starting with a title,
+/// which have no summary.
+fn summary(description: &str) -> Option<String> {
+    if description.starts_with('#') {
+        return None;
+    }
+    let paragraph = description.split("\n\n").next()?.split_whitespace().join(" ");
+    match paragraph.find(". ") {
+        Some(idx) => Some(paragraph[..idx + 1].to_string()),
+        None => Some(paragraph),
+    }
+}
@@ -0,0 +1,304 @@
+//! Completes the string arguments of the built-in macros which take the path of
+//! a file, like `include_str!("data.txt")`, or the name of an environment
+//! variable, like `env!("CARGO_PKG_NAME")`.
+//!
+//! Paths are relative to the directory of the current file. The included files
+//! are usually data files, which the VFS doesn't load, so the directory is
+//! listed on disk, unless the file isn't on disk, like in the tests.
+//! Environment variables are the ones set for the current crate and the ones
+//! of the `.env` file of the crate, as read by the `dotenv` crates.
+
+use std::{fs, iter};
+
+use ide_db::{
+    base_db::{FileId, SourceDatabase, SourceDatabaseExt, SourceRoot, VfsPath},
+    SymbolKind,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use stdx::split_once;
+use syntax::{
+    ast::{self, IsString},
+    AstNode, AstToken, Direction, TextRange, TextSize, T,
+};
+
+use crate::{item::CompletionKind, CompletionContext, CompletionItem, Completions};
+
+const PATH_MACROS: &[&str] = &["include", "include_str", "include_bytes"];
+const ENV_MACROS: &[&str] = &["env", "option_env"];
+
+pub(crate) fn complete_builtin_macro_arg(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+) -> Option<()> {
+    let string = ast::String::cast(ctx.original_token.clone())?;
+    let tt = ast::TokenTree::cast(string.syntax().parent()?)?;
+    let macro_call = ast::MacroCall::cast(tt.syntax().parent()?)?;
+    let name = macro_call.path()?.segment()?.name_ref()?;
+    if string.syntax().siblings_with_tokens(Direction::Prev).any(|it| it.kind() == T![,]) {
+        return None;
+    }
+    let contents = string.text_range_between_quotes()?;
+    let cursor = ctx.position.offset;
+    if !contents.contains_inclusive(cursor) {
+        return None;
+    }
+    let typed_range =
+        TextRange::new(contents.start(), cursor) - string.syntax().text_range().start();
+    let typed = &string.text()[typed_range];
+
+    let name = name.text();
+    if PATH_MACROS.contains(&name.as_str()) {
+        complete_paths(acc, ctx, typed, cursor)
+    } else if ENV_MACROS.contains(&name.as_str()) {
+        complete_env_vars(acc, ctx, TextRange::new(contents.start(), cursor))
+    } else {
+        None
+    }
+}
+
+fn complete_paths(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    typed: &str,
+    cursor: TextSize,
+) -> Option<()> {
+    let (typed_dir, typed_name) = match typed.rfind('/') {
+        Some(idx) => (&typed[..idx], &typed[idx + 1..]),
+        None => ("", typed),
+    };
+    let file_id = ctx.position.file_id;
+    let source_root = ctx.db.source_root(ctx.db.file_source_root(file_id));
+    let current = source_root.path_for_file(&file_id)?;
+    let mut dir = current.parent()?;
+    for component in typed_dir.split('/') {
+        dir = match component {
+            "" | "." => dir,
+            ".." => dir.parent()?,
+            _ => dir.join(component)?,
+        };
+    }
+    let range = TextRange::new(cursor - TextSize::of(typed_name), cursor);
+
+    let entries = match dir.as_path() {
+        Some(dir) => {
+            let entries = fs::read_dir(dir).into_iter().flatten().flatten();
+            entries
+                .filter(|entry| current.as_path() != Some(&*dir.join(entry.file_name())))
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    let is_dir = entry.file_type().ok()?.is_dir();
+                    Some(if is_dir { format!("{}/", name) } else { name })
+                })
+                .collect()
+        }
+        None => vfs_entries(&source_root, current, &dir),
+    };
+
+    let mut entries = entries.into_iter().collect::<Vec<_>>();
+    entries.sort();
+    for label in entries {
+        let mut item = CompletionItem::new(CompletionKind::Magic, range, label);
+        item.kind(SymbolKind::Module);
+        item.add_to(acc);
+    }
+    Some(())
+}
+
+/// Lists the entries of `dir` among the files of `source_root`, other than
+/// `current`. Directories end with a `/`.
+fn vfs_entries(source_root: &SourceRoot, current: &VfsPath, dir: &VfsPath) -> FxHashSet<String> {
+    let mut entries = FxHashSet::default();
+    for path in source_root.iter().filter_map(|it| source_root.path_for_file(&it)) {
+        if path == current {
+            continue;
+        }
+        // The child of `dir` which contains the file, or the file itself.
+        let entry = iter::successors(Some(path.clone()), |it| it.parent())
+            .find(|it| it.parent().as_ref() == Some(dir));
+        let entry = match entry {
+            Some(it) => it,
+            None => continue,
+        };
+        let name = match entry.name_and_extension() {
+            Some((name, Some(ext))) => format!("{}.{}", name, ext),
+            Some((name, None)) => name.to_string(),
+            None => continue,
+        };
+        entries.insert(if entry == *path { name } else { format!("{}/", name) });
+    }
+    entries
+}
+
+fn complete_env_vars(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    range: TextRange,
+) -> Option<()> {
+    let krate = ctx.krate?;
+    let crate_graph = ctx.db.crate_graph();
+    let krate = &crate_graph[krate.into()];
+    // The variables set for the crate take precedence, as with `dotenv`.
+    let mut vars = dotenv_vars(ctx, krate.root_file_id).unwrap_or_default();
+    vars.extend(krate.env.iter().map(|(name, value)| (name.to_string(), value.to_string())));
+    let mut vars = vars.into_iter().collect::<Vec<_>>();
+    vars.sort();
+    for (name, value) in vars {
+        let mut item = CompletionItem::new(CompletionKind::Magic, range, name);
+        item.kind(SymbolKind::Const).set_detail((!value.contains('\n')).then(|| value));
+        item.add_to(acc);
+    }
+    Some(())
+}
+
+/// Reads the variables of the first `.env` file found in the directory of the
+/// crate root or in one of its ancestors.
+fn dotenv_vars(ctx: &CompletionContext, root_file: FileId) -> Option<FxHashMap<String, String>> {
+    let source_root = ctx.db.source_root(ctx.db.file_source_root(root_file));
+    let root_dir = source_root.path_for_file(&root_file)?.parent()?;
+    let text = iter::successors(Some(root_dir), |it| it.parent()).find_map(|dir| {
+        let path = dir.join(".env")?;
+        match path.as_path() {
+            Some(path) => fs::read_to_string(path).ok(),
+            None => source_root.file_for_path(&path).map(|&it| ctx.db.file_text(it).to_string()),
+        }
+    })?;
+    let vars = text.lines().filter_map(|line| {
+        let line = line.trim();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if line.starts_with('#') {
+            return None;
+        }
+        let (name, value) = split_once(line, '=')?;
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        Some((name.trim().to_string(), value.to_string()))
+    });
+    Some(vars.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::tests::{check_edit, completion_list};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_paths() {
+        check(
+            r#"
+//- /main.rs
+fn main() {
+    include_str!("$0");
+}
+//- /data.rs
+//- /foo/bar.rs
+//- /foo/baz/qux.rs
+"#,
+            expect![[r#"
+                md data.rs
+                md foo/
+            "#]],
+        );
+        check(
+            r#"
+//- /main.rs
+fn main() {
+    include!("foo/b$0");
+}
+//- /data.rs
+//- /foo/bar.rs
+//- /foo/baz/qux.rs
+"#,
+            expect![[r#"
+                md bar.rs
+                md baz/
+            "#]],
+        );
+    }
+
+    #[test]
+    fn completes_paths_of_parent_dirs() {
+        check_edit(
+            "data.rs",
+            r#"
+//- /foo/main.rs crate:main
+fn main() {
+    include_bytes!("../d$0");
+}
+//- /data.rs
+"#,
+            r#"
+fn main() {
+    include_bytes!("../data.rs");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_env_vars() {
+        check(
+            r#"
+//- /main.rs crate:main env:OUT_DIR=/target/out,CARGO_PKG_NAME=main
+fn main() {
+    env!("CA$0");
+}
+"#,
+            expect![[r#"
+                ct CARGO_PKG_NAME main
+                ct OUT_DIR        /target/out
+            "#]],
+        );
+        check_edit(
+            "OUT_DIR",
+            r#"
+//- /main.rs crate:main env:OUT_DIR=/target/out
+fn main() {
+    option_env!("OUT$0");
+}
+"#,
+            r#"
+fn main() {
+    option_env!("OUT_DIR");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_dotenv_vars() {
+        check(
+            r#"
+//- /main.rs crate:main env:OUT_DIR=/target/out
+fn main() {
+    env!("$0");
+}
+//- /.env
+# The database of the tests.
+DATABASE_URL="postgres://localhost"
+export OUT_DIR=/tmp
+"#,
+            expect![[r#"
+                ct DATABASE_URL postgres://localhost
+                ct OUT_DIR      /target/out
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_completions_in_other_args() {
+        check(
+            r#"
+//- /main.rs crate:main env:OUT_DIR=/target/out
+fn main() {
+    env!("OUT_DIR", "$0");
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
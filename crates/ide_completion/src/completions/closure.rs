@@ -0,0 +1,196 @@
+//! Completes a closure skeleton, like `|item| $0`, for the arguments of
+//! higher-order functions, like `iter.map($0)`.
+//!
+//! The parameters of the closure are the ones of the expected function type:
+//! either the inferred type of the argument, when it's a function pointer, or
+//! the `Fn` bound of the declared type of the parameter, like `impl Fn(u32)` or
+//! a type parameter `F: FnMut(Self::Item) -> B`. They are named after their
+//! types.
+
+use ide_db::call_info::ActiveParameter;
+use stdx::to_lower_snake_case;
+use syntax::{ast, AstNode, SyntaxKind};
+
+use crate::{
+    item::CompletionKind, CompletionContext, CompletionItem, CompletionItemKind, Completions,
+};
+
+pub(crate) fn complete_closure_arg(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let cap = ctx.config.snippet_cap?;
+    if !ctx.expects_expression() || !ctx.is_trivial_path() {
+        return None;
+    }
+    let param = ctx.active_parameter.as_ref()?;
+    let param_types = match ctx.expected_type.as_ref().and_then(|ty| ty.as_callable(ctx.db)) {
+        Some(callable) => callable
+            .params(ctx.db)
+            .into_iter()
+            .map(|(_, ty)| ty.display(ctx.db).to_string())
+            .collect(),
+        None => declared_closure_params(param)?,
+    };
+
+    let mut names: Vec<String> = Vec::new();
+    for ty in &param_types {
+        let name = param_name(ty);
+        let count = names
+            .iter()
+            .filter_map(|it| it.strip_prefix(name.as_str()))
+            .filter(|suffix| suffix.chars().all(|c| c.is_ascii_digit()))
+            .count();
+        names.push(if count == 0 { name } else { format!("{}{}", name, count) });
+    }
+    let label = format!("|{}|", names.join(", "));
+    let mut item = CompletionItem::new(CompletionKind::Snippet, ctx.source_range(), label.clone());
+    item.insert_snippet(cap, format!("{} $0", label))
+        .kind(CompletionItemKind::Snippet)
+        .detail("closure");
+    item.add_to(acc);
+    Some(())
+}
+
+/// Returns the parameter types of the `Fn` bound of the declared type of
+/// `param`.
+fn declared_closure_params(param: &ActiveParameter) -> Option<Vec<String>> {
+    let pat = param.pat.as_ref().right()?;
+    let param = ast::Param::cast(pat.syntax().parent()?)?;
+    let bounds = match param.ty()? {
+        ast::Type::FnPtrType(it) => return Some(param_types(it.param_list()?)),
+        ast::Type::ImplTraitType(it) => vec![it.type_bound_list()?],
+        ast::Type::RefType(it) => match it.ty()? {
+            ast::Type::DynTraitType(it) => vec![it.type_bound_list()?],
+            _ => return None,
+        },
+        ast::Type::PathType(it) => {
+            let name = it.path()?.as_single_name_ref()?;
+            let func = param.syntax().ancestors().find_map(ast::Fn::cast)?;
+            generic_param_bounds(&func, &name.text())
+        }
+        _ => return None,
+    };
+    bounds.iter().flat_map(|it| it.bounds()).find_map(|bound| {
+        let path = match bound.ty()? {
+            ast::Type::PathType(it) => it.path()?,
+            _ => return None,
+        };
+        let segment = path.segment()?;
+        match segment.name_ref()?.text().as_str() {
+            "Fn" | "FnMut" | "FnOnce" => Some(param_types(segment.param_list()?)),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the bounds of the type parameter `name` of `func`, both the inline
+/// ones and the ones of the where clause.
+fn generic_param_bounds(func: &ast::Fn, name: &str) -> Vec<ast::TypeBoundList> {
+    let inline = func
+        .generic_param_list()
+        .into_iter()
+        .flat_map(|it| it.type_params())
+        .filter(|it| it.name().map_or(false, |it| it.text() == name))
+        .filter_map(|it| it.type_bound_list());
+    let where_clause = func
+        .where_clause()
+        .into_iter()
+        .flat_map(|it| it.predicates())
+        .filter(|it| it.ty().map_or(false, |it| it.syntax().text() == name))
+        .filter_map(|it| it.type_bound_list());
+    inline.chain(where_clause).collect()
+}
+
+fn param_types(param_list: ast::ParamList) -> Vec<String> {
+    param_list
+        .params()
+        .map(|it| it.ty().map_or_else(|| "_".to_string(), |ty| ty.to_string()))
+        .collect()
+}
+
+const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+
+/// Derives the name of a closure parameter from its type, like `item` for
+/// `Self::Item` or `path` for `&Path`. Primitives, tuples and single-letter
+/// type parameters are named `x`.
+fn param_name(ty: &str) -> String {
+    let ty = ty.trim_start_matches(|c: char| c == '&' || c.is_whitespace());
+    let ty = ty.trim_start_matches("mut ").trim_start_matches("dyn ");
+    let ty = ty.split('<').next().unwrap_or_default();
+    let name = ty.rsplit("::").next().unwrap_or_default().trim();
+    let is_ident = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if !is_ident || name.chars().count() == 1 || PRIMITIVES.contains(&name) {
+        return "x".to_string();
+    }
+    let name = to_lower_snake_case(name);
+    match SyntaxKind::from_keyword(&name) {
+        Some(_) => "x".to_string(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_edit;
+
+    #[test]
+    fn completes_closure_for_generic_param() {
+        check_edit(
+            "|item|",
+            r#"
+//- minicore: iterators
+fn main() {
+    core::iter::repeat(92).map($0)
+}
+"#,
+            r#"
+fn main() {
+    core::iter::repeat(92).map(|item| $0)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_closure_for_impl_trait() {
+        check_edit(
+            "|path, x, x1|",
+            r#"
+struct Path;
+fn visit(f: impl Fn(&Path, u32, (u8, u8))) {}
+fn main() {
+    visit($0);
+}
+"#,
+            r#"
+struct Path;
+fn visit(f: impl Fn(&Path, u32, (u8, u8))) {}
+fn main() {
+    visit(|path, x, x1| $0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_closure_for_fn_pointer() {
+        check_edit(
+            "|node_id|",
+            r#"
+struct NodeId;
+fn walk(f: fn(NodeId) -> bool) {}
+fn main() {
+    walk($0);
+}
+"#,
+            r#"
+struct NodeId;
+fn walk(f: fn(NodeId) -> bool) {}
+fn main() {
+    walk(|node_id| $0);
+}
+"#,
+        );
+    }
+}
@@ -13,7 +13,7 @@ pub(crate) fn complete_dot(acc: &mut Completions, ctx: &CompletionContext) {
         _ => return complete_undotted_self(acc, ctx),
     };
 
-    let receiver_ty = match ctx.sema.type_of_expr(dot_receiver) {
+    let receiver_ty = match &ctx.receiver_ty {
         Some(ty) => ty,
         _ => return,
     };
@@ -21,12 +21,12 @@ pub(crate) fn complete_dot(acc: &mut Completions, ctx: &CompletionContext) {
     if matches!(ctx.completion_location, Some(ImmediateLocation::MethodCall { .. })) {
         cov_mark::hit!(test_no_struct_field_completion_for_method_call);
     } else {
-        complete_fields(ctx, &receiver_ty, |field, ty| match field {
+        complete_fields(ctx, receiver_ty, |field, ty| match field {
             Either::Left(field) => acc.add_field(ctx, None, field, &ty),
             Either::Right(tuple_idx) => acc.add_tuple_field(ctx, None, tuple_idx, &ty),
         });
     }
-    complete_methods(ctx, &receiver_ty, |func| acc.add_method(ctx, func, None, None));
+    complete_methods(ctx, receiver_ty, |func| acc.add_method(ctx, func, None, None));
 }
 
 fn complete_undotted_self(acc: &mut Completions, ctx: &CompletionContext) {
@@ -70,6 +70,8 @@
 //! (i.e. in `HashMap` in the `std::collections::HashMap` path).
 //! For the same reasons, avoids searching for any path imports for inputs with their length less than 2 symbols
 //! (but shows all associated items for any input length).
+//! The items which other modules of the current crate already import are ranked higher, the more modules import them,
+//! the higher.
 //!
 //! .Import configuration
 //!
@@ -87,12 +89,19 @@
 //! .Feature toggle
 //!
 //! The feature can be forcefully turned off in the settings with the `rust-analyzer.completion.autoimport.enable` flag.
+//! The search for the methods of traits which aren't in scope, the costliest one, can be turned off separately with the
+//! `rust-analyzer.completion.autoimport.traitMethods` flag.
 //! Note that having this flag set to `true` does not guarantee that the feature is enabled: your client needs to have the corresponding
 //! capability enabled.
 
-use ide_db::helpers::{
-    import_assets::{ImportAssets, ImportCandidate},
-    insert_use::ImportScope,
+use std::cmp;
+
+use ide_db::{
+    helpers::{
+        import_assets::{ImportAssets, ImportCandidate, LocatedImport},
+        insert_use::ImportScope,
+    },
+    import_counts::ImportCountsDatabase,
 };
 use itertools::Itertools;
 use syntax::{AstNode, SyntaxNode, T};
@@ -109,6 +118,9 @@ pub(crate) fn import_on_the_fly(acc: &mut Completions, ctx: &CompletionContext)
     if !ctx.config.enable_imports_on_the_fly {
         return None;
     }
+    if ctx.dot_receiver().is_some() && !ctx.config.enable_trait_methods_on_the_fly {
+        return None;
+    }
     if ctx.in_use_tree()
         || ctx.is_path_disallowed()
         || ctx.expects_item()
@@ -134,21 +146,32 @@ pub(crate) fn import_on_the_fly(acc: &mut Completions, ctx: &CompletionContext)
         &ctx.sema,
     )?;
 
+    let import_counts =
+        ctx.krate.map(|krate| ctx.db.import_counts(krate.into())).unwrap_or_default();
+    let import_count =
+        |import: &LocatedImport| import_counts.get(&import.item_to_import).copied().unwrap_or(0);
+
     acc.add_all(
         import_assets
             .search_for_imports(&ctx.sema, ctx.config.insert_use.prefix_kind)
             .into_iter()
             .sorted_by_key(|located_import| {
-                compute_fuzzy_completion_order_key(
-                    &located_import.import_path,
-                    &user_input_lowercased,
+                (
+                    compute_fuzzy_completion_order_key(
+                        &located_import.import_path,
+                        &user_input_lowercased,
+                    ),
+                    cmp::Reverse(import_count(located_import)),
                 )
             })
             .filter_map(|import| {
-                render_resolution_with_import(
+                let is_used_in_crate = import_count(&import) > 0;
+                let mut item = render_resolution_with_import(
                     RenderContext::new(ctx),
                     ImportEdit { import, scope: import_scope.clone() },
-                )
+                )?;
+                item.relevance.is_used_in_crate = is_used_in_crate;
+                Some(item)
             }),
     );
     Some(())
@@ -176,7 +199,7 @@ fn import_assets(ctx: &CompletionContext, fuzzy_name: String) -> Option<ImportAs
     if let Some(dot_receiver) = ctx.dot_receiver() {
         ImportAssets::for_fuzzy_method_call(
             current_module,
-            ctx.sema.type_of_expr(dot_receiver)?,
+            ctx.receiver_ty.clone()?,
             fuzzy_name,
             dot_receiver.syntax().clone(),
         )
@@ -227,7 +250,11 @@ mod tests {
 
     use crate::{
         item::CompletionKind,
-        tests::{check_edit, check_edit_with_config, filtered_completion_list, TEST_CONFIG},
+        tests::{
+            check_edit, check_edit_with_config, filtered_completion_list,
+            filtered_completion_list_with_config, TEST_CONFIG,
+        },
+        CompletionConfig,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -375,6 +402,32 @@ fn main() {
         );
     }
 
+    #[test]
+    fn items_imported_in_crate_come_first() {
+        check(
+            r#"
+//- /lib.rs crate:obscure
+pub struct HashMap;
+//- /std.rs crate:std
+pub mod collections {
+    pub struct HashMap;
+}
+//- /main.rs crate:main deps:obscure,std
+mod cache {
+    use std::collections::HashMap;
+}
+
+fn main() {
+    HashM$0
+}
+"#,
+            expect![[r#"
+                st std::collections::HashMap
+                st obscure::HashMap
+            "#]],
+        );
+    }
+
     #[test]
     fn trait_function_fuzzy_completion() {
         let fixture = r#"
@@ -515,6 +568,33 @@ fn main() {
         );
     }
 
+    #[test]
+    fn trait_method_fuzzy_completion_disabled() {
+        let actual = filtered_completion_list_with_config(
+            CompletionConfig { enable_trait_methods_on_the_fly: false, ..TEST_CONFIG },
+            r#"
+//- /lib.rs crate:dep
+pub mod test_mod {
+    pub trait TestTrait {
+        fn random_method(&self);
+    }
+    pub struct TestStruct {}
+    impl TestTrait for TestStruct {
+        fn random_method(&self) {}
+    }
+}
+
+//- /main.rs crate:main deps:dep
+fn main() {
+    let test_struct = dep::test_mod::TestStruct {};
+    test_struct.ran$0
+}
+"#,
+            CompletionKind::Magic,
+        );
+        expect![[""]].assert_eq(&actual);
+    }
+
     #[test]
     fn no_trait_type_fuzzy_completion() {
         check(
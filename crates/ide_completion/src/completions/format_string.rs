@@ -0,0 +1,237 @@
+//! Completes the placeholders of the format strings of format macros, like
+//! `println!("{value:?}")`: the local variables captured by name, the fields of
+//! locals, passed as an additional argument, and the format specs.
+
+use ide_db::{helpers::format_macro_call_of_string, SymbolKind};
+use stdx::split_once;
+use syntax::{ast, AstNode, AstToken, TextRange, TextSize, T};
+use text_edit::TextEditBuilder;
+
+use crate::{
+    item::CompletionKind, CompletionContext, CompletionItem, CompletionItemKind, Completions,
+};
+
+pub(crate) fn complete_format_string(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let string = ast::String::cast(ctx.original_token.clone())?;
+    let macro_call = format_macro_call_of_string(&string)?;
+    let string_range = string.syntax().text_range();
+    let cursor = ctx.position.offset;
+    if !string_range.contains(cursor) || cursor == string_range.start() {
+        return None;
+    }
+    let text = string.text();
+    let before_cursor = &text[..usize::from(cursor - string_range.start())];
+    let open = open_placeholder(before_cursor)?;
+    let placeholder = &before_cursor[open + 1..];
+    let placeholder_start = string_range.start() + TextSize::from(open as u32 + 1);
+
+    match split_once(placeholder, ':') {
+        Some((_, spec)) if spec.is_empty() => complete_specs(acc, cursor),
+        Some(_) => (),
+        None => match split_once(placeholder, '.') {
+            Some((receiver, field)) => {
+                // The field access is moved out of the string, which is only
+                // correct for the last placeholder.
+                let after_cursor = &text[usize::from(cursor - string_range.start())..];
+                if after_cursor.contains('{') {
+                    return None;
+                }
+                let field_range = TextRange::at(cursor - TextSize::of(field), TextSize::of(field));
+                let placeholder_range = TextRange::new(placeholder_start, cursor);
+                let args_end = macro_call
+                    .token_tree()?
+                    .syntax()
+                    .last_token()
+                    .filter(|it| matches!(it.kind(), T![')'] | T![']'] | T!['}']))?
+                    .text_range()
+                    .start();
+                complete_fields(acc, ctx, receiver, field_range, placeholder_range, args_end);
+            }
+            None => complete_locals(acc, ctx, TextRange::new(placeholder_start, cursor)),
+        },
+    }
+    Some(())
+}
+
+/// Returns the offset of the brace opening the placeholder `text` ends in.
+fn open_placeholder(text: &str) -> Option<usize> {
+    let mut open = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' if open.is_none() => {
+                if chars.peek().map(|&(_, c)| c) == Some('{') {
+                    chars.next();
+                } else {
+                    open = Some(idx);
+                }
+            }
+            '}' => open = None,
+            _ => (),
+        }
+    }
+    open
+}
+
+fn complete_locals(acc: &mut Completions, ctx: &CompletionContext, range: TextRange) {
+    for (name, local) in &ctx.locals {
+        let mut item = CompletionItem::new(CompletionKind::Reference, range, name.as_str());
+        item.kind(SymbolKind::Local).detail(local.ty(ctx.db).display(ctx.db).to_string());
+        item.add_to(acc);
+    }
+}
+
+fn complete_fields(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    receiver: &str,
+    field_range: TextRange,
+    placeholder_range: TextRange,
+    args_end: TextSize,
+) {
+    let local = match ctx.locals.iter().find(|(name, _)| name == receiver) {
+        Some((_, local)) => local,
+        None => return,
+    };
+    for (field, ty) in local.ty(ctx.db).fields(ctx.db) {
+        let name = field.name(ctx.db).to_string();
+        let mut edit = TextEditBuilder::default();
+        edit.delete(placeholder_range);
+        edit.insert(args_end, format!(", {}.{}", receiver, name));
+        let mut item = CompletionItem::new(CompletionKind::Reference, field_range, name);
+        item.kind(SymbolKind::Field)
+            .detail(ty.display(ctx.db).to_string())
+            .text_edit(edit.finish());
+        item.add_to(acc);
+    }
+}
+
+const FORMAT_SPECS: &[(&str, &str)] = &[
+    ("?", "Debug"),
+    ("#?", "pretty-printed Debug"),
+    ("x", "lower hex"),
+    ("X", "upper hex"),
+    ("o", "octal"),
+    ("b", "binary"),
+    ("e", "lower exponent"),
+    (">8", "right-aligned in 8 columns"),
+    ("<8", "left-aligned in 8 columns"),
+    ("^8", "centered in 8 columns"),
+    (".2", "2 digits of precision"),
+];
+
+fn complete_specs(acc: &mut Completions, cursor: TextSize) {
+    for &(spec, description) in FORMAT_SPECS {
+        let mut item = CompletionItem::new(CompletionKind::Magic, TextRange::empty(cursor), spec);
+        item.kind(CompletionItemKind::Keyword).detail(description);
+        item.add_to(acc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::tests::{check_edit, completion_list};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let actual = completion_list(ra_fixture);
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn completes_locals() {
+        check(
+            r#"
+//- minicore: fmt
+fn main() {
+    let value = 92;
+    let name = "ferris";
+    format_args!("{} {va$0}", name);
+}
+"#,
+            expect![[r#"
+                lc name  &str
+                lc value i32
+            "#]],
+        );
+        check_edit(
+            "value",
+            r#"
+//- minicore: fmt
+fn main() {
+    let value = 92;
+    format_args!("{va$0}");
+}
+"#,
+            r#"
+fn main() {
+    let value = 92;
+    format_args!("{value}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_fields_as_arguments() {
+        check_edit(
+            "width",
+            r#"
+//- minicore: fmt
+struct Size { width: u32, height: u32 }
+fn main() {
+    let size = Size { width: 1, height: 2 };
+    format_args!("{} {size.$0}", 0);
+}
+"#,
+            r#"
+struct Size { width: u32, height: u32 }
+fn main() {
+    let size = Size { width: 1, height: 2 };
+    format_args!("{} {}", 0, size.width);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn completes_specs() {
+        check(
+            r#"
+//- minicore: fmt
+fn main() {
+    let value = 92;
+    format_args!("{value:$0}");
+}
+"#,
+            expect![[r#"
+                kw ?  Debug
+                kw #? pretty-printed Debug
+                kw x  lower hex
+                kw X  upper hex
+                kw o  octal
+                kw b  binary
+                kw e  lower exponent
+                kw >8 right-aligned in 8 columns
+                kw <8 left-aligned in 8 columns
+                kw ^8 centered in 8 columns
+                kw .2 2 digits of precision
+            "#]],
+        );
+    }
+
+    #[test]
+    fn no_completions_outside_placeholders() {
+        check(
+            r#"
+//- minicore: fmt
+fn main() {
+    let value = 92;
+    format_args!("{{va$0 {value}");
+}
+"#,
+            expect![[""]],
+        );
+    }
+}
@@ -22,15 +22,13 @@ pub(crate) fn complete_expr_keyword(acc: &mut Completions, ctx: &CompletionConte
     }
 
     // Suggest .await syntax for types that implement Future trait
-    if let Some(receiver) = ctx.dot_receiver() {
-        if let Some(ty) = ctx.sema.type_of_expr(receiver) {
-            if ty.impls_future(ctx.db) {
-                let mut item =
-                    CompletionItem::new(CompletionKind::Keyword, ctx.source_range(), "await");
-                item.kind(CompletionItemKind::Keyword).detail("expr.await");
-                item.add_to(acc);
-            }
-        };
+    if let Some(ty) = &ctx.receiver_ty {
+        if ty.impls_future(ctx.db) {
+            let mut item =
+                CompletionItem::new(CompletionKind::Keyword, ctx.source_range(), "await");
+            item.kind(CompletionItemKind::Keyword).detail("expr.await");
+            item.add_to(acc);
+        }
     }
 
     let mut add_keyword = |kw, snippet| add_keyword(ctx, acc, kw, snippet);
@@ -426,7 +424,16 @@ fn foo() {
             expect![[r#"
                 kw await expr.await
             "#]],
-        )
+        );
+
+        check(
+            r#"
+//- minicore: future
+struct A {}
+fn foo(a: A) { a.$0 }
+"#,
+            expect![[""]],
+        );
     }
 
     #[test]
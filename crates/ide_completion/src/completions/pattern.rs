@@ -1,6 +1,17 @@
 //! Completes constants and paths in patterns.
 
-use crate::{context::PatternRefutability, CompletionContext, Completions};
+use hir::StructKind;
+use ide_db::helpers::mod_path_to_ast;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel},
+    AstNode,
+};
+
+use crate::{
+    context::PatternRefutability, CompletionContext, CompletionItem, CompletionItemKind,
+    CompletionKind, Completions,
+};
 
 /// Completes constants and paths in patterns.
 pub(crate) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
@@ -10,6 +21,7 @@ pub(crate) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
     };
 
     if refutable {
+        complete_match_arms(acc, ctx);
         if let Some(hir::Adt::Enum(e)) =
             ctx.expected_type.as_ref().and_then(|ty| ty.strip_references().as_adt())
         {
@@ -55,3 +67,85 @@ pub(crate) fn complete_pattern(acc: &mut Completions, ctx: &CompletionContext) {
         }
     });
 }
+
+/// Completes all the arms of a `match` on an enum or a `bool` whose arm list
+/// is empty but for the pattern being completed.
+fn complete_match_arms(acc: &mut Completions, ctx: &CompletionContext) -> Option<()> {
+    let cap = ctx.config.snippet_cap?;
+    let arm_list = ctx.original_token.ancestors().find_map(ast::MatchArmList::cast)?;
+    let offset = ctx.position.offset;
+    let only_completed_arm = arm_list.arms().all(|arm| {
+        arm.fat_arrow_token().is_none() && arm.syntax().text_range().contains_inclusive(offset)
+    });
+    if !only_completed_arm {
+        return None;
+    }
+    let match_expr = ast::MatchExpr::cast(arm_list.syntax().parent()?)?;
+    let ty = ctx.sema.type_of_expr(&match_expr.expr()?)?;
+    let module = ctx.scope.module()?;
+    let pats = variant_pats(ctx, module, &ty.strip_references(), true)?;
+
+    let indent = IndentLevel::from_node(arm_list.syntax()) + 1;
+    let mut snippet = String::new();
+    for (idx, pat) in pats.iter().enumerate() {
+        if idx > 0 {
+            format_to!(snippet, "\n{}", indent);
+        }
+        format_to!(snippet, "{} => ${{{}:todo!()}},", pat, idx + 1);
+    }
+    let mut item =
+        CompletionItem::new(CompletionKind::Snippet, ctx.source_range(), "fill all arms");
+    item.insert_snippet(cap, snippet).kind(CompletionItemKind::Snippet);
+    item.add_to(acc);
+    Some(())
+}
+
+/// Returns a pattern for each variant of the enum `ty`. The variants with a
+/// single field of an enum type, like `Some` in `Option<Result<T, E>>`, are
+/// expanded one level deep if `expand_fields` is set.
+fn variant_pats(
+    ctx: &CompletionContext,
+    module: hir::Module,
+    ty: &hir::Type,
+    expand_fields: bool,
+) -> Option<Vec<String>> {
+    if ty.is_bool() {
+        return Some(vec!["true".to_string(), "false".to_string()]);
+    }
+    let e = match ty.as_adt()? {
+        hir::Adt::Enum(it) => it,
+        _ => return None,
+    };
+    let mut pats = Vec::new();
+    for variant in e.variants(ctx.db) {
+        let path = mod_path_to_ast(&module.find_use_path(ctx.db, hir::ModuleDef::from(variant))?);
+        let fields = ty.variant_fields(ctx.db, variant);
+        match variant.kind(ctx.db) {
+            StructKind::Unit => pats.push(path.to_string()),
+            StructKind::Tuple => {
+                let nested = match fields.as_slice() {
+                    [(_, field_ty)] if expand_fields => variant_pats(ctx, module, field_ty, false),
+                    _ => None,
+                };
+                match nested {
+                    Some(nested) => {
+                        pats.extend(nested.iter().map(|it| format!("{}({})", path, it)))
+                    }
+                    None => {
+                        let wildcards = vec!["_"; fields.len()].join(", ");
+                        pats.push(format!("{}({})", path, wildcards));
+                    }
+                }
+            }
+            StructKind::Record => {
+                let names = fields
+                    .iter()
+                    .map(|(field, _)| field.name(ctx.db).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                pats.push(format!("{} {{ {} }}", path, names));
+            }
+        }
+    }
+    Some(pats)
+}
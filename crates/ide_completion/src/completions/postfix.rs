@@ -37,7 +37,7 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
 
     let receiver_text = get_receiver_text(dot_receiver, receiver_is_ambiguous_float_literal);
 
-    let receiver_ty = match ctx.sema.type_of_expr(dot_receiver) {
+    let receiver_ty = match &ctx.receiver_ty {
         Some(it) => it,
         None => return,
     };
@@ -172,6 +172,20 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
                 .add_to(acc);
             }
         },
+        None if receiver_ty.strip_references().is_bool() => {
+            postfix_snippet(
+                ctx,
+                cap,
+                &dot_receiver,
+                "match",
+                "match expr {}",
+                &format!(
+                    "match {} {{\n    true => {{$1}},\n    false => {{$0}},\n}}",
+                    receiver_text
+                ),
+            )
+            .add_to(acc);
+        }
         None => {
             postfix_snippet(
                 ctx,
@@ -276,6 +290,31 @@ pub(crate) fn complete_postfix(acc: &mut Completions, ctx: &CompletionContext) {
             add_format_like_completions(acc, ctx, &dot_receiver, cap, &literal_text);
         }
     }
+
+    add_custom_postfix_completions(acc, ctx, cap, &dot_receiver, &receiver_text, receiver_ty);
+}
+
+fn add_custom_postfix_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext,
+    cap: SnippetCap,
+    receiver: &ast::Expr,
+    receiver_text: &str,
+    receiver_ty: &hir::Type,
+) {
+    let receiver_ty = receiver_ty.strip_references();
+    let receiver_ty_name = match receiver_ty.as_adt() {
+        Some(adt) => adt.name(ctx.db).to_string(),
+        None => receiver_ty.display(ctx.db).to_string(),
+    };
+    for snippet in &ctx.config.postfix_snippets {
+        if snippet.receiver_type.as_ref().map_or(false, |it| *it != receiver_ty_name) {
+            continue;
+        }
+        let detail = snippet.description.as_deref().unwrap_or(&snippet.template);
+        let text = snippet.template.replace("$receiver", receiver_text);
+        postfix_snippet(ctx, cap, receiver, &snippet.label, detail, &text).add_to(acc);
+    }
 }
 
 fn get_receiver_text(receiver: &ast::Expr, receiver_is_ambiguous_float_literal: bool) -> String {
@@ -328,8 +367,11 @@ mod tests {
     use expect_test::{expect, Expect};
 
     use crate::{
-        tests::{check_edit, filtered_completion_list},
-        CompletionKind,
+        tests::{
+            check_edit, check_edit_with_config, filtered_completion_list,
+            filtered_completion_list_with_config, TEST_CONFIG,
+        },
+        CompletionConfig, CompletionKind, PostfixSnippet,
     };
 
     fn check(ra_fixture: &str, expect: Expect) {
@@ -497,6 +539,28 @@ fn main() {
         );
     }
 
+    #[test]
+    fn bool_match() {
+        check_edit(
+            "match",
+            r#"
+fn main() {
+    let bar = true;
+    bar.$0
+}
+"#,
+            r#"
+fn main() {
+    let bar = true;
+    match bar {
+    true => {$1},
+    false => {$0},
+}
+}
+"#,
+        );
+    }
+
     #[test]
     fn postfix_completion_works_for_ambiguous_float_literal() {
         check_edit("refm", r#"fn main() { 42.$0 }"#, r#"fn main() { &mut 42 }"#)
@@ -587,4 +651,62 @@ fn main() {
             r#"fn main() { log::error!("{}", 2+2) }"#,
         );
     }
+
+    fn custom_snippets_config() -> CompletionConfig {
+        CompletionConfig {
+            postfix_snippets: vec![
+                PostfixSnippet {
+                    label: "arc".to_string(),
+                    template: "Arc::new($receiver)".to_string(),
+                    description: None,
+                    receiver_type: None,
+                },
+                PostfixSnippet {
+                    label: "unwrapd".to_string(),
+                    template: "$receiver.unwrap_or_default()".to_string(),
+                    description: Some("unwrap or default".to_string()),
+                    receiver_type: Some("Result".to_string()),
+                },
+            ],
+            ..TEST_CONFIG
+        }
+    }
+
+    #[test]
+    fn custom_postfix_completions() {
+        check_edit_with_config(
+            custom_snippets_config(),
+            "arc",
+            r#"fn main() { &42.$0 }"#,
+            r#"fn main() { Arc::new(&42) }"#,
+        );
+        check_edit_with_config(
+            custom_snippets_config(),
+            "unwrapd",
+            r#"
+//- minicore: result
+fn main() {
+    let bar: Result<u32, ()> = Ok(1);
+    bar.$0
+}
+"#,
+            r#"
+fn main() {
+    let bar: Result<u32, ()> = Ok(1);
+    bar.unwrap_or_default()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn custom_postfix_completions_filter_receiver_type() {
+        let list = filtered_completion_list_with_config(
+            custom_snippets_config(),
+            r#"fn main() { 42.$0 }"#,
+            CompletionKind::Postfix,
+        );
+        assert!(list.contains("arc"));
+        assert!(!list.contains("unwrapd"));
+    }
 }
@@ -27,7 +27,9 @@
 //! # }
 //!
 //! impl SomeTrait for () {
-//!     fn foo() {}$0
+//!     fn foo() {
+//!         todo!()
+//!     }
 //! }
 //! ```
 
@@ -162,12 +164,12 @@ fn add_function_impl(
             let function_decl = function_declaration(&transformed_fn);
             match ctx.config.snippet_cap {
                 Some(cap) => {
-                    let snippet = format!("{} {{\n    $0\n}}", function_decl);
+                    let snippet = format!("{} {{\n    ${{0:todo!()}}\n}}", function_decl);
                     item.snippet_edit(cap, TextEdit::replace(range, snippet));
                 }
                 None => {
-                    let header = format!("{} {{", function_decl);
-                    item.text_edit(TextEdit::replace(range, header));
+                    let function = format!("{} {{\n    todo!()\n}}", function_decl);
+                    item.text_edit(TextEdit::replace(range, function));
                 }
             };
             item.kind(completion_kind);
@@ -536,7 +538,7 @@ struct T;
 
 impl Test for T {
     fn test() {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -565,7 +567,7 @@ struct T;
 
 impl Test for T {
     fn test() {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -615,7 +617,7 @@ struct T;
 
 impl Test for T {
     fn foo<T>() {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -641,7 +643,7 @@ struct T;
 impl Test for T {
     fn foo<T>()
 where T: Into<String> {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -772,7 +774,7 @@ impl Test for T {{
             "default type OtherType = i32;",
             "default const OTHER_CONST: i32 = 0;",
         ] {
-            test("bar", "fn $0", "fn bar() {\n    $0\n}", next_sibling);
+            test("bar", "fn $0", "fn bar() {\n    ${0:todo!()}\n}", next_sibling);
             test("Foo", "type $0", "type Foo = ", next_sibling);
             test("CONST", "const $0", "const CONST: u16 = ", next_sibling);
         }
@@ -819,7 +821,7 @@ impl Foo for T {{
                 ),
             )
         };
-        test("function", "fn f$0", "fn function() {\n    $0\n}");
+        test("function", "fn f$0", "fn function() {\n    ${0:todo!()}\n}");
         test("Type", "type T$0", "type Type = ");
         test("CONST", "const C$0", "const CONST: i32 = ");
     }
@@ -846,7 +848,7 @@ struct Bar;
 
 impl Foo<u32> for Bar {
     fn function() -> u32 {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -875,7 +877,7 @@ struct Bar;
 
 impl Foo<u32> for Bar {
     fn function(bar: u32) {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -904,7 +906,7 @@ struct Bar;
 
 impl Foo<u32> for Bar {
     fn function(bar: Vec<u32>) {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -933,7 +935,7 @@ struct Bar;
 
 impl Foo<u32, Vec<usize>, u8> for Bar {
     fn function(bar: Vec<u32>, baz: Vec<usize>) -> Arc<Vec<u8>> {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -996,7 +998,7 @@ struct Bar;
 impl Foo<u32> for Bar {
     fn function()
 where Self: SomeTrait<u32> {
-    $0
+    ${0:todo!()}
 }
 }
 "#,
@@ -10,9 +10,30 @@ use ide_db::helpers::{insert_use::InsertUseConfig, SnippetCap};
 pub struct CompletionConfig {
     pub enable_postfix_completions: bool,
     pub enable_imports_on_the_fly: bool,
+    /// Whether imports on the fly also search the methods of traits which
+    /// aren't in scope after a `.`, which can be slow.
+    pub enable_trait_methods_on_the_fly: bool,
     pub enable_self_on_the_fly: bool,
     pub add_call_parenthesis: bool,
     pub add_call_argument_snippets: bool,
     pub snippet_cap: Option<SnippetCap>,
     pub insert_use: InsertUseConfig,
+    pub postfix_snippets: Vec<PostfixSnippet>,
+    /// The names of lints known besides the built-in ones, like the ones
+    /// reported by `cargo check`, completed in lint attributes.
+    pub additional_lints: Vec<String>,
+}
+
+/// A postfix completion defined by the user, offered alongside the built-in
+/// ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PostfixSnippet {
+    pub label: String,
+    /// The snippet which replaces the receiver and the label. `$receiver` in
+    /// it is replaced with the text of the receiver.
+    pub template: String,
+    pub description: Option<String>,
+    /// The name of the type the receiver must have, like `Result` or `bool`,
+    /// ignoring references.
+    pub receiver_type: Option<String>,
 }
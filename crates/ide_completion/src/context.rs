@@ -93,6 +93,9 @@ pub(crate) struct CompletionContext<'a> {
     pub(super) is_param: bool,
 
     pub(super) completion_location: Option<ImmediateLocation>,
+    /// The type of the receiver of the field access or method call being
+    /// completed.
+    pub(super) receiver_ty: Option<Type>,
     pub(super) prev_sibling: Option<ImmediatePrevSibling>,
     pub(super) attribute_under_caret: Option<ast::Attr>,
     pub(super) previous_token: Option<SyntaxToken>,
@@ -159,6 +162,7 @@ impl<'a> CompletionContext<'a> {
             is_pat_or_const: None,
             is_param: false,
             completion_location: None,
+            receiver_ty: None,
             prev_sibling: None,
             attribute_under_caret: None,
             previous_token: None,
@@ -502,6 +506,7 @@ impl<'a> CompletionContext<'a> {
         };
         self.completion_location =
             determine_location(&self.sema, original_file, offset, &name_like);
+        self.receiver_ty = self.dot_receiver().and_then(|it| self.sema.type_of_expr(it));
         self.prev_sibling = determine_prev_sibling(&name_like);
         match name_like {
             ast::NameLike::Lifetime(lifetime) => {
@@ -69,7 +69,7 @@ pub struct CompletionItem {
     /// Note that Relevance ignores fuzzy match score. We compute Relevance for
     /// all possible items, and then separately build an ordered completion list
     /// based on relevance and fuzzy matching with the already typed identifier.
-    relevance: CompletionRelevance,
+    pub(crate) relevance: CompletionRelevance,
 
     /// Indicates that a reference or mutable reference to this variable is a
     /// possible match.
@@ -144,6 +144,10 @@ pub struct CompletionRelevance {
     /// }
     /// ```
     pub is_local: bool,
+    /// This is set for the items imported on the fly which other modules of
+    /// the current crate already import, like the commonly used
+    /// `std::collections::HashMap`.
+    pub is_used_in_crate: bool,
     /// This is set in cases like these:
     ///
     /// ```
@@ -203,6 +207,9 @@ impl CompletionRelevance {
         if self.is_local {
             score += 1;
         }
+        if self.is_used_in_crate {
+            score += 1;
+        }
         if self.exact_postfix_snippet_match {
             score += 100;
         }
@@ -595,6 +602,7 @@ mod tests {
             vec![
                 CompletionRelevance { exact_name_match: true, ..CompletionRelevance::default() },
                 CompletionRelevance { is_local: true, ..CompletionRelevance::default() },
+                CompletionRelevance { is_used_in_crate: true, ..CompletionRelevance::default() },
             ],
             vec![CompletionRelevance {
                 exact_name_match: true,
@@ -624,6 +632,7 @@ mod tests {
                 exact_name_match: false,
                 type_match: None,
                 is_local: false,
+                is_used_in_crate: false,
                 exact_postfix_snippet_match: true,
             }],
         ];
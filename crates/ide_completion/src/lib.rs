@@ -24,7 +24,7 @@ use text_edit::TextEdit;
 use crate::{completions::Completions, context::CompletionContext, item::CompletionKind};
 
 pub use crate::{
-    config::CompletionConfig,
+    config::{CompletionConfig, PostfixSnippet},
     item::{CompletionItem, CompletionItemKind, CompletionRelevance, ImportEdit},
 };
 
@@ -148,7 +148,10 @@ pub fn completions(
 
     let mut acc = Completions::default();
     completions::attribute::complete_attribute(&mut acc, &ctx);
+    completions::builtin_macro_arg::complete_builtin_macro_arg(&mut acc, &ctx);
+    completions::closure::complete_closure_arg(&mut acc, &ctx);
     completions::fn_param::complete_fn_param(&mut acc, &ctx);
+    completions::format_string::complete_format_string(&mut acc, &ctx);
     completions::keyword::complete_expr_keyword(&mut acc, &ctx);
     completions::snippet::complete_expr_snippet(&mut acc, &ctx);
     completions::snippet::complete_item_snippet(&mut acc, &ctx);
@@ -389,6 +389,7 @@ mod tests {
                 ),
                 (relevance.exact_name_match, "name"),
                 (relevance.is_local, "local"),
+                (relevance.is_used_in_crate, "used"),
                 (relevance.exact_postfix_snippet_match, "snippet"),
             ]
             .into_iter()
@@ -541,6 +542,7 @@ fn main() { let _: m::Spam = S$0 }
                                 Exact,
                             ),
                             is_local: false,
+                            is_used_in_crate: false,
                             exact_postfix_snippet_match: false,
                         },
                         trigger_call_info: true,
@@ -570,6 +572,7 @@ fn main() { let _: m::Spam = S$0 }
                                 Exact,
                             ),
                             is_local: false,
+                            is_used_in_crate: false,
                             exact_postfix_snippet_match: false,
                         },
                     },
@@ -664,6 +667,7 @@ fn foo() { A { the$0 } }
                                 CouldUnify,
                             ),
                             is_local: false,
+                            is_used_in_crate: false,
                             exact_postfix_snippet_match: false,
                         },
                     },
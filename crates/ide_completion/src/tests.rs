@@ -53,6 +53,7 @@ pub macro Clone {}
 pub(crate) const TEST_CONFIG: CompletionConfig = CompletionConfig {
     enable_postfix_completions: true,
     enable_imports_on_the_fly: true,
+    enable_trait_methods_on_the_fly: true,
     enable_self_on_the_fly: true,
     add_call_parenthesis: true,
     add_call_argument_snippets: true,
@@ -64,6 +65,8 @@ pub(crate) const TEST_CONFIG: CompletionConfig = CompletionConfig {
         group: true,
         skip_glob_imports: true,
     },
+    postfix_snippets: Vec::new(),
+    additional_lints: Vec::new(),
 };
 
 pub(crate) fn completion_list(code: &str) -> String {
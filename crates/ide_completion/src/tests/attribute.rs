@@ -1,7 +1,10 @@
 //! Completion tests for attributes.
 use expect_test::{expect, Expect};
 
-use crate::tests::{check_edit, completion_list};
+use crate::{
+    tests::{check_edit, check_edit_with_config, completion_list, TEST_CONFIG},
+    CompletionConfig,
+};
 
 fn check(ra_fixture: &str, expect: Expect) {
     let actual = completion_list(ra_fixture);
@@ -35,7 +38,7 @@ use self as this;
 
 #[test]
 fn inside_nested_attr() {
-    check(r#"#[cfg($0)]"#, expect![[]])
+    check(r#"#[doc($0)]"#, expect![[]])
 }
 
 #[test]
@@ -443,6 +446,49 @@ fn attr_on_variant() {
     );
 }
 
+#[test]
+fn attr_on_variant_of_derive_with_helpers() {
+    check(
+        r#"
+//- proc_macros: derive_identity, derive_with_helpers
+#[derive(proc_macros::DeriveWithHelpers, proc_macros::DeriveIdentity)]
+enum Foo { #[$0] Bar }
+"#,
+        expect![[r#"
+            at allow(…)
+            at cfg(…)
+            at cfg_attr(…)
+            at deny(…)
+            at forbid(…)
+            at warn(…)
+            at non_exhaustive
+            at helper         derive(DeriveWithHelpers)
+        "#]],
+    );
+}
+
+#[test]
+fn derive_helpers_on_record_field() {
+    check_edit(
+        "helper",
+        r#"
+//- proc_macros: derive_with_helpers
+#[derive(proc_macros::DeriveWithHelpers)]
+struct Foo {
+    #[hel$0]
+    field: u32,
+}
+"#,
+        r#"
+#[derive(proc_macros::DeriveWithHelpers)]
+struct Foo {
+    #[helper]
+    field: u32,
+}
+"#,
+    );
+}
+
 #[test]
 fn attr_on_fn() {
     check(
@@ -553,6 +599,83 @@ mod cfg {
 "#]],
         );
     }
+
+    #[test]
+    fn cfg_keys() {
+        check(
+            r#"#[cfg($0)]"#,
+            expect![[r#"
+                at debug_assertions
+                at doc
+                at feature
+                at panic
+                at target_arch
+                at target_endian
+                at target_env
+                at target_family
+                at target_feature
+                at target_os
+                at target_pointer_width
+                at target_vendor
+                at test
+                at unix
+                at windows
+        "#]],
+        );
+        check(
+            r#"
+//- /main.rs crate:main cfg:feature=std,my_flag
+#[cfg(all(unix, $0))]
+fn f() {}
+"#,
+            expect![[r#"
+                at debug_assertions
+                at doc
+                at feature
+                at my_flag
+                at panic
+                at target_arch
+                at target_endian
+                at target_env
+                at target_family
+                at target_feature
+                at target_os
+                at target_pointer_width
+                at target_vendor
+                at test
+                at unix
+                at windows
+        "#]],
+        );
+    }
+
+    #[test]
+    fn cfg_feature_values() {
+        check(
+            r#"
+//- /main.rs crate:main cfg:feature=std,feature=serde
+#[cfg(feature = $0)]
+fn f() {}
+"#,
+            expect![[r#"
+                at serde
+                at std
+            "#]],
+        );
+    }
+
+    #[test]
+    fn cfg_attr_predicate() {
+        check(
+            r#"#[cfg_attr(target_pointer_width = $0, derive(Debug))]"#,
+            expect![[r#"
+                at 16
+                at 32
+                at 64
+            "#]],
+        );
+        check(r#"#[cfg_attr(unix, $0)]"#, expect![[]]);
+    }
 }
 
 mod derive {
@@ -672,6 +795,19 @@ mod lint {
         )
     }
 
+    #[test]
+    fn lint_additional() {
+        check_edit_with_config(
+            CompletionConfig {
+                additional_lints: vec!["clippy::brand_new_lint".to_string()],
+                ..TEST_CONFIG
+            },
+            "clippy::brand_new_lint",
+            r#"#[warn($0)] struct Test;"#,
+            r#"#[warn(clippy::brand_new_lint)] struct Test;"#,
+        )
+    }
+
     #[test]
     fn lint_feature() {
         check_edit(
@@ -680,6 +816,44 @@ mod lint {
             r#"#[feature(box_syntax)] struct Test;"#,
         )
     }
+
+    #[test]
+    fn no_features_on_stable() {
+        check(
+            r#"
+//- toolchain: stable
+#![feature(box_$0)]
+"#,
+            expect![[]],
+        );
+        check(
+            r#"
+//- toolchain: 1.54.0
+#![$0]
+"#,
+            expect![[r#"
+                at allow(…)
+                at cfg(…)
+                at cfg_attr(…)
+                at deny(…)
+                at forbid(…)
+                at warn(…)
+                at deprecated
+                at doc = "…"
+                at doc(hidden)
+                at doc(alias = "…")
+                at must_use
+                at no_mangle
+                at crate_name = ""
+                at no_implicit_prelude
+                at no_main
+                at no_std
+                at recursion_limit = …
+                at type_length_limit = …
+                at windows_subsystem = "…"
+            "#]],
+        );
+    }
 }
 
 mod repr {
@@ -1,7 +1,7 @@
 //! Completion tests for pattern position.
 use expect_test::{expect, Expect};
 
-use crate::tests::{completion_list, BASE_FIXTURE};
+use crate::tests::{check_edit, completion_list, BASE_FIXTURE};
 
 fn check(ra_fixture: &str, expect: Expect) {
     let actual = completion_list(ra_fixture);
@@ -298,10 +298,11 @@ impl Foo {
     "#,
         expect![[r#"
             kw mut
-            bn Self::Bar Self::Bar { baz$1 }$0
-            ev Self::Bar { baz: i32 }
-            bn Foo::Bar  Foo::Bar { baz$1 }$0
-            ev Foo::Bar  { baz: i32 }
+            sn fill all arms
+            bn Self::Bar     Self::Bar { baz$1 }$0
+            ev Self::Bar     { baz: i32 }
+            bn Foo::Bar      Foo::Bar { baz$1 }$0
+            ev Foo::Bar      { baz: i32 }
             sp Self
             en Foo
         "#]],
@@ -337,3 +338,69 @@ fn outer(Foo { bar$0 }: Foo) {}
         expect![[r#""#]],
     )
 }
+
+#[test]
+fn completes_all_match_arms() {
+    check_edit(
+        "fill all arms",
+        r#"
+enum Shape { Circle { radius: u32 }, Rect(u32, u32), Empty }
+fn area(shape: &Shape) {
+    match shape {
+        $0
+    }
+}
+"#,
+        r#"
+enum Shape { Circle { radius: u32 }, Rect(u32, u32), Empty }
+fn area(shape: &Shape) {
+    match shape {
+        Shape::Circle { radius } => ${1:todo!()},
+        Shape::Rect(_, _) => ${2:todo!()},
+        Shape::Empty => ${3:todo!()},
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn completes_nested_match_arms() {
+    check_edit(
+        "fill all arms",
+        r#"
+//- minicore: option, result
+fn f(x: Option<Result<i32, ()>>) {
+    match x {
+        S$0
+    }
+}
+"#,
+        r#"
+fn f(x: Option<Result<i32, ()>>) {
+    match x {
+        None => ${1:todo!()},
+        Some(Ok(_)) => ${2:todo!()},
+        Some(Err(_)) => ${3:todo!()},
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn no_match_arms_completion_with_existing_arms() {
+    check(
+        r#"
+fn f(x: bool) {
+    match x {
+        true => (),
+        $0
+    }
+}
+"#,
+        expect![[r#"
+            kw mut
+        "#]],
+    );
+}
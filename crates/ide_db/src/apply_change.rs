@@ -4,10 +4,10 @@ use std::{fmt, sync::Arc};
 
 use base_db::{
     salsa::{Database, Durability, SweepStrategy},
-    Change, FileId, SourceRootId,
+    Change, FileId, SourceDatabase, SourceDatabaseExt, SourceRootId,
 };
 use profile::{memory_usage, Bytes};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{symbol_index::SymbolsDatabase, RootDatabase};
 
@@ -211,6 +211,9 @@ impl RootDatabase {
 
             // LineIndexDatabase
             crate::LineIndexQuery
+
+            // ProcMacrosDatabase
+            crate::proc_macros::DeriveHelpersQuery
         ];
 
         // To collect interned data, we need to bump the revision counter by performing a synthetic
@@ -241,4 +244,35 @@ impl RootDatabase {
         acc.sort_by_key(|it| std::cmp::Reverse(it.1));
         acc
     }
+
+    /// Returns the crates, the number of files and the length of the source
+    /// text in bytes of each source root containing crates, largest first.
+    ///
+    /// This is the size of the text only, not the memory used to analyze it.
+    /// Crates sharing a source root, like the library and the binaries of a
+    /// package, are listed together so that their files are counted once.
+    pub fn per_source_root_text_size(&self) -> Vec<(Vec<String>, usize, usize)> {
+        let crate_graph = self.crate_graph();
+        let mut crates_per_root: FxHashMap<SourceRootId, Vec<String>> = FxHashMap::default();
+        for krate in crate_graph.iter() {
+            let data = &crate_graph[krate];
+            let name = data
+                .display_name
+                .as_ref()
+                .map_or_else(|| format!("{:?}", krate), |it| it.to_string());
+            let root = self.file_source_root(data.root_file_id);
+            crates_per_root.entry(root).or_default().push(name);
+        }
+        let mut acc: Vec<(Vec<String>, usize, usize)> = crates_per_root
+            .into_iter()
+            .map(|(root, mut crates)| {
+                crates.sort();
+                let source_root = self.source_root(root);
+                let len = source_root.iter().map(|file_id| self.file_text(file_id).len()).sum();
+                (crates, source_root.iter().count(), len)
+            })
+            .collect();
+        acc.sort_by_key(|it| std::cmp::Reverse(it.2));
+        acc
+    }
 }
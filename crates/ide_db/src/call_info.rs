@@ -1,15 +1,19 @@
 //! This crate provides primitives for tracking the information about a call site.
 use base_db::FilePosition;
 use either::Either;
-use hir::{HasAttrs, HirDisplay, Semantics, Type};
+use hir::{HasAttrs, HirDisplay, PathResolution, Semantics, StructKind, Type};
 use stdx::format_to;
 use syntax::{
     algo,
     ast::{self, ArgListOwner, NameOwner},
-    match_ast, AstNode, Direction, SyntaxNode, SyntaxToken, TextRange, TextSize,
+    match_ast, AstNode, AstToken, Direction, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken,
+    TextRange, TextSize, T,
 };
 
-use crate::RootDatabase;
+use crate::{
+    helpers::{format_placeholders, FORMAT_MACROS},
+    RootDatabase,
+};
 
 /// Contains information about a call site. Specifically the
 /// `FunctionSignature`and current parameter.
@@ -29,7 +33,7 @@ impl CallInfo {
         &self.parameters
     }
     fn push_param(&mut self, param: &str) {
-        if !self.signature.ends_with('(') {
+        if !self.signature.ends_with('(') && !self.signature.ends_with("{ ") {
             self.signature.push_str(", ");
         }
         let start = TextSize::of(&self.signature);
@@ -39,7 +43,8 @@ impl CallInfo {
     }
 }
 
-/// Computes parameter information for the given call expression.
+/// Computes parameter information for the given call expression, record
+/// literal, tuple struct pattern or format macro call.
 pub fn call_info(db: &RootDatabase, position: FilePosition) -> Option<CallInfo> {
     let sema = Semantics::new(db);
     let file = sema.parse(position.file_id);
@@ -50,8 +55,34 @@ pub fn call_info(db: &RootDatabase, position: FilePosition) -> Option<CallInfo>
         // if the cursor is sandwiched between two space tokens and the call is unclosed
         // this prevents us from leaving the CallExpression
         .and_then(|tok| algo::skip_trivia_token(tok, Direction::Prev))?;
+    if let Some(res) = format_call_info(&token) {
+        return Some(res);
+    }
     let token = sema.descend_into_macros(token);
 
+    // Record literals and tuple struct patterns take precedence over the calls
+    // they are in.
+    let node = token.parent()?;
+    let call = FnCallNode::with_node(&node);
+    let fields_owner = node
+        .ancestors()
+        .find(|it| matches!(it.kind(), SyntaxKind::RECORD_EXPR | SyntaxKind::TUPLE_STRUCT_PAT));
+    let fields_owner = fields_owner.filter(|owner| {
+        call.map_or(true, |call| call.syntax().text_range().contains_range(owner.text_range()))
+    });
+    if let Some(owner) = fields_owner {
+        let res = match_ast! {
+            match owner {
+                ast::RecordExpr(it) => record_call_info(&sema, &it, &token),
+                ast::TupleStructPat(it) => tuple_struct_pat_call_info(&sema, &it, &token),
+                _ => None,
+            }
+        };
+        if res.is_some() {
+            return res;
+        }
+    }
+
     let (callable, active_parameter) = call_info_impl(&sema, token)?;
 
     let mut res =
@@ -110,6 +141,184 @@ pub fn call_info(db: &RootDatabase, position: FilePosition) -> Option<CallInfo>
     Some(res)
 }
 
+fn record_call_info(
+    sema: &Semantics<RootDatabase>,
+    record: &ast::RecordExpr,
+    token: &SyntaxToken,
+) -> Option<CallInfo> {
+    let db = sema.db;
+    let field_list = record.record_expr_field_list()?;
+    if !field_list.syntax().text_range().contains_inclusive(token.text_range().start()) {
+        return None;
+    }
+    let variant = sema.resolve_variant(record.clone())?;
+    let fields = variant.fields(db);
+
+    let field_name = |field: &ast::RecordExprField| Some(field.field_name()?.to_string());
+    let position = |name: String| fields.iter().position(|it| it.name(db).to_string() == name);
+    let current_field = token
+        .parent()?
+        .ancestors()
+        .take_while(|it| it != field_list.syntax())
+        .find_map(ast::RecordExprField::cast);
+    // Outside of a field, the first field which isn't specified yet is active.
+    let active_parameter = match current_field {
+        Some(field) => field_name(&field).and_then(position),
+        None => {
+            let present = field_list.fields().filter_map(|it| field_name(&it)).collect::<Vec<_>>();
+            fields.iter().position(|it| !present.contains(&it.name(db).to_string()))
+        }
+    };
+
+    let mut res = variant_call_info(db, variant, active_parameter);
+    if fields.is_empty() {
+        res.signature.push_str(" {}");
+        return Some(res);
+    }
+    res.signature.push_str(" { ");
+    for field in fields {
+        res.push_param(&format!("{}: {}", field.name(db), field.ty(db).display(db)));
+    }
+    res.signature.push_str(" }");
+    Some(res)
+}
+
+fn tuple_struct_pat_call_info(
+    sema: &Semantics<RootDatabase>,
+    pat: &ast::TupleStructPat,
+    token: &SyntaxToken,
+) -> Option<CallInfo> {
+    let db = sema.db;
+    if token.text_range().start() < pat.l_paren_token()?.text_range().start() {
+        return None;
+    }
+    let variant = match sema.resolve_path(&pat.path()?)? {
+        PathResolution::Def(hir::ModuleDef::Adt(hir::Adt::Struct(it)))
+            if it.kind(db) == StructKind::Tuple =>
+        {
+            hir::VariantDef::from(it)
+        }
+        PathResolution::Def(hir::ModuleDef::Variant(it)) if it.kind(db) == StructKind::Tuple => {
+            hir::VariantDef::from(it)
+        }
+        _ => return None,
+    };
+
+    let mut before_rest = true;
+    let mut active_parameter = 0;
+    for field in pat.fields() {
+        if field.syntax().text_range().end() > token.text_range().start() {
+            break;
+        }
+        before_rest &= !matches!(field, ast::Pat::RestPat(_));
+        active_parameter += 1;
+    }
+    // The fields after a `..` can't be counted from the start.
+    let active_parameter = if before_rest { Some(active_parameter) } else { None };
+
+    let mut res = variant_call_info(db, variant, active_parameter);
+    res.signature.push('(');
+    for field in variant.fields(db) {
+        res.push_param(&field.ty(db).display(db).to_string());
+    }
+    res.signature.push(')');
+    Some(res)
+}
+
+/// Starts the signature of a struct, union or enum variant, with its
+/// documentation.
+fn variant_call_info(
+    db: &RootDatabase,
+    variant: hir::VariantDef,
+    active_parameter: Option<usize>,
+) -> CallInfo {
+    let mut res =
+        CallInfo { doc: None, signature: String::new(), parameters: vec![], active_parameter };
+    match variant {
+        hir::VariantDef::Struct(it) => {
+            res.doc = it.docs(db).map(|it| it.into());
+            format_to!(res.signature, "struct {}", it.name(db));
+        }
+        hir::VariantDef::Union(it) => {
+            res.doc = it.docs(db).map(|it| it.into());
+            format_to!(res.signature, "union {}", it.name(db));
+        }
+        hir::VariantDef::Variant(it) => {
+            res.doc = it.docs(db).map(|it| it.into());
+            format_to!(res.signature, "enum {}::{}", it.parent_enum(db).name(db), it.name(db));
+        }
+    }
+    res
+}
+
+/// Shows the placeholders of the format string of a format macro call, with
+/// the one filled by the argument under the cursor active.
+fn format_call_info(token: &SyntaxToken) -> Option<CallInfo> {
+    let tt = ast::TokenTree::cast(token.parent()?)?;
+    let macro_call = ast::MacroCall::cast(tt.syntax().parent()?)?;
+    let name = macro_call.path()?.segment()?.name_ref()?;
+    let &(_, format_string_idx) =
+        FORMAT_MACROS.iter().find(|(it, _)| *it == name.text().as_str())?;
+
+    let mut elements = tt.syntax().children_with_tokens().skip(1).collect::<Vec<_>>();
+    if matches!(elements.last().map(|it| it.kind()), Some(T![')']) | Some(T![']']) | Some(T!['}']))
+    {
+        elements.pop();
+    }
+    let mut args: Vec<Vec<SyntaxElement>> = vec![Vec::new()];
+    let mut cursor_arg = 0;
+    for element in elements {
+        if element.kind() == T![,] {
+            if element.text_range().start() <= token.text_range().start() {
+                cursor_arg += 1;
+            }
+            args.push(Vec::new());
+        } else if !element.kind().is_trivia() {
+            args.last_mut()?.push(element);
+        }
+    }
+
+    let format_string = args.get(format_string_idx)?.first()?.as_token()?.clone();
+    let format_string = ast::String::cast(format_string)?;
+    let format_args = &args[format_string_idx + 1..];
+    let active_arg = cursor_arg.checked_sub(format_string_idx + 1);
+    let arg_name = |arg: &[SyntaxElement]| match arg {
+        [name, eq, ..] if name.kind() == SyntaxKind::IDENT && eq.kind() == T![=] => {
+            Some(name.as_token()?.text().to_string())
+        }
+        _ => None,
+    };
+
+    let mut res = CallInfo {
+        doc: None,
+        signature: format!("{}!(", name),
+        parameters: vec![],
+        active_parameter: None,
+    };
+    let offset = TextSize::of(&res.signature);
+    let text = format_string.text();
+    let mut next_arg = 0;
+    for (open, close) in format_placeholders(text) {
+        let spec = &text[TextRange::new(open + TextSize::of('{'), close)];
+        let arg = spec.split(':').next().unwrap_or_default().trim();
+        let arg = if arg.is_empty() {
+            next_arg += 1;
+            Some(next_arg - 1)
+        } else if let Ok(idx) = arg.parse::<usize>() {
+            Some(idx)
+        } else {
+            format_args.iter().position(|it| arg_name(it).as_deref() == Some(arg))
+        };
+        if res.active_parameter.is_none() && arg.is_some() && arg == active_arg {
+            res.active_parameter = Some(res.parameters.len());
+        }
+        res.parameters.push(TextRange::new(open, close + TextSize::of('}')) + offset);
+    }
+    res.signature.push_str(text);
+    res.signature.push(')');
+    Some(res)
+}
+
 fn call_info_impl(
     sema: &Semantics<RootDatabase>,
     token: SyntaxToken,
@@ -229,6 +438,13 @@ impl FnCallNode {
         }
     }
 
+    fn syntax(&self) -> &SyntaxNode {
+        match self {
+            FnCallNode::CallExpr(expr) => expr.syntax(),
+            FnCallNode::MethodCallExpr(expr) => expr.syntax(),
+        }
+    }
+
     fn arg_list(&self) -> Option<ast::ArgList> {
         match self {
             FnCallNode::CallExpr(expr) => expr.arg_list(),
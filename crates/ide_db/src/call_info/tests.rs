@@ -563,3 +563,142 @@ fn main() {
         "#]],
     )
 }
+
+#[test]
+fn call_info_for_record_literal() {
+    check(
+        r#"
+/// A cool struct
+struct S { a: u32, b: i32, c: bool }
+fn main() {
+    let s = S { a: 0, $0 };
+}
+"#,
+        expect![[r#"
+            A cool struct
+            ------
+            struct S { a: u32, b: i32, c: bool }
+            (a: u32, <b: i32>, c: bool)
+        "#]],
+    );
+    check(
+        r#"
+struct S { a: u32, b: i32, c: bool }
+fn main() {
+    let s = S { c: true$0, a: 0 };
+}
+"#,
+        expect![[r#"
+            struct S { a: u32, b: i32, c: bool }
+            (a: u32, b: i32, <c: bool>)
+        "#]],
+    );
+}
+
+#[test]
+fn call_info_for_enum_record_literal() {
+    check(
+        r#"
+enum E {
+    /// And C
+    C { a: i32, b: i32 }
+}
+fn main() {
+    let e = E::C { $0 };
+}
+"#,
+        expect![[r#"
+            And C
+            ------
+            enum E::C { a: i32, b: i32 }
+            (<a: i32>, b: i32)
+        "#]],
+    );
+}
+
+#[test]
+fn call_info_for_nested_record_literal_and_call() {
+    check(
+        r#"
+struct S { a: u32 }
+fn foo(s: S) -> u32 { 0 }
+fn main() {
+    foo(S { $0 });
+}
+"#,
+        expect![[r#"
+            struct S { a: u32 }
+            (<a: u32>)
+        "#]],
+    );
+    check(
+        r#"
+struct S { a: u32 }
+fn foo(s: S) -> u32 { 0 }
+fn main() {
+    S { a: foo($0) };
+}
+"#,
+        expect![[r#"
+            fn foo(s: S) -> u32
+            (<s: S>)
+        "#]],
+    );
+}
+
+#[test]
+fn call_info_for_tuple_struct_pat() {
+    check(
+        r#"
+/// A cool tuple struct
+struct S(u32, i32);
+fn main(s: S) {
+    let S(a, $0) = s;
+}
+"#,
+        expect![[r#"
+            A cool tuple struct
+            ------
+            struct S(u32, i32)
+            (u32, <i32>)
+        "#]],
+    );
+    check(
+        r#"
+enum E { A(i32, bool) }
+fn main(e: E) {
+    let E::A(.., $0) = e;
+}
+"#,
+        expect![[r#"
+            enum E::A(i32, bool)
+            (i32, bool)
+        "#]],
+    );
+}
+
+#[test]
+fn call_info_for_format_macro() {
+    check(
+        r#"
+fn main() {
+    format!("{} and {:?} or {x}", 1, $0, x = 2);
+}
+"#,
+        expect![[r#"
+            format!("{} and {:?} or {x}")
+            ({}, <{:?}>, {x})
+        "#]],
+    );
+    check(
+        r#"
+fn main() {
+    writeln!(f, "{a} {{}} {b}", b = $0, a = 1);
+}
+"#,
+        expect![[r#"
+            writeln!("{a} {{}} {b}")
+            ({a}, <{b}>)
+        "#]],
+    );
+}
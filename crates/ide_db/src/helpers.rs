@@ -12,7 +12,8 @@ use either::Either;
 use hir::{Crate, Enum, ItemInNs, MacroDef, Module, ModuleDef, Name, ScopeDef, Semantics, Trait};
 use syntax::{
     ast::{self, make, LoopBodyOwner},
-    AstNode, SyntaxKind, SyntaxToken, TokenAtOffset, WalkEvent,
+    AstNode, AstToken, Direction, SyntaxKind, SyntaxToken, TextRange, TextSize, TokenAtOffset,
+    WalkEvent, T,
 };
 
 use crate::RootDatabase;
@@ -118,6 +119,10 @@ impl FamousDefs<'_, '_> {
         self.find_trait("core:convert:Into")
     }
 
+    pub fn core_convert_TryFrom(&self) -> Option<Trait> {
+        self.find_trait("core:convert:TryFrom")
+    }
+
     pub fn core_option_Option(&self) -> Option<Enum> {
         self.find_enum("core:option:Option")
     }
@@ -321,3 +326,158 @@ pub fn for_each_break_expr(
         }
     }
 }
+
+/// The macros taking a format string, with the number of arguments before it.
+pub const FORMAT_MACROS: &[(&str, usize)] = &[
+    ("format", 0),
+    ("format_args", 0),
+    ("format_args_nl", 0),
+    ("print", 0),
+    ("println", 0),
+    ("eprint", 0),
+    ("eprintln", 0),
+    ("panic", 0),
+    ("write", 1),
+    ("writeln", 1),
+];
+
+/// Returns the call of the format macro whose format string is `string`.
+pub fn format_macro_call_of_string(string: &ast::String) -> Option<ast::MacroCall> {
+    let tt = ast::TokenTree::cast(string.syntax().parent()?)?;
+    let macro_call = ast::MacroCall::cast(tt.syntax().parent()?)?;
+    let name = macro_call.path()?.segment()?.name_ref()?;
+    let &(_, format_string_idx) =
+        FORMAT_MACROS.iter().find(|(it, _)| *it == name.text().as_str())?;
+    let args_before = string
+        .syntax()
+        .siblings_with_tokens(Direction::Prev)
+        .filter(|it| it.kind() == T![,])
+        .count();
+    if args_before != format_string_idx {
+        return None;
+    }
+    Some(macro_call)
+}
+
+/// Returns the offsets of the braces of the placeholders of a format string,
+/// skipping the escaped `{{` and `}}`.
+pub fn format_placeholders(text: &str) -> Vec<(TextSize, TextSize)> {
+    let mut res = Vec::new();
+    let mut open = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        let idx = TextSize::from(idx as u32);
+        match c {
+            '{' if open.is_none() => {
+                if chars.peek().map(|&(_, c)| c) == Some('{') {
+                    chars.next();
+                } else {
+                    open = Some(idx);
+                }
+            }
+            '}' => match open.take() {
+                Some(open) => res.push((open, idx)),
+                None => {
+                    if chars.peek().map(|&(_, c)| c) == Some('}') {
+                        chars.next();
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+    res
+}
+
+/// How [`insert_whitespaces`] separates tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSpacing {
+    /// Spaces around keywords, identifiers and a few operators only.
+    Compact,
+    /// Also spaces after `:`, `,` and generic arguments, and before `{`, like rustfmt.
+    Rustfmt,
+}
+
+// FIXME: It would also be cool to share logic here and in the mbe tests,
+// which are pretty unreadable at the moment.
+/// Prints the tokens, indenting blocks by `indent_width` spaces, and calling
+/// `on_token` with the range of each of them in the result.
+pub fn insert_whitespaces(
+    tokens: Vec<SyntaxToken>,
+    indent_width: usize,
+    spacing: TokenSpacing,
+    mut on_token: impl FnMut(&SyntaxToken, TextRange),
+) -> String {
+    use SyntaxKind::*;
+
+    let mut res = String::new();
+    let mut token_iter = tokens.into_iter().peekable();
+
+    let spaced = spacing == TokenSpacing::Rustfmt;
+    let mut indent = 0;
+    let mut last: Option<SyntaxKind> = None;
+
+    while let Some(token) = token_iter.next() {
+        let mut is_next = |f: fn(SyntaxKind) -> bool, default| -> bool {
+            token_iter.peek().map(|it| f(it.kind())).unwrap_or(default)
+        };
+        let is_last =
+            |f: fn(SyntaxKind) -> bool, default| -> bool { last.map(f).unwrap_or(default) };
+
+        let before = res.len();
+        match token.kind() {
+            k if is_text(k) && is_next(|it| !it.is_punct(), true) => {
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            L_CURLY if is_next(|it| it != R_CURLY, true) => {
+                indent += 1;
+                if is_last(is_text, false) {
+                    res.push(' ');
+                }
+                res.push_str("{\n");
+                res.extend(std::iter::repeat(" ").take(indent_width * indent));
+            }
+            L_CURLY if spaced && is_last(|it| is_text(it) || it == T![>], false) => {
+                res.push_str(" {")
+            }
+            R_CURLY if is_last(|it| it != L_CURLY, true) => {
+                indent = indent.saturating_sub(1);
+                res.push('\n');
+                res.extend(std::iter::repeat(" ").take(indent_width * indent));
+                res.push_str("}");
+            }
+            R_CURLY => {
+                res.push_str("}\n");
+                res.extend(std::iter::repeat(" ").take(indent_width * indent));
+            }
+            LIFETIME_IDENT if is_next(|it| it == IDENT, true) => {
+                res.push_str(token.text());
+                res.push(' ');
+            }
+            T![;] => {
+                res.push_str(";\n");
+                res.extend(std::iter::repeat(" ").take(indent_width * indent));
+            }
+            T![>] if spaced && is_next(is_text, false) => res.push_str("> "),
+            T![:] if spaced => res.push_str(": "),
+            T![,] if spaced => res.push_str(", "),
+            T![->] => res.push_str(" -> "),
+            T![=] => res.push_str(" = "),
+            T![=>] => res.push_str(" => "),
+            _ => res.push_str(token.text()),
+        }
+
+        let start = before + res[before..].find(token.text()).unwrap_or(0);
+        let range = TextRange::at(TextSize::from(start as u32), TextSize::of(token.text()));
+        on_token(&token, range);
+
+        last = Some(token.kind());
+    }
+
+    return res;
+
+    fn is_text(k: SyntaxKind) -> bool {
+        k.is_keyword() || k.is_literal() || k == IDENT
+    }
+}
@@ -8,12 +8,13 @@ use hir::Semantics;
 use syntax::{
     algo,
     ast::{self, make, AstNode, AttrsOwner, ModuleItemOwner, PathSegmentKind, VisibilityOwner},
-    match_ast, ted, AstToken, Direction, NodeOrToken, SyntaxNode, SyntaxToken,
+    match_ast, ted, AstToken, Direction, NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken,
 };
 
 use crate::{
     helpers::merge_imports::{
-        common_prefix, eq_attrs, eq_visibility, try_merge_imports, use_tree_path_cmp, MergeBehavior,
+        common_prefix, eq_attrs, eq_visibility, path_is_self, try_merge_imports, use_tree_path_cmp,
+        MergeBehavior,
     },
     RootDatabase,
 };
@@ -205,6 +206,12 @@ pub fn insert_use<'a>(scope: &ImportScope, path: ast::Path, cfg: &InsertUseConfi
     // merge into existing imports if possible
     if let Some(mb) = mb {
         let filter = |it: &_| !(cfg.skip_glob_imports && ast::Use::is_simple_glob(it));
+        if mb == MergeBehavior::Module {
+            let existing_uses = scope.as_syntax_node().children().filter_map(ast::Use::cast);
+            for existing_use in existing_uses.filter(filter).collect::<Vec<_>>() {
+                split_into_module_imports(&existing_use, &path);
+            }
+        }
         for existing_use in
             scope.as_syntax_node().children().filter_map(ast::Use::cast).filter(filter)
         {
@@ -220,6 +227,168 @@ pub fn insert_use<'a>(scope: &ImportScope, path: ast::Path, cfg: &InsertUseConfi
     insert_use_(scope, &path, cfg.group, use_item);
 }
 
+/// Splits `use_item` into one import per module if it imports items of the
+/// module of `path` along with the ones of other modules, so that `path` can be
+/// merged into it with module granularity.
+fn split_into_module_imports(use_item: &ast::Use, path: &ast::Path) -> Option<()> {
+    let module = path.qualifier()?.to_string();
+    if use_item.attrs().next().is_some() {
+        return None;
+    }
+    let mut leaves = Vec::new();
+    flatten_use_tree(None, &use_item.use_tree()?, &mut leaves);
+    if leaves.iter().any(|(_, leaf)| leaf.star_token().is_some()) {
+        return None;
+    }
+
+    // The imported items grouped by module, in the order of the tree.
+    let mut modules: Vec<(String, Vec<ast::UseTree>)> = Vec::new();
+    for (path, leaf) in leaves {
+        let is_self = leaf.path().map_or(false, |it| path_is_self(&it));
+        let rename = leaf.rename();
+        let (module, item) = match path.qualifier() {
+            Some(qualifier) if !is_self => {
+                (qualifier.to_string(), make::path_unqualified(path.segment()?))
+            }
+            _ => (path.to_string(), make::path_from_text("self")),
+        };
+        let item = make::use_tree(item, None, rename, false);
+        match modules.iter_mut().find(|(it, _)| *it == module) {
+            Some((_, items)) => items.push(item),
+            None => modules.push((module, vec![item])),
+        }
+    }
+    if modules.len() < 2 || modules.iter().all(|(it, _)| *it != module) {
+        return None;
+    }
+
+    let indent = use_item
+        .syntax()
+        .prev_sibling_or_token()
+        .and_then(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+        .and_then(|it| it.text().rsplit('\n').next().map(ToString::to_string))
+        .unwrap_or_default();
+    let mut elements = Vec::new();
+    for (module, items) in modules {
+        let module = make::path_from_text(&module);
+        let tree = match items.as_slice() {
+            [item] => {
+                let path = item.path()?;
+                let path =
+                    if path_is_self(&path) { module } else { make::path_concat(module, path) };
+                make::use_tree(path, None, item.rename(), false)
+            }
+            _ => make::use_tree(module, Some(make::use_tree_list(items)), None, false),
+        };
+        if !elements.is_empty() {
+            elements.push(make::tokens::whitespace(&format!("\n{}", indent)).into());
+        }
+        elements.push(
+            make::use_(use_item.visibility(), tree).clone_for_update().syntax().clone().into(),
+        );
+    }
+    ted::replace_with_many(use_item.syntax(), elements);
+    Some(())
+}
+
+/// Collects the full paths imported by `tree`, which is nested in `prefix`,
+/// along with the trees importing them.
+fn flatten_use_tree(
+    prefix: Option<ast::Path>,
+    tree: &ast::UseTree,
+    acc: &mut Vec<(ast::Path, ast::UseTree)>,
+) {
+    let is_self = tree.path().map_or(false, |it| path_is_self(&it));
+    let path = match (prefix, tree.path()) {
+        (Some(prefix), Some(_)) if is_self => Some(prefix),
+        (Some(prefix), Some(path)) => Some(make::path_concat(prefix, path)),
+        (prefix, path) => path.or(prefix),
+    };
+    match tree.use_tree_list() {
+        Some(list) => {
+            for tree in list.use_trees() {
+                flatten_use_tree(path.clone(), &tree, acc);
+            }
+        }
+        None => {
+            if let Some(path) = path {
+                acc.push((path, tree.clone()));
+            }
+        }
+    }
+}
+
+/// Rebuilds `uses`, the imports of a scope, according to the configured granularity,
+/// leaving out the imported trees for which `keep` returns `false`. The new imports are
+/// sorted and, if grouping is enabled, split into import groups.
+pub fn organize_imports(
+    uses: &[ast::Use],
+    cfg: &InsertUseConfig,
+    keep: impl Fn(&ast::UseTree) -> bool,
+) -> Vec<Vec<ast::Use>> {
+    let mb = match cfg.granularity {
+        ImportGranularity::Crate => Some(MergeBehavior::Crate),
+        ImportGranularity::Module => Some(MergeBehavior::Module),
+        ImportGranularity::Item | ImportGranularity::Preserve => None,
+    };
+    let mut merged: Vec<ast::Use> = Vec::new();
+    for use_item in uses {
+        let mut leaves = Vec::new();
+        if let Some(tree) = use_item.use_tree() {
+            flatten_use_tree(None, &tree, &mut leaves);
+        }
+        // Preserved imports are only merged with the other trees of the same item.
+        let (first, mb) = match cfg.granularity {
+            ImportGranularity::Preserve => (merged.len(), Some(MergeBehavior::Crate)),
+            _ => (0, mb),
+        };
+        for (path, leaf) in leaves {
+            if !keep(&leaf) {
+                continue;
+            }
+            let tree = make::use_tree(path, None, leaf.rename(), leaf.star_token().is_some());
+            let new_use = make::use_(use_item.visibility(), tree).clone_for_update();
+            let is_merged = mb.map_or(false, |mb| {
+                merged[first..].iter_mut().any(|it| match try_merge_imports(it, &new_use, mb) {
+                    Some(res) => {
+                        *it = res;
+                        true
+                    }
+                    None => false,
+                })
+            });
+            if !is_merged {
+                merged.push(new_use);
+            }
+        }
+    }
+
+    let key = |use_item: &ast::Use| {
+        let tree = use_item.use_tree()?;
+        let path = tree.path()?;
+        Some((ImportGroup::new(&path), path, tree.use_tree_list().is_some()))
+    };
+    merged.sort_by(|a, b| match (key(a), key(b)) {
+        (Some((a_group, a_path, a_has_tl)), Some((b_group, b_path, b_has_tl))) => a_group
+            .cmp(&b_group)
+            .then_with(|| use_tree_path_cmp(&a_path, a_has_tl, &b_path, b_has_tl)),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    });
+
+    let mut groups: Vec<Vec<ast::Use>> = Vec::new();
+    let mut last_group = None;
+    for use_item in merged {
+        let group = key(&use_item).map(|(group, ..)| group);
+        match groups.last_mut() {
+            Some(last) if !cfg.group || group == last_group => last.push(use_item),
+            _ => groups.push(vec![use_item]),
+        }
+        last_group = group;
+    }
+    groups
+}
+
 #[derive(Eq, PartialEq, PartialOrd, Ord)]
 enum ImportGroup {
     // the order here defines the order of new group inserts
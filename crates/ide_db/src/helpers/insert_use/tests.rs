@@ -505,8 +505,35 @@ fn merge_groups_long_last_nested() {
     check_module(
         "std::foo::bar::Baz",
         r"use std::foo::bar::{Qux, quux::{Fez, Fizz}};",
-        r"use std::foo::bar::Baz;
-use std::foo::bar::{Qux, quux::{Fez, Fizz}};",
+        r"use std::foo::bar::{Baz, Qux};
+use std::foo::bar::quux::{Fez, Fizz};",
+    )
+}
+
+#[test]
+fn split_crate_tree_into_modules() {
+    check_module(
+        "std::collections::HashSet",
+        r"
+mod m {
+    use std::{collections::{hash_map::Entry, HashMap}, fmt::{self, Display as _}};
+}",
+        r"
+mod m {
+    use std::collections::hash_map::Entry;
+    use std::collections::{HashMap, HashSet};
+    use std::fmt::{self, Display as _};
+}",
+    )
+}
+
+#[test]
+fn dont_split_unaffected_crate_tree() {
+    check_module(
+        "std::io::Read",
+        r"use std::{collections::HashMap, fmt};",
+        r"use std::io::Read;
+use std::{collections::HashMap, fmt};",
     )
 }
 
@@ -847,6 +874,43 @@ use foo::bar::qux;
     );
 }
 
+#[test]
+fn organize_imports_module() {
+    check_organize(
+        r"
+use std::fmt::{Debug, Display};
+use crate::a::{b::C, D, E};
+use foo::*;
+use std::io;
+",
+        r"use std::fmt::{Debug, Display};
+use std::io;
+
+use foo::*;
+
+use crate::a::D;
+use crate::a::b::C;",
+        ImportGranularity::Module,
+    );
+}
+
+#[test]
+fn organize_imports_item() {
+    check_organize(
+        r"
+use std::{fmt::{Debug, Display}, io};
+use crate::a::{self, E as F};
+",
+        r"use std::fmt::Debug;
+use std::fmt::Display;
+use std::io;
+
+use crate::a;
+use crate::a::E as F;",
+        ImportGranularity::Item,
+    );
+}
+
 fn check_with_config(
     path: &str,
     ra_fixture_before: &str,
@@ -928,6 +992,26 @@ fn check_merge_only_fail(ra_fixture0: &str, ra_fixture1: &str, mb: MergeBehavior
     assert_eq!(result.map(|u| u.to_string()), None);
 }
 
+fn check_organize(ra_fixture: &str, expected: &str, granularity: ImportGranularity) {
+    let file = ast::SourceFile::parse(ra_fixture).tree();
+    let uses = file.syntax().children().filter_map(ast::Use::cast).collect::<Vec<_>>();
+    let config = InsertUseConfig {
+        granularity,
+        enforce_granularity: true,
+        prefix_kind: PrefixKind::Plain,
+        group: true,
+        skip_glob_imports: true,
+    };
+    // Leaves out the imports of `E` as unused.
+    let groups = organize_imports(&uses, &config, |tree| tree.syntax().text() != "E");
+    let result = groups
+        .iter()
+        .map(|group| group.iter().map(|it| it.to_string()).collect::<Vec<_>>().join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    assert_eq_text!(expected, &result);
+}
+
 fn check_guess(ra_fixture: &str, expected: ImportGranularityGuess) {
     let syntax = ast::SourceFile::parse(ra_fixture).tree().syntax().clone();
     let file = super::ImportScope::from(syntax).unwrap();
@@ -311,7 +311,7 @@ pub fn eq_attrs(
     stdx::iter_eq_by(attrs0, attrs1, |tok, tok2| tok.text() == tok2.text())
 }
 
-fn path_is_self(path: &ast::Path) -> bool {
+pub(super) fn path_is_self(path: &ast::Path) -> bool {
     path.segment().and_then(|seg| seg.self_token()).is_some() && path.qualifier().is_none()
 }
 
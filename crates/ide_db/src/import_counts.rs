@@ -0,0 +1,133 @@
+//! Counts how often the items are imported by the modules of a crate, to rank
+//! the items which are imported elsewhere higher when completing imports.
+
+use std::sync::Arc;
+
+use base_db::{salsa, CrateId, Upcast};
+use hir::{db::HirDatabase, Crate, ItemInNs, ModuleSource, ScopeDef};
+use rustc_hash::{FxHashMap, FxHashSet};
+use syntax::{
+    ast::{self, ModuleItemOwner, NameOwner},
+    AstNode, SmolStr,
+};
+
+#[salsa::query_group(ImportCountsDatabaseStorage)]
+pub trait ImportCountsDatabase: HirDatabase + Upcast<dyn HirDatabase> {
+    /// Counts, for each item imported by name into the modules of `krate`, the
+    /// number of modules importing it. Glob imports don't count, as they bring
+    /// many items into scope whether they are used or not.
+    fn import_counts(&self, krate: CrateId) -> Arc<FxHashMap<ItemInNs, usize>>;
+}
+
+fn import_counts(db: &dyn ImportCountsDatabase, krate: CrateId) -> Arc<FxHashMap<ItemInNs, usize>> {
+    let _p = profile::span("import_counts");
+    let db = Upcast::<dyn HirDatabase>::upcast(db);
+    let mut res = FxHashMap::default();
+    let mut modules = vec![Crate::from(krate).root_module(db)];
+    while let Some(module) = modules.pop() {
+        modules.extend(module.children(db));
+        let imported_names = match module.definition_source(db).value {
+            ModuleSource::SourceFile(it) => named_imports(it.items()),
+            ModuleSource::Module(it) => match it.item_list() {
+                Some(it) => named_imports(it.items()),
+                None => continue,
+            },
+            ModuleSource::BlockExpr(_) => continue,
+        };
+        for (name, def) in module.scope(db, None) {
+            if !imported_names.contains(name.to_string().as_str()) {
+                continue;
+            }
+            let (item, defining_module) = match def {
+                ScopeDef::ModuleDef(it) => (ItemInNs::from(it), it.module(db)),
+                ScopeDef::MacroDef(it) => (ItemInNs::from(it), it.module(db)),
+                _ => continue,
+            };
+            if defining_module.map_or(false, |it| it != module) {
+                *res.entry(item).or_insert(0) += 1;
+            }
+        }
+    }
+    Arc::new(res)
+}
+
+/// The names brought into scope by the `use` items among `items`, other than
+/// the glob imports.
+fn named_imports(items: impl Iterator<Item = ast::Item>) -> FxHashSet<SmolStr> {
+    let mut res = FxHashSet::default();
+    let use_trees = items
+        .filter_map(|item| match item {
+            ast::Item::Use(it) => it.use_tree(),
+            _ => None,
+        })
+        .flat_map(|tree| tree.syntax().descendants().filter_map(ast::UseTree::cast));
+    for tree in use_trees {
+        if tree.use_tree_list().is_some() || tree.star_token().is_some() {
+            continue;
+        }
+        if let Some(rename) = tree.rename() {
+            res.extend(rename.name().map(|it| it.text().into()));
+            continue;
+        }
+        let path = match tree.path() {
+            Some(it) => it,
+            None => continue,
+        };
+        // `use foo::{self}` and `use foo::self` import `foo`.
+        let path = match path.segment() {
+            Some(it) if it.self_token().is_some() => match path.qualifier() {
+                Some(it) => Some(it),
+                None => tree
+                    .syntax()
+                    .ancestors()
+                    .skip(1)
+                    .find_map(ast::UseTree::cast)
+                    .and_then(|it| it.path()),
+            },
+            _ => Some(path),
+        };
+        let name_ref = path.and_then(|it| it.segment()).and_then(|it| it.name_ref());
+        res.extend(name_ref.map(|it| it.text().into()));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::{fixture::WithFixture, FileLoader};
+
+    use super::*;
+    use crate::{helpers::item_name, RootDatabase};
+
+    #[test]
+    fn counts_named_imports_only() {
+        let (db, file_id) = RootDatabase::with_single_file(
+            r#"
+mod a {
+    pub struct Named;
+    pub struct Renamed;
+    pub struct Globbed;
+    pub mod m {}
+}
+mod b {
+    use crate::a::{m::{self}, Named, Renamed as R};
+    use crate::a::*;
+}
+mod c {
+    use crate::a::Named;
+}
+"#,
+        );
+        let krate = db.relevant_crates(file_id).iter().next().copied().unwrap();
+        let mut counts = db
+            .import_counts(krate)
+            .iter()
+            .map(|(&item, &count)| (item_name(&db, item).unwrap().to_string(), count))
+            .collect::<Vec<_>>();
+        counts.sort();
+        assert_eq!(
+            counts,
+            vec![("Named".to_string(), 2), ("Renamed".to_string(), 1), ("m".to_string(), 1)]
+        );
+    }
+}
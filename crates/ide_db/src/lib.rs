@@ -15,11 +15,20 @@ pub mod traits;
 pub mod call_info;
 pub mod helpers;
 pub mod path_transform;
+pub mod proc_macros;
+pub mod import_counts;
 
 pub mod search;
 pub mod rename;
 
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    panic::{self, UnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use base_db::{
     salsa::{self, Durability},
@@ -38,6 +47,8 @@ pub use base_db;
     base_db::SourceDatabaseExtStorage,
     LineIndexDatabaseStorage,
     symbol_index::SymbolsDatabaseStorage,
+    proc_macros::ProcMacrosDatabaseStorage,
+    import_counts::ImportCountsDatabaseStorage,
     hir::db::InternDatabaseStorage,
     hir::db::AstDatabaseStorage,
     hir::db::DefDatabaseStorage,
@@ -45,6 +56,7 @@ pub use base_db;
 )]
 pub struct RootDatabase {
     storage: salsa::Storage<RootDatabase>,
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl fmt::Debug for RootDatabase {
@@ -93,10 +105,11 @@ impl Default for RootDatabase {
 
 impl RootDatabase {
     pub fn new(lru_capacity: Option<usize>) -> RootDatabase {
-        let mut db = RootDatabase { storage: salsa::Storage::default() };
+        let mut db = RootDatabase { storage: salsa::Storage::default(), cancellation_token: None };
         db.set_crate_graph_with_durability(Default::default(), Durability::HIGH);
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_library_roots_with_durability(Default::default(), Durability::HIGH);
+        db.set_symbol_cache_with_durability(None, Durability::HIGH);
         db.set_enable_proc_attr_macros(Default::default());
         db.update_lru_capacity(lru_capacity);
         db
@@ -112,7 +125,74 @@ impl RootDatabase {
 
 impl salsa::ParallelDatabase for RootDatabase {
     fn snapshot(&self) -> salsa::Snapshot<RootDatabase> {
-        salsa::Snapshot::new(RootDatabase { storage: self.storage.snapshot() })
+        salsa::Snapshot::new(RootDatabase {
+            storage: self.storage.snapshot(),
+            cancellation_token: self.cancellation_token.clone(),
+        })
+    }
+}
+
+impl RootDatabase {
+    /// Like `snapshot`, but the long-running searches on the snapshot stop
+    /// once `token` is cancelled, without affecting other snapshots.
+    pub fn snapshot_with_cancellation(
+        &self,
+        token: CancellationToken,
+    ) -> salsa::Snapshot<RootDatabase> {
+        salsa::Snapshot::new(RootDatabase {
+            storage: self.storage.snapshot(),
+            cancellation_token: Some(token),
+        })
+    }
+
+    /// Unwinds with `TokenCancelled` if the token of this snapshot was
+    /// cancelled.
+    ///
+    /// This must not be called from queries: unwinding from the middle of a
+    /// query would also abort the other snapshots waiting for its result. It's
+    /// meant for the loops running outside of them, like the ones of the
+    /// reference search, going over many files.
+    pub fn unwind_if_token_cancelled(&self) {
+        if self.cancellation_token.as_ref().map_or(false, CancellationToken::is_cancelled) {
+            // Like salsa's own cancellation, this unwinds without running the
+            // panic hook.
+            panic::resume_unwind(Box::new(TokenCancelled));
+        }
+    }
+}
+
+/// Cancels the searches of a single database snapshot, unlike
+/// `RootDatabase::request_cancellation`, which cancels all the queries.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The panic payload of `RootDatabase::unwind_if_token_cancelled`.
+#[derive(Debug)]
+pub struct TokenCancelled;
+
+impl TokenCancelled {
+    /// Runs `f`, catching the unwinding caused by a cancelled token.
+    pub fn catch<F, T>(f: F) -> Result<T, TokenCancelled>
+    where
+        F: FnOnce() -> T + UnwindSafe,
+    {
+        match panic::catch_unwind(f) {
+            Ok(it) => Ok(it),
+            Err(payload) => match payload.downcast::<TokenCancelled>() {
+                Ok(it) => Err(*it),
+                Err(payload) => panic::resume_unwind(payload),
+            },
+        }
     }
 }
 
@@ -0,0 +1,73 @@
+//! The data about the procedural macros of the dependencies of a crate which
+//! isn't part of their definitions, like the helper attributes of derives,
+//! reported by the proc-macro server.
+
+use std::sync::Arc;
+
+use base_db::{salsa, CrateId, ProcMacroKind, Upcast};
+use hir::{db::HirDatabase, Crate, MacroDef, MacroKind, ScopeDef};
+use rustc_hash::FxHashMap;
+use syntax::SmolStr;
+
+#[salsa::query_group(ProcMacrosDatabaseStorage)]
+pub trait ProcMacrosDatabase: HirDatabase + Upcast<dyn HirDatabase> {
+    /// The helper attributes of the derive macros of the dependencies of
+    /// `krate`.
+    fn derive_helpers(&self, krate: CrateId) -> Arc<FxHashMap<MacroDef, Box<[SmolStr]>>>;
+}
+
+fn derive_helpers(
+    db: &dyn ProcMacrosDatabase,
+    krate: CrateId,
+) -> Arc<FxHashMap<MacroDef, Box<[SmolStr]>>> {
+    let db = Upcast::<dyn HirDatabase>::upcast(db);
+    let crate_graph = db.crate_graph();
+    let mut res = FxHashMap::default();
+    for dep in Crate::from(krate).dependencies(db) {
+        let proc_macros = &crate_graph[dep.krate.into()].proc_macro;
+        if proc_macros.is_empty() {
+            continue;
+        }
+        // The proc-macros of a crate are defined in its root module.
+        for (name, def) in dep.krate.root_module(db).scope(db, None) {
+            let mac = match def {
+                ScopeDef::MacroDef(it) if it.kind() == MacroKind::Derive => it,
+                _ => continue,
+            };
+            let helpers = proc_macros.iter().find(|it| {
+                it.kind == ProcMacroKind::CustomDerive && it.name == name.to_string().as_str()
+            });
+            match helpers {
+                Some(it) if !it.helpers.is_empty() => {
+                    res.insert(mac, it.helpers.clone());
+                }
+                _ => (),
+            }
+        }
+    }
+    Arc::new(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::{fixture::WithFixture, FileLoader};
+
+    use super::*;
+    use crate::RootDatabase;
+
+    #[test]
+    fn collects_derive_helpers_of_dependencies() {
+        let (db, file_id) = RootDatabase::with_single_file(
+            r#"
+//- proc_macros: identity, derive_identity, derive_with_helpers
+struct S;
+"#,
+        );
+        let krate = db.relevant_crates(file_id).iter().next().copied().unwrap();
+        let helpers = db.derive_helpers(krate);
+        assert_eq!(helpers.len(), 1);
+        let (mac, helpers) = helpers.iter().next().unwrap();
+        assert_eq!(mac.name(&db).unwrap().to_string(), "DeriveWithHelpers");
+        assert_eq!(&**helpers, &[SmolStr::new("helper")]);
+    }
+}
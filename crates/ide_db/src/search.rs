@@ -58,13 +58,19 @@ impl IntoIterator for UsageSearchResult {
 pub struct FileReference {
     pub range: TextRange,
     pub name: ast::NameLike,
-    pub access: Option<ReferenceAccess>,
+    pub category: Option<ReferenceCategory>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub enum ReferenceAccess {
+pub enum ReferenceCategory {
     Read,
     Write,
+    /// The callee of a call, method call or macro call.
+    Call,
+    /// A path in a `use` item.
+    Import,
+    /// The trait of a trait impl.
+    TraitImpl,
 }
 
 /// Generally, `search_scope` returns files that might contain references for the element.
@@ -113,7 +119,8 @@ impl SearchScope {
         }
     }
 
-    fn module(db: &RootDatabase, module: hir::Module) -> SearchScope {
+    /// The source of `module` and of all its descendants.
+    pub fn module(db: &RootDatabase, module: hir::Module) -> SearchScope {
         let mut entries = FxHashMap::default();
 
         let mut to_visit = vec![module];
@@ -227,6 +234,7 @@ impl Definition {
                 DefWithBody::Function(f) => f.source(db).map(|src| src.value.syntax().text_range()),
                 DefWithBody::Const(c) => c.source(db).map(|src| src.value.syntax().text_range()),
                 DefWithBody::Static(s) => s.source(db).map(|src| src.value.syntax().text_range()),
+                DefWithBody::Variant(v) => v.source(db).map(|src| src.value.syntax().text_range()),
             };
             return match range {
                 Some(range) => SearchScope::file_range(FileRange { file_id, range }),
@@ -385,6 +393,7 @@ impl<'a> FindUsages<'a> {
         let name = name.as_str();
 
         for (file_id, search_range) in search_scope {
+            sema.db.unwind_if_token_cancelled();
             let text = sema.db.file_text(file_id);
             let search_range =
                 search_range.unwrap_or_else(|| TextRange::up_to(TextSize::of(text.as_str())));
@@ -475,7 +484,7 @@ impl<'a> FindUsages<'a> {
                 let reference = FileReference {
                     range,
                     name: ast::NameLike::NameRef(name_ref.clone()),
-                    access: None,
+                    category: None,
                 };
                 sink(file_id, reference)
             }
@@ -494,7 +503,7 @@ impl<'a> FindUsages<'a> {
                 let reference = FileReference {
                     range,
                     name: ast::NameLike::NameRef(name_ref.clone()),
-                    access: None,
+                    category: ReferenceCategory::new(&def, name_ref),
                 };
                 sink(file_id, reference)
             }
@@ -513,7 +522,7 @@ impl<'a> FindUsages<'a> {
                 let reference = FileReference {
                     range,
                     name: ast::NameLike::Lifetime(lifetime.clone()),
-                    access: None,
+                    category: None,
                 };
                 sink(file_id, reference)
             }
@@ -532,7 +541,7 @@ impl<'a> FindUsages<'a> {
                 let reference = FileReference {
                     range,
                     name: ast::NameLike::NameRef(name_ref.clone()),
-                    access: reference_access(&def, name_ref),
+                    category: ReferenceCategory::new(&def, name_ref),
                 };
                 sink(file_id, reference)
             }
@@ -542,7 +551,7 @@ impl<'a> FindUsages<'a> {
                     let reference = FileReference {
                         range,
                         name: ast::NameLike::NameRef(name_ref.clone()),
-                        access: reference_access(&def, name_ref),
+                        category: ReferenceCategory::new(&def, name_ref),
                     };
                     sink(file_id, reference)
                 } else {
@@ -552,15 +561,20 @@ impl<'a> FindUsages<'a> {
             Some(NameRefClass::FieldShorthand { local_ref: local, field_ref: field }) => {
                 let field = Definition::Field(field);
                 let FileRange { file_id, range } = self.sema.original_range(name_ref.syntax());
-                let access = match self.def {
-                    Definition::Field(_) if field == self.def => reference_access(&field, name_ref),
+                let category = match self.def {
+                    Definition::Field(_) if field == self.def => {
+                        ReferenceCategory::new(&field, name_ref)
+                    }
                     Definition::Local(l) if local == l => {
-                        reference_access(&Definition::Local(local), name_ref)
+                        ReferenceCategory::new(&Definition::Local(local), name_ref)
                     }
                     _ => return false,
                 };
-                let reference =
-                    FileReference { range, name: ast::NameLike::NameRef(name_ref.clone()), access };
+                let reference = FileReference {
+                    range,
+                    name: ast::NameLike::NameRef(name_ref.clone()),
+                    category,
+                };
                 sink(file_id, reference)
             }
             _ => false,
@@ -583,14 +597,17 @@ impl<'a> FindUsages<'a> {
                     range,
                     name: ast::NameLike::Name(name.clone()),
                     // FIXME: mutable patterns should have `Write` access
-                    access: Some(ReferenceAccess::Read),
+                    category: Some(ReferenceCategory::Read),
                 };
                 sink(file_id, reference)
             }
             Some(NameClass::ConstReference(def)) if self.def == def => {
                 let FileRange { file_id, range } = self.sema.original_range(name.syntax());
-                let reference =
-                    FileReference { range, name: ast::NameLike::Name(name.clone()), access: None };
+                let reference = FileReference {
+                    range,
+                    name: ast::NameLike::Name(name.clone()),
+                    category: None,
+                };
                 sink(file_id, reference)
             }
             // Resolve trait impl function definitions to the trait definition's version if self.def is the trait definition's
@@ -614,7 +631,7 @@ impl<'a> FindUsages<'a> {
                         let reference = FileReference {
                             range,
                             name: ast::NameLike::Name(name.clone()),
-                            access: None,
+                            category: None,
                         };
                         sink(file_id, reference)
                     })
@@ -645,32 +662,135 @@ fn def_to_ty(sema: &Semantics<RootDatabase>, def: &Definition) -> Option<hir::Ty
     }
 }
 
-fn reference_access(def: &Definition, name_ref: &ast::NameRef) -> Option<ReferenceAccess> {
-    // Only Locals and Fields have accesses for now.
-    if !matches!(def, Definition::Local(_) | Definition::Field(_)) {
-        return None;
-    }
-
-    let mode = name_ref.syntax().ancestors().find_map(|node| {
-        match_ast! {
-            match (node) {
-                ast::BinExpr(expr) => {
-                    if expr.op_kind()?.is_assignment() {
-                        // If the variable or field ends on the LHS's end then it's a Write (covers fields and locals).
-                        // FIXME: This is not terribly accurate.
-                        if let Some(lhs) = expr.lhs() {
-                            if lhs.syntax().text_range().end() == name_ref.syntax().text_range().end() {
-                                return Some(ReferenceAccess::Write);
+impl ReferenceCategory {
+    fn new(def: &Definition, name_ref: &ast::NameRef) -> Option<ReferenceCategory> {
+        if name_ref.syntax().ancestors().any(|it| ast::UseTree::can_cast(it.kind())) {
+            return Some(ReferenceCategory::Import);
+        }
+        if is_callee(def, name_ref) {
+            return Some(ReferenceCategory::Call);
+        }
+        if is_impl_trait(def, name_ref) {
+            return Some(ReferenceCategory::TraitImpl);
+        }
+
+        // Only Locals and Fields have accesses for now.
+        if !matches!(def, Definition::Local(_) | Definition::Field(_)) {
+            return None;
+        }
+
+        let mode = name_ref.syntax().ancestors().find_map(|node| {
+            match_ast! {
+                match (node) {
+                    ast::BinExpr(expr) => {
+                        if expr.op_kind()?.is_assignment() {
+                            // If the variable or field ends on the LHS's end then it's a Write (covers fields and locals).
+                            // FIXME: This is not terribly accurate.
+                            if let Some(lhs) = expr.lhs() {
+                                if lhs.syntax().text_range().end() == name_ref.syntax().text_range().end() {
+                                    return Some(ReferenceCategory::Write);
+                                }
                             }
                         }
-                    }
-                    Some(ReferenceAccess::Read)
-                },
-                _ => None
+                        Some(ReferenceCategory::Read)
+                    },
+                    _ => None
+                }
             }
+        });
+
+        // Default Locals and Fields to read
+        mode.or(Some(ReferenceCategory::Read))
+    }
+}
+
+/// Whether `name_ref` names the function, closure or macro being called.
+fn is_callee(def: &Definition, name_ref: &ast::NameRef) -> bool {
+    match def {
+        Definition::ModuleDef(ModuleDef::Function(_)) | Definition::Local(_) => {}
+        Definition::Macro(_) => {
+            return last_segment_path(name_ref)
+                .and_then(|path| path.syntax().parent())
+                .map_or(false, |it| ast::MacroCall::can_cast(it.kind()));
         }
-    });
+        _ => return false,
+    }
+    if let Some(method_call) = name_ref.syntax().parent().and_then(ast::MethodCallExpr::cast) {
+        return method_call.name_ref().as_ref() == Some(name_ref);
+    }
+    (|| {
+        let path_expr =
+            last_segment_path(name_ref)?.syntax().parent().and_then(ast::PathExpr::cast)?;
+        let call = path_expr.syntax().parent().and_then(ast::CallExpr::cast)?;
+        Some(call.expr()?.syntax() == path_expr.syntax())
+    })()
+    .unwrap_or(false)
+}
 
-    // Default Locals and Fields to read
-    mode.or(Some(ReferenceAccess::Read))
+/// Whether `name_ref` names the trait of an `impl Trait for Type`.
+fn is_impl_trait(def: &Definition, name_ref: &ast::NameRef) -> bool {
+    if !matches!(def, Definition::ModuleDef(ModuleDef::Trait(_))) {
+        return false;
+    }
+    (|| {
+        let path = last_segment_path(name_ref)?;
+        let path_type = path.syntax().parent().and_then(ast::PathType::cast)?;
+        let impl_ = path_type.syntax().parent().and_then(ast::Impl::cast)?;
+        Some(impl_.trait_()?.syntax() == path_type.syntax())
+    })()
+    .unwrap_or(false)
+}
+
+/// The path `name_ref` is the last segment of.
+fn last_segment_path(name_ref: &ast::NameRef) -> Option<ast::Path> {
+    let segment = name_ref.syntax().parent().and_then(ast::PathSegment::cast)?;
+    let path = segment.parent_path();
+    match path.parent_path() {
+        Some(_) => None,
+        None => Some(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use base_db::fixture::WithFixture;
+
+    use super::*;
+    use crate::{CancellationToken, TokenCancelled};
+
+    #[test]
+    fn cancelled_token_stops_running_search() {
+        let (db, files) = RootDatabase::with_many_files(
+            r#"
+//- /lib.rs
+mod a;
+mod b;
+pub struct S;
+//- /a.rs
+fn f(_: crate::S) {}
+//- /b.rs
+fn g(_: crate::S) {}
+"#,
+        );
+        let token = CancellationToken::default();
+        let db = db.snapshot_with_cancellation(token.clone());
+        let sema = Semantics::new(&*db);
+        let strukt =
+            sema.parse(files[0]).syntax().descendants().find_map(ast::Struct::cast).unwrap();
+        let def = Definition::ModuleDef(sema.to_def(&strukt).unwrap().into());
+
+        // Each file has a single reference, the search stops before the next file.
+        let mut found = 0;
+        let res = TokenCancelled::catch(AssertUnwindSafe(|| {
+            def.usages(&sema).search(&mut |_, _| {
+                found += 1;
+                token.cancel();
+                false
+            })
+        }));
+        assert!(res.is_err());
+        assert_eq!(found, 1);
+    }
 }
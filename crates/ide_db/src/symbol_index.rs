@@ -19,6 +19,11 @@
 //! for each library (which is assumed to never change) and an FST for each Rust
 //! file in the current workspace, and run a query against the union of all
 //! those FSTs.
+//!
+//! The symbols of the individual files can also be persisted on disk with a
+//! [`SymbolCache`], to avoid parsing everything again after a restart.
+
+mod cache;
 
 use std::{
     cmp::Ordering,
@@ -38,13 +43,15 @@ use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
 use syntax::{
     ast::{self, NameOwner},
-    match_ast, AstNode, Parse, SmolStr, SourceFile,
-    SyntaxKind::*,
+    match_ast, AstNode, SmolStr, SourceFile,
+    SyntaxKind::{self, *},
     SyntaxNode, SyntaxNodePtr, TextRange, WalkEvent,
 };
 
 use crate::RootDatabase;
 
+pub use self::cache::SymbolCache;
+
 #[derive(Debug)]
 pub struct Query {
     query: String,
@@ -103,12 +110,16 @@ pub trait SymbolsDatabase: hir::db::HirDatabase + SourceDatabaseExt {
     /// Files in libraries are assumed to never change.
     #[salsa::input]
     fn library_roots(&self) -> Arc<FxHashSet<SourceRootId>>;
+    /// The on-disk cache of file symbols, if enabled.
+    #[salsa::input]
+    fn symbol_cache(&self) -> Option<Arc<SymbolCache>>;
 }
 
 fn library_symbols(db: &dyn SymbolsDatabase) -> Arc<FxHashMap<SourceRootId, SymbolIndex>> {
     let _p = profile::span("library_symbols");
 
     let roots = db.library_roots();
+    let cache = db.symbol_cache();
     let res = roots
         .iter()
         .map(|&root_id| {
@@ -117,10 +128,18 @@ fn library_symbols(db: &dyn SymbolsDatabase) -> Arc<FxHashMap<SourceRootId, Symb
                 .iter()
                 .map(|it| (it, SourceDatabaseExt::file_text(db, it)))
                 .collect::<Vec<_>>();
-            let symbol_index = SymbolIndex::for_files(
-                files.into_par_iter().map(|(file, text)| (file, SourceFile::parse(&text))),
-            );
-            (root_id, symbol_index)
+            let symbols = files
+                .into_par_iter()
+                .flat_map(|(file_id, text)| {
+                    let compute =
+                        || source_file_to_file_symbols(&SourceFile::parse(&text).tree(), file_id);
+                    match &cache {
+                        Some(cache) => cache.symbols(file_id, &text, compute),
+                        None => compute(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            (root_id, SymbolIndex::new(symbols))
         })
         .collect();
     Arc::new(res)
@@ -128,9 +147,11 @@ fn library_symbols(db: &dyn SymbolsDatabase) -> Arc<FxHashMap<SourceRootId, Symb
 
 fn file_symbols(db: &dyn SymbolsDatabase, file_id: FileId) -> Arc<SymbolIndex> {
     db.unwind_if_cancelled();
-    let parse = db.parse(file_id);
-
-    let symbols = source_file_to_file_symbols(&parse.tree(), file_id);
+    let compute = || source_file_to_file_symbols(&db.parse(file_id).tree(), file_id);
+    let symbols = match db.symbol_cache() {
+        Some(cache) => cache.symbols(file_id, &SourceDatabaseExt::file_text(db, file_id), compute),
+        None => compute(),
+    };
 
     // FIXME: add macros here
 
@@ -189,7 +210,10 @@ pub fn world_symbols(db: &RootDatabase, query: Query) -> Vec<FileSymbol> {
         let snap = Snap(db.snapshot());
         tmp2 = files
             .par_iter()
-            .map_with(snap, |db, &file_id| db.0.file_symbols(file_id))
+            .map_with(snap, |db, &file_id| {
+                db.0.unwind_if_token_cancelled();
+                db.0.file_symbols(file_id)
+            })
             .collect::<Vec<_>>();
         tmp2.iter().map(|it| &**it).collect()
     };
@@ -214,7 +238,10 @@ pub fn crate_symbols(db: &RootDatabase, krate: CrateId, query: Query) -> Vec<Fil
 
     let buf = files
         .par_iter()
-        .map_with(snap, |db, &file_id| db.0.file_symbols(file_id))
+        .map_with(snap, |db, &file_id| {
+            db.0.unwind_if_token_cancelled();
+            db.0.file_symbols(file_id)
+        })
         .collect::<Vec<_>>();
     let buf = buf.iter().map(|it| &**it).collect::<Vec<_>>();
 
@@ -297,15 +324,6 @@ impl SymbolIndex {
         self.map.as_fst().size() + self.symbols.len() * mem::size_of::<FileSymbol>()
     }
 
-    pub(crate) fn for_files(
-        files: impl ParallelIterator<Item = (FileId, Parse<ast::SourceFile>)>,
-    ) -> SymbolIndex {
-        let symbols = files
-            .flat_map(|(file_id, file)| source_file_to_file_symbols(&file.tree(), file_id))
-            .collect::<Vec<_>>();
-        SymbolIndex::new(symbols)
-    }
-
     fn range_to_map_value(start: usize, end: usize) -> u64 {
         debug_assert![start <= (std::u32::MAX as usize)];
         debug_assert![end <= (std::u32::MAX as usize)];
@@ -411,7 +429,12 @@ fn source_file_to_file_symbols(source_file: &SourceFile, file_id: FileId) -> Vec
                     symbol.container_name = stack.last().cloned();
 
                     stack.push(symbol.name.clone());
+                    // Items can be found by their `#[doc(alias)]`es as well.
+                    let aliases = doc_aliases(&node)
+                        .map(|alias| FileSymbol { name: alias, ..symbol.clone() })
+                        .collect::<Vec<_>>();
                     symbols.push(symbol);
+                    symbols.extend(aliases);
                 }
             }
 
@@ -452,23 +475,19 @@ fn to_symbol(node: &SyntaxNode) -> Option<(SmolStr, SyntaxNodePtr, TextRange)> {
     }
 }
 
+fn doc_aliases(node: &SyntaxNode) -> impl Iterator<Item = SmolStr> + '_ {
+    node.children()
+        .filter_map(ast::Attr::cast)
+        .flat_map(|attr| attr.doc_aliases())
+        .filter_map(|alias| alias.value().map(|it| SmolStr::from(&*it)))
+        // Like rustdoc, ignore aliases with whitespace.
+        .filter(|alias| !alias.is_empty() && !alias.contains(char::is_whitespace))
+}
+
 fn to_file_symbol(node: &SyntaxNode, file_id: FileId) -> Option<FileSymbol> {
     to_symbol(node).map(move |(name, ptr, name_range)| FileSymbol {
         name,
-        kind: match node.kind() {
-            FN => FileSymbolKind::Function,
-            STRUCT => FileSymbolKind::Struct,
-            ENUM => FileSymbolKind::Enum,
-            TRAIT => FileSymbolKind::Trait,
-            MODULE => FileSymbolKind::Module,
-            TYPE_ALIAS => FileSymbolKind::TypeAlias,
-            CONST => FileSymbolKind::Const,
-            STATIC => FileSymbolKind::Static,
-            MACRO_RULES => FileSymbolKind::Macro,
-            MACRO_DEF => FileSymbolKind::Macro,
-            UNION => FileSymbolKind::Union,
-            kind => unreachable!("{:?}", kind),
-        },
+        kind: symbol_kind(node.kind()),
         range: node.text_range(),
         ptr,
         file_id,
@@ -476,3 +495,20 @@ fn to_file_symbol(node: &SyntaxNode, file_id: FileId) -> Option<FileSymbol> {
         container_name: None,
     })
 }
+
+fn symbol_kind(kind: SyntaxKind) -> FileSymbolKind {
+    match kind {
+        FN => FileSymbolKind::Function,
+        STRUCT => FileSymbolKind::Struct,
+        ENUM => FileSymbolKind::Enum,
+        TRAIT => FileSymbolKind::Trait,
+        MODULE => FileSymbolKind::Module,
+        TYPE_ALIAS => FileSymbolKind::TypeAlias,
+        CONST => FileSymbolKind::Const,
+        STATIC => FileSymbolKind::Static,
+        MACRO_RULES => FileSymbolKind::Macro,
+        MACRO_DEF => FileSymbolKind::Macro,
+        UNION => FileSymbolKind::Union,
+        kind => unreachable!("{:?}", kind),
+    }
+}
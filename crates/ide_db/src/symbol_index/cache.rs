@@ -0,0 +1,317 @@
+//! On-disk cache of the symbols of individual files, so that workspace symbol
+//! search doesn't need to parse the whole workspace and its dependencies again
+//! after a restart.
+//!
+//! The symbols of a file only depend on its text, so they are keyed by a hash
+//! of the text alone: a changed file just misses the cache. Only the latest
+//! text of each file is kept in memory, and entries which weren't used for a
+//! few sessions, like the ones of changed or deleted files, are dropped when
+//! the cache is saved.
+//!
+//! The cache is a plain text file, with a `file <hash> <age>` line per file
+//! followed by a line per symbol.
+
+use std::{
+    fmt, fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use base_db::FileId;
+use once_cell::sync::OnceCell;
+use rustc_hash::{FxHashMap, FxHasher};
+use syntax::{SmolStr, SyntaxKind, SyntaxNodePtr, TextRange, TextSize};
+
+use super::{symbol_kind, FileSymbol};
+
+const HEADER: &str = "rust-analyzer symbol index v2";
+const FILE_NAME: &str = "symbol_index";
+/// Number of sessions an entry is kept for without being used.
+const MAX_AGE: u32 = 5;
+
+/// The syntax kinds of the symbols, with their names in the cache file.
+const KINDS: &[(SyntaxKind, &str)] = &[
+    (SyntaxKind::FN, "fn"),
+    (SyntaxKind::STRUCT, "struct"),
+    (SyntaxKind::ENUM, "enum"),
+    (SyntaxKind::TRAIT, "trait"),
+    (SyntaxKind::MODULE, "mod"),
+    (SyntaxKind::TYPE_ALIAS, "type"),
+    (SyntaxKind::CONST, "const"),
+    (SyntaxKind::STATIC, "static"),
+    (SyntaxKind::MACRO_RULES, "macro_rules"),
+    (SyntaxKind::MACRO_DEF, "macro"),
+    (SyntaxKind::UNION, "union"),
+];
+
+type Symbols = Arc<[FileSymbol]>;
+
+pub struct SymbolCache {
+    path: PathBuf,
+    /// The entries saved by the previous sessions, with their ages. Read on
+    /// first use.
+    loaded: OnceCell<FxHashMap<u64, (Symbols, u32)>>,
+    /// The entries used in this session, for the latest text of each file.
+    used: Mutex<FxHashMap<FileId, (u64, Symbols)>>,
+    /// Whether some of the entries used in this session aren't saved yet.
+    dirty: AtomicBool,
+}
+
+impl fmt::Debug for SymbolCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SymbolCache").field("path", &self.path).finish()
+    }
+}
+
+// Salsa inputs need to be comparable; there is only ever one cache per database.
+impl PartialEq for SymbolCache {
+    fn eq(&self, other: &SymbolCache) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for SymbolCache {}
+
+impl SymbolCache {
+    /// Creates a cache stored in `dir`. Nothing is read until the cache is
+    /// first used.
+    pub fn new(dir: &Path) -> SymbolCache {
+        SymbolCache {
+            path: dir.join(FILE_NAME),
+            loaded: OnceCell::new(),
+            used: Mutex::default(),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the symbols of the file with the given text, computing them with
+    /// `compute` if they aren't cached.
+    pub(super) fn symbols(
+        &self,
+        file_id: FileId,
+        text: &str,
+        compute: impl FnOnce() -> Vec<FileSymbol>,
+    ) -> Vec<FileSymbol> {
+        let hash = text_hash(text);
+        let used = match self.used.lock().unwrap().get(&file_id) {
+            Some((used_hash, symbols)) if *used_hash == hash => Some(symbols.clone()),
+            _ => None,
+        };
+        let cached = used.or_else(|| self.loaded().get(&hash).map(|(symbols, _)| symbols.clone()));
+        let symbols = match cached {
+            Some(it) => it,
+            None => {
+                self.dirty.store(true, Ordering::Relaxed);
+                Symbols::from(compute())
+            }
+        };
+        let res = symbols.iter().map(|symbol| FileSymbol { file_id, ..symbol.clone() }).collect();
+        // Replacing the previous text of the file keeps the cache from growing as
+        // the file is edited.
+        self.used.lock().unwrap().insert(file_id, (hash, symbols));
+        res
+    }
+
+    /// Writes the cache to disk, if symbols were computed since the last save.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let res = self.write();
+        if res.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        res
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let _p = profile::span("SymbolCache::write");
+        let used = self.used.lock().unwrap().values().cloned().collect::<FxHashMap<_, _>>();
+        let mut buf = format!("{}\n", HEADER);
+        for (hash, symbols) in used.iter() {
+            write_entry(&mut buf, *hash, 0, symbols);
+        }
+        for (hash, (symbols, age)) in self.loaded().iter() {
+            if !used.contains_key(hash) && age + 1 < MAX_AGE {
+                write_entry(&mut buf, *hash, age + 1, symbols);
+            }
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first, so that another server saving its
+        // cache at the same time doesn't leave a mix of both behind.
+        let tmp = self.path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, buf)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    fn loaded(&self) -> &FxHashMap<u64, (Symbols, u32)> {
+        self.loaded.get_or_init(|| {
+            let _p = profile::span("SymbolCache::read");
+            let text = match fs::read_to_string(&self.path) {
+                Ok(it) => it,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return FxHashMap::default(),
+                Err(err) => {
+                    log::warn!("failed to read the symbol cache {}: {}", self.path.display(), err);
+                    return FxHashMap::default();
+                }
+            };
+            parse(&text).unwrap_or_else(|| {
+                log::warn!("ignoring the invalid symbol cache {}", self.path.display());
+                FxHashMap::default()
+            })
+        })
+    }
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn write_entry(buf: &mut String, hash: u64, age: u32, symbols: &[FileSymbol]) {
+    use std::fmt::Write;
+
+    writeln!(buf, "file {:x} {}", hash, age).unwrap();
+    for symbol in symbols {
+        let kind = KINDS.iter().find(|(kind, _)| *kind == symbol.ptr.kind()).unwrap().1;
+        let name_range = match symbol.name_range {
+            Some(it) => format!("{}-{}", u32::from(it.start()), u32::from(it.end())),
+            None => "-".to_string(),
+        };
+        writeln!(
+            buf,
+            "{} {}-{} {} {} {}",
+            kind,
+            u32::from(symbol.range.start()),
+            u32::from(symbol.range.end()),
+            name_range,
+            symbol.name,
+            symbol.container_name.as_deref().unwrap_or("-"),
+        )
+        .unwrap();
+    }
+}
+
+fn parse(text: &str) -> Option<FxHashMap<u64, (Symbols, u32)>> {
+    let mut lines = text.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+
+    let mut res = FxHashMap::default();
+    let mut entry: Option<(u64, u32, Vec<FileSymbol>)> = None;
+    for line in lines {
+        let mut fields = line.split(' ');
+        let first = fields.next()?;
+        if first == "file" {
+            if let Some((hash, age, symbols)) = entry.take() {
+                res.insert(hash, (Symbols::from(symbols), age));
+            }
+            let hash = u64::from_str_radix(fields.next()?, 16).ok()?;
+            let age = fields.next()?.parse().ok()?;
+            entry = Some((hash, age, Vec::new()));
+            continue;
+        }
+
+        let kind = KINDS.iter().find(|(_, name)| *name == first)?.0;
+        let range = parse_range(fields.next()?)?;
+        let name_range = match fields.next()? {
+            "-" => None,
+            it => Some(parse_range(it)?),
+        };
+        let name = SmolStr::from(fields.next()?);
+        let container_name = match fields.next()? {
+            "-" => None,
+            it => Some(SmolStr::from(it)),
+        };
+        entry.as_mut()?.2.push(FileSymbol {
+            // Replaced by the actual file when the symbols are used.
+            file_id: FileId(0),
+            name,
+            kind: symbol_kind(kind),
+            range,
+            ptr: SyntaxNodePtr::from_raw_parts(kind, range),
+            name_range,
+            container_name,
+        });
+    }
+    if let Some((hash, age, symbols)) = entry {
+        res.insert(hash, (Symbols::from(symbols), age));
+    }
+    Some(res)
+}
+
+fn parse_range(text: &str) -> Option<TextRange> {
+    let (start, end) = stdx::split_once(text, '-')?;
+    let start = TextSize::from(start.parse::<u32>().ok()?);
+    let end = TextSize::from(end.parse::<u32>().ok()?);
+    if start > end {
+        return None;
+    }
+    Some(TextRange::new(start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::SourceFile;
+
+    use super::*;
+    use crate::symbol_index::source_file_to_file_symbols;
+
+    #[test]
+    fn roundtrip() {
+        let text = r#"
+mod m {
+    struct S;
+    macro_rules! m { () => {} }
+    pub macro m2() {}
+}
+fn f() {}
+"#;
+        let file_id = FileId(1);
+        let symbols = source_file_to_file_symbols(&SourceFile::parse(text).tree(), file_id);
+        assert_eq!(symbols.len(), 5);
+
+        let mut buf = format!("{}\n", HEADER);
+        write_entry(&mut buf, text_hash(text), 0, &symbols);
+        write_entry(&mut buf, 92, 3, &[]);
+        let parsed = parse(&buf).unwrap();
+
+        assert_eq!(parsed[&92].1, 3);
+        let (cached, age) = &parsed[&text_hash(text)];
+        assert_eq!(*age, 0);
+        let cached =
+            cached.iter().map(|it| FileSymbol { file_id, ..it.clone() }).collect::<Vec<_>>();
+        assert_eq!(cached, symbols);
+    }
+
+    #[test]
+    fn keeps_the_latest_text_of_each_file() {
+        let cache = SymbolCache::new(Path::new("/nonexistent"));
+        let file_id = FileId(1);
+        let compute =
+            |text: &str| source_file_to_file_symbols(&SourceFile::parse(text).tree(), file_id);
+        for text in ["fn f() {}", "fn f() {} fn g() {}", "fn f() {}"].iter() {
+            let symbols = cache.symbols(file_id, text, || compute(text));
+            assert_eq!(symbols, compute(text));
+        }
+        cache.symbols(FileId(2), "struct S;", || compute("struct S;"));
+
+        assert_eq!(cache.used.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert!(parse("rust-analyzer symbol index v0\nfile 1 0\n").is_none());
+        assert!(parse(&format!("{}\nfn 0-1 0-1 f -\n", HEADER)).is_none());
+    }
+}
@@ -81,6 +81,67 @@ fn main() {
         );
     }
 
+    #[test]
+    fn missing_unsafe_diagnostic_with_extern_static() {
+        check_diagnostics(
+            r#"
+extern "C" {
+    static EXTERN_STATIC: u32;
+}
+
+fn main() {
+    let x = EXTERN_STATIC;
+          //^^^^^^^^^^^^^ error: this operation is unsafe and requires an unsafe function or block
+    unsafe {
+        let x = EXTERN_STATIC;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn missing_unsafe_diagnostic_with_extern_static_mut() {
+        check_diagnostics(
+            r#"
+extern "C" {
+    static mut EXTERN_COUNTER: u32;
+}
+
+fn main() {
+    EXTERN_COUNTER = 1;
+  //^^^^^^^^^^^^^^ error: this operation is unsafe and requires an unsafe function or block
+    let r = &EXTERN_COUNTER;
+           //^^^^^^^^^^^^^^ error: this operation is unsafe and requires an unsafe function or block
+    unsafe {
+        EXTERN_COUNTER += 1;
+        let r = &EXTERN_COUNTER;
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_missing_unsafe_diagnostic_with_extern_static_in_unsafe_fn() {
+        check_diagnostics(
+            r#"
+extern "C" {
+    static EXTERN_STATIC: u32;
+}
+static STATIC: u32 = 0;
+
+unsafe fn read() -> u32 {
+    EXTERN_STATIC
+}
+
+fn main() {
+    let x = STATIC;
+}
+"#,
+        );
+    }
+
     #[test]
     fn no_missing_unsafe_diagnostic_with_safe_intrinsic() {
         check_diagnostics(
@@ -26,6 +26,12 @@
 // the replacement template (e.g. `bar($s)`), then *, & and &mut will be added as needed to mirror
 // whatever autoderef and autoref was happening implicitly in the matched code.
 //
+// A pattern can also be a sequence of statements, which matches consecutive statements of a block.
+// e.g. `foo($a); bar($b); ==>> foobar($a, $b);`. If the pattern starts with `#[unordered]`, then
+// the statements can be in any order. Within an impl, a trait or a module, a placeholder which is
+// the only thing between the braces matches all the items. e.g.
+// `impl Foo for $t { $items } ==>> impl Bar for $t { $items }`.
+//
 // The scope of the search / replace will be restricted to the current selection if any, otherwise
 // it will apply to the whole workspace.
 //
@@ -38,8 +44,14 @@
 //
 // | kind(literal) | Is a literal (e.g. `42` or `"forty two"`)
 // | not(a)        | Negates the constraint `a`
+// | type(t)       | Has a type matching `t`, where `_` and `$<name>` match any type (e.g. `type(Option<$t>)`)
+// | resolves_to(p) | Is a path to the same item as `p` (e.g. `resolves_to(core::mem::swap)`)
 // |===
 //
+// Several rules can be applied at once by giving one rule per line, lines starting with `//` are
+// ignored. A file of rules can also be applied from the command line with
+// `rust-analyzer ssr --rules <file>`, which prints the number of matches of each rule.
+//
 // Available via the command `rust-analyzer.ssr`.
 //
 // ```rust
@@ -84,6 +96,7 @@ pub use crate::errors::SsrError;
 pub use crate::from_comment::ssr_from_comment;
 pub use crate::matching::Match;
 use crate::matching::MatchFailureReason;
+pub use crate::parsing::parse_rules;
 use hir::Semantics;
 use ide_db::base_db::{FileId, FilePosition, FileRange};
 use resolving::ResolvedRule;
@@ -94,6 +107,8 @@ use text_edit::TextEdit;
 // A structured search replace rule. Create by calling `parse` on a str.
 #[derive(Debug)]
 pub struct SsrRule {
+    /// The source of the rule.
+    text: String,
     /// A structured pattern that we're searching for.
     pattern: parsing::RawPattern,
     /// What we'll replace it with.
@@ -117,6 +132,10 @@ pub struct MatchFinder<'db> {
     /// Our source of information about the user's code.
     sema: Semantics<'db, ide_db::RootDatabase>,
     rules: Vec<ResolvedRule>,
+    /// For each of `rules`, the index of the rule or pattern it was parsed from. A rule can be
+    /// parsed as several kinds of nodes.
+    rule_sources: Vec<usize>,
+    sources_count: usize,
     resolution_scope: resolving::ResolutionScope<'db>,
     restrict_ranges: Vec<FileRange>,
 }
@@ -132,7 +151,14 @@ impl<'db> MatchFinder<'db> {
         restrict_ranges.retain(|range| !range.range.is_empty());
         let sema = Semantics::new(db);
         let resolution_scope = resolving::ResolutionScope::new(&sema, lookup_context);
-        MatchFinder { sema, rules: Vec::new(), resolution_scope, restrict_ranges }
+        MatchFinder {
+            sema,
+            rules: Vec::new(),
+            rule_sources: Vec::new(),
+            sources_count: 0,
+            resolution_scope,
+            restrict_ranges,
+        }
     }
 
     /// Constructs an instance using the start of the first file in `db` as the lookup context.
@@ -156,21 +182,38 @@ impl<'db> MatchFinder<'db> {
     /// precedence. If a node is matched by an earlier rule, then later rules won't be permitted to
     /// match to it.
     pub fn add_rule(&mut self, rule: SsrRule) -> Result<(), SsrError> {
-        for parsed_rule in rule.parsed_rules {
-            self.rules.push(ResolvedRule::new(
-                parsed_rule,
-                &self.resolution_scope,
-                self.rules.len(),
-            )?);
-        }
-        Ok(())
+        self.add_parsed_rules(rule.parsed_rules)
     }
 
     /// Finds matches for all added rules and returns edits for all found matches.
     pub fn edits(&self) -> FxHashMap<FileId, TextEdit> {
+        self.matches_to_edits(self.matches())
+    }
+
+    /// Like `edits`, also returning the number of matches of each of the added rules and patterns
+    /// in each file, in the order in which they were added. Nested matches are counted too.
+    pub fn edits_and_match_counts(
+        &self,
+    ) -> (FxHashMap<FileId, TextEdit>, Vec<FxHashMap<FileId, usize>>) {
+        let matches = self.matches();
+        let mut counts = vec![FxHashMap::default(); self.sources_count];
+        self.count_matches(&matches, &mut counts);
+        (self.matches_to_edits(matches), counts)
+    }
+
+    fn count_matches(&self, matches: &SsrMatches, counts: &mut [FxHashMap<FileId, usize>]) {
+        for m in &matches.matches {
+            *counts[self.rule_sources[m.rule_index]].entry(m.range.file_id).or_insert(0) += 1;
+            for placeholder in m.placeholder_values.values() {
+                self.count_matches(&placeholder.inner_matches, counts);
+            }
+        }
+    }
+
+    fn matches_to_edits(&self, matches: SsrMatches) -> FxHashMap<FileId, TextEdit> {
         use ide_db::base_db::SourceDatabaseExt;
         let mut matches_by_file = FxHashMap::default();
-        for m in self.matches().matches {
+        for m in matches.matches {
             matches_by_file
                 .entry(m.range.file_id)
                 .or_insert_with(|| SsrMatches::default())
@@ -195,13 +238,19 @@ impl<'db> MatchFinder<'db> {
     /// Adds a search pattern. For use if you intend to only call `find_matches_in_file`. If you
     /// intend to do replacement, use `add_rule` instead.
     pub fn add_search_pattern(&mut self, pattern: SsrPattern) -> Result<(), SsrError> {
-        for parsed_rule in pattern.parsed_rules {
+        self.add_parsed_rules(pattern.parsed_rules)
+    }
+
+    fn add_parsed_rules(&mut self, parsed_rules: Vec<parsing::ParsedRule>) -> Result<(), SsrError> {
+        for parsed_rule in parsed_rules {
             self.rules.push(ResolvedRule::new(
                 parsed_rule,
                 &self.resolution_scope,
                 self.rules.len(),
             )?);
+            self.rule_sources.push(self.sources_count);
         }
+        self.sources_count += 1;
         Ok(())
     }
 
@@ -341,7 +390,19 @@ impl SsrMatches {
 
 impl Match {
     pub fn matched_text(&self) -> String {
-        self.matched_node.text().to_string()
+        match (&self.sequence_end, self.matched_node.parent()) {
+            (Some(end), Some(parent)) => {
+                let range = self.matched_node.text_range().cover(end.text_range());
+                parent.text().slice(range - parent.text_range().start()).to_string()
+            }
+            _ => self.matched_node.text().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for SsrRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
     }
 }
 
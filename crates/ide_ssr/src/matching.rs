@@ -2,7 +2,7 @@
 //! process of matching, placeholder values are recorded.
 
 use crate::{
-    parsing::{Constraint, NodeKind, Placeholder, Var},
+    parsing::{self, Constraint, NodeKind, Placeholder, StmtOrder, Var},
     resolving::{ResolvedPattern, ResolvedRule, UfcsCallInfo},
     SsrMatches,
 };
@@ -13,7 +13,7 @@ use std::{cell::Cell, iter::Peekable};
 use syntax::{ast, SyntaxElement, SyntaxElementChildren, SyntaxKind, SyntaxNode, SyntaxToken};
 use syntax::{
     ast::{AstNode, AstToken},
-    SmolStr,
+    SmolStr, TextRange, T,
 };
 
 // Creates a match error. If we're currently attempting to match some code that we thought we were
@@ -49,6 +49,8 @@ macro_rules! fail_match {
 pub struct Match {
     pub(crate) range: FileRange,
     pub(crate) matched_node: SyntaxNode,
+    /// For a sequence of statements, the last statement, `matched_node` being the first.
+    pub(crate) sequence_end: Option<SyntaxNode>,
     pub(crate) placeholder_values: FxHashMap<Var, PlaceholderMatch>,
     pub(crate) ignored_comments: Vec<ast::Comment>,
     pub(crate) rule_index: usize,
@@ -128,12 +130,37 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
         sema: &'sema Semantics<'db, ide_db::RootDatabase>,
     ) -> Result<Match, MatchFailed> {
         let match_state = Matcher { sema, restrict_range: *restrict_range, rule };
+        // Pairs of pattern and code nodes to match. There's more than one pair only for sequences
+        // of statements.
+        let pairs = match rule.stmt_sequence {
+            Some(order) => match_state.stmt_sequence_pairs(order, code)?,
+            None => vec![(rule.pattern.node.clone(), code.clone())],
+        };
         // First pass at matching, where we check that node types and idents match.
-        match_state.attempt_match_node(&mut Phase::First, &rule.pattern.node, code)?;
-        match_state.validate_range(&sema.original_range(code))?;
+        for (pattern, code) in &pairs {
+            match_state.attempt_match_node(&mut Phase::First, pattern, code)?;
+        }
+        // The code statements are in order, even when the pattern statements aren't.
+        let sequence_end = match rule.stmt_sequence {
+            Some(_) => pairs.last().map(|(_, code)| code),
+            None => None,
+        };
+        let range = match sequence_end {
+            Some(end) => {
+                let start = sema.original_range(code);
+                let end = sema.original_range(end);
+                if start.file_id != end.file_id {
+                    fail_match!("Statements are in different files");
+                }
+                FileRange { file_id: start.file_id, range: start.range.cover(end.range) }
+            }
+            None => sema.original_range(code),
+        };
+        match_state.validate_range(&range)?;
         let mut the_match = Match {
-            range: sema.original_range(code),
+            range,
             matched_node: code.clone(),
+            sequence_end: sequence_end.cloned(),
             placeholder_values: FxHashMap::default(),
             ignored_comments: Vec::new(),
             rule_index: rule.index,
@@ -142,11 +169,23 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
         };
         // Second matching pass, where we record placeholder matches, ignored comments and maybe do
         // any other more expensive checks that we didn't want to do on the first pass.
-        match_state.attempt_match_node(
-            &mut Phase::Second(&mut the_match),
-            &rule.pattern.node,
-            code,
-        )?;
+        for (pattern, code) in &pairs {
+            match_state.attempt_match_node(&mut Phase::Second(&mut the_match), pattern, code)?;
+        }
+        if let Some(end) = the_match.sequence_end.clone() {
+            // Comments between the statements would otherwise be lost by the replacement.
+            let mut phase = Phase::Second(&mut the_match);
+            let mut element = code.next_sibling_or_token();
+            while let Some(it) = element {
+                if it.text_range().start() >= end.text_range().start() {
+                    break;
+                }
+                if let SyntaxElement::Token(token) = &it {
+                    phase.record_ignored_comments(token);
+                }
+                element = it.next_sibling_or_token();
+            }
+        }
         the_match.depth = sema.ancestors_with_macros(the_match.matched_node.clone()).count();
         if let Some(template) = &rule.template {
             the_match.render_template_paths(template, sema)?;
@@ -191,6 +230,9 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
             }
             return Ok(());
         }
+        if let Some(stand_in) = parsing::item_list_placeholder(pattern) {
+            return self.attempt_match_item_list(phase, &stand_in, pattern, code);
+        }
         // We allow a UFCS call to match a method call, provided they resolve to the same function.
         if let Some(pattern_ufcs) = self.rule.pattern.ufcs_function_calls.get(pattern) {
             if let Some(code) = ast::MethodCallExpr::cast(code.clone()) {
@@ -221,6 +263,134 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
         }
     }
 
+    /// Returns the pairs of pattern and code statements to match, when the pattern is a sequence
+    /// of statements, which starts at the statement `code`.
+    fn stmt_sequence_pairs(
+        &self,
+        order: StmtOrder,
+        code: &SyntaxNode,
+    ) -> Result<Vec<(SyntaxNode, SyntaxNode)>, MatchFailed> {
+        let patterns = block_elements(&self.rule.pattern.node);
+        let block = code
+            .parent()
+            .filter(|it| it.kind() == SyntaxKind::BLOCK_EXPR)
+            .ok_or_else(|| match_error!("Code `{}` isn't a statement", code.text()))?;
+        let codes = block_elements(&block)
+            .into_iter()
+            .skip_while(|it| it != code)
+            .take(patterns.len())
+            .collect::<Vec<_>>();
+        if codes.len() < patterns.len() {
+            fail_match!("Code has fewer statements than the pattern");
+        }
+        let pairs = match order {
+            StmtOrder::Ordered => patterns.into_iter().zip(codes).collect(),
+            StmtOrder::Unordered => {
+                let mut used = vec![false; patterns.len()];
+                let mut assignment = Vec::new();
+                if !self.assign_stmts(&patterns, &codes, &mut used, &mut assignment) {
+                    fail_match!("Statements don't match the pattern in any order");
+                }
+                assignment.into_iter().map(|idx| patterns[idx].clone()).zip(codes).collect()
+            }
+        };
+        Ok(pairs)
+    }
+
+    /// Finds a pattern statement, in `patterns`, which matches each statement of `codes`. Each
+    /// pattern statement can be used only once. The index of the pattern matching each statement
+    /// is pushed to `assignment`.
+    fn assign_stmts(
+        &self,
+        patterns: &[SyntaxNode],
+        codes: &[SyntaxNode],
+        used: &mut [bool],
+        assignment: &mut Vec<usize>,
+    ) -> bool {
+        let code = match codes.get(assignment.len()) {
+            Some(it) => it,
+            None => return true,
+        };
+        for (idx, pattern) in patterns.iter().enumerate() {
+            if used[idx] || !self.stmt_matches(pattern, code) {
+                continue;
+            }
+            used[idx] = true;
+            assignment.push(idx);
+            if self.assign_stmts(patterns, codes, used, assignment) {
+                return true;
+            }
+            assignment.pop();
+            used[idx] = false;
+        }
+        false
+    }
+
+    /// Checks whether the statement `code` matches `pattern`, including the checks of the second
+    /// phase, such as path resolution.
+    fn stmt_matches(&self, pattern: &SyntaxNode, code: &SyntaxNode) -> bool {
+        if self.attempt_match_node(&mut Phase::First, pattern, code).is_err() {
+            return false;
+        }
+        let mut scratch = Match {
+            range: self.sema.original_range(code),
+            matched_node: code.clone(),
+            sequence_end: None,
+            placeholder_values: FxHashMap::default(),
+            ignored_comments: Vec::new(),
+            rule_index: self.rule.index,
+            depth: 0,
+            rendered_template_paths: FxHashMap::default(),
+        };
+        self.attempt_match_node(&mut Phase::Second(&mut scratch), pattern, code).is_ok()
+    }
+
+    /// A placeholder standing for all the items of an impl, a trait or a module matches the code
+    /// between the braces.
+    fn attempt_match_item_list(
+        &self,
+        phase: &mut Phase,
+        stand_in: &SmolStr,
+        pattern: &SyntaxNode,
+        code: &SyntaxNode,
+    ) -> Result<(), MatchFailed> {
+        if pattern.kind() != code.kind() {
+            fail_match!("Pattern had an item list, code had `{}`", code.text());
+        }
+        if let Phase::Second(matches_out) = phase {
+            let placeholder = match self.rule.pattern.placeholders_by_stand_in.get(stand_in) {
+                Some(it) => it,
+                None => fail_match!("Unknown placeholder `{}`", stand_in),
+            };
+            let items = code
+                .children_with_tokens()
+                .filter(|it| !matches!(it.kind(), SyntaxKind::WHITESPACE | T!['{'] | T!['}']))
+                .collect::<Vec<_>>();
+            let original_range = self.sema.original_range(code);
+            let range = match (items.first(), items.last()) {
+                (Some(first), Some(last)) => {
+                    let range = first.text_range().cover(last.text_range());
+                    FileRange {
+                        file_id: original_range.file_id,
+                        range: range - code.text_range().start() + original_range.range.start(),
+                    }
+                }
+                _ => {
+                    let offset = code
+                        .first_token()
+                        .map_or(code.text_range().start(), |it| it.text_range().end());
+                    let offset = offset - code.text_range().start() + original_range.range.start();
+                    FileRange { file_id: original_range.file_id, range: TextRange::empty(offset) }
+                }
+            };
+            self.validate_range(&range)?;
+            matches_out
+                .placeholder_values
+                .insert(placeholder.ident.clone(), PlaceholderMatch::from_range(range));
+        }
+        Ok(())
+    }
+
     fn attempt_match_node_children(
         &self,
         phase: &mut Phase,
@@ -329,10 +499,110 @@ impl<'db, 'sema> Matcher<'db, 'sema> {
                     fail_match!("Constraint {:?} failed for '{}'", constraint, code.text());
                 }
             }
+            Constraint::Type(pattern) => {
+                let ty = self.type_of(code).ok_or_else(|| {
+                    match_error!("Couldn't determine the type of '{}'", code.text())
+                })?;
+                if !self.type_matches(pattern, &ty, &mut FxHashMap::default()) {
+                    fail_match!("Type of '{}' doesn't match `{}`", code.text(), pattern);
+                }
+            }
+            Constraint::ResolvesTo(path) => {
+                let expected = self.rule.pattern.constraint_paths.get(path.syntax());
+                let resolution = path_of(code).and_then(|it| self.sema.resolve_path(&it));
+                if expected.is_none() || resolution.as_ref() != expected {
+                    cov_mark::hit!(resolves_to_constraint_failed);
+                    fail_match!("'{}' doesn't resolve to `{}`", code.text(), path);
+                }
+            }
         }
         Ok(())
     }
 
+    fn type_of(&self, code: &SyntaxNode) -> Option<hir::Type> {
+        if let Some(expr) = ast::Expr::cast(code.clone()) {
+            return self.sema.type_of_expr(&expr);
+        }
+        if let Some(pat) = ast::Pat::cast(code.clone()) {
+            return self.sema.type_of_pat(&pat);
+        }
+        self.sema.resolve_type(&ast::Type::cast(code.clone())?)
+    }
+
+    /// Checks whether `ty` matches the type `pattern` from a `type` constraint, recording the
+    /// types that the placeholders within `pattern` match in `bindings`.
+    fn type_matches(
+        &self,
+        pattern: &ast::Type,
+        ty: &hir::Type,
+        bindings: &mut FxHashMap<SmolStr, hir::Type>,
+    ) -> bool {
+        use hir::HirDisplay;
+        let db = self.sema.db;
+        match pattern {
+            ast::Type::InferType(_) => true,
+            ast::Type::ParenType(it) => {
+                it.ty().map_or(false, |it| self.type_matches(&it, ty, bindings))
+            }
+            ast::Type::RefType(it) => match (it.ty(), ty.remove_ref()) {
+                (Some(pattern), Some(inner)) => {
+                    ty.is_mutable_reference() == it.mut_token().is_some()
+                        && self.type_matches(&pattern, &inner, bindings)
+                }
+                _ => false,
+            },
+            ast::Type::TupleType(it) => {
+                let fields = ty.tuple_fields(db);
+                let patterns = it.fields().collect::<Vec<_>>();
+                if patterns.is_empty() {
+                    return ty.is_unit();
+                }
+                fields.len() == patterns.len()
+                    && patterns.iter().zip(&fields).all(|(p, f)| self.type_matches(p, f, bindings))
+            }
+            ast::Type::PathType(it) => {
+                let path = match it.path() {
+                    Some(it) => it,
+                    None => return false,
+                };
+                if let Some(name) = parsing::type_placeholder(&path) {
+                    return match bindings.get(&name) {
+                        Some(bound) => bound == ty,
+                        None => {
+                            bindings.insert(name, ty.clone());
+                            true
+                        }
+                    };
+                }
+                match self.rule.pattern.constraint_paths.get(path.syntax()) {
+                    Some(hir::PathResolution::Def(hir::ModuleDef::Adt(adt))) => {
+                        if ty.as_adt() != Some(*adt) {
+                            return false;
+                        }
+                        let args = match path.segment().and_then(|it| it.generic_arg_list()) {
+                            Some(it) => it
+                                .generic_args()
+                                .filter_map(|arg| match arg {
+                                    ast::GenericArg::TypeArg(it) => it.ty(),
+                                    _ => None,
+                                })
+                                .collect::<Vec<_>>(),
+                            None => return true,
+                        };
+                        let type_args = ty.type_arguments().collect::<Vec<_>>();
+                        args.len() == type_args.len()
+                            && args
+                                .iter()
+                                .zip(&type_args)
+                                .all(|(p, t)| self.type_matches(p, t, bindings))
+                    }
+                    _ => ty.display(db).to_string() == pattern.syntax().text().to_string(),
+                }
+            }
+            _ => ty.display(db).to_string() == pattern.syntax().text().to_string(),
+        }
+    }
+
     /// Paths are matched based on whether they refer to the same thing, even if they're written
     /// differently.
     fn attempt_match_path(
@@ -741,6 +1011,31 @@ impl NodeKind {
     }
 }
 
+/// Returns the statements and the tail expression of `block`.
+fn block_elements(block: &SyntaxNode) -> Vec<SyntaxNode> {
+    block
+        .children()
+        .filter(|it| ast::Stmt::can_cast(it.kind()) || ast::Expr::can_cast(it.kind()))
+        .collect()
+}
+
+/// Returns the path that `node`, a path or an expression, type or pattern consisting of a path,
+/// is made of.
+fn path_of(node: &SyntaxNode) -> Option<ast::Path> {
+    if let Some(path) = ast::Path::cast(node.clone()) {
+        return Some(path);
+    }
+    match ast::Expr::cast(node.clone()) {
+        Some(ast::Expr::PathExpr(it)) => return it.path(),
+        Some(_) => return None,
+        None => {}
+    }
+    match ast::Type::cast(node.clone())? {
+        ast::Type::PathType(it) => it.path(),
+        _ => None,
+    }
+}
+
 // If `node` contains nothing but an ident then return it, otherwise return None.
 fn only_ident(element: SyntaxElement) -> Option<SyntaxToken> {
     match element {
@@ -48,6 +48,25 @@ impl MatchCollector {
                 return;
             }
         }
+        // A sequence of statements isn't a single node, so it's compared with the other matches by
+        // range. Since other matches of its statements have already been seen, it's discarded if
+        // it overlaps any of them.
+        if m.sequence_end.is_some() {
+            let overlaps = |it: &Match| {
+                it.range.file_id == m.range.file_id
+                    && it.range.range.intersect(m.range.range).map_or(false, |it| !it.is_empty())
+            };
+            if self.matches_by_node.values().any(overlaps) {
+                return;
+            }
+        } else if let Some(existing) = self.matches_by_node.values_mut().find(|it| {
+            it.sequence_end.is_some()
+                && it.range.file_id == m.range.file_id
+                && it.range.range.contains_range(m.range.range)
+        }) {
+            try_add_sub_match(m, existing, sema);
+            return;
+        }
         self.matches_by_node.insert(matched_node, m);
     }
 }
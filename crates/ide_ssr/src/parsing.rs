@@ -16,6 +16,16 @@ pub(crate) struct ParsedRule {
     pub(crate) placeholders_by_stand_in: FxHashMap<SmolStr, Placeholder>,
     pub(crate) pattern: SyntaxNode,
     pub(crate) template: Option<SyntaxNode>,
+    /// Set when `pattern` and `template` are blocks, whose statements match a sequence of
+    /// statements within a block of code.
+    pub(crate) stmt_sequence: Option<StmtOrder>,
+}
+
+/// Whether the statements of a sequence pattern have to be matched in the order they're written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StmtOrder {
+    Ordered,
+    Unordered,
 }
 
 #[derive(Debug)]
@@ -47,6 +57,11 @@ pub(crate) struct Var(pub(crate) String);
 pub(crate) enum Constraint {
     Kind(NodeKind),
     Not(Box<Constraint>),
+    /// The type of the matched code has to match a type, in which `_` and `$name` match any
+    /// type. All the occurrences of `$name` have to match the same type.
+    Type(ast::Type),
+    /// The matched path has to resolve to the same item as a path.
+    ResolvesTo(ast::Path),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -64,6 +79,7 @@ impl ParsedRule {
     fn new(
         pattern: &RawPattern,
         template: Option<&RawPattern>,
+        unordered: bool,
     ) -> Result<Vec<ParsedRule>, SsrError> {
         let raw_pattern = pattern.as_rust_code();
         let raw_template = template.map(|t| t.as_rust_code());
@@ -80,10 +96,26 @@ impl ParsedRule {
             builder.try_add(ast::Expr::parse(&raw_pattern), raw_template_stmt.clone());
         }
         builder.try_add(ast::Type::parse(&raw_pattern), raw_template.map(ast::Type::parse));
-        builder.try_add(ast::Item::parse(&raw_pattern), raw_template.map(ast::Item::parse));
+        // Placeholders standing for all the items of an impl, trait or module are only valid
+        // Rust as macro calls.
+        let item = ast::Item::parse(&raw_pattern)
+            .or_else(|()| ast::Item::parse(&pattern.as_rust_code_with_item_lists()));
+        let template_item = template.map(|t| {
+            ast::Item::parse(&t.as_rust_code())
+                .or_else(|()| ast::Item::parse(&t.as_rust_code_with_item_lists()))
+        });
+        builder.try_add(item, template_item);
         builder.try_add(ast::Path::parse(&raw_pattern), raw_template.map(ast::Path::parse));
         builder.try_add(ast::Pat::parse(&raw_pattern), raw_template.map(ast::Pat::parse));
         builder.try_add(ast::Stmt::parse(&raw_pattern), raw_template_stmt);
+        match parse_stmts(&raw_pattern) {
+            Ok(block) if block.statements().count() + block.tail_expr().iter().count() > 1 => {
+                let order = if unordered { StmtOrder::Unordered } else { StmtOrder::Ordered };
+                builder.add_stmt_sequence(block, raw_template.map(parse_stmts), order);
+            }
+            _ if unordered => bail!("Only a sequence of statements can be matched in any order"),
+            _ => {}
+        }
         builder.build()
     }
 }
@@ -104,19 +136,40 @@ impl RuleBuilder {
                 placeholders_by_stand_in: self.placeholders_by_stand_in.clone(),
                 pattern: pattern.syntax().clone(),
                 template: Some(template.syntax().clone()),
+                stmt_sequence: None,
             }),
             (Ok(pattern), None) => self.rules.push(ParsedRule {
                 placeholders_by_stand_in: self.placeholders_by_stand_in.clone(),
                 pattern: pattern.syntax().clone(),
                 template: None,
+                stmt_sequence: None,
             }),
             _ => {}
         }
     }
 
+    fn add_stmt_sequence(
+        &mut self,
+        pattern: ast::BlockExpr,
+        template: Option<Result<ast::BlockExpr, ()>>,
+        order: StmtOrder,
+    ) {
+        let template = match template {
+            Some(Ok(it)) => Some(it.syntax().clone()),
+            Some(Err(())) => return,
+            None => None,
+        };
+        self.rules.push(ParsedRule {
+            placeholders_by_stand_in: self.placeholders_by_stand_in.clone(),
+            pattern: pattern.syntax().clone(),
+            template,
+            stmt_sequence: Some(order),
+        });
+    }
+
     fn build(mut self) -> Result<Vec<ParsedRule>, SsrError> {
         if self.rules.is_empty() {
-            bail!("Not a valid Rust expression, type, item, path or pattern");
+            bail!("Not a valid Rust expression, type, item, path, pattern or statements");
         }
         // If any rules contain paths, then we reject any rules that don't contain paths. Allowing a
         // mix leads to strange semantics, since the path-based rules only match things where the
@@ -137,6 +190,14 @@ impl RuleBuilder {
     }
 }
 
+/// Returns `text` parsed as the statements of a block.
+fn parse_stmts(text: &str) -> Result<ast::BlockExpr, ()> {
+    match ast::Expr::parse(&format!("{{\n{}\n}}", text))? {
+        ast::Expr::BlockExpr(it) if it.label().is_none() => Ok(it),
+        _ => Err(()),
+    }
+}
+
 /// Returns whether there are any paths in `node`.
 fn contains_path(node: &SyntaxNode) -> bool {
     node.kind() == SyntaxKind::PATH
@@ -157,15 +218,42 @@ impl FromStr for SsrRule {
         if it.next().is_some() {
             return Err(SsrError("More than one delimiter found".into()));
         }
+        let (pattern, unordered) = strip_unordered(pattern);
         let raw_pattern = pattern.parse()?;
         let raw_template = template.parse()?;
-        let parsed_rules = ParsedRule::new(&raw_pattern, Some(&raw_template))?;
-        let rule = SsrRule { pattern: raw_pattern, template: raw_template, parsed_rules };
+        let parsed_rules = ParsedRule::new(&raw_pattern, Some(&raw_template), unordered)?;
+        let rule = SsrRule {
+            text: query.trim().to_string(),
+            pattern: raw_pattern,
+            template: raw_template,
+            parsed_rules,
+        };
         validate_rule(&rule)?;
         Ok(rule)
     }
 }
 
+/// Removes the `#[unordered]` marker, which allows the statements of a sequence to match in any
+/// order, from the start of a pattern.
+fn strip_unordered(pattern: &str) -> (&str, bool) {
+    match pattern.strip_prefix("#[unordered]") {
+        Some(rest) => (rest.trim_start(), true),
+        None => (pattern, false),
+    }
+}
+
+/// Parses a file of rules, one per line. Empty lines and lines starting with `//` are skipped.
+pub fn parse_rules(text: &str) -> Result<Vec<SsrRule>, SsrError> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("//"))
+        .map(|(line_number, line)| {
+            line.parse().map_err(|SsrError(err)| SsrError(format!("line {}: {}", line_number, err)))
+        })
+        .collect()
+}
+
 impl FromStr for RawPattern {
     type Err = SsrError;
 
@@ -187,6 +275,32 @@ impl RawPattern {
         res
     }
 
+    /// Like `as_rust_code`, but placeholders which are the only thing within braces are written as
+    /// macro calls, so that they can stand for the items of an impl, a trait or a module.
+    fn as_rust_code_with_item_lists(&self) -> String {
+        let mut res = String::new();
+        let significant =
+            |t: &PatternElement| !matches!(t, PatternElement::Token(t) if t.kind.is_trivia());
+        for (idx, t) in self.tokens.iter().enumerate() {
+            match t {
+                PatternElement::Token(token) => res.push_str(&token.text),
+                PatternElement::Placeholder(placeholder) => {
+                    res.push_str(&placeholder.stand_in_name);
+                    let prev = self.tokens[..idx].iter().rev().find(|t| significant(*t));
+                    let next = self.tokens[idx + 1..].iter().find(|t| significant(*t));
+                    if let (Some(PatternElement::Token(prev)), Some(PatternElement::Token(next))) =
+                        (prev, next)
+                    {
+                        if prev.kind == T!['{'] && next.kind == T!['}'] {
+                            res.push_str("!{}");
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
     pub(crate) fn placeholders_by_stand_in(&self) -> FxHashMap<SmolStr, Placeholder> {
         let mut res = FxHashMap::default();
         for t in &self.tokens {
@@ -202,8 +316,9 @@ impl FromStr for SsrPattern {
     type Err = SsrError;
 
     fn from_str(pattern_str: &str) -> Result<SsrPattern, SsrError> {
+        let (pattern_str, unordered) = strip_unordered(pattern_str.trim());
         let raw_pattern = pattern_str.parse()?;
-        let parsed_rules = ParsedRule::new(&raw_pattern, None)?;
+        let parsed_rules = ParsedRule::new(&raw_pattern, None, unordered)?;
         Ok(SsrPattern { raw: raw_pattern, parsed_rules })
     }
 }
@@ -333,10 +448,51 @@ fn parse_constraint(tokens: &mut std::vec::IntoIter<Token>) -> Result<Constraint
             expect_token(tokens, ")")?;
             Ok(Constraint::Not(Box::new(sub)))
         }
+        "type" => {
+            expect_token(tokens, "(")?;
+            let text = parse_until_close_paren(tokens)?;
+            let ty = ast::Type::parse(&text)
+                .map_err(|()| SsrError::new(format!("Invalid type constraint `{}`", text)))?;
+            Ok(Constraint::Type(ty))
+        }
+        "resolves_to" => {
+            expect_token(tokens, "(")?;
+            let text = parse_until_close_paren(tokens)?;
+            let path = ast::Path::parse(&text)
+                .map_err(|()| SsrError::new(format!("Invalid path constraint `{}`", text)))?;
+            Ok(Constraint::ResolvesTo(path))
+        }
         x => bail!("Unsupported constraint type '{}'", x),
     }
 }
 
+/// Returns the source code of the tokens up to the closing parenthesis of a constraint, with
+/// placeholders replaced by their stand-ins.
+fn parse_until_close_paren(tokens: &mut std::vec::IntoIter<Token>) -> Result<String, SsrError> {
+    let mut res = String::new();
+    let mut depth = 0;
+    loop {
+        let token = tokens
+            .next()
+            .ok_or_else(|| SsrError::new("Constraint is missing closing parenthesis ')'"))?;
+        match token.kind {
+            T!['('] => depth += 1,
+            T![')'] if depth == 0 => return Ok(res),
+            T![')'] => depth -= 1,
+            T![$] => {
+                let name = tokens
+                    .next()
+                    .filter(|it| it.kind == SyntaxKind::IDENT)
+                    .ok_or_else(|| SsrError::new("Placeholder ($) with no name in constraint"))?;
+                res.push_str(&stand_in_name(&name.text));
+                continue;
+            }
+            _ => {}
+        }
+        res.push_str(&token.text);
+    }
+}
+
 fn expect_token(tokens: &mut std::vec::IntoIter<Token>, expected: &str) -> Result<(), SsrError> {
     if let Some(t) = tokens.next() {
         if t.text == expected {
@@ -358,12 +514,42 @@ impl NodeKind {
 
 impl Placeholder {
     fn new(name: SmolStr, constraints: Vec<Constraint>) -> Self {
-        Self {
-            stand_in_name: format!("__placeholder_{}", name),
-            constraints,
-            ident: Var(name.to_string()),
-        }
+        Self { stand_in_name: stand_in_name(&name), constraints, ident: Var(name.to_string()) }
+    }
+}
+
+/// Returns the name used in place of the placeholder `$name` when parsing patterns as Rust code.
+pub(crate) fn stand_in_name(name: &str) -> String {
+    format!("__placeholder_{}", name)
+}
+
+/// Returns the stand-in name if `node` is a placeholder standing for all the items of an impl, a
+/// trait or a module, which is parsed as a macro call.
+pub(crate) fn item_list_placeholder(node: &SyntaxNode) -> Option<SmolStr> {
+    let list = match node.kind() {
+        SyntaxKind::ASSOC_ITEM_LIST | SyntaxKind::ITEM_LIST => node,
+        _ => return None,
+    };
+    let mut children = list.children();
+    let call = ast::MacroCall::cast(children.next()?)?;
+    if children.next().is_some() || call.token_tree()?.syntax().text() != "{}" {
+        return None;
+    }
+    let name_ref = call.path()?.segment()?.name_ref()?;
+    let is_placeholder = name_ref.text().starts_with(&stand_in_name(""));
+    is_placeholder.then(|| SmolStr::new(name_ref.text().as_str()))
+}
+
+/// Returns the stand-in name if `path` is a placeholder within a `type` constraint.
+pub(crate) fn type_placeholder(path: &ast::Path) -> Option<SmolStr> {
+    if path.qualifier().is_some() {
+        return None;
     }
+    let segment = path.segment()?;
+    let name_ref = segment.name_ref()?;
+    let is_placeholder =
+        segment.generic_arg_list().is_none() && name_ref.text().starts_with(&stand_in_name(""));
+    is_placeholder.then(|| SmolStr::new(name_ref.text().as_str()))
 }
 
 impl Display for Var {
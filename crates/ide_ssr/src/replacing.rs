@@ -1,6 +1,6 @@
 //! Code for applying replacement templates for matches that have previously been found.
 
-use crate::{resolving::ResolvedRule, Match, SsrMatches};
+use crate::{parsing, resolving::ResolvedRule, Match, SsrMatches};
 use itertools::Itertools;
 use rustc_hash::{FxHashMap, FxHashSet};
 use syntax::ast::{self, AstNode, AstToken};
@@ -49,6 +49,9 @@ struct ReplacementRenderer<'a> {
     // is parsed, placeholders don't get split. e.g. if a template of `$a.to_string()` results in `1
     // + 2.to_string()` then the placeholder value `1 + 2` was split and needs parenthesis.
     placeholder_tokens_requiring_parenthesis: FxHashSet<SyntaxToken>,
+    // For a sequence of statements, the whitespace between the statements of the template, which is
+    // taken from the matched code.
+    stmt_separator: Option<String>,
 }
 
 fn render_replace(match_info: &Match, file_src: &str, rules: &[ResolvedRule]) -> String {
@@ -65,9 +68,23 @@ fn render_replace(match_info: &Match, file_src: &str, rules: &[ResolvedRule]) ->
         out: String::new(),
         placeholder_tokens_requiring_parenthesis: FxHashSet::default(),
         placeholder_tokens_by_range: FxHashMap::default(),
+        stmt_separator: None,
     };
-    renderer.render_node(&template.node);
+    if rule.stmt_sequence.is_some() {
+        let separator = match match_info.matched_node.next_sibling_or_token() {
+            Some(SyntaxElement::Token(it)) if it.kind() == SyntaxKind::WHITESPACE => {
+                it.text().to_string()
+            }
+            _ => " ".to_string(),
+        };
+        renderer.stmt_separator = Some(separator);
+    }
+    renderer.render_template(&template.node);
     renderer.maybe_rerender_with_extra_parenthesis(&template.node);
+    if renderer.stmt_separator.is_some() {
+        // Remove the braces added by `render_template`.
+        renderer.out = renderer.out[1..renderer.out.len() - 1].to_string();
+    }
     for comment in &match_info.ignored_comments {
         renderer.out.push_str(&comment.syntax().to_string());
     }
@@ -75,6 +92,23 @@ fn render_replace(match_info: &Match, file_src: &str, rules: &[ResolvedRule]) ->
 }
 
 impl ReplacementRenderer<'_> {
+    fn render_template(&mut self, template: &SyntaxNode) {
+        match self.stmt_separator.clone() {
+            Some(separator) => {
+                // The statements are rendered within braces, so that the result can be parsed.
+                self.out.push('{');
+                for (idx, stmt) in template.children().enumerate() {
+                    if idx > 0 {
+                        self.out.push_str(&separator);
+                    }
+                    self.render_node(&stmt);
+                }
+                self.out.push('}');
+            }
+            None => self.render_node(template),
+        }
+    }
+
     fn render_node_children(&mut self, node: &SyntaxNode) {
         for node_or_token in node.children_with_tokens() {
             self.render_node_or_token(&node_or_token);
@@ -93,7 +127,24 @@ impl ReplacementRenderer<'_> {
     }
 
     fn render_node(&mut self, node: &SyntaxNode) {
-        if let Some(mod_path) = self.match_info.rendered_template_paths.get(node) {
+        if let Some(stand_in) = parsing::item_list_placeholder(node) {
+            let placeholder_token = node
+                .descendants_with_tokens()
+                .filter_map(|it| it.into_token())
+                .find(|it| it.text() == stand_in.as_str());
+            for node_or_token in node.children_with_tokens() {
+                match node_or_token {
+                    SyntaxElement::Node(_) => {
+                        if let Some(token) = &placeholder_token {
+                            self.render_token(token);
+                            // The items are never split by the parser, they don't need parenthesis.
+                            self.placeholder_tokens_by_range.retain(|_, it| it != token);
+                        }
+                    }
+                    _ => self.render_node_or_token(&node_or_token),
+                }
+            }
+        } else if let Some(mod_path) = self.match_info.rendered_template_paths.get(node) {
             self.out.push_str(&mod_path.to_string());
             // Emit everything except for the segment's name-ref, since we already effectively
             // emitted that as part of `mod_path`.
@@ -189,7 +240,7 @@ impl ReplacementRenderer<'_> {
             self.placeholder_tokens_requiring_parenthesis =
                 self.placeholder_tokens_by_range.values().cloned().collect();
             self.out.clear();
-            self.render_node(template);
+            self.render_template(template);
         }
     }
 
@@ -16,6 +16,7 @@ pub(crate) struct ResolvedRule {
     pub(crate) pattern: ResolvedPattern,
     pub(crate) template: Option<ResolvedPattern>,
     pub(crate) index: usize,
+    pub(crate) stmt_sequence: Option<parsing::StmtOrder>,
 }
 
 pub(crate) struct ResolvedPattern {
@@ -25,6 +26,8 @@ pub(crate) struct ResolvedPattern {
     pub(crate) resolved_paths: FxHashMap<SyntaxNode, ResolvedPath>,
     pub(crate) ufcs_function_calls: FxHashMap<SyntaxNode, UfcsCallInfo>,
     pub(crate) contains_self: bool,
+    // Paths in the `type` and `resolves_to` constraints of placeholders that we've resolved.
+    pub(crate) constraint_paths: FxHashMap<SyntaxNode, hir::PathResolution>,
 }
 
 pub(crate) struct ResolvedPath {
@@ -56,6 +59,7 @@ impl ResolvedRule {
             pattern: resolver.resolve_pattern_tree(rule.pattern)?,
             template: resolved_template,
             index,
+            stmt_sequence: rule.stmt_sequence,
         })
     }
 
@@ -102,15 +106,49 @@ impl Resolver<'_, '_> {
                 SyntaxElement::Token(t) => t.kind() == T![self],
                 _ => false,
             });
+        let mut constraint_paths = FxHashMap::default();
+        for placeholder in self.placeholders_by_stand_in.values() {
+            for constraint in &placeholder.constraints {
+                self.resolve_constraint(constraint, &mut constraint_paths)?;
+            }
+        }
         Ok(ResolvedPattern {
             node: pattern,
             resolved_paths,
             placeholders_by_stand_in: self.placeholders_by_stand_in.clone(),
             ufcs_function_calls,
             contains_self,
+            constraint_paths,
         })
     }
 
+    fn resolve_constraint(
+        &self,
+        constraint: &parsing::Constraint,
+        constraint_paths: &mut FxHashMap<SyntaxNode, hir::PathResolution>,
+    ) -> Result<(), SsrError> {
+        use syntax::ast::AstNode;
+        let node = match constraint {
+            parsing::Constraint::Kind(_) => return Ok(()),
+            parsing::Constraint::Not(sub) => return self.resolve_constraint(sub, constraint_paths),
+            parsing::Constraint::Type(ty) => ty.syntax(),
+            parsing::Constraint::ResolvesTo(path) => path.syntax(),
+        };
+        // Resolve the outermost paths, except for the placeholders within types.
+        let paths = node.descendants().filter_map(ast::Path::cast).filter(|path| {
+            path.syntax().parent().and_then(ast::Path::cast).is_none()
+                && parsing::type_placeholder(path).is_none()
+        });
+        for path in paths {
+            let resolution = self
+                .resolution_scope
+                .resolve_path(&path)
+                .ok_or_else(|| error!("Failed to resolve path `{}`", path))?;
+            constraint_paths.insert(path.syntax().clone(), resolution);
+        }
+        Ok(())
+    }
+
     fn resolve(
         &self,
         node: SyntaxNode,
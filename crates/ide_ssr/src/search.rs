@@ -57,13 +57,24 @@ impl<'db> MatchFinder<'db> {
     ) {
         if let Some(resolved_path) = pick_path_for_usages(pattern) {
             let definition: Definition = resolved_path.resolution.clone().into();
+            let mut searched_blocks = FxHashSet::default();
             for file_range in self.find_usages(usage_cache, definition).file_ranges() {
                 if let Some(node_to_match) = self.find_node_to_match(resolved_path, file_range) {
                     if !is_search_permitted_ancestors(&node_to_match) {
                         cov_mark::hit!(use_declaration_with_braces);
                         continue;
                     }
-                    self.try_add_match(rule, &node_to_match, &None, matches_out);
+                    if rule.stmt_sequence.is_none() {
+                        self.try_add_match(rule, &node_to_match, &None, matches_out);
+                        continue;
+                    }
+                    // The node corresponding to the root of a sequence pattern is the block, the
+                    // sequence can start at any of its statements.
+                    if searched_blocks.insert(node_to_match.clone()) {
+                        for stmt in node_to_match.children() {
+                            self.try_add_match(rule, &stmt, &None, matches_out);
+                        }
+                    }
                 }
             }
         }
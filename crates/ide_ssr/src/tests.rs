@@ -1,4 +1,4 @@
-use crate::{MatchFinder, SsrRule};
+use crate::{parse_rules, MatchFinder, SsrRule};
 use expect_test::{expect, Expect};
 use ide_db::base_db::{salsa::Durability, FileId, FilePosition, FileRange, SourceDatabaseExt};
 use rustc_hash::FxHashSet;
@@ -39,7 +39,7 @@ fn parser_repeated_name() {
 fn parser_invalid_pattern() {
     assert_eq!(
         parse_error_text(" ==>> ()"),
-        "Parse error: Not a valid Rust expression, type, item, path or pattern"
+        "Parse error: Not a valid Rust expression, type, item, path, pattern or statements"
     );
 }
 
@@ -47,7 +47,7 @@ fn parser_invalid_pattern() {
 fn parser_invalid_template() {
     assert_eq!(
         parse_error_text("() ==>> )"),
-        "Parse error: Not a valid Rust expression, type, item, path or pattern"
+        "Parse error: Not a valid Rust expression, type, item, path, pattern or statements"
     );
 }
 
@@ -524,6 +524,156 @@ fn literal_constraint() {
     assert_matches("Some(${a:not(kind(literal))})", code, &["Some(x1)", "Some(40 + 2)"]);
 }
 
+#[test]
+fn type_constraint() {
+    let code = r#"
+        enum Option<T> { Some(T), None }
+        struct Meters(i32);
+        fn foo<T>(x: T) {}
+        fn f1(a: Option<i32>, b: Option<Meters>, c: i32, d: (i32, i32), e: (i32, u8)) {
+            foo(a);
+            foo(b);
+            foo(c);
+            foo(d);
+            foo(e);
+        }
+        "#;
+    assert_matches("foo(${x:type(Option<$t>)})", code, &["foo(a)", "foo(b)"]);
+    assert_matches("foo(${x:type(Option<Meters>)})", code, &["foo(b)"]);
+    assert_matches("foo(${x:type(i32)})", code, &["foo(c)"]);
+    assert_matches("foo(${x:type(($t, $t))})", code, &["foo(d)"]);
+    assert_matches("foo(${x:not(type((i32, _)))})", code, &["foo(a)", "foo(b)", "foo(c)"]);
+}
+
+#[test]
+fn resolves_to_constraint() {
+    cov_mark::check!(resolves_to_constraint_failed);
+    let code = r#"
+        mod mem { pub fn swap(a: &mut i32, b: &mut i32) {} }
+        mod other { pub fn swap(a: &mut i32, b: &mut i32) {} }
+        use other::swap;
+        fn f1(a: &mut i32, b: &mut i32) {
+            mem::swap(a, b);
+            swap(a, b);
+            crate::mem::swap(b, a);
+        }
+        "#;
+    assert_matches(
+        "${f:resolves_to(mem::swap)}($a, $b)",
+        code,
+        &["mem::swap(a, b)", "crate::mem::swap(b, a)"],
+    );
+}
+
+#[test]
+fn parser_invalid_constraint() {
+    assert_eq!(
+        parse_error_text("foo(${a:type(Option<)}) ==>> bar($a)"),
+        "Parse error: Invalid type constraint `Option<`"
+    );
+    assert_eq!(
+        parse_error_text("foo(${a:resolves_to(bar}) ==>> bar($a)"),
+        "Parse error: Invalid path constraint `bar}`"
+    );
+}
+
+#[test]
+fn parse_rule_file() {
+    let rules = parse_rules(
+        r#"
+// Migrate to the new API.
+foo($a) ==>> bar($a)
+
+    baz() ==>> qux()
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        rules.iter().map(|it| it.to_string()).collect::<Vec<_>>(),
+        ["foo($a) ==>> bar($a)", "baz() ==>> qux()"]
+    );
+    assert_eq!(
+        parse_rules("foo() ==>> bar()\nfoo($a) ==>> bar($b)").unwrap_err().to_string(),
+        "Parse error: line 2: Replacement contains undefined placeholders: $b"
+    );
+}
+
+#[test]
+fn match_counts_per_rule() {
+    let (db, position, selections) = single_file(
+        "fn foo(x: i32) {} fn bar(x: i32) {} fn baz() {} fn f() { foo(1); foo(bar(2)); bar(3); }",
+    );
+    let mut match_finder = MatchFinder::in_context(&db, position, selections);
+    for rule in parse_rules("foo($a) ==>> baz()\nbar($a) ==>> baz()\nbaz() ==>> foo(0)").unwrap() {
+        match_finder.add_rule(rule).unwrap();
+    }
+    let counts = match_finder.edits_and_match_counts().1;
+    assert_eq!(counts.iter().map(|it| it.values().sum()).collect::<Vec<usize>>(), vec![2, 2, 0]);
+}
+
+#[test]
+fn parser_unordered_single_statement() {
+    assert_eq!(
+        parse_error_text("#[unordered] foo($a) ==>> bar($a)"),
+        "Parse error: Only a sequence of statements can be matched in any order"
+    );
+}
+
+#[test]
+fn replace_stmt_sequence() {
+    assert_ssr_transform(
+        "foo($a); bar($b); ==>> foobar($a, $b);",
+        r#"
+fn foo(x: i32) {}
+fn bar(x: i32) {}
+fn foobar(x: i32, y: i32) {}
+fn f() {
+    foo(1);
+    bar(2);
+    bar(3);
+}
+"#,
+        expect![[r#"
+            fn foo(x: i32) {}
+            fn bar(x: i32) {}
+            fn foobar(x: i32, y: i32) {}
+            fn f() {
+                foobar(1, 2);
+                bar(3);
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn match_unordered_stmt_sequence() {
+    let code = "fn a() {} fn b() {} fn c() {} fn f() { c(); b(); a(); }";
+    assert_no_match("a(); b();", code);
+    assert_matches("#[unordered] a(); b();", code, &["b(); a();"]);
+    assert_matches("#[unordered] c(); b();", code, &["c(); b();"]);
+}
+
+#[test]
+fn replace_impl_items() {
+    assert_ssr_transform(
+        "impl Foo for $t { $items } ==>> impl Bar for $t { $items }",
+        r#"
+trait Foo { fn f(); }
+trait Bar { fn f(); }
+struct S;
+impl Foo for S {
+    fn f() {}
+}
+"#,
+        expect![[r#"
+            trait Foo { fn f(); }
+            trait Bar { fn f(); }
+            struct S;
+            impl Bar for S { fn f() {} }
+        "#]],
+    );
+}
+
 #[test]
 fn match_reordered_struct_instantiation() {
     assert_matches(
@@ -116,9 +116,15 @@ impl ProcMacroClient {
             Ok(macros) => macros,
         };
 
+        let ListMacrosResult { macros, derive_helpers } = macros;
         macros
             .into_iter()
             .map(|(name, kind)| {
+                let helpers = derive_helpers
+                    .iter()
+                    .filter(|(derive, _)| *derive == name)
+                    .flat_map(|(_, helpers)| helpers.iter().map(SmolStr::new))
+                    .collect();
                 let name = SmolStr::new(&name);
                 let kind = match kind {
                     ProcMacroKind::CustomDerive => base_db::ProcMacroKind::CustomDerive,
@@ -131,7 +137,7 @@ impl ProcMacroClient {
                     dylib_path: dylib_path.to_path_buf(),
                 });
 
-                ProcMacro { name, kind, expander }
+                ProcMacro { name, kind, helpers, expander }
             })
             .collect()
     }
@@ -12,7 +12,7 @@ use stdx::JodChild;
 
 use crate::{
     msg::{ErrorCode, Message, Request, Response, ResponseError},
-    rpc::{ListMacrosResult, ListMacrosTask, ProcMacroKind},
+    rpc::{ListMacrosResult, ListMacrosTask},
 };
 
 #[derive(Debug)]
@@ -38,11 +38,10 @@ impl ProcMacroProcessSrv {
     pub(crate) fn find_proc_macros(
         &mut self,
         dylib_path: &AbsPath,
-    ) -> Result<Vec<(String, ProcMacroKind)>, tt::ExpansionError> {
+    ) -> Result<ListMacrosResult, tt::ExpansionError> {
         let task = ListMacrosTask { lib: dylib_path.to_path_buf() };
 
-        let result: ListMacrosResult = self.send_task(Request::ListMacro(task))?;
-        Ok(result.macros)
+        self.send_task(Request::ListMacro(task))
     }
 
     pub(crate) fn send_task<R>(&mut self, req: Request) -> Result<R, tt::ExpansionError>
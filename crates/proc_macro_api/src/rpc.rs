@@ -28,6 +28,10 @@ pub enum ProcMacroKind {
 #[derive(Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
 pub struct ListMacrosResult {
     pub macros: Vec<(String, ProcMacroKind)>,
+    /// The helper attributes of the derive macros which have some, by the name
+    /// of the derived trait. Older servers don't send them.
+    #[serde(default)]
+    pub derive_helpers: Vec<(String, Vec<String>)>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
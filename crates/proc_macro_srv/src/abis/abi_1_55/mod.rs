@@ -101,4 +101,21 @@ impl Abi {
             })
             .collect()
     }
+
+    pub fn list_derive_helpers(&self) -> Vec<(String, Vec<String>)> {
+        self.exported_macros
+            .iter()
+            .filter_map(|proc_macro| match proc_macro {
+                proc_macro::bridge::client::ProcMacro::CustomDerive {
+                    trait_name,
+                    attributes,
+                    ..
+                } if !attributes.is_empty() => Some((
+                    trait_name.to_string(),
+                    attributes.iter().map(|it| it.to_string()).collect(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
 }
@@ -94,4 +94,11 @@ impl Abi {
             Self::Abi1_55(abi) => abi.list_macros(),
         }
     }
+
+    pub fn list_derive_helpers(&self) -> Vec<(String, Vec<String>)> {
+        match self {
+            Self::Abi1_47(abi) => abi.list_derive_helpers(),
+            Self::Abi1_55(abi) => abi.list_derive_helpers(),
+        }
+    }
 }
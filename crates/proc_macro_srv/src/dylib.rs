@@ -159,6 +159,10 @@ impl Expander {
     pub fn list_macros(&self) -> Vec<(String, ProcMacroKind)> {
         self.inner.abi.list_macros()
     }
+
+    pub fn list_derive_helpers(&self) -> Vec<(String, Vec<String>)> {
+        self.inner.abi.list_derive_helpers()
+    }
 }
 
 /// Copy the dylib to temp directory to prevent locking in Windows
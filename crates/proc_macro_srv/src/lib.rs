@@ -55,7 +55,10 @@ impl ProcMacroSrv {
 
     pub fn list_macros(&mut self, task: &ListMacrosTask) -> Result<ListMacrosResult, String> {
         let expander = self.expander(task.lib.as_ref())?;
-        Ok(ListMacrosResult { macros: expander.list_macros() })
+        Ok(ListMacrosResult {
+            macros: expander.list_macros(),
+            derive_helpers: expander.list_derive_helpers(),
+        })
     }
 
     fn expander(&mut self, path: &Path) -> Result<&dylib::Expander, String> {
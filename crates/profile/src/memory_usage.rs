@@ -91,6 +91,9 @@ fn memusage_linux() -> MemoryUsage {
 pub struct Bytes(isize);
 
 impl Bytes {
+    pub fn bytes(self) -> isize {
+        self.0
+    }
     pub fn megabytes(self) -> isize {
         self.0 / 1024 / 1024
     }
@@ -0,0 +1,44 @@
+//! Runs `rustc --version` to get the toolchain the crates are compiled with.
+
+use std::process::Command;
+
+use base_db::Toolchain;
+use paths::AbsPath;
+
+use crate::utf8_stdout;
+
+pub(crate) fn get(cargo_toml: Option<&AbsPath>) -> Option<Toolchain> {
+    let _p = profile::span("rustc_version::get");
+    let mut cmd = Command::new(toolchain::rustc());
+    // Run in the directory of the workspace to respect `rust-toolchain` files.
+    if let Some(dir) = cargo_toml.and_then(|it| it.parent()) {
+        cmd.current_dir(dir);
+    }
+    cmd.arg("--version");
+    let stdout = match utf8_stdout(cmd) {
+        Ok(it) => it,
+        Err(e) => {
+            log::error!("failed to get rustc version: {:#}", e);
+            return None;
+        }
+    };
+    let res = parse(&stdout);
+    if res.is_none() {
+        log::error!("failed to parse rustc version: {:?}", stdout);
+    }
+    res
+}
+
+/// Parses the output of `rustc --version`, like
+/// `rustc 1.55.0-nightly (b41936b92 2021-07-20)` or `rustc 1.54.0-beta.3 (..)`.
+fn parse(stdout: &str) -> Option<Toolchain> {
+    let version = stdout.split_whitespace().nth(1)?;
+    // The toolchain doesn't keep the number of beta releases, like the `.3`
+    // of `1.54.0-beta.3`.
+    let mut parts = version.splitn(2, '-');
+    let version = match (parts.next()?, parts.next()) {
+        (version, Some(channel)) => format!("{}-{}", version, channel.split('.').next()?),
+        (version, None) => version.to_string(),
+    };
+    version.parse().ok()
+}
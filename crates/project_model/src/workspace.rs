@@ -2,10 +2,12 @@
 //! metadata` or `rust-project.json`) into representation stored in the salsa
 //! database -- `CrateGraph`.
 
-use std::{collections::VecDeque, fmt, fs, process::Command};
+use std::{collections::VecDeque, fmt, fs, iter, process::Command};
 
 use anyhow::{format_err, Context, Result};
-use base_db::{CrateDisplayName, CrateGraph, CrateId, CrateName, Edition, Env, FileId, ProcMacro};
+use base_db::{
+    CrateDisplayName, CrateGraph, CrateId, CrateName, Edition, Env, FileId, ProcMacro, Toolchain,
+};
 use cargo_workspace::DepKind;
 use cfg::{CfgDiff, CfgOptions};
 use paths::{AbsPath, AbsPathBuf};
@@ -16,7 +18,7 @@ use crate::{
     build_data::{BuildDataResult, PackageBuildData, WorkspaceBuildData},
     cargo_workspace,
     cfg_flag::CfgFlag,
-    rustc_cfg,
+    rustc_cfg, rustc_version,
     sysroot::SysrootCrate,
     utf8_stdout, BuildDataCollector, CargoConfig, CargoWorkspace, ProjectJson, ProjectManifest,
     Sysroot, TargetKind,
@@ -49,9 +51,16 @@ pub enum ProjectWorkspace {
         /// different target.
         rustc_cfg: Vec<CfgFlag>,
         cfg_overrides: CfgOverrides,
+        /// The toolchain reported by `rustc --version`.
+        toolchain: Option<Toolchain>,
     },
     /// Project workspace was manually specified using a `rust-project.json` file.
-    Json { project: ProjectJson, sysroot: Option<Sysroot>, rustc_cfg: Vec<CfgFlag> },
+    Json {
+        project: ProjectJson,
+        sysroot: Option<Sysroot>,
+        rustc_cfg: Vec<CfgFlag>,
+        toolchain: Option<Toolchain>,
+    },
 
     // FIXME: The primary limitation of this approach is that the set of detached files needs to be fixed at the beginning.
     // That's not the end user experience we should strive for.
@@ -63,14 +72,26 @@ pub enum ProjectWorkspace {
     // //
     /// Project with a set of disjoint files, not belonging to any particular workspace.
     /// Backed by basic sysroot crates for basic completion and highlighting.
-    DetachedFiles { files: Vec<AbsPathBuf>, sysroot: Sysroot, rustc_cfg: Vec<CfgFlag> },
+    DetachedFiles {
+        files: Vec<AbsPathBuf>,
+        sysroot: Sysroot,
+        rustc_cfg: Vec<CfgFlag>,
+        toolchain: Option<Toolchain>,
+    },
 }
 
 impl fmt::Debug for ProjectWorkspace {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Make sure this isn't too verbose.
         match self {
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides } => f
+            ProjectWorkspace::Cargo {
+                cargo,
+                sysroot,
+                rustc,
+                rustc_cfg,
+                cfg_overrides,
+                toolchain,
+            } => f
                 .debug_struct("Cargo")
                 .field("root", &cargo.workspace_root().file_name())
                 .field("n_packages", &cargo.packages().len())
@@ -81,21 +102,24 @@ impl fmt::Debug for ProjectWorkspace {
                 )
                 .field("n_rustc_cfg", &rustc_cfg.len())
                 .field("n_cfg_overrides", &cfg_overrides.len())
+                .field("toolchain", toolchain)
                 .finish(),
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg } => {
+            ProjectWorkspace::Json { project, sysroot, rustc_cfg, toolchain } => {
                 let mut debug_struct = f.debug_struct("Json");
                 debug_struct.field("n_crates", &project.n_crates());
                 if let Some(sysroot) = sysroot {
                     debug_struct.field("n_sysroot_crates", &sysroot.crates().len());
                 }
                 debug_struct.field("n_rustc_cfg", &rustc_cfg.len());
+                debug_struct.field("toolchain", toolchain);
                 debug_struct.finish()
             }
-            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => f
+            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg, toolchain } => f
                 .debug_struct("DetachedFiles")
                 .field("n_files", &files.len())
                 .field("n_sysroot_crates", &sysroot.crates().len())
                 .field("n_rustc_cfg", &rustc_cfg.len())
+                .field("toolchain", toolchain)
                 .finish(),
         }
     }
@@ -170,8 +194,17 @@ impl ProjectWorkspace {
 
                 let rustc_cfg = rustc_cfg::get(Some(&cargo_toml), config.target.as_deref());
 
+                let toolchain = rustc_version::get(Some(&cargo_toml));
+
                 let cfg_overrides = config.cfg_overrides();
-                ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides }
+                ProjectWorkspace::Cargo {
+                    cargo,
+                    sysroot,
+                    rustc,
+                    rustc_cfg,
+                    cfg_overrides,
+                    toolchain,
+                }
             }
         };
 
@@ -187,7 +220,8 @@ impl ProjectWorkspace {
             None => None,
         };
         let rustc_cfg = rustc_cfg::get(None, target);
-        Ok(ProjectWorkspace::Json { project: project_json, sysroot, rustc_cfg })
+        let toolchain = rustc_version::get(None);
+        Ok(ProjectWorkspace::Json { project: project_json, sysroot, rustc_cfg, toolchain })
     }
 
     pub fn load_detached_files(detached_files: Vec<AbsPathBuf>) -> Result<ProjectWorkspace> {
@@ -195,7 +229,8 @@ impl ProjectWorkspace {
             detached_files.first().ok_or_else(|| format_err!("No detached files to load"))?,
         )?;
         let rustc_cfg = rustc_cfg::get(None, None);
-        Ok(ProjectWorkspace::DetachedFiles { files: detached_files, sysroot, rustc_cfg })
+        let toolchain = rustc_version::get(None);
+        Ok(ProjectWorkspace::DetachedFiles { files: detached_files, sysroot, rustc_cfg, toolchain })
     }
 
     /// Returns the roots for the current `ProjectWorkspace`
@@ -203,7 +238,7 @@ impl ProjectWorkspace {
     /// the root is a member of the current workspace
     pub fn to_roots(&self, build_data: Option<&BuildDataResult>) -> Vec<PackageRoot> {
         match self {
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg: _ } => project
+            ProjectWorkspace::Json { project, sysroot, .. } => project
                 .crates()
                 .map(|(_, krate)| PackageRoot {
                     is_member: krate.is_workspace_member,
@@ -220,7 +255,7 @@ impl ProjectWorkspace {
                     })
                 }))
                 .collect::<Vec<_>>(),
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg: _, cfg_overrides: _ } => {
+            ProjectWorkspace::Cargo { cargo, sysroot, rustc, .. } => {
                 cargo
                     .packages()
                     .map(|pkg| {
@@ -318,31 +353,37 @@ impl ProjectWorkspace {
         };
 
         let mut crate_graph = match self {
-            ProjectWorkspace::Json { project, sysroot, rustc_cfg } => project_json_to_crate_graph(
-                rustc_cfg.clone(),
-                &proc_macro_loader,
-                load,
-                project,
-                sysroot,
-            ),
-            ProjectWorkspace::Cargo { cargo, sysroot, rustc, rustc_cfg, cfg_overrides } => {
-                cargo_to_crate_graph(
+            ProjectWorkspace::Json { project, sysroot, rustc_cfg, toolchain } => {
+                project_json_to_crate_graph(
                     rustc_cfg.clone(),
-                    cfg_overrides,
+                    *toolchain,
                     &proc_macro_loader,
                     load,
-                    cargo,
-                    build_data.and_then(|it| it.get(cargo.workspace_root())),
+                    project,
                     sysroot,
-                    rustc,
-                    rustc
-                        .as_ref()
-                        .zip(build_data)
-                        .and_then(|(it, map)| map.get(it.workspace_root())),
                 )
             }
-            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => {
-                detached_files_to_crate_graph(rustc_cfg.clone(), load, files, sysroot)
+            ProjectWorkspace::Cargo {
+                cargo,
+                sysroot,
+                rustc,
+                rustc_cfg,
+                cfg_overrides,
+                toolchain,
+            } => cargo_to_crate_graph(
+                rustc_cfg.clone(),
+                *toolchain,
+                cfg_overrides,
+                &proc_macro_loader,
+                load,
+                cargo,
+                build_data.and_then(|it| it.get(cargo.workspace_root())),
+                sysroot,
+                rustc,
+                rustc.as_ref().zip(build_data).and_then(|(it, map)| map.get(it.workspace_root())),
+            ),
+            ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg, toolchain } => {
+                detached_files_to_crate_graph(rustc_cfg.clone(), *toolchain, load, files, sysroot)
             }
         };
         if crate_graph.patch_cfg_if() {
@@ -353,6 +394,22 @@ impl ProjectWorkspace {
         crate_graph
     }
 
+    /// Returns whether the build data of this workspace is the same in `old`
+    /// and `new`, in which case the crate graph doesn't change either.
+    pub fn has_same_build_data(
+        &self,
+        old: Option<&BuildDataResult>,
+        new: Option<&BuildDataResult>,
+    ) -> bool {
+        match self {
+            ProjectWorkspace::Cargo { cargo, rustc, .. } => {
+                let mut roots = iter::once(cargo).chain(rustc).map(|it| it.workspace_root());
+                roots.all(|root| old.and_then(|it| it.get(root)) == new.and_then(|it| it.get(root)))
+            }
+            ProjectWorkspace::Json { .. } | ProjectWorkspace::DetachedFiles { .. } => true,
+        }
+    }
+
     pub fn collect_build_data_configs(&self, collector: &mut BuildDataCollector) {
         match self {
             ProjectWorkspace::Cargo { cargo, .. } => {
@@ -365,15 +422,16 @@ impl ProjectWorkspace {
 
 fn project_json_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    toolchain: Option<Toolchain>,
     proc_macro_loader: &dyn Fn(&AbsPath) -> Vec<ProcMacro>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     project: &ProjectJson,
     sysroot: &Option<Sysroot>,
 ) -> CrateGraph {
     let mut crate_graph = CrateGraph::default();
-    let sysroot_deps = sysroot
-        .as_ref()
-        .map(|sysroot| sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load));
+    let sysroot_deps = sysroot.as_ref().map(|sysroot| {
+        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), toolchain, load)
+    });
 
     let mut cfg_cache: FxHashMap<&str, Vec<CfgFlag>> = FxHashMap::default();
     let crates: FxHashMap<CrateId, CrateId> = project
@@ -406,6 +464,8 @@ fn project_json_to_crate_graph(
                     cfg_options,
                     env,
                     proc_macro.unwrap_or_default(),
+                    krate.proc_macro_dylib_path.is_some(),
+                    toolchain,
                 ),
             )
         })
@@ -431,6 +491,7 @@ fn project_json_to_crate_graph(
 
 fn cargo_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    toolchain: Option<Toolchain>,
     override_cfg: &CfgOverrides,
     proc_macro_loader: &dyn Fn(&AbsPath) -> Vec<ProcMacro>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
@@ -443,7 +504,7 @@ fn cargo_to_crate_graph(
     let _p = profile::span("cargo_to_crate_graph");
     let mut crate_graph = CrateGraph::default();
     let (public_deps, libproc_macro) =
-        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load);
+        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), toolchain, load);
 
     let mut cfg_options = CfgOptions::default();
     cfg_options.extend(rustc_cfg);
@@ -486,6 +547,8 @@ fn cargo_to_crate_graph(
                     proc_macro_loader,
                     file_id,
                     &cargo[tgt].name,
+                    cargo[tgt].is_proc_macro,
+                    toolchain,
                 );
                 if cargo[tgt].kind == TargetKind::Lib {
                     lib_tgt = Some((crate_id, cargo[tgt].name.clone()));
@@ -562,6 +625,7 @@ fn cargo_to_crate_graph(
                 &public_deps,
                 cargo,
                 &pkg_crates,
+                toolchain,
             );
         }
     }
@@ -570,6 +634,7 @@ fn cargo_to_crate_graph(
 
 fn detached_files_to_crate_graph(
     rustc_cfg: Vec<CfgFlag>,
+    toolchain: Option<Toolchain>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
     detached_files: &[AbsPathBuf],
     sysroot: &Sysroot,
@@ -577,7 +642,7 @@ fn detached_files_to_crate_graph(
     let _p = profile::span("detached_files_to_crate_graph");
     let mut crate_graph = CrateGraph::default();
     let (public_deps, _libproc_macro) =
-        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), load);
+        sysroot_to_crate_graph(&mut crate_graph, sysroot, rustc_cfg.clone(), toolchain, load);
 
     let mut cfg_options = CfgOptions::default();
     cfg_options.extend(rustc_cfg);
@@ -602,6 +667,8 @@ fn detached_files_to_crate_graph(
             cfg_options.clone(),
             Env::default(),
             Vec::new(),
+            false,
+            toolchain,
         );
 
         for (name, krate) in public_deps.iter() {
@@ -622,6 +689,7 @@ fn handle_rustc_crates(
     public_deps: &[(CrateName, CrateId)],
     cargo: &CargoWorkspace,
     pkg_crates: &FxHashMap<la_arena::Idx<crate::PackageData>, Vec<(CrateId, TargetKind)>>,
+    toolchain: Option<Toolchain>,
 ) {
     let mut rustc_pkg_crates = FxHashMap::default();
     // The root package of the rustc-dev component is rustc_driver, so we match that
@@ -656,6 +724,8 @@ fn handle_rustc_crates(
                         proc_macro_loader,
                         file_id,
                         &rustc_workspace[tgt].name,
+                        rustc_workspace[tgt].is_proc_macro,
+                        toolchain,
                     );
                     pkg_to_lib_crate.insert(pkg, crate_id);
                     // Add dependencies on core / std / alloc for this crate
@@ -712,6 +782,8 @@ fn add_target_crate_root(
     proc_macro_loader: &dyn Fn(&AbsPath) -> Vec<ProcMacro>,
     file_id: FileId,
     cargo_name: &str,
+    is_proc_macro: bool,
+    toolchain: Option<Toolchain>,
 ) -> CrateId {
     let edition = pkg.edition;
     let cfg_options = {
@@ -754,15 +826,35 @@ fn add_target_crate_root(
         potential_cfg_options,
         env,
         proc_macro,
+        is_proc_macro,
+        toolchain,
     );
 
     crate_id
 }
 
+/// Adds the crates of `sysroot` to `crate_graph`, configured for the host
+/// target, and returns the ones other crates depend on implicitly (`core`,
+/// `alloc` and `std`).
+///
+/// Used by tests which attach the real standard library to a fixture.
+pub fn add_sysroot_to_crate_graph(
+    crate_graph: &mut CrateGraph,
+    sysroot: &Sysroot,
+    load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
+) -> Vec<(CrateName, CrateId)> {
+    let rustc_cfg = rustc_cfg::get(None, None);
+    let toolchain = rustc_version::get(None);
+    let (public_deps, _libproc_macro) =
+        sysroot_to_crate_graph(crate_graph, sysroot, rustc_cfg, toolchain, load);
+    public_deps
+}
+
 fn sysroot_to_crate_graph(
     crate_graph: &mut CrateGraph,
     sysroot: &Sysroot,
     rustc_cfg: Vec<CfgFlag>,
+    toolchain: Option<Toolchain>,
     load: &mut dyn FnMut(&AbsPath) -> Option<FileId>,
 ) -> (Vec<(CrateName, CrateId)>, Option<CrateId>) {
     let _p = profile::span("sysroot_to_crate_graph");
@@ -784,6 +876,8 @@ fn sysroot_to_crate_graph(
                 cfg_options.clone(),
                 env,
                 proc_macro,
+                false,
+                toolchain,
             );
             Some((krate, crate_id))
         })
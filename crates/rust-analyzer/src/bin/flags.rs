@@ -85,10 +85,24 @@ xflags::xflags! {
             optional --disable-proc-macros
         }
 
+        /// List the `pub` items of the workspace crates not used by the other workspace crates.
+        cmd unused-public-items
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+        {
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+            /// Don't use expand proc macros.
+            optional --disable-proc-macros
+        }
+
         cmd ssr
             /// A structured search replace rule (`$a.foo($b) ==> bar($a, $b)`)
             repeated rule: SsrRule
-        {}
+        {
+            /// A file with a rule on each line, applied after the rules given as arguments.
+            optional --rules path: PathBuf
+        }
 
         cmd search
             /// A structured search replace pattern (`$a.foo($b)`)
@@ -98,6 +112,12 @@ xflags::xflags! {
             optional --debug snippet: String
         }
 
+        /// Check the usage of `minicore` by the test fixtures in a directory.
+        cmd check-minicore
+            /// Directory to search for Rust files with test fixtures.
+            required path: PathBuf
+        {}
+
         cmd proc-macro {}
     }
 }
@@ -123,8 +143,10 @@ pub enum RustAnalyzerCmd {
     Highlight(Highlight),
     AnalysisStats(AnalysisStats),
     Diagnostics(Diagnostics),
+    UnusedPublicItems(UnusedPublicItems),
     Ssr(Ssr),
     Search(Search),
+    CheckMinicore(CheckMinicore),
     ProcMacro(ProcMacro),
 }
 
@@ -171,9 +193,19 @@ pub struct Diagnostics {
     pub disable_proc_macros: bool,
 }
 
+#[derive(Debug)]
+pub struct UnusedPublicItems {
+    pub path: PathBuf,
+
+    pub disable_build_scripts: bool,
+    pub disable_proc_macros: bool,
+}
+
 #[derive(Debug)]
 pub struct Ssr {
     pub rule: Vec<SsrRule>,
+
+    pub rules: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -183,6 +215,11 @@ pub struct Search {
     pub debug: Option<String>,
 }
 
+#[derive(Debug)]
+pub struct CheckMinicore {
+    pub path: PathBuf,
+}
+
 #[derive(Debug)]
 pub struct ProcMacro;
 
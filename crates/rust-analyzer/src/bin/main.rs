@@ -11,7 +11,7 @@ use lsp_server::Connection;
 use project_model::ProjectManifest;
 use rust_analyzer::{
     cli::{self, AnalysisStatsCmd},
-    config::Config,
+    config::{Config, ExtraClientCapabilities},
     from_json,
     lsp_ext::supports_utf8,
     Result,
@@ -107,8 +107,14 @@ fn try_main() -> Result<()> {
         flags::RustAnalyzerCmd::Diagnostics(cmd) => {
             cli::diagnostics(&cmd.path, !cmd.disable_build_scripts, !cmd.disable_proc_macros)?
         }
-        flags::RustAnalyzerCmd::Ssr(cmd) => cli::apply_ssr_rules(cmd.rule)?,
+        flags::RustAnalyzerCmd::UnusedPublicItems(cmd) => cli::unused_public_items(
+            &cmd.path,
+            !cmd.disable_build_scripts,
+            !cmd.disable_proc_macros,
+        )?,
+        flags::RustAnalyzerCmd::Ssr(cmd) => cli::apply_ssr_rules(cmd.rule, cmd.rules)?,
         flags::RustAnalyzerCmd::Search(cmd) => cli::search_for_patterns(cmd.pattern, cmd.debug)?,
+        flags::RustAnalyzerCmd::CheckMinicore(cmd) => cli::check_minicore(&cmd.path)?,
     }
     Ok(())
 }
@@ -162,6 +168,10 @@ fn run_server() -> Result<()> {
 
     let (initialize_id, initialize_params) = connection.initialize_start()?;
     log::info!("InitializeParams: {}", initialize_params);
+    let extra_caps = initialize_params
+        .get("capabilities")
+        .map(ExtraClientCapabilities::from_json)
+        .unwrap_or_default();
     let initialize_params =
         from_json::<lsp_types::InitializeParams>("InitializeParams", initialize_params)?;
 
@@ -178,6 +188,7 @@ fn run_server() -> Result<()> {
     };
 
     let mut config = Config::new(root_path, initialize_params.capabilities);
+    config.extra_caps = extra_caps;
     if let Some(json) = initialize_params.initialization_options {
         config.update(json);
     }
@@ -193,7 +204,10 @@ fn run_server() -> Result<()> {
         offset_encoding: if supports_utf8(&config.caps) { Some("utf-8".to_string()) } else { None },
     };
 
-    let initialize_result = serde_json::to_value(initialize_result).unwrap();
+    let mut initialize_result = serde_json::to_value(initialize_result).unwrap();
+    if let Some(capabilities) = initialize_result["capabilities"].as_object_mut() {
+        capabilities.extend(rust_analyzer::extra_server_capabilities(&config));
+    }
 
     connection.initialize_finish(initialize_id, initialize_result)?;
 
@@ -201,23 +215,25 @@ fn run_server() -> Result<()> {
         log::info!("Client '{}' {}", client_info.name, client_info.version.unwrap_or_default());
     }
 
+    if let Some(workspace_roots) = initialize_params
+        .workspace_folders
+        .map(|workspaces| {
+            workspaces
+                .into_iter()
+                .filter_map(|it| it.uri.to_file_path().ok())
+                .filter_map(|it| AbsPathBuf::try_from(it).ok())
+                .collect::<Vec<_>>()
+        })
+        .filter(|workspaces| !workspaces.is_empty())
+    {
+        config.workspace_roots = workspace_roots;
+    }
+
     if config.linked_projects().is_empty() && config.detached_files().is_empty() {
-        let workspace_roots = initialize_params
-            .workspace_folders
-            .map(|workspaces| {
-                workspaces
-                    .into_iter()
-                    .filter_map(|it| it.uri.to_file_path().ok())
-                    .filter_map(|it| AbsPathBuf::try_from(it).ok())
-                    .collect::<Vec<_>>()
-            })
-            .filter(|workspaces| !workspaces.is_empty())
-            .unwrap_or_else(|| vec![config.root_path.clone()]);
-
-        let discovered = ProjectManifest::discover_all(&workspace_roots);
+        let discovered = ProjectManifest::discover_all(&config.workspace_roots);
         log::info!("discovered projects: {:?}", discovered);
         if discovered.is_empty() {
-            log::error!("failed to find any projects in {:?}", workspace_roots);
+            log::error!("failed to find any projects in {:?}", config.workspace_roots);
         }
         config.discovered_projects = Some(discovered);
     }
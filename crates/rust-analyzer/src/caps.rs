@@ -9,11 +9,12 @@ use lsp_types::{
     SemanticTokensOptions, ServerCapabilities, SignatureHelpOptions, TextDocumentSyncCapability,
     TextDocumentSyncKind, TextDocumentSyncOptions, TypeDefinitionProviderCapability,
     WorkDoneProgressOptions, WorkspaceFileOperationsServerCapabilities,
-    WorkspaceServerCapabilities,
+    WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
 };
 use serde_json::json;
 
 use crate::config::{Config, RustfmtConfig};
+use crate::lsp_ext;
 use crate::semantic_tokens;
 
 pub fn server_capabilities(config: &Config) -> ServerCapabilities {
@@ -55,7 +56,13 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
         },
         document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
             first_trigger_character: "=".to_string(),
-            more_trigger_character: Some(vec![".".to_string(), ">".to_string(), "{".to_string()]),
+            more_trigger_character: Some(vec![
+                ".".to_string(),
+                ">".to_string(),
+                "{".to_string(),
+                "|".to_string(),
+                "<".to_string(),
+            ]),
         }),
         selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
         folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
@@ -68,7 +75,10 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
         color_provider: None,
         execute_command_provider: None,
         workspace: Some(WorkspaceServerCapabilities {
-            workspace_folders: None,
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
             file_operations: Some(WorkspaceFileOperationsServerCapabilities {
                 did_create: None,
                 will_create: None,
@@ -111,20 +121,41 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
             }
             .into(),
         ),
-        moniker_provider: None,
+        moniker_provider: Some(OneOf::Left(true)),
         experimental: Some(json!({
             "joinLines": true,
             "ssr": true,
+            "ssrPreview": true,
             "onEnter": true,
             "parentModule": true,
             "runnables": {
                 "kinds": [ "cargo" ],
             },
             "workspaceSymbolScopeKindFiltering": true,
+            "referenceCategories": true,
+            "potentialIncomingCalls": true,
         })),
     }
 }
 
+/// Server capabilities of LSP 3.17 which aren't part of `ServerCapabilities`
+/// yet, to be added to the ones above.
+pub fn extra_server_capabilities(config: &Config) -> serde_json::Map<String, serde_json::Value> {
+    let mut res = serde_json::Map::new();
+    res.insert("typeHierarchyProvider".to_string(), json!(true));
+    // Only advertised to clients pulling diagnostics, the other ones get them
+    // published.
+    if config.pull_diagnostics() {
+        let options = lsp_ext::DiagnosticOptions {
+            identifier: Some("rust-analyzer".to_string()),
+            inter_file_dependencies: true,
+            workspace_diagnostics: true,
+        };
+        res.insert("diagnosticProvider".to_string(), serde_json::to_value(options).unwrap());
+    }
+    res
+}
+
 fn completions_resolve_provider(client_caps: &ClientCapabilities) -> Option<bool> {
     if completion_item_edit_resolve(client_caps) {
         Some(true)
@@ -0,0 +1,181 @@
+//! Completion of the dependencies of `Cargo.toml` manifests, which aren't part
+//! of the analyzed sources: the names and versions of the crates downloaded to
+//! the local registry cache, and the features of the packages of the loaded
+//! workspaces.
+//!
+//! Manifests are looked at line by line, so values spanning several lines,
+//! like multi-line feature arrays, aren't completed.
+
+use std::fs;
+
+use rustc_hash::FxHashMap;
+use semver::Version;
+use stdx::split_once;
+
+/// The versions of the crates in the download cache of the registries, by
+/// crate name.
+pub(crate) type RegistryCrates = FxHashMap<String, Vec<Version>>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ManifestCompletion<'a> {
+    /// The name of a dependency, the key of an entry of a dependency table.
+    CrateName,
+    /// The version requirement of the dependency `krate`.
+    Version { krate: &'a str },
+    /// A feature of the dependency `krate`.
+    Feature { krate: &'a str },
+}
+
+/// Returns what is completed at `offset` in the manifest `text`, and the part
+/// of it which is already typed.
+pub(crate) fn completion_at(text: &str, offset: usize) -> Option<(ManifestCompletion<'_>, &str)> {
+    let line_start = text[..offset].rfind('\n').map_or(0, |it| it + 1);
+    let line = &text[line_start..offset];
+    let header = text[..line_start].lines().rev().map(str::trim).find(|it| it.starts_with('['))?;
+    let segments =
+        header.trim_matches(|c: char| c == '[' || c == ']').split('.').collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [.., table] if is_dependency_table(table) => match split_once(line, '=') {
+            Some((krate, value)) => dependency_value(krate.trim(), value, true),
+            None => {
+                let prefix = line.trim_start();
+                let is_name = prefix.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+                is_name.then(|| (ManifestCompletion::CrateName, prefix))
+            }
+        },
+        // `[dependencies.serde]`
+        [.., table, krate] if is_dependency_table(table) => dependency_value(krate, line, false),
+        _ => None,
+    }
+}
+
+fn is_dependency_table(name: &str) -> bool {
+    matches!(name, "dependencies" | "dev-dependencies" | "build-dependencies")
+}
+
+/// Classifies the string being typed at the end of `value`, a part of the
+/// specification of the dependency `krate`. A string which isn't the value of
+/// a key is a version if `plain_version` is set, like in `serde = "1.0"`.
+fn dependency_value<'a>(
+    krate: &'a str,
+    value: &'a str,
+    plain_version: bool,
+) -> Option<(ManifestCompletion<'a>, &'a str)> {
+    if value.matches('"').count() % 2 == 0 {
+        return None;
+    }
+    let quote = value.rfind('"')?;
+    let prefix = &value[quote + 1..];
+    let before = value[..quote].trim_end();
+
+    if let Some(idx) = before.rfind("features") {
+        let rest = &before[idx + "features".len()..];
+        if rest.trim_start().starts_with('=') && rest.contains('[') && !rest.contains(']') {
+            return Some((ManifestCompletion::Feature { krate }, prefix));
+        }
+    }
+    let key = before.strip_suffix('=').map(|it| {
+        let key_start = it.rfind(|c: char| c == '{' || c == ',').map_or(0, |it| it + 1);
+        it[key_start..].trim()
+    });
+    match key {
+        Some("version") => Some((ManifestCompletion::Version { krate }, prefix)),
+        None if before.is_empty() && plain_version => {
+            Some((ManifestCompletion::Version { krate }, prefix))
+        }
+        _ => None,
+    }
+}
+
+/// Lists the crates in the download cache of the registries, with their
+/// versions sorted from the newest.
+pub(crate) fn registry_crates() -> RegistryCrates {
+    let _p = profile::span("registry_crates");
+    let mut res: RegistryCrates = FxHashMap::default();
+    let cache = match toolchain::cargo_home() {
+        Some(it) => it.join("registry").join("cache"),
+        None => return res,
+    };
+    let registries = fs::read_dir(cache).into_iter().flatten().flatten();
+    for entry in registries.flat_map(|it| fs::read_dir(it.path())).flatten().flatten() {
+        let file_name = entry.file_name();
+        let package = match file_name.to_str().and_then(|it| it.strip_suffix(".crate")) {
+            Some(it) => it,
+            None => continue,
+        };
+        if let Some((name, version)) = split_package_id(package) {
+            res.entry(name.to_string()).or_default().push(version);
+        }
+    }
+    for versions in res.values_mut() {
+        versions.sort_by(|a, b| b.cmp(a));
+        versions.dedup();
+    }
+    res
+}
+
+/// Splits `serde-1.0.126` into the name and the version. Names may contain
+/// dashes followed by digits too, like `sha-1`.
+fn split_package_id(package: &str) -> Option<(&str, Version)> {
+    package.match_indices('-').rev().map(|(idx, _)| idx).find_map(|idx| {
+        let version = Version::parse(&package[idx + 1..]).ok()?;
+        Some((&package[..idx], version))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(manifest: &str, expected: Option<(ManifestCompletion, &str)>) {
+        let offset = manifest.find("$0").unwrap();
+        let text = manifest.replace("$0", "");
+        assert_eq!(completion_at(&text, offset), expected);
+    }
+
+    #[test]
+    fn completes_crate_names() {
+        check("[dependencies]\nser$0", Some((ManifestCompletion::CrateName, "ser")));
+        check(
+            "[target.'cfg(unix)'.dev-dependencies]\n$0",
+            Some((ManifestCompletion::CrateName, "")),
+        );
+        check("[package]\nna$0", None);
+    }
+
+    #[test]
+    fn completes_versions() {
+        let version = |krate| ManifestCompletion::Version { krate };
+        check("[dependencies]\nserde = \"1.$0", Some((version("serde"), "1.")));
+        check(
+            "[dependencies]\nserde = { features = [\"derive\"], version = \"$0",
+            Some((version("serde"), "")),
+        );
+        check("[dependencies.serde]\nversion = \"1$0", Some((version("serde"), "1")));
+        check("[dependencies]\nserde = { path = \"../$0", None);
+    }
+
+    #[test]
+    fn completes_features() {
+        let feature = |krate| ManifestCompletion::Feature { krate };
+        check(
+            "[dependencies]\nserde = { version = \"1\", features = [\"std\", \"de$0",
+            Some((feature("serde"), "de")),
+        );
+        check("[build-dependencies.cc]\nfeatures = [\"par$0", Some((feature("cc"), "par")));
+        check("[dependencies]\nserde = { features = [\"std\"], version = \"1\" }$0", None);
+    }
+
+    #[test]
+    fn splits_package_ids() {
+        let check = |package, expected: Option<(&str, &str)>| {
+            let expected = expected.map(|(name, version)| (name, Version::parse(version).unwrap()));
+            assert_eq!(split_package_id(package), expected);
+        };
+        check("serde-1.0.126", Some(("serde", "1.0.126")));
+        check("sha-1-0.9.6", Some(("sha-1", "0.9.6")));
+        check("futures-0.3.0-alpha.1", Some(("futures", "0.3.0-alpha.1")));
+        check("serde", None);
+    }
+}
@@ -3,20 +3,24 @@
 pub(crate) mod load_cargo;
 mod analysis_stats;
 mod diagnostics;
+mod minicore;
 mod progress_report;
 mod ssr;
+mod unused_public_items;
 
 use std::io::Read;
 
 use anyhow::Result;
-use ide::{Analysis, AnalysisHost};
+use ide::{Analysis, AnalysisHost, FileStructureConfig};
 use syntax::{AstNode, SourceFile};
 use vfs::Vfs;
 
 pub use self::{
     analysis_stats::AnalysisStatsCmd,
     diagnostics::diagnostics,
+    minicore::check_minicore,
     ssr::{apply_ssr_rules, search_for_patterns},
+    unused_public_items::unused_public_items,
 };
 
 #[derive(Clone, Copy)]
@@ -49,7 +53,8 @@ pub fn parse(no_dump: bool) -> Result<()> {
 pub fn symbols() -> Result<()> {
     let text = read_stdin()?;
     let (analysis, file_id) = Analysis::from_single_file(text);
-    let structure = analysis.file_structure(file_id).unwrap();
+    let config = FileStructureConfig { include_macro_generated: false };
+    let structure = analysis.file_structure(&config, file_id).unwrap();
     for s in structure {
         println!("{:?}", s);
     }
@@ -82,6 +87,7 @@ fn report_metric(metric: &str, value: u64, unit: &str) {
 }
 
 fn print_memory_usage(mut host: AnalysisHost, vfs: Vfs) {
+    let sources = host.per_source_root_text_size();
     let mut mem = host.per_query_memory_usage();
 
     let before = profile::memory_usage();
@@ -99,4 +105,9 @@ fn print_memory_usage(mut host: AnalysisHost, vfs: Vfs) {
         // NOTE: Not a debug print, so avoid going through the `eprintln` defined above.
         eprintln!("{:>8} {}", bytes, name);
     }
+
+    eprintln!("\nsource text length per source root:");
+    for (crates, files, text_len) in sources {
+        eprintln!("{:>10}b {} ({} files)", text_len, crates.join(", "), files);
+    }
 }
@@ -117,6 +117,8 @@ fn load_crate_graph(
                     vfs.set_file_contents(path.into(), contents);
                 }
             }
+            // Nothing is watched.
+            vfs::loader::Message::Changed { .. } => (),
         }
     }
     let changes = vfs.take_changes();
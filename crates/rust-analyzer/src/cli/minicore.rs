@@ -0,0 +1,40 @@
+//! Checks the usage of `minicore` by the test fixtures of a source tree.
+
+use std::{fs, path::Path};
+
+use anyhow::bail;
+use test_utils::MiniCoreUsage;
+
+use crate::cli::Result;
+
+pub fn check_minicore(path: &Path) -> Result<()> {
+    let mut usage = MiniCoreUsage::default();
+
+    let mut work = vec![path.to_path_buf()];
+    while let Some(dir) = work.pop() {
+        let mut entries =
+            fs::read_dir(&dir)?.map(|it| Ok(it?.path())).collect::<Result<Vec<_>>>()?;
+        entries.sort();
+        for path in entries {
+            let file_name = path.file_name().unwrap_or_default().to_str().unwrap_or_default();
+            if file_name.starts_with('.') || file_name == "target" {
+                continue;
+            }
+            if path.is_dir() {
+                work.push(path);
+            } else if file_name.ends_with(".rs") {
+                let text = fs::read_to_string(&path)?;
+                usage.add_file(&path.display().to_string(), &text);
+            }
+        }
+    }
+
+    let problems = usage.finish();
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    if !problems.is_empty() {
+        bail!("found {} problems with the usage of minicore", problems.len());
+    }
+    Ok(())
+}
@@ -5,9 +5,15 @@ use crate::cli::{
     Result,
 };
 use ide_ssr::{MatchFinder, SsrPattern, SsrRule};
+use std::path::PathBuf;
 
-pub fn apply_ssr_rules(rules: Vec<SsrRule>) -> Result<()> {
+/// Applies `rules` followed by the rules in `rules_file`, printing the number of matches of each
+/// rule.
+pub fn apply_ssr_rules(mut rules: Vec<SsrRule>, rules_file: Option<PathBuf>) -> Result<()> {
     use ide_db::base_db::SourceDatabaseExt;
+    if let Some(path) = rules_file {
+        rules.extend(ide_ssr::parse_rules(&std::fs::read_to_string(path)?)?);
+    }
     let cargo_config = Default::default();
     let load_cargo_config = LoadCargoConfig {
         load_out_dirs_from_check: true,
@@ -19,10 +25,15 @@ pub fn apply_ssr_rules(rules: Vec<SsrRule>) -> Result<()> {
         load_workspace_at(&std::env::current_dir()?, &cargo_config, &load_cargo_config, &|_| {})?;
     let db = host.raw_database();
     let mut match_finder = MatchFinder::at_first_file(db)?;
+    let rule_texts = rules.iter().map(|rule| rule.to_string()).collect::<Vec<_>>();
     for rule in rules {
         match_finder.add_rule(rule)?;
     }
-    let edits = match_finder.edits();
+    let (edits, match_counts) = match_finder.edits_and_match_counts();
+    for (text, counts) in rule_texts.iter().zip(match_counts) {
+        let count: usize = counts.values().sum();
+        println!("{:>6} matches in {:>4} files: {}", count, counts.len(), text);
+    }
     for (file_id, edit) in edits {
         if let Some(path) = vfs.file_path(file_id).as_path() {
             let mut contents = db.file_text(file_id).to_string();
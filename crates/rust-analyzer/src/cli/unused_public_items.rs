@@ -0,0 +1,63 @@
+//! Lists the `pub` items of the workspace crates which are not used by the
+//! other crates of the workspace.
+
+use std::path::Path;
+
+use hir::Crate;
+use ide_db::base_db::SourceDatabaseExt;
+
+use crate::cli::{
+    load_cargo::{load_workspace_at, LoadCargoConfig},
+    Result,
+};
+
+pub fn unused_public_items(
+    path: &Path,
+    load_out_dirs_from_check: bool,
+    with_proc_macro: bool,
+) -> Result<()> {
+    let cargo_config = Default::default();
+    let load_cargo_config = LoadCargoConfig {
+        load_out_dirs_from_check,
+        with_proc_macro,
+        wrap_rustc: false,
+        prefill_caches: false,
+    };
+    let (host, vfs, _proc_macro) =
+        load_workspace_at(path, &cargo_config, &load_cargo_config, &|_| {})?;
+    let db = host.raw_database();
+    let analysis = host.analysis();
+
+    let mut crates = Crate::all(db)
+        .into_iter()
+        .filter(|krate| {
+            let source_root = db.file_source_root(krate.root_file(db));
+            !db.source_root(source_root).is_library
+        })
+        .map(|krate| {
+            let name = krate.display_name(db).as_deref().unwrap_or("unknown").to_string();
+            (name, krate)
+        })
+        .collect::<Vec<_>>();
+    crates.sort_by(|(name, _), (other, _)| name.cmp(other));
+
+    for (name, krate) in crates {
+        let items = analysis.unused_public_items(krate.into()).unwrap();
+        if items.is_empty() {
+            continue;
+        }
+        println!("crate {}:", name);
+        for item in items {
+            let line_index = analysis.file_line_index(item.file_id).unwrap();
+            let line_col = line_index.line_col(item.focus_or_full_range().start());
+            println!(
+                "    {}:{}:{}: {}",
+                vfs.file_path(item.file_id),
+                line_col.line + 1,
+                line_col.col + 1,
+                item.name
+            );
+        }
+    }
+    Ok(())
+}
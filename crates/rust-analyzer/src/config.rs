@@ -11,8 +11,9 @@ use std::{ffi::OsString, iter, path::PathBuf};
 
 use flycheck::FlycheckConfig;
 use ide::{
-    AssistConfig, CompletionConfig, DiagnosticsConfig, HoverConfig, HoverDocFormat,
-    InlayHintsConfig, JoinLinesConfig,
+    AssistConfig, CompletionConfig, DiagnosticsConfig, FileStructureConfig, HoverConfig,
+    HoverDocFormat, InlayHintsConfig, JoinLinesConfig, LifetimeElisionHints, PostfixSnippet,
+    TypingConfig,
 };
 use ide_db::helpers::{
     insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
@@ -22,7 +23,7 @@ use lsp_types::{ClientCapabilities, MarkupKind};
 use project_model::{CargoConfig, ProjectJson, ProjectJsonData, ProjectManifest, RustcSource};
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{de::DeserializeOwned, Deserialize};
-use vfs::AbsPathBuf;
+use vfs::{AbsPath, AbsPathBuf};
 
 use crate::{
     caps::completion_item_edit_resolve, diagnostics::DiagnosticsMapConfig,
@@ -53,9 +54,19 @@ config_data! {
         /// Whether to allow import insertion to merge new imports into single path glob imports like `use std::fmt::*;`.
         assist_allowMergingIntoGlobImports: bool           = "true",
 
+        /// Directory to save the symbol index of the workspace and its dependencies in,
+        /// so that workspace symbol search doesn't need to parse everything again after
+        /// a restart. Relative paths are resolved against the workspace root.
+        cache_symbolIndexDir: Option<PathBuf>              = "null",
+
         /// Show function name and docs in parameter hints.
         callInfo_full: bool                                = "true",
 
+        /// Whether to include, in the incoming calls of a trait method
+        /// implementation, the calls through trait objects and generic bounds
+        /// which might dispatch to it, marked as potential calls.
+        callHierarchy_potentialCalls: bool                 = "false",
+
         /// Automatically refresh project info via `cargo metadata` on
         /// `Cargo.toml` changes.
         cargo_autoreload: bool           = "true",
@@ -109,9 +120,19 @@ config_data! {
         completion_addCallParenthesis: bool      = "true",
         /// Whether to show postfix snippets like `dbg`, `if`, `not`, etc.
         completion_postfix_enable: bool          = "true",
+        /// Custom postfix completions, by label. A completion is either the
+        /// snippet which replaces the expression, in which `$receiver` stands
+        /// for the expression, or an object with the snippet as `body`, an
+        /// optional `description` and an optional `receiverType`, the name of
+        /// the type the expression must have, like `Result`.
+        completion_postfix_snippets: FxHashMap<String, PostfixSnippetDef> = "{}",
         /// Toggles the additional completions that automatically add imports when completed.
         /// Note that your client must specify the `additionalTextEdits` LSP client capability to truly have this feature enabled.
         completion_autoimport_enable: bool       = "true",
+        /// Whether the completions which add imports also propose the methods
+        /// of traits which aren't in scope, importing the trait. Searching them
+        /// can be slow in large workspaces.
+        completion_autoimport_traitMethods: bool = "true",
         /// Toggles the additional completions that automatically show method calls and field accesses
         /// with `self` prefixed to them when inside a method.
         completion_autoself_enable: bool       = "true",
@@ -141,6 +162,10 @@ config_data! {
         experimental_procAttrMacros: bool = "false",
 
         /// Controls file watching implementation.
+        ///
+        /// `client` relies on the editor to watch files, falling back to
+        /// `server` if the editor doesn't support it. `server` uses
+        /// rust-analyzer's own file watcher for the workspace.
         files_watcher: String = "\"client\"",
         /// These directories will be ignored by rust-analyzer.
         files_excludeDirs: Vec<PathBuf> = "[]",
@@ -157,6 +182,9 @@ config_data! {
         /// Use markdown syntax for links in hover.
         hover_linksInHover |
         hoverActions_linksInHover: bool = "true",
+        /// Whether to show the size, alignment and field offsets of types on
+        /// hover.
+        hover_memoryLayout_enable: bool = "false",
 
         /// Whether to show `Debug` action. Only applies when
         /// `#rust-analyzer.hoverActions.enable#` is set.
@@ -178,6 +206,12 @@ config_data! {
 
         /// Whether to show inlay type hints for method chains.
         inlayHints_chainingHints: bool      = "true",
+        /// Whether to show inlay hints for the values dropped at the end of
+        /// their scope.
+        inlayHints_implicitDropHints: bool   = "false",
+        /// Whether to show inlay hints for the lifetimes elided in function
+        /// signatures.
+        inlayHints_lifetimeElisionHints: LifetimeElisionHintsDef = "\"never\"",
         /// Maximum length for inlay hints. Set to null to have an unlimited length.
         inlayHints_maxLength: Option<usize> = "25",
         /// Whether to show function parameter name inlay hints at the call
@@ -192,6 +226,8 @@ config_data! {
         joinLines_removeTrailingComma: bool = "true",
         /// Join lines unwraps trivial blocks.
         joinLines_unwrapTrivialBlock: bool = "true",
+        /// Join lines collapses a method chain onto a single line.
+        joinLines_joinMethodChains: bool = "false",
 
         /// Whether to show `Debug` lens. Only applies when
         /// `#rust-analyzer.lens.enable#` is set.
@@ -221,6 +257,10 @@ config_data! {
         /// Number of syntax trees rust-analyzer keeps in memory. Defaults to 128.
         lruCapacity: Option<usize>                 = "null",
 
+        /// Whether to show the items generated by macro calls in the outline
+        /// of a file, at the location of the macro call.
+        outline_includeMacroGenerated: bool        = "false",
+
         /// Whether to show `can't find Cargo.toml` error message.
         notifications_cargoTomlNotFound: bool      = "true",
 
@@ -255,6 +295,13 @@ config_data! {
         /// available on a nightly build.
         rustfmt_enableRangeFormatting: bool = "false",
 
+        /// Whether to insert the closing `|` when typing the opening `|` of the
+        /// parameters of a closure.
+        typing_autoClosingPipes: bool = "false",
+        /// Whether to insert the closing `>` when typing `<` after a path in type
+        /// position.
+        typing_autoClosingAngleBrackets: bool = "false",
+
         /// Workspace symbol search scope.
         workspace_symbol_search_scope: WorskpaceSymbolSearchScopeDef = "\"workspace\"",
         /// Workspace symbol search kind.
@@ -275,6 +322,35 @@ pub struct Config {
     detached_files: Vec<AbsPathBuf>,
     pub discovered_projects: Option<Vec<ProjectManifest>>,
     pub root_path: AbsPathBuf,
+    /// The workspace folders opened by the client.
+    pub workspace_roots: Vec<AbsPathBuf>,
+    /// Client capabilities which aren't part of `caps`, as lsp-types doesn't
+    /// support them yet.
+    pub extra_caps: ExtraClientCapabilities,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExtraClientCapabilities {
+    /// `textDocument.diagnostic`: the client pulls diagnostics instead of
+    /// waiting for them to be published.
+    pub pull_diagnostics: bool,
+    /// `workspace.diagnostics.refreshSupport`
+    pub diagnostics_refresh: bool,
+}
+
+impl ExtraClientCapabilities {
+    /// Reads the capabilities from the JSON of the client capabilities.
+    pub fn from_json(caps: &serde_json::Value) -> ExtraClientCapabilities {
+        ExtraClientCapabilities {
+            pull_diagnostics: caps
+                .pointer("/textDocument/diagnostic")
+                .map_or(false, |it| !it.is_null()),
+            diagnostics_refresh: caps
+                .pointer("/workspace/diagnostics/refreshSupport")
+                .and_then(|it| it.as_bool())
+                .unwrap_or(false),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -362,7 +438,9 @@ pub struct FilesConfig {
 #[derive(Debug, Clone)]
 pub enum FilesWatcher {
     Client,
-    Notify,
+    /// Watch files with `vfs-notify`, reporting changes like the client's
+    /// `workspace/didChangeWatchedFiles` notifications would.
+    Server,
 }
 
 #[derive(Debug, Clone)]
@@ -401,9 +479,58 @@ impl Config {
             data: ConfigData::default(),
             detached_files: Vec::new(),
             discovered_projects: None,
+            workspace_roots: vec![root_path.clone()],
             root_path,
+            extra_caps: ExtraClientCapabilities::default(),
+        }
+    }
+    /// Creates the config of the workspace folder at `root`, from the settings
+    /// the client scoped to that folder.
+    pub fn for_folder(&self, root: AbsPathBuf, json: serde_json::Value) -> Config {
+        let mut config = self.clone();
+        config.discovered_projects = self.discovered_projects.as_ref().map(|projects| {
+            projects
+                .iter()
+                .filter(|project| {
+                    let path = match project {
+                        ProjectManifest::ProjectJson(it) | ProjectManifest::CargoToml(it) => it,
+                    };
+                    self.workspace_root_for(path) == Some(&root)
+                })
+                .cloned()
+                .collect()
+        });
+        config.workspace_roots = vec![root.clone()];
+        config.root_path = root;
+        config.update(json);
+        config
+    }
+    /// Returns the innermost workspace folder containing `path`.
+    /// Adds the projects discovered in the workspace folders of the config,
+    /// skipping the ones of folders removed since the discovery started.
+    pub fn add_discovered_projects(&mut self, projects: &[ProjectManifest]) {
+        let workspace_roots = &self.workspace_roots;
+        let discovered = match &mut self.discovered_projects {
+            Some(it) => it,
+            None => return,
+        };
+        for project in projects {
+            let path = match project {
+                ProjectManifest::ProjectJson(it) | ProjectManifest::CargoToml(it) => it,
+            };
+            if workspace_roots.iter().any(|root| path.starts_with(root))
+                && !discovered.contains(project)
+            {
+                discovered.push(project.clone());
+            }
         }
     }
+    pub fn workspace_root_for(&self, path: &AbsPath) -> Option<&AbsPathBuf> {
+        self.workspace_roots
+            .iter()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len())
+    }
     pub fn update(&mut self, mut json: serde_json::Value) {
         log::info!("updating config from JSON: {:#}", json);
         if json.is_null() || json.as_object().map_or(false, |it| it.is_empty()) {
@@ -474,6 +601,9 @@ impl Config {
             try_or!(self.caps.text_document.as_ref()?.synchronization.clone()?, Default::default());
         caps.did_save == Some(true) && caps.dynamic_registration == Some(true)
     }
+    pub fn workspace_configuration(&self) -> bool {
+        try_or!(self.caps.workspace.as_ref()?.configuration?, false)
+    }
     pub fn did_change_watched_files_dynamic_registration(&self) -> bool {
         try_or!(
             self.caps.workspace.as_ref()?.did_change_watched_files.as_ref()?.dynamic_registration?,
@@ -487,6 +617,9 @@ impl Config {
     pub fn line_folding_only(&self) -> bool {
         try_or!(self.caps.text_document.as_ref()?.folding_range.as_ref()?.line_folding_only?, false)
     }
+    pub fn file_structure(&self) -> FileStructureConfig {
+        FileStructureConfig { include_macro_generated: self.data.outline_includeMacroGenerated }
+    }
     pub fn hierarchical_symbols(&self) -> bool {
         try_or!(
             self.caps
@@ -581,6 +714,12 @@ impl Config {
     pub fn publish_diagnostics(&self) -> bool {
         self.data.diagnostics_enable
     }
+    pub fn pull_diagnostics(&self) -> bool {
+        self.extra_caps.pull_diagnostics
+    }
+    pub fn diagnostics_refresh(&self) -> bool {
+        self.extra_caps.diagnostics_refresh
+    }
     pub fn diagnostics(&self) -> DiagnosticsConfig {
         DiagnosticsConfig {
             disable_experimental: !self.data.diagnostics_enableExperimental,
@@ -594,6 +733,9 @@ impl Config {
             warnings_as_hint: self.data.diagnostics_warningsAsHint.clone(),
         }
     }
+    pub fn symbol_cache_dir(&self) -> Option<AbsPathBuf> {
+        self.data.cache_symbolIndexDir.as_ref().map(|it| self.root_path.join(it))
+    }
     pub fn lru_capacity(&self) -> Option<usize> {
         self.data.lruCapacity
     }
@@ -613,7 +755,9 @@ impl Config {
     pub fn files(&self) -> FilesConfig {
         FilesConfig {
             watcher: match self.data.files_watcher.as_str() {
-                "notify" => FilesWatcher::Notify,
+                // `notify` is the old name of `server`.
+                "server" | "notify" => FilesWatcher::Server,
+                _ if !self.did_change_watched_files_dynamic_registration() => FilesWatcher::Server,
                 "client" | _ => FilesWatcher::Client,
             },
             exclude: self.data.files_excludeDirs.iter().map(|it| self.root_path.join(it)).collect(),
@@ -711,6 +855,12 @@ impl Config {
             type_hints: self.data.inlayHints_typeHints,
             parameter_hints: self.data.inlayHints_parameterHints,
             chaining_hints: self.data.inlayHints_chainingHints,
+            implicit_drop_hints: self.data.inlayHints_implicitDropHints,
+            lifetime_elision_hints: match self.data.inlayHints_lifetimeElisionHints {
+                LifetimeElisionHintsDef::Always => LifetimeElisionHints::Always,
+                LifetimeElisionHintsDef::SkipTrivial => LifetimeElisionHints::SkipTrivial,
+                LifetimeElisionHintsDef::Never => LifetimeElisionHints::Never,
+            },
             max_length: self.data.inlayHints_maxLength,
         }
     }
@@ -737,6 +887,7 @@ impl Config {
             enable_postfix_completions: self.data.completion_postfix_enable,
             enable_imports_on_the_fly: self.data.completion_autoimport_enable
                 && completion_item_edit_resolve(&self.caps),
+            enable_trait_methods_on_the_fly: self.data.completion_autoimport_traitMethods,
             enable_self_on_the_fly: self.data.completion_autoself_enable,
             add_call_parenthesis: self.data.completion_addCallParenthesis,
             add_call_argument_snippets: self.data.completion_addCallArgumentSnippets,
@@ -752,8 +903,33 @@ impl Config {
                     .snippet_support?,
                 false
             )),
+            postfix_snippets: self.postfix_snippets(),
+            additional_lints: Vec::new(),
         }
     }
+    fn postfix_snippets(&self) -> Vec<PostfixSnippet> {
+        let mut snippets = self
+            .data
+            .completion_postfix_snippets
+            .iter()
+            .map(|(label, def)| match def {
+                PostfixSnippetDef::Template(template) => PostfixSnippet {
+                    label: label.clone(),
+                    template: template.clone(),
+                    description: None,
+                    receiver_type: None,
+                },
+                PostfixSnippetDef::Full { body, description, receiver_type } => PostfixSnippet {
+                    label: label.clone(),
+                    template: body.clone(),
+                    description: description.clone(),
+                    receiver_type: receiver_type.clone(),
+                },
+            })
+            .collect::<Vec<_>>();
+        snippets.sort_by(|a, b| a.label.cmp(&b.label));
+        snippets
+    }
     pub fn assist(&self) -> AssistConfig {
         AssistConfig {
             snippet_cap: SnippetCap::new(self.experimental("snippetTextEdit")),
@@ -766,11 +942,22 @@ impl Config {
             join_else_if: self.data.joinLines_joinElseIf,
             remove_trailing_comma: self.data.joinLines_removeTrailingComma,
             unwrap_trivial_blocks: self.data.joinLines_unwrapTrivialBlock,
+            join_method_chains: self.data.joinLines_joinMethodChains,
+        }
+    }
+    pub fn typing(&self) -> TypingConfig {
+        TypingConfig {
+            close_closure_pipes: self.data.typing_autoClosingPipes,
+            close_generic_angles: self.data.typing_autoClosingAngleBrackets,
+            snippet_cap: SnippetCap::new(self.experimental("snippetTextEdit")),
         }
     }
     pub fn call_info_full(&self) -> bool {
         self.data.callInfo_full
     }
+    pub fn call_hierarchy_potential_calls(&self) -> bool {
+        self.data.callHierarchy_potentialCalls
+    }
     pub fn lens(&self) -> LensConfig {
         LensConfig {
             run: self.data.lens_enable && self.data.lens_run,
@@ -815,6 +1002,7 @@ impl Config {
                     HoverDocFormat::PlainText
                 }
             }),
+            memory_layout: self.data.hover_memoryLayout_enable,
         }
     }
 
@@ -883,6 +1071,28 @@ enum ImportPrefixDef {
     ByCrate,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum PostfixSnippetDef {
+    Template(String),
+    #[serde(rename_all = "camelCase")]
+    Full {
+        body: String,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        receiver_type: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum LifetimeElisionHintsDef {
+    Always,
+    SkipTrivial,
+    Never,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 enum WorskpaceSymbolSearchScopeDef {
@@ -1023,6 +1233,18 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
             "items": { "type": "string" },
             "uniqueItems": true,
         },
+        "FxHashMap<String, PostfixSnippetDef>" => set! {
+            "type": "object",
+            "additionalProperties": {
+                "type": ["string", "object"],
+                "properties": {
+                    "body": { "type": "string" },
+                    "description": { "type": "string" },
+                    "receiverType": { "type": "string" },
+                },
+                "required": ["body"],
+            },
+        },
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
@@ -1075,6 +1297,15 @@ fn field_props(field: &str, ty: &str, doc: &[&str], default: &str) -> serde_json
                 "Force import paths to be absolute by always starting them with `crate` or the extern crate name they come from."
             ],
         },
+        "LifetimeElisionHintsDef" => set! {
+            "type": "string",
+            "enum": ["always", "skip_trivial", "never"],
+            "enumDescriptions": [
+                "Always show lifetime elision hints.",
+                "Only show lifetime elision hints for functions returning a reference with an elided lifetime.",
+                "Never show lifetime elision hints."
+            ],
+        },
         "Vec<ManifestOrProjectJson>" => set! {
             "type": "array",
             "items": { "type": ["string", "object"] },
@@ -1163,6 +1394,56 @@ mod tests {
         ensure_file_contents(&docs_path, &expected);
     }
 
+    #[test]
+    fn folder_configs() {
+        let root = AbsPathBuf::assert(project_root());
+        let nested = root.join("crates");
+        let mut config = Config::new(root.clone(), ClientCapabilities::default());
+        config.workspace_roots = vec![root.clone(), nested.clone()];
+        config.discovered_projects = Some(vec![
+            ProjectManifest::CargoToml(root.join("Cargo.toml")),
+            ProjectManifest::CargoToml(nested.join("foo/Cargo.toml")),
+        ]);
+
+        let folder = config.for_folder(
+            nested.clone(),
+            serde_json::json!({ "checkOnSave": { "command": "clippy" } }),
+        );
+        assert_eq!(folder.root_path, nested);
+        assert_eq!(
+            folder.linked_projects(),
+            vec![LinkedProject::from(ProjectManifest::CargoToml(nested.join("foo/Cargo.toml")))]
+        );
+        match folder.flycheck() {
+            Some(FlycheckConfig::CargoCommand { command, .. }) => assert_eq!(command, "clippy"),
+            it => panic!("unexpected flycheck config: {:?}", it),
+        }
+        assert_eq!(config.workspace_root_for(&root.join("xtask")), Some(&root));
+    }
+
+    #[test]
+    fn add_discovered_projects() {
+        let root = AbsPathBuf::assert(project_root());
+        let nested = root.join("crates");
+        let mut config = Config::new(root.clone(), ClientCapabilities::default());
+        config.workspace_roots = vec![nested.clone()];
+        config.discovered_projects =
+            Some(vec![ProjectManifest::CargoToml(nested.join("a/Cargo.toml"))]);
+
+        config.add_discovered_projects(&[
+            ProjectManifest::CargoToml(nested.join("a/Cargo.toml")),
+            ProjectManifest::CargoToml(nested.join("b/Cargo.toml")),
+            ProjectManifest::CargoToml(root.join("Cargo.toml")),
+        ]);
+        assert_eq!(
+            config.discovered_projects,
+            Some(vec![
+                ProjectManifest::CargoToml(nested.join("a/Cargo.toml")),
+                ProjectManifest::CargoToml(nested.join("b/Cargo.toml")),
+            ])
+        );
+    }
+
     fn remove_ws(text: &str) -> String {
         text.replace(char::is_whitespace, "")
     }
@@ -9,6 +9,8 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use crate::lsp_ext;
 
 pub(crate) type CheckFixes = Arc<FxHashMap<FileId, Vec<Fix>>>;
+pub(crate) type CheckDiagnostics = Arc<FxHashMap<FileId, Vec<lsp_types::Diagnostic>>>;
+pub(crate) type CheckLints = Arc<Vec<String>>;
 
 #[derive(Debug, Default, Clone)]
 pub struct DiagnosticsMapConfig {
@@ -22,8 +24,11 @@ pub(crate) struct DiagnosticCollection {
     // FIXME: should be FxHashMap<FileId, Vec<ra_id::Diagnostic>>
     pub(crate) native: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
     // FIXME: should be Vec<flycheck::Diagnostic>
-    pub(crate) check: FxHashMap<FileId, Vec<lsp_types::Diagnostic>>,
+    pub(crate) check: CheckDiagnostics,
     pub(crate) check_fixes: CheckFixes,
+    /// The sorted names of the lints reported by `cargo check`, offered by the
+    /// completion of lint attributes.
+    pub(crate) check_lints: CheckLints,
     changes: FxHashSet<FileId>,
 }
 
@@ -36,7 +41,24 @@ pub(crate) struct Fix {
 impl DiagnosticCollection {
     pub(crate) fn clear_check(&mut self) {
         Arc::make_mut(&mut self.check_fixes).clear();
-        self.changes.extend(self.check.drain().map(|(key, _value)| key))
+        Arc::make_mut(&mut self.check_lints).clear();
+        self.changes.extend(Arc::make_mut(&mut self.check).drain().map(|(key, _value)| key))
+    }
+
+    /// Removes all the diagnostics of the given files, for example because
+    /// they are not part of the workspace anymore.
+    pub(crate) fn clear_files(&mut self, files: impl IntoIterator<Item = FileId>) {
+        let check = Arc::make_mut(&mut self.check);
+        let check_fixes = Arc::make_mut(&mut self.check_fixes);
+        for file_id in files {
+            let had_native = self.native.remove(&file_id).is_some();
+            let had_check = check.remove(&file_id).is_some();
+            check_fixes.remove(&file_id);
+            if had_native || had_check {
+                self.changes.insert(file_id);
+            }
+        }
+        self.check_lints = Arc::new(check_lints(check));
     }
 
     pub(crate) fn add_check_diagnostic(
@@ -45,7 +67,7 @@ impl DiagnosticCollection {
         diagnostic: lsp_types::Diagnostic,
         fixes: Vec<lsp_ext::CodeAction>,
     ) {
-        let diagnostics = self.check.entry(file_id).or_default();
+        let diagnostics = Arc::make_mut(&mut self.check).entry(file_id).or_default();
         for existing_diagnostic in diagnostics.iter() {
             if are_diagnostics_equal(existing_diagnostic, &diagnostic) {
                 return;
@@ -57,6 +79,11 @@ impl DiagnosticCollection {
             .entry(file_id)
             .or_default()
             .extend(fixes.into_iter().map(|action| Fix { range: diagnostic.range, action }));
+        if let Some(lint) = check_lint(&diagnostic) {
+            if let Err(idx) = self.check_lints.binary_search(&lint) {
+                Arc::make_mut(&mut self.check_lints).insert(idx, lint);
+            }
+        }
         diagnostics.push(diagnostic);
         self.changes.insert(file_id);
     }
@@ -98,6 +125,30 @@ impl DiagnosticCollection {
     }
 }
 
+fn check_lints(check: &FxHashMap<FileId, Vec<lsp_types::Diagnostic>>) -> Vec<String> {
+    let mut res = check.values().flatten().filter_map(check_lint).collect::<Vec<_>>();
+    res.sort();
+    res.dedup();
+    res
+}
+
+/// Returns the name of the lint reported by a `cargo check` diagnostic, naming
+/// the lints of tools like `clippy::needless_return`.
+fn check_lint(diagnostic: &lsp_types::Diagnostic) -> Option<String> {
+    let code = match &diagnostic.code {
+        Some(lsp_types::NumberOrString::String(it)) => it,
+        _ => return None,
+    };
+    // Errors have codes like `E0308` instead.
+    if !code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        return None;
+    }
+    match diagnostic.source.as_deref() {
+        None | Some("rustc") => Some(code.clone()),
+        Some(tool) => Some(format!("{}::{}", tool, code)),
+    }
+}
+
 fn are_diagnostics_equal(left: &lsp_types::Diagnostic, right: &lsp_types::Diagnostic) -> bool {
     left.source == right.source
         && left.severity == right.severity
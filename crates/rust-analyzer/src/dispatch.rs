@@ -63,7 +63,7 @@ impl<'a> RequestDispatcher<'a> {
         };
 
         self.global_state.task_pool.handle.spawn({
-            let world = self.global_state.snapshot();
+            let world = self.global_state.request_snapshot(id.clone());
 
             move || {
                 let _pctx = stdx::panic_context::enter(format!(
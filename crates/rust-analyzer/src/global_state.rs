@@ -3,23 +3,28 @@
 //!
 //! Each tick provides an immutable snapshot of the state as `WorldSnapshot`.
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crossbeam_channel::{unbounded, Receiver, Sender};
-use flycheck::FlycheckHandle;
-use ide::{Analysis, AnalysisHost, Cancellable, Change, FileId};
-use ide_db::base_db::{CrateId, VfsPath};
+use flycheck::{FlycheckHandle, TestRunHandle};
+use ide::{Analysis, AnalysisHost, Cancellable, CancellationToken, Change, FileId};
+use ide_db::base_db::{CrateGraph, CrateId, VfsPath};
 use lsp_types::{SemanticTokens, Url};
 use parking_lot::{Mutex, RwLock};
 use project_model::{
-    BuildDataCollector, BuildDataResult, CargoWorkspace, ProcMacroClient, ProjectWorkspace, Target,
+    BuildDataCollector, BuildDataResult, CargoConfig, CargoWorkspace, ProcMacroClient,
+    ProjectWorkspace, Target,
 };
 use rustc_hash::FxHashMap;
-use vfs::AnchoredPathBuf;
+use vfs::{AbsPath, AbsPathBuf, AnchoredPathBuf};
 
 use crate::{
-    config::Config,
-    diagnostics::{CheckFixes, DiagnosticCollection},
+    cargo_toml::{self, RegistryCrates},
+    config::{Config, LinkedProject},
+    diagnostics::{CheckDiagnostics, CheckFixes, CheckLints, DiagnosticCollection},
     document::DocumentData,
     from_proto,
     line_index::{LineEndings, LineIndex},
@@ -40,6 +45,10 @@ pub(crate) struct Handle<H, C> {
 }
 
 pub(crate) type ReqHandler = fn(&mut GlobalState, lsp_server::Response);
+/// How often the symbol cache is saved while the server is running, so that
+/// it survives crashes.
+const SYMBOL_CACHE_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
 pub(crate) type ReqQueue = lsp_server::ReqQueue<(String, Instant), ReqHandler>;
 
 /// `GlobalState` is the primary mutable state of the language server
@@ -52,14 +61,28 @@ pub(crate) type ReqQueue = lsp_server::ReqQueue<(String, Instant), ReqHandler>;
 pub(crate) struct GlobalState {
     sender: Sender<lsp_server::Message>,
     req_queue: ReqQueue,
+    /// Tokens cancelling the analysis of the requests running on the thread
+    /// pool, for `$/cancelRequest`.
+    in_flight_requests: FxHashMap<lsp_server::RequestId, CancellationToken>,
     pub(crate) task_pool: Handle<TaskPool<Task>, Receiver<Task>>,
     pub(crate) loader: Handle<Box<dyn vfs::loader::Handle>, Receiver<vfs::loader::Message>>,
     pub(crate) config: Arc<Config>,
+    /// The configs of the individual workspace folders, when the client scopes
+    /// its settings by folder. Empty otherwise.
+    pub(crate) folder_configs: Vec<Arc<Config>>,
+    /// The workspace folders whose settings were requested, in the order of
+    /// the items of each pending `workspace/configuration` request.
+    pub(crate) config_requests: FxHashMap<lsp_server::RequestId, Vec<AbsPathBuf>>,
     pub(crate) analysis_host: AnalysisHost,
     pub(crate) diagnostics: DiagnosticCollection,
     pub(crate) mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    /// The crates of the registry cache, listed on the first completion of a
+    /// `Cargo.toml` and again after the workspaces are reloaded.
+    pub(crate) registry_crates: Arc<Mutex<Option<Arc<RegistryCrates>>>>,
     pub(crate) shutdown_requested: bool,
+    /// When the symbol cache was last saved, see `save_symbol_cache`.
+    symbol_cache_saved: Instant,
     pub(crate) last_reported_status: Option<lsp_ext::ServerStatusParams>,
     pub(crate) source_root_config: SourceRootConfig,
     pub(crate) proc_macro_client: Option<ProcMacroClient>,
@@ -68,6 +91,12 @@ pub(crate) struct GlobalState {
     pub(crate) flycheck_sender: Sender<flycheck::Message>,
     pub(crate) flycheck_receiver: Receiver<flycheck::Message>,
 
+    /// Tests run on behalf of the client, by run id.
+    pub(crate) test_runs: FxHashMap<usize, TestRunHandle>,
+    pub(crate) next_test_run_id: usize,
+    pub(crate) test_run_sender: Sender<flycheck::TestRunMessage>,
+    pub(crate) test_run_receiver: Receiver<flycheck::TestRunMessage>,
+
     pub(crate) vfs: Arc<RwLock<(vfs::Vfs, FxHashMap<FileId, LineEndings>)>>,
     pub(crate) vfs_config_version: u32,
     pub(crate) vfs_progress_config_version: u32,
@@ -81,7 +110,22 @@ pub(crate) struct GlobalState {
     /// If the fetch (partially) fails, we do not update the values.
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
     pub(crate) fetch_workspaces_queue: OpQueue<(), Vec<anyhow::Result<ProjectWorkspace>>>,
+    /// The projects the workspaces of the last fetch were loaded from, in the
+    /// same order.
+    pub(crate) fetched_projects: Vec<(LinkedProject, CargoConfig)>,
+    /// Whether the requested fetch can reuse the workspaces of the last one
+    /// for the projects which didn't change, instead of loading everything.
+    pub(crate) reuse_fetched_workspaces: bool,
+    /// Projects which have to be loaded again by the requested fetch, even if
+    /// the workspaces of the last one are reused.
+    pub(crate) outdated_projects: Vec<LinkedProject>,
+    /// Workspaces loaded so far by the initial fetch, which are switched to
+    /// one by one so that features work before all of them are loaded.
+    pub(crate) partially_fetched_workspaces: Vec<anyhow::Result<ProjectWorkspace>>,
     pub(crate) workspace_build_data: Option<BuildDataResult>,
+    /// The crate graphs of the `workspaces`, in the same order, so that only
+    /// the workspaces which changed are lowered again when switching.
+    pub(crate) workspace_crate_graphs: Vec<CrateGraph>,
     pub(crate) fetch_build_data_queue:
         OpQueue<BuildDataCollector, Option<anyhow::Result<BuildDataResult>>>,
     pub(crate) prime_caches_queue: OpQueue<(), ()>,
@@ -94,9 +138,12 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) config: Arc<Config>,
     pub(crate) analysis: Analysis,
     pub(crate) check_fixes: CheckFixes,
+    pub(crate) check_diagnostics: CheckDiagnostics,
+    pub(crate) check_lints: CheckLints,
     pub(crate) latest_requests: Arc<RwLock<LatestRequests>>,
     mem_docs: FxHashMap<VfsPath, DocumentData>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    registry_crates: Arc<Mutex<Option<Arc<RegistryCrates>>>>,
     vfs: Arc<RwLock<(vfs::Vfs, FxHashMap<FileId, LineEndings>)>>,
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
 }
@@ -117,19 +164,28 @@ impl GlobalState {
             Handle { handle, receiver }
         };
 
-        let analysis_host = AnalysisHost::new(config.lru_capacity());
+        let mut analysis_host = AnalysisHost::new(config.lru_capacity());
+        if let Some(dir) = config.symbol_cache_dir() {
+            analysis_host.set_symbol_cache_dir(Some(dir.as_ref()));
+        }
         let (flycheck_sender, flycheck_receiver) = unbounded();
+        let (test_run_sender, test_run_receiver) = unbounded();
         let mut this = GlobalState {
             sender,
             req_queue: ReqQueue::default(),
+            in_flight_requests: FxHashMap::default(),
             task_pool,
             loader,
             config: Arc::new(config.clone()),
+            folder_configs: Vec::new(),
+            config_requests: FxHashMap::default(),
             analysis_host,
             diagnostics: Default::default(),
             mem_docs: FxHashMap::default(),
             semantic_tokens_cache: Arc::new(Default::default()),
+            registry_crates: Arc::new(Default::default()),
             shutdown_requested: false,
+            symbol_cache_saved: Instant::now(),
             last_reported_status: None,
             source_root_config: SourceRootConfig::default(),
             proc_macro_client: None,
@@ -138,6 +194,11 @@ impl GlobalState {
             flycheck_sender,
             flycheck_receiver,
 
+            test_runs: FxHashMap::default(),
+            next_test_run_id: 0,
+            test_run_sender,
+            test_run_receiver,
+
             vfs: Arc::new(RwLock::new((vfs::Vfs::default(), FxHashMap::default()))),
             vfs_config_version: 0,
             vfs_progress_config_version: 0,
@@ -146,14 +207,19 @@ impl GlobalState {
 
             workspaces: Arc::new(Vec::new()),
             fetch_workspaces_queue: OpQueue::default(),
+            fetched_projects: Vec::new(),
+            reuse_fetched_workspaces: false,
+            outdated_projects: Vec::new(),
+            partially_fetched_workspaces: Vec::new(),
             workspace_build_data: None,
+            workspace_crate_graphs: Vec::new(),
             prime_caches_queue: OpQueue::default(),
 
             fetch_build_data_queue: OpQueue::default(),
             latest_requests: Default::default(),
         };
         // Apply any required database inputs from the config.
-        this.update_configuration(config);
+        this.update_configuration(config, Vec::new());
         this
     }
 
@@ -205,16 +271,39 @@ impl GlobalState {
         true
     }
 
+    /// Returns the config of the workspace folder containing `path`.
+    pub(crate) fn config_for(&self, path: &AbsPath) -> Arc<Config> {
+        let root = self.config.workspace_root_for(path);
+        let config = self.folder_configs.iter().find(|config| Some(&config.root_path) == root);
+        Arc::clone(config.unwrap_or(&self.config))
+    }
+
     pub(crate) fn snapshot(&self) -> GlobalStateSnapshot {
+        self.snapshot_with(self.analysis_host.analysis())
+    }
+
+    /// Creates a snapshot for handling the request `id` on the thread pool,
+    /// whose analysis is cancelled if the client cancels the request.
+    pub(crate) fn request_snapshot(&mut self, id: lsp_server::RequestId) -> GlobalStateSnapshot {
+        let token = CancellationToken::default();
+        let analysis = self.analysis_host.analysis_with_cancellation(token.clone());
+        self.in_flight_requests.insert(id, token);
+        self.snapshot_with(analysis)
+    }
+
+    fn snapshot_with(&self, analysis: Analysis) -> GlobalStateSnapshot {
         GlobalStateSnapshot {
             config: Arc::clone(&self.config),
             workspaces: Arc::clone(&self.workspaces),
-            analysis: self.analysis_host.analysis(),
+            analysis,
             vfs: Arc::clone(&self.vfs),
             latest_requests: Arc::clone(&self.latest_requests),
             check_fixes: Arc::clone(&self.diagnostics.check_fixes),
+            check_diagnostics: Arc::clone(&self.diagnostics.check),
+            check_lints: Arc::clone(&self.diagnostics.check_lints),
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
+            registry_crates: Arc::clone(&self.registry_crates),
         }
     }
 
@@ -222,9 +311,11 @@ impl GlobalState {
         &mut self,
         params: R::Params,
         handler: ReqHandler,
-    ) {
+    ) -> lsp_server::RequestId {
         let request = self.req_queue.outgoing.register(R::METHOD.to_string(), params, handler);
+        let id = request.id.clone();
         self.send(request.into());
+        id
     }
     pub(crate) fn complete_request(&mut self, response: lsp_server::Response) {
         let handler = self.req_queue.outgoing.complete(response.id.clone());
@@ -249,6 +340,7 @@ impl GlobalState {
             .register(request.id.clone(), (request.method.clone(), request_received));
     }
     pub(crate) fn respond(&mut self, response: lsp_server::Response) {
+        self.in_flight_requests.remove(&response.id);
         if let Some((method, start)) = self.req_queue.incoming.complete(response.id.clone()) {
             let duration = start.elapsed();
             log::info!("handled req#{} in {:?}", response.id, duration);
@@ -257,7 +349,21 @@ impl GlobalState {
             self.send(response.into());
         }
     }
+    /// Saves the symbols computed since the last save to the symbol cache, at
+    /// most once per `SYMBOL_CACHE_SAVE_INTERVAL` unless `force` is set.
+    pub(crate) fn save_symbol_cache(&mut self, force: bool) {
+        if !force && self.symbol_cache_saved.elapsed() < SYMBOL_CACHE_SAVE_INTERVAL {
+            return;
+        }
+        self.symbol_cache_saved = Instant::now();
+        if let Err(err) = self.analysis_host.save_symbol_cache() {
+            log::error!("failed to save the symbol cache: {}", err);
+        }
+    }
     pub(crate) fn cancel(&mut self, request_id: lsp_server::RequestId) {
+        if let Some(token) = self.in_flight_requests.remove(&request_id) {
+            token.cancel();
+        }
         if let Some(response) = self.req_queue.incoming.cancel(request_id) {
             self.send(response.into());
         }
@@ -283,6 +389,11 @@ impl GlobalStateSnapshot {
         file_id_to_url(&self.vfs.read().0, id)
     }
 
+    pub(crate) fn registry_crates(&self) -> Arc<RegistryCrates> {
+        let mut cache = self.registry_crates.lock();
+        Arc::clone(cache.get_or_insert_with(|| Arc::new(cargo_toml::registry_crates())))
+    }
+
     pub(crate) fn file_line_index(&self, file_id: FileId) -> Cancellable<LineIndex> {
         let endings = self.vfs.read().1[&file_id];
         let index = self.analysis.file_line_index(file_id)?;
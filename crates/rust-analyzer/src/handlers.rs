@@ -3,14 +3,17 @@
 //! `ide` crate.
 
 use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
     io::{Read, Write as _},
     process::{self, Command, Stdio},
+    sync::Arc,
 };
 
 use ide::{
-    AnnotationConfig, AssistKind, AssistResolveStrategy, FileId, FilePosition, FileRange,
-    HoverAction, HoverGotoTypeData, Query, RangeInfo, Runnable, RunnableKind, SingleResolve,
-    SourceChange, TextEdit,
+    AnnotationConfig, AssistKind, AssistResolveStrategy, CompletionConfig, CrateGraphConfig,
+    FileId, FilePosition, FileRange, HoverAction, HoverGotoTypeData, Query, RangeInfo, Runnable,
+    RunnableKind, SingleResolve, SourceChange, TextEdit,
 };
 use ide_db::SymbolKind;
 use itertools::Itertools;
@@ -24,18 +27,19 @@ use lsp_types::{
     SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
     SemanticTokensResult, SymbolInformation, SymbolTag, TextDocumentIdentifier, Url, WorkspaceEdit,
 };
-use project_model::TargetKind;
+use project_model::{ProjectWorkspace, TargetKind};
 use serde_json::json;
 use stdx::{format_to, never};
 use syntax::{algo, ast, AstNode, TextRange, TextSize};
 
 use crate::{
     cargo_target_spec::CargoTargetSpec,
+    cargo_toml::{self, ManifestCompletion},
     config::RustfmtConfig,
     diff::diff,
     from_proto,
     global_state::{GlobalState, GlobalStateSnapshot},
-    line_index::LineEndings,
+    line_index::{LineEndings, LineIndex},
     lsp_ext::{self, InlayHint, InlayHintsParams, ViewCrateGraphParams, WorkspaceSymbolParams},
     lsp_utils::all_edits_are_disjoint,
     to_proto, LspError, Result,
@@ -85,16 +89,62 @@ pub(crate) fn handle_analyzer_status(
     Ok(buf)
 }
 
-pub(crate) fn handle_memory_usage(state: &mut GlobalState, _: ()) -> Result<String> {
+pub(crate) fn handle_memory_usage(
+    state: &mut GlobalState,
+    _: (),
+) -> Result<lsp_ext::MemoryUsageResult> {
     let _p = profile::span("handle_memory_usage");
-    let mut mem = state.analysis_host.per_query_memory_usage();
-    mem.push(("Remaining".into(), profile::memory_usage().allocated));
+    // Computed first, as measuring the queries clears the database.
+    let sources = state
+        .analysis_host
+        .per_source_root_text_size()
+        .into_iter()
+        .map(|(crates, files, text_len)| lsp_ext::SourceTextSize { crates, files, text_len })
+        .collect();
+    let queries = state
+        .analysis_host
+        .per_query_memory_usage()
+        .into_iter()
+        .map(|(name, bytes)| lsp_ext::QueryMemoryUsage { name, bytes: bytes.bytes() })
+        .collect();
+    let remaining = profile::memory_usage().allocated.bytes();
+    Ok(lsp_ext::MemoryUsageResult { queries, remaining, sources })
+}
 
-    let mut out = String::new();
-    for (name, bytes) in mem {
-        format_to!(out, "{:>8} {}\n", bytes, name);
+pub(crate) fn handle_test_run(
+    state: &mut GlobalState,
+    params: lsp_ext::TestRunParams,
+) -> Result<lsp_ext::TestRunResult> {
+    let _p = profile::span("handle_test_run");
+    let args = params.runnable.args;
+    let mut cmd = match args.override_cargo {
+        Some(it) => Command::new(it),
+        None => Command::new(toolchain::cargo()),
+    };
+    if let Some(workspace_root) = &args.workspace_root {
+        cmd.current_dir(workspace_root);
     }
-    Ok(out)
+    cmd.args(&args.cargo_args).args(&args.cargo_extra_args).arg("--");
+    // The output of each test has to be captured to be attributed to it.
+    cmd.args(args.executable_args.iter().filter(|it| *it != "--nocapture"));
+
+    let run_id = state.next_test_run_id;
+    state.next_test_run_id += 1;
+    let sender = state.test_run_sender.clone();
+    let handle =
+        flycheck::TestRunHandle::spawn(run_id, Box::new(move |msg| sender.send(msg).unwrap()), cmd)
+            .map_err(|err| format!("failed to run tests: {}", err))?;
+    state.test_runs.insert(run_id, handle);
+    Ok(lsp_ext::TestRunResult { run_id })
+}
+
+pub(crate) fn handle_cancel_test_run(
+    state: &mut GlobalState,
+    params: lsp_ext::CancelTestRunParams,
+) -> Result<()> {
+    let _p = profile::span("handle_cancel_test_run");
+    state.test_runs.remove(&params.run_id);
+    Ok(())
 }
 
 pub(crate) fn handle_syntax_tree(
@@ -119,6 +169,26 @@ pub(crate) fn handle_view_hir(
     Ok(res)
 }
 
+pub(crate) fn handle_interpret_function(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> Result<String> {
+    let _p = profile::span("handle_interpret_function");
+    let position = from_proto::file_position(&snap, params)?;
+    let res = snap.analysis.interpret_function(position)?;
+    Ok(res)
+}
+
+pub(crate) fn handle_view_memory_layout(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> Result<Option<lsp_ext::MemoryLayoutNode>> {
+    let _p = profile::span("handle_view_memory_layout");
+    let position = from_proto::file_position(&snap, params)?;
+    let res = snap.analysis.view_memory_layout(position)?;
+    Ok(res.map(to_proto::memory_layout_node))
+}
+
 pub(crate) fn handle_view_item_tree(
     snap: GlobalStateSnapshot,
     params: lsp_ext::ViewItemTreeParams,
@@ -132,10 +202,36 @@ pub(crate) fn handle_view_item_tree(
 pub(crate) fn handle_view_crate_graph(
     snap: GlobalStateSnapshot,
     params: ViewCrateGraphParams,
-) -> Result<String> {
+) -> Result<lsp_ext::CrateGraph> {
     let _p = profile::span("handle_view_crate_graph");
-    let dot = snap.analysis.view_crate_graph(params.full)??;
+    let config = CrateGraphConfig {
+        full: params.full,
+        name_filter: params.filter,
+        collapse_chains: params.collapse_chains,
+    };
+    let graph = snap.analysis.view_crate_graph(config)??;
+
+    let svg = match render_svg(&graph.dot) {
+        Ok(it) => Some(it),
+        Err(err) => {
+            log::warn!("failed to render the crate graph: {}", err);
+            None
+        }
+    };
+    let crates = graph
+        .crates
+        .into_iter()
+        .map(|krate| lsp_ext::CrateGraphNode {
+            id: krate.id.0,
+            name: krate.name,
+            is_workspace_member: krate.is_workspace_member,
+            dependencies: krate.dependencies.into_iter().map(|it| it.0).collect(),
+        })
+        .collect();
+    Ok(lsp_ext::CrateGraph { dot: graph.dot, svg, crates })
+}
 
+fn render_svg(dot: &str) -> Result<String> {
     // We shell out to `dot` to render to SVG, as there does not seem to be a pure-Rust renderer.
     let child = Command::new("dot")
         .arg("-Tsvg")
@@ -159,8 +255,25 @@ pub(crate) fn handle_expand_macro(
     let line_index = snap.file_line_index(file_id)?;
     let offset = from_proto::offset(&line_index, params.position);
 
-    let res = snap.analysis.expand_macro(FilePosition { file_id, offset })?;
-    Ok(res.map(|it| lsp_ext::ExpandedMacro { name: it.name, expansion: it.expansion }))
+    let res = match snap.analysis.expand_macro(FilePosition { file_id, offset }, params.depth)? {
+        Some(it) => it,
+        None => return Ok(None),
+    };
+    let expansion_line_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(&res.expansion)),
+        endings: LineEndings::Unix,
+        encoding: snap.config.offset_encoding(),
+    };
+    let origins = res
+        .origins
+        .into_iter()
+        .filter(|(_, origin)| origin.file_id == file_id)
+        .map(|(range, origin)| lsp_ext::ExpansionOrigin {
+            expansion_range: to_proto::range(&expansion_line_index, range),
+            original_range: to_proto::range(&line_index, origin.range),
+        })
+        .collect();
+    Ok(Some(lsp_ext::ExpandedMacro { name: res.name, expansion: res.expansion, origins }))
 }
 
 pub(crate) fn handle_selection_range(
@@ -271,7 +384,7 @@ pub(crate) fn handle_on_enter(
 pub(crate) fn handle_on_type_formatting(
     snap: GlobalStateSnapshot,
     params: lsp_types::DocumentOnTypeFormattingParams,
-) -> Result<Option<Vec<lsp_types::TextEdit>>> {
+) -> Result<Option<Vec<lsp_ext::SnippetTextEdit>>> {
     let _p = profile::span("handle_on_type_formatting");
     let mut position = from_proto::file_position(&snap, params.text_document_position)?;
     let line_index = snap.file_line_index(position.file_id)?;
@@ -293,16 +406,16 @@ pub(crate) fn handle_on_type_formatting(
         return Ok(None);
     }
 
-    let edit = snap.analysis.on_char_typed(position, char_typed)?;
+    let edit = snap.analysis.on_char_typed(&snap.config.typing(), position, char_typed)?;
     let edit = match edit {
         Some(it) => it,
         None => return Ok(None),
     };
 
     // This should be a single-file edit
-    let (_, edit) = edit.source_file_edits.into_iter().next().unwrap();
+    let (_, text_edit) = edit.source_file_edits.into_iter().next().unwrap();
 
-    let change = to_proto::text_edit_vec(&line_index, edit);
+    let change = to_proto::snippet_text_edit_vec(&line_index, edit.is_snippet, text_edit);
     Ok(Some(change))
 }
 
@@ -316,16 +429,22 @@ pub(crate) fn handle_document_symbol(
 
     let mut parents: Vec<(lsp_types::DocumentSymbol, Option<usize>)> = Vec::new();
 
-    for symbol in snap.analysis.file_structure(file_id)? {
+    for symbol in snap.analysis.file_structure(&snap.config.file_structure(), file_id)? {
         let mut tags = Vec::new();
         if symbol.deprecated {
             tags.push(SymbolTag::Deprecated)
         };
+        // LSP has no tag for generated symbols, so they are marked in the detail.
+        let detail = match (symbol.macro_generated, symbol.detail) {
+            (true, Some(detail)) => Some(format!("{} (macro-generated)", detail)),
+            (true, None) => Some("(macro-generated)".to_string()),
+            (false, detail) => detail,
+        };
 
         #[allow(deprecated)]
         let doc_symbol = lsp_types::DocumentSymbol {
             name: symbol.label,
-            detail: symbol.detail,
+            detail,
             kind: to_proto::structure_node_kind(symbol.kind),
             tags: Some(tags),
             deprecated: Some(symbol.deprecated),
@@ -758,7 +877,10 @@ pub(crate) fn handle_completion(
         return Ok(None);
     }
 
-    let completion_config = &snap.config.completion();
+    let completion_config = &CompletionConfig {
+        additional_lints: snap.check_lints.to_vec(),
+        ..snap.config.completion()
+    };
     let items = match snap.analysis.completions(completion_config, position)? {
         None => return Ok(None),
         Some(items) => items,
@@ -928,7 +1050,7 @@ pub(crate) fn handle_rename(
 pub(crate) fn handle_references(
     snap: GlobalStateSnapshot,
     params: lsp_types::ReferenceParams,
-) -> Result<Option<Vec<Location>>> {
+) -> Result<Option<Vec<lsp_ext::ReferenceLocation>>> {
     let _p = profile::span("handle_references");
     let position = from_proto::file_position(&snap, params.text_document_position)?;
 
@@ -938,9 +1060,10 @@ pub(crate) fn handle_references(
     };
 
     let decl = if params.context.include_declaration {
-        refs.declaration.map(|decl| FileRange {
-            file_id: decl.nav.file_id,
-            range: decl.nav.focus_or_full_range(),
+        refs.declaration.map(|decl| {
+            let frange =
+                FileRange { file_id: decl.nav.file_id, range: decl.nav.focus_or_full_range() };
+            (frange, decl.category)
         })
     } else {
         None
@@ -949,10 +1072,14 @@ pub(crate) fn handle_references(
         .references
         .into_iter()
         .flat_map(|(file_id, refs)| {
-            refs.into_iter().map(move |(range, _)| FileRange { file_id, range })
+            refs.into_iter().map(move |(range, category)| (FileRange { file_id, range }, category))
         })
         .chain(decl)
-        .filter_map(|frange| to_proto::location(&snap, frange).ok())
+        .filter_map(|(frange, category)| {
+            let location = to_proto::location(&snap, frange).ok()?;
+            let category = category.map(to_proto::reference_category);
+            Some(lsp_ext::ReferenceLocation { location, category })
+        })
         .collect();
 
     Ok(Some(locations))
@@ -1194,9 +1321,9 @@ pub(crate) fn handle_document_highlight(
     };
     let res = refs
         .into_iter()
-        .map(|ide::HighlightedRange { range, access }| lsp_types::DocumentHighlight {
+        .map(|ide::HighlightedRange { range, category }| lsp_types::DocumentHighlight {
             range: to_proto::range(&line_index, range),
-            kind: access.map(to_proto::document_highlight_kind),
+            kind: category.and_then(to_proto::document_highlight_kind),
         })
         .collect();
     Ok(Some(res))
@@ -1207,19 +1334,77 @@ pub(crate) fn handle_ssr(
     params: lsp_ext::SsrParams,
 ) -> Result<lsp_types::WorkspaceEdit> {
     let _p = profile::span("handle_ssr");
+    let (source_change, _) = ssr_source_change(&snap, params)?;
+    to_proto::workspace_edit(&snap, source_change)
+}
+
+pub(crate) fn handle_ssr_preview(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::SsrParams,
+) -> Result<lsp_ext::SsrPreviewResult> {
+    let _p = profile::span("handle_ssr_preview");
+    let (source_change, rules) = ssr_source_change(&snap, params)?;
+    let rules = rules
+        .into_iter()
+        .map(|it| lsp_ext::SsrRuleSummary {
+            rule: it.rule,
+            matches: it.match_counts.values().sum(),
+            files: it.match_counts.len(),
+        })
+        .collect();
+    let mut res = Vec::new();
+    for (file_id, edit) in source_change.source_file_edits {
+        let line_index = snap.file_line_index(file_id)?;
+        let text = snap.analysis.file_text(file_id)?;
+        let text_document = TextDocumentIdentifier::new(to_proto::url(&snap, file_id));
+        // Each replaced range is a match.
+        for indel in edit.iter() {
+            res.push(lsp_ext::SsrPreviewEntry {
+                text_document: text_document.clone(),
+                range: to_proto::range(&line_index, indel.delete),
+                before: text[indel.delete].to_string(),
+                after: indel.insert.clone(),
+            });
+        }
+    }
+    res.sort_by(|a, b| {
+        a.text_document.uri.as_str().cmp(b.text_document.uri.as_str()).then_with(|| {
+            (a.range.start.line, a.range.start.character)
+                .cmp(&(b.range.start.line, b.range.start.character))
+        })
+    });
+    Ok(lsp_ext::SsrPreviewResult { rules, matches: res })
+}
+
+/// Computes the edits of the SSR query of `params`, and the matches of each of its rules, without
+/// the excluded files.
+fn ssr_source_change(
+    snap: &GlobalStateSnapshot,
+    params: lsp_ext::SsrParams,
+) -> Result<(SourceChange, Vec<ide::SsrRuleMatches>)> {
     let selections = params
         .selections
         .iter()
-        .map(|range| from_proto::file_range(&snap, params.position.text_document.clone(), *range))
+        .map(|range| from_proto::file_range(snap, params.position.text_document.clone(), *range))
         .collect::<Result<Vec<_>, _>>()?;
-    let position = from_proto::file_position(&snap, params.position)?;
-    let source_change = snap.analysis.structural_search_replace(
+    // The files unknown to the server have no edits to exclude.
+    let excluded_files = params
+        .excluded_files
+        .iter()
+        .filter_map(|it| from_proto::file_id(snap, &it.uri).ok())
+        .collect::<Vec<_>>();
+    let position = from_proto::file_position(snap, params.position)?;
+    let (mut source_change, mut rules) = snap.analysis.structural_search_replace(
         &params.query,
         params.parse_only,
         position,
         selections,
     )??;
-    to_proto::workspace_edit(&snap, source_change)
+    source_change.source_file_edits.retain(|file_id, _| !excluded_files.contains(file_id));
+    for rule in &mut rules {
+        rule.match_counts.retain(|file_id, _| !excluded_files.contains(file_id));
+    }
+    Ok((source_change, rules))
 }
 
 pub(crate) fn publish_diagnostics(
@@ -1254,6 +1439,73 @@ pub(crate) fn publish_diagnostics(
     Ok(diagnostics)
 }
 
+pub(crate) fn handle_document_diagnostic(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::DocumentDiagnosticParams,
+) -> Result<lsp_ext::DocumentDiagnosticReport> {
+    let _p = profile::span("handle_document_diagnostic");
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let mut diagnostics = if snap.config.publish_diagnostics() {
+        publish_diagnostics(&snap, file_id)?
+    } else {
+        Vec::new()
+    };
+    diagnostics.extend(snap.check_diagnostics.get(&file_id).into_iter().flatten().cloned());
+    Ok(diagnostic_report(diagnostics, params.previous_result_id.as_deref()))
+}
+
+/// Reports the `cargo check` diagnostics of the files which aren't open, the
+/// open ones are pulled individually. Computing the native diagnostics of every
+/// file of the workspace would be too slow.
+pub(crate) fn handle_workspace_diagnostic(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::WorkspaceDiagnosticParams,
+) -> Result<lsp_ext::WorkspaceDiagnosticReport> {
+    let _p = profile::span("handle_workspace_diagnostic");
+    let previous_result_id = |uri: &Url| {
+        params.previous_result_ids.iter().find(|it| it.uri == *uri).map(|it| it.value.as_str())
+    };
+
+    let mut items = Vec::new();
+    for (&file_id, diagnostics) in snap.check_diagnostics.iter() {
+        let uri = snap.file_id_to_url(file_id);
+        if snap.url_file_version(&uri).is_some() {
+            continue;
+        }
+        let report = diagnostic_report(diagnostics.clone(), previous_result_id(&uri));
+        items.push(lsp_ext::WorkspaceDocumentDiagnosticReport { uri, version: None, report });
+    }
+    // Clear the diagnostics of the files which don't have any anymore.
+    for previous in &params.previous_result_ids {
+        let is_reported = items.iter().any(|it| it.uri == previous.uri);
+        if !is_reported && snap.url_file_version(&previous.uri).is_none() {
+            let report = diagnostic_report(Vec::new(), Some(&previous.value));
+            items.push(lsp_ext::WorkspaceDocumentDiagnosticReport {
+                uri: previous.uri.clone(),
+                version: None,
+                report,
+            });
+        }
+    }
+    Ok(lsp_ext::WorkspaceDiagnosticReport { items })
+}
+
+/// Result ids are hashes of the diagnostics, so that the client can be told
+/// they didn't change without keeping the reported ones around.
+fn diagnostic_report(
+    diagnostics: Vec<Diagnostic>,
+    previous_result_id: Option<&str>,
+) -> lsp_ext::DocumentDiagnosticReport {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&diagnostics).unwrap().hash(&mut hasher);
+    let result_id = format!("{:x}", hasher.finish());
+    if previous_result_id == Some(result_id.as_str()) {
+        lsp_ext::DocumentDiagnosticReport::Unchanged { result_id }
+    } else {
+        lsp_ext::DocumentDiagnosticReport::Full { result_id, items: diagnostics }
+    }
+}
+
 pub(crate) fn handle_inlay_hints(
     snap: GlobalStateSnapshot,
     params: InlayHintsParams,
@@ -1291,10 +1543,84 @@ pub(crate) fn handle_call_hierarchy_prepare(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_moniker(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::MonikerParams,
+) -> Result<Option<Vec<lsp_types::Moniker>>> {
+    let _p = profile::span("handle_moniker");
+    let position = from_proto::file_position(&snap, params.text_document_position_params)?;
+    let monikers = match snap.analysis.moniker(position)? {
+        None => return Ok(None),
+        Some(it) => it.info,
+    };
+    Ok(Some(monikers.into_iter().map(to_proto::moniker).collect()))
+}
+
+pub(crate) fn handle_prepare_type_hierarchy(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_prepare_type_hierarchy");
+    let position = from_proto::file_position(&snap, params)?;
+
+    let RangeInfo { range: _, info: navs } = match snap.analysis.type_hierarchy(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::call_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_supertypes(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TypeHierarchyParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_supertypes");
+    let position = type_hierarchy_item_position(&snap, params.item)?;
+    let navs = match snap.analysis.supertypes(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::call_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_type_hierarchy_subtypes(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TypeHierarchyParams,
+) -> Result<Option<Vec<lsp_ext::TypeHierarchyItem>>> {
+    let _p = profile::span("handle_type_hierarchy_subtypes");
+    let position = type_hierarchy_item_position(&snap, params.item)?;
+    let navs = match snap.analysis.subtypes(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let res = navs
+        .into_iter()
+        .map(|it| to_proto::call_hierarchy_item(&snap, it))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(res))
+}
+
+fn type_hierarchy_item_position(
+    snap: &GlobalStateSnapshot,
+    item: lsp_ext::TypeHierarchyItem,
+) -> Result<FilePosition> {
+    let doc = TextDocumentIdentifier::new(item.uri);
+    let frange = from_proto::file_range(snap, doc, item.selection_range)?;
+    Ok(FilePosition { file_id: frange.file_id, offset: frange.range.start() })
+}
+
 pub(crate) fn handle_call_hierarchy_incoming(
     snap: GlobalStateSnapshot,
     params: CallHierarchyIncomingCallsParams,
-) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+) -> Result<Option<Vec<lsp_ext::CallHierarchyIncomingCall>>> {
     let _p = profile::span("handle_call_hierarchy_incoming");
     let item = params.item;
 
@@ -1302,7 +1628,8 @@ pub(crate) fn handle_call_hierarchy_incoming(
     let frange = from_proto::file_range(&snap, doc, item.selection_range)?;
     let fpos = FilePosition { file_id: frange.file_id, offset: frange.range.start() };
 
-    let call_items = match snap.analysis.incoming_calls(fpos)? {
+    let potential_calls = snap.config.call_hierarchy_potential_calls();
+    let call_items = match snap.analysis.incoming_calls(fpos, potential_calls)? {
         None => return Ok(None),
         Some(it) => it,
     };
@@ -1313,13 +1640,16 @@ pub(crate) fn handle_call_hierarchy_incoming(
         let file_id = call_item.target.file_id;
         let line_index = snap.file_line_index(file_id)?;
         let item = to_proto::call_hierarchy_item(&snap, call_item.target)?;
-        res.push(CallHierarchyIncomingCall {
-            from: item,
-            from_ranges: call_item
-                .ranges
-                .into_iter()
-                .map(|it| to_proto::range(&line_index, it))
-                .collect(),
+        res.push(lsp_ext::CallHierarchyIncomingCall {
+            call: CallHierarchyIncomingCall {
+                from: item,
+                from_ranges: call_item
+                    .ranges
+                    .into_iter()
+                    .map(|it| to_proto::range(&line_index, it))
+                    .collect(),
+            },
+            potential: call_item.potential,
         });
     }
 
@@ -1460,6 +1790,65 @@ pub(crate) fn handle_open_cargo_toml(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_cargo_toml_completion(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::CargoTomlCompletionParams,
+) -> Result<Vec<CompletionItem>> {
+    let _p = profile::span("handle_cargo_toml_completion");
+    let (text, endings) = LineEndings::normalize(params.text);
+    let line_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(&text)),
+        endings,
+        encoding: snap.config.offset_encoding(),
+    };
+    let offset = from_proto::offset(&line_index, params.text_document_position.position);
+    let (completion, prefix) = match cargo_toml::completion_at(&text, offset.into()) {
+        Some(it) => it,
+        None => return Ok(Vec::new()),
+    };
+
+    let (labels, kind) = match completion {
+        ManifestCompletion::CrateName => {
+            let mut names = snap.registry_crates().keys().cloned().collect::<Vec<_>>();
+            names.sort();
+            (names, lsp_types::CompletionItemKind::Module)
+        }
+        ManifestCompletion::Version { krate } => {
+            let versions = snap.registry_crates().get(krate).cloned().unwrap_or_default();
+            let versions = versions.iter().map(ToString::to_string).collect();
+            (versions, lsp_types::CompletionItemKind::Value)
+        }
+        ManifestCompletion::Feature { krate } => {
+            let mut features = snap
+                .workspaces
+                .iter()
+                .filter_map(|ws| match ws {
+                    ProjectWorkspace::Cargo { cargo, .. } => Some(cargo),
+                    _ => None,
+                })
+                .flat_map(|cargo| cargo.packages().map(move |pkg| &cargo[pkg]))
+                .filter(|pkg| pkg.name == krate)
+                .flat_map(|pkg| pkg.features.keys().cloned())
+                .collect::<Vec<_>>();
+            features.sort();
+            features.dedup();
+            (features, lsp_types::CompletionItemKind::Property)
+        }
+    };
+
+    let range = to_proto::range(&line_index, TextRange::new(offset - TextSize::of(prefix), offset));
+    let res = labels
+        .into_iter()
+        .map(|label| CompletionItem {
+            text_edit: Some(lsp_types::TextEdit::new(range, label.clone()).into()),
+            label,
+            kind: Some(kind),
+            ..Default::default()
+        })
+        .collect();
+    Ok(res)
+}
+
 pub(crate) fn handle_move_item(
     snap: GlobalStateSnapshot,
     params: lsp_ext::MoveItemParams,
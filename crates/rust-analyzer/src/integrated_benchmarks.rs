@@ -134,6 +134,7 @@ fn integrated_completion_benchmark() {
         let config = CompletionConfig {
             enable_postfix_completions: true,
             enable_imports_on_the_fly: true,
+            enable_trait_methods_on_the_fly: true,
             enable_self_on_the_fly: true,
             add_call_parenthesis: true,
             add_call_argument_snippets: true,
@@ -145,6 +146,8 @@ fn integrated_completion_benchmark() {
                 group: true,
                 skip_glob_imports: true,
             },
+            postfix_snippets: Vec::new(),
+            additional_lints: Vec::new(),
         };
         let position =
             FilePosition { file_id, offset: TextSize::try_from(completion_offset).unwrap() };
@@ -170,6 +173,7 @@ fn integrated_completion_benchmark() {
         let config = CompletionConfig {
             enable_postfix_completions: true,
             enable_imports_on_the_fly: true,
+            enable_trait_methods_on_the_fly: true,
             enable_self_on_the_fly: true,
             add_call_parenthesis: true,
             add_call_argument_snippets: true,
@@ -181,6 +185,8 @@ fn integrated_completion_benchmark() {
                 group: true,
                 skip_glob_imports: true,
             },
+            postfix_snippets: Vec::new(),
+            additional_lints: Vec::new(),
         };
         let position =
             FilePosition { file_id, offset: TextSize::try_from(completion_offset).unwrap() };
@@ -24,6 +24,7 @@ mod dispatch;
 mod handlers;
 mod caps;
 mod cargo_target_spec;
+mod cargo_toml;
 mod to_proto;
 mod from_proto;
 mod semantic_tokens;
@@ -45,7 +46,10 @@ mod integrated_benchmarks;
 use serde::de::DeserializeOwned;
 use std::fmt;
 
-pub use crate::{caps::server_capabilities, main_loop::main_loop};
+pub use crate::{
+    caps::{extra_server_capabilities, server_capabilities},
+    main_loop::main_loop,
+};
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -27,10 +27,38 @@ pub enum MemoryUsage {}
 
 impl Request for MemoryUsage {
     type Params = ();
-    type Result = String;
+    type Result = MemoryUsageResult;
     const METHOD: &'static str = "rust-analyzer/memoryUsage";
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsageResult {
+    /// Memory freed by clearing each query, largest first.
+    pub queries: Vec<QueryMemoryUsage>,
+    /// Memory still allocated after clearing the database.
+    pub remaining: isize,
+    /// Size of the source text of the crates, largest first.
+    pub sources: Vec<SourceTextSize>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMemoryUsage {
+    pub name: String,
+    pub bytes: isize,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceTextSize {
+    /// The crates sharing the source root.
+    pub crates: Vec<String>,
+    pub files: usize,
+    /// Length of the text of the files in bytes, not the memory they use.
+    pub text_len: usize,
+}
+
 pub enum ReloadWorkspace {}
 
 impl Request for ReloadWorkspace {
@@ -39,6 +67,20 @@ impl Request for ReloadWorkspace {
     const METHOD: &'static str = "rust-analyzer/reloadWorkspace";
 }
 
+pub enum ReloadPackage {}
+
+impl Request for ReloadPackage {
+    type Params = ReloadPackageParams;
+    type Result = ();
+    const METHOD: &'static str = "rust-analyzer/reloadPackage";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadPackageParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
 pub enum SyntaxTree {}
 
 impl Request for SyntaxTree {
@@ -62,18 +104,75 @@ impl Request for ViewHir {
     const METHOD: &'static str = "rust-analyzer/viewHir";
 }
 
+pub enum InterpretFunction {}
+
+impl Request for InterpretFunction {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = String;
+    const METHOD: &'static str = "rust-analyzer/interpretFunction";
+}
+
+pub enum ViewMemoryLayout {}
+
+impl Request for ViewMemoryLayout {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = Option<MemoryLayoutNode>;
+    const METHOD: &'static str = "rust-analyzer/viewMemoryLayout";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryLayoutNode {
+    /// The name of the field, or of the item the type belongs to for the root.
+    pub item_name: String,
+    pub typename: String,
+    pub size: u64,
+    pub alignment: u64,
+    /// The offset in the parent.
+    pub offset: u64,
+    /// The number of padding bytes after the field.
+    pub padding: u64,
+    /// The fields, in the order they are placed in memory.
+    pub children: Vec<MemoryLayoutNode>,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ViewCrateGraphParams {
     /// Include *all* crates, not just crates in the workspace.
     pub full: bool,
+    /// Only include crates with names matching this regular expression.
+    pub filter: Option<String>,
+    /// Collapse chains of crates with a single dependent and a single
+    /// dependency into one edge.
+    #[serde(default)]
+    pub collapse_chains: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateGraph {
+    /// The graph in GraphViz "dot" syntax.
+    pub dot: String,
+    /// The graph rendered as SVG, if `dot` is available.
+    pub svg: Option<String>,
+    pub crates: Vec<CrateGraphNode>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateGraphNode {
+    pub id: u32,
+    pub name: String,
+    pub is_workspace_member: bool,
+    pub dependencies: Vec<u32>,
 }
 
 pub enum ViewCrateGraph {}
 
 impl Request for ViewCrateGraph {
     type Params = ViewCrateGraphParams;
-    type Result = String;
+    type Result = CrateGraph;
     const METHOD: &'static str = "rust-analyzer/viewCrateGraph";
 }
 
@@ -104,6 +203,9 @@ impl Request for ExpandMacro {
 pub struct ExpandMacroParams {
     pub text_document: TextDocumentIdentifier,
     pub position: Position,
+    /// How many levels of nested macro calls to expand, `1` only expanding
+    /// the macro at `position`. All of them are expanded if absent.
+    pub depth: Option<u32>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -111,6 +213,17 @@ pub struct ExpandMacroParams {
 pub struct ExpandedMacro {
     pub name: String,
     pub expansion: String,
+    pub origins: Vec<ExpansionOrigin>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpansionOrigin {
+    /// A range of `expansion`.
+    pub expansion_range: Range,
+    /// The range of the macro call input it was copied from, in the document
+    /// of the request.
+    pub original_range: Range,
 }
 
 pub enum MatchingBrace {}
@@ -159,6 +272,14 @@ impl Request for OnEnter {
     const METHOD: &'static str = "experimental/onEnter";
 }
 
+pub enum OnTypeFormatting {}
+
+impl Request for OnTypeFormatting {
+    type Params = lsp_types::DocumentOnTypeFormattingParams;
+    type Result = Option<Vec<SnippetTextEdit>>;
+    const METHOD: &'static str = "textDocument/onTypeFormatting";
+}
+
 pub enum Runnables {}
 
 impl Request for Runnables {
@@ -220,6 +341,79 @@ pub struct TestInfo {
     pub runnable: Runnable,
 }
 
+pub enum TestRun {}
+
+impl Request for TestRun {
+    type Params = TestRunParams;
+    type Result = TestRunResult;
+    const METHOD: &'static str = "rust-analyzer/testRun";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRunParams {
+    /// A `cargo test` runnable, as returned by `experimental/runnables`.
+    pub runnable: Runnable,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRunResult {
+    pub run_id: usize,
+}
+
+pub enum CancelTestRun {}
+
+impl Request for CancelTestRun {
+    type Params = CancelTestRunParams;
+    type Result = ();
+    const METHOD: &'static str = "rust-analyzer/cancelTestRun";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelTestRunParams {
+    pub run_id: usize,
+}
+
+pub enum TestRunEvent {}
+
+impl Notification for TestRunEvent {
+    type Params = TestRunEventParams;
+    const METHOD: &'static str = "rust-analyzer/testRunEvent";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRunEventParams {
+    pub run_id: usize,
+    #[serde(flatten)]
+    pub kind: TestRunEventKind,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TestRunEventKind {
+    Passed {
+        name: String,
+    },
+    Failed {
+        name: String,
+    },
+    Ignored {
+        name: String,
+    },
+    /// The captured output of a failed test.
+    Output {
+        name: String,
+        output: String,
+    },
+    /// The run is over, no more events are sent for it.
+    Finished {
+        error: Option<String>,
+    },
+}
+
 pub enum InlayHints {}
 
 impl Request for InlayHints {
@@ -239,6 +433,8 @@ pub enum InlayKind {
     TypeHint,
     ParameterHint,
     ChainingHint,
+    ImplicitDropHint,
+    LifetimeHint,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -269,6 +465,42 @@ pub struct SsrParams {
 
     /// Current selections. Search/replace will be restricted to these if non-empty.
     pub selections: Vec<lsp_types::Range>,
+
+    /// Files which are left unchanged, e.g. after being opted out in a preview.
+    #[serde(default)]
+    pub excluded_files: Vec<lsp_types::TextDocumentIdentifier>,
+}
+
+pub enum SsrPreview {}
+
+impl Request for SsrPreview {
+    type Params = SsrParams;
+    type Result = SsrPreviewResult;
+    const METHOD: &'static str = "experimental/ssrPreview";
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrPreviewResult {
+    pub rules: Vec<SsrRuleSummary>,
+    pub matches: Vec<SsrPreviewEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrRuleSummary {
+    pub rule: String,
+    pub matches: usize,
+    pub files: usize,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SsrPreviewEntry {
+    pub text_document: lsp_types::TextDocumentIdentifier,
+    pub range: lsp_types::Range,
+    pub before: String,
+    pub after: String,
 }
 
 pub enum ServerStatusNotification {}
@@ -405,6 +637,48 @@ pub struct CommandLink {
     pub tooltip: Option<String>,
 }
 
+pub enum References {}
+
+impl Request for References {
+    type Params = lsp_types::ReferenceParams;
+    type Result = Option<Vec<ReferenceLocation>>;
+    const METHOD: &'static str = "textDocument/references";
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct ReferenceLocation {
+    #[serde(flatten)]
+    pub location: lsp_types::Location,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<ReferenceCategory>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceCategory {
+    Read,
+    Write,
+    Call,
+    Import,
+    TraitImpl,
+}
+
+pub enum CallHierarchyIncomingCalls {}
+
+impl Request for CallHierarchyIncomingCalls {
+    type Params = lsp_types::CallHierarchyIncomingCallsParams;
+    type Result = Option<Vec<CallHierarchyIncomingCall>>;
+    const METHOD: &'static str = "callHierarchy/incomingCalls";
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct CallHierarchyIncomingCall {
+    #[serde(flatten)]
+    pub call: lsp_types::CallHierarchyIncomingCall,
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub potential: bool,
+}
+
 pub enum ExternalDocs {}
 
 impl Request for ExternalDocs {
@@ -427,6 +701,23 @@ pub struct OpenCargoTomlParams {
     pub text_document: TextDocumentIdentifier,
 }
 
+pub enum CargoTomlCompletion {}
+
+impl Request for CargoTomlCompletion {
+    type Params = CargoTomlCompletionParams;
+    type Result = Vec<lsp_types::CompletionItem>;
+    const METHOD: &'static str = "experimental/cargoTomlCompletion";
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CargoTomlCompletionParams {
+    #[serde(flatten)]
+    pub text_document_position: lsp_types::TextDocumentPositionParams,
+    /// The text of the manifest, which the server doesn't track.
+    pub text: String,
+}
+
 /// Information about CodeLens, that is to be resolved.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -506,3 +797,135 @@ pub struct CompletionResolveData {
     pub full_import_path: String,
     pub imported_name: String,
 }
+
+// Pull diagnostics, proposed for LSP 3.17. lsp-types doesn't support them yet.
+
+pub enum DocumentDiagnosticRequest {}
+
+impl Request for DocumentDiagnosticRequest {
+    type Params = DocumentDiagnosticParams;
+    type Result = DocumentDiagnosticReport;
+    const METHOD: &'static str = "textDocument/diagnostic";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    pub text_document: TextDocumentIdentifier,
+    pub identifier: Option<String>,
+    pub previous_result_id: Option<String>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentDiagnosticReport {
+    Full {
+        #[serde(rename = "resultId")]
+        result_id: String,
+        items: Vec<lsp_types::Diagnostic>,
+    },
+    Unchanged {
+        #[serde(rename = "resultId")]
+        result_id: String,
+    },
+}
+
+pub enum WorkspaceDiagnosticRequest {}
+
+impl Request for WorkspaceDiagnosticRequest {
+    type Params = WorkspaceDiagnosticParams;
+    type Result = WorkspaceDiagnosticReport;
+    const METHOD: &'static str = "workspace/diagnostic";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticParams {
+    pub identifier: Option<String>,
+    pub previous_result_ids: Vec<PreviousResultId>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousResultId {
+    pub uri: lsp_types::Url,
+    pub value: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDiagnosticReport {
+    pub items: Vec<WorkspaceDocumentDiagnosticReport>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceDocumentDiagnosticReport {
+    pub uri: lsp_types::Url,
+    pub version: Option<i32>,
+    #[serde(flatten)]
+    pub report: DocumentDiagnosticReport,
+}
+
+pub enum WorkspaceDiagnosticRefresh {}
+
+impl Request for WorkspaceDiagnosticRefresh {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticOptions {
+    pub identifier: Option<String>,
+    pub inter_file_dependencies: bool,
+    pub workspace_diagnostics: bool,
+}
+
+// Type hierarchy, proposed for LSP 3.17. lsp-types doesn't support it yet.
+
+/// Type hierarchy items have the same fields as call hierarchy ones.
+pub type TypeHierarchyItem = lsp_types::CallHierarchyItem;
+
+pub enum PrepareTypeHierarchy {}
+
+impl Request for PrepareTypeHierarchy {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "textDocument/prepareTypeHierarchy";
+}
+
+pub enum TypeHierarchySupertypes {}
+
+impl Request for TypeHierarchySupertypes {
+    type Params = TypeHierarchyParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/supertypes";
+}
+
+pub enum TypeHierarchySubtypes {}
+
+impl Request for TypeHierarchySubtypes {
+    type Params = TypeHierarchyParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/subtypes";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
@@ -1,7 +1,7 @@
 //! Utilities for LSP-related boilerplate code.
 use std::{error::Error, ops::Range, sync::Arc};
 
-use ide_db::base_db::Cancelled;
+use ide::Cancelled;
 use lsp_server::Notification;
 
 use crate::{
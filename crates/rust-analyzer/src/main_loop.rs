@@ -1,7 +1,7 @@
 //! The main loop of `rust-analyzer` responsible for dispatching LSP
 //! requests/replies and notifications back to the client.
 use std::{
-    env, fmt,
+    env, fmt, iter,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -12,7 +12,7 @@ use ide::{FileId, PrimeCachesProgress};
 use ide_db::base_db::VfsPath;
 use lsp_server::{Connection, Notification, Request, Response};
 use lsp_types::notification::Notification as _;
-use project_model::BuildDataCollector;
+use project_model::{BuildDataCollector, ProjectManifest};
 use vfs::ChangeKind;
 
 use crate::{
@@ -24,7 +24,7 @@ use crate::{
     handlers, lsp_ext,
     lsp_utils::{apply_document_changes, is_cancelled, notification_is, Progress},
     reload::{BuildDataProgress, ProjectWorkspaceProgress},
-    Result,
+    to_proto, Result,
 };
 
 pub fn main_loop(config: Config, connection: Connection) -> Result<()> {
@@ -57,6 +57,7 @@ enum Event {
     Task(Task),
     Vfs(vfs::loader::Message),
     Flycheck(flycheck::Message),
+    TestRun(flycheck::TestRunMessage),
 }
 
 #[derive(Debug)]
@@ -66,6 +67,7 @@ pub(crate) enum Task {
     PrimeCaches(PrimeCachesProgress),
     FetchWorkspace(ProjectWorkspaceProgress),
     FetchBuildData(BuildDataProgress),
+    DiscoverProjects(Vec<ProjectManifest>),
 }
 
 impl fmt::Debug for Event {
@@ -96,13 +98,14 @@ impl fmt::Debug for Event {
             Event::Task(it) => fmt::Debug::fmt(it, f),
             Event::Vfs(it) => fmt::Debug::fmt(it, f),
             Event::Flycheck(it) => fmt::Debug::fmt(it, f),
+            Event::TestRun(it) => fmt::Debug::fmt(it, f),
         }
     }
 }
 
 impl GlobalState {
     fn run(mut self, inbox: Receiver<lsp_server::Message>) -> Result<()> {
-        if self.config.linked_projects().is_empty()
+        if self.linked_projects().is_empty()
             && self.config.detached_files().is_empty()
             && self.config.notifications().cargo_toml_not_found
         {
@@ -147,6 +150,10 @@ impl GlobalState {
             );
         }
 
+        if self.config.workspace_roots.len() > 1 && self.config.workspace_configuration() {
+            self.fetch_configuration();
+        }
+
         self.fetch_workspaces_request();
         self.fetch_workspaces_if_needed();
 
@@ -175,6 +182,9 @@ impl GlobalState {
 
             recv(self.flycheck_receiver) -> task =>
                 Some(Event::Flycheck(task.unwrap())),
+
+            recv(self.test_run_receiver) -> task =>
+                Some(Event::TestRun(task.unwrap())),
         }
     }
 
@@ -190,6 +200,7 @@ impl GlobalState {
         }
 
         let was_quiescent = self.is_quiescent();
+        let mut switched_to_partial_workspaces = false;
         match event {
             Event::Lsp(msg) => match msg {
                 lsp_server::Message::Request(req) => self.on_request(loop_start, req)?,
@@ -200,6 +211,7 @@ impl GlobalState {
             },
             Event::Task(mut task) => {
                 let _p = profile::span("GlobalState::handle_event/task");
+                let mut loaded_partial_workspaces = false;
                 let mut prime_caches_progress = Vec::new();
                 loop {
                     match task {
@@ -228,14 +240,27 @@ impl GlobalState {
                                 ProjectWorkspaceProgress::Report(msg) => {
                                     (Progress::Report, Some(msg))
                                 }
+                                ProjectWorkspaceProgress::Loaded(workspace) => {
+                                    // Switched to once all the workspaces loaded
+                                    // by now are handled, below.
+                                    loaded_partial_workspaces |=
+                                        self.fetch_workspaces_loaded_one(workspace);
+                                    (Progress::Report, None)
+                                }
                                 ProjectWorkspaceProgress::End(workspaces) => {
+                                    // Build data was not requested for the
+                                    // workspaces switched to while fetching.
+                                    let loaded_partially =
+                                        !self.partially_fetched_workspaces.is_empty();
                                     self.fetch_workspaces_completed(workspaces);
 
                                     let old = Arc::clone(&self.workspaces);
                                     self.switch_workspaces();
                                     let workspaces_updated = !Arc::ptr_eq(&old, &self.workspaces);
 
-                                    if self.config.run_build_scripts() && workspaces_updated {
+                                    if self.config.run_build_scripts()
+                                        && (workspaces_updated || loaded_partially)
+                                    {
                                         let mut collector =
                                             BuildDataCollector::new(self.config.wrap_rustc());
                                         for ws in self.workspaces.iter() {
@@ -250,6 +275,7 @@ impl GlobalState {
 
                             self.report_progress("Fetching", state, msg, None);
                         }
+                        Task::DiscoverProjects(projects) => self.add_discovered_projects(projects),
                         Task::FetchBuildData(progress) => {
                             let (state, msg) = match progress {
                                 BuildDataProgress::Begin => (Some(Progress::Begin), None),
@@ -278,6 +304,13 @@ impl GlobalState {
                     };
                 }
 
+                // The crate graph is built once for all the workspaces loaded
+                // during this turn, unless the fetch has already completed.
+                if loaded_partial_workspaces && !self.partially_fetched_workspaces.is_empty() {
+                    self.switch_workspaces();
+                    switched_to_partial_workspaces = true;
+                }
+
                 for progress in prime_caches_progress {
                     let (state, message, fraction);
                     match progress {
@@ -316,6 +349,13 @@ impl GlobalState {
                                 }
                             }
                         }
+                        // Sent by the server-side file watcher, handled like the client's
+                        // `workspace/didChangeWatchedFiles`.
+                        vfs::loader::Message::Changed { files } => {
+                            for path in files {
+                                self.loader.handle.invalidate(path);
+                            }
+                        }
                         vfs::loader::Message::Progress { n_total, n_done, config_version } => {
                             always!(config_version <= self.vfs_config_version);
 
@@ -409,6 +449,47 @@ impl GlobalState {
                     }
                 }
             }
+            Event::TestRun(message) => {
+                let _p = profile::span("GlobalState::handle_event/test_run");
+                let (run_id, kind) = match message {
+                    flycheck::TestRunMessage::Event { id, event } => {
+                        let kind = match event {
+                            flycheck::TestEvent::Finished { name, outcome } => match outcome {
+                                flycheck::TestOutcome::Passed => {
+                                    lsp_ext::TestRunEventKind::Passed { name }
+                                }
+                                flycheck::TestOutcome::Failed => {
+                                    lsp_ext::TestRunEventKind::Failed { name }
+                                }
+                                flycheck::TestOutcome::Ignored => {
+                                    lsp_ext::TestRunEventKind::Ignored { name }
+                                }
+                            },
+                            flycheck::TestEvent::Output { name, output } => {
+                                lsp_ext::TestRunEventKind::Output { name, output }
+                            }
+                        };
+                        (id, kind)
+                    }
+                    flycheck::TestRunMessage::Finished { id, result } => {
+                        let error = result.err().map(|err| err.to_string());
+                        (id, lsp_ext::TestRunEventKind::Finished { error })
+                    }
+                };
+                // Runs cancelled by the client are not reported anymore.
+                let is_running = match kind {
+                    lsp_ext::TestRunEventKind::Finished { .. } => {
+                        self.test_runs.remove(&run_id).is_some()
+                    }
+                    _ => self.test_runs.contains_key(&run_id),
+                };
+                if is_running {
+                    self.send_notification::<lsp_ext::TestRunEvent>(lsp_ext::TestRunEventParams {
+                        run_id,
+                        kind,
+                    });
+                }
+            }
         }
 
         let state_changed = self.process_changes();
@@ -432,19 +513,33 @@ impl GlobalState {
             if self.config.code_lens_refresh() {
                 self.send_request::<lsp_types::request::CodeLensRefresh>((), |_, _| ());
             }
+        } else if (state_changed || switched_to_partial_workspaces)
+            && !self.partially_fetched_workspaces.is_empty()
+        {
+            // The initial load is still in progress, but files in the crates
+            // loaded so far can already be checked.
+            self.update_file_notifications_on_threadpool();
         }
 
         if let Some(diagnostic_changes) = self.diagnostics.take_changes() {
-            for file_id in diagnostic_changes {
-                let url = file_id_to_url(&self.vfs.read().0, file_id);
-                let diagnostics = self.diagnostics.diagnostics_for(file_id).cloned().collect();
-                let version = from_proto::vfs_path(&url)
-                    .map(|path| self.mem_docs.get(&path).map(|it| it.version))
-                    .unwrap_or_default();
-
-                self.send_notification::<lsp_types::notification::PublishDiagnostics>(
-                    lsp_types::PublishDiagnosticsParams { uri: url, diagnostics, version },
-                );
+            if self.config.pull_diagnostics() {
+                // Native diagnostics are computed when they are pulled, so the
+                // changes are `cargo check` results the client has to pull.
+                if self.config.diagnostics_refresh() {
+                    self.send_request::<lsp_ext::WorkspaceDiagnosticRefresh>((), |_, _| ());
+                }
+            } else {
+                for file_id in diagnostic_changes {
+                    let url = file_id_to_url(&self.vfs.read().0, file_id);
+                    let diagnostics = self.diagnostics.diagnostics_for(file_id).cloned().collect();
+                    let version = from_proto::vfs_path(&url)
+                        .map(|path| self.mem_docs.get(&path).map(|it| it.version))
+                        .unwrap_or_default();
+
+                    self.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                        lsp_types::PublishDiagnosticsParams { uri: url, diagnostics, version },
+                    );
+                }
             }
         }
 
@@ -452,6 +547,9 @@ impl GlobalState {
             self.fetch_workspaces_if_needed();
         }
         self.fetch_build_data_if_needed();
+        if self.is_quiescent() {
+            self.save_symbol_cache(false);
+        }
 
         self.report_new_status_if_needed();
 
@@ -498,10 +596,17 @@ impl GlobalState {
                 s.fetch_workspaces_if_needed();
                 Ok(())
             })?
+            .on_sync::<lsp_ext::ReloadPackage>(|s, p| {
+                let path = from_proto::abs_path(&p.text_document.uri)?;
+                s.fetch_workspace_for_request(&path);
+                s.fetch_workspaces_if_needed();
+                Ok(())
+            })?
             .on_sync::<lsp_ext::JoinLines>(|s, p| handlers::handle_join_lines(s.snapshot(), p))?
             .on_sync::<lsp_ext::OnEnter>(|s, p| handlers::handle_on_enter(s.snapshot(), p))?
             .on_sync::<lsp_types::request::Shutdown>(|s, ()| {
                 s.shutdown_requested = true;
+                s.save_symbol_cache(true);
                 Ok(())
             })?
             .on_sync::<lsp_types::request::SelectionRangeRequest>(|s, p| {
@@ -511,9 +616,13 @@ impl GlobalState {
                 handlers::handle_matching_brace(s.snapshot(), p)
             })?
             .on_sync::<lsp_ext::MemoryUsage>(|s, p| handlers::handle_memory_usage(s, p))?
+            .on_sync::<lsp_ext::TestRun>(handlers::handle_test_run)?
+            .on_sync::<lsp_ext::CancelTestRun>(handlers::handle_cancel_test_run)?
             .on::<lsp_ext::AnalyzerStatus>(handlers::handle_analyzer_status)
             .on::<lsp_ext::SyntaxTree>(handlers::handle_syntax_tree)
             .on::<lsp_ext::ViewHir>(handlers::handle_view_hir)
+            .on::<lsp_ext::InterpretFunction>(handlers::handle_interpret_function)
+            .on::<lsp_ext::ViewMemoryLayout>(handlers::handle_view_memory_layout)
             .on::<lsp_ext::ViewCrateGraph>(handlers::handle_view_crate_graph)
             .on::<lsp_ext::ViewItemTree>(handlers::handle_view_item_tree)
             .on::<lsp_ext::ExpandMacro>(handlers::handle_expand_macro)
@@ -526,9 +635,10 @@ impl GlobalState {
             .on::<lsp_ext::HoverRequest>(handlers::handle_hover)
             .on::<lsp_ext::ExternalDocs>(handlers::handle_open_docs)
             .on::<lsp_ext::OpenCargoToml>(handlers::handle_open_cargo_toml)
+            .on::<lsp_ext::CargoTomlCompletion>(handlers::handle_cargo_toml_completion)
             .on::<lsp_ext::MoveItem>(handlers::handle_move_item)
             .on::<lsp_ext::WorkspaceSymbol>(handlers::handle_workspace_symbol)
-            .on::<lsp_types::request::OnTypeFormatting>(handlers::handle_on_type_formatting)
+            .on::<lsp_ext::OnTypeFormatting>(handlers::handle_on_type_formatting)
             .on::<lsp_types::request::DocumentSymbolRequest>(handlers::handle_document_symbol)
             .on::<lsp_types::request::GotoDefinition>(handlers::handle_goto_definition)
             .on::<lsp_types::request::GotoDeclaration>(handlers::handle_goto_declaration)
@@ -542,14 +652,18 @@ impl GlobalState {
             .on::<lsp_types::request::SignatureHelpRequest>(handlers::handle_signature_help)
             .on::<lsp_types::request::PrepareRenameRequest>(handlers::handle_prepare_rename)
             .on::<lsp_types::request::Rename>(handlers::handle_rename)
-            .on::<lsp_types::request::References>(handlers::handle_references)
+            .on::<lsp_ext::References>(handlers::handle_references)
             .on::<lsp_types::request::Formatting>(handlers::handle_formatting)
             .on::<lsp_types::request::RangeFormatting>(handlers::handle_range_formatting)
             .on::<lsp_types::request::DocumentHighlightRequest>(handlers::handle_document_highlight)
+            .on::<lsp_types::request::MonikerRequest>(handlers::handle_moniker)
+            .on::<lsp_ext::PrepareTypeHierarchy>(handlers::handle_prepare_type_hierarchy)
+            .on::<lsp_ext::TypeHierarchySupertypes>(handlers::handle_type_hierarchy_supertypes)
+            .on::<lsp_ext::TypeHierarchySubtypes>(handlers::handle_type_hierarchy_subtypes)
+            .on::<lsp_ext::DocumentDiagnosticRequest>(handlers::handle_document_diagnostic)
+            .on::<lsp_ext::WorkspaceDiagnosticRequest>(handlers::handle_workspace_diagnostic)
             .on::<lsp_types::request::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)
-            .on::<lsp_types::request::CallHierarchyIncomingCalls>(
-                handlers::handle_call_hierarchy_incoming,
-            )
+            .on::<lsp_ext::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)
             .on::<lsp_types::request::CallHierarchyOutgoingCalls>(
                 handlers::handle_call_hierarchy_outgoing,
             )
@@ -564,6 +678,7 @@ impl GlobalState {
             )
             .on::<lsp_types::request::WillRenameFiles>(handlers::handle_will_rename_files)
             .on::<lsp_ext::Ssr>(handlers::handle_ssr)
+            .on::<lsp_ext::SsrPreview>(handlers::handle_ssr_preview)
             .finish();
         Ok(())
     }
@@ -650,13 +765,15 @@ impl GlobalState {
                 // Clear the diagnostics for the previously known version of the file.
                 // This prevents stale "cargo check" diagnostics if the file is
                 // closed, "cargo check" is run and then the file is reopened.
-                this.send_notification::<lsp_types::notification::PublishDiagnostics>(
-                    lsp_types::PublishDiagnosticsParams {
-                        uri: params.text_document.uri,
-                        diagnostics: Vec::new(),
-                        version,
-                    },
-                );
+                if !this.config.pull_diagnostics() {
+                    this.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                        lsp_types::PublishDiagnosticsParams {
+                            uri: params.text_document.uri,
+                            diagnostics: Vec::new(),
+                            version,
+                        },
+                    );
+                }
                 Ok(())
             })?
             .on::<lsp_types::notification::DidSaveTextDocument>(|this, params| {
@@ -671,37 +788,11 @@ impl GlobalState {
             .on::<lsp_types::notification::DidChangeConfiguration>(|this, _params| {
                 // As stated in https://github.com/microsoft/language-server-protocol/issues/676,
                 // this notification's parameters should be ignored and the actual config queried separately.
-                this.send_request::<lsp_types::request::WorkspaceConfiguration>(
-                    lsp_types::ConfigurationParams {
-                        items: vec![lsp_types::ConfigurationItem {
-                            scope_uri: None,
-                            section: Some("rust-analyzer".to_string()),
-                        }],
-                    },
-                    |this, resp| {
-                        log::debug!("config update response: '{:?}", resp);
-                        let Response { error, result, .. } = resp;
-
-                        match (error, result) {
-                            (Some(err), _) => {
-                                log::error!("failed to fetch the server settings: {:?}", err)
-                            }
-                            (None, Some(mut configs)) => {
-                                if let Some(json) = configs.get_mut(0) {
-                                    // Note that json can be null according to the spec if the client can't
-                                    // provide a configuration. This is handled in Config::update below.
-                                    let mut config = Config::clone(&*this.config);
-                                    config.update(json.take());
-                                    this.update_configuration(config);
-                                }
-                            }
-                            (None, None) => log::error!(
-                                "received empty server settings response from the client"
-                            ),
-                        }
-                    },
-                );
-
+                this.fetch_configuration();
+                Ok(())
+            })?
+            .on::<lsp_types::notification::DidChangeWorkspaceFolders>(|this, params| {
+                this.change_workspace_folders(params.event);
                 Ok(())
             })?
             .on::<lsp_types::notification::DidChangeWatchedFiles>(|this, params| {
@@ -715,6 +806,63 @@ impl GlobalState {
             .finish();
         Ok(())
     }
+
+    /// Requests the settings from the client. With several workspace folders,
+    /// the settings scoped to each of them are requested as well.
+    pub(crate) fn fetch_configuration(&mut self) {
+        let folders = if self.config.workspace_roots.len() > 1 {
+            self.config.workspace_roots.clone()
+        } else {
+            Vec::new()
+        };
+        let items = iter::once(None)
+            .chain(folders.iter().map(|root| Some(to_proto::url_from_abs_path(root))))
+            .map(|scope_uri| lsp_types::ConfigurationItem {
+                scope_uri,
+                section: Some("rust-analyzer".to_string()),
+            })
+            .collect();
+        let id = self.send_request::<lsp_types::request::WorkspaceConfiguration>(
+            lsp_types::ConfigurationParams { items },
+            |this, resp| {
+                log::debug!("config update response: '{:?}", resp);
+                let Response { id, error, result } = resp;
+                // The workspace folders may have changed since the request was sent.
+                let folders = this.config_requests.remove(&id).unwrap_or_default();
+
+                match (error, result) {
+                    (Some(err), _) => {
+                        log::error!("failed to fetch the server settings: {:?}", err)
+                    }
+                    (None, Some(serde_json::Value::Array(configs))) => {
+                        let mut configs = configs.into_iter();
+                        if let Some(json) = configs.next() {
+                            // Note that json can be null according to the spec if the client can't
+                            // provide a configuration. This is handled in Config::update below.
+                            let mut config = Config::clone(&*this.config);
+                            config.update(json);
+                            let folder_configs = if config.workspace_roots.len() > 1 {
+                                folders
+                                    .into_iter()
+                                    .zip(configs)
+                                    .filter(|(root, _)| config.workspace_roots.contains(root))
+                                    .map(|(root, json)| config.for_folder(root, json))
+                                    .collect()
+                            } else {
+                                Vec::new()
+                            };
+                            this.update_configuration(config, folder_configs);
+                        }
+                    }
+                    (None, _) => {
+                        log::error!("received empty server settings response from the client")
+                    }
+                }
+            },
+        );
+        self.config_requests.insert(id, folders);
+    }
+
     fn update_file_notifications_on_threadpool(&mut self) {
         self.maybe_update_diagnostics();
 
@@ -745,7 +893,7 @@ impl GlobalState {
             .collect::<Vec<_>>();
 
         log::trace!("updating notifications for {:?}", subscriptions);
-        if self.config.publish_diagnostics() {
+        if self.config.publish_diagnostics() && !self.config.pull_diagnostics() {
             let snapshot = self.snapshot();
             self.task_pool.handle.spawn(move || {
                 let diagnostics = subscriptions
@@ -5,11 +5,15 @@ use flycheck::{FlycheckConfig, FlycheckHandle};
 use hir::db::DefDatabase;
 use ide::Change;
 use ide_db::base_db::{CrateGraph, SourceRoot, VfsPath};
-use project_model::{BuildDataCollector, BuildDataResult, ProcMacroClient, ProjectWorkspace};
+use project_model::{
+    BuildDataCollector, BuildDataResult, CargoConfig, ProcMacroClient, ProjectManifest,
+    ProjectWorkspace,
+};
 use vfs::{file_set::FileSetConfig, AbsPath, AbsPathBuf, ChangeKind};
 
 use crate::{
     config::{Config, FilesWatcher, LinkedProject},
+    from_proto,
     global_state::GlobalState,
     lsp_ext,
     main_loop::Task,
@@ -19,6 +23,9 @@ use crate::{
 pub(crate) enum ProjectWorkspaceProgress {
     Begin,
     Report(String),
+    /// One of the linked projects has been loaded. Sent before `End`, so that
+    /// the project can be used while the remaining ones are being fetched.
+    Loaded(anyhow::Result<ProjectWorkspace>),
     End(Vec<anyhow::Result<ProjectWorkspace>>),
 }
 
@@ -37,15 +44,23 @@ impl GlobalState {
             || self.vfs_progress_n_done < self.vfs_progress_n_total)
     }
 
-    pub(crate) fn update_configuration(&mut self, config: Config) {
+    pub(crate) fn update_configuration(&mut self, config: Config, folder_configs: Vec<Config>) {
         let _p = profile::span("GlobalState::update_configuration");
+        let old_linked_projects = self.linked_projects();
+        let old_flycheck_configs = self.flycheck_configs();
         let old_config = mem::replace(&mut self.config, Arc::new(config));
+        self.folder_configs = folder_configs.into_iter().map(Arc::new).collect();
         if self.config.lru_capacity() != old_config.lru_capacity() {
             self.analysis_host.update_lru_capacity(self.config.lru_capacity());
         }
-        if self.config.linked_projects() != old_config.linked_projects() {
-            self.fetch_workspaces_request()
-        } else if self.config.flycheck() != old_config.flycheck() {
+        if self.config.symbol_cache_dir() != old_config.symbol_cache_dir() {
+            self.save_symbol_cache(true);
+            let dir = self.config.symbol_cache_dir();
+            self.analysis_host.set_symbol_cache_dir(dir.as_deref().map(AsRef::as_ref));
+        }
+        if self.linked_projects() != old_linked_projects {
+            self.fetch_new_workspaces_request()
+        } else if self.flycheck_configs() != old_flycheck_configs {
             self.reload_flycheck();
         }
 
@@ -143,19 +158,202 @@ impl GlobalState {
         }
     }
 
+    /// Adds and removes workspace folders. Only the projects of the new
+    /// folders are loaded, the ones of the remaining folders are kept as is.
+    pub(crate) fn change_workspace_folders(
+        &mut self,
+        event: lsp_types::WorkspaceFoldersChangeEvent,
+    ) {
+        let to_paths = |folders: Vec<lsp_types::WorkspaceFolder>| {
+            folders
+                .into_iter()
+                .filter_map(|it| from_proto::abs_path(&it.uri).ok())
+                .collect::<Vec<_>>()
+        };
+        let added = to_paths(event.added);
+        let removed = to_paths(event.removed);
+        log::info!("workspace folders changed, added: {:?}, removed: {:?}", added, removed);
+
+        let old_config = Arc::clone(&self.config);
+        let is_removed = |path: &AbsPath| {
+            old_config.workspace_root_for(path).map_or(false, |root| removed.contains(root))
+        };
+
+        let mut config = Config::clone(&old_config);
+        config.workspace_roots.retain(|root| !removed.contains(root));
+        for root in &added {
+            if !config.workspace_roots.contains(root) {
+                config.workspace_roots.push(root.clone());
+            }
+        }
+        if let Some(projects) = &mut config.discovered_projects {
+            projects.retain(|project| {
+                let path = match project {
+                    ProjectManifest::ProjectJson(it) | ProjectManifest::CargoToml(it) => it,
+                };
+                !is_removed(path)
+            });
+        }
+
+        let mut folder_configs = self
+            .folder_configs
+            .iter()
+            .filter(|it| !removed.contains(&it.root_path))
+            .map(|it| Config::clone(it))
+            .collect::<Vec<_>>();
+        if !folder_configs.is_empty() {
+            // Until the client sends the settings of the new folders, they use
+            // the global ones.
+            folder_configs.extend(
+                added.iter().map(|root| config.for_folder(root.clone(), serde_json::Value::Null)),
+            );
+        }
+
+        let removed_files = self
+            .vfs
+            .read()
+            .0
+            .iter()
+            .filter(|(_, path)| path.as_path().map_or(false, |path| is_removed(path)))
+            .map(|(file_id, _)| file_id)
+            .collect::<Vec<_>>();
+        self.diagnostics.clear_files(removed_files);
+
+        let has_several_folders = config.workspace_roots.len() > 1;
+        let discover_projects = !added.is_empty() && config.discovered_projects.is_some();
+        self.update_configuration(config, folder_configs);
+        if !added.is_empty() && has_several_folders && self.config.workspace_configuration() {
+            self.fetch_configuration();
+        }
+        if discover_projects {
+            // Walking the new folders can take a while, the projects are
+            // loaded once they are found.
+            self.task_pool
+                .handle
+                .spawn(move || Task::DiscoverProjects(ProjectManifest::discover_all(&added)));
+        }
+    }
+
+    pub(crate) fn add_discovered_projects(&mut self, projects: Vec<ProjectManifest>) {
+        let mut config = Config::clone(&self.config);
+        config.add_discovered_projects(&projects);
+        let folder_configs = self
+            .folder_configs
+            .iter()
+            .map(|it| {
+                // Projects of nested folders only belong to the innermost one.
+                let projects = projects
+                    .iter()
+                    .filter(|project| {
+                        let path = match project {
+                            ProjectManifest::ProjectJson(it) | ProjectManifest::CargoToml(it) => it,
+                        };
+                        config.workspace_root_for(path) == Some(&it.root_path)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let mut folder_config = Config::clone(it);
+                folder_config.add_discovered_projects(&projects);
+                folder_config
+            })
+            .collect();
+        self.update_configuration(config, folder_configs);
+    }
+
+    /// Returns the projects to load, with the cargo config of the workspace
+    /// folder each of them was found in.
+    pub(crate) fn linked_projects(&self) -> Vec<(LinkedProject, CargoConfig)> {
+        if self.folder_configs.is_empty() {
+            let cargo_config = self.config.cargo();
+            return self
+                .config
+                .linked_projects()
+                .into_iter()
+                .map(|project| (project, cargo_config.clone()))
+                .collect();
+        }
+
+        let mut res: Vec<(LinkedProject, CargoConfig)> = Vec::new();
+        for config in &self.folder_configs {
+            let cargo_config = config.cargo();
+            for project in config.linked_projects() {
+                // Settings which aren't scoped to a folder are the same for all of them.
+                if res.iter().all(|(it, _)| *it != project) {
+                    res.push((project, cargo_config.clone()));
+                }
+            }
+        }
+        res
+    }
+
     pub(crate) fn fetch_workspaces_request(&mut self) {
+        self.reuse_fetched_workspaces = false;
         self.fetch_workspaces_queue.request_op(())
     }
+    /// Requests a fetch of the projects which changed since the last one,
+    /// like the ones of a new workspace folder.
+    pub(crate) fn fetch_new_workspaces_request(&mut self) {
+        if !self.fetch_workspaces_queue.op_requested() {
+            self.reuse_fetched_workspaces = true;
+        }
+        self.fetch_workspaces_queue.request_op(())
+    }
+    /// Requests a fetch of the project containing `path` only, reusing the
+    /// workspaces of the other projects. Everything is fetched if `path`
+    /// isn't part of any loaded project, like a new `Cargo.toml`.
+    pub(crate) fn fetch_workspace_for_request(&mut self, path: &AbsPath) {
+        let project = match self.project_containing(path) {
+            Some(it) => it,
+            None => {
+                log::info!("no loaded project contains {}, reloading all of them", path.display());
+                return self.fetch_workspaces_request();
+            }
+        };
+        log::info!("will reload {:?}", project);
+        self.outdated_projects.push(project);
+        self.fetch_new_workspaces_request()
+    }
+    fn project_containing(&self, path: &AbsPath) -> Option<LinkedProject> {
+        let build_data = self.workspace_build_data.as_ref();
+        self.fetched_projects
+            .iter()
+            .zip(self.fetch_workspaces_queue.last_op_result())
+            .filter_map(|((project, _), ws)| Some((project, ws.as_ref().ok()?)))
+            .find(|(project, ws)| {
+                let manifest_dir = match project {
+                    LinkedProject::ProjectManifest(ProjectManifest::ProjectJson(it))
+                    | LinkedProject::ProjectManifest(ProjectManifest::CargoToml(it)) => it.parent(),
+                    LinkedProject::InlineJsonProject(_) => None,
+                };
+                manifest_dir.map_or(false, |dir| path.starts_with(dir))
+                    || ws.to_roots(build_data).iter().any(|root| {
+                        root.is_member && root.include.iter().any(|it| path.starts_with(it))
+                    })
+            })
+            .map(|(project, _)| project.clone())
+    }
     pub(crate) fn fetch_workspaces_if_needed(&mut self) {
         if self.fetch_workspaces_queue.should_start_op().is_none() {
             return;
         }
         log::info!("will fetch workspaces");
 
+        let linked_projects = self.linked_projects();
+        let mut reusable_workspaces = Vec::new();
+        let outdated_projects = mem::take(&mut self.outdated_projects);
+        if mem::take(&mut self.reuse_fetched_workspaces) {
+            reusable_workspaces = self
+                .fetched_projects
+                .iter()
+                .zip(self.fetch_workspaces_queue.last_op_result())
+                .filter(|((project, _), _)| !outdated_projects.contains(project))
+                .filter_map(|(project, ws)| Some((project.clone(), ws.as_ref().ok()?.clone())))
+                .collect::<Vec<_>>();
+        }
+        self.fetched_projects = linked_projects.clone();
+
         self.task_pool.handle.spawn_with_sender({
-            let linked_projects = self.config.linked_projects();
             let detached_files = self.config.detached_files().to_vec();
-            let cargo_config = self.config.cargo();
 
             move |sender| {
                 let progress = {
@@ -167,30 +365,51 @@ impl GlobalState {
                     }
                 };
 
+                let loaded = |workspace: &anyhow::Result<ProjectWorkspace>| {
+                    let workspace = match workspace {
+                        Ok(it) => Ok(it.clone()),
+                        Err(err) => Err(anyhow::format_err!("{:#}", err)),
+                    };
+                    sender
+                        .send(Task::FetchWorkspace(ProjectWorkspaceProgress::Loaded(workspace)))
+                        .unwrap()
+                };
+
                 sender.send(Task::FetchWorkspace(ProjectWorkspaceProgress::Begin)).unwrap();
 
                 let mut workspaces = linked_projects
                     .iter()
-                    .map(|project| match project {
-                        LinkedProject::ProjectManifest(manifest) => {
-                            project_model::ProjectWorkspace::load(
-                                manifest.clone(),
-                                &cargo_config,
-                                &progress,
-                            )
+                    .map(|(project, cargo_config)| {
+                        let reusable = reusable_workspaces
+                            .iter()
+                            .position(|((it, config), _)| it == project && config == cargo_config);
+                        if let Some(idx) = reusable {
+                            return Ok(reusable_workspaces.swap_remove(idx).1);
                         }
-                        LinkedProject::InlineJsonProject(it) => {
-                            project_model::ProjectWorkspace::load_inline(
-                                it.clone(),
-                                cargo_config.target.as_deref(),
-                            )
+                        match project {
+                            LinkedProject::ProjectManifest(manifest) => {
+                                project_model::ProjectWorkspace::load(
+                                    manifest.clone(),
+                                    cargo_config,
+                                    &progress,
+                                )
+                            }
+                            LinkedProject::InlineJsonProject(it) => {
+                                project_model::ProjectWorkspace::load_inline(
+                                    it.clone(),
+                                    cargo_config.target.as_deref(),
+                                )
+                            }
                         }
                     })
+                    .inspect(&loaded)
                     .collect::<Vec<_>>();
 
                 if !detached_files.is_empty() {
-                    workspaces
-                        .push(project_model::ProjectWorkspace::load_detached_files(detached_files));
+                    let workspace =
+                        project_model::ProjectWorkspace::load_detached_files(detached_files);
+                    loaded(&workspace);
+                    workspaces.push(workspace);
                 }
 
                 log::info!("did fetch workspaces {:?}", workspaces);
@@ -200,12 +419,38 @@ impl GlobalState {
             }
         });
     }
+    /// Records a single workspace of the fetch in progress. Returns whether
+    /// the workspaces loaded so far should be switched to, which is only the
+    /// case during the initial load: on reloads, we keep using the old
+    /// workspaces until all the new ones are available.
+    pub(crate) fn fetch_workspaces_loaded_one(
+        &mut self,
+        workspace: anyhow::Result<ProjectWorkspace>,
+    ) -> bool {
+        if !self.fetch_workspaces_queue.last_op_result().is_empty() {
+            return false;
+        }
+        self.partially_fetched_workspaces.push(workspace);
+        true
+    }
     pub(crate) fn fetch_workspaces_completed(
         &mut self,
         workspaces: Vec<anyhow::Result<ProjectWorkspace>>,
     ) {
+        self.partially_fetched_workspaces.clear();
         self.fetch_workspaces_queue.op_completed(workspaces)
     }
+    /// The result of the last fetch, or the workspaces loaded so far if the
+    /// initial fetch is still in progress.
+    fn fetched_workspaces(&self) -> &[anyhow::Result<ProjectWorkspace>] {
+        if self.fetch_workspaces_queue.op_in_progress()
+            && !self.partially_fetched_workspaces.is_empty()
+        {
+            &self.partially_fetched_workspaces
+        } else {
+            self.fetch_workspaces_queue.last_op_result()
+        }
+    }
 
     pub(crate) fn fetch_build_data_request(&mut self, build_data_collector: BuildDataCollector) {
         self.fetch_build_data_queue.request_op(build_data_collector);
@@ -245,14 +490,15 @@ impl GlobalState {
                 return;
             }
         }
+        // Most of the symbols indexed so far stay valid with the new workspaces.
+        self.save_symbol_cache(true);
 
         if let Some(error_message) = self.build_data_error() {
             log::error!("failed to switch build data: {}", error_message);
         }
 
         let workspaces = self
-            .fetch_workspaces_queue
-            .last_op_result()
+            .fetched_workspaces()
             .iter()
             .filter_map(|res| res.as_ref().ok().cloned())
             .collect::<Vec<_>>();
@@ -265,6 +511,8 @@ impl GlobalState {
         if *self.workspaces == workspaces && self.workspace_build_data == workspace_build_data {
             return;
         }
+        // Fetching the metadata of the workspaces may have downloaded crates.
+        *self.registry_crates.lock() = None;
 
         if let FilesWatcher::Client = self.config.files().watcher {
             if self.config.did_change_watched_files_dynamic_registration() {
@@ -319,7 +567,7 @@ impl GlobalState {
 
         let watch = match files_config.watcher {
             FilesWatcher::Client => vec![],
-            FilesWatcher::Notify => project_folders.watch,
+            FilesWatcher::Server => project_folders.watch,
         };
         self.vfs_config_version += 1;
         self.loader.handle.set_config(vfs::loader::Config {
@@ -328,9 +576,10 @@ impl GlobalState {
             version: self.vfs_config_version,
         });
 
-        // Create crate graph from all the workspaces
-        let crate_graph = {
-            let mut crate_graph = CrateGraph::default();
+        // Create crate graph from all the workspaces, reusing the crates of
+        // the ones which didn't change.
+        let crate_graphs = {
+            let mut crate_graphs = Vec::with_capacity(workspaces.len());
             let vfs = &mut self.vfs.write().0;
             let loader = &mut self.loader;
             let mem_docs = &self.mem_docs;
@@ -348,20 +597,40 @@ impl GlobalState {
                 res
             };
             for ws in workspaces.iter() {
-                crate_graph.extend(ws.to_crate_graph(
-                    workspace_build_data.as_ref(),
-                    self.proc_macro_client.as_ref(),
-                    &mut load,
-                ));
+                let unchanged = self
+                    .workspaces
+                    .iter()
+                    .position(|it| it == ws)
+                    .filter(|_| {
+                        ws.has_same_build_data(
+                            self.workspace_build_data.as_ref(),
+                            workspace_build_data.as_ref(),
+                        )
+                    })
+                    .and_then(|idx| self.workspace_crate_graphs.get(idx));
+                let crate_graph = match unchanged {
+                    Some(it) => it.clone(),
+                    None => ws.to_crate_graph(
+                        workspace_build_data.as_ref(),
+                        self.proc_macro_client.as_ref(),
+                        &mut load,
+                    ),
+                };
+                crate_graphs.push(crate_graph);
             }
 
-            crate_graph
+            crate_graphs
         };
+        let mut crate_graph = CrateGraph::default();
+        for it in crate_graphs.iter() {
+            crate_graph.extend(it.clone());
+        }
         change.set_crate_graph(crate_graph);
 
         self.source_root_config = project_folders.source_root_config;
         self.workspaces = Arc::new(workspaces);
         self.workspace_build_data = workspace_build_data;
+        self.workspace_crate_graphs = crate_graphs;
 
         self.analysis_host.apply_change(change);
         self.process_changes();
@@ -372,7 +641,7 @@ impl GlobalState {
     fn fetch_workspace_error(&self) -> Option<String> {
         let mut buf = String::new();
 
-        for ws in self.fetch_workspaces_queue.last_op_result() {
+        for ws in self.fetched_workspaces() {
             if let Err(err) = ws {
                 stdx::format_to!(buf, "rust-analyzer failed to load workspace: {:#}\n", err);
             }
@@ -397,42 +666,46 @@ impl GlobalState {
 
     fn reload_flycheck(&mut self) {
         let _p = profile::span("GlobalState::reload_flycheck");
-        let config = match self.config.flycheck() {
-            Some(it) => it,
-            None => {
-                self.flycheck = Vec::new();
-                return;
-            }
-        };
-
         let sender = self.flycheck_sender.clone();
         self.flycheck = self
-            .workspaces
-            .iter()
-            .enumerate()
-            .filter_map(|(id, w)| match w {
-                ProjectWorkspace::Cargo { cargo, .. } => Some((id, cargo.workspace_root())),
-                ProjectWorkspace::Json { project, .. } => {
-                    // Enable flychecks for json projects if a custom flycheck command was supplied
-                    // in the workspace configuration.
-                    match config {
-                        FlycheckConfig::CustomCommand { .. } => Some((id, project.path())),
-                        _ => None,
-                    }
-                }
-                ProjectWorkspace::DetachedFiles { .. } => None,
-            })
-            .map(|(id, root)| {
+            .flycheck_configs()
+            .into_iter()
+            .map(|(id, root, config)| {
                 let sender = sender.clone();
                 FlycheckHandle::spawn(
                     id,
                     Box::new(move |msg| sender.send(msg).unwrap()),
-                    config.clone(),
-                    root.to_path_buf().into(),
+                    config,
+                    root,
                 )
             })
             .collect();
     }
+
+    /// Returns the workspaces to run `cargo check` for, with their roots and
+    /// the flycheck configs of the workspace folders they are in.
+    fn flycheck_configs(&self) -> Vec<(usize, AbsPathBuf, FlycheckConfig)> {
+        self.workspaces
+            .iter()
+            .enumerate()
+            .filter_map(|(id, w)| {
+                let root = match w {
+                    ProjectWorkspace::Cargo { cargo, .. } => cargo.workspace_root(),
+                    ProjectWorkspace::Json { project, .. } => project.path(),
+                    ProjectWorkspace::DetachedFiles { .. } => return None,
+                };
+                let config = self.config_for(root).flycheck()?;
+                if let ProjectWorkspace::Json { .. } = w {
+                    // Enable flychecks for json projects if a custom flycheck command was supplied
+                    // in the workspace configuration.
+                    if !matches!(config, FlycheckConfig::CustomCommand { .. }) {
+                        return None;
+                    }
+                }
+                Some((id, root.to_path_buf(), config))
+            })
+            .collect()
+    }
 }
 
 #[derive(Default)]
@@ -92,6 +92,7 @@ define_semantic_token_modifiers![
     (INJECTED, "injected"),
     (MUTABLE, "mutable"),
     (CONSUMING, "consuming"),
+    (MUTABLE_USE, "mutableUse"),
     (ASYNC, "async"),
     (LIBRARY, "library"),
     (PUBLIC, "public"),
@@ -100,6 +101,7 @@ define_semantic_token_modifiers![
     (TRAIT_MODIFIER, "trait"),
     (CALLABLE, "callable"),
     (INTRA_DOC_LINK, "intraDocLink"),
+    (INTERIOR_MUTABLE, "interiorMutable"),
 ];
 
 #[derive(Default)]
@@ -9,8 +9,9 @@ use ide::{
     Annotation, AnnotationKind, Assist, AssistKind, CallInfo, Cancellable, CompletionItem,
     CompletionItemKind, CompletionRelevance, Documentation, FileId, FileRange, FileSystemEdit,
     Fold, FoldKind, Highlight, HlMod, HlOperator, HlPunct, HlRange, HlTag, Indel, InlayHint,
-    InlayKind, Markup, NavigationTarget, ReferenceAccess, RenameError, Runnable, Severity,
-    SourceChange, StructureNodeKind, SymbolKind, TextEdit, TextRange, TextSize,
+    InlayKind, Markup, MemoryLayoutNode, MonikerKind, MonikerResult, NavigationTarget,
+    ReferenceCategory, RenameError, Runnable, Severity, SourceChange, StructureNodeKind,
+    SymbolKind, TextEdit, TextRange, TextSize,
 };
 use itertools::Itertools;
 use serde_json::to_value;
@@ -72,11 +73,22 @@ pub(crate) fn structure_node_kind(kind: StructureNodeKind) -> lsp_types::SymbolK
 }
 
 pub(crate) fn document_highlight_kind(
-    reference_access: ReferenceAccess,
-) -> lsp_types::DocumentHighlightKind {
-    match reference_access {
-        ReferenceAccess::Read => lsp_types::DocumentHighlightKind::Read,
-        ReferenceAccess::Write => lsp_types::DocumentHighlightKind::Write,
+    category: ReferenceCategory,
+) -> Option<lsp_types::DocumentHighlightKind> {
+    match category {
+        ReferenceCategory::Read => Some(lsp_types::DocumentHighlightKind::Read),
+        ReferenceCategory::Write => Some(lsp_types::DocumentHighlightKind::Write),
+        ReferenceCategory::Call | ReferenceCategory::Import | ReferenceCategory::TraitImpl => None,
+    }
+}
+
+pub(crate) fn reference_category(category: ReferenceCategory) -> lsp_ext::ReferenceCategory {
+    match category {
+        ReferenceCategory::Read => lsp_ext::ReferenceCategory::Read,
+        ReferenceCategory::Write => lsp_ext::ReferenceCategory::Write,
+        ReferenceCategory::Call => lsp_ext::ReferenceCategory::Call,
+        ReferenceCategory::Import => lsp_ext::ReferenceCategory::Import,
+        ReferenceCategory::TraitImpl => lsp_ext::ReferenceCategory::TraitImpl,
     }
 }
 
@@ -413,6 +425,8 @@ pub(crate) fn inlay_hint(line_index: &LineIndex, inlay_hint: InlayHint) -> lsp_e
             InlayKind::ParameterHint => lsp_ext::InlayKind::ParameterHint,
             InlayKind::TypeHint => lsp_ext::InlayKind::TypeHint,
             InlayKind::ChainingHint => lsp_ext::InlayKind::ChainingHint,
+            InlayKind::ImplicitDropHint => lsp_ext::InlayKind::ImplicitDropHint,
+            InlayKind::LifetimeHint => lsp_ext::InlayKind::LifetimeHint,
         },
     }
 }
@@ -543,9 +557,11 @@ fn semantic_token_type_and_modifiers(
             HlMod::ControlFlow => semantic_tokens::CONTROL_FLOW,
             HlMod::Mutable => semantic_tokens::MUTABLE,
             HlMod::Consuming => semantic_tokens::CONSUMING,
+            HlMod::MutableUse => semantic_tokens::MUTABLE_USE,
             HlMod::Async => semantic_tokens::ASYNC,
             HlMod::Library => semantic_tokens::LIBRARY,
             HlMod::Public => semantic_tokens::PUBLIC,
+            HlMod::InteriorMutable => semantic_tokens::INTERIOR_MUTABLE,
             HlMod::Unsafe => semantic_tokens::UNSAFE,
             HlMod::Callable => semantic_tokens::CALLABLE,
             HlMod::Static => lsp_types::SemanticTokenModifier::STATIC,
@@ -568,7 +584,8 @@ pub(crate) fn folding_range(
     let kind = match fold.kind {
         FoldKind::Comment => Some(lsp_types::FoldingRangeKind::Comment),
         FoldKind::Imports => Some(lsp_types::FoldingRangeKind::Imports),
-        FoldKind::Region => Some(lsp_types::FoldingRangeKind::Region),
+        // So that clients folding all the regions fold the disabled code too.
+        FoldKind::Region | FoldKind::InactiveCode => Some(lsp_types::FoldingRangeKind::Region),
         FoldKind::Mods
         | FoldKind::Block
         | FoldKind::ArgList
@@ -1202,6 +1219,31 @@ pub(crate) fn rename_error(err: RenameError) -> crate::LspError {
     crate::LspError { code: lsp_server::ErrorCode::InvalidParams as i32, message: err.to_string() }
 }
 
+pub(crate) fn memory_layout_node(node: MemoryLayoutNode) -> lsp_ext::MemoryLayoutNode {
+    lsp_ext::MemoryLayoutNode {
+        item_name: node.item_name,
+        typename: node.typename,
+        size: node.size,
+        alignment: node.alignment,
+        offset: node.offset,
+        padding: node.padding,
+        children: node.children.into_iter().map(memory_layout_node).collect(),
+    }
+}
+
+pub(crate) fn moniker(moniker: MonikerResult) -> lsp_types::Moniker {
+    lsp_types::Moniker {
+        scheme: "rust-analyzer".to_string(),
+        identifier: moniker.identifier.to_string(),
+        // The identifiers contain the crate name, but not its version.
+        unique: lsp_types::UniquenessLevel::Scheme,
+        kind: Some(match moniker.kind {
+            MonikerKind::Import => lsp_types::MonikerKind::Import,
+            MonikerKind::Export => lsp_types::MonikerKind::Export,
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -1238,6 +1280,7 @@ mod tests {
                 &ide::CompletionConfig {
                     enable_postfix_completions: true,
                     enable_imports_on_the_fly: true,
+                    enable_trait_methods_on_the_fly: true,
                     enable_self_on_the_fly: true,
                     add_call_parenthesis: true,
                     add_call_argument_snippets: true,
@@ -1249,6 +1292,8 @@ mod tests {
                         group: true,
                         skip_glob_imports: true,
                     },
+                    postfix_snippets: Vec::new(),
+                    additional_lints: Vec::new(),
                 },
                 file_position,
             )
@@ -1325,6 +1370,32 @@ fn main() {
         }
     }
 
+    #[test]
+    fn conv_fold_inactive_code_as_region() {
+        let text = r#"#[cfg(never)]
+fn f() {
+    g();
+}"#;
+
+        let (analysis, file_id) = Analysis::from_single_file(text.to_string());
+        let folds = analysis.folding_ranges(file_id).unwrap();
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new(text)),
+            endings: LineEndings::Unix,
+            encoding: OffsetEncoding::Utf16,
+        };
+        let converted: Vec<(u32, u32, Option<lsp_types::FoldingRangeKind>)> = folds
+            .into_iter()
+            .map(|it| folding_range(text, &line_index, true, it))
+            .map(|it| (it.start_line, it.end_line, it.kind))
+            .collect();
+
+        assert_eq!(
+            converted,
+            vec![(1, 3, None), (0, 3, Some(lsp_types::FoldingRangeKind::Region))]
+        );
+    }
+
     // `Url` is not able to parse windows paths on unix machines.
     #[test]
     #[cfg(target_os = "windows")]
@@ -13,9 +13,10 @@ mod tidy;
 mod testdir;
 mod support;
 
-use std::{collections::HashMap, path::PathBuf, time::Instant};
+use std::{collections::HashMap, env, path::PathBuf, time::Instant};
 
 use expect_test::expect;
+use ide::FilePosition;
 use lsp_types::{
     notification::DidOpenTextDocument,
     request::{
@@ -27,12 +28,19 @@ use lsp_types::{
     PartialResultParams, Position, Range, RenameFilesParams, TextDocumentItem,
     TextDocumentPositionParams, WorkDoneProgressParams,
 };
-use rust_analyzer::lsp_ext::{OnEnter, Runnables, RunnablesParams};
+use rust_analyzer::{
+    config::{Config, ExtraClientCapabilities},
+    extra_server_capabilities,
+    lsp_ext::{
+        DocumentDiagnosticParams, DocumentDiagnosticRequest, OnEnter, Runnables, RunnablesParams,
+    },
+};
 use serde_json::json;
 use test_utils::skip_slow_tests;
+use vfs::AbsPathBuf;
 
 use crate::{
-    support::{project, Project},
+    support::{analysis_host, project, Project},
     testdir::TestDir,
 };
 
@@ -74,6 +82,29 @@ use std::collections::Spam;
     assert!(res.to_string().contains("HashMap"));
 }
 
+#[test]
+fn goto_def_into_real_sysroot() {
+    if skip_slow_tests() || env::var_os("RUST_SRC_PATH").is_none() {
+        return;
+    }
+
+    let (host, change_fixture) = analysis_host(
+        r#"
+//- sysroot: real
+fn main() {
+    let v: Vec<u8> = Vec::new$0();
+}
+"#,
+    );
+    let (file_id, range_or_offset) = change_fixture.file_position.unwrap();
+    let position = FilePosition { file_id, offset: range_or_offset.expect_offset() };
+    let navs =
+        host.analysis().goto_definition(position).unwrap().expect("no definition found").info;
+    assert_eq!(navs.len(), 1);
+    assert_eq!(navs[0].name, "new");
+    assert_ne!(navs[0].file_id, file_id);
+}
+
 #[test]
 fn test_runnables_project() {
     if skip_slow_tests() {
@@ -567,6 +598,75 @@ version = \"0.0.0\"
     );
 }
 
+#[test]
+fn pull_diagnostics() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- /src/lib.rs
+mod missing;
+"#,
+    )
+    .with_extra_caps(json!({ "textDocument": { "diagnostic": {} } }))
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    let params = |previous_result_id| DocumentDiagnosticParams {
+        text_document: server.doc_id("src/lib.rs"),
+        identifier: None,
+        previous_result_id,
+        work_done_progress_params: WorkDoneProgressParams::default(),
+        partial_result_params: PartialResultParams::default(),
+    };
+
+    let res = server.send_request::<DocumentDiagnosticRequest>(params(None));
+    assert_eq!(res["kind"], "full", "{}", res);
+    assert_eq!(res["items"][0]["code"], "unresolved-module", "{}", res);
+    let result_id = res["resultId"].as_str().unwrap().to_string();
+
+    server.request::<DocumentDiagnosticRequest>(
+        params(Some(result_id.clone())),
+        json!({ "kind": "unchanged", "resultId": result_id }),
+    );
+
+    let res = server.send_request::<DocumentDiagnosticRequest>(params(Some("stale".to_string())));
+    assert_eq!(res["kind"], "full", "{}", res);
+    assert_eq!(res["resultId"], result_id.as_str(), "{}", res);
+}
+
+#[test]
+fn diagnostic_provider_needs_client_support() {
+    let caps = |client_caps| {
+        let mut config = Config::new(
+            AbsPathBuf::assert(env::current_dir().unwrap()),
+            lsp_types::ClientCapabilities::default(),
+        );
+        config.extra_caps = ExtraClientCapabilities::from_json(&client_caps);
+        extra_server_capabilities(&config)
+    };
+
+    assert!(!caps(json!({})).contains_key("diagnosticProvider"));
+    assert!(
+        !caps(json!({ "textDocument": { "diagnostic": null } })).contains_key("diagnosticProvider")
+    );
+    assert_eq!(
+        caps(json!({ "textDocument": { "diagnostic": {} } }))["diagnosticProvider"],
+        json!({
+            "identifier": "rust-analyzer",
+            "interFileDependencies": true,
+            "workspaceDiagnostics": true,
+        })
+    );
+}
+
 #[test]
 fn out_dirs_check() {
     if skip_slow_tests() {
@@ -806,6 +906,54 @@ pub fn foo(_input: TokenStream) -> TokenStream {
     .assert_eq(value);
 }
 
+#[test]
+fn loads_all_linked_projects() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let server = Project::with_fixture(
+        r#"
+//- /foo/Cargo.toml
+[package]
+name = "foo"
+version = "0.0.0"
+
+//- /foo/src/lib.rs
+pub struct Foo;
+
+//- /bar/Cargo.toml
+[package]
+name = "bar"
+version = "0.0.0"
+
+//- /bar/src/lib.rs
+pub struct Bar;
+"#,
+    )
+    .with_config(serde_json::json!({
+        "cargo": { "noSysroot": true }
+    }))
+    .root("foo")
+    .root("bar")
+    .server()
+    .wait_until_workspace_is_loaded();
+
+    for (path, expected) in
+        [("foo/src/lib.rs", "pub struct Foo"), ("bar/src/lib.rs", "pub struct Bar")]
+    {
+        let res = server.send_request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                server.doc_id(path),
+                Position::new(0, 12),
+            ),
+            work_done_progress_params: Default::default(),
+        });
+        let value = res.get("contents").unwrap().get("value").unwrap().as_str().unwrap();
+        assert!(value.contains(expected), "{}", value);
+    }
+}
+
 #[test]
 fn test_will_rename_files_same_level() {
     if skip_slow_tests() {
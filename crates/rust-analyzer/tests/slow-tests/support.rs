@@ -1,20 +1,25 @@
 use std::{
     cell::{Cell, RefCell},
-    fs,
+    fs, mem,
     path::{Path, PathBuf},
-    sync::Once,
+    sync::{Arc, Once},
     time::Duration,
 };
 
 use crossbeam_channel::{after, select, Receiver};
+use ide::AnalysisHost;
+use ide_db::base_db::{fixture::ChangeFixture, Change, FileId, FileSet, SourceRoot, VfsPath};
 use lsp_server::{Connection, Message, Notification, Request};
 use lsp_types::{notification::Exit, request::Shutdown, TextDocumentIdentifier, Url};
-use project_model::ProjectManifest;
-use rust_analyzer::{config::Config, lsp_ext, main_loop};
+use project_model::{ProjectManifest, Sysroot};
+use rust_analyzer::{
+    config::{Config, ExtraClientCapabilities},
+    lsp_ext, main_loop,
+};
 use serde::Serialize;
 use serde_json::{json, to_string_pretty, Value};
-use test_utils::Fixture;
-use vfs::AbsPathBuf;
+use test_utils::{Fixture, TextOrBytes};
+use vfs::{AbsPath, AbsPathBuf};
 
 use crate::testdir::TestDir;
 
@@ -23,6 +28,7 @@ pub(crate) struct Project<'a> {
     tmp_dir: Option<TestDir>,
     roots: Vec<PathBuf>,
     config: serde_json::Value,
+    extra_caps: serde_json::Value,
 }
 
 impl<'a> Project<'a> {
@@ -39,6 +45,7 @@ impl<'a> Project<'a> {
                     "useRustcWrapperForBuildScripts": false,
                 }
             }),
+            extra_caps: Value::Null,
         }
     }
 
@@ -67,6 +74,13 @@ impl<'a> Project<'a> {
         self
     }
 
+    /// Client capabilities which aren't part of `lsp_types::ClientCapabilities`
+    /// yet, as the client would send them in `initialize`.
+    pub(crate) fn with_extra_caps(mut self, extra_caps: serde_json::Value) -> Project<'a> {
+        self.extra_caps = extra_caps;
+        self
+    }
+
     pub(crate) fn server(self) -> Server {
         let tmp_dir = self.tmp_dir.unwrap_or_else(TestDir::new);
         static INIT: Once = Once::new();
@@ -75,12 +89,17 @@ impl<'a> Project<'a> {
             profile::init_from(crate::PROFILE);
         });
 
-        let (mini_core, fixtures) = Fixture::parse(self.fixture);
+        let (mini_core, proc_macros, fixtures) = Fixture::parse(self.fixture);
         assert!(mini_core.is_none());
+        assert!(proc_macros.is_empty());
         for entry in fixtures {
             let path = tmp_dir.path().join(&entry.path['/'.len_utf8()..]);
             fs::create_dir_all(path.parent().unwrap()).unwrap();
-            fs::write(path.as_path(), entry.text.as_bytes()).unwrap();
+            let contents = match entry.text_or_bytes() {
+                TextOrBytes::Text(text) => text.as_bytes(),
+                TextOrBytes::Bytes(bytes) => bytes,
+            };
+            fs::write(path.as_path(), contents).unwrap();
         }
 
         let tmp_dir_path = AbsPathBuf::assert(tmp_dir.path().to_path_buf());
@@ -124,6 +143,7 @@ impl<'a> Project<'a> {
                 ..Default::default()
             },
         );
+        config.extra_caps = ExtraClientCapabilities::from_json(&self.extra_caps);
         config.discovered_projects = Some(discovered_projects);
         config.update(self.config);
 
@@ -135,6 +155,75 @@ pub(crate) fn project(fixture: &str) -> Server {
     Project::with_fixture(fixture).server()
 }
 
+/// Creates an `AnalysisHost` for `ra_fixture`, loading the standard library
+/// sources found via `RUST_SRC_PATH` if it asks for `//- sysroot: real`.
+pub(crate) fn analysis_host(ra_fixture: &str) -> (AnalysisHost, ChangeFixture) {
+    let mut host = AnalysisHost::default();
+    let mut change_fixture = ChangeFixture::parse(ra_fixture);
+    if change_fixture.real_sysroot {
+        attach_real_sysroot(&mut change_fixture.change);
+    }
+    host.apply_change(mem::take(&mut change_fixture.change));
+    (host, change_fixture)
+}
+
+/// Adds the standard library found by project-model's sysroot discovery to
+/// the fixture, making all the fixture crates depend on it.
+fn attach_real_sysroot(change: &mut Change) {
+    let manifest = AbsPathBuf::assert(PathBuf::from(env!("CARGO_MANIFEST_DIR"))).join("Cargo.toml");
+    let sysroot = Sysroot::discover(&manifest).expect("failed to discover the sysroot");
+
+    let mut crate_graph = change.crate_graph.take().unwrap();
+    let mut roots = change.roots.take().unwrap_or_default();
+    let fixture_crates = crate_graph.iter().collect::<Vec<_>>();
+
+    let mut file_id =
+        FileId(change.files_changed.iter().map(|(file_id, _)| file_id.0 + 1).max().unwrap_or(0));
+    let mut file_set = FileSet::default();
+    for krate in sysroot.crates() {
+        for path in rust_files(sysroot[krate].root_dir()) {
+            let text = fs::read_to_string(&path).ok();
+            change.change_file(file_id, text.map(Arc::new));
+            file_set.insert(file_id, VfsPath::from(path));
+            file_id.0 += 1;
+        }
+    }
+
+    let mut load = |path: &AbsPath| file_set.file_for_path(&path.to_path_buf().into()).copied();
+    let public_deps =
+        project_model::add_sysroot_to_crate_graph(&mut crate_graph, &sysroot, &mut load);
+    for krate in fixture_crates {
+        for (name, dep) in &public_deps {
+            crate_graph.add_dep(krate, name.clone(), *dep).unwrap();
+        }
+    }
+
+    roots.push(SourceRoot::new_library(file_set));
+    change.set_roots(roots);
+    change.set_crate_graph(crate_graph);
+}
+
+/// Returns the `.rs` files in `dir` and its subdirectories.
+fn rust_files(dir: &AbsPath) -> Vec<AbsPathBuf> {
+    let mut res = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(it) => it,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = dir.join(entry.file_name());
+            match entry.file_type() {
+                Ok(it) if it.is_dir() => dirs.push(path),
+                Ok(_) if path.extension().map_or(false, |it| it == "rs") => res.push(path),
+                _ => (),
+            }
+        }
+    }
+    res
+}
+
 pub(crate) struct Server {
     req_id: Cell<i32>,
     messages: RefCell<Vec<Message>>,
@@ -91,6 +91,40 @@ fn files_are_tidy() {
     tidy_marks.finish();
 }
 
+#[test]
+fn check_minicore_usage() {
+    let root = sourcegen::project_root();
+    let mut usage = test_utils::MiniCoreUsage::default();
+    for path in sourcegen::list_rust_files(&root.join("crates")) {
+        // Its tests use unknown flags on purpose.
+        if path.ends_with("test_utils/src/minicore_usage.rs") {
+            continue;
+        }
+        let text = read_file(&path).unwrap();
+        let path = path.strip_prefix(&root).unwrap_or(&path);
+        usage.add_file(&path.display().to_string().replace('\\', "/"), &text);
+    }
+
+    let own_lang_items: &[&str] = &[
+        // The highlighted paths of the `Fn` traits are part of the expected output.
+        "crates/ide/src/syntax_highlighting/tests.rs",
+    ];
+    let problems = usage
+        .finish()
+        .into_iter()
+        .filter(|it| match it {
+            test_utils::MiniCoreProblem::LangItemInFixture { location, .. } => {
+                !own_lang_items.iter().any(|path| location.starts_with(path))
+            }
+            _ => true,
+        })
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+    if !problems.is_empty() {
+        panic!("\nminicore is not used as it should be:\n{}\n", problems.join("\n"));
+    }
+}
+
 fn check_cargo_toml(path: &Path, text: String) -> () {
     let mut section = None;
     for (line_no, text) in text.lines().enumerate() {
@@ -274,6 +308,10 @@ fn check_todo(path: &Path, text: &str) {
         "handlers/add_turbo_fish.rs",
         "handlers/generate_function.rs",
         "handlers/fill_match_arms.rs",
+        "handlers/generate_from_impl_for_similar_struct.rs",
+        // Completing a `match` with all its arms fills them with `todo!()`.
+        "ide_completion/src/completions/pattern.rs",
+        "ide_completion/src/tests/pattern.rs",
         // To support generating `todo!()` in assists, we have `expr_todo()` in
         // `ast::make`.
         "ast/make.rs",
@@ -270,6 +270,15 @@ pub fn expr_break(expr: Option<ast::Expr>) -> ast::Expr {
         None => expr_from_text("break"),
     }
 }
+pub fn expr_break_with_label(label: &str, expr: Option<ast::Expr>) -> ast::Expr {
+    match expr {
+        Some(expr) => expr_from_text(&format!("break {} {}", label, expr)),
+        None => expr_from_text(&format!("break {}", label)),
+    }
+}
+pub fn expr_loop_with_label(label: &str, block: ast::BlockExpr) -> ast::Expr {
+    expr_from_text(&format!("{}: loop {}", label, block))
+}
 pub fn expr_return(expr: Option<ast::Expr>) -> ast::Expr {
     match expr {
         Some(expr) => expr_from_text(&format!("return {}", expr)),
@@ -517,6 +526,15 @@ pub fn param(pat: ast::Pat, ty: ast::Type) -> ast::Param {
     ast_from_text(&format!("fn f({}: {}) {{ }}", pat, ty))
 }
 
+pub fn self_param(kind: ast::SelfParamKind) -> ast::SelfParam {
+    let text = match kind {
+        ast::SelfParamKind::Owned => "self",
+        ast::SelfParamKind::Ref => "&self",
+        ast::SelfParamKind::MutRef => "&mut self",
+    };
+    ast_from_text(&format!("fn f({}) {{ }}", text))
+}
+
 pub fn ret_type(ty: ast::Type) -> ast::RetType {
     ast_from_text(&format!("fn f() -> {} {{ }}", ty))
 }
@@ -247,6 +247,37 @@ impl ast::Attr {
     pub fn token_tree(&self) -> Option<ast::TokenTree> {
         self.meta()?.token_tree()
     }
+
+    /// The string literals of `#[doc(alias = "name")]` and `#[doc(alias("a", "b"))]`.
+    pub fn doc_aliases(&self) -> Vec<ast::String> {
+        let tt = match self.as_simple_call() {
+            Some((name, tt)) if name == "doc" => tt,
+            _ => return Vec::new(),
+        };
+        let mut res = Vec::new();
+        let mut elements = tt.syntax().children_with_tokens().filter(|it| !it.kind().is_trivia());
+        while let Some(element) = elements.next() {
+            let is_alias = matches!(
+                &element,
+                NodeOrToken::Token(token) if token.kind() == SyntaxKind::IDENT && token.text() == "alias"
+            );
+            if !is_alias {
+                continue;
+            }
+            match elements.next() {
+                Some(NodeOrToken::Token(token)) if token.kind() == T![=] => res.extend(
+                    elements.next().and_then(|it| it.into_token()).and_then(ast::String::cast),
+                ),
+                Some(NodeOrToken::Node(node)) => res.extend(
+                    node.children_with_tokens()
+                        .filter_map(|it| it.into_token())
+                        .filter_map(ast::String::cast),
+                ),
+                _ => (),
+            }
+        }
+        res
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,6 +32,16 @@ impl SyntaxNodePtr {
         SyntaxNodePtr { range: node.text_range(), kind: node.kind() }
     }
 
+    /// Creates a pointer to the node of the given kind and range, for example
+    /// to restore a pointer saved to disk.
+    pub fn from_raw_parts(kind: SyntaxKind, range: TextRange) -> SyntaxNodePtr {
+        SyntaxNodePtr { range, kind }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
     /// "Dereference" the pointer to get the node it points to.
     ///
     /// Panics if node is not found, so make sure that `root` syntax tree is
@@ -49,9 +49,27 @@
 //! Metadata allows specifying all settings and variables
 //! that are available in a real rust project:
 //! - crate names via `crate:cratename`
-//! - dependencies via `deps:dep1,dep2`
+//! - dependencies via `deps:dep1,dep2`, optional dependencies are marked as
+//!   `dep?` and are only added when the feature of the same name is enabled,
+//!   renamed dependencies are written as `dep(rename=alias)`
 //! - configuration settings via `cfg:dbg=false,opt_level=2`
-//! - environment variables via `env:PATH=/bin,RUST_LOG=debug`
+//! - target-specific configuration (`target_os`, `target_arch`, `unix`, ...)
+//!   via `target:x86_64-pc-windows-msvc`
+//! - cargo features via `features:std,serde/derive`, where `serde/derive`
+//!   enables the `derive` feature of the `serde` dependency
+//! - environment variables via `env:PATH=/bin,RUST_LOG=debug`, values
+//!   containing commas, `=` or whitespace can be double-quoted, like
+//!   `env:FLAGS="-C opt-level=2"`, with `\"` and `\\` escapes
+//! - crate-level attributes via `crate-attr:no_std,feature=never_type`, where
+//!   `key=value` is shorthand for `#![key(value)]` and `key="value"` for
+//!   `#![key = "value"]`, like `recursion_limit="256"`
+//! - binary files via `binary:base64`, the text of the file is then decoded
+//!   from base64
+//! - library files (placed into an immutable source root, like the sysroot
+//!   or crates.io dependencies) via `library`
+//! - the crate type via `crate-type:proc-macro`, `lib` being the default
+//! - the toolchain via `toolchain:nightly` or `toolchain:1.54`, to test
+//!   behavior depending on the release channel or version
 //!
 //! Example using all available metadata:
 //! ```
@@ -61,27 +79,189 @@
 //! "
 //! ```
 
+use std::{env, fmt, fs, mem, path::Path};
+
 use rustc_hash::FxHashMap;
 use stdx::{lines_with_ends, split_once, trim_indent};
+use text_size::TextRange;
+
+use crate::{
+    extract_annotations, extract_range_or_offset, RangeOrOffset, CURSOR_MARKER,
+    ESCAPED_CURSOR_MARKER,
+};
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Default, Eq, PartialEq)]
 pub struct Fixture {
     pub path: String,
     pub text: String,
     pub krate: Option<String>,
-    pub deps: Vec<String>,
+    pub deps: Vec<FixtureDep>,
     pub cfg_atoms: Vec<String>,
     pub cfg_key_values: Vec<(String, String)>,
+    /// Features enabled for this crate. They are also included in
+    /// `cfg_key_values` as `feature="name"`.
+    pub features: Vec<String>,
+    /// Features this crate enables for its dependencies, as `(dep, feature)`
+    /// pairs.
+    pub dep_features: Vec<(String, String)>,
+    /// Target triple, the corresponding cfgs are included in `cfg_atoms` and
+    /// `cfg_key_values`.
+    pub target: Option<String>,
     pub edition: Option<String>,
+    /// Release channel or version of the toolchain, like `nightly` or
+    /// `1.54.0`.
+    pub toolchain: Option<String>,
     pub env: FxHashMap<String, String>,
+    /// Contents of the `#![...]` attributes to add to the crate root,
+    /// e.g. `no_std` or `feature(never_type)`.
+    pub crate_attrs: Vec<String>,
+    /// Whether the crate is a `proc-macro` crate, set via
+    /// `crate-type:proc-macro`.
+    pub is_proc_macro: bool,
     pub introduce_new_source_root: bool,
+    pub is_library: bool,
+    /// Whether the real standard library should be loaded instead of
+    /// `minicore`, set for all files by `//- sysroot: real`.
+    pub real_sysroot: bool,
+    /// Decoded contents of a `binary:base64` file, `text` then holds the
+    /// encoded form.
+    pub bytes: Option<Vec<u8>>,
+}
+
+/// A dependency of a fixture crate, see the module docs for the syntax.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FixtureDep {
+    /// Name of the crate depended upon.
+    pub name: String,
+    /// Name the dependency is available under, like `package` renames in
+    /// `Cargo.toml`.
+    pub rename: Option<String>,
+    /// Optional dependencies are enabled by the feature named after
+    /// [`FixtureDep::extern_name`].
+    pub optional: bool,
+}
+
+impl FixtureDep {
+    /// Name of the dependency in the depending crate.
+    pub fn extern_name(&self) -> &str {
+        self.rename.as_deref().unwrap_or(&self.name)
+    }
+
+    fn parse(dep: &str) -> Option<FixtureDep> {
+        let (dep, optional) = match dep.strip_suffix('?') {
+            Some(dep) => (dep, true),
+            None => (dep, false),
+        };
+        let (name, rename) = match dep.strip_suffix(')').and_then(|it| split_once(it, '(')) {
+            Some((name, args)) => {
+                let (key, value) = split_once(args, '=')?;
+                if key != "rename" || value.is_empty() {
+                    return None;
+                }
+                (name, Some(value.to_string()))
+            }
+            None => (dep, None),
+        };
+        if name.is_empty() || name.contains(|c| "?()=".contains(c)) {
+            return None;
+        }
+        Some(FixtureDep { name: name.to_string(), rename, optional })
+    }
 }
 
+impl fmt::Display for FixtureDep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(rename) = &self.rename {
+            write!(f, "(rename={})", rename)?;
+        }
+        if self.optional {
+            write!(f, "?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Contents of a fixture file, see [`Fixture::text_or_bytes`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TextOrBytes<'a> {
+    Text(&'a str),
+    Bytes(&'a [u8]),
+}
+
+#[derive(Debug)]
 pub struct MiniCore {
     activated_flags: Vec<String>,
     valid_flags: Vec<String>,
 }
 
+/// A problem with the fixture text, reported by [`Fixture::try_parse`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FixtureError {
+    /// A `//-` line which is indented differently from the other ones.
+    InvalidIndentation { line: usize, text: String },
+    /// A `// key: value` comment which was probably meant to be a `//-` line.
+    SuspiciousMetaLine(String),
+    /// A `//-` line which can't be split into a path and `key:value` pairs.
+    InvalidMetaLine(String),
+    /// A file path which does not start with `/`.
+    InvalidPath(String),
+    /// A `key:value` pair with an unsupported key.
+    UnknownMetaKey(String),
+    /// A fixture-wide `//- key:` declaration with an unsupported key.
+    UnknownHeader(String),
+    /// A minicore flag which is listed twice.
+    DuplicateMiniCoreFlag(String),
+    /// A `target:` value which doesn't look like a target triple.
+    InvalidTarget(String),
+    /// With `auto_mods`, a file which can belong to several crates.
+    AmbiguousCrate(String),
+    /// A binary file with an unknown encoding or malformed contents.
+    InvalidBinary(String),
+    /// A malformed entry of `deps:`.
+    InvalidDependency(String),
+    /// An `//- include:` of a file which can't be read, or which includes
+    /// itself.
+    InvalidInclude(String),
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::InvalidIndentation { line, text } => write!(
+                f,
+                "Metadata line {} has invalid indentation. \
+                 All metadata lines need to have the same indentation.\n\
+                 The offending line: {:?}",
+                line, text
+            ),
+            FixtureError::SuspiciousMetaLine(line) => {
+                write!(f, "looks like invalid metadata line: {:?}", line)
+            }
+            FixtureError::InvalidMetaLine(meta) => write!(f, "invalid meta line: {:?}", meta),
+            FixtureError::InvalidPath(path) => {
+                write!(f, "fixture path does not start with `/`: {:?}", path)
+            }
+            FixtureError::UnknownMetaKey(component) => write!(f, "bad component: {:?}", component),
+            FixtureError::UnknownHeader(header) => {
+                write!(f, "unknown fixture header: {:?}", header)
+            }
+            FixtureError::DuplicateMiniCoreFlag(flag) => {
+                write!(f, "duplicate minicore flag: {:?}", flag)
+            }
+            FixtureError::InvalidTarget(target) => write!(f, "invalid target: {:?}", target),
+            FixtureError::AmbiguousCrate(path) => {
+                write!(f, "can't determine the crate of {:?}, several crate roots match", path)
+            }
+            FixtureError::InvalidBinary(path) => write!(f, "invalid binary file: {:?}", path),
+            FixtureError::InvalidDependency(dep) => write!(f, "invalid dependency: {:?}", dep),
+            FixtureError::InvalidInclude(path) => write!(f, "can't include fixture {:?}", path),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
 impl Fixture {
     /// Parses text which looks like this:
     ///
@@ -100,35 +280,111 @@ impl Fixture {
     ///
     /// That will include a subset of `libcore` into the fixture, see
     /// `minicore.rs` for what's available.
-    pub fn parse(ra_fixture: &str) -> (Option<MiniCore>, Vec<Fixture>) {
-        let fixture = trim_indent(ra_fixture);
+    ///
+    /// A list of test proc macros to load can be declared the same way:
+    ///
+    /// ```
+    /// //- proc_macros: identity, derive_identity
+    /// ```
+    ///
+    /// The names are returned as is, it's up to the consumer of the fixture
+    /// to map them to actual proc macros.
+    ///
+    /// Finally, the default edition and toolchain of all files can be set with
+    ///
+    /// ```
+    /// //- edition: 2021
+    /// //- toolchain: nightly
+    /// ```
+    ///
+    /// Individual files can still override them with `edition:` and
+    /// `toolchain:` in their meta line.
+    ///
+    /// Slow tests which need the actual standard library can request it with
+    ///
+    /// ```
+    /// //- sysroot: real
+    /// ```
+    ///
+    /// It's up to the consumer of the fixture to load the sources, as found
+    /// via `RUST_SRC_PATH`. Such fixtures shouldn't declare `minicore`.
+    ///
+    /// With
+    ///
+    /// ```
+    /// //- auto_mods
+    /// ```
+    ///
+    /// a `//- /foo/bar/baz.rs` file is automatically declared as a module, with
+    /// `pub mod` items and, if missing, `/foo.rs` and `/foo/bar.rs` files
+    /// added to connect it to the crate root.
+    ///
+    /// Fixture text shared between tests can be spliced in with
+    ///
+    /// ```
+    /// //- include: test_data/fixtures/fake_serde.rs
+    /// ```
+    ///
+    /// The path is relative to the directory of the crate running the test.
+    /// Included files can contain fixture-wide declarations only if they are
+    /// included before the first file.
+    ///
+    /// These fixture-wide declarations can come in any order, but all of them
+    /// must precede the first file.
+    ///
+    /// # Panics
+    /// Panics if the fixture is malformed, see [`Fixture::try_parse`] for a
+    /// non-panicking version.
+    pub fn parse(ra_fixture: &str) -> (Option<MiniCore>, Vec<String>, Vec<Fixture>) {
+        match Fixture::try_parse(ra_fixture) {
+            Ok(it) => it,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Same as [`Fixture::parse`], but reports malformed metadata as an error.
+    pub fn try_parse(
+        ra_fixture: &str,
+    ) -> Result<(Option<MiniCore>, Vec<String>, Vec<Fixture>), FixtureError> {
+        let fixture = expand_includes(&trim_indent(ra_fixture), &mut Vec::new())?;
         let mut fixture = fixture.as_str();
         let mut mini_core = None;
+        let mut proc_macros = Vec::new();
+        let mut default_edition = None;
+        let mut default_toolchain = None;
+        let mut real_sysroot = false;
+        let mut auto_mods = false;
         let mut res: Vec<Fixture> = Vec::new();
 
-        if fixture.starts_with("//- minicore:") {
+        while let Some(header) = fixture_header(fixture) {
             let first_line = fixture.split('\n').next().unwrap().to_owned() + "\n";
-            
-            mini_core = Some(MiniCore::parse(&first_line));
+            match split_once(header, ':') {
+                Some(("minicore", _)) => mini_core = Some(MiniCore::parse(&first_line)?),
+                Some(("proc_macros", value)) => {
+                    proc_macros = value
+                        .split(',')
+                        .map(|it| it.trim().to_string())
+                        .filter(|it| !it.is_empty())
+                        .collect()
+                }
+                Some(("edition", value)) => default_edition = Some(value.trim().to_string()),
+                Some(("toolchain", value)) => default_toolchain = Some(value.trim().to_string()),
+                Some(("sysroot", value)) if value.trim() == "real" => real_sysroot = true,
+                None if header == "auto_mods" => auto_mods = true,
+                _ => return Err(FixtureError::UnknownHeader(header.to_string())),
+            }
             fixture = &fixture[first_line.len()..];
         }
 
         let default = if fixture.contains("//-") { None } else { Some("//- /main.rs") };
 
         for (ix, line) in default.into_iter().chain(lines_with_ends(&fixture)).enumerate() {
-            if line.contains("//-") {
-                assert!(
-                    line.starts_with("//-"),
-                    "Metadata line {} has invalid indentation. \
-                     All metadata lines need to have the same indentation.\n\
-                     The offending line: {:?}",
-                    ix,
-                    line
-                );
+            if line.contains("//-") && !line.starts_with("//-") {
+                return Err(FixtureError::InvalidIndentation { line: ix, text: line.to_string() });
             }
 
             if line.starts_with("//-") {
-                let meta = Fixture::parse_meta_line(line);
+                let meta = Fixture::parse_meta_line(line)?;
                 res.push(meta)
             } else {
                 if line.starts_with("// ")
@@ -136,7 +392,7 @@ impl Fixture {
                     && !line.contains("::")
                     && line.chars().all(|it| !it.is_uppercase())
                 {
-                    panic!("looks like invalid metadata line: {:?}", line)
+                    return Err(FixtureError::SuspiciousMetaLine(line.to_string()));
                 }
 
                 if let Some(entry) = res.last_mut() {
@@ -145,45 +401,118 @@ impl Fixture {
             }
         }
 
-        (mini_core, res)
+        for entry in res.iter_mut().filter(|it| it.bytes.is_some()) {
+            match decode_base64(&entry.text) {
+                Some(bytes) => entry.bytes = Some(bytes),
+                None => return Err(FixtureError::InvalidBinary(entry.path.clone())),
+            }
+        }
+
+        if let Some(edition) = default_edition {
+            for entry in res.iter_mut().filter(|it| it.edition.is_none()) {
+                entry.edition = Some(edition.clone());
+            }
+        }
+        if let Some(toolchain) = default_toolchain {
+            for entry in res.iter_mut().filter(|it| it.toolchain.is_none()) {
+                entry.toolchain = Some(toolchain.clone());
+            }
+        }
+
+        if auto_mods {
+            add_module_declarations(&mut res)?;
+        }
+        if real_sysroot {
+            for entry in res.iter_mut() {
+                entry.real_sysroot = true;
+            }
+        }
+
+        Ok((mini_core, proc_macros, res))
+    }
+
+    /// Returns the contents of this file, decoded for binary files.
+    pub fn text_or_bytes(&self) -> TextOrBytes<'_> {
+        match &self.bytes {
+            Some(bytes) => TextOrBytes::Bytes(bytes),
+            None => TextOrBytes::Text(&self.text),
+        }
+    }
+
+    /// Returns the cursor position (`$0`) or selection (`$0...$0`) marked in
+    /// the text of this file, together with the text without the markers.
+    ///
+    /// Escaped markers (`\$0`) are unescaped and don't denote a position.
+    pub fn range_or_offset(&self) -> (Option<RangeOrOffset>, String) {
+        if !self.text.contains(CURSOR_MARKER) {
+            return (None, self.text.clone());
+        }
+        if self.text.contains(ESCAPED_CURSOR_MARKER) {
+            return (None, self.text.replace(ESCAPED_CURSOR_MARKER, CURSOR_MARKER));
+        }
+        let (range_or_offset, text) = extract_range_or_offset(&self.text);
+        (Some(range_or_offset), text)
+    }
+
+    /// Returns the `//^^^ text` annotations of this file, see
+    /// [`extract_annotations`]. The ranges are relative to the text returned by
+    /// [`Fixture::range_or_offset`].
+    pub fn annotations(&self) -> Vec<(TextRange, String)> {
+        let (_, text) = self.range_or_offset();
+        extract_annotations(&text)
     }
 
     //- /lib.rs crate:foo deps:bar,baz cfg:foo=a,bar=b env:OUTDIR=path/to,OTHER=foo
-    fn parse_meta_line(meta: &str) -> Fixture {
+    fn parse_meta_line(meta: &str) -> Result<Fixture, FixtureError> {
         assert!(meta.starts_with("//-"));
         let meta = meta["//-".len()..].trim();
-        let components = meta.split_ascii_whitespace().collect::<Vec<_>>();
+        let components = split_unquoted(meta, |c| c.is_ascii_whitespace())
+            .into_iter()
+            .filter(|it| !it.is_empty())
+            .collect::<Vec<_>>();
 
-        let path = components[0].to_string();
-        assert!(path.starts_with('/'), "fixture path does not start with `/`: {:?}", path);
+        let path = match components.first() {
+            Some(it) => it.to_string(),
+            None => return Err(FixtureError::InvalidMetaLine(meta.to_string())),
+        };
+        if !path.starts_with('/') {
+            return Err(FixtureError::InvalidPath(path));
+        }
 
         let mut krate = None;
         let mut deps = Vec::new();
         let mut edition = None;
+        let mut toolchain = None;
         let mut cfg_atoms = Vec::new();
         let mut cfg_key_values = Vec::new();
+        let mut features = Vec::new();
+        let mut dep_features = Vec::new();
+        let mut target = None;
         let mut env = FxHashMap::default();
+        let mut crate_attrs = Vec::new();
+        let mut is_proc_macro = false;
         let mut introduce_new_source_root = false;
+        let mut is_library = false;
+        let mut bytes = None;
         for component in components[1..].iter() {
-            let mut splitted = component.split(':');
-            let key: &str;
-            let value: &str;
-            match splitted.next() {
-                Some(key_part) => {
-                    match splitted.next() {
-                        Some(value_part) => {
-                            key = key_part;
-                            value = value_part;
-                        }
-                        None => {panic!("invalid meta line: {:?}", meta);}
-                   }
-                }
-                None => {panic!("invalid meta line: {:?}", meta);}
-            }
+            let (key, value) = match split_once(component, ':') {
+                Some(it) => it,
+                // Flags don't need a value.
+                None if matches!(*component, "library" | "new_source_root") => (*component, ""),
+                None => return Err(FixtureError::InvalidMetaLine(meta.to_string())),
+            };
             match key {
                 "crate" => krate = Some(value.to_string()),
-                "deps" => deps = value.split(',').map(|it| it.to_string()).collect(),
+                "deps" => {
+                    for dep in value.split(',') {
+                        match FixtureDep::parse(dep) {
+                            Some(it) => deps.push(it),
+                            None => return Err(FixtureError::InvalidDependency(dep.to_string())),
+                        }
+                    }
+                }
                 "edition" => edition = Some(value.to_string()),
+                "toolchain" => toolchain = Some(value.to_string()),
                 "cfg" => {
                     for entry in value.split(',') {
                         match split_once(entry, '=') {
@@ -192,33 +521,634 @@ impl Fixture {
                         }
                     }
                 }
+                "features" => {
+                    for entry in value.split(',') {
+                        match split_once(entry, '/') {
+                            Some((dep, feature)) => {
+                                dep_features.push((dep.to_string(), feature.to_string()))
+                            }
+                            None => {
+                                cfg_key_values.push(("feature".to_string(), entry.to_string()));
+                                features.push(entry.to_string());
+                            }
+                        }
+                    }
+                }
+                "target" => {
+                    let (atoms, key_values) = match target_cfgs(value) {
+                        Some(it) => it,
+                        None => return Err(FixtureError::InvalidTarget(value.to_string())),
+                    };
+                    cfg_atoms.extend(atoms);
+                    cfg_key_values.extend(key_values);
+                    target = Some(value.to_string());
+                }
                 "env" => {
-                    for key in value.split(',') {
-                        if let Some((k, v)) = split_once(key, '=') {
-                            env.insert(k.into(), v.into());
+                    for entry in split_unquoted(value, |c| c == ',') {
+                        if let Some((k, v)) = split_once(entry, '=') {
+                            let v = match unquote(v) {
+                                Some(it) => it,
+                                None => {
+                                    return Err(FixtureError::InvalidMetaLine(meta.to_string()))
+                                }
+                            };
+                            env.insert(k.into(), v);
                         }
                     }
                 }
+                "crate-attr" => {
+                    for entry in value.split(',') {
+                        match split_once(entry, '=') {
+                            Some((k, v)) if v.starts_with('"') => {
+                                crate_attrs.push(format!("{} = {}", k, v))
+                            }
+                            Some((k, v)) => crate_attrs.push(format!("{}({})", k, v)),
+                            None => crate_attrs.push(entry.to_string()),
+                        }
+                    }
+                }
+                "crate-type" => match value {
+                    "lib" => is_proc_macro = false,
+                    "proc-macro" => is_proc_macro = true,
+                    _ => return Err(FixtureError::UnknownMetaKey(component.to_string())),
+                },
                 "new_source_root" => introduce_new_source_root = true,
-                _ => panic!("bad component: {:?}", component),
+                "library" => is_library = true,
+                // Decoded once the text of the file is known.
+                "binary" if value == "base64" => bytes = Some(Vec::new()),
+                "binary" => return Err(FixtureError::InvalidBinary(path)),
+                _ => return Err(FixtureError::UnknownMetaKey(component.to_string())),
             }
         }
 
-        Fixture {
+        Ok(Fixture {
             path,
             text: String::new(),
             krate,
             deps,
             cfg_atoms,
             cfg_key_values,
+            features,
+            dep_features,
+            target,
             edition,
+            toolchain,
             env,
+            crate_attrs,
+            is_proc_macro,
             introduce_new_source_root,
+            is_library,
+            real_sysroot: false,
+            bytes,
+        })
+    }
+}
+
+/// Splits `text` at the characters matching `is_sep`, except for the ones
+/// inside double quotes.
+fn split_unquoted(text: &str, is_sep: impl Fn(char) -> bool) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if !in_quotes && is_sep(c) => {
+                res.push(&text[start..idx]);
+                start = idx + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+    res.push(&text[start..]);
+    res
+}
+
+/// Removes the double quotes around `value`, if any, unescaping `\"` and
+/// `\\`. Other backslashes are kept as is, for the sake of Windows paths.
+fn unquote(value: &str) -> Option<String> {
+    let inner = match value.strip_prefix('"') {
+        Some(it) => it.strip_suffix('"')?,
+        None if value.contains('"') => return None,
+        None => return Some(value.to_string()),
+    };
+    let mut res = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '"' => res.push('"'),
+                '\\' => res.push('\\'),
+                c => {
+                    res.push('\\');
+                    res.push(c);
+                }
+            },
+            '"' => return None,
+            c => res.push(c),
+        }
+    }
+    Some(res)
+}
+
+/// The inverse of [`unquote`].
+fn quote(value: &str) -> String {
+    if !value.contains(|c: char| c == ',' || c == '=' || c == '"' || c.is_whitespace()) {
+        return value.to_string();
+    }
+    let mut res = String::from("\"");
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // Lone backslashes are kept verbatim, to keep Windows paths readable.
+            '\\' if matches!(chars.peek(), None | Some('"') | Some('\\')) => res.push_str("\\\\"),
+            '"' => res.push_str("\\\""),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Computes the cfgs rustc sets for the `triple` target, approximately.
+///
+/// The triple is expected to be `arch-vendor-os[-env]`, `arch-os-env` (like
+/// `arm-linux-androideabi`) is understood as well.
+fn target_cfgs(triple: &str) -> Option<(Vec<String>, Vec<(String, String)>)> {
+    let components = triple.split('-').collect::<Vec<_>>();
+    let (arch, vendor, os, env) = match components.as_slice() {
+        [arch, os @ "linux", env] => (*arch, "unknown", *os, *env),
+        [arch, vendor, os] => (*arch, *vendor, *os, ""),
+        [arch, vendor, os, env] => (*arch, *vendor, *os, *env),
+        _ => return None,
+    };
+
+    let arch = match arch {
+        "i386" | "i586" | "i686" => "x86",
+        "riscv64gc" | "riscv64imac" => "riscv64",
+        "riscv32i" | "riscv32imc" | "riscv32imac" => "riscv32",
+        "powerpc64le" => "powerpc64",
+        "mipsel" => "mips",
+        "mips64el" => "mips64",
+        it if it.starts_with("armv") || it.starts_with("thumbv") => "arm",
+        it => it,
+    };
+    let pointer_width = match arch {
+        "x86_64" | "aarch64" | "riscv64" | "powerpc64" | "mips64" | "s390x" | "sparc64"
+        | "wasm64" => "64",
+        "msp430" | "avr" => "16",
+        _ => "32",
+    };
+    let big_endian =
+        matches!(components[0], "powerpc" | "powerpc64" | "mips" | "mips64" | "s390x" | "sparc64");
+
+    let (os, env) = match (os, env) {
+        ("darwin", _) => ("macos", ""),
+        ("linux", env) if env.starts_with("android") => ("android", ""),
+        ("linux", env) if env.starts_with("gnu") => ("linux", "gnu"),
+        ("linux", env) if env.starts_with("musl") => ("linux", "musl"),
+        ("windows", env) if env.starts_with("gnu") => ("windows", "gnu"),
+        (os, env) if env.starts_with("eabi") => (os, ""),
+        (os, env) => (os, env),
+    };
+    let family = match os {
+        "windows" => Some("windows"),
+        "linux" | "android" | "macos" | "ios" | "freebsd" | "netbsd" | "openbsd" | "dragonfly"
+        | "solaris" | "illumos" | "haiku" | "redox" => Some("unix"),
+        _ if arch.starts_with("wasm") => Some("wasm"),
+        _ => None,
+    };
+
+    let mut atoms = Vec::new();
+    let mut key_values = vec![
+        ("target_arch", arch),
+        ("target_vendor", vendor),
+        ("target_os", os),
+        ("target_env", env),
+        ("target_pointer_width", pointer_width),
+        ("target_endian", if big_endian { "big" } else { "little" }),
+    ];
+    if let Some(family) = family {
+        key_values.push(("target_family", family));
+        if family != "wasm" {
+            atoms.push(family.to_string());
+        }
+    }
+    let key_values = key_values.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    Some((atoms, key_values))
+}
+
+/// Renders `fixtures` back into the `//-` fixture syntax, such that
+/// [`Fixture::parse`] returns equivalent fixtures.
+///
+/// Fixture-wide declarations are not rendered, use the `Display` impl of
+/// [`MiniCore`] to render the `//- minicore:` line.
+pub fn render_fixtures(fixtures: &[Fixture]) -> String {
+    fixtures.iter().map(|it| it.to_string()).collect()
+}
+
+/// Builds fixtures programmatically, producing the same [`Fixture`]s as the
+/// `//-` syntax does. Useful to generate many files without formatting them
+/// into a string first.
+///
+/// Methods configuring a file apply to the last added one.
+#[derive(Debug, Default)]
+pub struct FixtureBuilder {
+    mini_core: Option<MiniCore>,
+    fixtures: Vec<Fixture>,
+}
+
+impl FixtureBuilder {
+    pub fn new() -> FixtureBuilder {
+        FixtureBuilder::default()
+    }
+
+    pub fn file(&mut self, path: &str, text: &str) -> &mut FixtureBuilder {
+        assert!(path.starts_with('/'), "fixture path does not start with `/`: {:?}", path);
+        let mut text = text.to_string();
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.fixtures.push(Fixture { path: path.to_string(), text, ..Fixture::default() });
+        self
+    }
+
+    /// Makes the last file the root of the crate `name`.
+    pub fn krate(&mut self, name: &str) -> &mut FixtureBuilder {
+        self.last().krate = Some(name.to_string());
+        self
+    }
+
+    /// Adds a dependency of the crate `from` on the crate `to`.
+    pub fn dep(&mut self, from: &str, to: &str) -> &mut FixtureBuilder {
+        let krate = self.fixtures.iter_mut().find(|it| it.krate.as_deref() == Some(from));
+        let krate = krate.unwrap_or_else(|| panic!("no crate named {:?}", from));
+        krate.deps.push(FixtureDep { name: to.to_string(), rename: None, optional: false });
+        self
+    }
+
+    /// Adds a cfg to the last file, either an atom or a `key=value` pair.
+    pub fn cfg(&mut self, cfg: &str) -> &mut FixtureBuilder {
+        let fixture = self.last();
+        match split_once(cfg, '=') {
+            Some((k, v)) => fixture.cfg_key_values.push((k.to_string(), v.to_string())),
+            None => fixture.cfg_atoms.push(cfg.to_string()),
+        }
+        self
+    }
+
+    pub fn edition(&mut self, edition: &str) -> &mut FixtureBuilder {
+        self.last().edition = Some(edition.to_string());
+        self
+    }
+
+    /// Activates the `minicore` flags `flags`, like a `//- minicore:` line.
+    pub fn minicore(&mut self, flags: &[&str]) -> &mut FixtureBuilder {
+        let line = format!("//- minicore: {}", flags.join(", "));
+        let mini_core = MiniCore::parse(&line).unwrap_or_else(|err| panic!("{}", err));
+        self.mini_core = Some(mini_core);
+        self
+    }
+
+    /// Returns the `minicore` and the files built so far, leaving the builder
+    /// empty.
+    pub fn finish(&mut self) -> (Option<MiniCore>, Vec<Fixture>) {
+        (self.mini_core.take(), mem::take(&mut self.fixtures))
+    }
+
+    fn last(&mut self) -> &mut Fixture {
+        self.fixtures.last_mut().expect("no file was added to the fixture")
+    }
+}
+
+impl fmt::Display for Fixture {
+    /// Renders the meta line of the fixture, followed by its text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "//- {}", self.path)?;
+        if let Some(krate) = &self.krate {
+            write!(f, " crate:{}", krate)?;
+        }
+        if !self.deps.is_empty() {
+            let deps = self.deps.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+            write!(f, " deps:{}", deps.join(","))?;
+        }
+        if let Some(edition) = &self.edition {
+            write!(f, " edition:{}", edition)?;
+        }
+        if let Some(toolchain) = &self.toolchain {
+            write!(f, " toolchain:{}", toolchain)?;
+        }
+
+        // Cfgs implied by `target:` and `features:` are rendered as such.
+        let mut cfg_atoms = self.cfg_atoms.clone();
+        let mut cfg_key_values = self.cfg_key_values.clone();
+        let mut implied_key_values =
+            self.features.iter().map(|it| ("feature".to_string(), it.clone())).collect::<Vec<_>>();
+        if let Some((atoms, key_values)) = self.target.as_deref().and_then(target_cfgs) {
+            for atom in atoms {
+                if let Some(idx) = cfg_atoms.iter().position(|it| *it == atom) {
+                    cfg_atoms.remove(idx);
+                }
+            }
+            implied_key_values.extend(key_values);
+        }
+        for key_value in implied_key_values {
+            if let Some(idx) = cfg_key_values.iter().position(|it| *it == key_value) {
+                cfg_key_values.remove(idx);
+            }
+        }
+        let cfgs = cfg_atoms
+            .iter()
+            .cloned()
+            .chain(cfg_key_values.iter().map(|(k, v)| format!("{}={}", k, v)))
+            .collect::<Vec<_>>();
+        if !cfgs.is_empty() {
+            write!(f, " cfg:{}", cfgs.join(","))?;
+        }
+
+        let features = self
+            .features
+            .iter()
+            .cloned()
+            .chain(self.dep_features.iter().map(|(dep, feature)| format!("{}/{}", dep, feature)))
+            .collect::<Vec<_>>();
+        if !features.is_empty() {
+            write!(f, " features:{}", features.join(","))?;
+        }
+        if let Some(target) = &self.target {
+            write!(f, " target:{}", target)?;
+        }
+        if !self.env.is_empty() {
+            let mut env =
+                self.env.iter().map(|(k, v)| format!("{}={}", k, quote(v))).collect::<Vec<_>>();
+            env.sort();
+            write!(f, " env:{}", env.join(","))?;
+        }
+        if !self.crate_attrs.is_empty() {
+            let attrs = self
+                .crate_attrs
+                .iter()
+                .map(|attr| {
+                    let key_value = split_once(attr, " = ")
+                        .or_else(|| attr.strip_suffix(')').and_then(|it| split_once(it, '(')));
+                    match key_value {
+                        Some((key, value)) => format!("{}={}", key, value),
+                        None => attr.clone(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            write!(f, " crate-attr:{}", attrs.join(","))?;
+        }
+        if self.is_proc_macro {
+            write!(f, " crate-type:proc-macro")?;
+        }
+        if self.introduce_new_source_root {
+            write!(f, " new_source_root")?;
+        }
+        if self.is_library {
+            write!(f, " library")?;
+        }
+        if self.bytes.is_some() {
+            write!(f, " binary:base64")?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Decodes standard base64 with padding, ignoring whitespace.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        let res = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(res as u32)
+    }
+
+    let text = text.bytes().filter(|it| !it.is_ascii_whitespace()).collect::<Vec<_>>();
+    if text.len() % 4 != 0 {
+        return None;
+    }
+    let mut res = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&it| it == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut acc = 0;
+        for &c in &chunk[..4 - padding] {
+            acc = acc << 6 | value(c)?;
+        }
+        acc <<= 6 * padding;
+        let decoded = [(acc >> 16) as u8, (acc >> 8) as u8, acc as u8];
+        res.extend_from_slice(&decoded[..3 - padding]);
+    }
+    Some(res)
+}
+
+/// Returns the contents of the first line of `fixture` if it is a fixture-wide
+/// `//- key: value` declaration rather than the meta line of a file.
+fn fixture_header(fixture: &str) -> Option<&str> {
+    let line = fixture.strip_prefix("//-")?.split('\n').next().unwrap().trim();
+    let key = line.split_ascii_whitespace().next()?;
+    if key.starts_with('/') || !(key.contains(':') || key == "auto_mods") {
+        return None;
+    }
+    Some(line)
+}
+
+/// Replaces `//- include: path` lines with the contents of the fixture files
+/// they refer to, `included` holding the files being currently expanded.
+fn expand_includes(fixture: &str, included: &mut Vec<String>) -> Result<String, FixtureError> {
+    if !fixture.contains("//- include:") {
+        return Ok(fixture.to_string());
+    }
+    let mut res = String::with_capacity(fixture.len());
+    for line in lines_with_ends(fixture) {
+        let path = match line.strip_prefix("//- include:") {
+            Some(it) => it.trim(),
+            None => {
+                res.push_str(line);
+                continue;
+            }
+        };
+        if included.iter().any(|it| it == path) {
+            return Err(FixtureError::InvalidInclude(path.to_string()));
+        }
+        let text = env::var("CARGO_MANIFEST_DIR")
+            .ok()
+            .and_then(|dir| fs::read_to_string(Path::new(&dir).join(path)).ok())
+            .ok_or_else(|| FixtureError::InvalidInclude(path.to_string()))?;
+
+        included.push(path.to_string());
+        res.push_str(&expand_includes(&trim_indent(&text), included)?);
+        included.pop();
+        if !res.ends_with('\n') {
+            res.push('\n');
+        }
+    }
+    Ok(res)
+}
+
+/// Implements `//- auto_mods`: declares every non-root file as a module of
+/// its crate, adding the missing intermediate module files.
+fn add_module_declarations(res: &mut Vec<Fixture>) -> Result<(), FixtureError> {
+    let is_crate_root = |entry: &Fixture| {
+        entry.krate.is_some() || entry.path == "/main.rs" || entry.path == "/lib.rs"
+    };
+    let dir = |path: &str| path[..path.rfind('/').unwrap() + 1].to_string();
+    let roots = res
+        .iter()
+        .filter(|it| is_crate_root(it))
+        .map(|it| (dir(&it.path), it.path.clone()))
+        .collect::<Vec<_>>();
+
+    // New files are pushed to `res`, so that their own parents are handled
+    // by the subsequent iterations.
+    let mut idx = 0;
+    while idx < res.len() {
+        if is_crate_root(&res[idx]) {
+            idx += 1;
+            continue;
+        }
+        let path = res[idx].path.clone();
+
+        // Find the crate root with the deepest directory containing the file.
+        let candidates = roots.iter().filter(|(dir, _)| path.starts_with(dir.as_str()));
+        let (root_dir, root_path) = match candidates.clone().max_by_key(|(dir, _)| dir.len()) {
+            Some(it) => it,
+            None => {
+                idx += 1;
+                continue;
+            }
+        };
+        if candidates.filter(|(dir, _)| dir == root_dir).count() > 1 {
+            return Err(FixtureError::AmbiguousCrate(path));
+        }
+
+        let relative = &path[root_dir.len()..];
+        let module = relative.strip_suffix("/mod.rs").or_else(|| relative.strip_suffix(".rs"));
+        let (parent, name) = match module {
+            Some(module) => match module.rfind('/') {
+                Some(slash) => (Some(&module[..slash]), &module[slash + 1..]),
+                None => (None, module),
+            },
+            None => {
+                idx += 1;
+                continue;
+            }
+        };
+
+        let parent_path = match parent {
+            None => root_path.clone(),
+            Some(parent) => {
+                let file = format!("{}{}.rs", root_dir, parent);
+                let mod_rs = format!("{}{}/mod.rs", root_dir, parent);
+                match res.iter().find(|it| it.path == file || it.path == mod_rs) {
+                    Some(it) => it.path.clone(),
+                    None => {
+                        let is_library = res[idx].is_library;
+                        res.push(Fixture { path: file.clone(), is_library, ..Fixture::default() });
+                        file
+                    }
+                }
+            }
+        };
+
+        let declaration = format!("mod {};", name);
+        let parent = res.iter_mut().find(|it| it.path == parent_path).unwrap();
+        if !parent.text.contains(&declaration) {
+            if !parent.text.is_empty() && !parent.text.ends_with('\n') {
+                parent.text.push('\n');
+            }
+            parent.text.push_str(&format!("pub {}\n", declaration));
+        }
+        idx += 1;
+    }
+    Ok(())
+}
+
+/// A minicore flag, as declared in the preamble of `minicore.rs`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FlagInfo {
+    pub name: String,
+    /// Flags which are implicitly activated by this one.
+    pub deps: Vec<String>,
+    pub description: String,
+    /// Lang items defined under this flag.
+    pub lang_items: Vec<String>,
+}
+
+/// Parses the `//!` preamble of `minicore.rs` and returns the declared flags
+/// along with the remaining lines of the file.
+fn parse_preamble() -> (Vec<FlagInfo>, std::str::Split<'static, char>) {
+    let raw_mini_core = include_str!("./minicore.rs");
+    let mut lines = raw_mini_core.split('\n');
+
+    let mut parsing_flags = false;
+    let mut flags = Vec::new();
+
+    for line in lines.by_ref() {
+        let line = match line.strip_prefix("//!") {
+            Some(it) => it,
+            None => {
+                assert!(line.trim().is_empty());
+                break;
+            }
+        };
+
+        if parsing_flags {
+            // `flag: dep1, dep2 -- description`
+            let (decl, description) = match line.find("--") {
+                Some(idx) => (&line[..idx], &line[idx + "--".len()..]),
+                None => (line, ""),
+            };
+            let (name, deps) = split_once(decl, ':').unwrap_or((decl, ""));
+            flags.push(FlagInfo {
+                name: name.trim().to_string(),
+                deps: deps
+                    .split(", ")
+                    .map(|it| it.trim().to_string())
+                    .filter(|it| !it.is_empty())
+                    .collect(),
+                description: description.trim().to_string(),
+                lang_items: Vec::new(),
+            });
+        }
+
+        if line.contains("Available flags:") {
+            parsing_flags = true;
         }
     }
+
+    (flags, lines)
+}
+
+impl fmt::Display for MiniCore {
+    /// Renders the `//- minicore:` line this was parsed from.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "//- minicore: {}", self.activated_flags.join(", "))
+    }
 }
 
 impl MiniCore {
+    /// Returns the flags listed on the `//- minicore:` line, without the ones
+    /// they imply.
+    pub fn activated_flags(&self) -> &[String] {
+        &self.activated_flags
+    }
+
     fn has_flag(&self, flag: &str) -> bool {
         self.activated_flags.iter().any(|it| it == flag)
     }
@@ -230,18 +1160,56 @@ impl MiniCore {
         }
     }
 
-    fn parse(line: &str) -> MiniCore {
+    fn parse(line: &str) -> Result<MiniCore, FixtureError> {
         let mut res = MiniCore { activated_flags: Vec::new(), valid_flags: Vec::new() };
 
-        let line = line.strip_prefix("//- minicore:").unwrap().trim();
+        let line = match line.strip_prefix("//- minicore:") {
+            Some(it) => it.trim(),
+            None => return Err(FixtureError::InvalidMetaLine(line.trim().to_string())),
+        };
         for entry in line.split(", ") {
             if res.has_flag(entry) {
-                panic!("duplicate minicore flag: {:?}", entry)
+                return Err(FixtureError::DuplicateMiniCoreFlag(entry.to_string()));
             }
             res.activated_flags.push(entry.to_string())
         }
 
-        res
+        Ok(res)
+    }
+
+    /// Returns all the flags declared in the preamble of `minicore.rs`, in
+    /// declaration order.
+    pub fn available_flags() -> Vec<FlagInfo> {
+        let (mut flags, lines) = parse_preamble();
+
+        let mut active_regions: Vec<&str> = Vec::new();
+        for line in lines {
+            let trimmed = line.trim();
+            if let Some(region) = trimmed.strip_prefix("// region:") {
+                active_regions.push(region);
+                continue;
+            }
+            if trimmed.starts_with("// endregion:") {
+                active_regions.pop().unwrap();
+                continue;
+            }
+
+            let region = match trimmed.find("// :") {
+                Some(idx) => Some(&trimmed[idx + "// :".len()..]),
+                None => active_regions.last().copied(),
+            };
+            let lang_item = trimmed
+                .strip_prefix("#[lang = \"")
+                .and_then(|it| it.split('"').next())
+                .map(|it| it.to_string());
+            if let (Some(region), Some(lang_item)) = (region, lang_item) {
+                if let Some(flag) = flags.iter_mut().find(|it| it.name == region) {
+                    flag.lang_items.push(lang_item);
+                }
+            }
+        }
+
+        flags
     }
 
     /// Strips parts of minicore.rs which are flagged by inactive flags.
@@ -249,56 +1217,14 @@ impl MiniCore {
     /// This is probably over-engineered to support flags dependencies.
     pub fn source_code(mut self) -> String {
         let mut buf = String::new();
-        let raw_mini_core = include_str!("./minicore.rs");
-        let mut lines = raw_mini_core.split('\n');
+        let (flags, lines) = parse_preamble();
 
-        let mut parsing_flags = false;
         let mut implications = Vec::new();
-
-        // Parse `//!` preamble and extract flags and dependencies.
-        for line in lines.by_ref() {
-            let line = match line.strip_prefix("//!") {
-                Some(it) => it.to_string() + "\n",
-                None => {
-                    assert!(line.trim().is_empty());
-                    break;
-                }
-            };
-
-            if parsing_flags {
-                let flag: String;
-                let deps: String;
-                let mut splitted = line.split(':');
-                match splitted.next() {
-                    Some(flag_v) => {
-                        flag = flag_v.to_owned();
-                        match splitted.next() {
-                            Some(deps_v) => {
-                                deps = deps_v.to_owned();
-                            },
-                            None => {
-                                deps = "".to_owned();
-                            }
-                        }
-                    },
-                    None => {
-                        flag = "".to_owned();
-                        deps = "".to_owned();
-                    }
-                }
-                let flag = flag.trim();
-                self.valid_flags.push(flag.to_string().to_owned());
-                for dep in deps.split(", ") {
-                    let dep = dep.trim();
-                    if !dep.is_empty() {
-                        self.assert_valid_flag(&dep);
-                        implications.push((flag.to_owned(), dep.to_owned()));
-                    }
-                }
-            }
-
-            if line.contains("Available flags:") {
-                parsing_flags = true;
+        for flag in flags {
+            self.valid_flags.push(flag.name.clone());
+            for dep in flag.deps {
+                self.assert_valid_flag(&dep);
+                implications.push((flag.name.clone(), dep));
             }
         }
 
@@ -370,6 +1296,36 @@ impl MiniCore {
     }
 }
 
+#[test]
+fn minicore_available_flags() {
+    let flags = MiniCore::available_flags();
+    let flag = |name: &str| flags.iter().find(|it| it.name == name).unwrap();
+
+    let ord = flag("ord");
+    assert_eq!(ord.deps, vec!["eq".to_string(), "option".to_string()]);
+    assert!(!ord.description.is_empty());
+    assert!(ord.lang_items.contains(&"partial_ord".to_string()));
+
+    assert!(flag("sized").deps.is_empty());
+    assert_eq!(flag("deref_mut").lang_items, vec!["deref_mut".to_string()]);
+    assert!(flags.iter().all(|it| !it.description.is_empty()));
+
+    let all = flags.iter().map(|it| it.name.clone()).collect();
+    let source = MiniCore { activated_flags: all, valid_flags: Vec::new() }.source_code();
+    assert!(source.contains("pub trait Sized"));
+}
+
+#[test]
+fn minicore_flag_implications() {
+    let source =
+        MiniCore { activated_flags: vec!["into_future".to_string()], valid_flags: Vec::new() }
+            .source_code();
+    assert!(source.contains("pub trait IntoFuture"));
+    assert!(source.contains("pub trait Future"));
+    assert!(source.contains("pub enum Poll"));
+    assert!(!source.contains("pub enum Option"));
+}
+
 #[test]
 #[should_panic]
 fn parse_fixture_checks_further_indented_metadata() {
@@ -387,14 +1343,16 @@ fn parse_fixture_checks_further_indented_metadata() {
 
 #[test]
 fn parse_fixture_gets_full_meta() {
-    let (mini_core, parsed) = Fixture::parse(
+    let (mini_core, proc_macros, parsed) = Fixture::parse(
         r#"
+//- proc_macros: identity
 //- minicore: coerce_unsized
 //- /lib.rs crate:foo deps:bar,baz cfg:foo=a,bar=b,atom env:OUTDIR=path/to,OTHER=foo
 mod m;
 "#,
     );
     assert_eq!(mini_core.unwrap().activated_flags, vec!["coerce_unsized".to_string()]);
+    assert_eq!(proc_macros, vec!["identity".to_string()]);
     assert_eq!(1, parsed.len());
 
     let meta = &parsed[0];
@@ -404,3 +1362,383 @@ mod m;
     assert_eq!("/lib.rs", meta.path);
     assert_eq!(2, meta.env.len());
 }
+
+#[test]
+fn parse_fixture_gets_quoted_env() {
+    let fixture = r#"
+//- /lib.rs env:FLAGS="-C opt=2",OUTDIR="C:\a,b",PLAIN=x,QUOTE="say \"hi\"",TRAILING="a,b\\"
+"#;
+    let (_, _, parsed) = Fixture::parse(fixture);
+    let env = &parsed[0].env;
+    assert_eq!(env["OUTDIR"], r"C:\a,b");
+    assert_eq!(env["FLAGS"], "-C opt=2");
+    assert_eq!(env["QUOTE"], r#"say "hi""#);
+    assert_eq!(env["PLAIN"], "x");
+    assert_eq!(env["TRAILING"], r"a,b\");
+
+    let rendered = render_fixtures(&parsed);
+    assert_eq!(rendered, trim_indent(fixture));
+
+    assert_eq!(
+        Fixture::try_parse("//- /lib.rs env:A=\"unterminated\n").err().unwrap(),
+        FixtureError::InvalidMetaLine("/lib.rs env:A=\"unterminated".to_string())
+    );
+}
+
+#[test]
+fn parse_fixture_gets_structured_deps() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main deps:foo,bar?,baz(rename=qux),quux(rename=corge)?
+"#,
+    );
+    let dep = |name: &str, rename: Option<&str>, optional| FixtureDep {
+        name: name.to_string(),
+        rename: rename.map(|it| it.to_string()),
+        optional,
+    };
+    assert_eq!(
+        parsed[0].deps,
+        vec![
+            dep("foo", None, false),
+            dep("bar", None, true),
+            dep("baz", Some("qux"), false),
+            dep("quux", Some("corge"), true),
+        ]
+    );
+    assert_eq!(parsed[0].deps[2].extern_name(), "qux");
+
+    let err = |fixture| Fixture::try_parse(fixture).err().unwrap();
+    assert_eq!(
+        err("//- /main.rs crate:main deps:foo(alias=bar)\n"),
+        FixtureError::InvalidDependency("foo(alias=bar)".to_string())
+    );
+    assert_eq!(
+        err("//- /main.rs crate:main deps:foo,,bar\n"),
+        FixtureError::InvalidDependency("".to_string())
+    );
+}
+
+#[test]
+fn parse_fixture_gets_crate_attrs() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /lib.rs crate:foo crate-attr:no_std,feature=never_type,recursion_limit="256"
+"#,
+    );
+    assert_eq!(
+        parsed[0].crate_attrs,
+        vec![
+            "no_std".to_string(),
+            "feature(never_type)".to_string(),
+            "recursion_limit = \"256\"".to_string()
+        ]
+    );
+    assert_eq!(
+        parsed[0].to_string(),
+        "//- /lib.rs crate:foo crate-attr:no_std,feature=never_type,recursion_limit=\"256\"\n"
+    );
+}
+
+#[test]
+fn fixture_builder_matches_parser() {
+    let mut builder = FixtureBuilder::new();
+    builder.minicore(&["sized", "fn"]);
+    builder.file("/main.rs", "fn main() {}").krate("main").cfg("test").cfg("opt=2");
+    for i in 0..3 {
+        let name = format!("dep{}", i);
+        builder.file(&format!("/{}/lib.rs", name), "").krate(&name).edition("2021");
+        builder.dep("main", &name);
+    }
+    let (built_mini_core, built) = builder.finish();
+
+    let (mini_core, _, parsed) = Fixture::parse(
+        r#"
+//- minicore: sized, fn
+//- /main.rs crate:main deps:dep0,dep1,dep2 cfg:test,opt=2
+fn main() {}
+//- /dep0/lib.rs crate:dep0 edition:2021
+//- /dep1/lib.rs crate:dep1 edition:2021
+//- /dep2/lib.rs crate:dep2 edition:2021
+"#,
+    );
+    assert_eq!(built, parsed);
+    assert_eq!(built_mini_core.map(|it| it.to_string()), mini_core.map(|it| it.to_string()));
+    assert!(builder.finish().1.is_empty());
+}
+
+#[test]
+fn parse_fixture_includes_files() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main deps:serde
+use serde::Serialize;
+//- include: test_data/fixtures/fake_serde.rs
+"#,
+    );
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].text, "use serde::Serialize;\n");
+    assert_eq!(parsed[1].path, "/serde/lib.rs");
+    assert_eq!(parsed[1].krate.as_deref(), Some("serde"));
+
+    assert_eq!(
+        Fixture::try_parse("//- include: test_data/fixtures/missing.rs\n").err().unwrap(),
+        FixtureError::InvalidInclude("test_data/fixtures/missing.rs".to_string())
+    );
+}
+
+#[test]
+fn parse_fixture_applies_default_edition() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- edition: 2021
+//- /main.rs crate:main deps:dep
+fn main() {}
+//- /dep.rs crate:dep edition:2015
+pub fn f() {}
+"#,
+    );
+    assert_eq!(parsed[0].edition.as_deref(), Some("2021"));
+    assert_eq!(parsed[1].edition.as_deref(), Some("2015"));
+}
+
+#[test]
+fn parse_fixture_gets_toolchain() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- toolchain: nightly
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep toolchain:1.54
+"#,
+    );
+    assert_eq!(parsed[0].toolchain.as_deref(), Some("nightly"));
+    assert_eq!(parsed[1].toolchain.as_deref(), Some("1.54"));
+}
+
+#[test]
+fn parse_fixture_gets_real_sysroot() {
+    let (mini_core, _, parsed) = Fixture::parse(
+        r#"
+//- sysroot: real
+//- /main.rs crate:main deps:dep
+//- /dep.rs crate:dep
+"#,
+    );
+    assert!(mini_core.is_none());
+    assert!(parsed.iter().all(|it| it.real_sysroot));
+
+    let (_, _, parsed) = Fixture::parse("//- /main.rs\n");
+    assert!(!parsed[0].real_sysroot);
+    assert_eq!(
+        Fixture::try_parse("//- sysroot: fake\n").err().unwrap(),
+        FixtureError::UnknownHeader("sysroot: fake".to_string())
+    );
+}
+
+#[test]
+fn parse_fixture_gets_features() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main deps:serde features:std,serde/derive
+"#,
+    );
+    let meta = &parsed[0];
+    assert_eq!(meta.features, vec!["std".to_string()]);
+    assert_eq!(meta.cfg_key_values, vec![("feature".to_string(), "std".to_string())]);
+    assert_eq!(meta.dep_features, vec![("serde".to_string(), "derive".to_string())]);
+}
+
+#[test]
+fn parse_fixture_gets_target_cfgs() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main target:x86_64-pc-windows-msvc
+//- /lib.rs crate:lib target:aarch64-unknown-linux-gnu
+"#,
+    );
+    let cfgs = |meta: &Fixture| {
+        meta.cfg_key_values.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(parsed[0].target.as_deref(), Some("x86_64-pc-windows-msvc"));
+    assert_eq!(parsed[0].cfg_atoms, vec!["windows".to_string()]);
+    assert_eq!(
+        cfgs(&parsed[0]),
+        [
+            "target_arch=x86_64",
+            "target_vendor=pc",
+            "target_os=windows",
+            "target_env=msvc",
+            "target_pointer_width=64",
+            "target_endian=little",
+            "target_family=windows",
+        ]
+    );
+
+    assert_eq!(parsed[1].cfg_atoms, vec!["unix".to_string()]);
+    assert_eq!(
+        cfgs(&parsed[1]),
+        [
+            "target_arch=aarch64",
+            "target_vendor=unknown",
+            "target_os=linux",
+            "target_env=gnu",
+            "target_pointer_width=64",
+            "target_endian=little",
+            "target_family=unix",
+        ]
+    );
+}
+
+#[test]
+fn parse_fixture_gets_library_flag() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main deps:std
+//- /std/lib.rs crate:std library
+"#,
+    );
+    assert!(!parsed[0].is_library);
+    assert!(parsed[1].is_library);
+}
+
+#[test]
+fn parse_fixture_gets_crate_type() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs crate:main deps:macros,helpers
+//- /macros.rs crate:macros crate-type:proc-macro
+//- /helpers.rs crate:helpers crate-type:lib
+"#,
+    );
+    assert!(!parsed[0].is_proc_macro);
+    assert!(parsed[1].is_proc_macro);
+    assert!(!parsed[2].is_proc_macro);
+}
+
+#[test]
+fn fixture_markup() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs
+fn main() {
+    let $0x = 92;
+      //^ x
+}
+//- /foo.rs
+fn foo() {}
+ //^^^ foo
+ //  | multiline
+"#,
+    );
+    let (cursor, text) = parsed[0].range_or_offset();
+    assert_eq!(cursor, Some(RangeOrOffset::Offset(20.into())));
+    let annotations = parsed[0].annotations();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(&text[annotations[0].0], "x");
+
+    assert_eq!(parsed[1].range_or_offset().0, None);
+    let annotations = parsed[1].annotations();
+    assert_eq!(&parsed[1].text[annotations[0].0], "foo");
+    assert_eq!(annotations[0].1, "foo\nmultiline\n");
+}
+
+#[test]
+fn parse_fixture_auto_mods() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- auto_mods
+//- /main.rs
+fn main() {}
+//- /foo/bar/baz.rs
+pub fn baz() {}
+//- /qux/mod.rs
+mod quux;
+//- /qux/quux.rs
+"#,
+    );
+    let files = parsed.iter().map(|it| (it.path.as_str(), it.text.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [
+            ("/main.rs", "fn main() {}\npub mod qux;\npub mod foo;\n"),
+            ("/foo/bar/baz.rs", "pub fn baz() {}\n"),
+            ("/qux/mod.rs", "mod quux;\n"),
+            ("/qux/quux.rs", ""),
+            ("/foo/bar.rs", "pub mod baz;\n"),
+            ("/foo.rs", "pub mod bar;\n"),
+        ]
+    );
+}
+
+#[test]
+fn render_fixtures_roundtrip() {
+    let fixture = r#"
+//- minicore: sized, fn
+//- /main.rs crate:main deps:foo,bar?,baz(rename=qux) edition:2021 toolchain:nightly cfg:test,opt=2 env:A=a,B=b crate-attr:no_std,feature=never_type
+fn main() {}
+
+//- /foo.rs crate:foo cfg:atom features:std,bar/derive target:x86_64-unknown-linux-gnu new_source_root
+pub fn foo() {}
+//- /bar/lib.rs crate:bar crate-type:proc-macro library
+"#;
+    let (mini_core, _, parsed) = Fixture::parse(fixture);
+    let rendered = format!("{}{}", mini_core.unwrap(), render_fixtures(&parsed));
+    assert_eq!(rendered, trim_indent(fixture));
+
+    let (_, _, reparsed) = Fixture::parse(&rendered);
+    assert_eq!(parsed, reparsed);
+}
+
+#[test]
+fn parse_fixture_binary_files() {
+    let (_, _, parsed) = Fixture::parse(
+        r#"
+//- /main.rs
+//- /data.bin binary:base64
+AAH/
+//- /hello.bin binary:base64
+aGVsbG8=
+"#,
+    );
+    assert_eq!(parsed[0].text_or_bytes(), TextOrBytes::Text(""));
+    assert_eq!(parsed[1].text_or_bytes(), TextOrBytes::Bytes(&[0, 1, 255]));
+    assert_eq!(parsed[2].text_or_bytes(), TextOrBytes::Bytes(b"hello"));
+
+    let err = |fixture| Fixture::try_parse(fixture).err().unwrap();
+    assert_eq!(
+        err("//- /data.bin binary:base64\nnot base64\n"),
+        FixtureError::InvalidBinary("/data.bin".to_string())
+    );
+    assert_eq!(
+        err("//- /data.bin binary:hex\n00\n"),
+        FixtureError::InvalidBinary("/data.bin".to_string())
+    );
+}
+
+#[test]
+fn try_parse_fixture_reports_errors() {
+    let err = |fixture| Fixture::try_parse(fixture).err().unwrap();
+
+    assert_eq!(err("//- lib.rs\n"), FixtureError::InvalidPath("lib.rs".to_string()));
+    assert_eq!(
+        err("//- /lib.rs crate\n"),
+        FixtureError::InvalidMetaLine("/lib.rs crate".to_string())
+    );
+    assert_eq!(
+        err("//- /lib.rs create:foo\n"),
+        FixtureError::UnknownMetaKey("create:foo".to_string())
+    );
+    assert_eq!(
+        err("//- mincore: sized\n"),
+        FixtureError::UnknownHeader("mincore: sized".to_string())
+    );
+    assert_eq!(
+        err("//- minicore: sized, sized\n"),
+        FixtureError::DuplicateMiniCoreFlag("sized".to_string())
+    );
+    assert_eq!(
+        err("//- /lib.rs\n// crate: foo\n"),
+        FixtureError::SuspiciousMetaLine("// crate: foo\n".to_string())
+    );
+}
@@ -9,6 +9,7 @@
 pub mod bench_fixture;
 mod fixture;
 mod assert_linear;
+mod minicore_usage;
 
 use std::{
     collections::BTreeMap,
@@ -26,7 +27,11 @@ pub use rustc_hash::FxHashMap;
 
 pub use crate::{
     assert_linear::AssertLinear,
-    fixture::{Fixture, MiniCore},
+    fixture::{
+        render_fixtures, Fixture, FixtureBuilder, FixtureDep, FixtureError, FlagInfo, MiniCore,
+        TextOrBytes,
+    },
+    minicore_usage::{extract_fixtures, FixtureLiteral, MiniCoreProblem, MiniCoreUsage},
 };
 
 pub const CURSOR_MARKER: &str = "$0";
@@ -94,7 +99,7 @@ fn try_extract_range(text: &str) -> Option<(TextRange, String)> {
     Some((TextRange::new(start, end), text))
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RangeOrOffset {
     Range(TextRange),
     Offset(TextSize),
@@ -137,6 +142,76 @@ pub fn extract_range_or_offset(text: &str) -> (RangeOrOffset, String) {
     (RangeOrOffset::Offset(offset), text)
 }
 
+/// Returns offsets of all `$0` markers in `text` and the copy of `text` without
+/// the markers.
+pub fn extract_offsets(mut text: &str) -> (Vec<TextSize>, String) {
+    let mut offsets = Vec::new();
+    let mut res = String::with_capacity(text.len());
+    while let Some(idx) = text.find(CURSOR_MARKER) {
+        res.push_str(&text[..idx]);
+        offsets.push(TextSize::of(&res));
+        text = &text[idx + CURSOR_MARKER.len()..];
+    }
+    res.push_str(text);
+    (offsets, res)
+}
+
+#[test]
+fn test_extract_offsets() {
+    let (offsets, text) = extract_offsets("fn $0foo($0) {}$0");
+    assert_eq!(text, "fn foo() {}");
+    assert_eq!(offsets, vec![3.into(), 7.into(), 11.into()]);
+}
+
+/// Returns offsets of the `$name` markers for each of the `names` and the copy
+/// of `text` without these markers.
+///
+/// Names are either identifiers or numbers. Unlike `$0`, named markers are
+/// only recognized when asked for, as `$name` also is the syntax of
+/// `macro_rules!` metavariables.
+///
+/// # Panics
+/// Panics if a marker is present more than once.
+pub fn extract_named_offsets(
+    mut text: &str,
+    names: &[&str],
+) -> (FxHashMap<String, TextSize>, String) {
+    let mut offsets = FxHashMap::default();
+    let mut res = String::with_capacity(text.len());
+    while let Some(idx) = text.find('$') {
+        res.push_str(&text[..idx]);
+        text = &text[idx + '$'.len_utf8()..];
+        // Numbered markers like `$1` may be directly followed by an identifier.
+        let name_len = if text.starts_with(|c: char| c.is_ascii_digit()) {
+            text.find(|c: char| !c.is_ascii_digit())
+        } else {
+            text.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        }
+        .unwrap_or(text.len());
+        let name = &text[..name_len];
+        if names.contains(&name) {
+            let prev = offsets.insert(name.to_string(), TextSize::of(&res));
+            assert!(prev.is_none(), "marker `${}` is present more than once", name);
+            text = &text[name_len..];
+        } else {
+            res.push('$');
+        }
+    }
+    res.push_str(text);
+    (offsets, res)
+}
+
+#[test]
+fn test_extract_named_offsets() {
+    let (offsets, text) =
+        extract_named_offsets("fn $a$1foo($x: $0) {}$end", &["a", "1", "end", "unused"]);
+    assert_eq!(text, "fn foo($x: $0) {}");
+    assert_eq!(offsets.len(), 3);
+    assert_eq!(offsets["a"], 3.into());
+    assert_eq!(offsets["1"], 3.into());
+    assert_eq!(offsets["end"], 17.into());
+}
+
 /// Extracts ranges, marked with `<tag> </tag>` pairs from the `text`
 pub fn extract_tags(mut text: &str, tag: &str) -> (Vec<(TextRange, Option<String>)>, String) {
     let open = format!("<{}", tag);
@@ -8,28 +8,34 @@
 //! We then strip all the code marked with other flags.
 //!
 //! Available flags:
-//!     sized:
-//!     unsize: sized
-//!     coerce_unsized: unsize
-//!     slice:
-//!     range:
-//!     deref: sized
-//!     deref_mut: deref
-//!     index: sized
-//!     fn:
-//!     pin:
-//!     future: pin
-//!     option:
-//!     result:
-//!     iterator: option
-//!     iterators: iterator, fn
-//!     default: sized
-//!     clone: sized
-//!     copy: clone
-//!     from: sized
-//!     eq: sized
-//!     ord: eq, option
-//!     derive:
+//!     sized:                        -- `Sized` marker trait
+//!     unsize: sized                 -- `Unsize` marker trait
+//!     coerce_unsized: unsize        -- `CoerceUnsized` and unsizing coercions
+//!     slice:                        -- slice indexing and inherent methods
+//!     range:                        -- range types
+//!     deref: sized                  -- `Deref` trait
+//!     deref_mut: deref              -- `DerefMut` trait
+//!     index: sized                  -- `Index` and `IndexMut` traits
+//!     fn:                           -- `Fn`, `FnMut` and `FnOnce` traits
+//!     pin:                          -- `Pin` type
+//!     poll:                         -- `Poll` and `Context` types
+//!     future: pin, poll             -- `Future` trait and `ready`
+//!     into_future: future           -- `IntoFuture` trait
+//!     option:                       -- `Option` type
+//!     result:                       -- `Result` type
+//!     fmt: result                   -- `Debug`, `Display` and `Write` traits, `write!` and `format_args!`
+//!     error: fmt, option            -- `Error` trait, also as `std::error::Error`
+//!     iterator: option              -- `Iterator` and `IntoIterator` traits
+//!     iterators: iterator, fn       -- iterator adapters and sources, `collect` and `FromIterator`
+//!     default: sized                -- `Default` trait
+//!     clone: sized                  -- `Clone` trait
+//!     copy: clone                   -- `Copy` trait
+//!     from: sized                   -- `From` and `Into` traits
+//!     try_from: from, result        -- `TryFrom` trait
+//!     eq: sized                     -- `PartialEq` and `Eq` traits
+//!     ord: eq, option               -- `PartialOrd` and `Ord` traits, `Ordering`
+//!     derive:                       -- `derive` attribute and built-in derive macros
+//!     cell:                         -- `UnsafeCell` and `Cell` types
 
 pub mod marker {
     // region:sized
@@ -121,9 +127,30 @@ pub mod convert {
             t
         }
     }
+
+    // region:try_from
+    pub trait TryFrom<T>: Sized {
+        type Error;
+        fn try_from(value: T) -> crate::result::Result<Self, Self::Error>;
+    }
+    // endregion:try_from
 }
 // endregion:from
 
+// region:cell
+pub mod cell {
+    #[lang = "unsafe_cell"]
+    #[repr(transparent)]
+    pub struct UnsafeCell<T> {
+        value: T,
+    }
+
+    pub struct Cell<T> {
+        value: UnsafeCell<T>,
+    }
+}
+// endregion:cell
+
 pub mod ops {
     // region:coerce_unsized
     mod unsize {
@@ -353,6 +380,102 @@ pub mod result {
 }
 // endregion:result
 
+// region:fmt
+pub mod fmt {
+    pub struct Error;
+    pub type Result = crate::result::Result<(), Error>;
+
+    pub struct Formatter<'a> {
+        buf: &'a mut dyn Write,
+    }
+
+    impl Formatter<'_> {
+        pub fn write_str(&mut self, data: &str) -> Result {
+            loop {}
+        }
+        pub fn write_fmt(&mut self, fmt: Arguments<'_>) -> Result {
+            loop {}
+        }
+    }
+
+    #[lang = "format_arguments"]
+    pub struct Arguments<'a> {
+        pieces: &'a [&'static str],
+        args: &'a [ArgumentV1<'a>],
+    }
+
+    impl<'a> Arguments<'a> {
+        pub const fn new_v1(
+            pieces: &'a [&'static str],
+            args: &'a [ArgumentV1<'a>],
+        ) -> Arguments<'a> {
+            loop {}
+        }
+    }
+
+    pub struct ArgumentV1<'a> {
+        value: &'a (),
+        formatter: fn(&(), &mut Formatter<'_>) -> Result,
+    }
+
+    impl<'a> ArgumentV1<'a> {
+        pub fn new<'b, T>(x: &'b T, f: fn(&T, &mut Formatter<'_>) -> Result) -> ArgumentV1<'b> {
+            loop {}
+        }
+    }
+
+    pub trait Debug {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+
+    pub trait Display {
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result;
+    }
+
+    pub trait Write {
+        fn write_str(&mut self, s: &str) -> Result;
+        fn write_char(&mut self, c: char) -> Result {
+            loop {}
+        }
+        fn write_fmt(&mut self, args: Arguments<'_>) -> Result {
+            loop {}
+        }
+    }
+
+    pub(crate) mod macros {
+        #[rustc_builtin_macro]
+        pub macro Debug($item:item) {}
+    }
+    pub use macros::Debug;
+}
+
+#[macro_export]
+#[rustc_builtin_macro]
+macro_rules! format_args {
+    ($fmt:expr) => {{ /* compiler built-in */ }};
+    ($fmt:expr, $($args:tt)*) => {{ /* compiler built-in */ }};
+}
+
+#[macro_export]
+macro_rules! write {
+    ($dst:expr, $($arg:tt)*) => {
+        $dst.write_fmt($crate::format_args!($($arg)*))
+    };
+}
+// endregion:fmt
+
+// region:error
+pub mod error {
+    use crate::fmt::{Debug, Display};
+
+    pub trait Error: Debug + Display {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            None
+        }
+    }
+}
+// endregion:error
+
 // region:pin
 pub mod pin {
     #[lang = "pin"]
@@ -376,7 +499,42 @@ pub mod future {
         #[lang = "poll"]
         fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output>;
     }
+
+    pub struct Ready<T> {
+        value: T,
+    }
+
+    impl<T> Future for Ready<T> {
+        type Output = T;
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            loop {}
+        }
+    }
+
+    pub fn ready<T>(t: T) -> Ready<T> {
+        loop {}
+    }
+
+    // region:into_future
+    pub trait IntoFuture {
+        type Output;
+        type IntoFuture: Future<Output = Self::Output>;
+        #[lang = "into_future"]
+        fn into_future(self) -> Self::IntoFuture;
+    }
+
+    impl<F: Future> IntoFuture for F {
+        type Output = F::Output;
+        type IntoFuture = F;
+        fn into_future(self) -> F {
+            self
+        }
+    }
+    // endregion:into_future
 }
+// endregion:future
+
+// region:poll
 pub mod task {
     pub enum Poll<T> {
         #[lang = "Ready"]
@@ -389,7 +547,7 @@ pub mod task {
         waker: &'a (),
     }
 }
-// endregion:future
+// endregion:poll
 
 // region:iterator
 pub mod iter {
@@ -425,8 +583,40 @@ pub mod iter {
                 loop {}
             }
         }
+
+        pub struct Map<I, F> {
+            iter: I,
+            f: F,
+        }
+        impl<B, I: Iterator, F> Iterator for Map<I, F>
+        where
+            F: FnMut(I::Item) -> B,
+        {
+            type Item = B;
+
+            #[inline]
+            fn next(&mut self) -> Option<B> {
+                loop {}
+            }
+        }
+
+        pub struct Filter<I, P> {
+            iter: I,
+            predicate: P,
+        }
+        impl<I: Iterator, P> Iterator for Filter<I, P>
+        where
+            P: FnMut(&I::Item) -> bool,
+        {
+            type Item = I::Item;
+
+            #[inline]
+            fn next(&mut self) -> Option<I::Item> {
+                loop {}
+            }
+        }
     }
-    pub use self::adapters::Take;
+    pub use self::adapters::{Filter, FilterMap, Map, Take};
 
     mod sources {
         mod repeat {
@@ -479,6 +669,26 @@ pub mod iter {
                 {
                     loop {}
                 }
+                fn map<B, F>(self, f: F) -> crate::iter::Map<Self, F>
+                where
+                    Self: Sized,
+                    F: FnMut(Self::Item) -> B,
+                {
+                    loop {}
+                }
+                fn filter<P>(self, predicate: P) -> crate::iter::Filter<Self, P>
+                where
+                    Self: Sized,
+                    P: FnMut(&Self::Item) -> bool,
+                {
+                    loop {}
+                }
+                fn collect<B: crate::iter::FromIterator<Self::Item>>(self) -> B
+                where
+                    Self: Sized,
+                {
+                    loop {}
+                }
                 // endregion:iterators
             }
             impl<I: Iterator + ?Sized> Iterator for &mut I {
@@ -504,10 +714,21 @@ pub mod iter {
                     self
                 }
             }
+            // region:iterators
+            pub trait FromIterator<A>: Sized {
+                fn from_iter<T: IntoIterator<Item = A>>(iter: T) -> Self;
+            }
+            // endregion:iterators
         }
         pub use self::collect::IntoIterator;
+        // region:iterators
+        pub use self::collect::FromIterator;
+        // endregion:iterators
     }
     pub use self::traits::{IntoIterator, Iterator};
+    // region:iterators
+    pub use self::traits::FromIterator;
+    // endregion:iterators
 }
 // endregion:iterator
 
@@ -530,6 +751,7 @@ pub mod prelude {
             cmp::{Ord, PartialOrd},             // :ord
             convert::{From, Into},              // :from
             default::Default,                   // :default
+            fmt::macros::Debug,                 // :fmt
             iter::{IntoIterator, Iterator},     // :iterator
             macros::builtin::derive,            // :derive
             marker::Copy,                       // :copy
@@ -0,0 +1,251 @@
+//! Checks how test fixtures across the workspace use `minicore`.
+//!
+//! Fixtures are extracted from the raw string literals of arbitrary Rust files,
+//! so the check can run over the whole source tree (see the `check-minicore`
+//! subcommand and the `check_minicore_usage` tidy test of rust-analyzer).
+
+use std::fmt;
+
+use rustc_hash::FxHashSet;
+
+use crate::{Fixture, FlagInfo, MiniCore};
+
+/// A raw string literal which looks like a fixture, that is contains `//-`
+/// meta lines.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FixtureLiteral {
+    /// One-based line of the literal in the file.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Extracts the fixtures from the raw string literals of the Rust source `text`.
+pub fn extract_fixtures(text: &str) -> Vec<FixtureLiteral> {
+    let mut res = Vec::new();
+    let mut offset = 0;
+    while let Some(idx) = text[offset..].find('r') {
+        let start = offset + idx;
+        offset = start + 1;
+
+        let preceded_by_ident =
+            matches!(text[..start].chars().next_back(), Some(c) if c.is_alphanumeric() || c == '_');
+        if preceded_by_ident {
+            continue;
+        }
+        let hashes = text[offset..].len() - text[offset..].trim_start_matches('#').len();
+        if !text[offset + hashes..].starts_with('"') {
+            continue;
+        }
+        let content_start = offset + hashes + 1;
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let content_len = match text[content_start..].find(&terminator) {
+            Some(it) => it,
+            None => break,
+        };
+        let content = &text[content_start..content_start + content_len];
+        offset = content_start + content_len + terminator.len();
+
+        if content.lines().any(|line| line.trim_start().starts_with("//-")) {
+            let line = text[..start].matches('\n').count() + 1;
+            res.push(FixtureLiteral { line, text: content.to_string() });
+        }
+    }
+    res
+}
+
+/// A problem with the usage of `minicore`, found by [`MiniCoreUsage`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MiniCoreProblem {
+    /// A fixture activates a flag which `minicore.rs` doesn't declare.
+    UnknownFlag { location: String, flag: String },
+    /// No fixture activates the flag, directly or through other flags.
+    UnusedFlag { flag: String },
+    /// A fixture defines a lang item which is available from `minicore`.
+    LangItemInFixture { location: String, lang_item: String, flag: String },
+}
+
+impl fmt::Display for MiniCoreProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiniCoreProblem::UnknownFlag { location, flag } => {
+                write!(f, "{}: unknown minicore flag `{}`", location, flag)
+            }
+            MiniCoreProblem::UnusedFlag { flag } => {
+                write!(f, "minicore flag `{}` is not used by any fixture", flag)
+            }
+            MiniCoreProblem::LangItemInFixture { location, lang_item, flag } => write!(
+                f,
+                "{}: lang item `{}` can be replaced with the `{}` minicore flag",
+                location, lang_item, flag
+            ),
+        }
+    }
+}
+
+/// Collects the usage of `minicore` flags over a set of files.
+pub struct MiniCoreUsage {
+    flags: Vec<FlagInfo>,
+    used_flags: FxHashSet<String>,
+    problems: Vec<MiniCoreProblem>,
+}
+
+impl Default for MiniCoreUsage {
+    fn default() -> MiniCoreUsage {
+        MiniCoreUsage {
+            flags: MiniCore::available_flags(),
+            used_flags: FxHashSet::default(),
+            problems: Vec::new(),
+        }
+    }
+}
+
+impl MiniCoreUsage {
+    /// Checks the fixtures of the Rust source file at `path`.
+    ///
+    /// Fixtures which fail to parse are skipped, as some tests use malformed
+    /// fixtures on purpose.
+    pub fn add_file(&mut self, path: &str, text: &str) {
+        for literal in extract_fixtures(text) {
+            let location = format!("{}:{}", path, literal.line);
+            let (mini_core, _, fixture) = match Fixture::try_parse(&literal.text) {
+                Ok(it) => it,
+                Err(_) => continue,
+            };
+
+            let mut activated = Vec::new();
+            for flag in mini_core.iter().flat_map(|it| it.activated_flags()) {
+                if self.flags.iter().any(|it| &it.name == flag) {
+                    activated.push(flag.clone());
+                } else {
+                    self.problems.push(MiniCoreProblem::UnknownFlag {
+                        location: location.clone(),
+                        flag: flag.clone(),
+                    });
+                }
+            }
+            // Flags imply their dependencies.
+            let mut idx = 0;
+            while let Some(flag) = activated.get(idx) {
+                let info = self.flags.iter().find(|it| &it.name == flag).unwrap();
+                for dep in &info.deps {
+                    if !activated.contains(dep) {
+                        activated.push(dep.clone());
+                    }
+                }
+                idx += 1;
+            }
+
+            for file in &fixture {
+                for lang_item in lang_items(&file.text) {
+                    let info = self.flags.iter().find(|it| it.lang_items.contains(&lang_item));
+                    if let Some(info) = info {
+                        self.problems.push(MiniCoreProblem::LangItemInFixture {
+                            location: location.clone(),
+                            lang_item,
+                            flag: info.name.clone(),
+                        });
+                    }
+                }
+            }
+
+            self.used_flags.extend(activated);
+        }
+    }
+
+    /// Returns the problems found in all the added files, including the flags
+    /// which were never used.
+    pub fn finish(self) -> Vec<MiniCoreProblem> {
+        let mut problems = self.problems;
+        for flag in &self.flags {
+            if !self.used_flags.contains(&flag.name) {
+                problems.push(MiniCoreProblem::UnusedFlag { flag: flag.name.clone() });
+            }
+        }
+        problems
+    }
+}
+
+/// Returns the names of the `#[lang = "..."]` attributes in `text`.
+fn lang_items(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("#[lang = \""))
+        .filter_map(|it| it.split('"').next())
+        .map(|it| it.to_string())
+        .collect()
+}
+
+#[test]
+fn extracts_fixtures_from_raw_strings() {
+    let text = r####"
+fn foo() {}
+
+#[test]
+fn bar() {
+    check(r#"
+//- /main.rs
+fn main() {}
+"#);
+    check(r"fn not_a_fixture() {}");
+    check(r##"
+//- minicore: sized
+let s = "r#";
+"##);
+}
+"####;
+    let fixtures = extract_fixtures(text);
+    assert_eq!(
+        fixtures,
+        vec![
+            FixtureLiteral { line: 6, text: "\n//- /main.rs\nfn main() {}\n".to_string() },
+            FixtureLiteral {
+                line: 11,
+                text: "\n//- minicore: sized\nlet s = \"r#\";\n".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn reports_minicore_problems() {
+    let text = r###"
+check(r#"
+//- minicore: sized, unknown
+//- /main.rs
+#[lang = "sized"]
+trait Sized {}
+"#);
+check(r#"
+//- minicore: ord
+"#);
+"###;
+    let mut usage = MiniCoreUsage::default();
+    usage.add_file("foo.rs", text);
+    let problems = usage.finish();
+
+    assert_eq!(
+        problems[..2],
+        [
+            MiniCoreProblem::UnknownFlag {
+                location: "foo.rs:2".to_string(),
+                flag: "unknown".to_string()
+            },
+            MiniCoreProblem::LangItemInFixture {
+                location: "foo.rs:2".to_string(),
+                lang_item: "sized".to_string(),
+                flag: "sized".to_string()
+            },
+        ]
+    );
+    let unused = problems[2..]
+        .iter()
+        .map(|it| match it {
+            MiniCoreProblem::UnusedFlag { flag } => flag.as_str(),
+            _ => panic!("unexpected problem: {}", it),
+        })
+        .collect::<Vec<_>>();
+    assert!(unused.contains(&"range"));
+    // Used directly, or implied by `ord`.
+    assert!(!unused.contains(&"sized"));
+    assert!(!unused.contains(&"option"));
+    assert!(!unused.contains(&"eq"));
+}
@@ -0,0 +1,3 @@
+//- /serde/lib.rs crate:serde
+pub trait Serialize {}
+pub trait Deserialize<'de> {}
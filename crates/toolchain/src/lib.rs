@@ -17,6 +17,12 @@ pub fn rustfmt() -> PathBuf {
     get_path_for_executable("rustfmt")
 }
 
+/// Returns the directory where Cargo keeps the registry caches, `$CARGO_HOME`
+/// or `~/.cargo`.
+pub fn cargo_home() -> Option<PathBuf> {
+    home::cargo_home().ok()
+}
+
 /// Return a `PathBuf` to use for the given executable.
 ///
 /// E.g., `get_path_for_executable("cargo")` may return just `cargo` if that
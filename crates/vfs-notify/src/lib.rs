@@ -2,7 +2,10 @@
 //!
 //! The file watching bits here are untested and quite probably buggy. For this
 //! reason, by default we don't watch files and rely on editor's file watching
-//! capabilities.
+//! capabilities, unless the editor doesn't have any.
+//!
+//! Watched files are not read right away: their paths are reported with
+//! [`loader::Message::Changed`], like an editor would notify about them.
 //!
 //! Hopefully, one day a reliable file watching/walking crate appears on
 //! crates.io, and we can reduce this to trivial glue code.
@@ -123,8 +126,9 @@ impl NotifyActor {
                             .into_iter()
                             .map(|path| AbsPathBuf::try_from(path).unwrap())
                             .filter_map(|path| {
-                                let meta = fs::metadata(&path).ok()?;
-                                if meta.file_type().is_dir()
+                                // Deleted files have no metadata, but should be reported.
+                                let file_type = fs::metadata(&path).ok().map(|it| it.file_type());
+                                if file_type.map_or(false, |it| it.is_dir())
                                     && self
                                         .watched_entries
                                         .iter()
@@ -134,7 +138,7 @@ impl NotifyActor {
                                     return None;
                                 }
 
-                                if !meta.file_type().is_file() {
+                                if file_type.map_or(false, |it| !it.is_file()) {
                                     return None;
                                 }
                                 if !self
@@ -145,11 +149,12 @@ impl NotifyActor {
                                     return None;
                                 }
 
-                                let contents = read(&path);
-                                Some((path, contents))
+                                Some(path)
                             })
-                            .collect();
-                        self.send(loader::Message::Loaded { files })
+                            .collect::<Vec<_>>();
+                        if !files.is_empty() {
+                            self.send(loader::Message::Changed { files })
+                        }
                     }
                 }
             }
@@ -22,6 +22,11 @@ impl FileSet {
         self.files.len()
     }
 
+    /// Returns whether no paths are stored.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
     /// Get the id of the file corresponding to `path`.
     ///
     /// If either `path`'s [`anchor`](AnchoredPath::anchor) or the resolved path is not in
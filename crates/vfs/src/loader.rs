@@ -51,6 +51,11 @@ pub enum Message {
     Progress { n_total: usize, n_done: usize, config_version: u32 },
     /// The handle loaded the following files' content.
     Loaded { files: Vec<(AbsPathBuf, Option<Vec<u8>>)> },
+    /// The following watched files were changed on disk.
+    ///
+    /// Their contents are not loaded, the receiver is expected to
+    /// [`invalidate`](Handle::invalidate) them if it's interested.
+    Changed { files: Vec<AbsPathBuf> },
 }
 
 /// Type that will receive [`Messages`](Message) from a [`Handle`].
@@ -199,6 +204,9 @@ impl fmt::Debug for Message {
             Message::Loaded { files } => {
                 f.debug_struct("Loaded").field("n_files", &files.len()).finish()
             }
+            Message::Changed { files } => {
+                f.debug_struct("Changed").field("n_files", &files.len()).finish()
+            }
             Message::Progress { n_total, n_done, config_version } => f
                 .debug_struct("Progress")
                 .field("n_total", n_total)
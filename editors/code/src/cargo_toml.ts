@@ -0,0 +1,25 @@
+import * as vscode from 'vscode';
+
+import * as ra from './lsp_ext';
+import { Ctx } from './ctx';
+
+// `Cargo.toml` manifests aren't synchronized with the server, which only
+// tracks Rust documents, so their text is sent with each completion request.
+export function activateCargoTomlCompletion(ctx: Ctx) {
+    const provider: vscode.CompletionItemProvider = {
+        async provideCompletionItems(document, position, token) {
+            const client = ctx.client;
+            const items = await client.sendRequest(ra.cargoTomlCompletion, {
+                textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(document),
+                position: client.code2ProtocolConverter.asPosition(position),
+                text: document.getText(),
+            }, token);
+            return client.protocol2CodeConverter.asCompletionResult(items);
+        },
+    };
+    ctx.pushCleanup(vscode.languages.registerCompletionItemProvider(
+        { scheme: 'file', pattern: '**/Cargo.toml' },
+        provider,
+        '"',
+    ));
+}
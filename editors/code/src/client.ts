@@ -3,6 +3,7 @@ import * as vscode from 'vscode';
 import * as ra from '../src/lsp_ext';
 import * as Is from 'vscode-languageclient/lib/common/utils/is';
 import { assert } from './util';
+import { applySnippetTextEdits } from './snippets';
 import { WorkspaceEdit } from 'vscode';
 import { Workspace } from './ctx';
 
@@ -72,6 +73,17 @@ export function createClient(serverPath: string, workspace: Workspace, extraEnv:
                         return Promise.resolve(null);
                     });
             },
+            // Typing `|` or `<` may return a snippet edit, placing the cursor between the pair,
+            // which VS Code would insert as is.
+            async provideOnTypeFormattingEdits(document: vscode.TextDocument, position: vscode.Position, ch: string, options: vscode.FormattingOptions, token: vscode.CancellationToken, next: lc.ProvideOnTypeFormattingEditsSignature) {
+                const edits = await next(document, position, ch, options, token);
+                const editor = vscode.window.activeTextEditor;
+                if (!edits || !edits.some((edit) => edit.newText.includes('$0')) || !editor || editor.document !== document) {
+                    return edits;
+                }
+                await applySnippetTextEdits(editor, edits);
+                return [];
+            },
             // Using custom handling of CodeActions to support action groups and snippet edits.
             // Note that this means we have to re-implement lazy edit resolving ourselves as well.
             async provideCodeActions(document: vscode.TextDocument, range: vscode.Range, context: vscode.CodeActionContext, token: vscode.CancellationToken, _next: lc.ProvideCodeActionsSignature) {
@@ -61,6 +61,21 @@ export function analyzerStatus(ctx: Ctx): Cmd {
     };
 }
 
+// Formats like `profile::Bytes`, right-aligned.
+function formatBytes(bytes: number): string {
+    let value = bytes;
+    let suffix = "b";
+    if (Math.abs(value) > 4096) {
+        value = Math.trunc(value / 1024);
+        suffix = "kb";
+        if (Math.abs(value) > 4096) {
+            value = Math.trunc(value / 1024);
+            suffix = "mb";
+        }
+    }
+    return `${value}${suffix}`.padStart(8);
+}
+
 export function memoryUsage(ctx: Ctx): Cmd {
     const tdcp = new class implements vscode.TextDocumentContentProvider {
         readonly uri = vscode.Uri.parse('rust-analyzer-memory://memory');
@@ -69,8 +84,14 @@ export function memoryUsage(ctx: Ctx): Cmd {
         provideTextDocumentContent(_uri: vscode.Uri): vscode.ProviderResult<string> {
             if (!vscode.window.activeTextEditor) return '';
 
-            return ctx.client.sendRequest(ra.memoryUsage).then((mem: any) => {
-                return 'Per-query memory usage:\n' + mem + '\n(note: database has been cleared)';
+            return ctx.client.sendRequest(ra.memoryUsage).then((mem) => {
+                const queries = mem.queries
+                    .map((query) => `${formatBytes(query.bytes)} ${query.name}`)
+                    .concat(`${formatBytes(mem.remaining)} Remaining`);
+                const sources = mem.sources
+                    .map((source) => `${formatBytes(source.textLen)} ${source.crates.join(', ')} (${source.files} files)`);
+                return 'Per-query memory usage:\n' + queries.join('\n') + '\n(note: database has been cleared)\n\n'
+                    + 'Source text length per source root:\n' + sources.join('\n');
             });
         }
 
@@ -429,6 +450,134 @@ export function viewHir(ctx: Ctx): Cmd {
     };
 }
 
+// Opens the virtual file that will show the result of evaluating the function containing the cursor position
+//
+// The contents of the file come from the `TextDocumentContentProvider`
+export function interpretFunction(ctx: Ctx): Cmd {
+    const tdcp = new class implements vscode.TextDocumentContentProvider {
+        readonly uri = vscode.Uri.parse('rust-analyzer://interpretFunction/result.txt');
+        readonly eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+        constructor() {
+            vscode.workspace.onDidChangeTextDocument(this.onDidChangeTextDocument, this, ctx.subscriptions);
+            vscode.window.onDidChangeActiveTextEditor(this.onDidChangeActiveTextEditor, this, ctx.subscriptions);
+        }
+
+        private onDidChangeTextDocument(event: vscode.TextDocumentChangeEvent) {
+            if (isRustDocument(event.document)) {
+                // We need to order this after language server updates, but there's no API for that.
+                // Hence, good old sleep().
+                void sleep(10).then(() => this.eventEmitter.fire(this.uri));
+            }
+        }
+        private onDidChangeActiveTextEditor(editor: vscode.TextEditor | undefined) {
+            if (editor && isRustEditor(editor)) {
+                this.eventEmitter.fire(this.uri);
+            }
+        }
+
+        provideTextDocumentContent(_uri: vscode.Uri, ct: vscode.CancellationToken): vscode.ProviderResult<string> {
+            const rustEditor = ctx.activeRustEditor;
+            const client = ctx.client;
+            if (!rustEditor || !client) return '';
+
+            const params = {
+                textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(rustEditor.document),
+                position: client.code2ProtocolConverter.asPosition(
+                    rustEditor.selection.active,
+                ),
+            };
+            return client.sendRequest(ra.interpretFunction, params, ct);
+        }
+
+        get onDidChange(): vscode.Event<vscode.Uri> {
+            return this.eventEmitter.event;
+        }
+    };
+
+    ctx.pushCleanup(vscode.workspace.registerTextDocumentContentProvider('rust-analyzer', tdcp));
+
+    return async () => {
+        const document = await vscode.workspace.openTextDocument(tdcp.uri);
+        tdcp.eventEmitter.fire(tdcp.uri);
+        void await vscode.window.showTextDocument(document, {
+            viewColumn: vscode.ViewColumn.Two,
+            preserveFocus: true
+        });
+    };
+}
+
+// Opens the virtual file that will show the memory layout of the type under the cursor
+//
+// The contents of the file come from the `TextDocumentContentProvider`
+export function viewMemoryLayout(ctx: Ctx): Cmd {
+    const tdcp = new class implements vscode.TextDocumentContentProvider {
+        readonly uri = vscode.Uri.parse('rust-analyzer://viewMemoryLayout/layout.txt');
+        readonly eventEmitter = new vscode.EventEmitter<vscode.Uri>();
+        constructor() {
+            vscode.workspace.onDidChangeTextDocument(this.onDidChangeTextDocument, this, ctx.subscriptions);
+            vscode.window.onDidChangeActiveTextEditor(this.onDidChangeActiveTextEditor, this, ctx.subscriptions);
+        }
+
+        private onDidChangeTextDocument(event: vscode.TextDocumentChangeEvent) {
+            if (isRustDocument(event.document)) {
+                // We need to order this after language server updates, but there's no API for that.
+                // Hence, good old sleep().
+                void sleep(10).then(() => this.eventEmitter.fire(this.uri));
+            }
+        }
+        private onDidChangeActiveTextEditor(editor: vscode.TextEditor | undefined) {
+            if (editor && isRustEditor(editor)) {
+                this.eventEmitter.fire(this.uri);
+            }
+        }
+
+        async provideTextDocumentContent(_uri: vscode.Uri, ct: vscode.CancellationToken): Promise<string> {
+            const rustEditor = ctx.activeRustEditor;
+            const client = ctx.client;
+            if (!rustEditor || !client) return '';
+
+            const params = {
+                textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(rustEditor.document),
+                position: client.code2ProtocolConverter.asPosition(
+                    rustEditor.selection.active,
+                ),
+            };
+            const layout = await client.sendRequest(ra.viewMemoryLayout, params, ct);
+            if (!layout) return 'The memory layout of the type under the cursor is unknown';
+
+            const lines: string[] = [];
+            const render = (node: ra.MemoryLayoutNode, base: number, indent: string) => {
+                const start = base + node.offset;
+                const end = start + node.size;
+                lines.push(`${indent}[${start}..${end}) ${node.itemName}: ${node.typename} (size ${node.size}, align ${node.alignment})`);
+                for (const child of node.children) {
+                    render(child, start, indent + '    ');
+                }
+                if (node.padding > 0) {
+                    lines.push(`${indent}[${end}..${end + node.padding}) padding`);
+                }
+            };
+            render(layout, 0, '');
+            return lines.join('\n');
+        }
+
+        get onDidChange(): vscode.Event<vscode.Uri> {
+            return this.eventEmitter.event;
+        }
+    };
+
+    ctx.pushCleanup(vscode.workspace.registerTextDocumentContentProvider('rust-analyzer', tdcp));
+
+    return async () => {
+        const document = await vscode.workspace.openTextDocument(tdcp.uri);
+        tdcp.eventEmitter.fire(tdcp.uri);
+        void await vscode.window.showTextDocument(document, {
+            viewColumn: vscode.ViewColumn.Two,
+            preserveFocus: true
+        });
+    };
+}
+
 export function viewItemTree(ctx: Ctx): Cmd {
     const tdcp = new class implements vscode.TextDocumentContentProvider {
         readonly uri = vscode.Uri.parse('rust-analyzer://viewItemTree/itemtree.rs');
@@ -485,8 +634,13 @@ function crateGraph(ctx: Ctx, full: boolean): Cmd {
         const params = {
             full: full,
         };
-        const svg = await ctx.client.sendRequest(ra.viewCrateGraph, params);
-        panel.webview.html = svg;
+        const graph = await ctx.client.sendRequest(ra.viewCrateGraph, params);
+        if (!graph.svg) {
+            panel.dispose();
+            void vscode.window.showErrorMessage("Failed to render the crate graph, is graphviz's `dot` installed?");
+            return;
+        }
+        panel.webview.html = graph.svg;
     };
 }
 
@@ -558,6 +712,18 @@ export function reloadWorkspace(ctx: Ctx): Cmd {
     return async () => ctx.client.sendRequest(ra.reloadWorkspace);
 }
 
+export function reloadPackage(ctx: Ctx): Cmd {
+    return async () => {
+        const editor = vscode.window.activeTextEditor;
+        const client = ctx.client;
+        if (!editor || !client) return;
+
+        await client.sendRequest(ra.reloadPackage, {
+            textDocument: ctx.client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
+        });
+    };
+}
+
 async function showReferencesImpl(client: LanguageClient, uri: string, position: lc.Position, locations: lc.Location[]) {
     if (client) {
         await vscode.commands.executeCommand(
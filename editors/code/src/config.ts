@@ -115,6 +115,8 @@ export class Config {
             typeHints: this.get<boolean>("inlayHints.typeHints"),
             parameterHints: this.get<boolean>("inlayHints.parameterHints"),
             chainingHints: this.get<boolean>("inlayHints.chainingHints"),
+            implicitDropHints: this.get<boolean>("inlayHints.implicitDropHints"),
+            lifetimeElisionHints: this.get<"always" | "skip_trivial" | "never">("inlayHints.lifetimeElisionHints"),
             smallerHints: this.get<boolean>("inlayHints.smallerHints"),
             maxLength: this.get<null | number>("inlayHints.maxLength"),
         };
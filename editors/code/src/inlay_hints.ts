@@ -14,6 +14,8 @@ interface InlayHintsStyles {
     typeHints: InlayHintStyle;
     paramHints: InlayHintStyle;
     chainingHints: InlayHintStyle;
+    dropHints: InlayHintStyle;
+    lifetimeHints: InlayHintStyle;
 }
 
 
@@ -23,7 +25,9 @@ export function activateInlayHints(ctx: Ctx) {
         async onConfigChange() {
             const anyEnabled = ctx.config.inlayHints.typeHints
                 || ctx.config.inlayHints.parameterHints
-                || ctx.config.inlayHints.chainingHints;
+                || ctx.config.inlayHints.chainingHints
+                || ctx.config.inlayHints.implicitDropHints
+                || ctx.config.inlayHints.lifetimeElisionHints !== "never";
             const enabled = ctx.config.inlayHints.enable && anyEnabled;
 
             if (!enabled) return this.dispose();
@@ -51,13 +55,16 @@ export function activateInlayHints(ctx: Ctx) {
     maybeUpdater.onConfigChange().catch(console.error);
 }
 
-function createHintStyle(hintKind: "type" | "parameter" | "chaining", smallerHints: boolean): InlayHintStyle {
+function createHintStyle(hintKind: "type" | "parameter" | "chaining" | "drop" | "lifetime", smallerHints: boolean): InlayHintStyle {
     // U+200C is a zero-width non-joiner to prevent the editor from forming a ligature
     // between code and type hints
     const [pos, render] = ({
         type: ["after", (label: string) => `\u{200c}: ${label}`],
         parameter: ["before", (label: string) => `${label}: `],
         chaining: ["after", (label: string) => `\u{200c}: ${label}`],
+        drop: ["before", (label: string) => `${label} `],
+        // Generic parameter lists are rendered as is, lifetimes of references need a space
+        lifetime: ["after", (label: string) => label.startsWith("<") ? label : `${label} `],
     } as const)[hintKind];
 
     const fg = new vscode.ThemeColor(`rust_analyzer.inlayHints.foreground.${hintKind}Hints`);
@@ -85,12 +92,16 @@ const smallHintsStyles = {
     typeHints: createHintStyle("type", true),
     paramHints: createHintStyle("parameter", true),
     chainingHints: createHintStyle("chaining", true),
+    dropHints: createHintStyle("drop", true),
+    lifetimeHints: createHintStyle("lifetime", true),
 };
 
 const biggerHintsStyles = {
     typeHints: createHintStyle("type", false),
     paramHints: createHintStyle("parameter", false),
     chainingHints: createHintStyle("chaining", false),
+    dropHints: createHintStyle("drop", false),
+    lifetimeHints: createHintStyle("lifetime", false),
 };
 
 class HintsUpdater implements Disposable {
@@ -128,7 +139,7 @@ class HintsUpdater implements Disposable {
 
     dispose() {
         this.sourceFiles.forEach(file => file.inlaysRequest?.cancel());
-        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, { param: [], type: [], chaining: [] }));
+        this.ctx.visibleRustEditors.forEach(editor => this.renderDecorations(editor, { param: [], type: [], chaining: [], drop: [], lifetime: [] }));
         this.disposables.forEach(d => d.dispose());
     }
 
@@ -193,21 +204,25 @@ class HintsUpdater implements Disposable {
     }
 
     private renderDecorations(editor: RustEditor, decorations: InlaysDecorations) {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
+        const { typeHints, paramHints, chainingHints, dropHints, lifetimeHints } = this.inlayHintsStyles;
         if (this.pendingDisposeDecorations !== undefined) {
-            const { typeHints, paramHints, chainingHints } = this.pendingDisposeDecorations;
+            const { typeHints, paramHints, chainingHints, dropHints, lifetimeHints } = this.pendingDisposeDecorations;
             editor.setDecorations(typeHints.decorationType, []);
             editor.setDecorations(paramHints.decorationType, []);
             editor.setDecorations(chainingHints.decorationType, []);
+            editor.setDecorations(dropHints.decorationType, []);
+            editor.setDecorations(lifetimeHints.decorationType, []);
         }
         editor.setDecorations(typeHints.decorationType, decorations.type);
         editor.setDecorations(paramHints.decorationType, decorations.param);
         editor.setDecorations(chainingHints.decorationType, decorations.chaining);
+        editor.setDecorations(dropHints.decorationType, decorations.drop);
+        editor.setDecorations(lifetimeHints.decorationType, decorations.lifetime);
     }
 
     private hintsToDecorations(hints: ra.InlayHint[]): InlaysDecorations {
-        const { typeHints, paramHints, chainingHints } = this.inlayHintsStyles;
-        const decorations: InlaysDecorations = { type: [], param: [], chaining: [] };
+        const { typeHints, paramHints, chainingHints, dropHints, lifetimeHints } = this.inlayHintsStyles;
+        const decorations: InlaysDecorations = { type: [], param: [], chaining: [], drop: [], lifetime: [] };
         const conv = this.ctx.client.protocol2CodeConverter;
 
         for (const hint of hints) {
@@ -224,6 +239,14 @@ class HintsUpdater implements Disposable {
                     decorations.chaining.push(chainingHints.toDecoration(hint, conv));
                     continue;
                 }
+                case ra.InlayHint.Kind.ImplicitDropHint: {
+                    decorations.drop.push(dropHints.toDecoration(hint, conv));
+                    continue;
+                }
+                case ra.InlayHint.Kind.LifetimeHint: {
+                    decorations.lifetime.push(lifetimeHints.toDecoration(hint, conv));
+                    continue;
+                }
             }
         }
         return decorations;
@@ -251,6 +274,8 @@ interface InlaysDecorations {
     type: vscode.DecorationOptions[];
     param: vscode.DecorationOptions[];
     chaining: vscode.DecorationOptions[];
+    drop: vscode.DecorationOptions[];
+    lifetime: vscode.DecorationOptions[];
 }
 
 interface RustSourceFile {
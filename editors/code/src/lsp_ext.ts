@@ -8,7 +8,12 @@ export interface AnalyzerStatusParams {
     textDocument?: lc.TextDocumentIdentifier;
 }
 export const analyzerStatus = new lc.RequestType<AnalyzerStatusParams, string, void>("rust-analyzer/analyzerStatus");
-export const memoryUsage = new lc.RequestType0<string, void>("rust-analyzer/memoryUsage");
+export interface MemoryUsageResult {
+    queries: { name: string; bytes: number }[];
+    remaining: number;
+    sources: { crates: string[]; files: number; textLen: number }[];
+}
+export const memoryUsage = new lc.RequestType0<MemoryUsageResult, void>("rust-analyzer/memoryUsage");
 
 export interface ServerStatusParams {
     health: "ok" | "warning" | "error";
@@ -19,6 +24,11 @@ export const serverStatus = new lc.NotificationType<ServerStatusParams>("experim
 
 export const reloadWorkspace = new lc.RequestType0<null, void>("rust-analyzer/reloadWorkspace");
 
+export interface ReloadPackageParams {
+    textDocument: lc.TextDocumentIdentifier;
+}
+export const reloadPackage = new lc.RequestType<ReloadPackageParams, void, void>("rust-analyzer/reloadPackage");
+
 export interface SyntaxTreeParams {
     textDocument: lc.TextDocumentIdentifier;
     range: lc.Range | null;
@@ -27,6 +37,19 @@ export const syntaxTree = new lc.RequestType<SyntaxTreeParams, string, void>("ru
 
 export const viewHir = new lc.RequestType<lc.TextDocumentPositionParams, string, void>("rust-analyzer/viewHir");
 
+export const interpretFunction = new lc.RequestType<lc.TextDocumentPositionParams, string, void>("rust-analyzer/interpretFunction");
+
+export interface MemoryLayoutNode {
+    itemName: string;
+    typename: string;
+    size: number;
+    alignment: number;
+    offset: number;
+    padding: number;
+    children: MemoryLayoutNode[];
+}
+export const viewMemoryLayout = new lc.RequestType<lc.TextDocumentPositionParams, MemoryLayoutNode | null, void>("rust-analyzer/viewMemoryLayout");
+
 export interface ViewItemTreeParams {
     textDocument: lc.TextDocumentIdentifier;
 }
@@ -35,17 +58,38 @@ export const viewItemTree = new lc.RequestType<ViewItemTreeParams, string, void>
 
 export interface ViewCrateGraphParams {
     full: boolean;
+    filter?: string;
+    collapseChains?: boolean;
 }
 
-export const viewCrateGraph = new lc.RequestType<ViewCrateGraphParams, string, void>("rust-analyzer/viewCrateGraph");
+export interface CrateGraphNode {
+    id: number;
+    name: string;
+    isWorkspaceMember: boolean;
+    dependencies: number[];
+}
+
+export interface CrateGraph {
+    dot: string;
+    svg?: string;
+    crates: CrateGraphNode[];
+}
+
+export const viewCrateGraph = new lc.RequestType<ViewCrateGraphParams, CrateGraph, void>("rust-analyzer/viewCrateGraph");
 
 export interface ExpandMacroParams {
     textDocument: lc.TextDocumentIdentifier;
     position: lc.Position;
+    depth?: number;
+}
+export interface ExpansionOrigin {
+    expansionRange: lc.Range;
+    originalRange: lc.Range;
 }
 export interface ExpandedMacro {
     name: string;
     expansion: string;
+    origins: ExpansionOrigin[];
 }
 export const expandMacro = new lc.RequestType<ExpandMacroParams, ExpandedMacro | null, void>("rust-analyzer/expandMacro");
 
@@ -91,13 +135,37 @@ export interface TestInfo {
 
 export const relatedTests = new lc.RequestType<lc.TextDocumentPositionParams, TestInfo[], void>("rust-analyzer/relatedTests");
 
-export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint;
+export interface TestRunParams {
+    runnable: Runnable;
+}
+export interface TestRunResult {
+    runId: number;
+}
+export const testRun = new lc.RequestType<TestRunParams, TestRunResult, void>("rust-analyzer/testRun");
+
+export type TestRunEventParams = { runId: number } & (
+    | { kind: "passed"; name: string }
+    | { kind: "failed"; name: string }
+    | { kind: "ignored"; name: string }
+    | { kind: "output"; name: string; output: string }
+    | { kind: "finished"; error?: string }
+);
+export const testRunEvent = new lc.NotificationType<TestRunEventParams>("rust-analyzer/testRunEvent");
+
+export interface CancelTestRunParams {
+    runId: number;
+}
+export const cancelTestRun = new lc.RequestType<CancelTestRunParams, void, void>("rust-analyzer/cancelTestRun");
+
+export type InlayHint = InlayHint.TypeHint | InlayHint.ParamHint | InlayHint.ChainingHint | InlayHint.ImplicitDropHint | InlayHint.LifetimeHint;
 
 export namespace InlayHint {
     export const enum Kind {
         TypeHint = "TypeHint",
         ParamHint = "ParameterHint",
         ChainingHint = "ChainingHint",
+        ImplicitDropHint = "ImplicitDropHint",
+        LifetimeHint = "LifetimeHint",
     }
     interface Common {
         range: lc.Range;
@@ -106,6 +174,8 @@ export namespace InlayHint {
     export type TypeHint = Common & { kind: Kind.TypeHint };
     export type ParamHint = Common & { kind: Kind.ParamHint };
     export type ChainingHint = Common & { kind: Kind.ChainingHint };
+    export type ImplicitDropHint = Common & { kind: Kind.ImplicitDropHint };
+    export type LifetimeHint = Common & { kind: Kind.LifetimeHint };
 }
 export interface InlayHintsParams {
     textDocument: lc.TextDocumentIdentifier;
@@ -118,9 +188,27 @@ export interface SsrParams {
     textDocument: lc.TextDocumentIdentifier;
     position: lc.Position;
     selections: lc.Range[];
+    excludedFiles?: lc.TextDocumentIdentifier[];
 }
 export const ssr = new lc.RequestType<SsrParams, lc.WorkspaceEdit, void>('experimental/ssr');
 
+export interface SsrPreviewEntry {
+    textDocument: lc.TextDocumentIdentifier;
+    range: lc.Range;
+    before: string;
+    after: string;
+}
+export interface SsrRuleSummary {
+    rule: string;
+    matches: number;
+    files: number;
+}
+export interface SsrPreviewResult {
+    rules: SsrRuleSummary[];
+    matches: SsrPreviewEntry[];
+}
+export const ssrPreview = new lc.RequestType<SsrParams, SsrPreviewResult, void>('experimental/ssrPreview');
+
 export interface CommandLink extends lc.Command {
     /**
      * A tooltip for the command, when represented in the UI.
@@ -141,6 +229,12 @@ export interface OpenCargoTomlParams {
     textDocument: lc.TextDocumentIdentifier;
 }
 
+export const cargoTomlCompletion = new lc.RequestType<CargoTomlCompletionParams, lc.CompletionItem[], void>("experimental/cargoTomlCompletion");
+
+export interface CargoTomlCompletionParams extends lc.TextDocumentPositionParams {
+    text: string;
+}
+
 export const moveItem = new lc.RequestType<MoveItemParams, lc.TextEdit[], void>("experimental/moveItem");
 
 export interface MoveItemParams {
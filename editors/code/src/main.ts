@@ -3,6 +3,7 @@ import * as os from "os";
 
 import * as commands from './commands';
 import { activateInlayHints } from './inlay_hints';
+import { activateCargoTomlCompletion } from './cargo_toml';
 import { Ctx } from './ctx';
 import { Config } from './config';
 import { log, assert, isValidExecutable, isRustDocument } from './util';
@@ -61,6 +62,7 @@ async function tryActivate(context: vscode.ExtensionContext) {
     await initCommonContext(context, ctx);
 
     activateInlayHints(ctx);
+    activateCargoTomlCompletion(ctx);
     warnAboutExtensionConflicts();
 
     vscode.workspace.onDidChangeConfiguration(
@@ -116,11 +118,14 @@ async function initCommonContext(context: vscode.ExtensionContext, ctx: Ctx) {
     ctx.registerCommand('analyzerStatus', commands.analyzerStatus);
     ctx.registerCommand('memoryUsage', commands.memoryUsage);
     ctx.registerCommand('reloadWorkspace', commands.reloadWorkspace);
+    ctx.registerCommand('reloadPackage', commands.reloadPackage);
     ctx.registerCommand('matchingBrace', commands.matchingBrace);
     ctx.registerCommand('joinLines', commands.joinLines);
     ctx.registerCommand('parentModule', commands.parentModule);
     ctx.registerCommand('syntaxTree', commands.syntaxTree);
     ctx.registerCommand('viewHir', commands.viewHir);
+    ctx.registerCommand('interpretFunction', commands.interpretFunction);
+    ctx.registerCommand('viewMemoryLayout', commands.viewMemoryLayout);
     ctx.registerCommand('viewItemTree', commands.viewItemTree);
     ctx.registerCommand('viewCrateGraph', commands.viewCrateGraph);
     ctx.registerCommand('viewFullCrateGraph', commands.viewFullCrateGraph);